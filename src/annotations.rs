@@ -17,6 +17,12 @@
 //!   be overridden via annotations regardless of configuration.
 //! - The admin can disable all annotation processing via
 //!   `REAPER_ANNOTATIONS_ENABLED=false`.
+//! - `shared-overlay` crosses the namespace boundary, so it is only honored
+//!   for names the admin lists in `REAPER_ALLOWED_SHARED_OVERLAYS`; by default
+//!   no pod can join a shared overlay.
+//! - `overlay-isolation=node` puts a pod on the node-wide overlay shared by
+//!   every namespace, so it is only honored when the admin sets
+//!   `REAPER_ALLOW_NODE_ISOLATION=true`.
 //! - Unknown annotation keys are silently ignored.
 //! - Invalid values for known keys are logged and ignored.
 
@@ -27,7 +33,21 @@ pub const ANNOTATION_PREFIX: &str = "reaper.runtime/";
 
 /// Known annotation keys that users may override (stripped of prefix).
 /// These map to specific Reaper configuration parameters.
-const USER_OVERRIDABLE_KEYS: &[&str] = &["dns-mode", "overlay-name"];
+const USER_OVERRIDABLE_KEYS: &[&str] = &[
+    "dns-mode",
+    "overlay-name",
+    "missing-volume-policy",
+    "image-rootfs",
+    "overlay-isolation",
+    "after",
+    "fast-exit",
+    "argv0",
+    "host-network",
+    "shared-overlay",
+    "termination-grace",
+    "env-from-mount",
+    "enable-fuse",
+];
 
 /// Parsed Reaper annotations from a pod spec.
 #[derive(Debug, Clone, Default, PartialEq)]
@@ -37,6 +57,53 @@ pub struct ReaperAnnotations {
     /// Named overlay group override. DNS label format: [a-z0-9][a-z0-9-]*, max 63 chars.
     /// Pods with the same overlay-name (within the same namespace) share an overlay.
     pub overlay_name: Option<String>,
+    /// Per-mount missing-source policy: destination -> "skip" | "fail".
+    /// Annotation format: `/etc/creds=fail,/cache=skip`.
+    pub missing_volume_policy: Option<HashMap<String, String>>,
+    /// Layer the container image rootfs over the host root (private overlay).
+    pub image_rootfs: Option<bool>,
+    /// Overlay isolation override: "namespace" or "node".
+    pub overlay_isolation: Option<String>,
+    /// Container to wait for before starting. Annotation format:
+    /// `<container-id-or-name>[=running|stopped]`.
+    pub after: Option<StartAfter>,
+    /// Skip the settle delay before reaping a non-terminal workload, for
+    /// batch jobs nobody attaches to.
+    pub fast_exit: Option<bool>,
+    /// `argv[0]` for the workload when it must differ from the program path,
+    /// e.g. `-sh` for a login shell or an applet name for a multi-call binary.
+    pub argv0: Option<String>,
+    /// Treat the pod as host-network (`hostNetwork: true`): DNS and hosts
+    /// come from the node instead of the kubelet-prepared files.
+    pub host_network: Option<bool>,
+    /// Node-wide shared overlay to join instead of the namespace's, e.g. a
+    /// build cache shared across namespaces. DNS label format like
+    /// `overlay_name`; takes precedence over `overlay_name` and
+    /// `overlay_isolation`.
+    pub shared_overlay: Option<String>,
+    /// Seconds between SIGTERM and the SIGKILL the runtime escalates to,
+    /// typically the pod's `terminationGracePeriodSeconds`. `0` disables
+    /// escalation; `REAPER_KILL_GRACE_SECS` wins when set.
+    pub termination_grace: Option<u64>,
+    /// File of `KEY=value` lines, read inside the container after volume
+    /// mounts (e.g. a ConfigMap mounted at `/etc/config/env`), merged into
+    /// the workload env below `process.env`. Absolute path without `..`;
+    /// the runtime also requires it to resolve under one of the pod's mount
+    /// destinations and reads it as the workload user, never as root.
+    pub env_from_mount: Option<String>,
+    /// Make sure `/dev/fuse` exists in the container, for FUSE workloads
+    /// (fuse-overlayfs, s3fs, squashfuse). The mount capability itself is
+    /// only granted when the admin allows it (`REAPER_FUSE_CAP_SYS_ADMIN`).
+    pub enable_fuse: Option<bool>,
+}
+
+/// A start dependency from the `after` annotation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StartAfter {
+    /// Container ID, or Kubernetes container name within the same pod.
+    pub container: String,
+    /// Status the dependency must reach: "running" (default) or "stopped".
+    pub status: String,
 }
 
 /// Check whether annotation-based configuration is enabled.
@@ -52,9 +119,35 @@ pub fn annotations_enabled() -> bool {
         .unwrap_or(true)
 }
 
+/// Check whether the admin allows pods to join shared overlay `name`.
+///
+/// Reads `REAPER_ALLOWED_SHARED_OVERLAYS`, a comma-separated list of overlay
+/// names. Unset or empty allows none.
+pub fn shared_overlay_allowed(name: &str) -> bool {
+    std::env::var("REAPER_ALLOWED_SHARED_OVERLAYS")
+        .map(|v| {
+            v.split(',')
+                .map(|n| n.trim().to_ascii_lowercase())
+                .any(|n| n == name)
+        })
+        .unwrap_or(false)
+}
+
+/// Check whether the admin allows the `overlay-isolation=node` annotation.
+///
+/// Reads `REAPER_ALLOW_NODE_ISOLATION`. Default is `false`.
+pub fn node_isolation_allowed() -> bool {
+    std::env::var("REAPER_ALLOW_NODE_ISOLATION")
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
 /// Valid values for the `dns-mode` annotation.
 const VALID_DNS_MODES: &[&str] = &["host", "kubernetes", "k8s"];
 
+/// Valid values for the `overlay-isolation` annotation.
+const VALID_OVERLAY_ISOLATIONS: &[&str] = &["namespace", "node"];
+
 /// Validate an overlay name: DNS label format ([a-z0-9][a-z0-9-]*, max 63 chars).
 fn is_valid_overlay_name(name: &str) -> bool {
     if name.is_empty() || name.len() > 63 {
@@ -64,6 +157,52 @@ fn is_valid_overlay_name(name: &str) -> bool {
         .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
 }
 
+/// Statuses the `after` annotation can wait for.
+const VALID_AFTER_STATUSES: &[&str] = &["running", "stopped"];
+
+/// Parse an `after` value: `<container>` or `<container>=<status>`.
+/// The container must be a valid container ID or name ([a-zA-Z0-9._-]).
+fn parse_after(value: &str) -> Option<StartAfter> {
+    let (container, status) = match value.trim().split_once('=') {
+        Some((c, s)) => (c.trim(), s.trim().to_ascii_lowercase()),
+        None => (value.trim(), "running".to_string()),
+    };
+    let valid_container = !container.is_empty()
+        && container.len() <= 256
+        && container
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_' || c == '-');
+    if !valid_container || !VALID_AFTER_STATUSES.contains(&status.as_str()) {
+        return None;
+    }
+    Some(StartAfter {
+        container: container.to_string(),
+        status,
+    })
+}
+
+/// Valid policies for the `missing-volume-policy` annotation.
+const VALID_MISSING_VOLUME_POLICIES: &[&str] = &["skip", "fail"];
+
+/// Parse a `missing-volume-policy` value: comma-separated `destination=policy`
+/// entries. Destinations must be absolute. Returns `None` if any entry is invalid.
+fn parse_missing_volume_policy(value: &str) -> Option<HashMap<String, String>> {
+    let mut result = HashMap::new();
+    for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (dest, policy) = entry.split_once('=')?;
+        let (dest, policy) = (dest.trim(), policy.trim().to_ascii_lowercase());
+        if !dest.starts_with('/') || !VALID_MISSING_VOLUME_POLICIES.contains(&policy.as_str()) {
+            return None;
+        }
+        result.insert(dest.to_string(), policy);
+    }
+    if result.is_empty() {
+        None
+    } else {
+        Some(result)
+    }
+}
+
 /// Parse Reaper annotations from an OCI config.json annotations map.
 ///
 /// Filters for the `reaper.runtime/` prefix, validates keys against the
@@ -153,6 +292,128 @@ fn validate_and_apply_annotation(
                 value, display_key
             );
         }
+    } else if stripped_key == "shared-overlay" {
+        let normalized = value.to_ascii_lowercase();
+        if !is_valid_overlay_name(&normalized) {
+            eprintln!(
+                "reaper: annotation: ignoring invalid shared-overlay {:?} for {:?} \
+                 (must be DNS label: [a-z0-9-], max 63 chars)",
+                value, display_key
+            );
+        } else if !shared_overlay_allowed(&normalized) {
+            eprintln!(
+                "reaper: annotation: ignoring shared-overlay {:?} for {:?} \
+                 (not in REAPER_ALLOWED_SHARED_OVERLAYS)",
+                value, display_key
+            );
+        } else {
+            result.shared_overlay = Some(normalized);
+        }
+    } else if stripped_key == "overlay-isolation" {
+        let normalized = value.to_ascii_lowercase();
+        if normalized == "node" && !node_isolation_allowed() {
+            eprintln!(
+                "reaper: annotation: ignoring {:?} for {:?} \
+                 (node isolation requires REAPER_ALLOW_NODE_ISOLATION=true)",
+                value, display_key
+            );
+        } else if VALID_OVERLAY_ISOLATIONS.contains(&normalized.as_str()) {
+            result.overlay_isolation = Some(normalized);
+        } else {
+            eprintln!(
+                "reaper: annotation: ignoring invalid value {:?} for {:?} (valid: {:?})",
+                value, display_key, VALID_OVERLAY_ISOLATIONS
+            );
+        }
+    } else if stripped_key == "image-rootfs" {
+        match value.to_ascii_lowercase().as_str() {
+            "true" => result.image_rootfs = Some(true),
+            "false" => result.image_rootfs = Some(false),
+            _ => eprintln!(
+                "reaper: annotation: ignoring invalid value {:?} for {:?} (valid: true, false)",
+                value, display_key
+            ),
+        }
+    } else if stripped_key == "fast-exit" {
+        match value.to_ascii_lowercase().as_str() {
+            "true" => result.fast_exit = Some(true),
+            "false" => result.fast_exit = Some(false),
+            _ => eprintln!(
+                "reaper: annotation: ignoring invalid value {:?} for {:?} (valid: true, false)",
+                value, display_key
+            ),
+        }
+    } else if stripped_key == "host-network" {
+        match value.to_ascii_lowercase().as_str() {
+            "true" => result.host_network = Some(true),
+            "false" => result.host_network = Some(false),
+            _ => eprintln!(
+                "reaper: annotation: ignoring invalid value {:?} for {:?} (valid: true, false)",
+                value, display_key
+            ),
+        }
+    } else if stripped_key == "enable-fuse" {
+        match value.to_ascii_lowercase().as_str() {
+            "true" => result.enable_fuse = Some(true),
+            "false" => result.enable_fuse = Some(false),
+            _ => eprintln!(
+                "reaper: annotation: ignoring invalid value {:?} for {:?} (valid: true, false)",
+                value, display_key
+            ),
+        }
+    } else if stripped_key == "termination-grace" {
+        match value.trim().parse::<u64>() {
+            Ok(secs) => result.termination_grace = Some(secs),
+            Err(_) => eprintln!(
+                "reaper: annotation: ignoring invalid termination-grace {:?} for {:?} \
+                 (expected whole seconds)",
+                value, display_key
+            ),
+        }
+    } else if stripped_key == "env-from-mount" {
+        let path = std::path::Path::new(value);
+        let valid = path.is_absolute()
+            && !value.contains('\0')
+            && !path
+                .components()
+                .any(|c| c == std::path::Component::ParentDir);
+        if valid {
+            result.env_from_mount = Some(value.to_string());
+        } else {
+            eprintln!(
+                "reaper: annotation: ignoring invalid env-from-mount {:?} for {:?} \
+                 (must be an absolute path without '..')",
+                value, display_key
+            );
+        }
+    } else if stripped_key == "argv0" {
+        if value.is_empty() || value.contains('\0') {
+            eprintln!(
+                "reaper: annotation: ignoring invalid argv0 {:?} for {:?} \
+                 (must be non-empty and contain no NUL bytes)",
+                value, display_key
+            );
+        } else {
+            result.argv0 = Some(value.to_string());
+        }
+    } else if stripped_key == "after" {
+        match parse_after(value) {
+            Some(after) => result.after = Some(after),
+            None => eprintln!(
+                "reaper: annotation: ignoring invalid after {:?} for {:?} \
+                 (expected <container>[=running|stopped])",
+                value, display_key
+            ),
+        }
+    } else if stripped_key == "missing-volume-policy" {
+        match parse_missing_volume_policy(value) {
+            Some(policies) => result.missing_volume_policy = Some(policies),
+            None => eprintln!(
+                "reaper: annotation: ignoring invalid missing-volume-policy {:?} for {:?} \
+                 (expected /path=skip|fail, comma-separated)",
+                value, display_key
+            ),
+        }
     }
 }
 
@@ -525,6 +786,283 @@ mod tests {
         assert_eq!(extracted.get("dns-mode"), Some(&"kubernetes".to_string()));
     }
 
+    #[test]
+    #[serial]
+    fn test_parse_missing_volume_policy_valid() {
+        std::env::remove_var("REAPER_ANNOTATIONS_ENABLED");
+        let annots = make_annotations(&[(
+            "reaper.runtime/missing-volume-policy",
+            "/etc/creds=fail, /cache=SKIP",
+        )]);
+        let result = parse_annotations(&annots).unwrap();
+        let policies = result.missing_volume_policy.unwrap();
+        assert_eq!(policies.len(), 2);
+        assert_eq!(policies.get("/etc/creds"), Some(&"fail".to_string()));
+        assert_eq!(policies.get("/cache"), Some(&"skip".to_string()));
+    }
+
+    #[test]
+    #[serial]
+    fn test_parse_missing_volume_policy_invalid_rejects_whole_value() {
+        std::env::remove_var("REAPER_ANNOTATIONS_ENABLED");
+        for bad in [
+            "/etc/creds=maybe",
+            "etc/creds=fail",
+            "/etc/creds",
+            "/ok=fail,/bad=nope",
+            "",
+            " , ",
+        ] {
+            let annots = make_annotations(&[("reaper.runtime/missing-volume-policy", bad)]);
+            let result = parse_annotations(&annots).unwrap();
+            assert_eq!(result.missing_volume_policy, None, "value {:?}", bad);
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_missing_volume_policy_cli_round_trip() {
+        std::env::remove_var("REAPER_ANNOTATIONS_ENABLED");
+        let annots = make_annotations(&[("reaper.runtime/missing-volume-policy", "/a=fail")]);
+        let extracted = extract_reaper_annotations(&annots);
+        let cli_args = annotations_to_cli_args(&extracted);
+        let parsed = parse_cli_annotations(&cli_args);
+        let result = parse_stripped_annotations(&parsed).unwrap();
+        assert_eq!(
+            result.missing_volume_policy.unwrap().get("/a"),
+            Some(&"fail".to_string())
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_parse_image_rootfs() {
+        std::env::remove_var("REAPER_ANNOTATIONS_ENABLED");
+        for (value, expected) in [
+            ("true", Some(true)),
+            ("TRUE", Some(true)),
+            ("false", Some(false)),
+            ("yes", None),
+            ("", None),
+        ] {
+            let annots = make_annotations(&[("reaper.runtime/image-rootfs", value)]);
+            let result = parse_annotations(&annots).unwrap();
+            assert_eq!(result.image_rootfs, expected, "value {:?}", value);
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_parse_fast_exit() {
+        std::env::remove_var("REAPER_ANNOTATIONS_ENABLED");
+        for (value, expected) in [("true", Some(true)), ("False", Some(false)), ("1", None)] {
+            let annots = make_annotations(&[("reaper.runtime/fast-exit", value)]);
+            let result = parse_annotations(&annots).unwrap();
+            assert_eq!(result.fast_exit, expected, "value {:?}", value);
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_parse_host_network() {
+        std::env::remove_var("REAPER_ANNOTATIONS_ENABLED");
+        for (value, expected) in [("true", Some(true)), ("FALSE", Some(false)), ("yes", None)] {
+            let annots = make_annotations(&[("reaper.runtime/host-network", value)]);
+            let result = parse_annotations(&annots).unwrap();
+            assert_eq!(result.host_network, expected, "value {:?}", value);
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_parse_shared_overlay() {
+        std::env::remove_var("REAPER_ANNOTATIONS_ENABLED");
+        std::env::set_var("REAPER_ALLOWED_SHARED_OVERLAYS", "ci-cache, other");
+        for (value, expected) in [
+            ("ci-cache", Some("ci-cache")),
+            ("CI-Cache", Some("ci-cache")),
+            ("", None),
+            ("../evil", None),
+            ("a_b", None),
+        ] {
+            let annots = make_annotations(&[("reaper.runtime/shared-overlay", value)]);
+            let result = parse_annotations(&annots).unwrap();
+            assert_eq!(
+                result.shared_overlay.as_deref(),
+                expected,
+                "value {:?}",
+                value
+            );
+        }
+        std::env::remove_var("REAPER_ALLOWED_SHARED_OVERLAYS");
+    }
+
+    #[test]
+    #[serial]
+    fn test_parse_shared_overlay_not_allowed() {
+        std::env::remove_var("REAPER_ANNOTATIONS_ENABLED");
+        let annots = make_annotations(&[("reaper.runtime/shared-overlay", "ci-cache")]);
+
+        // Nothing allowed by default
+        std::env::remove_var("REAPER_ALLOWED_SHARED_OVERLAYS");
+        assert_eq!(parse_annotations(&annots).unwrap().shared_overlay, None);
+
+        // Only the listed names
+        std::env::set_var("REAPER_ALLOWED_SHARED_OVERLAYS", "ci-cache-2,other");
+        assert_eq!(parse_annotations(&annots).unwrap().shared_overlay, None);
+        std::env::set_var("REAPER_ALLOWED_SHARED_OVERLAYS", "other,CI-Cache");
+        assert_eq!(
+            parse_annotations(&annots)
+                .unwrap()
+                .shared_overlay
+                .as_deref(),
+            Some("ci-cache")
+        );
+        std::env::remove_var("REAPER_ALLOWED_SHARED_OVERLAYS");
+    }
+
+    #[test]
+    #[serial]
+    fn test_parse_termination_grace() {
+        std::env::remove_var("REAPER_ANNOTATIONS_ENABLED");
+        for (value, expected) in [
+            ("30", Some(30)),
+            (" 5 ", Some(5)),
+            ("0", Some(0)),
+            ("-1", None),
+            ("30s", None),
+        ] {
+            let annots = make_annotations(&[("reaper.runtime/termination-grace", value)]);
+            let result = parse_annotations(&annots).unwrap();
+            assert_eq!(result.termination_grace, expected, "value {:?}", value);
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_parse_env_from_mount() {
+        std::env::remove_var("REAPER_ANNOTATIONS_ENABLED");
+        for (value, expected) in [
+            ("/etc/config/env", Some("/etc/config/env")),
+            ("etc/config/env", None),
+            ("/etc/config/../../root/env", None),
+            ("", None),
+        ] {
+            let annots = make_annotations(&[("reaper.runtime/env-from-mount", value)]);
+            let result = parse_annotations(&annots).unwrap();
+            assert_eq!(
+                result.env_from_mount.as_deref(),
+                expected,
+                "value {:?}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_parse_enable_fuse() {
+        std::env::remove_var("REAPER_ANNOTATIONS_ENABLED");
+        for (value, expected) in [
+            ("true", Some(true)),
+            ("TRUE", Some(true)),
+            ("false", Some(false)),
+            ("yes", None),
+        ] {
+            let annots = make_annotations(&[("reaper.runtime/enable-fuse", value)]);
+            let result = parse_annotations(&annots).unwrap();
+            assert_eq!(result.enable_fuse, expected, "value {:?}", value);
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_parse_argv0() {
+        std::env::remove_var("REAPER_ANNOTATIONS_ENABLED");
+        for (value, expected) in [
+            ("-sh", Some("-sh")),
+            ("ls", Some("ls")),
+            ("", None),
+            ("a\0b", None),
+        ] {
+            let annots = make_annotations(&[("reaper.runtime/argv0", value)]);
+            let result = parse_annotations(&annots).unwrap();
+            assert_eq!(result.argv0.as_deref(), expected, "value {:?}", value);
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_parse_overlay_isolation() {
+        std::env::remove_var("REAPER_ANNOTATIONS_ENABLED");
+        std::env::set_var("REAPER_ALLOW_NODE_ISOLATION", "true");
+        for (value, expected) in [
+            ("node", Some("node")),
+            ("Namespace", Some("namespace")),
+            ("cluster", None),
+            ("", None),
+        ] {
+            let annots = make_annotations(&[("reaper.runtime/overlay-isolation", value)]);
+            let result = parse_annotations(&annots).unwrap();
+            assert_eq!(
+                result.overlay_isolation.as_deref(),
+                expected,
+                "value {:?}",
+                value
+            );
+        }
+        std::env::remove_var("REAPER_ALLOW_NODE_ISOLATION");
+    }
+
+    #[test]
+    #[serial]
+    fn test_parse_overlay_isolation_node_not_allowed() {
+        std::env::remove_var("REAPER_ANNOTATIONS_ENABLED");
+        let isolation = |value: &str| {
+            let annots = make_annotations(&[("reaper.runtime/overlay-isolation", value)]);
+            parse_annotations(&annots).unwrap().overlay_isolation
+        };
+
+        for flag in [None, Some("false"), Some("0"), Some("yes")] {
+            match flag {
+                Some(v) => std::env::set_var("REAPER_ALLOW_NODE_ISOLATION", v),
+                None => std::env::remove_var("REAPER_ALLOW_NODE_ISOLATION"),
+            }
+            assert_eq!(isolation("node"), None, "flag {:?}", flag);
+            assert_eq!(isolation("NODE"), None, "flag {:?}", flag);
+            // Namespace isolation never needs the flag
+            assert_eq!(isolation("namespace").as_deref(), Some("namespace"));
+        }
+
+        std::env::set_var("REAPER_ALLOW_NODE_ISOLATION", "1");
+        assert_eq!(isolation("node").as_deref(), Some("node"));
+        std::env::remove_var("REAPER_ALLOW_NODE_ISOLATION");
+    }
+
+    #[test]
+    #[serial]
+    fn test_parse_after() {
+        std::env::remove_var("REAPER_ANNOTATIONS_ENABLED");
+        let after = |container: &str, status: &str| {
+            Some(StartAfter {
+                container: container.to_string(),
+                status: status.to_string(),
+            })
+        };
+        for (value, expected) in [
+            ("init-fs", after("init-fs", "running")),
+            ("init-fs=Stopped", after("init-fs", "stopped")),
+            (" 4f2a9c ", after("4f2a9c", "running")),
+            ("init-fs=ready", None),
+            ("../etc", None),
+            ("", None),
+        ] {
+            let annots = make_annotations(&[("reaper.runtime/after", value)]);
+            let result = parse_annotations(&annots).unwrap();
+            assert_eq!(result.after, expected, "value {:?}", value);
+        }
+    }
+
     // --- CLI serialization round-trip tests ---
 
     #[test]
@@ -6,7 +6,7 @@ use containerd_shim::{
 use containerd_shim_protos::{
     api, api::DeleteResponse, shim_async::Task, ttrpc::r#async::TtrpcContext,
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 #[cfg(unix)]
 use std::os::unix::fs::OpenOptionsExt;
 use std::path::Path;
@@ -16,6 +16,7 @@ use tracing_subscriber::EnvFilter;
 
 #[path = "../../config.rs"]
 mod config;
+mod stats;
 
 #[path = "../../annotations.rs"]
 #[allow(dead_code)]
@@ -62,6 +63,156 @@ fn reap_orphaned_children() {
 #[cfg(not(target_os = "linux"))]
 fn reap_orphaned_children() {}
 
+/// Coordinates background reaping with in-flight `Command::output()` calls.
+///
+/// `waitpid(-1)` would steal the zombie an `output()` is about to wait on,
+/// making it fail with ECHILD. Every `output()` holds a shared lock for its
+/// whole spawn-to-wait span; reaping takes the lock exclusively and is
+/// skipped while any output() is active.
+static CHILD_WAITS: std::sync::RwLock<()> = std::sync::RwLock::new(());
+
+/// Held while a `Command::output()` call is waiting on its child.
+struct ChildWaitGuard(#[allow(dead_code)] std::sync::RwLockReadGuard<'static, ()>);
+
+impl ChildWaitGuard {
+    fn enter() -> Self {
+        ChildWaitGuard(CHILD_WAITS.read().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+/// Reap orphans unless an output() wait is in flight. Returns whether it ran.
+fn reap_orphaned_children_if_idle() -> bool {
+    let _exclusive = match CHILD_WAITS.try_write() {
+        Ok(guard) => guard,
+        Err(std::sync::TryLockError::Poisoned(e)) => e.into_inner(),
+        Err(std::sync::TryLockError::WouldBlock) => return false,
+    };
+    reap_orphaned_children();
+    true
+}
+
+/// `Command::output()` under a [`ChildWaitGuard`]. All shim child processes
+/// must be run through this so the SIGCHLD reaper cannot race them.
+trait GuardedOutput {
+    fn guarded_output(&mut self) -> std::io::Result<std::process::Output>;
+
+    /// Like [`guarded_output`](Self::guarded_output), but gives up after
+    /// `timeout` (`None` waits forever): the child is killed and a
+    /// `TimedOut` error returned, so a hung runtime can't pin a blocking
+    /// thread. A child stuck in the kernel is left to the SIGCHLD reaper.
+    fn guarded_output_timeout(
+        &mut self,
+        timeout: Option<std::time::Duration>,
+    ) -> std::io::Result<std::process::Output>;
+}
+
+/// How often a timed child is polled for exit.
+const CHILD_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(10);
+
+/// How long a timed-out child gets to die after SIGKILL before it is left
+/// to the SIGCHLD reaper.
+const CHILD_KILL_GRACE: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// Read `pipe` to EOF on its own thread.
+fn drain_pipe<R: std::io::Read + Send + 'static>(
+    pipe: Option<R>,
+) -> Option<std::thread::JoinHandle<Vec<u8>>> {
+    pipe.map(|mut pipe| {
+        std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            let _ = pipe.read_to_end(&mut buf);
+            buf
+        })
+    })
+}
+
+impl GuardedOutput for std::process::Command {
+    fn guarded_output(&mut self) -> std::io::Result<std::process::Output> {
+        let _guard = ChildWaitGuard::enter();
+        self.output()
+    }
+
+    fn guarded_output_timeout(
+        &mut self,
+        timeout: Option<std::time::Duration>,
+    ) -> std::io::Result<std::process::Output> {
+        use std::process::Stdio;
+
+        let Some(timeout) = timeout else {
+            return self.guarded_output();
+        };
+        let _guard = ChildWaitGuard::enter();
+        let mut child = self
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        // Drain both pipes concurrently so a chatty child can't fill one and
+        // block before exiting.
+        let stdout = drain_pipe(child.stdout.take());
+        let stderr = drain_pipe(child.stderr.take());
+
+        let deadline = std::time::Instant::now() + timeout;
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if std::time::Instant::now() >= deadline {
+                let _ = child.kill();
+                let grace = std::time::Instant::now() + CHILD_KILL_GRACE;
+                while matches!(child.try_wait(), Ok(None)) && std::time::Instant::now() < grace {
+                    std::thread::sleep(CHILD_POLL_INTERVAL);
+                }
+                // The pipe readers are left to finish on their own: a
+                // grandchild may still hold the pipes open.
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    format!("timed out after {:?}", timeout),
+                ));
+            }
+            std::thread::sleep(CHILD_POLL_INTERVAL);
+        };
+
+        let collect = |reader: Option<std::thread::JoinHandle<Vec<u8>>>| {
+            reader.and_then(|r| r.join().ok()).unwrap_or_default()
+        };
+        Ok(std::process::Output {
+            status,
+            stdout: collect(stdout),
+            stderr: collect(stderr),
+        })
+    }
+}
+
+/// Delay before retrying a reap that was deferred by an in-flight output().
+const REAP_RETRY_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// Reap orphaned monitoring daemons as SIGCHLD arrives, so zombies don't pile
+/// up between the fixed reap points under heavy pod churn. Tokio's signal
+/// driver delivers SIGCHLD through a self-pipe, so no work runs in the handler.
+#[cfg(target_os = "linux")]
+fn spawn_sigchld_reaper() {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut sigchld = match signal(SignalKind::child()) {
+        Ok(s) => s,
+        Err(e) => {
+            tracing::warn!("Failed to install SIGCHLD handler: {}", e);
+            return;
+        }
+    };
+    tokio::spawn(async move {
+        while sigchld.recv().await.is_some() {
+            while !reap_orphaned_children_if_idle() {
+                tokio::time::sleep(REAP_RETRY_INTERVAL).await;
+            }
+        }
+    });
+}
+
+#[cfg(not(target_os = "linux"))]
+fn spawn_sigchld_reaper() {}
+
 /// Helper function to execute a command and properly reap the child process
 /// This is critical when forking happens inside the spawned process - we need to ensure
 /// the parent process is fully reaped even if it exits before the child is ready
@@ -72,10 +223,10 @@ fn execute_and_reap_child(program: &str, args: Vec<&str>) -> std::io::Result<std
     }
 
     // Spawn and wait for the process
-    let output = cmd.output()?;
+    let output = cmd.guarded_output_timeout(runtime_timeout())?;
 
     // Reap any orphaned child processes (monitoring daemons) adopted by the shim.
-    reap_orphaned_children();
+    reap_orphaned_children_if_idle();
 
     Ok(output)
 }
@@ -90,12 +241,43 @@ fn parse_runtime_version(output: &str) -> String {
         .to_string()
 }
 
-/// Check if the runtime version matches the shim version.
+/// Compatibility series of a `<major>.<minor>.<patch> (<hash> <date>)`
+/// version: the major version, or `0.<minor>` before 1.0, where minor
+/// releases may break (semver).
+fn version_series(version: &str) -> Option<String> {
+    let release = version.split_whitespace().next()?;
+    let mut parts = release.split('.');
+    let major: u64 = parts.next()?.parse().ok()?;
+    let minor: u64 = parts.next()?.parse().ok()?;
+    Some(if major == 0 {
+        format!("0.{}", minor)
+    } else {
+        major.to_string()
+    })
+}
+
+/// Whether a shim and runtime at these versions can work together: the
+/// same build, or builds of the same compatibility series.
+fn versions_compatible(shim_version: &str, runtime_version: &str) -> bool {
+    shim_version == runtime_version
+        || version_series(shim_version)
+            .is_some_and(|series| version_series(runtime_version) == Some(series))
+}
+
+/// `REAPER_STRICT_RUNTIME_VERSION=1`: exit at startup when the runtime is
+/// missing or incompatible instead of refusing each create.
+fn strict_runtime_version() -> bool {
+    std::env::var("REAPER_STRICT_RUNTIME_VERSION")
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+/// Check if the runtime version is compatible with the shim version.
 /// Returns `(compatible, runtime_version_string)`.
 fn check_version_compatibility(runtime_path: &str, shim_version: &str) -> (bool, String) {
     match std::process::Command::new(runtime_path)
         .arg("--version")
-        .output()
+        .guarded_output()
     {
         Ok(output) if output.status.success() => {
             let full_output = String::from_utf8_lossy(&output.stdout).trim().to_string();
@@ -106,10 +288,17 @@ fn check_version_compatibility(runtime_path: &str, shim_version: &str) -> (bool,
                     shim_version
                 );
                 (true, rt_version)
+            } else if versions_compatible(shim_version, &rt_version) {
+                warn!(
+                    "Version check OK: shim={} runtime={} are different builds \
+                     of a compatible version",
+                    shim_version, rt_version
+                );
+                (true, rt_version)
             } else {
                 tracing::error!(
                     "CRITICAL: Version mismatch! shim={} runtime={}. \
-                     Both binaries must be from the same build.",
+                     Both binaries must be from the same release series (major, or 0.minor for 0.x).",
                     shim_version,
                     rt_version
                 );
@@ -176,11 +365,26 @@ impl Shim for ReaperShim {
             info!("Runtime binary verified at: {}", runtime_path);
         }
 
-        // Version compatibility check: ensure shim and runtime were built from the same commit.
-        // The version string includes the git hash, so identical strings prove same build.
+        // Version compatibility check: the runtime must run and report a
+        // version of the shim's compatibility series (see version_series).
         let shim_version = version_string();
         let (compatible, runtime_version) =
             check_version_compatibility(&runtime_path, &shim_version);
+        if !compatible && strict_runtime_version() {
+            tracing::error!(
+                "REAPER_STRICT_RUNTIME_VERSION: runtime at {} is missing or incompatible \
+                 (shim {}, runtime {}), exiting",
+                runtime_path,
+                shim_version,
+                runtime_version
+            );
+            eprintln!(
+                "containerd-shim-reaper-v2: runtime {} is missing or incompatible \
+                 (shim {}, runtime {})",
+                runtime_path, shim_version, runtime_version
+            );
+            std::process::exit(1);
+        }
 
         ReaperShim {
             exit: Arc::new(ExitSignal::default()),
@@ -231,7 +435,8 @@ impl Shim for ReaperShim {
         ReaperTask {
             runtime_path: self.runtime_path.clone(),
             sandbox_state: Arc::new(Mutex::new(HashMap::new())),
-            stdin_holders: Arc::new(Mutex::new(HashMap::new())),
+            stdin_holders: StdinHolders::default(),
+            awaiting_start: Arc::new(Mutex::new(HashSet::new())),
             publisher: Arc::new(publisher),
             namespace: self.namespace.clone(),
             exit: self.exit.clone(),
@@ -241,6 +446,53 @@ impl Shim for ReaperShim {
     }
 }
 
+/// Default grace before delete() drops a stdin holder that close_io() never released.
+const DEFAULT_STDIN_HOLDER_LINGER_MS: u64 = 100;
+
+fn stdin_holder_linger() -> std::time::Duration {
+    let ms = std::env::var("REAPER_STDIN_HOLDER_LINGER_MS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_STDIN_HOLDER_LINGER_MS);
+    std::time::Duration::from_millis(ms)
+}
+
+/// Stdin FIFO read ends held open per container.
+///
+/// Holding the read end keeps containerd from getting EPIPE when the daemon
+/// exits before containerd closes the write end. close_io() is the
+/// authoritative release point; delete() only releases holders close_io()
+/// never touched, after a short linger so late writes still land.
+#[derive(Clone, Default)]
+struct StdinHolders(Arc<Mutex<HashMap<String, std::fs::File>>>);
+
+impl StdinHolders {
+    fn hold(&self, id: &str, file: std::fs::File) {
+        self.0.lock().unwrap().insert(id.to_string(), file);
+    }
+
+    fn is_held(&self, id: &str) -> bool {
+        self.0.lock().unwrap().contains_key(id)
+    }
+
+    /// Drop the holder immediately. Returns whether one was held.
+    fn release(&self, id: &str) -> bool {
+        self.0.lock().unwrap().remove(id).is_some()
+    }
+
+    /// Drop the holder after `linger`, unless close_io() already released it.
+    /// Returns whether this call released it.
+    async fn release_after(&self, id: &str, linger: std::time::Duration) -> bool {
+        if !self.is_held(id) {
+            return false;
+        }
+        if !linger.is_zero() {
+            tokio::time::sleep(linger).await;
+        }
+        self.release(id)
+    }
+}
+
 #[derive(Clone)]
 struct SandboxInfo {
     is_sandbox: bool,
@@ -253,10 +505,10 @@ struct ReaperTask {
     runtime_path: String,
     // Track which containers are sandboxes (pause containers) vs real workloads
     sandbox_state: Arc<Mutex<HashMap<String, SandboxInfo>>>,
-    // Hold stdin FIFO read ends open so containerd doesn't get EPIPE
-    // when the daemon exits before containerd closes the write end.
-    // Dropped on close_io() or delete().
-    stdin_holders: Arc<Mutex<HashMap<String, std::fs::File>>>,
+    // Stdin FIFO read ends, released on close_io() (or delete() as a fallback)
+    stdin_holders: StdinHolders,
+    // Workloads created with REAPER_CREATE_TTL set that start() hasn't reached yet
+    awaiting_start: Arc<Mutex<HashSet<String>>>,
     // Publisher for sending task lifecycle events to containerd
     publisher: Arc<RemotePublisher>,
     // Namespace for events
@@ -325,6 +577,277 @@ fn is_sandbox_container(bundle: &str) -> bool {
     false
 }
 
+/// Sandbox flag recorded by `reaper-runtime create --sandbox`, or `None` if
+/// the container has no (readable) state file.
+fn persisted_sandbox_flag(container_id: &str) -> Option<bool> {
+    validate_id(container_id).ok()?;
+    let path = format!("{}/{}/state.json", runtime_state_dir(), container_id);
+    let data = std::fs::read(path).ok()?;
+    let state: serde_json::Value = serde_json::from_slice(&data).ok()?;
+    Some(state["sandbox"].as_bool().unwrap_or(false))
+}
+
+/// cgroup directory `reaper-runtime start` recorded for a container, if any.
+fn persisted_cgroup_path(container_id: &str) -> Option<String> {
+    let path = format!("{}/{}/state.json", runtime_state_dir(), container_id);
+    let data = std::fs::read(path).ok()?;
+    let state: serde_json::Value = serde_json::from_slice(&data).ok()?;
+    state["cgroup_path"].as_str().map(str::to_string)
+}
+
+/// Whether a container is a sandbox. The persisted flag wins; config.json is
+/// only read for containers without state, since the bundle may be gone.
+fn resolve_sandbox(container_id: &str, bundle: &str) -> bool {
+    persisted_sandbox_flag(container_id).unwrap_or_else(|| is_sandbox_container(bundle))
+}
+
+/// Default wait() poll interval for workload state (`REAPER_WAIT_POLL_MS`).
+const DEFAULT_WAIT_POLL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// Default wait() poll interval for exec state; execs are mostly interactive.
+const DEFAULT_EXEC_WAIT_POLL: std::time::Duration = std::time::Duration::from_millis(200);
+
+/// Default cap the poll interval backs off to (`REAPER_WAIT_POLL_MAX_MS`).
+const DEFAULT_WAIT_POLL_MAX: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Bounds both settings are clamped to.
+const WAIT_POLL_FLOOR: std::time::Duration = std::time::Duration::from_millis(10);
+const WAIT_POLL_CEILING: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// How often wait() re-reads the state of a running process. Polls at
+/// `initial` while the process is young, then backs off to 1% of the time
+/// waited so far, up to `max`: short jobs keep their exit latency low and
+/// long-running ones cost few state reads.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct WaitPoll {
+    initial: std::time::Duration,
+    max: std::time::Duration,
+}
+
+impl WaitPoll {
+    /// Intervals from `REAPER_WAIT_POLL_MS` (default `default_initial`) and
+    /// `REAPER_WAIT_POLL_MAX_MS`, clamped to 10ms..=10s. Setting the max to
+    /// the initial interval disables backoff.
+    fn from_env(default_initial: std::time::Duration) -> Self {
+        let ms = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.trim().parse::<u64>().ok())
+                .map(std::time::Duration::from_millis)
+        };
+        let initial = ms("REAPER_WAIT_POLL_MS")
+            .unwrap_or(default_initial)
+            .clamp(WAIT_POLL_FLOOR, WAIT_POLL_CEILING);
+        let max = ms("REAPER_WAIT_POLL_MAX_MS")
+            .unwrap_or(DEFAULT_WAIT_POLL_MAX)
+            .clamp(initial, WAIT_POLL_CEILING);
+        WaitPoll { initial, max }
+    }
+
+    /// Interval to sleep after having waited `elapsed`.
+    fn interval(&self, elapsed: std::time::Duration) -> std::time::Duration {
+        (elapsed / 100).clamp(self.initial, self.max)
+    }
+}
+
+/// `REAPER_MAX_CONTAINERS`: cap on workload containers per node, or `None`
+/// (the default) for no limit. Sandboxes are not counted.
+fn max_containers() -> Option<usize> {
+    std::env::var("REAPER_MAX_CONTAINERS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|&n| n > 0)
+}
+
+/// Number of workload containers under `root` that haven't stopped,
+/// not counting `exclude` (a retried create for the same id).
+fn active_workload_count(root: &Path, exclude: &str) -> usize {
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .filter(|e| e.file_name().to_str() != Some(exclude))
+        .filter_map(|e| std::fs::read(e.path().join("state.json")).ok())
+        .filter_map(|data| serde_json::from_slice::<serde_json::Value>(&data).ok())
+        .filter(|state| {
+            !state["sandbox"].as_bool().unwrap_or(false)
+                && state["status"].as_str() != Some("stopped")
+        })
+        .count()
+}
+
+/// How long a created workload may wait for start() before the shim reaps
+/// it (`REAPER_CREATE_TTL`, in seconds). Unset or `0` disables reaping.
+fn create_ttl() -> Option<std::time::Duration> {
+    std::env::var("REAPER_CREATE_TTL")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|&secs| secs > 0)
+        .map(std::time::Duration::from_secs)
+}
+
+/// Default for `REAPER_RUNTIME_TIMEOUT_SECS`. Above the runtime's default
+/// `REAPER_START_AFTER_TIMEOUT_SECS` (60s), which `start` may spend waiting.
+const DEFAULT_RUNTIME_TIMEOUT_SECS: u64 = 120;
+
+/// How long a `reaper-runtime` create/start/state/delete call may take
+/// before it is killed (`REAPER_RUNTIME_TIMEOUT_SECS`). `0` disables the limit.
+fn runtime_timeout() -> Option<std::time::Duration> {
+    let secs = std::env::var("REAPER_RUNTIME_TIMEOUT_SECS")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(DEFAULT_RUNTIME_TIMEOUT_SECS);
+    (secs > 0).then(|| std::time::Duration::from_secs(secs))
+}
+
+/// Default for `REAPER_CREATE_RETRIES`.
+const DEFAULT_CREATE_RETRIES: u32 = 2;
+
+/// Delay before the first create retry; doubled on each further attempt.
+const CREATE_RETRY_BASE_DELAY: std::time::Duration = std::time::Duration::from_millis(200);
+
+/// How many times a transiently failed `reaper-runtime create` is retried
+/// (`REAPER_CREATE_RETRIES`). `0` disables retrying.
+fn create_retries() -> u32 {
+    std::env::var("REAPER_CREATE_RETRIES")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(DEFAULT_CREATE_RETRIES)
+}
+
+/// OS errors that can clear up on their own, e.g. the state root being
+/// briefly unwritable or busy while the node is still starting.
+const TRANSIENT_CREATE_ERRNOS: &[i32] = &[
+    nix::libc::EACCES,
+    nix::libc::EAGAIN,
+    nix::libc::EBUSY,
+    nix::libc::EINTR,
+    nix::libc::EMFILE,
+    nix::libc::ENFILE,
+    nix::libc::ENOSPC,
+    nix::libc::EROFS,
+];
+
+/// Whether a failed `reaper-runtime create` is worth retrying, judged from
+/// its error output. Bad input (malformed config.json, invalid IDs) never is.
+fn is_transient_create_error(stderr: &str) -> bool {
+    if stderr.contains("parsing config.json") || stderr.contains("invalid") {
+        return false;
+    }
+    TRANSIENT_CREATE_ERRNOS
+        .iter()
+        .any(|errno| stderr.contains(&format!("(os error {})", errno)))
+}
+
+/// The workloads among `ids` still `created` (no PID yet) in the runtime
+/// state tree whose state was last written at least `ttl` before `now`.
+fn abandoned_created<'a>(
+    root: &Path,
+    ids: impl IntoIterator<Item = &'a str>,
+    ttl: std::time::Duration,
+    now: std::time::SystemTime,
+) -> Vec<String> {
+    ids.into_iter()
+        .filter(|id| {
+            let path = root.join(id).join("state.json");
+            let Ok(modified) = std::fs::metadata(&path).and_then(|m| m.modified()) else {
+                return false;
+            };
+            let Some(state) = std::fs::read(&path)
+                .ok()
+                .and_then(|data| serde_json::from_slice::<serde_json::Value>(&data).ok())
+            else {
+                return false;
+            };
+            state["status"].as_str() == Some("created")
+                && state["pid"].is_null()
+                && !state["sandbox"].as_bool().unwrap_or(false)
+                && now.duration_since(modified).is_ok_and(|age| age >= ttl)
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+/// Whether create() for an id that already has runtime state succeeds with
+/// that state (`REAPER_CREATE_IDEMPOTENT`, default) or fails.
+fn create_idempotent() -> bool {
+    std::env::var("REAPER_CREATE_IDEMPOTENT")
+        .map(|v| !(v == "0" || v.eq_ignore_ascii_case("false")))
+        .unwrap_or(true)
+}
+
+/// Existing runtime state for `container_id`, or `None` if the id is new.
+/// Fails with ALREADY_EXISTS when duplicate creates are not idempotent or
+/// the existing container has a different bundle (as the runtime does), and
+/// with INTERNAL when the state file is corrupt.
+fn check_duplicate_create(
+    container_id: &str,
+    bundle: &str,
+    idempotent: bool,
+) -> TtrpcResult<Option<serde_json::Value>> {
+    let path = Path::new(&runtime_state_dir())
+        .join(container_id)
+        .join("state.json");
+    let Ok(data) = std::fs::read(&path) else {
+        return Ok(None);
+    };
+    let state: serde_json::Value = serde_json::from_slice(&data).map_err(|e| {
+        ttrpc::Error::RpcStatus(ttrpc::get_status(
+            ttrpc::Code::INTERNAL,
+            format!(
+                "container {:?} has a corrupt state file {}: {}",
+                container_id,
+                path.display(),
+                e
+            ),
+        ))
+    })?;
+    if !idempotent {
+        return Err(ttrpc::Error::RpcStatus(ttrpc::get_status(
+            ttrpc::Code::ALREADY_EXISTS,
+            format!(
+                "container {:?} already exists (status: {})",
+                container_id,
+                state["status"].as_str().unwrap_or("unknown")
+            ),
+        )));
+    }
+    let existing_bundle = state["bundle"].as_str();
+    if existing_bundle.map(Path::new) != Some(Path::new(bundle)) {
+        return Err(ttrpc::Error::RpcStatus(ttrpc::get_status(
+            ttrpc::Code::ALREADY_EXISTS,
+            format!(
+                "container {:?} already exists with bundle {}",
+                container_id,
+                existing_bundle.unwrap_or("unknown")
+            ),
+        )));
+    }
+    Ok(Some(state))
+}
+
+/// Refuse a new workload once `REAPER_MAX_CONTAINERS` are active.
+///
+/// Counted from the runtime state root, so the limit holds across the
+/// per-pod shims; two creates racing for the last slot may both pass.
+fn check_container_limit(container_id: &str) -> TtrpcResult<()> {
+    let Some(max) = max_containers() else {
+        return Ok(());
+    };
+    let active = active_workload_count(Path::new(&runtime_state_dir()), container_id);
+    if active >= max {
+        return Err(ttrpc::Error::RpcStatus(ttrpc::get_status(
+            ttrpc::Code::RESOURCE_EXHAUSTED,
+            format!(
+                "node has {} active reaper containers (REAPER_MAX_CONTAINERS={})",
+                active, max
+            ),
+        )));
+    }
+    Ok(())
+}
+
 /// Extract the Kubernetes namespace from OCI config.json annotations.
 ///
 /// Containerd CRI writes `io.kubernetes.cri.sandbox-namespace` into OCI spec annotations.
@@ -388,13 +911,24 @@ fn extract_reaper_annotations_from_bundle(bundle: &str) -> HashMap<String, Strin
     }
 }
 
+/// Longest accepted ID, from `REAPER_MAX_ID_LENGTH` (default 256), matching
+/// the runtime's limit.
+fn max_id_length() -> usize {
+    std::env::var("REAPER_MAX_ID_LENGTH")
+        .ok()
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(256)
+}
+
 /// Validate that an ID is safe for use in filesystem paths.
-/// Rejects empty strings, path traversal (`..`), and characters outside `[a-zA-Z0-9._-]`.
+/// Rejects empty strings, anything containing `..`, overlong IDs, and
+/// characters outside `[a-zA-Z0-9._-]`.
 fn validate_id(id: &str) -> Result<(), Error> {
     if id.is_empty()
-        || id.len() > 256
+        || id.len() > max_id_length()
         || id == "."
-        || id == ".."
+        || id.contains("..")
         || !id
             .chars()
             .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_' || c == '-')
@@ -416,17 +950,294 @@ fn build_exec_state_path(container_id: &str, exec_id: &str) -> Result<String, Er
     ))
 }
 
+/// Polls of the exec state file while waiting for an exec's PID. Overlay
+/// namespace entry on Linux can be slow, so allow 10s there (as `do_start`
+/// does for the main workload) and 2s elsewhere.
+const EXEC_PID_POLL_ATTEMPTS: u32 = if cfg!(target_os = "linux") { 100 } else { 20 };
+const EXEC_PID_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// What one read of an exec state file says about the exec's PID.
+#[derive(Debug, PartialEq)]
+enum ExecPidProbe {
+    /// File missing, or mid-write and not yet valid JSON
+    Absent,
+    /// State written, PID not recorded yet
+    Pending,
+    /// PID recorded
+    Started(u32),
+    /// Exec stopped before a PID was recorded
+    Stopped(i32),
+}
+
+fn probe_exec_pid(data: Option<&str>) -> ExecPidProbe {
+    let Some(state) = data.and_then(|d| serde_json::from_str::<serde_json::Value>(d).ok()) else {
+        return ExecPidProbe::Absent;
+    };
+    match state["pid"].as_u64() {
+        Some(pid) if pid > 0 => ExecPidProbe::Started(pid as u32),
+        _ if state["status"].as_str() == Some("stopped") => {
+            ExecPidProbe::Stopped(state["exit_code"].as_i64().unwrap_or(-1) as i32)
+        }
+        _ => ExecPidProbe::Pending,
+    }
+}
+
+/// Poll `read` until the exec has a PID or has stopped, up to `attempts`
+/// times. Returns the last probe, so callers can tell a missing file from
+/// an exec that is still setting up.
+fn poll_exec_pid(
+    mut read: impl FnMut() -> Option<String>,
+    attempts: u32,
+    interval: std::time::Duration,
+) -> ExecPidProbe {
+    let mut probe = ExecPidProbe::Absent;
+    for attempt in 0..attempts {
+        probe = probe_exec_pid(read().as_deref());
+        if matches!(probe, ExecPidProbe::Started(_) | ExecPidProbe::Stopped(_)) {
+            break;
+        }
+        if attempt + 1 < attempts {
+            std::thread::sleep(interval);
+        }
+    }
+    probe
+}
+
+/// How many times `connect()` reads state while a running container has no
+/// PID recorded yet, and how long it waits between reads.
+const CONNECT_PID_ATTEMPTS: u32 = 20;
+const CONNECT_PID_INTERVAL: std::time::Duration = std::time::Duration::from_millis(50);
+
+/// Whether runtime state says the container is running but has no PID yet.
+fn running_without_pid(state: &serde_json::Value) -> bool {
+    state["status"].as_str() == Some("running") && state["pid"].as_u64().unwrap_or(0) == 0
+}
+
+/// Read state until a running container reports its PID, up to `attempts`
+/// times. Any other state is returned as soon as it is read.
+fn poll_running_pid(
+    mut read: impl FnMut() -> TtrpcResult<serde_json::Value>,
+    attempts: u32,
+    interval: std::time::Duration,
+) -> TtrpcResult<serde_json::Value> {
+    let mut attempt = 1;
+    loop {
+        let state = read()?;
+        if !running_without_pid(&state) || attempt >= attempts {
+            return Ok(state);
+        }
+        attempt += 1;
+        std::thread::sleep(interval);
+    }
+}
+
+/// Serializes the exec-id check and state write in `exec()` so two concurrent
+/// requests with the same id can't both see the slot as free.
+static EXEC_STATE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Write a new exec state file, refusing to clobber a live exec with the same id.
+///
+/// An existing file is only replaced once its exec has stopped; a missing or
+/// unreadable file is treated as free.
+fn write_exec_state_exclusive(exec_path: &str, exec_state: &serde_json::Value) -> TtrpcResult<()> {
+    let _guard = EXEC_STATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    if let Some(status) = std::fs::read(exec_path)
+        .ok()
+        .and_then(|data| serde_json::from_slice::<serde_json::Value>(&data).ok())
+        .and_then(|v| v["status"].as_str().map(String::from))
+    {
+        if status != "stopped" {
+            return Err(ttrpc::Error::RpcStatus(ttrpc::get_status(
+                ttrpc::Code::ALREADY_EXISTS,
+                format!(
+                    "exec id {:?} already exists (status: {})",
+                    exec_state["exec_id"].as_str().unwrap_or_default(),
+                    status
+                ),
+            )));
+        }
+    }
+
+    std::fs::write(exec_path, serde_json::to_vec_pretty(exec_state).unwrap()).map_err(|e| {
+        ttrpc::Error::RpcStatus(ttrpc::get_status(
+            ttrpc::Code::INTERNAL,
+            format!("write exec state: {}", e),
+        ))
+    })
+}
+
 /// Map a status string from runtime state JSON to the protobuf Status enum.
+///
+/// The strings are the `STATUS_*` constants written by reaper-runtime's state
+/// module. "starting" (start in progress, no PID yet) has no containerd
+/// equivalent and is still CREATED. Anything unrecognised maps to UNKNOWN.
 fn parse_container_status(status: &str) -> ::protobuf::EnumOrUnknown<api::Status> {
     match status {
-        "created" => ::protobuf::EnumOrUnknown::new(api::Status::CREATED),
+        "created" | "starting" => ::protobuf::EnumOrUnknown::new(api::Status::CREATED),
         "running" => ::protobuf::EnumOrUnknown::new(api::Status::RUNNING),
         "stopped" => ::protobuf::EnumOrUnknown::new(api::Status::STOPPED),
+        "paused" => ::protobuf::EnumOrUnknown::new(api::Status::PAUSED),
+        "pausing" => ::protobuf::EnumOrUnknown::new(api::Status::PAUSING),
         _ => ::protobuf::EnumOrUnknown::new(api::Status::UNKNOWN),
     }
 }
 
+/// The `populated` flag of a cgroup v2 `cgroup.events` file.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_cgroup_populated(events: &str) -> Option<bool> {
+    events.lines().find_map(|line| match line.split_once(' ')? {
+        ("populated", "1") => Some(true),
+        ("populated", "0") => Some(false),
+        _ => None,
+    })
+}
+
+/// Whether `populated` went from 1 to 0, i.e. the last process left.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn cgroup_emptied(previous: Option<bool>, current: Option<bool>) -> bool {
+    previous == Some(true) && current == Some(false)
+}
+
+/// Watches a container's dedicated cgroup so wait() can block on
+/// `cgroup.events` instead of polling the runtime state while it runs.
+#[cfg(target_os = "linux")]
+struct CgroupWatch {
+    events: std::fs::File,
+    populated: Option<bool>,
+}
+
+#[cfg(target_os = "linux")]
+impl CgroupWatch {
+    fn open(cgroup_dir: &Path) -> std::io::Result<Self> {
+        let mut watch = Self {
+            events: std::fs::File::open(cgroup_dir.join("cgroup.events"))?,
+            populated: None,
+        };
+        watch.populated = watch.read_populated()?;
+        Ok(watch)
+    }
+
+    fn read_populated(&mut self) -> std::io::Result<Option<bool>> {
+        use std::io::{Read, Seek, SeekFrom};
+        let mut events = String::new();
+        self.events.seek(SeekFrom::Start(0))?;
+        self.events.read_to_string(&mut events)?;
+        Ok(parse_cgroup_populated(&events))
+    }
+
+    fn is_populated(&self) -> bool {
+        self.populated == Some(true)
+    }
+
+    /// Block until `cgroup.events` changes or `timeout` elapses. Returns
+    /// whether the cgroup emptied.
+    fn wait_change(&mut self, timeout: std::time::Duration) -> std::io::Result<bool> {
+        use std::os::unix::io::AsRawFd;
+        // kernfs signals modifications of cgroup.events with POLLPRI
+        let mut pfd = nix::libc::pollfd {
+            fd: self.events.as_raw_fd(),
+            events: nix::libc::POLLPRI,
+            revents: 0,
+        };
+        let ms = timeout.as_millis().min(i32::MAX as u128) as i32;
+        if unsafe { nix::libc::poll(&mut pfd, 1, ms) } < 0 {
+            let err = std::io::Error::last_os_error();
+            if err.kind() != std::io::ErrorKind::Interrupted {
+                return Err(err);
+            }
+        }
+        let previous = self.populated;
+        self.populated = self.read_populated()?;
+        Ok(cgroup_emptied(previous, self.populated))
+    }
+}
+
 impl ReaperTask {
+    /// Sandbox info for a container, rebuilt from the persisted state flag
+    /// when the in-memory map doesn't know it (e.g. after a shim restart).
+    fn sandbox_info(&self, container_id: &str) -> Option<SandboxInfo> {
+        let mut state = self.sandbox_state.lock().unwrap();
+        if let Some(info) = state.get(container_id) {
+            return Some(info.clone());
+        }
+        let info = SandboxInfo {
+            is_sandbox: persisted_sandbox_flag(container_id)?,
+            exit_notify: Arc::new(tokio::sync::Notify::new()),
+        };
+        state.insert(container_id.to_string(), info.clone());
+        Some(info)
+    }
+
+    fn is_sandbox(&self, container_id: &str) -> bool {
+        self.sandbox_info(container_id)
+            .is_some_and(|info| info.is_sandbox)
+    }
+
+    /// Run a best-effort runtime command for sandbox bookkeeping; failures
+    /// are logged, since sandboxes are otherwise handled in the shim alone.
+    async fn run_runtime_for_sandbox(&self, args: Vec<String>) {
+        let runtime_path = self.runtime_path.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            std::process::Command::new(&runtime_path)
+                .args(&args)
+                .guarded_output_timeout(runtime_timeout())
+        })
+        .await;
+        match result {
+            Ok(Ok(output)) if output.status.success() => {}
+            Ok(Ok(output)) => warn!(
+                "sandbox state update failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            Ok(Err(e)) => warn!("sandbox state update failed: {}", e),
+            Err(e) => warn!("sandbox state update task failed: {}", e),
+        }
+    }
+
+    /// After `ttl`, reap a workload that start() never reached: release its
+    /// stdin holder and have the runtime delete its state.
+    ///
+    /// A start() that arrived, even one still waiting (e.g. on a
+    /// `reaper.runtime/after` dependency), has already taken the id out of
+    /// `awaiting_start`, so only containers never asked to start are reaped.
+    async fn reap_if_never_started(&self, container_id: String, ttl: std::time::Duration) {
+        tokio::time::sleep(ttl).await;
+        if !self.awaiting_start.lock().unwrap().remove(&container_id) {
+            return;
+        }
+        let root = runtime_state_dir();
+        let now = std::time::SystemTime::now();
+        if abandoned_created(Path::new(&root), [container_id.as_str()], ttl, now).is_empty() {
+            return;
+        }
+
+        warn!(
+            "container {} not started within REAPER_CREATE_TTL ({:?}), reaping it",
+            container_id, ttl
+        );
+        self.stdin_holders.release(&container_id);
+        let runtime_path = self.runtime_path.clone();
+        let id = container_id.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            std::process::Command::new(&runtime_path)
+                .arg("delete")
+                .arg(&id)
+                .guarded_output_timeout(runtime_timeout())
+        })
+        .await;
+        match result {
+            Ok(Ok(output)) if output.status.success() => {}
+            Ok(Ok(output)) => warn!(
+                "reaping {} failed: {}",
+                container_id,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            Ok(Err(e)) => warn!("reaping {} failed: {}", container_id, e),
+            Err(e) => warn!("reaping {} task failed: {}", container_id, e),
+        }
+    }
+
     /// Publish a TaskExit event to containerd
     async fn publish_exit_event(
         &self,
@@ -478,6 +1289,41 @@ impl ReaperTask {
             tracing::error!("Failed to publish TaskExit event: {:?}", e);
         }
     }
+
+    /// Publish a TaskOOM event to containerd
+    async fn publish_oom_event(&self, container_id: &str) {
+        let (topic, namespace, event) = oom_event(&self.namespace, container_id);
+        info!("Publishing TaskOOM event: container_id={}", container_id);
+        if let Err(e) = self
+            .publisher
+            .publish(
+                ::containerd_shim::Context::default(),
+                topic,
+                namespace,
+                Box::new(event),
+            )
+            .await
+        {
+            tracing::error!("Failed to publish TaskOOM event: {:?}", e);
+        }
+    }
+}
+
+/// Topic, namespace and payload of the event telling containerd that the
+/// OOM killer hit `container_id`.
+fn oom_event<'a>(
+    namespace: &'a str,
+    container_id: &str,
+) -> (
+    &'static str,
+    &'a str,
+    containerd_shim_protos::events::task::TaskOOM,
+) {
+    let event = containerd_shim_protos::events::task::TaskOOM {
+        container_id: container_id.to_string(),
+        ..Default::default()
+    };
+    ("/tasks/oom", namespace, event)
 }
 
 #[async_trait::async_trait]
@@ -491,6 +1337,7 @@ impl Task for ReaperTask {
             "create() called - container_id={}, bundle={}",
             req.id, req.bundle
         );
+        validate_id(&req.id)?;
 
         // Refuse all workloads if shim and runtime versions don't match.
         // This prevents silent failures from mismatched binaries.
@@ -498,7 +1345,7 @@ impl Task for ReaperTask {
             let shim_version = version_string();
             let msg = format!(
                 "Version mismatch: shim is {} but runtime is {}. \
-                 Both must be from the same build.",
+                 Both must be from the same release series (major, or 0.minor for 0.x).",
                 shim_version, self.runtime_version
             );
             tracing::error!("create() refused: {}", msg);
@@ -508,20 +1355,55 @@ impl Task for ReaperTask {
             )));
         }
 
+        // A repeated create for an id the runtime already knows either
+        // returns the existing container or fails, mirroring the runtime
+        if let Some(existing) = check_duplicate_create(&req.id, &req.bundle, create_idempotent())? {
+            info!(
+                "create() - container_id={} already exists, returning existing state",
+                req.id
+            );
+            let is_sandbox = existing["sandbox"].as_bool().unwrap_or(false);
+            self.sandbox_state
+                .lock()
+                .unwrap()
+                .entry(req.id.clone())
+                .or_insert_with(|| SandboxInfo {
+                    is_sandbox,
+                    exit_notify: Arc::new(tokio::sync::Notify::new()),
+                });
+            let pid = if is_sandbox {
+                1
+            } else {
+                existing["pid"].as_u64().unwrap_or(0) as u32
+            };
+            return Ok(api::CreateTaskResponse {
+                pid,
+                ..Default::default()
+            });
+        }
+
         // Detect if this is a sandbox/pause container
-        let is_sandbox = is_sandbox_container(&req.bundle);
+        let is_sandbox = resolve_sandbox(&req.id, &req.bundle);
 
         if is_sandbox {
             info!("create() - detected SANDBOX container, faking creation");
             // Track this as a sandbox with fake PID
-            let mut state = self.sandbox_state.lock().unwrap();
-            state.insert(
+            self.sandbox_state.lock().unwrap().insert(
                 req.id.clone(),
                 SandboxInfo {
                     is_sandbox: true,
                     exit_notify: Arc::new(tokio::sync::Notify::new()),
                 },
             );
+            // Persist the flag so later calls don't depend on config.json
+            self.run_runtime_for_sandbox(vec![
+                "create".into(),
+                req.id.clone(),
+                "--bundle".into(),
+                req.bundle.clone(),
+                "--sandbox".into(),
+            ])
+            .await;
 
             info!("create() succeeded - container_id={} (sandbox)", req.id);
             return Ok(api::CreateTaskResponse {
@@ -530,6 +1412,11 @@ impl Task for ReaperTask {
             });
         }
 
+        if let Err(e) = check_container_limit(&req.id) {
+            tracing::error!("create() refused: {}", e);
+            return Err(e);
+        }
+
         // Real workload container - call reaper-runtime
         info!("create() - detected WORKLOAD container, calling reaper-runtime");
 
@@ -580,49 +1467,80 @@ impl Task for ReaperTask {
         let stderr_path = req.stderr.clone();
         let annotation_args = annotations::annotations_to_cli_args(&reaper_annotations);
 
-        let output = tokio::task::spawn_blocking(move || {
-            let mut cmd = std::process::Command::new(&runtime_path);
-            cmd.arg("create")
-                .arg(&container_id)
-                .arg("--bundle")
-                .arg(&bundle_path);
+        let mut args: Vec<String> = vec![
+            "create".into(),
+            container_id,
+            "--bundle".into(),
+            bundle_path,
+        ];
 
-            // Pass K8s namespace for per-namespace overlay isolation
-            if let Some(ref ns) = k8s_namespace {
-                cmd.arg("--namespace").arg(ns);
-            }
+        // Pass K8s namespace for per-namespace overlay isolation
+        if let Some(ns) = k8s_namespace {
+            args.extend(["--namespace".into(), ns]);
+        }
 
-            // Pass Reaper annotations for per-pod configuration
-            for ann in &annotation_args {
-                cmd.arg("--annotation").arg(ann);
-            }
+        // Pass Reaper annotations for per-pod configuration
+        for ann in annotation_args {
+            args.extend(["--annotation".into(), ann]);
+        }
 
-            // Pass terminal flag if containerd requests a PTY (kubectl run -it)
-            if terminal {
-                cmd.arg("--terminal");
-            }
+        // Pass terminal flag if containerd requests a PTY (kubectl run -it)
+        if terminal {
+            args.push("--terminal".into());
+        }
 
-            // Pass I/O paths if provided by containerd
-            if !stdin_path.is_empty() {
-                cmd.arg("--stdin").arg(&stdin_path);
-            }
-            if !stdout_path.is_empty() {
-                cmd.arg("--stdout").arg(&stdout_path);
-            }
-            if !stderr_path.is_empty() {
-                cmd.arg("--stderr").arg(&stderr_path);
-            }
+        // Pass I/O paths if provided by containerd
+        if !stdin_path.is_empty() {
+            args.extend(["--stdin".into(), stdin_path]);
+        }
+        if !stdout_path.is_empty() {
+            args.extend(["--stdout".into(), stdout_path]);
+        }
+        if !stderr_path.is_empty() {
+            args.extend(["--stderr".into(), stderr_path]);
+        }
 
-            cmd.output()
-        })
-        .await
-        .map_err(|e| {
-            tracing::error!("Failed to spawn reaper-runtime task: {}", e);
-            ttrpc::Error::RpcStatus(ttrpc::get_status(
-                ttrpc::Code::INTERNAL,
-                format!("Failed to spawn reaper-runtime task: {}", e),
-            ))
-        })?
+        // Retry failures that look transient (e.g. /run/reaper not yet
+        // writable during node startup) with exponential backoff.
+        let retries = create_retries();
+        let mut attempt = 0;
+        let output = loop {
+            let runtime_path = runtime_path.clone();
+            let args = args.clone();
+            let result = tokio::task::spawn_blocking(move || {
+                std::process::Command::new(&runtime_path)
+                    .args(&args)
+                    .guarded_output_timeout(runtime_timeout())
+            })
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to spawn reaper-runtime task: {}", e);
+                ttrpc::Error::RpcStatus(ttrpc::get_status(
+                    ttrpc::Code::INTERNAL,
+                    format!("Failed to spawn reaper-runtime task: {}", e),
+                ))
+            })?;
+
+            let transient = match &result {
+                Ok(output) if output.status.success() => false,
+                // Killed by a signal rather than failing on its own
+                Ok(output) if output.status.code().is_none() => true,
+                Ok(output) => is_transient_create_error(&String::from_utf8_lossy(&output.stderr)),
+                Err(e) => is_transient_create_error(&e.to_string()),
+            };
+            if !transient || attempt >= retries {
+                break result;
+            }
+            let delay = CREATE_RETRY_BASE_DELAY * 2u32.pow(attempt);
+            attempt += 1;
+            tracing::warn!(
+                "create() - reaper-runtime create failed transiently, retrying in {:?} (attempt {}/{})",
+                delay,
+                attempt,
+                retries
+            );
+            tokio::time::sleep(delay).await;
+        }
         .map_err(|e| {
             tracing::error!("Failed to execute reaper-runtime create: {}", e);
             ttrpc::Error::RpcStatus(ttrpc::get_status(
@@ -658,10 +1576,7 @@ impl Task for ReaperTask {
             {
                 Ok(file) => {
                     info!("create() - holding stdin FIFO open: {}", req.stdin);
-                    self.stdin_holders
-                        .lock()
-                        .unwrap()
-                        .insert(req.id.clone(), file);
+                    self.stdin_holders.hold(&req.id, file);
                 }
                 Err(e) => {
                     tracing::warn!("create() - failed to open stdin FIFO {}: {}", req.stdin, e);
@@ -669,6 +1584,13 @@ impl Task for ReaperTask {
             }
         }
 
+        if let Some(ttl) = create_ttl() {
+            self.awaiting_start.lock().unwrap().insert(req.id.clone());
+            let task = self.clone();
+            let container_id = req.id.clone();
+            tokio::spawn(async move { task.reap_if_never_started(container_id, ttl).await });
+        }
+
         let mut resp = api::CreateTaskResponse::new();
         resp.set_pid(0); // PID will be set on start
         info!("create() succeeded - container_id={}", req.id);
@@ -716,23 +1638,43 @@ impl Task for ReaperTask {
             let exec_path = build_exec_state_path(&req.id, &req.exec_id)?;
             let exec_path_clone = exec_path.clone();
 
-            let pid = tokio::task::spawn_blocking(move || {
-                for _ in 0..20 {
-                    if let Ok(data) = std::fs::read_to_string(&exec_path_clone) {
-                        if let Ok(state) = serde_json::from_str::<serde_json::Value>(&data) {
-                            if let Some(pid) = state["pid"].as_u64() {
-                                if pid > 0 {
-                                    return pid as u32;
-                                }
-                            }
-                        }
-                    }
-                    std::thread::sleep(std::time::Duration::from_millis(100));
-                }
-                0u32
+            let probe = tokio::task::spawn_blocking(move || {
+                poll_exec_pid(
+                    || std::fs::read_to_string(&exec_path_clone).ok(),
+                    EXEC_PID_POLL_ATTEMPTS,
+                    EXEC_PID_POLL_INTERVAL,
+                )
             })
             .await
-            .unwrap_or(0);
+            .unwrap_or(ExecPidProbe::Pending);
+
+            let pid = match probe {
+                ExecPidProbe::Started(pid) => pid,
+                ExecPidProbe::Stopped(code) if code != 0 => {
+                    return Err(ttrpc::Error::RpcStatus(ttrpc::get_status(
+                        ttrpc::Code::INTERNAL,
+                        format!(
+                            "exec {} exited with code {} before it started",
+                            req.exec_id, code
+                        ),
+                    )));
+                }
+                ExecPidProbe::Stopped(_) => 0,
+                ExecPidProbe::Absent => {
+                    return Err(ttrpc::Error::RpcStatus(ttrpc::get_status(
+                        ttrpc::Code::NOT_FOUND,
+                        format!("exec state {} missing or unreadable", exec_path),
+                    )));
+                }
+                ExecPidProbe::Pending => {
+                    warn!(
+                        "start() exec {} has no PID after {}ms, reporting pid 0",
+                        req.exec_id,
+                        EXEC_PID_POLL_ATTEMPTS as u128 * EXEC_PID_POLL_INTERVAL.as_millis()
+                    );
+                    0
+                }
+            };
 
             info!(
                 "start() exec succeeded - exec_id={}, pid={}",
@@ -744,14 +1686,11 @@ impl Task for ReaperTask {
             });
         }
 
+        // Started (or starting): no longer a candidate for REAPER_CREATE_TTL
+        self.awaiting_start.lock().unwrap().remove(&req.id);
+
         // Check if this is a sandbox container
-        let is_sandbox = {
-            let state = self.sandbox_state.lock().unwrap();
-            state
-                .get(&req.id)
-                .map(|info| info.is_sandbox)
-                .unwrap_or(false)
-        };
+        let is_sandbox = self.is_sandbox(&req.id);
 
         if is_sandbox {
             info!("start() - SANDBOX container, returning fake PID");
@@ -803,7 +1742,7 @@ impl Task for ReaperTask {
             std::process::Command::new(&runtime_path_state)
                 .arg("state")
                 .arg(&container_id_state)
-                .output()
+                .guarded_output_timeout(runtime_timeout())
         })
         .await
         .map_err(|e| {
@@ -859,23 +1798,24 @@ impl Task for ReaperTask {
             });
         }
 
-        // Clean up stdin holder if still present
-        self.stdin_holders.lock().unwrap().remove(&req.id);
+        // Fallback release if close_io() never fired
+        if self
+            .stdin_holders
+            .release_after(&req.id, stdin_holder_linger())
+            .await
+        {
+            info!("delete() - released stdin FIFO holder for {}", req.id);
+        }
 
         // Check if this is a sandbox container
-        let is_sandbox = {
-            let mut state = self.sandbox_state.lock().unwrap();
-            let result = state
-                .get(&req.id)
-                .map(|info| info.is_sandbox)
-                .unwrap_or(false);
-            // Remove from state
-            state.remove(&req.id);
-            result
-        };
+        let is_sandbox = self.is_sandbox(&req.id);
+        self.sandbox_state.lock().unwrap().remove(&req.id);
+        self.awaiting_start.lock().unwrap().remove(&req.id);
 
         if is_sandbox {
             info!("delete() - SANDBOX container, cleaning up fake state");
+            self.run_runtime_for_sandbox(vec!["delete".into(), req.id.clone()])
+                .await;
             return Ok(api::DeleteResponse {
                 pid: 1,
                 exit_status: 0,
@@ -893,7 +1833,7 @@ impl Task for ReaperTask {
             std::process::Command::new(&runtime_path)
                 .arg("delete")
                 .arg(&container_id)
-                .output()
+                .guarded_output_timeout(runtime_timeout())
         })
         .await
         .map_err(|e| {
@@ -917,7 +1857,7 @@ impl Task for ReaperTask {
         }
 
         // Reap any zombie monitoring daemons from this or previous containers.
-        reap_orphaned_children();
+        reap_orphaned_children_if_idle();
 
         let mut resp = api::DeleteResponse::new();
         resp.set_pid(0);
@@ -933,10 +1873,7 @@ impl Task for ReaperTask {
         );
 
         // Check if this is a sandbox container
-        let sandbox_info = {
-            let state = self.sandbox_state.lock().unwrap();
-            state.get(&req.id).cloned()
-        };
+        let sandbox_info = self.sandbox_info(&req.id);
 
         if let Some(info) = sandbox_info {
             if info.is_sandbox {
@@ -995,7 +1932,7 @@ impl Task for ReaperTask {
                 .arg("kill")
                 .arg(&container_id)
                 .arg(signal.to_string())
-                .output()
+                .guarded_output()
         });
 
         let output =
@@ -1050,10 +1987,7 @@ impl Task for ReaperTask {
         );
 
         // Check if this is a sandbox container
-        let sandbox_info = {
-            let state = self.sandbox_state.lock().unwrap();
-            state.get(&req.id).cloned()
-        };
+        let sandbox_info = self.sandbox_info(&req.id);
 
         if let Some(ref info) = sandbox_info {
             if info.is_sandbox {
@@ -1085,6 +2019,7 @@ impl Task for ReaperTask {
             let exec_path = build_exec_state_path(&req.id, &req.exec_id)?;
             let container_id = req.id.clone();
             let exec_id_clone = req.exec_id.clone();
+            let poll = WaitPoll::from_env(DEFAULT_EXEC_WAIT_POLL);
 
             let (exit_code, pid) = tokio::task::spawn_blocking(move || {
                 let timeout = std::time::Duration::from_secs(3600); // 1 hour for interactive
@@ -1103,7 +2038,7 @@ impl Task for ReaperTask {
                             }
                         }
                     }
-                    std::thread::sleep(std::time::Duration::from_millis(200));
+                    std::thread::sleep(poll.interval(start.elapsed()));
                 }
             })
             .await
@@ -1132,11 +2067,17 @@ impl Task for ReaperTask {
         // The monitoring daemon forked by reaper-runtime will update the state when the process exits
         let container_id = req.id.clone();
         let runtime_path = self.runtime_path.clone();
+        let poll = WaitPoll::from_env(DEFAULT_WAIT_POLL);
 
-        // Return both exit_code and pid with a timeout to prevent hanging during pod cleanup
-        let (exit_code, pid) = tokio::task::spawn_blocking(move || {
+        // Return exit_code, pid and OOM kill with a timeout to prevent hanging during pod cleanup
+        let (exit_code, pid, oom) = tokio::task::spawn_blocking(move || {
             let start = std::time::Instant::now();
             let timeout = std::time::Duration::from_secs(3600); // 1 hour - interactive containers may run a long time
+            // Set once the state names a dedicated cgroup; None means state polling only
+            #[cfg(target_os = "linux")]
+            let mut cgroup_watch: Option<CgroupWatch> = None;
+            #[cfg(target_os = "linux")]
+            let mut cgroup_checked = false;
 
             loop {
                 // Check timeout
@@ -1145,13 +2086,13 @@ impl Task for ReaperTask {
                         "wait() polling timeout after 1h for container {}",
                         container_id
                     );
-                    return (1, 0); // Return error exit code on timeout
+                    return (1, 0, false); // Return error exit code on timeout
                 }
 
                 let output = std::process::Command::new(&runtime_path)
                     .arg("state")
                     .arg(&container_id)
-                    .output();
+                    .guarded_output_timeout(runtime_timeout());
 
                 if let Ok(output) = output {
                     if output.status.success() {
@@ -1161,24 +2102,64 @@ impl Task for ReaperTask {
                             if state["status"].as_str() == Some("stopped") {
                                 let code = state["exit_code"].as_i64().unwrap_or(0) as i32;
                                 let pid = state["pid"].as_u64().unwrap_or(0) as u32;
+                                let oom = state["oom"].as_bool().unwrap_or(false);
                                 info!(
-                                    "wait() - container {} stopped with exit_code={}, pid={}",
-                                    container_id, code, pid
+                                    "wait() - container {} stopped with exit_code={}, pid={}, oom={}",
+                                    container_id, code, pid, oom
                                 );
+                                if state["failure_origin"].as_str() == Some("runtime") {
+                                    warn!(
+                                        "wait() - container {} failed during reaper setup, not in the workload (see runtime log)",
+                                        container_id
+                                    );
+                                }
                                 // Give the monitoring daemon a moment to exit after
                                 // writing "stopped".
                                 std::thread::sleep(std::time::Duration::from_millis(50));
-                                return (code, pid);
+                                return (code, pid, oom);
+                            }
+                            #[cfg(target_os = "linux")]
+                            if !cgroup_checked {
+                                cgroup_checked = true;
+                                cgroup_watch = state["cgroup_path"].as_str().and_then(|dir| {
+                                    CgroupWatch::open(Path::new(dir))
+                                        .inspect_err(|e| {
+                                            warn!(
+                                                "wait() - can't watch cgroup {} ({}), polling state",
+                                                dir, e
+                                            )
+                                        })
+                                        .ok()
+                                });
                             }
                         }
                     }
                 }
-                std::thread::sleep(std::time::Duration::from_millis(100));
+
+                // While the cgroup holds processes, block on its events (re-checking
+                // the state now and then); otherwise poll the state.
+                #[cfg(target_os = "linux")]
+                if let Some(watch) = cgroup_watch.as_mut().filter(|w| w.is_populated()) {
+                    match watch.wait_change(std::time::Duration::from_secs(5)) {
+                        Ok(true) => info!("wait() - cgroup of container {} emptied", container_id),
+                        Ok(false) => {}
+                        Err(e) => {
+                            warn!("wait() - cgroup watch failed ({}), polling state", e);
+                            cgroup_watch = None;
+                        }
+                    }
+                    continue;
+                }
+                std::thread::sleep(poll.interval(start.elapsed()));
             }
         })
         .await
-        .unwrap_or((1, 0));
+        .unwrap_or((1, 0, false));
 
+        // OOM first, so the exit that follows is reported as OOMKilled
+        if oom {
+            self.publish_oom_event(&req.id).await;
+        }
         // Publish TaskExit event to notify containerd
         self.publish_exit_event(&req.id, "", pid, exit_code as u32)
             .await;
@@ -1239,13 +2220,7 @@ impl Task for ReaperTask {
         }
 
         // Check if this is a sandbox container
-        let is_sandbox = {
-            let state = self.sandbox_state.lock().unwrap();
-            state
-                .get(&req.id)
-                .map(|info| info.is_sandbox)
-                .unwrap_or(false)
-        };
+        let is_sandbox = self.is_sandbox(&req.id);
 
         if is_sandbox {
             info!("state() - SANDBOX container, returning running state");
@@ -1268,7 +2243,7 @@ impl Task for ReaperTask {
             std::process::Command::new(&runtime_path)
                 .arg("state")
                 .arg(&container_id)
-                .output()
+                .guarded_output_timeout(runtime_timeout())
         })
         .await
         .map_err(|e| {
@@ -1344,7 +2319,7 @@ impl Task for ReaperTask {
             std::process::Command::new(&runtime_path)
                 .arg("state")
                 .arg(&container_id)
-                .output()
+                .guarded_output_timeout(runtime_timeout())
         })
         .await
         .map_err(|e| {
@@ -1423,6 +2398,7 @@ impl Task for ReaperTask {
                 .collect()
         });
         let cwd: Option<String> = process["cwd"].as_str().map(String::from);
+        let rlimits = process["rlimits"].as_array().cloned().unwrap_or_default();
 
         if args.is_empty() {
             return Err(ttrpc::Error::RpcStatus(ttrpc::get_status(
@@ -1441,6 +2417,9 @@ impl Task for ReaperTask {
             "args": args,
             "env": env,
             "cwd": cwd,
+            "rlimits": rlimits,
+            "capabilities": process["capabilities"],
+            "no_new_privileges": process["noNewPrivileges"].as_bool().unwrap_or(false),
             "terminal": req.terminal,
             "stdin": if req.stdin.is_empty() { serde_json::Value::Null } else { serde_json::Value::String(req.stdin.clone()) },
             "stdout": if req.stdout.is_empty() { serde_json::Value::Null } else { serde_json::Value::String(req.stdout.clone()) },
@@ -1448,15 +2427,7 @@ impl Task for ReaperTask {
         });
 
         let exec_path = build_exec_state_path(&req.id, &req.exec_id)?;
-
-        std::fs::write(&exec_path, serde_json::to_vec_pretty(&exec_state).unwrap()).map_err(
-            |e| {
-                ttrpc::Error::RpcStatus(ttrpc::get_status(
-                    ttrpc::Code::INTERNAL,
-                    format!("write exec state: {}", e),
-                ))
-            },
-        )?;
+        write_exec_state_exclusive(&exec_path, &exec_state)?;
 
         info!("exec() succeeded - wrote exec state to {}", exec_path);
         Ok(api::Empty::new())
@@ -1469,10 +2440,20 @@ impl Task for ReaperTask {
     ) -> TtrpcResult<api::StatsResponse> {
         info!("stats() called - container_id={}", req.id);
 
-        // For now, return basic stats - in a real implementation we'd collect actual metrics
-        let resp = api::StatsResponse::new();
-        // TODO: Implement actual resource monitoring (CPU, memory, etc.)
-        // For Milestone 4, we provide basic placeholder stats
+        let mut resp = api::StatsResponse::new();
+        if validate_id(&req.id).is_err() {
+            return Ok(resp);
+        }
+        let recorded = persisted_cgroup_path(&req.id);
+        let dir = stats::cgroup_dir(&req.id, recorded.as_deref());
+        // No cgroup (e.g. a container without limits): nothing to report
+        let Some(usage) = stats::CgroupUsage::read(&dir) else {
+            return Ok(resp);
+        };
+        match containerd_shim::util::convert_to_any(Box::new(usage.to_metrics())) {
+            Ok(any) => resp.stats = Some(any).into(),
+            Err(e) => warn!("stats() - can't encode metrics for {}: {}", req.id, e),
+        }
 
         Ok(resp)
     }
@@ -1490,6 +2471,10 @@ impl Task for ReaperTask {
         if req.width == 0 && req.height == 0 {
             return Ok(api::Empty::new());
         }
+        validate_id(&req.id)?;
+        if !req.exec_id.is_empty() {
+            validate_id(&req.exec_id)?;
+        }
 
         // Write resize dimensions to a file the runtime daemon polls.
         // For exec processes, use exec-specific resize file.
@@ -1526,7 +2511,7 @@ impl Task for ReaperTask {
         );
         // Drop our stdin FIFO read-end so containerd can detect the closed pipe
         // and stop writing. Without this, the held fd prevents clean teardown.
-        if req.stdin && self.stdin_holders.lock().unwrap().remove(&req.id).is_some() {
+        if req.stdin && self.stdin_holders.release(&req.id) {
             info!("close_io() - released stdin FIFO holder for {}", req.id);
         }
         Ok(api::Empty::new())
@@ -1540,13 +2525,7 @@ impl Task for ReaperTask {
         info!("connect() called - container_id={}", req.id);
 
         // Check if this is a sandbox container
-        let is_sandbox = {
-            let state = self.sandbox_state.lock().unwrap();
-            state
-                .get(&req.id)
-                .map(|info| info.is_sandbox)
-                .unwrap_or(false)
-        };
+        let is_sandbox = self.is_sandbox(&req.id);
 
         if is_sandbox {
             info!("connect() - SANDBOX container, returning fake PID");
@@ -1559,13 +2538,32 @@ impl Task for ReaperTask {
         // Real workload - get PID from reaper-runtime
         info!("connect() - WORKLOAD container, querying reaper-runtime");
 
+        // A container that was just started may briefly be reported running
+        // before its PID is readable; retry rather than hand back PID 0.
         let runtime_path = self.runtime_path.clone();
         let container_id = req.id.clone();
-        let state_output = tokio::task::spawn_blocking(move || {
-            std::process::Command::new(&runtime_path)
-                .arg("state")
-                .arg(&container_id)
-                .output()
+        let state = tokio::task::spawn_blocking(move || {
+            let read_state = || -> TtrpcResult<serde_json::Value> {
+                let state_output = std::process::Command::new(&runtime_path)
+                    .arg("state")
+                    .arg(&container_id)
+                    .guarded_output_timeout(runtime_timeout())
+                    .map_err(|e| {
+                        tracing::error!("Failed to execute reaper-runtime state: {}", e);
+                        ttrpc::Error::RpcStatus(ttrpc::get_status(
+                            ttrpc::Code::INTERNAL,
+                            format!("Failed to execute reaper-runtime state: {}", e),
+                        ))
+                    })?;
+                serde_json::from_slice(&state_output.stdout).map_err(|e| {
+                    tracing::error!("Failed to parse state output: {}", e);
+                    ttrpc::Error::RpcStatus(ttrpc::get_status(
+                        ttrpc::Code::INTERNAL,
+                        format!("Failed to parse state output: {}", e),
+                    ))
+                })
+            };
+            poll_running_pid(read_state, CONNECT_PID_ATTEMPTS, CONNECT_PID_INTERVAL)
         })
         .await
         .map_err(|e| {
@@ -1574,23 +2572,7 @@ impl Task for ReaperTask {
                 ttrpc::Code::INTERNAL,
                 format!("Failed to spawn reaper-runtime task: {}", e),
             ))
-        })?
-        .map_err(|e| {
-            tracing::error!("Failed to execute reaper-runtime state: {}", e);
-            ttrpc::Error::RpcStatus(ttrpc::get_status(
-                ttrpc::Code::INTERNAL,
-                format!("Failed to execute reaper-runtime state: {}", e),
-            ))
-        })?;
-
-        let state: serde_json::Value =
-            serde_json::from_slice(&state_output.stdout).map_err(|e| {
-                tracing::error!("Failed to parse state output: {}", e);
-                ttrpc::Error::RpcStatus(ttrpc::get_status(
-                    ttrpc::Code::INTERNAL,
-                    format!("Failed to parse state output: {}", e),
-                ))
-            })?;
+        })??;
 
         let pid = state["pid"].as_u64().unwrap_or(0) as u32;
         let mut resp = api::ConnectResponse::new();
@@ -1655,6 +2637,7 @@ async fn main() {
         if let Ok(log_file) = std::fs::OpenOptions::new()
             .create(true)
             .append(true)
+            .mode(config::log_file_mode())
             .open(&log_path)
         {
             tracing_subscriber::fmt()
@@ -1688,16 +2671,16 @@ async fn main() {
 
     set_child_subreaper();
 
-    // NOTE: Do NOT run a background reaper loop here. A background task calling
-    // waitpid(-1, WNOHANG) races with std::process::Command::output() which
-    // calls waitpid(child_pid). If the background reaper steals the zombie
-    // before output() can wait on it, output() gets ECHILD (os error 10).
-    //
-    // Instead, orphaned monitoring daemons (reparented via PR_SET_CHILD_SUBREAPER)
-    // are reaped at well-defined points where no concurrent waitpid is active:
+    // NOTE: A background task calling waitpid(-1, WNOHANG) races with
+    // std::process::Command::output() which calls waitpid(child_pid). If the
+    // reaper steals the zombie before output() can wait on it, output() gets
+    // ECHILD (os error 10). The SIGCHLD reaper therefore only runs while no
+    // guarded_output() call is in flight (see CHILD_WAITS), and orphaned
+    // monitoring daemons (reparented via PR_SET_CHILD_SUBREAPER) are also
+    // reaped at fixed points:
     //   - execute_and_reap_child(): after cmd.output() returns
-    //   - wait() polling loop: every 100-200ms iteration
     //   - delete(): after runtime delete completes
+    spawn_sigchld_reaper();
 
     info!("Calling containerd_shim::run()...");
 
@@ -1880,6 +2863,181 @@ mod tests {
         assert!(!is_sandbox_container(bundle.path().to_str().unwrap()));
     }
 
+    // --- persisted sandbox flag tests ---
+
+    fn write_runtime_state(root: &Path, id: &str, state: serde_json::Value) {
+        let dir = root.join(id);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("state.json"), state.to_string()).unwrap();
+    }
+
+    fn pause_bundle() -> TempDir {
+        let bundle = TempDir::new().unwrap();
+        std::fs::write(
+            bundle.path().join("config.json"),
+            serde_json::json!({"process": {"args": ["/pause"]}}).to_string(),
+        )
+        .unwrap();
+        bundle
+    }
+
+    #[test]
+    #[serial]
+    fn test_persisted_sandbox_flag() {
+        let root = TempDir::new().unwrap();
+        std::env::set_var("REAPER_RUNTIME_ROOT", root.path());
+        write_runtime_state(
+            root.path(),
+            "sb",
+            serde_json::json!({"id": "sb", "status": "created", "sandbox": true}),
+        );
+        write_runtime_state(
+            root.path(),
+            "legacy",
+            serde_json::json!({"id": "legacy", "status": "running"}),
+        );
+
+        assert_eq!(persisted_sandbox_flag("sb"), Some(true));
+        assert_eq!(persisted_sandbox_flag("legacy"), Some(false));
+        assert_eq!(persisted_sandbox_flag("missing"), None);
+        assert_eq!(persisted_sandbox_flag("../escape"), None);
+        std::env::remove_var("REAPER_RUNTIME_ROOT");
+    }
+
+    #[test]
+    #[serial]
+    fn test_resolve_sandbox_prefers_persisted_flag() {
+        let root = TempDir::new().unwrap();
+        std::env::set_var("REAPER_RUNTIME_ROOT", root.path());
+
+        // Bundle gone: the persisted flag still answers
+        write_runtime_state(
+            root.path(),
+            "sb",
+            serde_json::json!({"id": "sb", "status": "created", "sandbox": true}),
+        );
+        assert!(resolve_sandbox("sb", "/nonexistent/bundle"));
+
+        // Persisted flag beats what config.json would say
+        let bundle = pause_bundle();
+        write_runtime_state(
+            root.path(),
+            "wl",
+            serde_json::json!({"id": "wl", "status": "created", "sandbox": false}),
+        );
+        assert!(!resolve_sandbox("wl", bundle.path().to_str().unwrap()));
+
+        // No state yet: fall back to config.json
+        assert!(resolve_sandbox("new", bundle.path().to_str().unwrap()));
+        std::env::remove_var("REAPER_RUNTIME_ROOT");
+    }
+
+    #[test]
+    #[serial]
+    fn test_check_duplicate_create() {
+        let root = TempDir::new().unwrap();
+        std::env::set_var("REAPER_RUNTIME_ROOT", root.path());
+        write_runtime_state(
+            root.path(),
+            "dup",
+            serde_json::json!({"id": "dup", "bundle": "/b/dup", "status": "running", "pid": 4242}),
+        );
+
+        // Idempotent: the existing state comes back unchanged
+        let existing = check_duplicate_create("dup", "/b/dup", true)
+            .unwrap()
+            .unwrap();
+        assert_eq!(existing["pid"], 4242);
+        assert_eq!(existing["status"], "running");
+
+        // Strict: the duplicate is rejected
+        expect_rpc_code(
+            check_duplicate_create("dup", "/b/dup", false).map(|_| ()),
+            ttrpc::Code::ALREADY_EXISTS,
+        );
+        // A different bundle is a different container, even when idempotent
+        expect_rpc_code(
+            check_duplicate_create("dup", "/b/other", true).map(|_| ()),
+            ttrpc::Code::ALREADY_EXISTS,
+        );
+
+        // A corrupt state file is an error, not a container with pid 0
+        let corrupt = root.path().join("corrupt");
+        std::fs::create_dir_all(&corrupt).unwrap();
+        std::fs::write(corrupt.join("state.json"), "{not json").unwrap();
+        expect_rpc_code(
+            check_duplicate_create("corrupt", "/b/corrupt", true).map(|_| ()),
+            ttrpc::Code::INTERNAL,
+        );
+
+        // New ids pass in either mode
+        assert!(check_duplicate_create("new", "/b/new", true)
+            .unwrap()
+            .is_none());
+        assert!(check_duplicate_create("new", "/b/new", false)
+            .unwrap()
+            .is_none());
+        std::env::remove_var("REAPER_RUNTIME_ROOT");
+    }
+
+    #[test]
+    #[serial]
+    fn test_create_idempotent_env() {
+        std::env::remove_var("REAPER_CREATE_IDEMPOTENT");
+        assert!(create_idempotent());
+        std::env::set_var("REAPER_CREATE_IDEMPOTENT", "false");
+        assert!(!create_idempotent());
+        std::env::set_var("REAPER_CREATE_IDEMPOTENT", "0");
+        assert!(!create_idempotent());
+        std::env::set_var("REAPER_CREATE_IDEMPOTENT", "1");
+        assert!(create_idempotent());
+        std::env::remove_var("REAPER_CREATE_IDEMPOTENT");
+    }
+
+    // --- cgroup.events tests ---
+
+    #[test]
+    fn test_parse_cgroup_populated() {
+        assert_eq!(
+            parse_cgroup_populated("populated 1\nfrozen 0\n"),
+            Some(true)
+        );
+        assert_eq!(
+            parse_cgroup_populated("frozen 0\npopulated 0\n"),
+            Some(false)
+        );
+        assert_eq!(parse_cgroup_populated("frozen 0\n"), None);
+        assert_eq!(parse_cgroup_populated("populated x\n"), None);
+    }
+
+    #[test]
+    fn test_cgroup_emptied_transition() {
+        assert!(cgroup_emptied(Some(true), Some(false)));
+        assert!(!cgroup_emptied(Some(true), Some(true)));
+        // Never seen populated: not yet started, not an exit
+        assert!(!cgroup_emptied(None, Some(false)));
+        assert!(!cgroup_emptied(Some(false), Some(false)));
+        assert!(!cgroup_emptied(Some(false), Some(true)));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_cgroup_watch_detects_emptied() {
+        let dir = TempDir::new().unwrap();
+        let events = dir.path().join("cgroup.events");
+        std::fs::write(&events, "populated 1\nfrozen 0\n").unwrap();
+
+        let mut watch = CgroupWatch::open(dir.path()).unwrap();
+        assert!(watch.is_populated());
+        let timeout = std::time::Duration::from_millis(10);
+        assert!(!watch.wait_change(timeout).unwrap());
+
+        std::fs::write(&events, "populated 0\nfrozen 0\n").unwrap();
+        assert!(watch.wait_change(timeout).unwrap());
+        assert!(!watch.is_populated());
+        assert!(CgroupWatch::open(&dir.path().join("missing")).is_err());
+    }
+
     // --- parse_container_status tests ---
 
     #[test]
@@ -1888,6 +3046,13 @@ mod tests {
         assert_eq!(status, ::protobuf::EnumOrUnknown::new(api::Status::CREATED));
     }
 
+    #[test]
+    fn test_parse_container_status_starting() {
+        // containerd has no "starting"; the task is still created until it has a PID
+        let status = parse_container_status("starting");
+        assert_eq!(status, ::protobuf::EnumOrUnknown::new(api::Status::CREATED));
+    }
+
     #[test]
     fn test_parse_container_status_running() {
         let status = parse_container_status("running");
@@ -1912,6 +3077,45 @@ mod tests {
         assert_eq!(status, ::protobuf::EnumOrUnknown::new(api::Status::UNKNOWN));
     }
 
+    #[test]
+    fn test_parse_container_status_paused() {
+        let status = parse_container_status("paused");
+        assert_eq!(status, ::protobuf::EnumOrUnknown::new(api::Status::PAUSED));
+    }
+
+    #[test]
+    fn test_parse_container_status_pausing() {
+        let status = parse_container_status("pausing");
+        assert_eq!(status, ::protobuf::EnumOrUnknown::new(api::Status::PAUSING));
+    }
+
+    #[test]
+    fn test_parse_container_status_matrix_matches_runtime_strings() {
+        // Every status string reaper-runtime writes (state::STATUS_*) must map
+        // to the containerd enum of the same name, and vice versa.
+        let matrix = [
+            ("created", api::Status::CREATED),
+            ("running", api::Status::RUNNING),
+            ("stopped", api::Status::STOPPED),
+            ("paused", api::Status::PAUSED),
+            ("pausing", api::Status::PAUSING),
+        ];
+        for (status_str, expected) in matrix {
+            assert_eq!(
+                parse_container_status(status_str),
+                ::protobuf::EnumOrUnknown::new(expected),
+                "status {:?}",
+                status_str
+            );
+            assert_eq!(format!("{:?}", expected).to_ascii_lowercase(), status_str);
+        }
+        // Case matters: the runtime only ever writes lowercase
+        assert_eq!(
+            parse_container_status("RUNNING"),
+            ::protobuf::EnumOrUnknown::new(api::Status::UNKNOWN)
+        );
+    }
+
     // --- build_exec_state_path tests ---
 
     #[test]
@@ -1932,6 +3136,579 @@ mod tests {
         std::env::remove_var("REAPER_RUNTIME_ROOT");
     }
 
+    #[test]
+    #[serial]
+    fn test_validate_id() {
+        std::env::remove_var("REAPER_MAX_ID_LENGTH");
+        assert!(validate_id(&"0123456789abcdef".repeat(4)).is_ok());
+        assert!(validate_id("3f2b8c1e-9d4a-4b6f-8e2a-1c5d7f9b0a3e").is_ok());
+        assert!(validate_id("a.b_c-d").is_ok());
+        for bad in ["", ".", "..", "../etc", "a/b", "foo..bar", "a b"] {
+            assert!(validate_id(bad).is_err(), "{:?} should be rejected", bad);
+        }
+        assert!(validate_id(&"a".repeat(257)).is_err());
+        assert!(build_exec_state_path("ctr", "../../etc/passwd").is_err());
+
+        std::env::set_var("REAPER_MAX_ID_LENGTH", "8");
+        assert!(validate_id("abcdefgh").is_ok());
+        assert!(validate_id("abcdefghi").is_err());
+        std::env::remove_var("REAPER_MAX_ID_LENGTH");
+    }
+
+    // --- SIGCHLD reaper coordination tests ---
+
+    #[test]
+    #[serial]
+    fn test_reap_suppressed_while_output_in_flight() {
+        let guard = ChildWaitGuard::enter();
+        assert!(!reap_orphaned_children_if_idle());
+
+        // Concurrent output() calls share the lock; reaping stays off until all finish
+        let second = ChildWaitGuard::enter();
+        drop(guard);
+        assert!(!reap_orphaned_children_if_idle());
+        drop(second);
+
+        assert!(reap_orphaned_children_if_idle());
+    }
+
+    #[test]
+    #[serial]
+    fn test_guarded_output_not_raced_by_reaper() {
+        let stop = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let reaper = {
+            let stop = stop.clone();
+            std::thread::spawn(move || {
+                while !stop.load(std::sync::atomic::Ordering::Relaxed) {
+                    reap_orphaned_children_if_idle();
+                }
+            })
+        };
+        for _ in 0..50 {
+            let output = std::process::Command::new("true")
+                .guarded_output()
+                .expect("output() must not fail with ECHILD");
+            assert!(output.status.success());
+        }
+        stop.store(true, std::sync::atomic::Ordering::Relaxed);
+        reaper.join().unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn test_guarded_output_timeout() {
+        let timeout = Some(std::time::Duration::from_millis(200));
+        let started = std::time::Instant::now();
+        let err = std::process::Command::new("sleep")
+            .arg("30")
+            .guarded_output_timeout(timeout)
+            .expect_err("a slow child must time out");
+        assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+
+        let output = std::process::Command::new("sh")
+            .args(["-c", "echo out; echo err >&2; exit 3"])
+            .guarded_output_timeout(timeout)
+            .unwrap();
+        assert_eq!(output.status.code(), Some(3));
+        assert_eq!(output.stdout, b"out\n");
+        assert_eq!(output.stderr, b"err\n");
+    }
+
+    #[test]
+    #[serial]
+    fn test_runtime_timeout() {
+        std::env::remove_var("REAPER_RUNTIME_TIMEOUT_SECS");
+        assert_eq!(
+            runtime_timeout(),
+            Some(std::time::Duration::from_secs(DEFAULT_RUNTIME_TIMEOUT_SECS))
+        );
+        std::env::set_var("REAPER_RUNTIME_TIMEOUT_SECS", "5");
+        assert_eq!(runtime_timeout(), Some(std::time::Duration::from_secs(5)));
+        std::env::set_var("REAPER_RUNTIME_TIMEOUT_SECS", "0");
+        assert_eq!(runtime_timeout(), None);
+        std::env::remove_var("REAPER_RUNTIME_TIMEOUT_SECS");
+    }
+
+    // --- container limit tests ---
+
+    #[test]
+    #[serial]
+    fn test_container_limit() {
+        let root = TempDir::new().unwrap();
+        std::env::set_var("REAPER_RUNTIME_ROOT", root.path());
+        std::env::set_var("REAPER_MAX_CONTAINERS", "2");
+        write_runtime_state(
+            root.path(),
+            "sb",
+            serde_json::json!({"id": "sb", "status": "created", "sandbox": true}),
+        );
+        write_runtime_state(
+            root.path(),
+            "done",
+            serde_json::json!({"id": "done", "status": "stopped"}),
+        );
+        write_runtime_state(
+            root.path(),
+            "a",
+            serde_json::json!({"id": "a", "status": "running"}),
+        );
+        assert_eq!(active_workload_count(root.path(), "new"), 1);
+        assert!(check_container_limit("new").is_ok());
+
+        write_runtime_state(
+            root.path(),
+            "b",
+            serde_json::json!({"id": "b", "status": "created"}),
+        );
+        match check_container_limit("new") {
+            Err(ttrpc::Error::RpcStatus(status)) => {
+                assert_eq!(status.code(), ttrpc::Code::RESOURCE_EXHAUSTED);
+                assert!(status.message.contains("REAPER_MAX_CONTAINERS=2"));
+            }
+            other => panic!("expected RESOURCE_EXHAUSTED, got {:?}", other),
+        }
+        // A retried create for an already-counted id is not refused
+        assert!(check_container_limit("b").is_ok());
+
+        std::env::set_var("REAPER_MAX_CONTAINERS", "0");
+        assert!(check_container_limit("new").is_ok());
+        std::env::remove_var("REAPER_MAX_CONTAINERS");
+        assert!(check_container_limit("new").is_ok());
+        std::env::remove_var("REAPER_RUNTIME_ROOT");
+    }
+
+    // --- stdin holder tests ---
+
+    fn holder_file(dir: &TempDir) -> std::fs::File {
+        std::fs::File::create(dir.path().join("stdin")).unwrap()
+    }
+
+    #[test]
+    #[serial]
+    fn test_abandoned_created_selects_aged_entries() {
+        let root = TempDir::new().unwrap();
+        for (id, state) in [
+            ("old", serde_json::json!({"id": "old", "status": "created"})),
+            (
+                "recent",
+                serde_json::json!({"id": "recent", "status": "created"}),
+            ),
+            (
+                "running",
+                serde_json::json!({"id": "running", "status": "running", "pid": 42}),
+            ),
+            (
+                "sb",
+                serde_json::json!({"id": "sb", "status": "created", "sandbox": true}),
+            ),
+        ] {
+            write_runtime_state(root.path(), id, state);
+        }
+        let hour = std::time::Duration::from_secs(3600);
+        let now = std::time::SystemTime::now();
+        for id in ["old", "running", "sb"] {
+            std::fs::File::options()
+                .write(true)
+                .open(root.path().join(id).join("state.json"))
+                .unwrap()
+                .set_modified(now - 2 * hour)
+                .unwrap();
+        }
+
+        let ids = ["old", "recent", "running", "sb", "missing"];
+        assert_eq!(abandoned_created(root.path(), ids, hour, now), ["old"]);
+        // Nothing has been waiting for 3 hours
+        assert!(abandoned_created(root.path(), ids, 3 * hour, now).is_empty());
+
+        std::env::remove_var("REAPER_CREATE_TTL");
+        assert_eq!(create_ttl(), None);
+        std::env::set_var("REAPER_CREATE_TTL", "0");
+        assert_eq!(create_ttl(), None);
+        std::env::set_var("REAPER_CREATE_TTL", "300");
+        assert_eq!(create_ttl(), Some(std::time::Duration::from_secs(300)));
+        std::env::remove_var("REAPER_CREATE_TTL");
+    }
+
+    #[test]
+    #[serial]
+    fn test_create_retry_classification() {
+        // The state root not yet writable during node startup
+        assert!(is_transient_create_error(
+            "Error: Permission denied (os error 13)"
+        ));
+        assert!(is_transient_create_error(
+            "Error: Read-only file system (os error 30)"
+        ));
+        assert!(is_transient_create_error(
+            "Error: Resource temporarily unavailable (os error 11)"
+        ));
+        // Bad input never succeeds on retry
+        assert!(!is_transient_create_error(
+            "Error: parsing config.json\n\nCaused by:\n    expected value at line 1 column 1"
+        ));
+        assert!(!is_transient_create_error(
+            "Error: ID contains invalid characters (allowed: a-zA-Z0-9._-)"
+        ));
+        assert!(!is_transient_create_error(
+            "Error: reading /b/config.json\n\nCaused by:\n    No such file or directory (os error 2)"
+        ));
+        assert!(!is_transient_create_error(""));
+
+        std::env::remove_var("REAPER_CREATE_RETRIES");
+        assert_eq!(create_retries(), DEFAULT_CREATE_RETRIES);
+        std::env::set_var("REAPER_CREATE_RETRIES", "0");
+        assert_eq!(create_retries(), 0);
+        std::env::set_var("REAPER_CREATE_RETRIES", "5");
+        assert_eq!(create_retries(), 5);
+        std::env::remove_var("REAPER_CREATE_RETRIES");
+    }
+
+    #[test]
+    #[serial]
+    fn test_wait_poll_from_env_clamps() {
+        std::env::remove_var("REAPER_WAIT_POLL_MS");
+        std::env::remove_var("REAPER_WAIT_POLL_MAX_MS");
+        let poll = WaitPoll::from_env(DEFAULT_EXEC_WAIT_POLL);
+        assert_eq!(poll.initial, DEFAULT_EXEC_WAIT_POLL);
+        assert_eq!(poll.max, DEFAULT_WAIT_POLL_MAX);
+
+        std::env::set_var("REAPER_WAIT_POLL_MS", "1");
+        std::env::set_var("REAPER_WAIT_POLL_MAX_MS", "600000");
+        let poll = WaitPoll::from_env(DEFAULT_WAIT_POLL);
+        assert_eq!(poll.initial, WAIT_POLL_FLOOR);
+        assert_eq!(poll.max, WAIT_POLL_CEILING);
+
+        // A max below the initial interval is raised to it; garbage is ignored
+        std::env::set_var("REAPER_WAIT_POLL_MS", "500");
+        std::env::set_var("REAPER_WAIT_POLL_MAX_MS", "50");
+        let poll = WaitPoll::from_env(DEFAULT_WAIT_POLL);
+        assert_eq!(poll.initial, std::time::Duration::from_millis(500));
+        assert_eq!(poll.max, poll.initial);
+        std::env::set_var("REAPER_WAIT_POLL_MS", "fast");
+        assert_eq!(
+            WaitPoll::from_env(DEFAULT_WAIT_POLL).initial,
+            DEFAULT_WAIT_POLL
+        );
+
+        std::env::remove_var("REAPER_WAIT_POLL_MS");
+        std::env::remove_var("REAPER_WAIT_POLL_MAX_MS");
+    }
+
+    #[test]
+    fn test_wait_poll_interval_backs_off() {
+        use std::time::Duration;
+        let poll = WaitPoll {
+            initial: Duration::from_millis(20),
+            max: Duration::from_secs(2),
+        };
+        assert_eq!(poll.interval(Duration::ZERO), Duration::from_millis(20));
+        assert_eq!(
+            poll.interval(Duration::from_secs(1)),
+            Duration::from_millis(20)
+        );
+        assert_eq!(
+            poll.interval(Duration::from_secs(30)),
+            Duration::from_millis(300)
+        );
+        assert_eq!(
+            poll.interval(Duration::from_secs(3600)),
+            Duration::from_secs(2)
+        );
+
+        // max == initial: fixed interval
+        let fixed = WaitPoll {
+            initial: Duration::from_millis(100),
+            max: Duration::from_millis(100),
+        };
+        assert_eq!(
+            fixed.interval(Duration::from_secs(3600)),
+            Duration::from_millis(100)
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_stdin_holder_linger_from_env() {
+        std::env::remove_var("REAPER_STDIN_HOLDER_LINGER_MS");
+        assert_eq!(
+            stdin_holder_linger(),
+            std::time::Duration::from_millis(DEFAULT_STDIN_HOLDER_LINGER_MS)
+        );
+        std::env::set_var("REAPER_STDIN_HOLDER_LINGER_MS", "0");
+        assert!(stdin_holder_linger().is_zero());
+        std::env::set_var("REAPER_STDIN_HOLDER_LINGER_MS", "bogus");
+        assert_eq!(
+            stdin_holder_linger(),
+            std::time::Duration::from_millis(DEFAULT_STDIN_HOLDER_LINGER_MS)
+        );
+        std::env::remove_var("REAPER_STDIN_HOLDER_LINGER_MS");
+    }
+
+    #[tokio::test]
+    async fn test_stdin_holder_released_by_close_io() {
+        let dir = TempDir::new().unwrap();
+        let holders = StdinHolders::default();
+        holders.hold("ctr", holder_file(&dir));
+        assert!(holders.is_held("ctr"));
+
+        // close_io releases; the later delete finds nothing to do
+        assert!(holders.release("ctr"));
+        assert!(!holders.is_held("ctr"));
+        assert!(
+            !holders
+                .release_after("ctr", std::time::Duration::from_millis(50))
+                .await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stdin_holder_released_by_delete_after_linger() {
+        let dir = TempDir::new().unwrap();
+        let holders = StdinHolders::default();
+        holders.hold("ctr", holder_file(&dir));
+
+        let linger = std::time::Duration::from_millis(50);
+        let started = std::time::Instant::now();
+        assert!(holders.release_after("ctr", linger).await);
+        assert!(started.elapsed() >= linger);
+        assert!(!holders.is_held("ctr"));
+    }
+
+    #[tokio::test]
+    async fn test_stdin_holder_close_io_during_linger_wins() {
+        let dir = TempDir::new().unwrap();
+        let holders = StdinHolders::default();
+        holders.hold("ctr", holder_file(&dir));
+
+        let pending = {
+            let holders = holders.clone();
+            tokio::spawn(async move {
+                holders
+                    .release_after("ctr", std::time::Duration::from_millis(200))
+                    .await
+            })
+        };
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        assert!(holders.release("ctr"));
+        assert!(!pending.await.unwrap());
+    }
+
+    // --- exec PID polling tests ---
+
+    /// Feed `poll_exec_pid` a fixed sequence of reads; the last one repeats.
+    fn poll_sequence(reads: &[Option<&str>], attempts: u32) -> ExecPidProbe {
+        let mut i = 0;
+        poll_exec_pid(
+            || {
+                let r = reads[i.min(reads.len() - 1)].map(str::to_string);
+                i += 1;
+                r
+            },
+            attempts,
+            std::time::Duration::ZERO,
+        )
+    }
+
+    #[test]
+    fn test_probe_exec_pid() {
+        assert_eq!(probe_exec_pid(None), ExecPidProbe::Absent);
+        assert_eq!(probe_exec_pid(Some(r#"{"pid": nu"#)), ExecPidProbe::Absent);
+        assert_eq!(
+            probe_exec_pid(Some(r#"{"status": "created", "pid": null}"#)),
+            ExecPidProbe::Pending
+        );
+        assert_eq!(
+            probe_exec_pid(Some(r#"{"status": "running", "pid": 42}"#)),
+            ExecPidProbe::Started(42)
+        );
+        // A fast exec that already finished still reports its PID
+        assert_eq!(
+            probe_exec_pid(Some(r#"{"status": "stopped", "pid": 42, "exit_code": 0}"#)),
+            ExecPidProbe::Started(42)
+        );
+        assert_eq!(
+            probe_exec_pid(Some(
+                r#"{"status": "stopped", "pid": null, "exit_code": 127}"#
+            )),
+            ExecPidProbe::Stopped(127)
+        );
+    }
+
+    #[test]
+    fn test_poll_exec_pid_null_then_pid() {
+        let probe = poll_sequence(
+            &[
+                Some(r#"{"status": "created", "pid": null}"#),
+                Some(r#"{"status": "created", "pid": null}"#),
+                Some(r#"{"status": "running", "pid": 4242}"#),
+            ],
+            10,
+        );
+        assert_eq!(probe, ExecPidProbe::Started(4242));
+    }
+
+    #[test]
+    fn test_poll_exec_pid_null_then_stopped() {
+        let probe = poll_sequence(
+            &[
+                Some(r#"{"status": "created", "pid": null}"#),
+                Some(r#"{"status": "stopped", "pid": null, "exit_code": 1}"#),
+            ],
+            10,
+        );
+        assert_eq!(probe, ExecPidProbe::Stopped(1));
+    }
+
+    #[test]
+    fn test_poll_exec_pid_half_written_then_pid() {
+        let probe = poll_sequence(
+            &[None, Some(r#"{"status": "runn"#), Some(r#"{"pid": 7}"#)],
+            10,
+        );
+        assert_eq!(probe, ExecPidProbe::Started(7));
+    }
+
+    #[test]
+    fn test_poll_exec_pid_gives_up() {
+        let pending = poll_sequence(&[Some(r#"{"status": "created", "pid": null}"#)], 5);
+        assert_eq!(pending, ExecPidProbe::Pending);
+        let absent = poll_sequence(&[None], 5);
+        assert_eq!(absent, ExecPidProbe::Absent);
+    }
+
+    #[test]
+    fn test_poll_running_pid_retries_until_pid() {
+        let states = [
+            serde_json::json!({"status": "running"}),
+            serde_json::json!({"status": "running", "pid": 0}),
+            serde_json::json!({"status": "running", "pid": 4242}),
+        ];
+        let mut reads = 0;
+        let state = poll_running_pid(
+            || {
+                reads += 1;
+                Ok(states[reads - 1].clone())
+            },
+            10,
+            std::time::Duration::ZERO,
+        )
+        .unwrap();
+        assert_eq!(state["pid"], 4242);
+        assert_eq!(reads, 3);
+    }
+
+    #[test]
+    fn test_poll_running_pid_returns_other_states_immediately() {
+        let mut reads = 0;
+        let state = poll_running_pid(
+            || {
+                reads += 1;
+                Ok(serde_json::json!({"status": "created"}))
+            },
+            10,
+            std::time::Duration::ZERO,
+        )
+        .unwrap();
+        assert_eq!(state["status"], "created");
+        assert_eq!(reads, 1);
+
+        // Gives up after `attempts` and returns the last read
+        let mut reads = 0;
+        let state = poll_running_pid(
+            || {
+                reads += 1;
+                Ok(serde_json::json!({"status": "running"}))
+            },
+            3,
+            std::time::Duration::ZERO,
+        )
+        .unwrap();
+        assert!(running_without_pid(&state));
+        assert_eq!(reads, 3);
+    }
+
+    // --- write_exec_state_exclusive tests ---
+
+    fn exec_state_with_status(status: &str) -> serde_json::Value {
+        serde_json::json!({
+            "container_id": "ctr",
+            "exec_id": "e1",
+            "status": status,
+            "args": ["/bin/sh"],
+        })
+    }
+
+    fn expect_rpc_code(result: TtrpcResult<()>, code: ttrpc::Code) {
+        match result {
+            Err(ttrpc::Error::RpcStatus(status)) => assert_eq!(status.code(), code),
+            other => panic!("expected {:?}, got {:?}", code, other),
+        }
+    }
+
+    #[test]
+    fn test_write_exec_state_exclusive_rejects_live_duplicate() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("exec-e1.json");
+        let path = path.to_str().unwrap();
+
+        // First exec claims the id
+        write_exec_state_exclusive(path, &exec_state_with_status("created")).unwrap();
+
+        // Duplicate while created is rejected and the original is untouched
+        expect_rpc_code(
+            write_exec_state_exclusive(path, &exec_state_with_status("created")),
+            ttrpc::Code::ALREADY_EXISTS,
+        );
+
+        // Runtime marks it running; duplicate is still rejected
+        std::fs::write(path, exec_state_with_status("running").to_string()).unwrap();
+        expect_rpc_code(
+            write_exec_state_exclusive(path, &exec_state_with_status("created")),
+            ttrpc::Code::ALREADY_EXISTS,
+        );
+        let on_disk: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+        assert_eq!(on_disk["status"], "running");
+
+        // Once stopped, the id may be reused
+        std::fs::write(path, exec_state_with_status("stopped").to_string()).unwrap();
+        write_exec_state_exclusive(path, &exec_state_with_status("created")).unwrap();
+        let on_disk: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+        assert_eq!(on_disk["status"], "created");
+    }
+
+    #[test]
+    fn test_write_exec_state_exclusive_allows_reuse_after_delete() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("exec-e1.json");
+        let path = path.to_str().unwrap();
+
+        write_exec_state_exclusive(path, &exec_state_with_status("created")).unwrap();
+        std::fs::remove_file(path).unwrap();
+        write_exec_state_exclusive(path, &exec_state_with_status("created")).unwrap();
+    }
+
+    #[test]
+    fn test_write_exec_state_exclusive_replaces_corrupt_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("exec-e1.json");
+        std::fs::write(&path, b"not json").unwrap();
+        write_exec_state_exclusive(path.to_str().unwrap(), &exec_state_with_status("created"))
+            .unwrap();
+    }
+
+    // --- oom_event tests ---
+
+    #[test]
+    fn test_oom_event() {
+        let (topic, namespace, event) = oom_event("k8s.io", "abc123");
+        assert_eq!(topic, "/tasks/oom");
+        assert_eq!(namespace, "k8s.io");
+        assert_eq!(event.container_id, "abc123");
+    }
+
     // --- version_string tests ---
 
     #[test]
@@ -2072,6 +3849,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_versions_compatible() {
+        let shim = "0.2.27 (abc1234 2026-02-18)";
+        assert!(versions_compatible(shim, shim));
+        // Another build or patch release of the same series
+        assert!(versions_compatible(shim, "0.2.27 (def5678 2026-03-01)"));
+        assert!(versions_compatible(shim, "0.2.30 (def5678 2026-03-01)"));
+        // Before 1.0 a minor release may break
+        assert!(!versions_compatible(shim, "0.3.0 (def5678 2026-03-01)"));
+        assert!(!versions_compatible(shim, "1.2.27 (abc1234 2026-02-18)"));
+        assert!(versions_compatible(
+            "1.4.0 (abc1234 2026-02-18)",
+            "1.0.2 (def5678 2026-01-01)"
+        ));
+        assert!(!versions_compatible(
+            "1.4.0 (abc1234 2026-02-18)",
+            "2.0.0 (def5678 2026-01-01)"
+        ));
+        // Unparsable versions only match exactly
+        assert!(!versions_compatible(shim, "unknown (--version failed)"));
+        assert!(!versions_compatible("dev", "dev build"));
+        assert!(versions_compatible("dev", "dev"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_strict_runtime_version_from_env() {
+        std::env::remove_var("REAPER_STRICT_RUNTIME_VERSION");
+        assert!(!strict_runtime_version());
+        std::env::set_var("REAPER_STRICT_RUNTIME_VERSION", "1");
+        assert!(strict_runtime_version());
+        std::env::set_var("REAPER_STRICT_RUNTIME_VERSION", "no");
+        assert!(!strict_runtime_version());
+        std::env::remove_var("REAPER_STRICT_RUNTIME_VERSION");
+    }
+
     #[test]
     fn test_check_version_compatibility_failing_binary() {
         // Use /usr/bin/false — exits non-zero
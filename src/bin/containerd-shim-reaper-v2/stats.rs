@@ -0,0 +1,258 @@
+//! Resource usage for the `stats()` RPC, read from a container's cgroup v2
+//! directory.
+//!
+//! containerd (and through it the kubelet, `kubectl top` and metrics-server)
+//! decodes `io.containerd.cgroups.v1.Metrics`, the only metrics message in
+//! the shim protos, so the v2 values are reported in its fields: CPU times in
+//! nanoseconds, `memory.current` as usage, and the `memory.stat` counters
+//! under their v1 names (`anon` as rss, `file` as cache). The kubelet's
+//! working set is usage minus `total_inactive_file`.
+
+use containerd_shim_protos::cgroups::metrics::{
+    CPUStat, CPUUsage, MemoryEntry, MemoryStat, Metrics, PidsStat, Throttle,
+};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Mount point of the cgroup v2 unified hierarchy.
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+/// cgroup directory of `container_id`: the one the runtime recorded in state,
+/// else `reaper/<id>`, where it puts containers without a `cgroupsPath`.
+pub fn cgroup_dir(container_id: &str, recorded: Option<&str>) -> PathBuf {
+    match recorded {
+        Some(dir) => PathBuf::from(dir),
+        None => Path::new(CGROUP_ROOT).join("reaper").join(container_id),
+    }
+}
+
+/// Usage counters of one cgroup, as read from its v2 files.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CgroupUsage {
+    /// `cpu.stat` `usage_usec`, `user_usec` and `system_usec`
+    pub cpu_usage_usec: u64,
+    pub cpu_user_usec: u64,
+    pub cpu_system_usec: u64,
+    /// `cpu.stat` `nr_periods`, `nr_throttled` and `throttled_usec`
+    pub nr_periods: u64,
+    pub nr_throttled: u64,
+    pub throttled_usec: u64,
+    /// `memory.current`
+    pub memory_current: u64,
+    /// `memory.max`, `None` when unlimited
+    pub memory_max: Option<u64>,
+    /// `memory.stat`, key by key
+    pub memory_stat: HashMap<String, u64>,
+    /// `pids.current`
+    pub pids_current: u64,
+}
+
+/// `key value` lines, as in `cpu.stat` and `memory.stat`. Lines that don't
+/// parse are skipped.
+fn parse_flat_keyed(content: &str) -> HashMap<String, u64> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(' ')?;
+            Some((key.to_string(), value.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+/// A single-value file such as `memory.current`; `max` and junk are `None`.
+fn parse_single(content: &str) -> Option<u64> {
+    content.trim().parse().ok()
+}
+
+impl CgroupUsage {
+    /// Read the usage of cgroup `dir`, or `None` if it doesn't exist.
+    /// Missing files (e.g. a controller not enabled for it) read as zero.
+    pub fn read(dir: &Path) -> Option<Self> {
+        if !dir.is_dir() {
+            return None;
+        }
+        let read = |name: &str| std::fs::read_to_string(dir.join(name)).unwrap_or_default();
+        let cpu = parse_flat_keyed(&read("cpu.stat"));
+        let cpu_value = |key: &str| cpu.get(key).copied().unwrap_or(0);
+        Some(Self {
+            cpu_usage_usec: cpu_value("usage_usec"),
+            cpu_user_usec: cpu_value("user_usec"),
+            cpu_system_usec: cpu_value("system_usec"),
+            nr_periods: cpu_value("nr_periods"),
+            nr_throttled: cpu_value("nr_throttled"),
+            throttled_usec: cpu_value("throttled_usec"),
+            memory_current: parse_single(&read("memory.current")).unwrap_or(0),
+            memory_max: parse_single(&read("memory.max")),
+            memory_stat: parse_flat_keyed(&read("memory.stat")),
+            pids_current: parse_single(&read("pids.current")).unwrap_or(0),
+        })
+    }
+
+    /// The usage as containerd's cgroup metrics.
+    pub fn to_metrics(&self) -> Metrics {
+        let stat = |key: &str| self.memory_stat.get(key).copied().unwrap_or(0);
+        let mut metrics = Metrics::new();
+        metrics.cpu = Some(CPUStat {
+            usage: Some(CPUUsage {
+                total: self.cpu_usage_usec * 1000,
+                user: self.cpu_user_usec * 1000,
+                kernel: self.cpu_system_usec * 1000,
+                ..Default::default()
+            })
+            .into(),
+            throttling: Some(Throttle {
+                periods: self.nr_periods,
+                throttled_periods: self.nr_throttled,
+                throttled_time: self.throttled_usec * 1000,
+                ..Default::default()
+            })
+            .into(),
+            ..Default::default()
+        })
+        .into();
+        // No hierarchy below a container cgroup: totals equal own values
+        metrics.memory = Some(MemoryStat {
+            cache: stat("file"),
+            total_cache: stat("file"),
+            rss: stat("anon"),
+            total_rss: stat("anon"),
+            rss_huge: stat("anon_thp"),
+            total_rss_huge: stat("anon_thp"),
+            mapped_file: stat("file_mapped"),
+            total_mapped_file: stat("file_mapped"),
+            dirty: stat("file_dirty"),
+            total_dirty: stat("file_dirty"),
+            writeback: stat("file_writeback"),
+            total_writeback: stat("file_writeback"),
+            pg_fault: stat("pgfault"),
+            total_pg_fault: stat("pgfault"),
+            pg_maj_fault: stat("pgmajfault"),
+            total_pg_maj_fault: stat("pgmajfault"),
+            inactive_anon: stat("inactive_anon"),
+            total_inactive_anon: stat("inactive_anon"),
+            active_anon: stat("active_anon"),
+            total_active_anon: stat("active_anon"),
+            inactive_file: stat("inactive_file"),
+            total_inactive_file: stat("inactive_file"),
+            active_file: stat("active_file"),
+            total_active_file: stat("active_file"),
+            unevictable: stat("unevictable"),
+            total_unevictable: stat("unevictable"),
+            usage: Some(MemoryEntry {
+                usage: self.memory_current,
+                limit: self.memory_max.unwrap_or(u64::MAX),
+                ..Default::default()
+            })
+            .into(),
+            ..Default::default()
+        })
+        .into();
+        metrics.pids = Some(PidsStat {
+            current: self.pids_current,
+            ..Default::default()
+        })
+        .into();
+        metrics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CPU_STAT: &str = "usage_usec 2500000\n\
+                            user_usec 2000000\n\
+                            system_usec 500000\n\
+                            nr_periods 120\n\
+                            nr_throttled 7\n\
+                            throttled_usec 35000\n";
+
+    const MEMORY_STAT: &str = "anon 10485760\n\
+                               file 4194304\n\
+                               file_mapped 1048576\n\
+                               inactive_file 3145728\n\
+                               active_file 1048576\n\
+                               pgfault 5000\n\
+                               pgmajfault 12\n";
+
+    fn fixture_cgroup(files: &[(&str, &str)]) -> tempfile::TempDir {
+        let dir = tempfile::TempDir::new().unwrap();
+        for (name, content) in files {
+            std::fs::write(dir.path().join(name), content).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_read_cgroup_files() {
+        let dir = fixture_cgroup(&[
+            ("cpu.stat", CPU_STAT),
+            ("memory.current", "16777216\n"),
+            ("memory.max", "max\n"),
+            ("memory.stat", MEMORY_STAT),
+        ]);
+        let usage = CgroupUsage::read(dir.path()).unwrap();
+        assert_eq!(usage.cpu_usage_usec, 2_500_000);
+        assert_eq!(usage.cpu_user_usec, 2_000_000);
+        assert_eq!(usage.cpu_system_usec, 500_000);
+        assert_eq!(
+            (usage.nr_periods, usage.nr_throttled, usage.throttled_usec),
+            (120, 7, 35_000)
+        );
+        assert_eq!(usage.memory_current, 16_777_216);
+        assert_eq!(usage.memory_max, None);
+        assert_eq!(usage.memory_stat["inactive_file"], 3_145_728);
+        // pids controller not enabled
+        assert_eq!(usage.pids_current, 0);
+
+        // Only memory.current, with a limit: the rest reads as zero
+        let dir = fixture_cgroup(&[("memory.current", "4096\n"), ("memory.max", "268435456\n")]);
+        let usage = CgroupUsage::read(dir.path()).unwrap();
+        assert_eq!(usage.memory_current, 4096);
+        assert_eq!(usage.memory_max, Some(268_435_456));
+        assert_eq!(usage.cpu_usage_usec, 0);
+        assert!(usage.memory_stat.is_empty());
+
+        assert_eq!(CgroupUsage::read(&dir.path().join("gone")), None);
+    }
+
+    #[test]
+    fn test_usage_to_metrics() {
+        let usage = CgroupUsage {
+            cpu_usage_usec: 2_500_000,
+            cpu_user_usec: 2_000_000,
+            cpu_system_usec: 500_000,
+            nr_throttled: 7,
+            throttled_usec: 35_000,
+            memory_current: 16_777_216,
+            memory_stat: parse_flat_keyed(MEMORY_STAT),
+            pids_current: 3,
+            ..Default::default()
+        };
+        let metrics = usage.to_metrics();
+        assert_eq!(metrics.cpu.usage.total, 2_500_000_000);
+        assert_eq!(metrics.cpu.usage.user, 2_000_000_000);
+        assert_eq!(metrics.cpu.usage.kernel, 500_000_000);
+        assert_eq!(metrics.cpu.throttling.throttled_periods, 7);
+        assert_eq!(metrics.cpu.throttling.throttled_time, 35_000_000);
+        assert_eq!(metrics.memory.usage.usage, 16_777_216);
+        assert_eq!(metrics.memory.usage.limit, u64::MAX);
+        assert_eq!(metrics.memory.total_rss, 10_485_760);
+        assert_eq!(metrics.memory.total_cache, 4_194_304);
+        assert_eq!(metrics.memory.total_inactive_file, 3_145_728);
+        assert_eq!(metrics.memory.total_pg_maj_fault, 12);
+        assert_eq!(metrics.pids.current, 3);
+    }
+
+    #[test]
+    fn test_cgroup_dir() {
+        assert_eq!(
+            cgroup_dir("abc", None),
+            PathBuf::from("/sys/fs/cgroup/reaper/abc")
+        );
+        assert_eq!(
+            cgroup_dir("abc", Some("/sys/fs/cgroup/kubepods/pod1/abc")),
+            PathBuf::from("/sys/fs/cgroup/kubepods/pod1/abc")
+        );
+    }
+}
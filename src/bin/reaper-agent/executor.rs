@@ -120,10 +120,7 @@ impl JobManager {
                         // with hostPID: true).
                         #[cfg(target_os = "linux")]
                         if host_exec {
-                            let fd = libc::open(
-                                b"/proc/1/ns/mnt\0".as_ptr() as *const libc::c_char,
-                                libc::O_RDONLY,
-                            );
+                            let fd = libc::open(c"/proc/1/ns/mnt".as_ptr(), libc::O_RDONLY);
                             if fd < 0 {
                                 return Err(std::io::Error::last_os_error());
                             }
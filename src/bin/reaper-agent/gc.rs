@@ -1,9 +1,11 @@
 use serde::Deserialize;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tracing::{debug, info, warn};
 
 use crate::metrics::MetricsState;
+use crate::psi;
+use crate::upper_changes;
 
 /// Minimal deserialization of state.json — only the fields GC needs.
 #[derive(Debug, Deserialize)]
@@ -14,6 +16,19 @@ pub struct ContainerStateMinimal {
     #[serde(default)]
     #[allow(dead_code)]
     pub exit_code: Option<i32>,
+    /// Dedicated cgroup of the workload, when the runtime placed it in one
+    #[serde(default)]
+    pub cgroup_path: Option<PathBuf>,
+    #[serde(default)]
+    pub effective: Option<EffectiveMinimal>,
+}
+
+/// The part of the runtime's `effective` settings GC reports on.
+#[derive(Debug, Deserialize)]
+pub struct EffectiveMinimal {
+    /// Overlay upper layer the workload writes to
+    #[serde(default)]
+    pub overlay_upper: Option<PathBuf>,
 }
 
 /// Check whether a PID is still alive using kill(pid, 0).
@@ -49,6 +64,8 @@ pub async fn run_gc(state_dir: &str, metrics: &MetricsState) {
     let mut stopped = 0u64;
     let mut created = 0u64;
     let mut cleaned = 0u64;
+    metrics.clear_container_pressure();
+    metrics.clear_container_fs_changes();
 
     // Infrastructure directories that are NOT container state dirs — skip during GC
     const INFRA_DIRS: &[&str] = &["overlay", "merged", "ns"];
@@ -104,6 +121,15 @@ pub async fn run_gc(state_dir: &str, metrics: &MetricsState) {
                 if let Some(pid) = state.pid {
                     if is_pid_alive(pid) {
                         running += 1;
+                        if let Some(cgroup) = &state.cgroup_path {
+                            report_pressure(metrics, &state.id, cgroup);
+                        }
+                        let upper = state.effective.and_then(|e| e.overlay_upper);
+                        if let Some(upper) = upper {
+                            if let Some(changes) = scan_upper_changes(upper.clone()).await {
+                                metrics.set_container_fs_changes(&state.id, &upper, &changes);
+                            }
+                        }
                     } else {
                         // Process is gone — mark as stopped
                         info!(
@@ -129,7 +155,7 @@ pub async fn run_gc(state_dir: &str, metrics: &MetricsState) {
                 }
             }
             "stopped" => stopped += 1,
-            "created" => created += 1,
+            "created" | "starting" => created += 1,
             other => {
                 debug!(id = state.id, status = other, "unknown container status");
             }
@@ -148,6 +174,29 @@ pub async fn run_gc(state_dir: &str, metrics: &MetricsState) {
     );
 }
 
+/// Count the changes in `upper` through the shared cache. The walk is
+/// blocking and a large upper layer takes a while, so it runs off the
+/// async workers.
+async fn scan_upper_changes(upper: PathBuf) -> Option<upper_changes::UpperChanges> {
+    match tokio::task::spawn_blocking(move || upper_changes::cache().get(&upper)).await {
+        Ok(changes) => changes,
+        Err(e) => {
+            warn!(error = %e, "upper layer scan failed");
+            None
+        }
+    }
+}
+
+/// Export the PSI of a running container's cgroup. Resources without a
+/// readable `<resource>.pressure` file are skipped.
+fn report_pressure(metrics: &MetricsState, id: &str, cgroup: &Path) {
+    for resource in psi::PSI_RESOURCES {
+        if let Some(stats) = psi::read_psi(cgroup, resource) {
+            metrics.set_container_pressure(id, resource, &stats);
+        }
+    }
+}
+
 /// Update a state file to mark the container as stopped with exit_code -1.
 /// Uses serde_json::Value to preserve all existing fields.
 fn mark_stopped(state_file: &Path, data: &[u8]) -> anyhow::Result<()> {
@@ -13,6 +13,8 @@ mod metrics;
 mod node_condition;
 mod overlay_api;
 mod overlay_gc;
+mod psi;
+mod upper_changes;
 
 // config.rs is available as shared module but not needed by the agent
 // (the agent writes config files, it doesn't read them)
@@ -1,10 +1,12 @@
 use axum::{extract::State, http::StatusCode, response::IntoResponse, routing::get, Router};
 use prometheus_client::{
-    encoding::text::encode,
-    metrics::{counter::Counter, gauge::Gauge},
+    encoding::{text::encode, EncodeLabelSet},
+    metrics::{counter::Counter, family::Family, gauge::Gauge},
     registry::Registry,
 };
 use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::atomic::AtomicU64;
 use std::sync::{Arc, Mutex};
 use tracing::info;
 
@@ -12,6 +14,30 @@ use crate::executor::JobManager;
 use crate::health;
 use crate::jobs::{JobRequest, JobResponse, JobState};
 use crate::overlay_api;
+use crate::psi::PsiStats;
+use crate::upper_changes::UpperChanges;
+
+/// Labels of the per-container PSI gauges.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct PressureLabels {
+    container: String,
+    /// `memory`, `cpu` or `io`
+    resource: String,
+    /// `some` or `full`
+    kind: String,
+    /// `avg10`, `avg60` or `avg300`
+    window: String,
+}
+
+/// Labels of the per-container filesystem change gauges.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct FsChangeLabels {
+    container: String,
+    /// Upper layer counted; shared by every container of the overlay
+    overlay: String,
+    /// `modified` or `deleted`
+    kind: String,
+}
 
 /// Shared metrics state used across all agent tasks.
 #[derive(Clone)]
@@ -47,6 +73,12 @@ struct MetricsInner {
     // Node condition reporting metrics
     node_condition_updates_total: Counter,
     node_condition_healthy: Gauge,
+
+    // Per-container pressure stall information
+    container_pressure: Family<PressureLabels, Gauge<f64, AtomicU64>>,
+
+    // Per-container entries in the overlay upper layer
+    container_fs_changes: Family<FsChangeLabels, Gauge>,
 }
 
 impl MetricsState {
@@ -66,6 +98,8 @@ impl MetricsState {
         let ns_cleaned_total = Counter::default();
         let node_condition_updates_total = Counter::default();
         let node_condition_healthy = Gauge::default();
+        let container_pressure = Family::<PressureLabels, Gauge<f64, AtomicU64>>::default();
+        let container_fs_changes = Family::<FsChangeLabels, Gauge>::default();
 
         registry.register(
             "reaper_containers_created",
@@ -132,6 +166,16 @@ impl MetricsState {
             "Whether the last node condition patch reported healthy (1=healthy, 0=unhealthy)",
             node_condition_healthy.clone(),
         );
+        registry.register(
+            "reaper_container_pressure",
+            "Share of time (percent) tasks of a running container's cgroup stalled on a resource (PSI)",
+            container_pressure.clone(),
+        );
+        registry.register(
+            "reaper_container_fs_changes",
+            "Files a running container's overlay upper layer holds, created or modified and deleted (whiteouts); the count is per overlay, shared by the containers using it",
+            container_fs_changes.clone(),
+        );
 
         Self {
             inner: Arc::new(MetricsInner {
@@ -149,6 +193,8 @@ impl MetricsState {
                 ns_cleaned_total,
                 node_condition_updates_total,
                 node_condition_healthy,
+                container_pressure,
+                container_fs_changes,
             }),
         }
     }
@@ -207,6 +253,55 @@ impl MetricsState {
             .set(if healthy { 1 } else { 0 });
     }
 
+    /// Drop all per-container pressure series, so containers that stopped
+    /// since the last GC pass disappear.
+    pub fn clear_container_pressure(&self) {
+        self.inner.container_pressure.clear();
+    }
+
+    pub fn set_container_pressure(&self, container: &str, resource: &str, stats: &PsiStats) {
+        let lines =
+            std::iter::once(("some", &stats.some)).chain(stats.full.as_ref().map(|f| ("full", f)));
+        for (kind, avgs) in lines {
+            for (window, value) in [
+                ("avg10", avgs.avg10),
+                ("avg60", avgs.avg60),
+                ("avg300", avgs.avg300),
+            ] {
+                let labels = PressureLabels {
+                    container: container.to_string(),
+                    resource: resource.to_string(),
+                    kind: kind.to_string(),
+                    window: window.to_string(),
+                };
+                self.inner
+                    .container_pressure
+                    .get_or_create(&labels)
+                    .set(value);
+            }
+        }
+    }
+
+    /// Drop all per-container filesystem change series, like
+    /// `clear_container_pressure`.
+    pub fn clear_container_fs_changes(&self) {
+        self.inner.container_fs_changes.clear();
+    }
+
+    pub fn set_container_fs_changes(&self, container: &str, upper: &Path, changes: &UpperChanges) {
+        for (kind, count) in [("modified", changes.modified), ("deleted", changes.deleted)] {
+            let labels = FsChangeLabels {
+                container: container.to_string(),
+                overlay: upper.display().to_string(),
+                kind: kind.to_string(),
+            };
+            self.inner
+                .container_fs_changes
+                .get_or_create(&labels)
+                .set(count as i64);
+        }
+    }
+
     pub fn set_overlay_namespaces(&self, count: u64) {
         self.inner.overlay_namespaces.set(count as i64);
     }
@@ -149,6 +149,12 @@ pub fn delete_overlay(state_dir: &str, namespace: &str, name: &str) -> Result<bo
     // 4. Remove lock file
     remove_path(&lock_path, "lock file");
 
+    // 5. Remove sensitive-path filter placeholders
+    remove_path(
+        &base.join("overlay-filters").join(&ns_key),
+        "filter placeholders",
+    );
+
     info!(
         namespace = namespace,
         name = name,
@@ -157,6 +163,33 @@ pub fn delete_overlay(state_dir: &str, namespace: &str, name: &str) -> Result<bo
     Ok(true)
 }
 
+/// Remove a file or directory, logging on failure.
+fn remove_path(path: &Path, description: &str) {
+    if !path.exists() {
+        return;
+    }
+
+    let result = if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    };
+
+    match result {
+        Ok(()) => {
+            info!(path = ?path, what = description, "removed overlay artifact");
+        }
+        Err(e) => {
+            warn!(
+                error = %e,
+                path = ?path,
+                what = description,
+                "failed to remove overlay artifact"
+            );
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -265,30 +298,3 @@ mod tests {
         assert!(!detail.ready);
     }
 }
-
-/// Remove a file or directory, logging on failure.
-fn remove_path(path: &Path, description: &str) {
-    if !path.exists() {
-        return;
-    }
-
-    let result = if path.is_dir() {
-        fs::remove_dir_all(path)
-    } else {
-        fs::remove_file(path)
-    };
-
-    match result {
-        Ok(()) => {
-            info!(path = ?path, what = description, "removed overlay artifact");
-        }
-        Err(e) => {
-            warn!(
-                error = %e,
-                path = ?path,
-                what = description,
-                "failed to remove overlay artifact"
-            );
-        }
-    }
-}
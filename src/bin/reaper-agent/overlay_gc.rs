@@ -8,6 +8,11 @@ use tracing::{debug, error, info, warn};
 
 use crate::metrics::MetricsState;
 
+/// Overlay directory holding node-wide shared overlays (the `shared-overlay`
+/// annotation). They outlive any one namespace and are never reconciled
+/// against Kubernetes namespaces.
+const SHARED_OVERLAY_DIR: &str = "_shared";
+
 /// Check whether any container state directory references the given namespace
 /// with status "running". Returns true if at least one running container exists.
 fn has_running_containers(state_dir: &str, namespace: &str) -> bool {
@@ -80,7 +85,7 @@ fn kill_helper(pid: i32) {
 /// Read a PID file in `<pid> <inode>` format. Returns None on any error.
 fn read_pid_file(path: &Path) -> Option<(i32, u64)> {
     let content = fs::read_to_string(path).ok()?;
-    let mut parts = content.trim().split_whitespace();
+    let mut parts = content.split_whitespace();
     let pid: i32 = parts.next()?.parse().ok()?;
     let inode: u64 = parts.next()?.parse().ok()?;
     Some((pid, inode))
@@ -168,10 +173,7 @@ fn try_lock_nonblocking(lock_path: &Path) -> Option<nix::fcntl::Flock<std::fs::F
         Ok(f) => f,
         Err(_) => return None,
     };
-    match Flock::lock(file, FlockArg::LockExclusiveNonblock) {
-        Ok(lock) => Some(lock),
-        Err(_) => None,
-    }
+    Flock::lock(file, FlockArg::LockExclusiveNonblock).ok()
 }
 
 /// Parse a namespace filename into (k8s_namespace, optional_overlay_name).
@@ -338,6 +340,12 @@ pub fn run_ns_cleanup(state_dir: &str, metrics: &MetricsState) {
             }
             remove_path(&pid_file, "stale ns pid file");
         }
+
+        // Filter placeholders belong to the namespace that was just removed
+        remove_path(
+            &Path::new(state_dir).join("overlay-filters").join(&name),
+            "filter placeholders",
+        );
     }
 
     metrics.inc_ns_cleanup_runs();
@@ -406,6 +414,30 @@ fn remove_path(path: &Path, description: &str) -> bool {
     }
 }
 
+/// Remove the runtime's sensitive-path filter placeholders for a namespace:
+/// `overlay-filters/<ns>` and every named group `overlay-filters/<ns>--*`.
+/// Returns the number of placeholder directories removed.
+fn remove_filter_placeholders(state_dir: &str, namespace: &str) -> u64 {
+    let filter_dir = Path::new(state_dir).join("overlay-filters");
+    let entries = match fs::read_dir(&filter_dir) {
+        Ok(e) => e,
+        Err(_) => return 0,
+    };
+
+    let group_prefix = format!("{}--", namespace);
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        if let Some(name) = entry.file_name().to_str() {
+            if (name == namespace || name.starts_with(&group_prefix))
+                && remove_path(&entry.path(), "filter placeholders")
+            {
+                removed += 1;
+            }
+        }
+    }
+    removed
+}
+
 /// Run a single overlay GC reconciliation pass.
 ///
 /// Lists K8s namespaces via the API, compares against on-disk overlay directories,
@@ -448,7 +480,9 @@ pub async fn run_overlay_gc(
     for entry in entries.flatten() {
         if entry.path().is_dir() {
             if let Some(name) = entry.file_name().to_str() {
-                on_disk_namespaces.push(name.to_string());
+                if name != SHARED_OVERLAY_DIR {
+                    on_disk_namespaces.push(name.to_string());
+                }
             }
         }
     }
@@ -543,6 +577,9 @@ pub async fn run_overlay_gc(
             }
         }
 
+        // Sensitive-path filter placeholders for the namespace and its groups
+        remove_filter_placeholders(state_dir, ns_name);
+
         cleaned += 1;
     }
 
@@ -612,3 +649,29 @@ pub async fn overlay_gc_loop(state_dir: &str, interval_secs: u64, metrics: &Metr
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_remove_filter_placeholders_for_namespace() {
+        let state_dir = TempDir::new().expect("create temp state dir");
+        let filter_dir = state_dir.path().join("overlay-filters");
+        for name in ["default", "default--group", "defaults", "other--group"] {
+            let dir = filter_dir.join(name);
+            fs::create_dir_all(&dir).expect("create placeholder dir");
+            fs::write(dir.join("_etc_shadow"), b"").expect("create placeholder");
+        }
+
+        let state = state_dir.path().to_str().unwrap();
+        assert_eq!(remove_filter_placeholders(state, "default"), 2);
+        assert!(!filter_dir.join("default").exists());
+        assert!(!filter_dir.join("default--group").exists());
+        assert!(filter_dir.join("defaults").exists());
+        assert!(filter_dir.join("other--group").exists());
+
+        assert_eq!(remove_filter_placeholders(state, "default"), 0);
+    }
+}
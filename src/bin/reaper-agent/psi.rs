@@ -0,0 +1,138 @@
+//! Pressure stall information (PSI) of container cgroups.
+//!
+//! On cgroup v2, `memory.pressure`, `cpu.pressure` and `io.pressure` report
+//! how long tasks in the cgroup were stalled on that resource:
+//!
+//! ```text
+//! some avg10=0.12 avg60=0.05 avg300=0.01 total=123456
+//! full avg10=0.00 avg60=0.00 avg300=0.00 total=7890
+//! ```
+
+use std::fs;
+use std::path::Path;
+
+/// Resources whose pressure is reported per container.
+pub const PSI_RESOURCES: &[&str] = &["memory", "cpu", "io"];
+
+/// Stall averages (percent of wall time) and total stall time (µs) of one
+/// PSI line.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PsiAverages {
+    pub avg10: f64,
+    pub avg60: f64,
+    pub avg300: f64,
+    pub total_us: u64,
+}
+
+/// Parsed `<resource>.pressure` file.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PsiStats {
+    /// Some tasks stalled
+    pub some: PsiAverages,
+    /// All tasks stalled at once; absent for `cpu.pressure` on older kernels
+    pub full: Option<PsiAverages>,
+}
+
+/// Parse one `avg10=.. avg60=.. avg300=.. total=..` field list.
+fn parse_averages<'a>(fields: impl Iterator<Item = &'a str>) -> Option<PsiAverages> {
+    let (mut avg10, mut avg60, mut avg300, mut total_us) = (None, None, None, None);
+    for field in fields {
+        let (key, value) = field.split_once('=')?;
+        match key {
+            "avg10" => avg10 = Some(value.parse().ok()?),
+            "avg60" => avg60 = Some(value.parse().ok()?),
+            "avg300" => avg300 = Some(value.parse().ok()?),
+            "total" => total_us = Some(value.parse().ok()?),
+            _ => {}
+        }
+    }
+    Some(PsiAverages {
+        avg10: avg10?,
+        avg60: avg60?,
+        avg300: avg300?,
+        total_us: total_us?,
+    })
+}
+
+/// Parse the content of a `<resource>.pressure` file. Returns `None` if the
+/// `some` line is missing or any line is malformed.
+pub fn parse_psi(content: &str) -> Option<PsiStats> {
+    let mut some = None;
+    let mut full = None;
+    for line in content.lines().filter(|l| !l.trim().is_empty()) {
+        let mut fields = line.split_whitespace();
+        match fields.next()? {
+            "some" => some = Some(parse_averages(fields)?),
+            "full" => full = Some(parse_averages(fields)?),
+            _ => return None,
+        }
+    }
+    Some(PsiStats { some: some?, full })
+}
+
+/// Read `<resource>.pressure` of the cgroup at `cgroup_dir`. `None` if the
+/// file is missing (cgroup v1, PSI disabled, cgroup gone) or unparsable.
+pub fn read_psi(cgroup_dir: &Path, resource: &str) -> Option<PsiStats> {
+    let content = fs::read_to_string(cgroup_dir.join(format!("{}.pressure", resource))).ok()?;
+    parse_psi(&content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_memory_pressure() {
+        let content = "some avg10=1.53 avg60=0.87 avg300=0.22 total=4521984\n\
+                       full avg10=0.50 avg60=0.25 avg300=0.05 total=1203001\n";
+        let stats = parse_psi(content).unwrap();
+        assert_eq!(
+            stats.some,
+            PsiAverages {
+                avg10: 1.53,
+                avg60: 0.87,
+                avg300: 0.22,
+                total_us: 4521984,
+            }
+        );
+        assert_eq!(
+            stats.full,
+            Some(PsiAverages {
+                avg10: 0.50,
+                avg60: 0.25,
+                avg300: 0.05,
+                total_us: 1203001,
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_psi_some_only_and_malformed() {
+        let stats = parse_psi("some avg10=0.00 avg60=0.00 avg300=0.00 total=0\n").unwrap();
+        assert_eq!(stats.some, PsiAverages::default());
+        assert_eq!(stats.full, None);
+
+        assert_eq!(parse_psi(""), None);
+        assert_eq!(
+            parse_psi("full avg10=0.00 avg60=0.00 avg300=0.00 total=0\n"),
+            None
+        );
+        assert_eq!(
+            parse_psi("some avg10=x avg60=0.00 avg300=0.00 total=0\n"),
+            None
+        );
+        assert_eq!(parse_psi("some avg10=0.00 total=0\n"), None);
+    }
+
+    #[test]
+    fn test_read_psi_missing_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        assert_eq!(read_psi(dir.path(), "memory"), None);
+        fs::write(
+            dir.path().join("cpu.pressure"),
+            "some avg10=2.00 avg60=1.00 avg300=0.50 total=99\n",
+        )
+        .unwrap();
+        assert_eq!(read_psi(dir.path(), "cpu").unwrap().some.total_us, 99);
+    }
+}
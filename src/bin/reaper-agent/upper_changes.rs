@@ -0,0 +1,177 @@
+//! Count of filesystem changes in a container's overlay upper layer.
+//!
+//! Everything a workload writes lands in the upper layer of its overlay
+//! (recorded by the runtime in state as `effective.overlay_upper`). Created
+//! or modified files show up there as regular entries, deleted lower-layer
+//! files as whiteouts: character devices with device number 0/0. Counting
+//! them is a cheap stand-in for a full diff, enough for dashboards to flag
+//! containers writing unexpectedly many files.
+//!
+//! Containers sharing an overlay share its upper layer, so they all report
+//! the same count.
+
+use std::collections::HashMap;
+use std::fs;
+use std::os::unix::fs::{FileTypeExt, MetadataExt};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// How long a count is reused before the upper layer is scanned again.
+pub const CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Non-directory entries of an upper layer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UpperChanges {
+    /// Files, symlinks and other entries created or copied up
+    pub modified: u64,
+    /// Whiteouts, i.e. deleted lower-layer files
+    pub deleted: u64,
+}
+
+/// Walk `upper` and count its changes. Symlinks are not followed, and
+/// entries that vanish mid-scan are skipped.
+pub fn count_upper_changes(upper: &Path) -> UpperChanges {
+    let mut changes = UpperChanges::default();
+    let mut pending = vec![upper.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(meta) = entry.path().symlink_metadata() else {
+                continue;
+            };
+            let file_type = meta.file_type();
+            if file_type.is_dir() {
+                pending.push(entry.path());
+            } else if file_type.is_char_device() && meta.rdev() == 0 {
+                changes.deleted += 1;
+            } else {
+                changes.modified += 1;
+            }
+        }
+    }
+    changes
+}
+
+/// Counts per upper layer, each reused for `ttl`.
+pub struct ChangeCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<PathBuf, (Instant, UpperChanges)>>,
+}
+
+impl ChangeCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Count of `upper`, scanning only if the cached one is older than the
+    /// TTL. `None` if `upper` is not a directory (e.g. the overlay is gone).
+    pub fn get(&self, upper: &Path) -> Option<UpperChanges> {
+        if !upper.is_dir() {
+            self.entries.lock().unwrap().remove(upper);
+            return None;
+        }
+        if let Some((at, changes)) = self.entries.lock().unwrap().get(upper) {
+            if at.elapsed() < self.ttl {
+                return Some(*changes);
+            }
+        }
+        let changes = count_upper_changes(upper);
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(upper.to_path_buf(), (Instant::now(), changes));
+        Some(changes)
+    }
+}
+
+/// Process-wide cache shared by GC passes.
+pub fn cache() -> &'static ChangeCache {
+    static CACHE: OnceLock<ChangeCache> = OnceLock::new();
+    CACHE.get_or_init(|| ChangeCache::new(CACHE_TTL))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nix::sys::stat::{mknod, Mode, SFlag};
+
+    fn synthetic_upper() -> tempfile::TempDir {
+        let dir = tempfile::TempDir::new().unwrap();
+        let upper = dir.path();
+        fs::create_dir_all(upper.join("etc/app")).unwrap();
+        fs::write(upper.join("etc/app/config"), "x").unwrap();
+        fs::write(upper.join("etc/hosts"), "127.0.0.1 localhost").unwrap();
+        std::os::unix::fs::symlink("/nonexistent", upper.join("etc/link")).unwrap();
+        fs::create_dir(upper.join("empty")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_count_regular_files_and_symlinks() {
+        let upper = synthetic_upper();
+        assert_eq!(
+            count_upper_changes(upper.path()),
+            UpperChanges {
+                modified: 3,
+                deleted: 0,
+            }
+        );
+        assert_eq!(
+            count_upper_changes(&upper.path().join("missing")),
+            UpperChanges::default()
+        );
+    }
+
+    #[test]
+    fn test_count_whiteouts() {
+        // Whiteouts are 0/0 character devices, which only root can create
+        if !nix::unistd::getuid().is_root() {
+            return;
+        }
+        let upper = synthetic_upper();
+        for name in ["etc/passwd", "empty/gone"] {
+            mknod(
+                &upper.path().join(name),
+                SFlag::S_IFCHR,
+                Mode::from_bits_truncate(0o000),
+                0,
+            )
+            .unwrap();
+        }
+        // A real character device is not a whiteout
+        mknod(
+            &upper.path().join("null"),
+            SFlag::S_IFCHR,
+            Mode::from_bits_truncate(0o666),
+            nix::sys::stat::makedev(1, 3),
+        )
+        .unwrap();
+        assert_eq!(
+            count_upper_changes(upper.path()),
+            UpperChanges {
+                modified: 4,
+                deleted: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_cache_reuses_count_within_ttl() {
+        let upper = synthetic_upper();
+        let cache = ChangeCache::new(Duration::from_secs(60));
+        assert_eq!(cache.get(upper.path()).unwrap().modified, 3);
+
+        fs::write(upper.path().join("new"), "y").unwrap();
+        assert_eq!(cache.get(upper.path()).unwrap().modified, 3);
+
+        let uncached = ChangeCache::new(Duration::ZERO);
+        assert_eq!(uncached.get(upper.path()).unwrap().modified, 4);
+        assert_eq!(cache.get(&upper.path().join("missing")), None);
+    }
+}
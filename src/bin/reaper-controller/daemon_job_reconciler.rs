@@ -367,7 +367,7 @@ async fn list_ready_nodes(
     Ok(nodes
         .items
         .into_iter()
-        .filter(|node| is_node_ready(node))
+        .filter(is_node_ready)
         .filter_map(|node| node.metadata.name)
         .collect())
 }
@@ -476,6 +476,7 @@ mod tests {
     use super::*;
     use k8s_openapi::api::core::v1::{NodeCondition, NodeStatus};
     use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
+    use reaper::crds::ReaperDaemonJobSpec;
 
     fn make_node(name: &str, ready: bool) -> Node {
         Node {
@@ -0,0 +1,79 @@
+//! Audit log of what every container executes (`REAPER_EXEC_AUDIT_LOG`).
+//!
+//! One JSON line per workload start or exec, written just before the
+//! process is spawned and independent of the workload's own logging:
+//!
+//! ```text
+//! {"ts":1760000000,"event":"start","container_id":"abc","namespace":"default","program":"/bin/sh","argv":["sh","-c","echo hi"],"uid":1000,"gid":1000,"cwd":"/tmp"}
+//! ```
+//!
+//! argv is recorded verbatim. Secrets passed on a command line end up in
+//! this file, so it should be protected like the runtime log.
+
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+
+/// One executed process.
+#[derive(Debug, Serialize)]
+pub struct AuditRecord<'a> {
+    /// Unix time (seconds) of the spawn
+    pub ts: u64,
+    /// `start` for the workload, `exec` for an exec'd process
+    pub event: &'a str,
+    pub container_id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exec_id: Option<&'a str>,
+    /// Kubernetes namespace, when the shim passed one
+    pub namespace: Option<&'a str>,
+    /// Path handed to execve (bare names are looked up in the workload's PATH)
+    pub program: &'a str,
+    /// Full argv, including argv[0]
+    pub argv: Vec<&'a str>,
+    pub uid: u32,
+    pub gid: u32,
+    pub cwd: Option<&'a str>,
+}
+
+/// Open `REAPER_EXEC_AUDIT_LOG` for appending, or `None` when unset or
+/// unopenable. Opened before the runtime forks and enters the container's
+/// mount namespace, so records land in the host's file.
+pub fn open_log() -> Option<File> {
+    let path = std::env::var("REAPER_EXEC_AUDIT_LOG").ok()?;
+    if path.is_empty() {
+        return None;
+    }
+    match OpenOptions::new()
+        .create(true)
+        .append(true)
+        .mode(crate::config::log_file_mode())
+        .open(&path)
+    {
+        Ok(file) => Some(file),
+        Err(e) => {
+            tracing::warn!("exec audit log {} unavailable: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Append `record` as one line. A single write, so concurrent starts and
+/// execs appending to the same file don't interleave.
+pub fn write_record(log: &mut File, record: &AuditRecord) {
+    let mut line = match serde_json::to_string(record) {
+        Ok(line) => line,
+        Err(e) => {
+            tracing::warn!("exec audit record not serializable: {}", e);
+            return;
+        }
+    };
+    line.push('\n');
+    if let Err(e) = log.write_all(line.as_bytes()) {
+        tracing::warn!(
+            "failed to write exec audit record for {}: {}",
+            record.container_id,
+            e
+        );
+    }
+}
@@ -0,0 +1,349 @@
+//! Linux capability sets from OCI `process.capabilities`.
+//!
+//! The sets are resolved to bitmasks before the workload is forked, then
+//! applied from its `pre_exec` in two steps around the uid/gid switch, as
+//! runc does: [`CapabilityPlan::narrow_bounding`] drops everything outside
+//! the bounding set and sets `PR_SET_KEEPCAPS` while the process is still
+//! privileged, and [`CapabilityPlan::apply`] sets effective, permitted and
+//! inheritable with `capset(2)` and raises the ambient set. Both only make
+//! syscalls, so they are safe to call between fork and exec.
+//!
+//! A process without a `capabilities` block is left alone: a uid 0 workload
+//! keeps the runtime's full set, as before.
+
+use crate::state::OciCapabilities;
+use nix::libc;
+
+/// Capability names by number, without the `CAP_` prefix (linux/capability.h).
+const CAP_NAMES: &[&str] = &[
+    "CHOWN",
+    "DAC_OVERRIDE",
+    "DAC_READ_SEARCH",
+    "FOWNER",
+    "FSETID",
+    "KILL",
+    "SETGID",
+    "SETUID",
+    "SETPCAP",
+    "LINUX_IMMUTABLE",
+    "NET_BIND_SERVICE",
+    "NET_BROADCAST",
+    "NET_ADMIN",
+    "NET_RAW",
+    "IPC_LOCK",
+    "IPC_OWNER",
+    "SYS_MODULE",
+    "SYS_RAWIO",
+    "SYS_CHROOT",
+    "SYS_PTRACE",
+    "SYS_PACCT",
+    "SYS_ADMIN",
+    "SYS_BOOT",
+    "SYS_NICE",
+    "SYS_RESOURCE",
+    "SYS_TIME",
+    "SYS_TTY_CONFIG",
+    "MKNOD",
+    "LEASE",
+    "AUDIT_WRITE",
+    "AUDIT_CONTROL",
+    "SETFCAP",
+    "MAC_OVERRIDE",
+    "MAC_ADMIN",
+    "SYSLOG",
+    "WAKE_ALARM",
+    "BLOCK_SUSPEND",
+    "AUDIT_READ",
+    "PERFMON",
+    "BPF",
+    "CHECKPOINT_RESTORE",
+];
+
+/// `_LINUX_CAPABILITY_VERSION_3`: 64-bit sets as two 32-bit halves.
+const CAPABILITY_VERSION_3: u32 = 0x2008_0522;
+
+#[repr(C)]
+struct CapHeader {
+    version: u32,
+    pid: libc::c_int,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct CapData {
+    effective: u32,
+    permitted: u32,
+    inheritable: u32,
+}
+
+/// Number of a capability named as in the OCI spec, e.g. `CAP_NET_ADMIN`.
+pub fn capability_number(name: &str) -> Option<u32> {
+    let name = name.strip_prefix("CAP_")?;
+    CAP_NAMES.iter().position(|n| *n == name).map(|i| i as u32)
+}
+
+/// Bitmask of `names`. Unknown names are logged and skipped.
+fn mask(set: &str, names: &[String]) -> u64 {
+    names
+        .iter()
+        .fold(0, |mask, name| match capability_number(name) {
+            Some(cap) => mask | 1 << cap,
+            None => {
+                tracing::warn!("skipping unknown capability {:?} in {} set", name, set);
+                mask
+            }
+        })
+}
+
+/// Highest capability the kernel knows (`/proc/sys/kernel/cap_last_cap`).
+fn last_cap() -> u32 {
+    std::fs::read_to_string("/proc/sys/kernel/cap_last_cap")
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(CAP_NAMES.len() as u32 - 1)
+}
+
+/// Capability sets of the calling process.
+fn current_sets() -> std::io::Result<[CapData; 2]> {
+    let mut header = CapHeader {
+        version: CAPABILITY_VERSION_3,
+        pid: 0,
+    };
+    let mut data = [CapData::default(); 2];
+    // SAFETY: header and two data structs, as capget(2) expects for version 3
+    if unsafe { libc::syscall(libc::SYS_capget, &mut header, data.as_mut_ptr()) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(data)
+}
+
+/// Permitted set of the calling process.
+fn current_permitted() -> std::io::Result<u64> {
+    let data = current_sets()?;
+    Ok(u64::from(data[0].permitted) | u64::from(data[1].permitted) << 32)
+}
+
+/// Effective set of the calling process, i.e. what it may do right now.
+pub fn current_effective() -> std::io::Result<u64> {
+    let data = current_sets()?;
+    Ok(u64::from(data[0].effective) | u64::from(data[1].effective) << 32)
+}
+
+/// Whether `REAPER_FUSE_CAP_SYS_ADMIN` lets `enable-fuse` workloads mount
+/// FUSE filesystems themselves. Off by default: CAP_SYS_ADMIN is far broader
+/// than FUSE, so the node admin decides, never a pod annotation.
+pub fn fuse_sys_admin_allowed() -> bool {
+    std::env::var("REAPER_FUSE_CAP_SYS_ADMIN")
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+/// Add capability `name` to the bounding, permitted and effective sets of
+/// `spec`, where it is not already.
+pub fn grant(spec: &mut OciCapabilities, name: &str) {
+    for set in [&mut spec.bounding, &mut spec.permitted, &mut spec.effective] {
+        if !set.iter().any(|c| c == name) {
+            set.push(name.to_string());
+        }
+    }
+}
+
+/// Capability sets to give a process, as bitmasks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapabilityPlan {
+    pub bounding: u64,
+    pub effective: u64,
+    pub permitted: u64,
+    pub inheritable: u64,
+    pub ambient: u64,
+    last_cap: u32,
+}
+
+impl CapabilityPlan {
+    /// Resolve `spec` for this kernel and runtime. Capabilities the runtime
+    /// doesn't hold itself can't be granted and are left out with a warning.
+    pub fn from_spec(spec: &OciCapabilities) -> Self {
+        let available = current_permitted().unwrap_or(u64::MAX);
+        Self::resolve(spec, last_cap(), available)
+    }
+
+    /// `spec` limited to capabilities up to `last_cap` that are `available`.
+    /// A missing set is empty. Effective must lie within permitted, and
+    /// ambient within permitted and inheritable, or `capset`/`prctl` refuse.
+    fn resolve(spec: &OciCapabilities, last_cap: u32, available: u64) -> Self {
+        let known = if last_cap >= 63 {
+            u64::MAX
+        } else {
+            (1u64 << (last_cap + 1)) - 1
+        };
+        let grantable = known & available;
+        let limit = |set: &str, names: &[String]| {
+            let requested = mask(set, names) & known;
+            if requested & !grantable != 0 {
+                tracing::warn!(
+                    "capabilities {:#x} in {} set are not held by the runtime; leaving them out",
+                    requested & !grantable,
+                    set
+                );
+            }
+            requested & grantable
+        };
+        let permitted = limit("permitted", &spec.permitted);
+        let inheritable = limit("inheritable", &spec.inheritable);
+        Self {
+            bounding: mask("bounding", &spec.bounding) & known,
+            effective: limit("effective", &spec.effective) & permitted,
+            permitted,
+            inheritable,
+            ambient: limit("ambient", &spec.ambient) & permitted & inheritable,
+            last_cap,
+        }
+    }
+
+    /// Drop every capability outside the bounding set, and keep permitted
+    /// capabilities across the coming setuid. Call before switching uid.
+    pub fn narrow_bounding(&self) -> std::io::Result<()> {
+        for cap in 0..=self.last_cap {
+            if self.bounding & 1 << cap != 0 {
+                continue;
+            }
+            // SAFETY: prctl with integer arguments only
+            let present = unsafe { libc::prctl(libc::PR_CAPBSET_READ, cap as libc::c_ulong) };
+            if present != 1 {
+                continue;
+            }
+            if unsafe { libc::prctl(libc::PR_CAPBSET_DROP, cap as libc::c_ulong) } != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+        }
+        if unsafe { libc::prctl(libc::PR_SET_KEEPCAPS, 1 as libc::c_ulong) } != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// Set effective, permitted and inheritable, then the ambient set.
+    /// Call after switching uid.
+    pub fn apply(&self) -> std::io::Result<()> {
+        let mut header = CapHeader {
+            version: CAPABILITY_VERSION_3,
+            pid: 0,
+        };
+        let data = [
+            CapData {
+                effective: self.effective as u32,
+                permitted: self.permitted as u32,
+                inheritable: self.inheritable as u32,
+            },
+            CapData {
+                effective: (self.effective >> 32) as u32,
+                permitted: (self.permitted >> 32) as u32,
+                inheritable: (self.inheritable >> 32) as u32,
+            },
+        ];
+        // SAFETY: header and two data structs, as capset(2) expects for version 3
+        if unsafe { libc::syscall(libc::SYS_capset, &mut header, data.as_ptr()) } != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        let clear = unsafe {
+            libc::prctl(
+                libc::PR_CAP_AMBIENT,
+                libc::PR_CAP_AMBIENT_CLEAR_ALL as libc::c_ulong,
+                0 as libc::c_ulong,
+                0 as libc::c_ulong,
+                0 as libc::c_ulong,
+            )
+        };
+        // Kernels without ambient capabilities (< 4.3) have nothing to clear
+        if clear != 0 && self.ambient != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        for cap in 0..=self.last_cap {
+            if self.ambient & 1 << cap == 0 {
+                continue;
+            }
+            let raised = unsafe {
+                libc::prctl(
+                    libc::PR_CAP_AMBIENT,
+                    libc::PR_CAP_AMBIENT_RAISE as libc::c_ulong,
+                    cap as libc::c_ulong,
+                    0 as libc::c_ulong,
+                    0 as libc::c_ulong,
+                )
+            };
+            if raised != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn caps(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_capability_number() {
+        assert_eq!(capability_number("CAP_CHOWN"), Some(0));
+        assert_eq!(capability_number("CAP_NET_ADMIN"), Some(12));
+        assert_eq!(capability_number("CAP_CHECKPOINT_RESTORE"), Some(40));
+        assert_eq!(capability_number("NET_ADMIN"), None);
+        assert_eq!(capability_number("CAP_BOGUS"), None);
+    }
+
+    #[test]
+    fn test_grant() {
+        let mut spec = OciCapabilities {
+            bounding: caps(&["CAP_CHOWN", "CAP_SYS_ADMIN"]),
+            effective: caps(&["CAP_CHOWN"]),
+            permitted: caps(&["CAP_CHOWN"]),
+            ..Default::default()
+        };
+        grant(&mut spec, "CAP_SYS_ADMIN");
+        assert_eq!(spec.bounding, caps(&["CAP_CHOWN", "CAP_SYS_ADMIN"]));
+        assert_eq!(spec.permitted, caps(&["CAP_CHOWN", "CAP_SYS_ADMIN"]));
+        assert_eq!(spec.effective, caps(&["CAP_CHOWN", "CAP_SYS_ADMIN"]));
+        assert!(spec.inheritable.is_empty() && spec.ambient.is_empty());
+
+        let plan = CapabilityPlan::resolve(&spec, 40, u64::MAX);
+        assert_eq!(plan.effective, 1 | 1 << 21);
+    }
+
+    #[test]
+    fn test_resolve_drop_all() {
+        let plan = CapabilityPlan::resolve(&OciCapabilities::default(), 40, u64::MAX);
+        assert_eq!((plan.bounding, plan.effective, plan.permitted), (0, 0, 0));
+        assert_eq!((plan.inheritable, plan.ambient), (0, 0));
+    }
+
+    #[test]
+    fn test_resolve_sets() {
+        let spec = OciCapabilities {
+            bounding: caps(&["CAP_NET_ADMIN", "CAP_CHOWN", "CAP_BOGUS"]),
+            effective: caps(&["CAP_NET_ADMIN", "CAP_KILL"]),
+            permitted: caps(&["CAP_NET_ADMIN", "CAP_CHOWN"]),
+            inheritable: caps(&["CAP_NET_ADMIN"]),
+            ambient: caps(&["CAP_NET_ADMIN", "CAP_CHOWN"]),
+        };
+        let net_admin = 1 << 12;
+        let chown = 1;
+        let plan = CapabilityPlan::resolve(&spec, 40, u64::MAX);
+        assert_eq!(plan.bounding, net_admin | chown);
+        assert_eq!(plan.permitted, net_admin | chown);
+        // CAP_KILL is not permitted, CAP_CHOWN is not inheritable
+        assert_eq!(plan.effective, net_admin);
+        assert_eq!(plan.inheritable, net_admin);
+        assert_eq!(plan.ambient, net_admin);
+
+        // Capabilities past the kernel's last one, or not held, are left out
+        let plan = CapabilityPlan::resolve(&spec, 11, u64::MAX);
+        assert_eq!(plan.bounding, chown);
+        let plan = CapabilityPlan::resolve(&spec, 40, net_admin);
+        assert_eq!(plan.permitted, net_admin);
+    }
+}
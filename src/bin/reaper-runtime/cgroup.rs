@@ -0,0 +1,536 @@
+//! Placing the workload in the cgroup named by OCI `linux.cgroupsPath`.
+//!
+//! Kubernetes puts each container under its pod's cgroup so pod-level
+//! resource accounting works; reaper joins that cgroup (cgroup v2 only)
+//! instead of leaving the workload in the shim's.
+//!
+//! Memory and CPU limits and CPU shares from `linux.resources` are written
+//! to that cgroup before the workload joins it. The workload moves itself in
+//! between fork and exec, so nothing it forks can start outside. A container with limits but no
+//! `cgroupsPath` gets its own cgroup, `reaper/<id>`. The monitoring daemon
+//! removes the cgroup once the workload has exited.
+
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::path::{Component, Path, PathBuf};
+
+/// Mount point of the cgroup v2 unified hierarchy.
+pub const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+/// `cgroupsPath` for a container that has limits but no cgroupsPath.
+pub fn default_cgroups_path(container_id: &str) -> String {
+    format!("reaper/{}", container_id)
+}
+
+/// Period for `cpu.max` when `linux.resources.cpu` has no period.
+const DEFAULT_CPU_PERIOD: u64 = 100_000;
+
+/// cgroup v1 `cpu.shares` as cgroup v2 `cpu.weight`: the range 2–262144
+/// mapped linearly onto 1–10000, as runc and crun do.
+pub fn shares_to_weight(shares: u64) -> u64 {
+    let shares = shares.clamp(2, 262_144);
+    1 + ((shares - 2) * 9999) / 262_142
+}
+
+/// `cpu.max` value for `quota` microseconds per `period`. No quota, or a
+/// negative one (-1 in CRI), is unlimited.
+pub fn cpu_max(quota: Option<i64>, period: u64) -> String {
+    match quota.filter(|&q| q > 0) {
+        Some(quota) => format!("{} {}", quota, period),
+        None => format!("max {}", period),
+    }
+}
+
+/// Limits from `linux.resources`, as the values of the cgroup v2 files
+/// they go to.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CgroupLimits {
+    pub memory_max: Option<String>,
+    pub memory_swap_max: Option<String>,
+    pub cpu_max: Option<String>,
+    pub cpu_weight: Option<String>,
+}
+
+impl CgroupLimits {
+    /// Convert OCI resources to cgroup v2 values. As in runc, `memory.swap`
+    /// is memory plus swap, so `memory.swap.max` gets the difference; -1
+    /// means unlimited and 0 leaves a value unset. A `cpu` section always sets
+    /// `cpu.max`, to `max` without a quota.
+    pub fn from_resources(resources: &super::OciResources) -> Result<Self> {
+        let mut limits = Self::default();
+        if let Some(ref memory) = resources.memory {
+            let limit = memory.limit.filter(|&l| l != 0);
+            limits.memory_max = limit.map(|l| if l < 0 { "max".into() } else { l.to_string() });
+            limits.memory_swap_max = match (memory.swap.filter(|&s| s != 0), limit) {
+                (None, _) => None,
+                (Some(swap), _) if swap < 0 => Some("max".into()),
+                (Some(_), None) => bail!("memory.swap is set without memory.limit"),
+                (Some(_), Some(limit)) if limit < 0 => {
+                    bail!("memory.swap is limited while memory.limit is unlimited")
+                }
+                (Some(swap), Some(limit)) if swap < limit => bail!(
+                    "memory.swap ({}) is lower than memory.limit ({})",
+                    swap,
+                    limit
+                ),
+                (Some(swap), Some(limit)) => Some((swap - limit).to_string()),
+            };
+        }
+        if let Some(ref cpu) = resources.cpu {
+            let period = cpu.period.filter(|&p| p != 0).unwrap_or(DEFAULT_CPU_PERIOD);
+            limits.cpu_max = Some(cpu_max(cpu.quota, period));
+            limits.cpu_weight = cpu
+                .shares
+                .filter(|&s| s != 0)
+                .map(|shares| shares_to_weight(shares).to_string());
+        }
+        Ok(limits)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Controllers the limits need enabled on the cgroup's parents.
+    fn controllers(&self) -> Vec<&'static str> {
+        let mut controllers = Vec::new();
+        if self.memory_max.is_some() || self.memory_swap_max.is_some() {
+            controllers.push("memory");
+        }
+        if self.cpu_max.is_some() || self.cpu_weight.is_some() {
+            controllers.push("cpu");
+        }
+        controllers
+    }
+
+    /// (file, value) pairs to write.
+    fn files(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        [
+            ("memory.max", &self.memory_max),
+            ("memory.swap.max", &self.memory_swap_max),
+            ("cpu.max", &self.cpu_max),
+            ("cpu.weight", &self.cpu_weight),
+        ]
+        .into_iter()
+        .filter_map(|(file, value)| value.as_deref().map(|v| (file, v)))
+    }
+}
+
+/// `oom_kill` count from the contents of a `memory.events` file.
+fn parse_oom_kills(events: &str) -> Option<u64> {
+    events.lines().find_map(|line| {
+        let (key, value) = line.split_once(' ')?;
+        (key == "oom_kill").then(|| value.trim().parse().ok())?
+    })
+}
+
+/// Expand a systemd slice name into its nested path:
+/// `kubepods-besteffort.slice` → `kubepods.slice/kubepods-besteffort.slice`.
+fn expand_slice(slice: &str) -> Result<PathBuf> {
+    let Some(name) = slice.strip_suffix(".slice") else {
+        bail!("invalid slice name {:?}: must end in .slice", slice);
+    };
+    if name == "-" {
+        return Ok(PathBuf::new());
+    }
+    if name.is_empty() || name.contains('/') || name.starts_with('-') || name.contains("--") {
+        bail!("invalid slice name {:?}", slice);
+    }
+    let mut path = PathBuf::new();
+    let mut prefix = String::new();
+    for part in name.split('-') {
+        if !prefix.is_empty() {
+            prefix.push('-');
+        }
+        prefix.push_str(part);
+        path.push(format!("{}.slice", prefix));
+    }
+    Ok(path)
+}
+
+/// Turn `linux.cgroupsPath` into a path relative to [`CGROUP_ROOT`].
+///
+/// Accepts the systemd form `slice:prefix:name` (e.g.
+/// `kubepods-besteffort-pod123.slice:cri-containerd:<id>`, which becomes
+/// `.../kubepods-besteffort-pod123.slice/cri-containerd-<id>.scope`) and the
+/// cgroupfs form (`/kubepods/besteffort/pod123/<id>`).
+pub fn cgroup_relative_path(cgroups_path: &str) -> Result<PathBuf> {
+    let parts: Vec<&str> = cgroups_path.split(':').collect();
+    let path = match parts.as_slice() {
+        [slice, prefix, name] => {
+            let parent = if slice.is_empty() {
+                PathBuf::new()
+            } else {
+                expand_slice(slice)?
+            };
+            if name.is_empty() || name.contains('/') {
+                bail!("invalid cgroupsPath {:?}: bad unit name", cgroups_path);
+            }
+            let unit = if name.ends_with(".slice") {
+                name.to_string()
+            } else if prefix.is_empty() {
+                format!("{}.scope", name)
+            } else {
+                format!("{}-{}.scope", prefix, name)
+            };
+            parent.join(unit)
+        }
+        [path] => Path::new(path.trim_start_matches('/')).to_path_buf(),
+        _ => bail!(
+            "invalid cgroupsPath {:?}: expected slice:prefix:name or a path",
+            cgroups_path
+        ),
+    };
+    if path.as_os_str().is_empty() || !path.components().all(|c| matches!(c, Component::Normal(_)))
+    {
+        bail!("invalid cgroupsPath {:?}", cgroups_path);
+    }
+    Ok(path)
+}
+
+/// Move the calling process into the cgroup whose `cgroup.procs` is open as
+/// `procs_fd` (`"0"` means the writer). Only a write(2), so it is safe in
+/// `pre_exec`, between fork and exec.
+pub fn join_self(procs_fd: RawFd) -> std::io::Result<()> {
+    // SAFETY: writes one byte from a static buffer to a caller-owned fd
+    let n = unsafe { nix::libc::write(procs_fd, b"0".as_ptr().cast(), 1) };
+    if n < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// A created cgroup, with its `cgroup.procs` and parent directory opened
+/// ahead of time so the workload can move in, and the cgroup be removed,
+/// after the daemon has entered the overlay (where /sys/fs/cgroup may not
+/// be reachable).
+pub struct WorkloadCgroup {
+    pub path: PathBuf,
+    procs: fs::File,
+    parent: fs::File,
+}
+
+impl WorkloadCgroup {
+    /// Create (or reuse) the cgroup for `cgroups_path` under `root`.
+    pub fn prepare(root: &Path, cgroups_path: &str) -> Result<Self> {
+        if !root.join("cgroup.controllers").exists() {
+            bail!("{} is not a cgroup v2 hierarchy", root.display());
+        }
+        let path = root.join(cgroup_relative_path(cgroups_path)?);
+        fs::create_dir_all(&path).with_context(|| format!("creating {}", path.display()))?;
+        let procs_path = path.join("cgroup.procs");
+        let procs = fs::OpenOptions::new()
+            .write(true)
+            .open(&procs_path)
+            .with_context(|| format!("opening {}", procs_path.display()))?;
+        let parent_path = path.parent().unwrap_or(root);
+        let parent = fs::File::open(parent_path)
+            .with_context(|| format!("opening {}", parent_path.display()))?;
+        Ok(Self {
+            path,
+            procs,
+            parent,
+        })
+    }
+
+    /// Enable the controllers `limits` need on every cgroup between `root`
+    /// and this one (where not already enabled), then write the limits.
+    /// Call before the workload joins.
+    pub fn apply_limits(&self, root: &Path, limits: &CgroupLimits) -> Result<()> {
+        let controllers = limits.controllers();
+        let relative = self.path.strip_prefix(root).unwrap_or(&self.path);
+        let mut ancestor = root.to_path_buf();
+        for component in relative.components() {
+            let subtree_control = ancestor.join("cgroup.subtree_control");
+            let enabled = fs::read_to_string(&subtree_control).unwrap_or_default();
+            let missing: Vec<String> = controllers
+                .iter()
+                .filter(|c| !enabled.split_whitespace().any(|e| e == **c))
+                .map(|c| format!("+{}", c))
+                .collect();
+            if !missing.is_empty() {
+                fs::write(&subtree_control, missing.join(" ")).with_context(|| {
+                    format!("enabling {:?} in {}", missing, subtree_control.display())
+                })?;
+            }
+            ancestor.push(component);
+        }
+        for (file, value) in limits.files() {
+            let path = self.path.join(file);
+            fs::write(&path, value)
+                .with_context(|| format!("writing {} to {}", value, path.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Remove the cgroup. Fails while it still holds processes.
+    pub fn remove(&self) -> Result<()> {
+        let name = self.path.file_name().context("cgroup path has no name")?;
+        nix::unistd::unlinkat(
+            Some(self.parent.as_raw_fd()),
+            name,
+            nix::unistd::UnlinkatFlags::RemoveDir,
+        )
+        .with_context(|| format!("removing {}", self.path.display()))
+    }
+
+    /// Processes the OOM killer has killed in the cgroup (`oom_kill` in
+    /// `memory.events`). None without the memory controller.
+    pub fn oom_kills(&self) -> Option<u64> {
+        let events = fs::read_to_string(self.path.join("memory.events")).ok()?;
+        parse_oom_kills(&events)
+    }
+
+    /// `cgroup.procs` for the workload to pass to [`join_self`].
+    pub fn procs_fd(&self) -> RawFd {
+        self.procs.as_raw_fd()
+    }
+
+    /// Whether `pid` is in the cgroup. Read through the parent directory,
+    /// which stays reachable inside the overlay.
+    pub fn holds(&self, pid: i32) -> bool {
+        use nix::fcntl::{openat, OFlag};
+        use nix::sys::stat::Mode;
+        use std::io::Read;
+
+        let Some(name) = self.path.file_name() else {
+            return false;
+        };
+        let Ok(fd) = openat(
+            Some(self.parent.as_raw_fd()),
+            &Path::new(name).join("cgroup.procs"),
+            OFlag::O_RDONLY | OFlag::O_CLOEXEC,
+            Mode::empty(),
+        ) else {
+            return false;
+        };
+        // SAFETY: openat returned a fresh fd that nothing else owns
+        let mut file = unsafe { fs::File::from_raw_fd(fd) };
+        let mut procs = String::new();
+        if file.read_to_string(&mut procs).is_err() {
+            return false;
+        }
+        let pid = pid.to_string();
+        procs.lines().any(|line| line.trim() == pid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cgroup_relative_path_systemd() {
+        assert_eq!(
+            cgroup_relative_path("kubepods-besteffort-pod1234.slice:cri-containerd:abc123")
+                .unwrap(),
+            PathBuf::from(
+                "kubepods.slice/kubepods-besteffort.slice/\
+                 kubepods-besteffort-pod1234.slice/cri-containerd-abc123.scope"
+            )
+        );
+        assert_eq!(
+            cgroup_relative_path("system.slice:docker:abc").unwrap(),
+            PathBuf::from("system.slice/docker-abc.scope")
+        );
+        assert_eq!(
+            cgroup_relative_path("-.slice::abc").unwrap(),
+            PathBuf::from("abc.scope")
+        );
+        assert_eq!(
+            cgroup_relative_path("kubepods.slice::kubepods-burstable.slice").unwrap(),
+            PathBuf::from("kubepods.slice/kubepods-burstable.slice")
+        );
+    }
+
+    #[test]
+    fn test_cgroup_relative_path_cgroupfs() {
+        assert_eq!(
+            cgroup_relative_path("/kubepods/besteffort/pod1234/abc123").unwrap(),
+            PathBuf::from("kubepods/besteffort/pod1234/abc123")
+        );
+        assert_eq!(
+            cgroup_relative_path("reaper/abc").unwrap(),
+            PathBuf::from("reaper/abc")
+        );
+    }
+
+    #[test]
+    fn test_cgroup_relative_path_rejects_invalid() {
+        assert!(cgroup_relative_path("").is_err());
+        assert!(cgroup_relative_path("/").is_err());
+        assert!(cgroup_relative_path("/kubepods/../etc").is_err());
+        assert!(cgroup_relative_path("kubepods:abc").is_err());
+        assert!(cgroup_relative_path("kubepods:cri:abc").is_err());
+        assert!(cgroup_relative_path("kube--pods.slice:cri:abc").is_err());
+        assert!(cgroup_relative_path("kubepods.slice:cri:a/b").is_err());
+    }
+
+    fn resources(json: serde_json::Value) -> crate::OciResources {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_limits_from_resources() {
+        let limits = CgroupLimits::from_resources(&resources(serde_json::json!({
+            "memory": {"limit": 268435456, "swap": 536870912},
+            "cpu": {"quota": 50000, "period": 100000, "shares": 512}
+        })))
+        .unwrap();
+        assert_eq!(
+            limits,
+            CgroupLimits {
+                memory_max: Some("268435456".into()),
+                memory_swap_max: Some("268435456".into()),
+                cpu_max: Some("50000 100000".into()),
+                cpu_weight: Some("20".into()),
+            }
+        );
+
+        let limits = CgroupLimits::from_resources(&resources(serde_json::json!({
+            "memory": {"limit": 1048576},
+            "cpu": {"quota": 20000}
+        })))
+        .unwrap();
+        assert_eq!(limits.memory_max.as_deref(), Some("1048576"));
+        assert_eq!(limits.memory_swap_max, None);
+        assert_eq!(limits.cpu_max.as_deref(), Some("20000 100000"));
+
+        let limits = CgroupLimits::from_resources(&resources(serde_json::json!({
+            "memory": {"limit": -1, "swap": -1},
+            "cpu": {"quota": -1, "period": 50000}
+        })))
+        .unwrap();
+        assert_eq!(limits.memory_max.as_deref(), Some("max"));
+        assert_eq!(limits.memory_swap_max.as_deref(), Some("max"));
+        assert_eq!(limits.cpu_max.as_deref(), Some("max 50000"));
+
+        // Shares alone: weighted, with unlimited CPU time
+        let limits = CgroupLimits::from_resources(&resources(serde_json::json!({
+            "cpu": {"shares": 1024}
+        })))
+        .unwrap();
+        assert_eq!(limits.cpu_max.as_deref(), Some("max 100000"));
+        assert_eq!(limits.cpu_weight.as_deref(), Some("39"));
+
+        assert!(
+            CgroupLimits::from_resources(&resources(serde_json::json!({})))
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_parse_oom_kills() {
+        let events = "low 0\nhigh 0\nmax 12\noom 3\noom_kill 2\noom_group_kill 0\n";
+        assert_eq!(parse_oom_kills(events), Some(2));
+        assert_eq!(parse_oom_kills("low 0\nhigh 0\n"), None);
+        assert_eq!(parse_oom_kills("oom_kill x\n"), None);
+    }
+
+    #[test]
+    fn test_shares_to_weight() {
+        assert_eq!(shares_to_weight(2), 1);
+        assert_eq!(shares_to_weight(1024), 39);
+        assert_eq!(shares_to_weight(262_144), 10_000);
+        // Out of range shares are clamped
+        assert_eq!(shares_to_weight(0), 1);
+        assert_eq!(shares_to_weight(1_000_000), 10_000);
+    }
+
+    #[test]
+    fn test_cpu_max_format() {
+        assert_eq!(cpu_max(Some(50_000), 100_000), "50000 100000");
+        assert_eq!(cpu_max(Some(-1), 100_000), "max 100000");
+        assert_eq!(cpu_max(None, 50_000), "max 50000");
+        assert_eq!(cpu_max(Some(0), 100_000), "max 100000");
+    }
+
+    #[test]
+    fn test_limits_reject_inconsistent_swap() {
+        for memory in [
+            serde_json::json!({"swap": 1048576}),
+            serde_json::json!({"limit": -1, "swap": 1048576}),
+            serde_json::json!({"limit": 2097152, "swap": 1048576}),
+        ] {
+            let json = serde_json::json!({ "memory": memory });
+            assert!(
+                CgroupLimits::from_resources(&resources(json.clone())).is_err(),
+                "{}",
+                json
+            );
+        }
+    }
+
+    #[test]
+    fn test_apply_limits_and_remove() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        fs::write(root.join("cgroup.controllers"), "cpu memory").unwrap();
+        fs::write(root.join("cgroup.subtree_control"), "cpu memory").unwrap();
+        let leaf = root.join("reaper/abc");
+        fs::create_dir_all(&leaf).unwrap();
+        fs::write(leaf.join("cgroup.procs"), "").unwrap();
+
+        let cgroup = WorkloadCgroup::prepare(root, &default_cgroups_path("abc")).unwrap();
+        let limits = CgroupLimits {
+            memory_max: Some("268435456".into()),
+            memory_swap_max: Some("0".into()),
+            cpu_max: None,
+            cpu_weight: None,
+        };
+        cgroup.apply_limits(root, &limits).unwrap();
+        assert_eq!(
+            fs::read_to_string(leaf.join("memory.max")).unwrap(),
+            "268435456"
+        );
+        assert_eq!(
+            fs::read_to_string(leaf.join("memory.swap.max")).unwrap(),
+            "0"
+        );
+        assert!(!leaf.join("cpu.max").exists());
+        // Enabled where missing, left alone where already enabled
+        assert_eq!(
+            fs::read_to_string(root.join("reaper/cgroup.subtree_control")).unwrap(),
+            "+memory"
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("cgroup.subtree_control")).unwrap(),
+            "cpu memory"
+        );
+        assert!(!leaf.join("cgroup.subtree_control").exists());
+
+        // Not empty, as a real cgroup with processes would refuse
+        assert!(cgroup.remove().is_err());
+        for file in ["cgroup.procs", "memory.max", "memory.swap.max"] {
+            fs::remove_file(leaf.join(file)).unwrap();
+        }
+        cgroup.remove().unwrap();
+        assert!(!leaf.exists());
+    }
+
+    #[test]
+    fn test_prepare_requires_cgroup_v2() {
+        let tmp = tempfile::tempdir().unwrap();
+        let err = WorkloadCgroup::prepare(tmp.path(), "/kubepods/abc")
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("cgroup v2"), "{}", err);
+
+        fs::write(tmp.path().join("cgroup.controllers"), "").unwrap();
+        fs::create_dir_all(tmp.path().join("kubepods/abc")).unwrap();
+        fs::write(tmp.path().join("kubepods/abc/cgroup.procs"), "").unwrap();
+        let cgroup = WorkloadCgroup::prepare(tmp.path(), "/kubepods/abc").unwrap();
+        assert_eq!(cgroup.path, tmp.path().join("kubepods/abc"));
+        join_self(cgroup.procs_fd()).unwrap();
+        assert_eq!(
+            fs::read_to_string(tmp.path().join("kubepods/abc/cgroup.procs")).unwrap(),
+            "0"
+        );
+        assert!(!cgroup.holds(4242));
+        fs::write(tmp.path().join("kubepods/abc/cgroup.procs"), "17\n4242\n").unwrap();
+        assert!(cgroup.holds(4242));
+        assert!(!cgroup.holds(424));
+    }
+}
@@ -0,0 +1,176 @@
+//! Container output in the CRI log format (`REAPER_CRI_LOG_FORMAT`).
+//!
+//! When a container's stdout/stderr goes to a regular file rather than
+//! containerd's FIFO, each line is written the way kubelet stores logs, so
+//! `kubectl logs` and log collectors can parse the file as is:
+//!
+//! ```text
+//! 2026-01-02T03:04:05.123456789Z stdout F a complete line
+//! 2026-01-02T03:04:05.123456789Z stderr P the start of a line
+//! 2026-01-02T03:04:05.200000000Z stderr F and its end
+//! ```
+//!
+//! `F` marks the end of a line, `P` a part continued by the next entry: the
+//! output is not buffered, so text read without its newline is written as a
+//! partial entry straight away.
+
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Whether `REAPER_CRI_LOG_FORMAT` asks for CRI formatted log files.
+pub fn enabled() -> bool {
+    std::env::var("REAPER_CRI_LOG_FORMAT")
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+/// `time` as RFC 3339 in UTC with nanoseconds, e.g.
+/// `2026-01-02T03:04:05.000000006Z`.
+pub fn rfc3339_nano(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    // Civil date from days since the epoch (Howard Hinnant's algorithm)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:09}Z",
+        year,
+        month,
+        day,
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60,
+        since_epoch.subsec_nanos()
+    )
+}
+
+/// `chunk` of `stream` output as CRI log entries stamped `timestamp`: one
+/// `F` entry per line it ends, and a `P` entry for trailing text without a
+/// newline.
+pub fn format_chunk(chunk: &[u8], stream: &str, timestamp: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(chunk.len() + 48);
+    let mut rest = chunk;
+    while !rest.is_empty() {
+        let (line, tag, next) = match rest.iter().position(|&b| b == b'\n') {
+            Some(end) => (&rest[..end], "F", &rest[end + 1..]),
+            None => (rest, "P", &rest[rest.len()..]),
+        };
+        out.extend_from_slice(format!("{} {} {} ", timestamp, stream, tag).as_bytes());
+        out.extend_from_slice(line);
+        out.push(b'\n');
+        rest = next;
+    }
+    out
+}
+
+/// Writer that stores everything written to it as CRI log entries of one
+/// stream. Meant for a relay, where each write is one chunk read from the
+/// workload.
+pub struct CriLogWriter<W> {
+    inner: W,
+    stream: &'static str,
+}
+
+impl<W: Write> CriLogWriter<W> {
+    /// `stream` is `stdout` or `stderr`.
+    pub fn new(inner: W, stream: &'static str) -> Self {
+        Self { inner, stream }
+    }
+}
+
+impl<W: Write> Write for CriLogWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let timestamp = rfc3339_nano(SystemTime::now());
+        self.inner
+            .write_all(&format_chunk(buf, self.stream, &timestamp))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const TS: &str = "2026-01-02T03:04:05.000000006Z";
+
+    fn format(chunk: &str, stream: &str) -> String {
+        String::from_utf8(format_chunk(chunk.as_bytes(), stream, TS)).unwrap()
+    }
+
+    #[test]
+    fn test_rfc3339_nano() {
+        assert_eq!(rfc3339_nano(UNIX_EPOCH), "1970-01-01T00:00:00.000000000Z");
+        let time = UNIX_EPOCH + Duration::new(1_767_323_045, 6);
+        assert_eq!(rfc3339_nano(time), TS);
+        // Leap day
+        let time = UNIX_EPOCH + Duration::new(951_782_400, 123_456_789);
+        assert_eq!(rfc3339_nano(time), "2000-02-29T00:00:00.123456789Z");
+    }
+
+    #[test]
+    fn test_format_full_lines() {
+        assert_eq!(
+            format("one\ntwo\n", "stdout"),
+            format!("{TS} stdout F one\n{TS} stdout F two\n")
+        );
+        // An empty line is still a line
+        assert_eq!(format("\n", "stderr"), format!("{TS} stderr F \n"));
+        assert_eq!(format("", "stdout"), "");
+    }
+
+    #[test]
+    fn test_format_partial_lines() {
+        // A line split across chunks: P for the start, F where it ends
+        assert_eq!(
+            format("done\nprogress 5", "stdout"),
+            format!("{TS} stdout F done\n{TS} stdout P progress 5\n")
+        );
+        assert_eq!(format("0%\n", "stdout"), format!("{TS} stdout F 0%\n"));
+        assert_eq!(
+            format("no newline", "stderr"),
+            format!("{TS} stderr P no newline\n")
+        );
+    }
+
+    #[test]
+    fn test_writer_tags_each_chunk() {
+        let mut writer = CriLogWriter::new(Vec::new(), "stderr");
+        writer.write_all(b"abc").unwrap();
+        writer.write_all(b"def\nghi\n").unwrap();
+        let out = String::from_utf8(writer.inner).unwrap();
+        let entries: Vec<(&str, &str, &str)> = out
+            .lines()
+            .map(|l| {
+                let mut parts = l.splitn(4, ' ');
+                let ts = parts.next().unwrap();
+                assert!(ts.ends_with('Z') && ts.len() == TS.len(), "{}", ts);
+                (
+                    parts.next().unwrap(),
+                    parts.next().unwrap(),
+                    parts.next().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                ("stderr", "P", "abc"),
+                ("stderr", "F", "def"),
+                ("stderr", "F", "ghi")
+            ]
+        );
+    }
+}
@@ -0,0 +1,598 @@
+//! Reaper's own processes on the node, for `reaper-runtime diag`.
+//!
+//! Three kinds of long-lived processes pile up on a busy node: one shim per
+//! pod, one monitoring daemon per running workload or exec, and one anchor
+//! helper per overlay namespace. They are found by scanning `/proc` for the
+//! shim and runtime binaries and told apart using what reaper recorded: a
+//! daemon is the parent of a workload or exec PID in state, an anchor is the
+//! PID in a namespace's `.pid` file. Forked daemons and helpers keep the
+//! command line of `reaper-runtime start <id>` (or `exec <id>`), which names
+//! the container they were started for.
+//!
+//! Processes that fit no record are reported as suspected leaks: daemons
+//! whose container state is gone, and helpers holding an overlay namespace
+//! nobody recorded. Classification works on parsed entries, so it is tested
+//! without a real `/proc`.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+/// Binary name of the containerd shim.
+pub const SHIM_BINARY: &str = "containerd-shim-reaper-v2";
+/// Binary name of the runtime, also carried by its daemons and helpers.
+pub const RUNTIME_BINARY: &str = "reaper-runtime";
+
+/// A process from `/proc`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcEntry {
+    pub pid: i32,
+    pub ppid: i32,
+    /// File name of `argv[0]`
+    pub binary: String,
+    /// `argv[1..]`
+    pub args: Vec<String>,
+    /// Resident memory (`VmRSS`), in KiB
+    pub rss_kb: u64,
+    /// Inode of the mount namespace, if readable
+    pub mnt_ns: Option<u64>,
+}
+
+/// Parse a process from its `/proc/<pid>/status` and `cmdline`. Kernel
+/// threads (empty command line) and unparseable entries are `None`.
+pub fn parse_proc_entry(
+    pid: i32,
+    status: &str,
+    cmdline: &[u8],
+    mnt_ns: Option<u64>,
+) -> Option<ProcEntry> {
+    let field = |name: &str| {
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+            .map(str::trim)
+    };
+    let ppid = field("PPid")?.parse().ok()?;
+    // Absent for zombies
+    let rss_kb = field("VmRSS")
+        .and_then(|v| v.trim_end_matches("kB").trim().parse().ok())
+        .unwrap_or(0);
+    let mut argv = cmdline
+        .split(|&b| b == 0)
+        .filter(|arg| !arg.is_empty())
+        .map(|arg| String::from_utf8_lossy(arg).into_owned());
+    let argv0 = argv.next()?;
+    let binary = Path::new(&argv0)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or(argv0);
+    Some(ProcEntry {
+        pid,
+        ppid,
+        binary,
+        args: argv.collect(),
+        rss_kb,
+        mnt_ns,
+    })
+}
+
+/// Every process under `proc_root` (normally `/proc`) that can be read.
+pub fn scan_proc(proc_root: &Path) -> Vec<ProcEntry> {
+    let Ok(entries) = fs::read_dir(proc_root) else {
+        return Vec::new();
+    };
+    let mut procs: Vec<ProcEntry> = entries
+        .flatten()
+        .filter_map(|e| {
+            let pid: i32 = e.file_name().to_str()?.parse().ok()?;
+            let dir = e.path();
+            let status = fs::read_to_string(dir.join("status")).ok()?;
+            let cmdline = fs::read(dir.join("cmdline")).ok()?;
+            let mnt_ns = fs::metadata(dir.join("ns/mnt")).ok().map(|m| m.ino());
+            parse_proc_entry(pid, &status, &cmdline, mnt_ns)
+        })
+        .collect();
+    procs.sort_by_key(|p| p.pid);
+    procs
+}
+
+/// What reaper recorded about its containers and namespaces.
+#[derive(Debug, Default)]
+pub struct Records {
+    /// Container IDs with state, and whether each has stopped
+    pub containers: HashMap<String, bool>,
+    /// Workload and exec PIDs from state, with the container (or
+    /// `<container>/<exec>`) they belong to
+    pub workloads: HashMap<i32, String>,
+    /// Anchor helpers from the namespaces' `.pid` files: PID to namespace
+    /// path and namespace inode
+    pub anchors: HashMap<i32, (PathBuf, u64)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// containerd-shim-reaper-v2
+    Shim,
+    /// Monitoring daemon of a workload or exec
+    Daemon,
+    /// Anchor helper of an overlay namespace
+    Anchor,
+    /// Any other reaper-runtime process, e.g. a command in progress
+    Other,
+}
+
+const ROLES: [Role; 4] = [Role::Shim, Role::Daemon, Role::Anchor, Role::Other];
+
+/// A reaper process and what it is for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReaperProcess {
+    pub pid: i32,
+    pub role: Role,
+    pub rss_kb: u64,
+    /// Container ID, or namespace path for an anchor
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    /// Why the process looks leaked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suspect: Option<String>,
+}
+
+/// Container a shim serves: its `-id` argument.
+fn shim_container(args: &[String]) -> Option<&str> {
+    let pos = args.iter().position(|a| a == "-id" || a == "--id")?;
+    args.get(pos + 1).map(String::as_str)
+}
+
+/// Container a runtime process was started for: the ID after its `start`
+/// or `exec` subcommand (kept by forked daemons and helpers).
+fn command_container(args: &[String]) -> Option<&str> {
+    let pos = args.iter().position(|a| a == "start" || a == "exec")?;
+    args[pos + 1..]
+        .iter()
+        .find(|a| !a.starts_with('-'))
+        .map(String::as_str)
+}
+
+/// Classify the reaper processes among `procs`. `host_mnt_ns` is the mount
+/// namespace of the caller (the host's), `self_pid` the caller itself, which
+/// is left out.
+pub fn classify(
+    procs: &[ProcEntry],
+    records: &Records,
+    host_mnt_ns: Option<u64>,
+    self_pid: i32,
+) -> Vec<ReaperProcess> {
+    let recorded_ns: Vec<u64> = records.anchors.values().map(|(_, ino)| *ino).collect();
+    let mut found = Vec::new();
+    for proc in procs {
+        if proc.pid == self_pid {
+            continue;
+        }
+        let entry = |role, subject: Option<String>, suspect: Option<String>| ReaperProcess {
+            pid: proc.pid,
+            role,
+            rss_kb: proc.rss_kb,
+            subject,
+            suspect,
+        };
+        if proc.binary == SHIM_BINARY {
+            let id = shim_container(&proc.args);
+            let suspect = id
+                .filter(|id| !records.containers.contains_key(*id))
+                .map(|id| format!("no container state for {}", id));
+            found.push(entry(Role::Shim, id.map(str::to_string), suspect));
+            continue;
+        }
+        if proc.binary != RUNTIME_BINARY {
+            continue;
+        }
+
+        if let Some((ns_path, inode)) = records.anchors.get(&proc.pid) {
+            // A different namespace means the recorded PID was recycled
+            if proc.mnt_ns == Some(*inode) {
+                let ns = ns_path.display().to_string();
+                found.push(entry(Role::Anchor, Some(ns), None));
+                continue;
+            }
+        }
+
+        let children: Vec<i32> = procs
+            .iter()
+            .filter(|p| p.ppid == proc.pid)
+            .map(|p| p.pid)
+            .collect();
+        if let Some(owner) = children.iter().find_map(|pid| records.workloads.get(pid)) {
+            let container = owner.split('/').next().unwrap_or(owner);
+            let suspect = match records.containers.get(container) {
+                Some(true) => Some(format!("container {} is stopped", container)),
+                Some(false) => None,
+                None => Some(format!("container {} was deleted", container)),
+            };
+            found.push(entry(Role::Daemon, Some(owner.clone()), suspect));
+            continue;
+        }
+
+        let id = command_container(&proc.args);
+        let in_overlay = proc.mnt_ns.is_some() && proc.mnt_ns != host_mnt_ns;
+        if in_overlay && !proc.mnt_ns.is_some_and(|ns| recorded_ns.contains(&ns)) {
+            found.push(entry(
+                Role::Anchor,
+                id.map(str::to_string),
+                Some("orphaned anchor: holds an overlay namespace nobody recorded".to_string()),
+            ));
+            continue;
+        }
+        if !children.is_empty() {
+            // Monitoring something that is in no state file
+            let suspect = format!(
+                "monitors pid {} of no known container{}",
+                children[0],
+                id.map(|id| format!(" (started for {})", id))
+                    .unwrap_or_default()
+            );
+            found.push(entry(Role::Daemon, id.map(str::to_string), Some(suspect)));
+            continue;
+        }
+        match id {
+            // A daemon between spawning and recording its workload, or one
+            // whose workload has just exited
+            Some(id) if in_overlay => {
+                let suspect = (!records.containers.contains_key(id))
+                    .then(|| format!("container {} was deleted", id));
+                found.push(entry(Role::Daemon, Some(id.to_string()), suspect));
+            }
+            _ => found.push(entry(Role::Other, id.map(str::to_string), None)),
+        }
+    }
+    found
+}
+
+/// Anchor records whose helper is gone (or its PID recycled): the namespace
+/// can't be joined through them any more.
+pub fn dead_anchor_records(procs: &[ProcEntry], records: &Records) -> Vec<PathBuf> {
+    let mut dead: Vec<PathBuf> = records
+        .anchors
+        .iter()
+        .filter(|(pid, (_, inode))| {
+            !procs
+                .iter()
+                .any(|p| p.pid == **pid && p.mnt_ns == Some(*inode))
+        })
+        .map(|(_, (ns_path, _))| ns_path.clone())
+        .collect();
+    dead.sort();
+    dead
+}
+
+/// Process count and total RSS of one role.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RoleSummary {
+    pub role: Role,
+    pub count: usize,
+    pub rss_kb: u64,
+}
+
+/// Totals per role, in a fixed order, including empty roles.
+pub fn summarize(found: &[ReaperProcess]) -> Vec<RoleSummary> {
+    ROLES
+        .iter()
+        .map(|&role| {
+            let of_role = found.iter().filter(|p| p.role == role);
+            RoleSummary {
+                role,
+                count: of_role.clone().count(),
+                rss_kb: of_role.map(|p| p.rss_kb).sum(),
+            }
+        })
+        .collect()
+}
+
+/// Human-readable report: totals per role, then suspected leaks.
+pub fn render_report(found: &[ReaperProcess], dead_anchors: &[PathBuf]) -> String {
+    let mut out = String::new();
+    let summary = summarize(found);
+    for s in &summary {
+        out.push_str(&format!(
+            "{:<8} {:>5} processes {:>10} KiB\n",
+            format!("{:?}", s.role).to_lowercase(),
+            s.count,
+            s.rss_kb
+        ));
+    }
+    out.push_str(&format!(
+        "{:<8} {:>5} processes {:>10} KiB\n",
+        "total",
+        found.len(),
+        summary.iter().map(|s| s.rss_kb).sum::<u64>()
+    ));
+    let suspects: Vec<&ReaperProcess> = found.iter().filter(|p| p.suspect.is_some()).collect();
+    if suspects.is_empty() && dead_anchors.is_empty() {
+        out.push_str("\nno suspected leaks\n");
+        return out;
+    }
+    out.push_str("\nsuspected leaks:\n");
+    for p in suspects {
+        out.push_str(&format!(
+            "  pid {} ({}, {} KiB): {}\n",
+            p.pid,
+            format!("{:?}", p.role).to_lowercase(),
+            p.rss_kb,
+            p.suspect.as_deref().unwrap_or_default()
+        ));
+    }
+    for ns in dead_anchors {
+        out.push_str(&format!(
+            "  {}: anchor helper is gone; the next container joining re-anchors or recreates it\n",
+            ns.display()
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(ppid: i32, rss_kb: Option<u64>) -> String {
+        let rss = rss_kb
+            .map(|kb| format!("VmRSS:\t    {} kB\n", kb))
+            .unwrap_or_default();
+        format!(
+            "Name:\tx\nState:\tS (sleeping)\nPPid:\t{}\n{}Threads:\t1\n",
+            ppid, rss
+        )
+    }
+
+    fn proc(pid: i32, ppid: i32, cmdline: &[&str], rss_kb: u64, mnt_ns: u64) -> ProcEntry {
+        let cmdline = cmdline.join("\0");
+        parse_proc_entry(
+            pid,
+            &status(ppid, Some(rss_kb)),
+            cmdline.as_bytes(),
+            Some(mnt_ns),
+        )
+        .unwrap()
+    }
+
+    const HOST_NS: u64 = 4026531840;
+    const OVERLAY_NS: u64 = 4026532500;
+
+    #[test]
+    fn test_parse_proc_entry() {
+        let entry = parse_proc_entry(
+            42,
+            &status(7, Some(2048)),
+            b"/usr/local/bin/reaper-runtime\0--root\0/run/reaper\0start\0abc\0",
+            Some(HOST_NS),
+        )
+        .unwrap();
+        assert_eq!(entry.pid, 42);
+        assert_eq!(entry.ppid, 7);
+        assert_eq!(entry.binary, "reaper-runtime");
+        assert_eq!(entry.args, ["--root", "/run/reaper", "start", "abc"]);
+        assert_eq!(entry.rss_kb, 2048);
+        assert_eq!(command_container(&entry.args), Some("abc"));
+
+        // Zombie: no VmRSS
+        let zombie = parse_proc_entry(43, &status(1, None), b"sleep\0", None).unwrap();
+        assert_eq!((zombie.binary.as_str(), zombie.rss_kb), ("sleep", 0));
+        // Kernel thread
+        assert_eq!(parse_proc_entry(2, &status(0, None), b"", None), None);
+        assert_eq!(parse_proc_entry(2, "garbage", b"x\0", None), None);
+    }
+
+    #[test]
+    fn test_scan_synthetic_proc() {
+        let root = tempfile::tempdir().unwrap();
+        for (pid, cmdline) in [("100", "containerd-shim-reaper-v2\0-id\0pod"), ("7", "")] {
+            let dir = root.path().join(pid);
+            fs::create_dir_all(dir.join("ns")).unwrap();
+            fs::write(dir.join("status"), status(1, Some(512))).unwrap();
+            fs::write(dir.join("cmdline"), cmdline).unwrap();
+            fs::write(dir.join("ns/mnt"), "").unwrap();
+        }
+        fs::create_dir(root.path().join("self")).unwrap();
+        let procs = scan_proc(root.path());
+        assert_eq!(procs.len(), 1);
+        assert_eq!(procs[0].binary, SHIM_BINARY);
+        assert_eq!(shim_container(&procs[0].args), Some("pod"));
+        assert!(procs[0].mnt_ns.is_some());
+    }
+
+    #[test]
+    fn test_classify() {
+        let procs = vec![
+            proc(1, 0, &["/sbin/init"], 9000, HOST_NS),
+            // Shims: one for a live pod, one for a pod whose state is gone
+            proc(
+                100,
+                1,
+                &[SHIM_BINARY, "-namespace", "k8s.io", "-id", "pod1"],
+                8000,
+                HOST_NS,
+            ),
+            proc(101, 1, &[SHIM_BINARY, "-id", "gone-pod"], 7000, HOST_NS),
+            // Daemon of a running container, and its workload
+            proc(200, 100, &[RUNTIME_BINARY, "start", "c1"], 1500, OVERLAY_NS),
+            proc(201, 200, &["sleep", "infinity"], 300, OVERLAY_NS),
+            // Recorded anchor of the namespace
+            proc(300, 100, &[RUNTIME_BINARY, "start", "c0"], 900, OVERLAY_NS),
+            // Daemon still monitoring a workload whose container was deleted
+            proc(
+                400,
+                100,
+                &[RUNTIME_BINARY, "start", "deleted"],
+                1400,
+                OVERLAY_NS,
+            ),
+            proc(401, 400, &["python", "job.py"], 40000, OVERLAY_NS),
+            // Helper left in a namespace that is no longer recorded
+            proc(500, 1, &[RUNTIME_BINARY, "start", "old"], 800, 4026532999),
+            // A command in progress, and the diag process itself
+            proc(600, 100, &[RUNTIME_BINARY, "state", "c1"], 1000, HOST_NS),
+            proc(700, 1, &[RUNTIME_BINARY, "diag"], 1000, HOST_NS),
+            // Daemon whose container stopped but which hasn't exited
+            proc(
+                800,
+                100,
+                &[RUNTIME_BINARY, "start", "done"],
+                1200,
+                OVERLAY_NS,
+            ),
+        ];
+        let records = Records {
+            containers: HashMap::from([
+                ("pod1".to_string(), false),
+                ("c1".to_string(), false),
+                ("done".to_string(), true),
+            ]),
+            workloads: HashMap::from([(201, "c1".to_string())]),
+            anchors: HashMap::from([
+                (300, (PathBuf::from("/run/reaper/ns/default"), OVERLAY_NS)),
+                // Helper died; its PID now belongs to init's namespace
+                (1, (PathBuf::from("/run/reaper/ns/team-a"), 4026532777)),
+            ]),
+        };
+
+        let found = classify(&procs, &records, Some(HOST_NS), 700);
+        let by_pid: HashMap<i32, &ReaperProcess> = found.iter().map(|p| (p.pid, p)).collect();
+        assert_eq!(found.len(), 8, "{:#?}", found);
+        assert!(!by_pid.contains_key(&700) && !by_pid.contains_key(&201));
+
+        assert_eq!(by_pid[&100].role, Role::Shim);
+        assert_eq!(by_pid[&100].suspect, None);
+        assert_eq!(by_pid[&101].role, Role::Shim);
+        assert_eq!(
+            by_pid[&101].suspect.as_deref(),
+            Some("no container state for gone-pod")
+        );
+        assert_eq!(by_pid[&200].role, Role::Daemon);
+        assert_eq!(by_pid[&200].subject.as_deref(), Some("c1"));
+        assert_eq!(by_pid[&200].suspect, None);
+        assert_eq!(by_pid[&300].role, Role::Anchor);
+        assert_eq!(
+            by_pid[&300].subject.as_deref(),
+            Some("/run/reaper/ns/default")
+        );
+        assert_eq!(by_pid[&300].suspect, None);
+        assert_eq!(by_pid[&400].role, Role::Daemon);
+        assert!(by_pid[&400].suspect.as_deref().unwrap().contains("pid 401"));
+        assert_eq!(by_pid[&500].role, Role::Anchor);
+        assert!(by_pid[&500]
+            .suspect
+            .as_deref()
+            .unwrap()
+            .contains("orphaned anchor"));
+        assert_eq!(by_pid[&600].role, Role::Other);
+        assert_eq!(by_pid[&600].suspect, None);
+        assert_eq!(by_pid[&800].role, Role::Daemon);
+        assert_eq!(by_pid[&800].suspect, None);
+
+        assert_eq!(
+            dead_anchor_records(&procs, &records),
+            [PathBuf::from("/run/reaper/ns/team-a")]
+        );
+
+        let summary = summarize(&found);
+        assert_eq!(
+            summary,
+            [
+                RoleSummary {
+                    role: Role::Shim,
+                    count: 2,
+                    rss_kb: 15000
+                },
+                RoleSummary {
+                    role: Role::Daemon,
+                    count: 3,
+                    rss_kb: 4100
+                },
+                RoleSummary {
+                    role: Role::Anchor,
+                    count: 2,
+                    rss_kb: 1700
+                },
+                RoleSummary {
+                    role: Role::Other,
+                    count: 1,
+                    rss_kb: 1000
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_daemon_of_stopped_or_deleted_container() {
+        let procs = vec![
+            proc(
+                200,
+                1,
+                &[RUNTIME_BINARY, "exec", "c1", "--exec-id", "e1"],
+                1000,
+                OVERLAY_NS,
+            ),
+            proc(201, 200, &["sh"], 100, OVERLAY_NS),
+            proc(300, 1, &[RUNTIME_BINARY, "start", "c2"], 1000, OVERLAY_NS),
+        ];
+        let mut records = Records {
+            containers: HashMap::from([("c1".to_string(), true)]),
+            workloads: HashMap::from([(201, "c1/e1".to_string())]),
+            anchors: HashMap::from([(999, (PathBuf::from("/run/reaper/ns/x"), OVERLAY_NS))]),
+        };
+        let found = classify(&procs, &records, Some(HOST_NS), 1);
+        assert_eq!(found[0].subject.as_deref(), Some("c1/e1"));
+        assert_eq!(found[0].suspect.as_deref(), Some("container c1 is stopped"));
+        // Childless daemon in a recorded namespace whose container is gone
+        assert_eq!(found[1].role, Role::Daemon);
+        assert_eq!(
+            found[1].suspect.as_deref(),
+            Some("container c2 was deleted")
+        );
+
+        records.containers.clear();
+        let found = classify(&procs, &records, Some(HOST_NS), 1);
+        assert_eq!(
+            found[0].suspect.as_deref(),
+            Some("container c1 was deleted")
+        );
+    }
+
+    #[test]
+    fn test_render_report() {
+        let found = vec![
+            ReaperProcess {
+                pid: 100,
+                role: Role::Shim,
+                rss_kb: 8000,
+                subject: Some("pod1".into()),
+                suspect: None,
+            },
+            ReaperProcess {
+                pid: 400,
+                role: Role::Daemon,
+                rss_kb: 1400,
+                subject: None,
+                suspect: Some("monitors pid 401 of no known container".into()),
+            },
+        ];
+        let report = render_report(&found, &[PathBuf::from("/run/reaper/ns/team-a")]);
+        assert!(
+            report.contains("shim         1 processes       8000 KiB"),
+            "{}",
+            report
+        );
+        assert!(
+            report.contains("total        2 processes       9400 KiB"),
+            "{}",
+            report
+        );
+        assert!(report.contains("pid 400 (daemon, 1400 KiB): monitors pid 401"));
+        assert!(report.contains("/run/reaper/ns/team-a: anchor helper is gone"));
+
+        let report = render_report(&found[..1], &[]);
+        assert!(report.ends_with("no suspected leaks\n"), "{}", report);
+    }
+}
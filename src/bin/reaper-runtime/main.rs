@@ -2,16 +2,21 @@ use anyhow::{bail, Context, Result};
 use clap::{Parser, Subcommand};
 use serde::Deserialize;
 use std::fs;
+use std::os::unix::fs::OpenOptionsExt;
 use std::os::unix::process::{CommandExt, ExitStatusExt};
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use tracing::info;
-use tracing_subscriber::EnvFilter;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
 
 mod state;
 use state::{
     delete as delete_state, load_exec_state, load_pid, load_state, save_exec_state, save_pid,
-    save_state, ContainerState, OciUser,
+    save_state, state_path, ContainerState, ExecOutput, FailureOrigin, OciRlimit, OciUser,
+    ResourceUsage, STATUS_RUNNING, STATUS_STARTING, STATUS_STOPPED,
 };
 #[cfg(target_os = "linux")]
 use state::{exec_resize_path, resize_path};
@@ -19,6 +24,28 @@ use state::{exec_resize_path, resize_path};
 #[cfg(target_os = "linux")]
 mod overlay;
 
+#[cfg(target_os = "linux")]
+mod preflight;
+
+#[cfg(target_os = "linux")]
+mod diag;
+
+#[cfg(target_os = "linux")]
+mod cgroup;
+
+#[cfg(target_os = "linux")]
+mod caps;
+
+#[cfg(target_os = "linux")]
+mod privileges;
+
+#[cfg(feature = "otel")]
+mod telemetry;
+
+mod audit;
+
+mod crilog;
+
 #[path = "../../config.rs"]
 mod config;
 
@@ -58,7 +85,7 @@ struct Cli {
     log: Option<PathBuf>,
 
     /// Log format (json|text) (runc: --log-format)
-    #[arg(global = true, long = "log-format")]
+    #[arg(global = true, long = "log-format", value_parser = ["json", "text"])]
     log_format: Option<String>,
 
     /// PID file path (runc: --pid-file)
@@ -93,6 +120,9 @@ enum Commands {
         /// Reaper annotation overrides (key=value, repeatable)
         #[arg(long = "annotation", value_name = "KEY=VALUE")]
         annotations: Vec<String>,
+        /// Record a sandbox (pause) container; it has no workload to start
+        #[arg(long)]
+        sandbox: bool,
     },
     /// Start the container process
     Start {
@@ -110,6 +140,11 @@ enum Commands {
         id: String,
         /// Signal number (default: 15 = SIGTERM)
         signal: Option<i32>,
+        /// Seconds to wait before sending SIGKILL if the workload is still
+        /// running (default: REAPER_KILL_GRACE_SECS, else the
+        /// termination-grace annotation); 0 disables escalation
+        #[arg(long)]
+        timeout: Option<u64>,
     },
     /// Delete container state
     Delete {
@@ -126,21 +161,85 @@ enum Commands {
         #[arg(long)]
         exec_id: String,
     },
+    /// Print the mount tree inside a shared overlay namespace (read-only)
+    OverlayMounts {
+        /// Kubernetes namespace whose overlay to inspect (namespace isolation mode)
+        #[arg(long)]
+        namespace: Option<String>,
+        /// Named overlay group within the namespace
+        #[arg(long)]
+        overlay_name: Option<String>,
+        /// Include kernel pseudo filesystems (proc, sysfs, cgroup, ...)
+        #[arg(long)]
+        all: bool,
+    },
+    /// List the containers in the state directory
+    List {
+        /// Output format
+        #[arg(long, value_parser = ["table", "json"], default_value = "table")]
+        format: String,
+    },
+    /// Check that this node meets reaper's requirements
+    Preflight {
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Report reaper's own processes (shims, monitoring daemons, anchor helpers) and their memory
+    Diag {
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print state, resolved process, mounts, overlay and liveness as one JSON document
+    Inspect {
+        /// Container ID
+        id: String,
+    },
+    /// Print the volume mounts reaper applied for a container and whether they are live
+    Mounts {
+        /// Container ID
+        id: String,
+    },
+    /// Remove exec state files whose exec stopped or whose process is gone
+    GcExecs {
+        /// Only consider exec states untouched for at least this many seconds
+        #[arg(long, default_value_t = 3600)]
+        max_age_secs: u64,
+        /// List what would be removed without removing it
+        #[arg(long)]
+        dry_run: bool,
+    },
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 struct OciProcess {
     args: Option<Vec<String>>, // command and args
     env: Option<Vec<String>>,  // key=value
     cwd: Option<String>,
     user: Option<OciUser>,
-    // #[serde(default)]
-    // terminal: bool,
+    /// Only an explicit `false` matters: it overrides `--terminal` from create
+    #[serde(default)]
+    terminal: Option<bool>,
+    /// Windows-only single-string command line. Never valid for reaper; only
+    /// parsed so we can reject configs meant for another platform.
+    #[serde(default, rename = "commandLine")]
+    command_line: Option<String>,
+    #[serde(default)]
+    rlimits: Vec<OciRlimit>,
+    /// None leaves the process's capabilities as they are
+    #[serde(default)]
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    capabilities: Option<state::OciCapabilities>,
+    /// Set `PR_SET_NO_NEW_PRIVS`, so setuid binaries and file capabilities
+    /// can't raise the workload's privileges
+    #[serde(default, rename = "noNewPrivileges")]
+    no_new_privileges: bool,
 }
 
 /// OCI mount specification from config.json.
 /// Containerd populates this array with bind-mount directives for volumes.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, serde::Serialize)]
 pub struct OciMount {
     pub destination: String,
     #[serde(default)]
@@ -151,12 +250,191 @@ pub struct OciMount {
     pub options: Vec<String>,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Default, serde::Deserialize)]
 struct OciConfig {
     process: Option<OciProcess>,
     #[serde(default)]
     #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
     mounts: Vec<OciMount>,
+    /// Platform-specific sections for non-Linux runtimes (see `check_linux_platform`).
+    #[serde(default)]
+    windows: Option<serde_json::Value>,
+    #[serde(default)]
+    solaris: Option<serde_json::Value>,
+    #[serde(default)]
+    vm: Option<serde_json::Value>,
+    /// Container rootfs, relative to the bundle (or absolute)
+    root: Option<OciRoot>,
+    #[serde(default)]
+    annotations: std::collections::HashMap<String, String>,
+    /// Container hostname (CRI: the pod's)
+    #[serde(default)]
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    hostname: Option<String>,
+    #[serde(default)]
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    linux: Option<OciLinux>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct OciLinux {
+    /// Cgroup for the workload (Kubernetes: under the pod's cgroup)
+    #[serde(default, rename = "cgroupsPath")]
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    cgroups_path: Option<String>,
+    /// Paths the runtime should make read-only (CRI sets e.g. `/proc/sys`)
+    #[serde(default, rename = "readonlyPaths")]
+    readonly_paths: Vec<String>,
+    /// Paths the runtime should hide (CRI sets e.g. `/proc/sysrq-trigger`)
+    #[serde(default, rename = "maskedPaths")]
+    masked_paths: Vec<String>,
+    /// Namespaces CRI asks for; `None` when the config omits the list
+    #[serde(default)]
+    namespaces: Option<Vec<OciNamespace>>,
+    /// Resource limits; memory and CPU limits are enforced via the cgroup
+    #[serde(default)]
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    resources: Option<OciResources>,
+}
+
+/// OCI `linux.resources`, the parts reaper enforces.
+#[derive(Debug, Default, Deserialize)]
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub struct OciResources {
+    #[serde(default)]
+    pub memory: Option<OciMemory>,
+    #[serde(default)]
+    pub cpu: Option<OciCpu>,
+}
+
+/// OCI `linux.resources.memory`, in bytes; `swap` is memory plus swap.
+#[derive(Debug, Default, Deserialize)]
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub struct OciMemory {
+    #[serde(default)]
+    pub limit: Option<i64>,
+    #[serde(default)]
+    pub swap: Option<i64>,
+}
+
+/// OCI `linux.resources.cpu`: quota in microseconds per period, and the
+/// cgroup v1 style relative `shares`.
+#[derive(Debug, Default, Deserialize)]
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub struct OciCpu {
+    #[serde(default)]
+    pub quota: Option<i64>,
+    #[serde(default)]
+    pub period: Option<u64>,
+    #[serde(default)]
+    pub shares: Option<u64>,
+}
+
+/// OCI `linux.namespaces` entry, e.g. `{"type": "network", "path": "/var/run/netns/x"}`.
+#[derive(Debug, Default, Deserialize)]
+struct OciNamespace {
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+/// Whether the container is privileged. The OCI spec has no such flag; CRI
+/// expresses it by leaving `linux.readonlyPaths` and `linux.maskedPaths`
+/// empty, while a config without a `linux` section is treated as unprivileged.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn is_privileged(cfg: &OciConfig) -> bool {
+    cfg.linux
+        .as_ref()
+        .is_some_and(|l| l.readonly_paths.is_empty() && l.masked_paths.is_empty())
+}
+
+/// CRI-O annotation set on containers of `hostNetwork: true` pods.
+const CRIO_HOST_NETWORK: &str = "io.kubernetes.cri-o.HostNetwork";
+
+/// Whether the pod shares the host network (`hostNetwork: true`). The
+/// `host-network` annotation decides when set; otherwise CRI-O's annotation,
+/// or a `linux.namespaces` list without a network entry (containerd's way of
+/// saying the same), marks the pod as host-network.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn is_host_network(cfg: &OciConfig, annotation: Option<bool>) -> bool {
+    if let Some(host_network) = annotation {
+        return host_network;
+    }
+    if cfg
+        .annotations
+        .get(CRIO_HOST_NETWORK)
+        .is_some_and(|v| v.eq_ignore_ascii_case("true"))
+    {
+        return true;
+    }
+    cfg.linux
+        .as_ref()
+        .and_then(|l| l.namespaces.as_ref())
+        .is_some_and(|ns| !ns.iter().any(|n| n.kind == "network"))
+}
+
+/// Hostname the workload gets in its own UTS namespace: the config's
+/// `hostname` when `linux.namespaces` asks for a UTS namespace, as CRI does
+/// for every pod that is not `hostNetwork`. Any namespace path is ignored:
+/// it would name the sandbox's, which reaper doesn't run.
+#[cfg(target_os = "linux")]
+fn workload_hostname(cfg: &OciConfig) -> Option<String> {
+    let wants_uts = cfg
+        .linux
+        .as_ref()
+        .and_then(|l| l.namespaces.as_ref())
+        .is_some_and(|ns| ns.iter().any(|n| n.kind == "uts"));
+    cfg.hostname
+        .clone()
+        .filter(|name| wants_uts && !name.is_empty())
+}
+
+/// Move into a new UTS namespace named `hostname`, which the workload and
+/// its children inherit.
+///
+/// Tested by integration tests (requires root).
+#[cfg(target_os = "linux")]
+#[cfg(not(tarpaulin_include))]
+fn enter_uts_namespace(hostname: &str) -> Result<()> {
+    use nix::sched::{unshare, CloneFlags};
+    unshare(CloneFlags::CLONE_NEWUTS).context("unshare CLONE_NEWUTS")?;
+    nix::unistd::sethostname(hostname).with_context(|| format!("sethostname {:?}", hostname))?;
+    Ok(())
+}
+
+/// Join the UTS namespace of the container's workload `pid`, so an exec
+/// sees the container's hostname. Best effort: the workload may be gone.
+#[cfg(target_os = "linux")]
+#[cfg(not(tarpaulin_include))]
+fn join_uts_namespace(pid: i32) {
+    use nix::sched::{setns, CloneFlags};
+    use std::os::unix::fs::MetadataExt;
+    let target = format!("/proc/{}/ns/uts", pid);
+    let same = match (fs::metadata(&target), fs::metadata("/proc/self/ns/uts")) {
+        (Ok(theirs), Ok(ours)) => theirs.ino() == ours.ino(),
+        _ => return,
+    };
+    if same {
+        return;
+    }
+    if let Err(e) = fs::File::open(&target)
+        .map_err(anyhow::Error::from)
+        .and_then(|f| setns(f, CloneFlags::CLONE_NEWUTS).map_err(Into::into))
+    {
+        tracing::warn!(
+            "exec: not joining the UTS namespace of pid {}: {:#}",
+            pid,
+            e
+        );
+    }
+}
+
+/// CRI annotations identifying the Kubernetes container and its pod.
+const CRI_CONTAINER_NAME: &str = "io.kubernetes.cri.container-name";
+const CRI_SANDBOX_ID: &str = "io.kubernetes.cri.sandbox-id";
+
+#[derive(Debug, Default, Deserialize)]
+struct OciRoot {
+    path: String,
 }
 
 fn read_oci_config(bundle: &Path) -> Result<OciConfig> {
@@ -195,10 +473,133 @@ unsafe fn safe_setgroups(gids: &[nix::libc::gid_t]) -> std::io::Result<()> {
     }
 }
 
+/// What to do when `setgroups` is refused (EPERM), e.g. a non-root runtime
+/// without CAP_SETGID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SetgroupsPolicy {
+    /// Fail the start (default)
+    Strict,
+    /// Continue without supplementary groups
+    BestEffort,
+}
+
+impl SetgroupsPolicy {
+    fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "strict" => Some(Self::Strict),
+            "best-effort" => Some(Self::BestEffort),
+            _ => None,
+        }
+    }
+}
+
+/// Policy from `REAPER_SETGROUPS_POLICY`: "strict" (default) or "best-effort".
+fn setgroups_policy() -> SetgroupsPolicy {
+    match std::env::var("REAPER_SETGROUPS_POLICY") {
+        Ok(v) => SetgroupsPolicy::parse(&v).unwrap_or_else(|| {
+            tracing::warn!("invalid REAPER_SETGROUPS_POLICY {:?}, using strict", v);
+            SetgroupsPolicy::Strict
+        }),
+        Err(_) => SetgroupsPolicy::Strict,
+    }
+}
+
+/// Read the setgroups policy before forking and, under best-effort, warn up
+/// front when supplementary groups are likely to be dropped (pre_exec can't log).
+fn setgroups_policy_for(user: Option<&OciUser>) -> SetgroupsPolicy {
+    let policy = setgroups_policy();
+    let refused = !nix::unistd::geteuid().is_root() || state::rootless_enabled();
+    if policy == SetgroupsPolicy::BestEffort && refused {
+        if let Some(user) = user.filter(|u| !u.additional_gids.is_empty()) {
+            tracing::warn!(
+                "not running as root; supplementary groups {:?} may be dropped (REAPER_SETGROUPS_POLICY=best-effort)",
+                user.additional_gids
+            );
+        }
+    }
+    policy
+}
+
+/// Supplementary groups every workload joins on top of its config's
+/// `additionalGids` (`REAPER_DEFAULT_ADDITIONAL_GIDS`, comma-separated), e.g.
+/// a node-wide GPU or fuse group. Invalid entries are logged and skipped.
+fn default_additional_gids() -> Vec<u32> {
+    let Ok(value) = std::env::var("REAPER_DEFAULT_ADDITIONAL_GIDS") else {
+        return Vec::new();
+    };
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .filter_map(|s| match s.parse() {
+            Ok(gid) => Some(gid),
+            Err(_) => {
+                tracing::warn!(
+                    "ignoring invalid gid {:?} in REAPER_DEFAULT_ADDITIONAL_GIDS",
+                    s
+                );
+                None
+            }
+        })
+        .collect()
+}
+
+/// Add `defaults` to the user's supplementary groups: the union of both,
+/// config gids first, without duplicates. No user config means the workload
+/// runs as the runtime's user and keeps its groups, so nothing changes.
+fn with_default_gids(user: Option<OciUser>, defaults: &[u32]) -> Option<OciUser> {
+    user.map(|mut user| {
+        let mut seen = std::collections::HashSet::new();
+        let gids = user.additional_gids.iter().chain(defaults);
+        user.additional_gids = gids.copied().filter(|gid| seen.insert(*gid)).collect();
+        user
+    })
+}
+
+/// Fail a rootless start whose workload asks for supplementary groups under
+/// the strict policy: the user namespace denies `setgroups`, so they could
+/// never be set. Checked before forking to fail with a clear message.
+fn check_rootless_setgroups(
+    rootless: bool,
+    user: Option<&OciUser>,
+    policy: SetgroupsPolicy,
+) -> Result<()> {
+    match user.filter(|u| rootless && !u.additional_gids.is_empty()) {
+        Some(user) if policy == SetgroupsPolicy::Strict => bail!(
+            "supplementary groups {:?} cannot be set in rootless mode (setgroups is denied \
+             in the user namespace); drop them or set REAPER_SETGROUPS_POLICY=best-effort",
+            user.additional_gids
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// `safe_setgroups`, tolerating EPERM under the best-effort policy. In
+/// rootless mode an empty list is left alone, as there is nothing to add and
+/// the user namespace refuses even clearing the groups.
+unsafe fn apply_setgroups(
+    gids: &[nix::libc::gid_t],
+    policy: SetgroupsPolicy,
+) -> std::io::Result<()> {
+    if gids.is_empty() && state::rootless_enabled() {
+        return Ok(());
+    }
+    match safe_setgroups(gids) {
+        Err(e)
+            if policy == SetgroupsPolicy::BestEffort
+                && e.raw_os_error() == Some(nix::libc::EPERM) =>
+        {
+            Ok(())
+        }
+        result => result,
+    }
+}
+
 /// Open a FIFO for writing. FIFOs are created by containerd and we open them for writing.
 /// Uses O_RDWR so the open succeeds even if the reader (containerd) hasn't connected yet —
 /// O_WRONLY|O_NONBLOCK returns ENXIO on Linux when no reader exists.
 /// Also uses O_NONBLOCK during open to prevent blocking, then clears it so writes block normally.
+/// A missing path is an error, never created: a plain file there would have no reader.
 fn open_log_file(path: &str) -> Result<std::fs::File> {
     use std::fs::OpenOptions;
     use std::os::unix::fs::OpenOptionsExt;
@@ -226,25 +627,448 @@ fn open_log_file(path: &str) -> Result<std::fs::File> {
     Ok(file)
 }
 
+/// Open the runtime's own log file for appending, creating it with
+/// `REAPER_LOG_FILE_MODE` (default 0640) if missing.
+fn create_log_file(path: &Path) -> std::io::Result<std::fs::File> {
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .mode(config::log_file_mode())
+        .open(path)
+}
+
+/// Whether to append an EOF marker to regular-file logs on clean exit
+/// (`REAPER_LOG_EOF_MARKER=1`).
+fn log_eof_marker_enabled() -> bool {
+    std::env::var("REAPER_LOG_EOF_MARKER")
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+/// Append `{"reaper":"eof","exit_code":N}` to each distinct regular-file log
+/// target, so log consumers can tell a clean end of stream from truncation.
+///
+/// Only when the workload exited on its own: a workload killed by a signal
+/// gets no marker. FIFOs are never written to (containerd owns the stream).
+/// `paths` are stdout then stderr, which names the stream of the marker in a
+/// CRI formatted log.
+fn append_log_eof_markers(paths: &[Option<&str>], status: std::process::ExitStatus) {
+    let Some(exit_code) = status.code() else {
+        return;
+    };
+    let marker = format!("{{\"reaper\":\"eof\",\"exit_code\":{}}}\n", exit_code);
+    let timestamp = crilog::enabled().then(|| crilog::rfc3339_nano(std::time::SystemTime::now()));
+    let mut written: Vec<&str> = Vec::new();
+    for (i, path) in paths.iter().enumerate() {
+        let Some(path) = path.filter(|p| !p.is_empty()) else {
+            continue;
+        };
+        if written.contains(&path) || !fs::metadata(path).is_ok_and(|m| m.is_file()) {
+            continue;
+        }
+        written.push(path);
+        // In a CRI formatted log the marker is an entry of its own
+        let marker = match timestamp.as_deref() {
+            Some(ts) => {
+                let stream = if i == 0 { "stdout" } else { "stderr" };
+                crilog::format_chunk(marker.as_bytes(), stream, ts)
+            }
+            None => marker.clone().into_bytes(),
+        };
+        let result = fs::OpenOptions::new()
+            .append(true)
+            .open(path)
+            .and_then(|mut f| std::io::Write::write_all(&mut f, &marker));
+        if let Err(e) = result {
+            tracing::warn!("failed to append EOF marker to {}: {}", path, e);
+        }
+    }
+}
+
+/// `<REAPER_LOG_SOCKET_DIR>/<id>.sock`, where a log shipper may listen for a
+/// copy of the container's output, or `None` when the variable is unset.
+fn log_socket_path(id: &str) -> Option<PathBuf> {
+    let dir = std::env::var("REAPER_LOG_SOCKET_DIR").ok()?;
+    if dir.trim().is_empty() {
+        return None;
+    }
+    Some(Path::new(dir.trim()).join(format!("{}.sock", id)))
+}
+
+/// Best-effort copy of a container's output on a unix stream socket.
+///
+/// The socket is non-blocking: a chunk the listener can't take right away is
+/// dropped, and the first hard error (e.g. the listener went away) disables
+/// the socket. Either way the primary FIFO/file is never held up.
+#[derive(Clone)]
+struct LogSocket(std::sync::Arc<std::sync::Mutex<Option<std::os::unix::net::UnixStream>>>);
+
+impl LogSocket {
+    /// Connect to a listening log shipper; `None` if nothing is listening.
+    fn connect(path: &Path) -> Option<Self> {
+        let stream = match std::os::unix::net::UnixStream::connect(path) {
+            Ok(stream) => stream,
+            Err(e) => {
+                tracing::warn!("log socket {} unavailable: {}", path.display(), e);
+                return None;
+            }
+        };
+        stream.set_nonblocking(true).ok()?;
+        info!(
+            "streaming container output to log socket {}",
+            path.display()
+        );
+        Some(Self(std::sync::Arc::new(std::sync::Mutex::new(Some(
+            stream,
+        )))))
+    }
+
+    fn send(&self, buf: &[u8]) {
+        use std::io::Write;
+        let mut guard = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(stream) = guard.as_mut() else {
+            return;
+        };
+        match stream.write(buf) {
+            Ok(_) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {}
+            Err(e) => {
+                tracing::warn!("log socket write failed, disabling it: {}", e);
+                *guard = None;
+            }
+        }
+    }
+}
+
+/// Copy `reader` to `primary` until EOF, a read error, or a failed write to
+/// `primary`, teeing each chunk to `socket`.
+fn relay_output(
+    mut reader: impl std::io::Read,
+    mut primary: impl std::io::Write,
+    socket: Option<&LogSocket>,
+) {
+    let mut buf = [0u8; 4096];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => {
+                if primary.write_all(&buf[..n]).is_err() {
+                    break;
+                }
+                if let Some(socket) = socket {
+                    socket.send(&buf[..n]);
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(_) => break, // EIO when a PTY slave closes
+        }
+    }
+}
+
+/// Relay threads teeing a non-PTY workload's stdout/stderr to a log socket,
+/// or writing it to a log file in the CRI format.
+///
+/// The workload writes to a pipe instead of the FIFO and a thread copies the
+/// pipe to the FIFO and the socket. A handle to each FIFO stays open until
+/// the daemon exits, as it does when the workload writes to the FIFOs itself.
+#[derive(Default)]
+struct LogSocketTee {
+    threads: Vec<std::thread::JoinHandle<()>>,
+    fifos: Vec<fs::File>,
+}
+
+impl LogSocketTee {
+    /// Stdio for the workload: `file` itself when there is nothing to add,
+    /// otherwise the write end of a pipe relayed to `socket` and to `file`,
+    /// as CRI log entries of `cri_stream` if `file` is a regular file (never
+    /// containerd's FIFO).
+    fn stdio(
+        &mut self,
+        file: fs::File,
+        socket: Option<&LogSocket>,
+        cri_stream: Option<&'static str>,
+    ) -> Stdio {
+        let cri_stream = cri_stream.filter(|_| file.metadata().is_ok_and(|m| m.is_file()));
+        if socket.is_none() && cri_stream.is_none() {
+            return Stdio::from(file);
+        }
+        let (read_end, write_end) = match (nix::unistd::pipe(), file.try_clone()) {
+            (Ok(pipe), Ok(holder)) => {
+                self.fifos.push(holder);
+                pipe
+            }
+            (Err(e), _) => {
+                tracing::warn!("output relay: pipe failed, writing output directly: {}", e);
+                return Stdio::from(file);
+            }
+            (_, Err(e)) => {
+                tracing::warn!("output relay: dup failed, writing output directly: {}", e);
+                return Stdio::from(file);
+            }
+        };
+        let socket = socket.cloned();
+        self.threads.push(std::thread::spawn(move || {
+            let reader = fs::File::from(read_end);
+            match cri_stream {
+                Some(stream) => relay_output(
+                    reader,
+                    crilog::CriLogWriter::new(file, stream),
+                    socket.as_ref(),
+                ),
+                None => relay_output(reader, file, socket.as_ref()),
+            }
+        }));
+        Stdio::from(write_end)
+    }
+
+    fn is_active(&self) -> bool {
+        !self.threads.is_empty()
+    }
+
+    /// Wait up to `timeout` for the relays to drain. Background processes
+    /// that inherited the pipe can keep it open, so don't wait forever.
+    fn finish(&mut self, timeout: std::time::Duration) {
+        let deadline = std::time::Instant::now() + timeout;
+        while self.threads.iter().any(|t| !t.is_finished()) {
+            if std::time::Instant::now() >= deadline {
+                tracing::warn!("log socket relay still busy after {:?}", timeout);
+                return;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Byte counting of exec output: `REAPER_EXEC_OUTPUT_STATS` turns it on and
+/// `REAPER_EXEC_OUTPUT_CAP_BYTES` (which implies it) caps what is forwarded
+/// per stream. `None` when off.
+fn exec_output_accounting() -> Option<OutputAccounting> {
+    let cap = std::env::var("REAPER_EXEC_OUTPUT_CAP_BYTES")
+        .ok()
+        .and_then(|v| match v.parse::<u64>() {
+            Ok(0) => None,
+            Ok(cap) => Some(cap),
+            Err(_) => {
+                tracing::warn!("invalid REAPER_EXEC_OUTPUT_CAP_BYTES {:?}, not capping", v);
+                None
+            }
+        });
+    let enabled = std::env::var("REAPER_EXEC_OUTPUT_STATS")
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false);
+    (enabled || cap.is_some()).then_some(OutputAccounting { cap })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct OutputAccounting {
+    /// Bytes forwarded per stream before the rest is dropped
+    cap: Option<u64>,
+}
+
+/// Running totals of one output stream, shared with its relay thread.
+#[derive(Debug, Default)]
+struct OutputCounter {
+    bytes: std::sync::atomic::AtomicU64,
+    truncated: std::sync::atomic::AtomicBool,
+}
+
+impl OutputCounter {
+    fn bytes(&self) -> u64 {
+        self.bytes.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    fn truncated(&self) -> bool {
+        self.truncated.load(std::sync::atomic::Ordering::Relaxed)
+    }
+}
+
+/// Copy `reader` to `writer`, counting every byte read. Past `cap` bytes the
+/// output is still read and counted but dropped, so the workload doesn't
+/// block on a full pipe. Stops when either side closes.
+fn relay_counted(
+    mut reader: impl std::io::Read,
+    mut writer: impl std::io::Write,
+    cap: Option<u64>,
+    counter: &OutputCounter,
+) {
+    use std::sync::atomic::Ordering;
+    let mut buf = [0u8; 4096];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => {
+                let before = counter.bytes.fetch_add(n as u64, Ordering::Relaxed);
+                let room = cap.map_or(n, |cap| cap.saturating_sub(before).min(n as u64) as usize);
+                if room < n {
+                    counter.truncated.store(true, Ordering::Relaxed);
+                }
+                if room > 0 && writer.write_all(&buf[..room]).is_err() {
+                    break;
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(_) => break, // EIO when a PTY slave closes
+        }
+    }
+}
+
+/// Counting relays of an exec's stdout and stderr (see [`relay_counted`]).
+#[derive(Default)]
+struct ExecOutputRelays {
+    threads: Vec<std::thread::JoinHandle<()>>,
+    stdout: Option<std::sync::Arc<OutputCounter>>,
+    stderr: Option<std::sync::Arc<OutputCounter>>,
+}
+
+impl ExecOutputRelays {
+    /// Relay `reader` to `writer` on a thread, returning its counter.
+    fn spawn(
+        &mut self,
+        reader: impl std::io::Read + Send + 'static,
+        writer: impl std::io::Write + Send + 'static,
+        cap: Option<u64>,
+    ) -> std::sync::Arc<OutputCounter> {
+        let counter = std::sync::Arc::new(OutputCounter::default());
+        let relay_counter = counter.clone();
+        self.threads.push(std::thread::spawn(move || {
+            relay_counted(reader, writer, cap, &relay_counter)
+        }));
+        counter
+    }
+
+    /// Stdio for a workload stream going to `file`: with accounting, the
+    /// write end of a pipe relayed to `file` through a counter; otherwise (or
+    /// if the pipe can't be created) `file` itself, uncounted.
+    fn counted_stdio(
+        &mut self,
+        file: fs::File,
+        accounting: Option<OutputAccounting>,
+    ) -> (Stdio, Option<std::sync::Arc<OutputCounter>>) {
+        let Some(accounting) = accounting else {
+            return (Stdio::from(file), None);
+        };
+        match nix::unistd::pipe() {
+            Ok((read_end, write_end)) => {
+                let counter = self.spawn(fs::File::from(read_end), file, accounting.cap);
+                (Stdio::from(write_end), Some(counter))
+            }
+            Err(e) => {
+                tracing::warn!("exec output: pipe failed, not counting: {}", e);
+                (Stdio::from(file), None)
+            }
+        }
+    }
+
+    fn stdout(&mut self, file: fs::File, accounting: Option<OutputAccounting>) -> Stdio {
+        let (stdio, counter) = self.counted_stdio(file, accounting);
+        self.stdout = counter;
+        stdio
+    }
+
+    fn stderr(&mut self, file: fs::File, accounting: Option<OutputAccounting>) -> Stdio {
+        let (stdio, counter) = self.counted_stdio(file, accounting);
+        self.stderr = counter;
+        stdio
+    }
+
+    /// Wait up to `timeout` for the relays to drain (background processes can
+    /// hold the pipes open) and report the counts so far.
+    fn finish(self, timeout: std::time::Duration) -> ExecOutput {
+        let deadline = std::time::Instant::now() + timeout;
+        while self.threads.iter().any(|t| !t.is_finished()) {
+            if std::time::Instant::now() >= deadline {
+                tracing::warn!("exec output relay still busy after {:?}", timeout);
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        let counters = [&self.stdout, &self.stderr];
+        ExecOutput {
+            stdout_bytes: self.stdout.as_ref().map(|c| c.bytes()),
+            stderr_bytes: self.stderr.as_ref().map(|c| c.bytes()),
+            truncated: counters
+                .iter()
+                .flat_map(|c| c.as_ref())
+                .any(|c| c.truncated()),
+        }
+    }
+}
+
+/// How long an exec's output relays may keep draining after it exits.
+const EXEC_OUTPUT_DRAIN: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Store `output` in the exec's state.
+fn record_exec_output(container_id: &str, exec_id: &str, output: ExecOutput) {
+    if output.truncated {
+        tracing::warn!(
+            "exec {} output exceeded REAPER_EXEC_OUTPUT_CAP_BYTES and was truncated",
+            exec_id
+        );
+    }
+    if let Ok(mut state) = load_exec_state(container_id, exec_id) {
+        state.output = Some(output);
+        let _ = save_exec_state(&state);
+    }
+}
+
 /// Extract exit code from an ExitStatus, handling signal-killed processes.
 ///
 /// When a process is killed by a signal, `ExitStatus::code()` returns `None`.
 /// PTY sessions commonly see SIGHUP (signal 1) during teardown — the kernel
-/// sends it when the controlling terminal's slave side closes. This is normal
-/// and should be treated as a clean exit (code 0), not an error.
-/// Other signals use the standard 128+signal convention.
-fn exit_code_from_status(status: std::process::ExitStatus) -> i32 {
+/// sends it when the controlling terminal's slave side closes. For `terminal`
+/// processes this is normal and treated as a clean exit (code 0); without a
+/// PTY a SIGHUP is a real hangup and is reported like any other signal.
+/// Signals use the standard 128+signal convention.
+fn exit_code_from_status(status: std::process::ExitStatus, terminal: bool) -> i32 {
     if let Some(code) = status.code() {
         return code;
     }
     // Process was killed by a signal
     match status.signal() {
-        Some(nix::libc::SIGHUP) => 0, // PTY teardown — not an error
+        Some(nix::libc::SIGHUP) if terminal => 0, // PTY teardown — not an error
         Some(sig) => 128 + sig,
         None => 1,
     }
 }
 
+/// Convert a kernel rusage into the fields recorded in container state.
+fn resource_usage_from_rusage(ru: &nix::libc::rusage) -> ResourceUsage {
+    let micros =
+        |tv: nix::libc::timeval| (tv.tv_sec.max(0) as u64) * 1_000_000 + tv.tv_usec.max(0) as u64;
+    ResourceUsage {
+        // ru_maxrss is already in KiB on Linux
+        max_rss_kib: ru.ru_maxrss.max(0) as u64,
+        user_cpu_us: micros(ru.ru_utime),
+        system_cpu_us: micros(ru.ru_stime),
+    }
+}
+
+/// Like `Child::wait`, but reaps with wait4() so the child's rusage is
+/// captured along with its exit status.
+fn wait_with_usage(
+    child: std::process::Child,
+) -> std::io::Result<(std::process::ExitStatus, ResourceUsage)> {
+    let pid = child.id() as nix::libc::pid_t;
+    let mut status: nix::libc::c_int = 0;
+    let mut ru: nix::libc::rusage = unsafe { std::mem::zeroed() };
+    loop {
+        let ret = unsafe { nix::libc::wait4(pid, &mut status, 0, &mut ru) };
+        if ret == pid {
+            return Ok((
+                std::process::ExitStatus::from_raw(status),
+                resource_usage_from_rusage(&ru),
+            ));
+        }
+        let err = std::io::Error::last_os_error();
+        if err.kind() != std::io::ErrorKind::Interrupted {
+            return Err(err);
+        }
+    }
+}
+
 /// Spawn a thread that polls a resize file and applies `TIOCSWINSZ` to the PTY master.
 ///
 /// The shim writes `"width height\n"` to `resize_file`. This thread polls every 100ms,
@@ -307,10 +1131,12 @@ fn do_create(
     stderr: Option<String>,
     namespace: Option<String>,
     cli_annotations: &[String],
+    sandbox: bool,
 ) -> Result<()> {
+    let mut timer = state::PhaseTimer::new();
     let parsed_annotations = annotations::parse_cli_annotations(cli_annotations);
     info!(
-        "do_create() called - id={}, bundle={}, terminal={}, stdin={:?}, stdout={:?}, stderr={:?}, namespace={:?}, annotations={:?}",
+        "do_create() called - id={}, bundle={}, terminal={}, stdin={:?}, stdout={:?}, stderr={:?}, namespace={:?}, annotations={:?}, sandbox={}",
         id,
         bundle.display(),
         terminal,
@@ -318,26 +1144,342 @@ fn do_create(
         stdout,
         stderr,
         namespace,
-        parsed_annotations
+        parsed_annotations,
+        sandbox
     );
+    if let Some(existing) = existing_container(id, bundle, create_idempotent())? {
+        info!(
+            "do_create() - container={} already exists (status {}), returning existing state",
+            id, existing.status
+        );
+        println!("{}", serde_json::to_string_pretty(&existing)?);
+        return Ok(());
+    }
     let mut state = ContainerState::new(id.to_string(), bundle.to_path_buf());
+    state.sandbox = sandbox;
+    if sandbox {
+        save_state(&state)?;
+        info!(
+            "do_create() succeeded - sandbox state saved for container={}",
+            id
+        );
+        return Ok(());
+    }
     state.terminal = terminal;
     state.stdin = stdin;
     state.stdout = stdout;
     state.stderr = stderr;
     state.namespace = namespace;
+    // Best effort: only needed to resolve `after` dependencies by name
+    if let Ok(cfg) = read_oci_config(bundle) {
+        state.container_name = cfg.annotations.get(CRI_CONTAINER_NAME).cloned();
+        state.sandbox_id = cfg.annotations.get(CRI_SANDBOX_ID).cloned();
+        state.created_config = Some(config_snapshot(&cfg));
+    }
     if !parsed_annotations.is_empty() {
         state.annotations = Some(parsed_annotations);
     }
-    save_state(&state)?;
-    info!("do_create() succeeded - state saved for container={}", id);
-    println!("{}", serde_json::to_string_pretty(&state)?);
+    let image_rootfs_annotation = state
+        .annotations
+        .as_ref()
+        .and_then(annotations::parse_stripped_annotations)
+        .and_then(|a| a.image_rootfs);
+    match image_rootfs_setting(image_rootfs_annotation) {
+        Some(true) => {
+            let cfg = read_oci_config(bundle)?;
+            state.image_rootfs = resolve_image_rootfs(bundle, &cfg)?;
+            match state.image_rootfs {
+                Some(ref rootfs) => info!("do_create() - image rootfs: {}", rootfs.display()),
+                None => tracing::warn!(
+                    "do_create() - image rootfs requested but config.json has no root.path; using host root only"
+                ),
+            }
+        }
+        Some(false) => {}
+        None => {
+            // Unconfigured: a read-only rootfs still gets layered, since the
+            // runtime is expected to provide its writable layer
+            let readonly_rootfs = read_oci_config(bundle)
+                .ok()
+                .and_then(|cfg| resolve_image_rootfs(bundle, &cfg).ok().flatten())
+                .filter(|rootfs| is_readonly_mount(rootfs));
+            if let Some(rootfs) = readonly_rootfs {
+                info!(
+                    "do_create() - read-only image rootfs {}, layering it with a private writable upper",
+                    rootfs.display()
+                );
+                state.image_rootfs = Some(rootfs);
+            }
+        }
+    }
+    timer.mark("create");
+    state.timings = timer.phases().to_vec();
+    save_state(&state)?;
+    info!("do_create() succeeded - state saved for container={}", id);
+    println!("{}", serde_json::to_string_pretty(&state)?);
+    Ok(())
+}
+
+/// Whether a repeated `create` for an id that already has state returns that
+/// state unchanged (`REAPER_CREATE_IDEMPOTENT`, default) instead of failing.
+fn create_idempotent() -> bool {
+    std::env::var("REAPER_CREATE_IDEMPOTENT")
+        .map(|v| !(v == "0" || v.eq_ignore_ascii_case("false")))
+        .unwrap_or(true)
+}
+
+/// State of an existing container `id`, if `create` is called for it again.
+///
+/// Errors with "already exists" unless duplicates are idempotent; an
+/// idempotent duplicate must still name the same bundle, otherwise it is a
+/// different container reusing the id. `Ok(None)` if the id is new.
+fn existing_container(id: &str, bundle: &Path, idempotent: bool) -> Result<Option<ContainerState>> {
+    if !state_path(id).exists() {
+        return Ok(None);
+    }
+    let existing = load_state(id)?;
+    if !idempotent {
+        anyhow::bail!(
+            "container {} already exists (status {})",
+            id,
+            existing.status
+        );
+    }
+    if existing.bundle != bundle {
+        anyhow::bail!(
+            "container {} already exists with bundle {}",
+            id,
+            existing.bundle.display()
+        );
+    }
+    Ok(Some(existing))
+}
+
+/// CRI annotation marking sandbox (pause) containers.
+const CRI_CONTAINER_TYPE: &str = "io.kubernetes.cri.container-type";
+
+/// Snapshot of the config.json fields `reconcile_config` compares.
+fn config_snapshot(cfg: &OciConfig) -> state::ConfigSnapshot {
+    let process = cfg.process.as_ref();
+    state::ConfigSnapshot {
+        args: process.and_then(|p| p.args.clone()).unwrap_or_default(),
+        cwd: process.and_then(|p| p.cwd.clone()),
+        root: cfg.root.as_ref().map(|r| r.path.clone()),
+        mounts: cfg.mounts.iter().map(|m| m.destination.clone()).collect(),
+        annotations: cfg
+            .annotations
+            .iter()
+            .filter(|(k, _)| {
+                k.starts_with(annotations::ANNOTATION_PREFIX) || *k == CRI_CONTAINER_TYPE
+            })
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect(),
+    }
+}
+
+/// Which version of config.json a field of a started container comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConfigSource {
+    Create,
+    Start,
+}
+
+impl ConfigSource {
+    fn as_str(self) -> &'static str {
+        match self {
+            ConfigSource::Create => "create",
+            ConfigSource::Start => "start",
+        }
+    }
+}
+
+/// A config.json field that changed between create and start.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ConfigChange {
+    field: &'static str,
+    /// The version `start` goes by
+    source: ConfigSource,
+    detail: String,
+}
+
+/// Compare the config.json seen at create with the one read at start.
+///
+/// containerd may rewrite the bundle in between (e.g. to inject mounts).
+/// What the workload runs with follows the start-time config: `args`, `cwd`
+/// and `mounts` are read again at start. What create already acted on stays
+/// as recorded: the image `root` (resolved into state at create), and the
+/// Reaper and sandbox annotations (the shim passed them to create, and the
+/// sandbox flag is persisted). Returns only the fields that differ.
+fn reconcile_config(
+    created: &state::ConfigSnapshot,
+    current: &state::ConfigSnapshot,
+) -> Vec<ConfigChange> {
+    let mut changes = Vec::new();
+    let mut changed = |field, source, detail: String| {
+        changes.push(ConfigChange {
+            field,
+            source,
+            detail,
+        })
+    };
+    if created.args != current.args {
+        changed(
+            "args",
+            ConfigSource::Start,
+            format!("{:?} -> {:?}", created.args, current.args),
+        );
+    }
+    if created.cwd != current.cwd {
+        changed(
+            "cwd",
+            ConfigSource::Start,
+            format!("{:?} -> {:?}", created.cwd, current.cwd),
+        );
+    }
+    if created.mounts != current.mounts {
+        let added: Vec<&String> = current
+            .mounts
+            .iter()
+            .filter(|m| !created.mounts.contains(m))
+            .collect();
+        let removed: Vec<&String> = created
+            .mounts
+            .iter()
+            .filter(|m| !current.mounts.contains(m))
+            .collect();
+        changed(
+            "mounts",
+            ConfigSource::Start,
+            format!("added {:?}, removed {:?}", added, removed),
+        );
+    }
+    if created.root != current.root {
+        changed(
+            "root",
+            ConfigSource::Create,
+            format!("{:?} -> {:?}", created.root, current.root),
+        );
+    }
+    if created.annotations != current.annotations {
+        let keys: Vec<&String> = created
+            .annotations
+            .keys()
+            .chain(current.annotations.keys())
+            .collect::<std::collections::BTreeSet<_>>()
+            .into_iter()
+            .filter(|k| created.annotations.get(*k) != current.annotations.get(*k))
+            .collect();
+        changed(
+            "annotations",
+            ConfigSource::Create,
+            format!("changed {:?}", keys),
+        );
+    }
+    changes
+}
+
+/// Whether this container's image rootfs should be layered over the host root.
+///
+/// The `image-rootfs` annotation wins over `REAPER_IMAGE_ROOTFS`. `None`
+/// when neither is set: the rootfs is then layered only if it is a read-only
+/// mount (see [`is_readonly_mount`]).
+fn image_rootfs_setting(annotation: Option<bool>) -> Option<bool> {
+    annotation.or_else(|| {
+        std::env::var("REAPER_IMAGE_ROOTFS")
+            .ok()
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+    })
+}
+
+/// Whether `path` is on a read-only mount, e.g. an image rootfs that
+/// containerd mounted read-only for the runtime to put a writable layer on.
+fn is_readonly_mount(path: &Path) -> bool {
+    nix::sys::statvfs::statvfs(path)
+        .is_ok_and(|st| st.flags().contains(nix::sys::statvfs::FsFlags::ST_RDONLY))
+}
+
+/// Resolve OCI `root.path` against the bundle. Returns `None` if the config
+/// has no root; errors if the rootfs directory does not exist.
+fn resolve_image_rootfs(bundle: &Path, cfg: &OciConfig) -> Result<Option<PathBuf>> {
+    let Some(root) = cfg.root.as_ref().filter(|r| !r.path.is_empty()) else {
+        return Ok(None);
+    };
+    let path = bundle.join(&root.path);
+    let resolved = fs::canonicalize(&path)
+        .with_context(|| format!("image rootfs {} does not exist", path.display()))?;
+    if !resolved.is_dir() {
+        bail!("image rootfs {} is not a directory", resolved.display());
+    }
+    Ok(Some(resolved))
+}
+
+/// Overlay config for a container: the shared overlay, or a private one when
+/// the container layers its image rootfs over the host root. A node-wide
+/// `shared_overlay` replaces the namespace's (or node's) overlay.
+#[cfg(target_os = "linux")]
+fn container_overlay_config(
+    container_id: &str,
+    namespace: Option<&str>,
+    overlay_name: Option<&str>,
+    isolation: Option<&str>,
+    shared_overlay: Option<&str>,
+    image_rootfs: Option<&Path>,
+) -> Result<overlay::OverlayConfig> {
+    let config = match shared_overlay {
+        Some(name) => {
+            if overlay_name.is_some() || isolation.is_some() {
+                tracing::warn!(
+                    "shared-overlay {:?} takes precedence over overlay-name and overlay-isolation",
+                    name
+                );
+            }
+            overlay::read_shared_config(name)?
+        }
+        None => overlay::read_config(namespace, overlay_name, isolation)?,
+    };
+    match image_rootfs {
+        Some(rootfs) => config.with_image_rootfs(container_id, rootfs),
+        None => Ok(config),
+    }
+}
+
+/// Reject configs written for a non-Linux OCI runtime.
+///
+/// A wrong runtimeClass binding can route a Windows (or VM-based) container to
+/// reaper. Without this check it fails later with the generic "process.args
+/// must contain at least one element", which hides the real problem.
+fn check_linux_platform(cfg: &OciConfig) -> Result<()> {
+    let mut found = Vec::new();
+    if cfg
+        .process
+        .as_ref()
+        .is_some_and(|p| p.command_line.is_some())
+    {
+        found.push("process.commandLine");
+    }
+    if cfg.windows.is_some() {
+        found.push("windows");
+    }
+    if cfg.solaris.is_some() {
+        found.push("solaris");
+    }
+    if cfg.vm.is_some() {
+        found.push("vm");
+    }
+    if !found.is_empty() {
+        bail!(
+            "config.json appears to target a non-Linux platform (found {}); \
+             reaper is a Linux OCI runtime — check the pod's runtimeClassName",
+            found.join(", ")
+        );
+    }
     Ok(())
 }
 
 /// Extract program path and arguments from an OCI config.
 /// Returns (program_path, remaining_argv).
 fn parse_program_and_args(cfg: &OciConfig) -> Result<(PathBuf, Vec<String>)> {
+    check_linux_platform(cfg)?;
     let proc = cfg
         .process
         .as_ref()
@@ -351,391 +1493,1621 @@ fn parse_program_and_args(cfg: &OciConfig) -> Result<(PathBuf, Vec<String>)> {
     Ok((program, argv))
 }
 
-fn do_start(id: &str, bundle: &Path) -> Result<()> {
-    info!("do_start() called - id={}, bundle={}", id, bundle.display());
+/// Record that `start` is under way, so the shim's `state()` doesn't report a
+/// plain "created" while the overlay and mounts are being set up.
+fn mark_starting(state: &mut ContainerState) -> Result<()> {
+    state.status = STATUS_STARTING.into();
+    save_state(state)
+}
 
-    // Load state to get the original bundle path (in case bundle arg is just ".")
-    let state = load_state(id)?;
-    let bundle = &state.bundle;
-    info!("do_start() - using bundle from state: {}", bundle.display());
+/// Record that reaper failed to set up or supervise the container.
+///
+/// Used by every fatal branch in the start daemon so the shim and operators
+/// can tell a failed deployment apart from a workload that exited 1.
+fn mark_runtime_failure(container_id: &str) {
+    if let Ok(mut state) = load_state(container_id) {
+        state.mark_stopped(1, FailureOrigin::Runtime);
+        let _ = save_state(&state);
+    }
+}
 
-    let cfg = read_oci_config(bundle)?;
-    let (program_path, argv) = parse_program_and_args(&cfg)?;
-    let program = program_path.to_string_lossy().to_string();
-    let proc = cfg
-        .process
-        .as_ref()
-        .context("config.json missing 'process' section")?;
+/// Like `mark_spawn_failure`, for a `start` that failed before the monitoring
+/// daemon was forked, so the container doesn't stay "starting" for good.
+fn mark_start_failure(container_id: &str, err: &anyhow::Error) {
+    tracing::error!("do_start() - start failed: {:#}", err);
+    if let Ok(mut state) = load_state(container_id) {
+        state.mark_stopped(1, FailureOrigin::Runtime);
+        state.failure_message = Some(format!("{:#}", err));
+        let _ = save_state(&state);
+    }
+}
 
-    info!(
-        "do_start() - program={}, resolved_path={}, args={:?}, cwd={:?}",
-        program,
-        program_path.display(),
-        argv,
-        proc.cwd
-    );
+/// `REAPER_NO_OVERLAY=1` disables the overlay for unit tests that lack
+/// CAP_SYS_ADMIN, so workloads run on the host filesystem. Debug builds only.
+fn overlay_disabled() -> bool {
+    cfg!(debug_assertions)
+        && std::env::var("REAPER_NO_OVERLAY")
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false)
+}
 
-    // Handle user/group ID switching before exec
-    let user_config = &proc.user;
-    if let Some(ref user) = user_config {
-        info!(
-            "do_start() - user config: uid={}, gid={}, additional_gids={:?}, umask={:?}",
-            user.uid, user.gid, user.additional_gids, user.umask
-        );
-    } else {
-        info!("do_start() - no user config, will run as current user");
+/// Like `mark_runtime_failure`, also recording why the workload couldn't be
+/// spawned.
+fn mark_spawn_failure(container_id: &str, message: String) {
+    tracing::error!("do_start() - failed to spawn workload: {}", message);
+    if let Ok(mut state) = load_state(container_id) {
+        state.mark_stopped(1, FailureOrigin::Runtime);
+        state.failure_message = Some(message);
+        let _ = save_state(&state);
     }
+}
 
-    // Parse Reaper annotations from state for per-pod config overrides.
-    // Annotations are stored with the prefix already stripped, so use
-    // parse_stripped_annotations() to avoid a wasteful re-prefix round-trip.
-    let parsed_annotations = state
-        .annotations
-        .as_ref()
-        .and_then(annotations::parse_stripped_annotations);
-    if let Some(ref annots) = parsed_annotations {
-        info!("do_start() - parsed annotations: {:?}", annots);
+/// One-line phase breakdown for the log, e.g. `overlay=120ms spawn=3ms`.
+fn format_timings(phases: &[state::PhaseTiming]) -> String {
+    phases
+        .iter()
+        .map(|t| format!("{}={}ms", t.phase, t.ms))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Like `mark_runtime_failure`, for a start aborted by strict sensitive path
+/// filtering: records the dedicated exit code and the path that failed.
+fn mark_filter_failure(container_id: &str, failure: &overlay::FilterFailure) {
+    if let Ok(mut state) = load_state(container_id) {
+        state.mark_stopped(overlay::FILTER_FAILURE_EXIT_CODE, FailureOrigin::Runtime);
+        state.failure_message = Some(failure.to_string());
+        let _ = save_state(&state);
     }
+}
 
-    // Clone data needed for the forked child
-    let container_id = id.to_string();
-    #[cfg(target_os = "linux")]
-    let container_namespace = state.namespace.clone();
-    let cwd = proc.cwd.clone();
-    let env_vars = proc.env.clone();
-    #[cfg(target_os = "linux")]
-    let oci_mounts = cfg.mounts.clone();
-    #[cfg(target_os = "linux")]
-    let dns_mode_override = parsed_annotations.as_ref().and_then(|a| a.dns_mode.clone());
-    #[cfg(target_os = "linux")]
-    let overlay_name_override = parsed_annotations
-        .as_ref()
-        .and_then(|a| a.overlay_name.clone());
+/// Interpreter named on the `#!` line of `path`, if it starts with one.
+fn shebang_interpreter(path: &Path) -> Option<PathBuf> {
+    use std::io::Read;
+    let mut head = [0u8; 256];
+    let n = fs::File::open(path).ok()?.read(&mut head).ok()?;
+    let line = head[..n].strip_prefix(b"#!")?;
+    let line = line.split(|&b| b == b'\n').next()?;
+    let interpreter = std::str::from_utf8(line).ok()?.split_whitespace().next()?;
+    Some(PathBuf::from(interpreter))
+}
 
-    use nix::unistd::{fork, ForkResult};
+fn is_executable_file(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path)
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
 
-    // CRITICAL: Fork FIRST, then spawn the workload in the forked child.
-    // This ensures the monitoring daemon is the actual parent of the workload,
-    // allowing it to properly call wait() and reap the child.
-    //
-    // Previous bug: We were spawning the workload first, then forking.
-    // After fork(), the std::process::Child handle is invalid in the forked child
-    // because it was created by the parent process.
+/// Describe a failed spawn of `program` (run from `cwd`).
+///
+/// Diagnostics only: when exec fails with ENOEXEC, EACCES or ENOENT on a
+/// file that exists, look for a shebang and say whether its interpreter is
+/// usable in the overlay, or whether the file itself lacks execute
+/// permission.
+fn spawn_failure_message(program: &Path, cwd: Option<&str>, err: &std::io::Error) -> String {
+    use std::os::unix::fs::PermissionsExt;
+    let base = format!("failed to exec {}: {}", program.display(), err);
+    let errno = err.raw_os_error();
+    if !matches!(
+        errno,
+        Some(nix::libc::ENOEXEC | nix::libc::EACCES | nix::libc::ENOENT)
+    ) {
+        return base;
+    }
+    // Bare names are looked up in PATH; only paths are inspected
+    if !program.to_string_lossy().contains('/') {
+        return base;
+    }
+    let path = Path::new(cwd.unwrap_or("/")).join(program);
+    let Ok(meta) = fs::metadata(&path) else {
+        return base;
+    };
+    if !meta.is_file() {
+        return base;
+    }
+    let mode = meta.permissions().mode() & 0o7777;
+    let not_executable = mode & 0o111 == 0;
+
+    match shebang_interpreter(&path) {
+        Some(interpreter) if !interpreter.exists() => format!(
+            "{}; its shebang interpreter {} does not exist in the overlay",
+            base,
+            interpreter.display()
+        ),
+        Some(interpreter) if !is_executable_file(&interpreter) => format!(
+            "{}; its shebang interpreter {} is not an executable file",
+            base,
+            interpreter.display()
+        ),
+        Some(interpreter) if not_executable => format!(
+            "{}; the script is not executable (mode {:04o}): chmod +x it or run it as [\"{}\", \"{}\"]",
+            base,
+            mode,
+            interpreter.display(),
+            program.display()
+        ),
+        None if not_executable => format!(
+            "{}; the file is not executable (mode {:04o})",
+            base, mode
+        ),
+        None if errno == Some(nix::libc::ENOEXEC) => format!(
+            "{}; the file is neither a binary for this platform nor a script with a #! line",
+            base
+        ),
+        _ => base,
+    }
+}
 
-    match unsafe { fork() } {
-        Ok(ForkResult::Parent { child: daemon_pid }) => {
-            // Parent process (original reaper-runtime start command)
-            // We don't know the workload PID yet - the daemon will report it
-            // For now, just report the daemon PID and let the daemon update state
-            info!(
-                "do_start() - forked monitoring daemon (pid={}), parent returning",
-                daemon_pid
-            );
+/// Exec audit record for spawning `program` with the full `argv` in
+/// `container_id` (an exec when `exec_id` is set). Without an OCI user the
+/// process runs as the runtime's own uid/gid.
+fn audit_record<'a>(
+    container_id: &'a str,
+    exec_id: Option<&'a str>,
+    namespace: Option<&'a str>,
+    program: &'a str,
+    argv: Vec<&'a str>,
+    user: Option<&OciUser>,
+    cwd: Option<&'a str>,
+) -> audit::AuditRecord<'a> {
+    let (uid, gid) = match user {
+        Some(user) => (user.uid, user.gid),
+        None => (
+            nix::unistd::getuid().as_raw(),
+            nix::unistd::getgid().as_raw(),
+        ),
+    };
+    audit::AuditRecord {
+        ts: state::unix_now(),
+        event: if exec_id.is_some() { "exec" } else { "start" },
+        container_id,
+        exec_id,
+        namespace,
+        program,
+        argv,
+        uid,
+        gid,
+        cwd,
+    }
+}
 
-            // Wait for daemon to spawn workload and update state
-            // The daemon needs time to: setsid(), [enter overlay namespace on Linux], spawn workload, write state to disk
-            // On Linux, overlay namespace setup can take significant time (creating namespace, mounting, pivot_root)
-            // Poll the state file until we see the workload PID or the container exits
-            let mut workload_pid = None;
-            let max_attempts = if cfg!(target_os = "linux") { 100 } else { 20 };
-            let poll_interval_ms = 100;
+/// Environment the workload is spawned with: the runtime's own, overlaid
+/// with `env_vars`, as `Command` builds it.
+fn workload_env(env_vars: Option<&[String]>) -> Vec<String> {
+    let mut env: Vec<(String, String)> = std::env::vars_os()
+        .map(|(k, v)| {
+            (
+                k.to_string_lossy().into_owned(),
+                v.to_string_lossy().into_owned(),
+            )
+        })
+        .collect();
+    for kv in env_vars.into_iter().flatten() {
+        if let Some((k, v)) = kv.split_once('=') {
+            env.retain(|(existing, _)| existing != k);
+            env.push((k.to_string(), v.to_string()));
+        }
+    }
+    env.into_iter()
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect()
+}
 
-            for attempt in 0..max_attempts {
-                if let Ok(state) = load_state(&container_id) {
-                    if let Some(pid) = state.pid {
-                        workload_pid = Some(pid);
-                        break;
-                    }
-                    // If container is already stopped, daemon failed to start workload
-                    if state.status == "stopped" {
-                        info!(
-                            "do_start() - container stopped before PID was recorded (daemon likely failed), exit_code={:?}",
-                            state.exit_code
-                        );
-                        break;
-                    }
-                }
-                std::thread::sleep(std::time::Duration::from_millis(poll_interval_ms));
+/// Bytes `execve` counts against `ARG_MAX` for `program argv...` with `env`:
+/// every string with its NUL terminator, plus the pointer arrays.
+fn exec_args_size(program: &Path, argv: &[String], env: &[String]) -> usize {
+    let strings = program.as_os_str().len()
+        + 1
+        + argv.iter().map(|a| a.len() + 1).sum::<usize>()
+        + env.iter().map(|e| e.len() + 1).sum::<usize>();
+    // argv and envp entries plus their NULL terminators
+    let pointers = (1 + argv.len() + 1 + env.len() + 1) * std::mem::size_of::<usize>();
+    strings + pointers
+}
 
-                // Log progress every second for debugging
-                if attempt > 0 && attempt % 10 == 0 {
-                    info!(
-                        "do_start() - still waiting for workload PID (attempt {}/{})",
-                        attempt, max_attempts
-                    );
-                }
-            }
+/// The system's `ARG_MAX`, if it can be determined.
+fn arg_max() -> Option<usize> {
+    nix::unistd::sysconf(nix::unistd::SysconfVar::ARG_MAX)
+        .ok()
+        .flatten()
+        .and_then(|n| usize::try_from(n).ok())
+}
 
-            // Print the workload PID if we got it, otherwise fall back to daemon PID
-            if let Some(pid) = workload_pid {
-                println!("started pid={}", pid);
-            } else {
-                // Fallback: report daemon PID if workload PID not yet available
-                info!(
-                    "do_start() - timeout waiting for workload PID after {}ms, reporting daemon PID instead",
-                    max_attempts * poll_interval_ms
-                );
-                println!("started pid={}", daemon_pid);
-            }
+/// Spawn failure message for argv+env of `size` bytes hitting E2BIG.
+fn args_too_long_message(size: usize, limit: Option<usize>) -> String {
+    match limit {
+        Some(limit) if size > limit => format!(
+            "argv+env size of {} bytes exceeds the system limit of {} bytes (ARG_MAX)",
+            size, limit
+        ),
+        Some(limit) => format!(
+            "argv+env size of {} bytes was rejected (E2BIG) although under the system limit of {} bytes; a single argument or environment string may be too long",
+            size, limit
+        ),
+        None => format!(
+            "argv+env size of {} bytes exceeds the system limit (ARG_MAX)",
+            size
+        ),
+    }
+}
 
-            // Attempt to reap daemon if it has already exited (non-blocking).
-            // WNOHANG means don't block if still running. This prevents zombie processes.
-            use nix::sys::wait::{waitpid, WaitPidFlag};
-            use nix::unistd::Pid;
-            let daemon_pid_raw = Pid::from_raw(daemon_pid.as_raw());
-            let _ = waitpid(daemon_pid_raw, Some(WaitPidFlag::WNOHANG));
+/// Fail early when argv+env can't fit in `ARG_MAX`, rather than at `execve`.
+fn check_exec_args_size(size: usize, limit: Option<usize>) -> std::result::Result<(), String> {
+    match limit {
+        Some(limit) if size > limit => Err(args_too_long_message(size, Some(limit))),
+        _ => Ok(()),
+    }
+}
 
-            Ok(())
+/// Record the workload's real exit status and resource usage.
+fn mark_workload_exit(
+    container_id: &str,
+    exit_code: i32,
+    exit_signal: Option<i32>,
+    usage: Option<ResourceUsage>,
+    oom: bool,
+) {
+    if let Some(u) = usage {
+        info!(
+            "workload exited: container={}, exit_code={}, max_rss_kib={}, user_cpu_us={}, system_cpu_us={}",
+            container_id, exit_code, u.max_rss_kib, u.user_cpu_us, u.system_cpu_us
+        );
+    }
+    if oom {
+        info!(
+            "workload killed by the OOM killer: container={}",
+            container_id
+        );
+    }
+    if let Ok(mut state) = load_state(container_id) {
+        state.mark_stopped(exit_code, FailureOrigin::Workload);
+        state.exit_signal = exit_signal;
+        state.resource_usage = usage;
+        state.oom = oom;
+        let _ = save_state(&state);
+    }
+}
+
+/// Working directory for a process spec, so it never inherits wherever
+/// containerd happened to start the runtime. An explicit cwd wins; otherwise
+/// `REAPER_DEFAULT_CWD`, else "/".
+fn effective_cwd(cwd: Option<&str>) -> String {
+    cwd.filter(|c| !c.is_empty())
+        .map(str::to_string)
+        .or_else(|| {
+            std::env::var("REAPER_DEFAULT_CWD")
+                .ok()
+                .filter(|c| !c.is_empty())
+        })
+        .unwrap_or_else(|| "/".to_string())
+}
+
+/// PATH applied by `REAPER_DERIVE_HOME` when the process env doesn't set one.
+const DEFAULT_PATH: &str = "/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin";
+
+/// Whether HOME/PATH should be derived for the target user (`REAPER_DERIVE_HOME=1`).
+fn derive_home_enabled() -> bool {
+    std::env::var("REAPER_DERIVE_HOME")
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+/// Find the home directory for `uid` in passwd-format `contents`.
+fn passwd_home_for_uid(contents: &str, uid: u32) -> Option<String> {
+    contents.lines().find_map(|line| {
+        // name:passwd:uid:gid:gecos:home:shell
+        let fields: Vec<&str> = line.split(':').collect();
+        if fields.len() < 7 || fields[2].parse::<u32>().ok()? != uid {
+            return None;
         }
-        Ok(ForkResult::Child) => {
-            // Child process (monitoring daemon)
-            // This process will spawn and monitor the workload
+        Some(fields[5])
+            .filter(|home| !home.is_empty())
+            .map(String::from)
+    })
+}
 
-            // CRITICAL: Close inherited stdout/stderr immediately after fork.
-            // The shim calls reaper-runtime via cmd.output() which creates pipes.
-            // If we keep those pipe fds open, cmd.output() blocks until WE exit,
-            // which prevents the shim's start() from returning and leaves the pod
-            // stuck in ContainerCreating. Redirecting to /dev/null lets the parent's
-            // pipes close when the parent exits.
-            {
-                use std::os::unix::io::AsRawFd;
-                if let Ok(devnull) = std::fs::File::open("/dev/null") {
-                    let fd = devnull.as_raw_fd();
-                    unsafe {
-                        nix::libc::dup2(fd, 1); // stdout
-                        nix::libc::dup2(fd, 2); // stderr
-                    }
-                }
-            }
+/// Add HOME and PATH to `env` unless it already sets them.
+///
+/// HOME falls back to "/" when the user has no passwd entry, matching runc.
+fn default_user_env(env: Option<Vec<String>>, home: Option<&str>) -> Vec<String> {
+    let mut env = env.unwrap_or_default();
+    let has = |env: &[String], key: &str| {
+        env.iter()
+            .any(|kv| kv.split_once('=').is_some_and(|(k, _)| k == key))
+    };
+    if !has(&env, "HOME") {
+        env.push(format!("HOME={}", home.unwrap_or("/")));
+    }
+    if !has(&env, "PATH") {
+        env.push(format!("PATH={}", DEFAULT_PATH));
+    }
+    env
+}
 
-            // Detach from parent session to become a proper daemon
-            if let Err(e) = nix::unistd::setsid() {
-                // Can't use eprintln! here since stderr is now /dev/null
-                let _ = e;
-            }
+/// Apply `REAPER_DERIVE_HOME` to a process env.
+///
+/// Must run after entering the overlay so the namespace's /etc/passwd is used.
+fn derive_user_env(env: Option<Vec<String>>, user: Option<&OciUser>) -> Option<Vec<String>> {
+    if !derive_home_enabled() {
+        return env;
+    }
+    let uid = user
+        .map(|u| u.uid)
+        .unwrap_or_else(|| nix::unistd::getuid().as_raw());
+    let home = fs::read_to_string("/etc/passwd")
+        .ok()
+        .and_then(|contents| passwd_home_for_uid(&contents, uid));
+    info!("derive_user_env() - uid={}, passwd home={:?}", uid, home);
+    Some(default_user_env(env, home.as_deref()))
+}
 
-            // Join shared overlay namespace (Linux only).
-            // Overlay is mandatory in production — workloads must not run on the host filesystem.
-            // REAPER_NO_OVERLAY=1 disables overlay for unit tests that lack CAP_SYS_ADMIN.
-            #[cfg(target_os = "linux")]
-            {
-                #[cfg(debug_assertions)]
-                let skip_overlay = std::env::var("REAPER_NO_OVERLAY")
-                    .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
-                    .unwrap_or(false);
-                #[cfg(not(debug_assertions))]
-                let skip_overlay = false;
-
-                if skip_overlay {
-                    info!("do_start() - overlay disabled via REAPER_NO_OVERLAY");
-                } else {
-                    let overlay_config = match overlay::read_config(
-                        container_namespace.as_deref(),
-                        overlay_name_override.as_deref(),
-                    ) {
-                        Ok(c) => c,
-                        Err(e) => {
-                            tracing::error!(
-                                "do_start() - overlay config failed: {:#}, refusing to run",
-                                e
-                            );
-                            if let Ok(mut state) = load_state(&container_id) {
-                                state.status = "stopped".into();
-                                state.exit_code = Some(1);
-                                let _ = save_state(&state);
-                            }
-                            std::process::exit(1);
-                        }
-                    };
-                    if let Err(e) = overlay::enter_overlay(&overlay_config) {
-                        tracing::error!(
-                            "do_start() - overlay setup failed: {:#}, refusing to run without isolation",
-                            e
-                        );
-                        if let Ok(mut state) = load_state(&container_id) {
-                            state.status = "stopped".into();
-                            state.exit_code = Some(1);
-                            let _ = save_state(&state);
-                        }
-                        std::process::exit(1);
-                    }
-                    info!("do_start() - joined shared overlay namespace");
+/// `KEY=value` pairs of an env file, in order; a repeated key keeps its
+/// first position and its last value. Blank lines, `#` comments and an
+/// `export ` prefix are allowed, and one pair of matching quotes around a
+/// value is removed. Lines without a valid variable name are skipped.
+fn parse_env_file(content: &str) -> Vec<(String, String)> {
+    let mut vars: Vec<(String, String)> = Vec::new();
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, value)) = line.split_once('=') else {
+            tracing::warn!("env-from-mount: skipping line without '=': {:?}", line);
+            continue;
+        };
+        let key = key.trim();
+        let valid_key = key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid_key {
+            tracing::warn!("env-from-mount: skipping invalid name {:?}", key);
+            continue;
+        }
+        let value = value.trim();
+        let value = ['"', '\'']
+            .iter()
+            .find_map(|&q| value.strip_prefix(q)?.strip_suffix(q))
+            .unwrap_or(value);
+        match vars.iter_mut().find(|(k, _)| k == key) {
+            Some(existing) => existing.1 = value.to_string(),
+            None => vars.push((key.to_string(), value.to_string())),
+        }
+    }
+    vars
+}
 
-                    // Apply volume mounts from OCI config (FATAL on failure)
-                    if !oci_mounts.is_empty() {
-                        if let Err(e) = overlay::apply_volume_mounts(&oci_mounts) {
-                            tracing::error!(
-                                "do_start() - volume mount failed: {:#}, refusing to start workload",
-                                e
-                            );
-                            if let Ok(mut state) = load_state(&container_id) {
-                                state.status = "stopped".into();
-                                state.exit_code = Some(1);
-                                let _ = save_state(&state);
-                            }
-                            std::process::exit(1);
-                        }
-                        info!("do_start() - volume mounts applied");
-                    }
+/// Directories `env-from-mount` may read from: the destinations of the
+/// pod's mounts. The container root is never one, since its lower layer is
+/// the host's root.
+fn env_file_roots(mounts: &[OciMount]) -> Vec<PathBuf> {
+    mounts
+        .iter()
+        .map(|m| PathBuf::from(&m.destination))
+        .filter(|dest| dest.is_absolute() && dest != Path::new("/"))
+        .collect()
+}
 
-                    // Apply Kubernetes DNS if configured (FATAL on failure)
-                    // Per-pod annotation override takes precedence over node-level config
-                    let dns_config =
-                        overlay::read_dns_config_with_override(dns_mode_override.as_deref());
-                    if dns_config.mode == overlay::DnsMode::Kubernetes {
-                        if let Err(e) = overlay::apply_kubernetes_dns(&oci_mounts) {
-                            tracing::error!(
-                                "do_start() - kubernetes DNS setup failed: {:#}, refusing to start workload",
-                                e
-                            );
-                            if let Ok(mut state) = load_state(&container_id) {
-                                state.status = "stopped".into();
-                                state.exit_code = Some(1);
-                                let _ = save_state(&state);
-                            }
-                            std::process::exit(1);
+/// `path` with symlinks resolved, if that lies under one of `roots`.
+fn resolve_env_file(path: &Path, roots: &[PathBuf]) -> std::io::Result<PathBuf> {
+    let resolved = fs::canonicalize(path)?;
+    let allowed = roots
+        .iter()
+        .filter_map(|root| fs::canonicalize(root).ok())
+        .any(|root| root != Path::new("/") && resolved.starts_with(&root));
+    if !allowed {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            format!(
+                "{} is not under a mount of the pod (resolves to {})",
+                path.display(),
+                resolved.display()
+            ),
+        ));
+    }
+    Ok(resolved)
+}
+
+/// Read the env file with the workload's credentials, in a child that drops
+/// to `user` first, so a pod can't read files only root may read. A workload
+/// running as root (or without a user) reads as nobody (65534). An
+/// unprivileged runtime (rootless) reads as itself.
+fn read_env_file(
+    path: &Path,
+    roots: &[PathBuf],
+    user: Option<&OciUser>,
+) -> std::result::Result<String, String> {
+    use nix::sys::wait::{waitpid, WaitStatus};
+    use nix::unistd::{fork, ForkResult};
+    use std::io::{Read, Write};
+
+    const NOBODY: u32 = 65534;
+    let (uid, gid, gids) = match user.filter(|u| u.uid != 0) {
+        Some(u) => (u.uid, u.gid, u.additional_gids.clone()),
+        None => (NOBODY, NOBODY, Vec::new()),
+    };
+    let drop_privileges = nix::unistd::geteuid().is_root();
+    let (read_end, write_end) = nix::unistd::pipe().map_err(|e| format!("pipe: {}", e))?;
+    match unsafe { fork() } {
+        Ok(ForkResult::Child) => {
+            drop(read_end);
+            let result = (|| {
+                if drop_privileges {
+                    // SAFETY: single-threaded child; gid before uid. Fewer
+                    // groups only narrow what the reader can see.
+                    unsafe {
+                        apply_setgroups(&gids, SetgroupsPolicy::BestEffort)?;
+                        if nix::libc::setgid(gid) != 0 || nix::libc::setuid(uid) != 0 {
+                            return Err(std::io::Error::last_os_error());
                         }
-                        info!("do_start() - kubernetes DNS configured");
                     }
                 }
+                fs::read_to_string(resolve_env_file(path, roots)?)
+            })();
+            let (code, out) = match result {
+                Ok(content) => (0, content),
+                Err(e) => (1, e.to_string()),
+            };
+            let _ = fs::File::from(write_end).write_all(out.as_bytes());
+            unsafe { nix::libc::_exit(code) }
+        }
+        Ok(ForkResult::Parent { child }) => {
+            drop(write_end);
+            let mut out = String::new();
+            let read = fs::File::from(read_end).read_to_string(&mut out);
+            match (waitpid(child, None), read) {
+                (Ok(WaitStatus::Exited(_, 0)), Ok(_)) => Ok(out),
+                (Ok(WaitStatus::Exited(_, _)), _) => Err(format!("{} (as uid {})", out, uid)),
+                (status, _) => Err(format!("reader child failed: {:?}", status)),
             }
+        }
+        Err(e) => Err(format!("fork: {}", e)),
+    }
+}
 
-            // Now spawn the workload - we are its parent!
-            // Reload state to get I/O paths and terminal flag from create
-            let io_state = load_state(&container_id).ok();
-            let use_terminal = io_state.as_ref().is_some_and(|s| s.terminal);
+/// Merge the env file at `path` (`env-from-mount` annotation) below
+/// process.env. The file must resolve under one of `roots` and is read as the
+/// workload user (see [`read_env_file`]); otherwise, or if it is unreadable,
+/// this is logged and the env is left unchanged.
+fn env_from_file(
+    env: Option<Vec<String>>,
+    path: &Path,
+    roots: &[PathBuf],
+    user: Option<&OciUser>,
+) -> Option<Vec<String>> {
+    let content = match read_env_file(path, roots, user) {
+        Ok(content) => content,
+        Err(e) => {
+            tracing::warn!("env-from-mount: cannot read {}: {}", path.display(), e);
+            return env;
+        }
+    };
+    let vars = parse_env_file(&content);
+    info!(
+        "env-from-mount: {} variable(s) from {}",
+        vars.len(),
+        path.display()
+    );
+    let names: Vec<String> = vars.iter().map(|(k, _)| k.clone()).collect();
+    merge_passthrough_env(env, &names, |name| {
+        vars.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone())
+    })
+}
 
-            // Clone user config for use in pre_exec closures (both PTY and non-PTY modes)
-            let user_cfg_for_exec = user_config.clone();
+/// Names listed in `REAPER_PASSTHROUGH_ENV` (comma-separated).
+fn passthrough_env_names() -> Vec<String> {
+    std::env::var("REAPER_PASSTHROUGH_ENV")
+        .map(|v| {
+            v.split(',')
+                .map(str::trim)
+                .filter(|n| !n.is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
 
-            if use_terminal {
-                // Terminal mode: allocate a PTY so the shell sees isatty()=true.
-                // Relay between containerd FIFOs and the PTY master.
-                use nix::pty::openpty;
-                use std::io::{Read, Write};
-                use std::os::unix::io::AsRawFd;
+/// Prepend `name=value` for each of `names` that `lookup` finds and `env`
+/// doesn't already set, so process.env always wins. Shared by
+/// `REAPER_PASSTHROUGH_ENV` and the `env-from-mount` file.
+fn merge_passthrough_env(
+    env: Option<Vec<String>>,
+    names: &[String],
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Option<Vec<String>> {
+    let sets = |key: &str| {
+        env.iter()
+            .flatten()
+            .any(|kv| kv.split_once('=').is_some_and(|(k, _)| k == key))
+    };
+    let mut merged: Vec<String> = names
+        .iter()
+        .filter(|name| !sets(name))
+        .filter_map(|name| lookup(name).map(|value| format!("{}={}", name, value)))
+        .collect();
+    if merged.is_empty() {
+        return env;
+    }
+    merged.extend(env.unwrap_or_default());
+    Some(merged)
+}
 
-                let pty = match openpty(None, None) {
-                    Ok(pty) => pty,
-                    Err(e) => {
-                        tracing::error!("do_start() - openpty failed: {}", e);
-                        if let Ok(mut state) = load_state(&container_id) {
-                            state.status = "stopped".into();
-                            state.exit_code = Some(1);
-                            let _ = save_state(&state);
-                        }
-                        std::process::exit(1);
-                    }
-                };
+/// Apply `REAPER_PASSTHROUGH_ENV`: copy the named variables from the
+/// runtime's own environment into a process env, below process.env.
+fn passthrough_env(env: Option<Vec<String>>) -> Option<Vec<String>> {
+    let names = passthrough_env_names();
+    if names.is_empty() {
+        return env;
+    }
+    merge_passthrough_env(env, &names, |name| std::env::var(name).ok())
+}
 
-                let slave_raw_fd = pty.slave.as_raw_fd();
+/// Node-wide rlimit defaults and the variables that set them.
+const DEFAULT_RLIMIT_VARS: &[(&str, &str)] = &[
+    ("RLIMIT_NOFILE", "REAPER_DEFAULT_RLIMIT_NOFILE"),
+    ("RLIMIT_NPROC", "REAPER_DEFAULT_RLIMIT_NPROC"),
+];
+
+/// Parse a default rlimit value: `N` (soft and hard) or `SOFT:HARD`.
+fn parse_rlimit_value(value: &str) -> Option<(u64, u64)> {
+    let (soft, hard) = match value.split_once(':') {
+        Some((soft, hard)) => (soft.trim().parse().ok()?, hard.trim().parse().ok()?),
+        None => {
+            let n = value.trim().parse().ok()?;
+            (n, n)
+        }
+    };
+    (soft <= hard).then_some((soft, hard))
+}
 
-                let mut cmd = Command::new(&program_path);
-                cmd.args(&argv);
-                if let Some(cwd) = cwd.as_deref() {
-                    cmd.current_dir(cwd);
-                }
-                if let Some(envs) = env_vars.as_ref() {
-                    for kv in envs {
-                        if let Some((k, v)) = kv.split_once('=') {
-                            cmd.env(k, v);
-                        }
-                    }
+/// Rlimits from `REAPER_DEFAULT_RLIMIT_*`. Invalid values are ignored with a warning.
+fn default_rlimits() -> Vec<OciRlimit> {
+    DEFAULT_RLIMIT_VARS
+        .iter()
+        .filter_map(|(kind, var)| {
+            let value = std::env::var(var).ok()?;
+            match parse_rlimit_value(&value) {
+                Some((soft, hard)) => Some(OciRlimit {
+                    kind: kind.to_string(),
+                    soft,
+                    hard,
+                }),
+                None => {
+                    tracing::warn!("ignoring invalid {}={:?}", var, value);
+                    None
                 }
+            }
+        })
+        .collect()
+}
 
-                // PTY slave becomes stdin/stdout/stderr via pre_exec
-                cmd.stdin(Stdio::null());
-                cmd.stdout(Stdio::null());
-                cmd.stderr(Stdio::null());
+/// Defaults overlaid with the container's own rlimits; the container wins
+/// for any type it sets.
+fn merge_rlimits(defaults: Vec<OciRlimit>, config: &[OciRlimit]) -> Vec<OciRlimit> {
+    defaults
+        .into_iter()
+        .filter(|d| !config.iter().any(|c| c.kind == d.kind))
+        .chain(config.iter().cloned())
+        .collect()
+}
 
-                unsafe {
-                    cmd.pre_exec(move || {
-                        // New session so we can set controlling terminal
-                        if nix::libc::setsid() < 0 {
-                            return Err(std::io::Error::last_os_error());
-                        }
-                        // Set controlling terminal
-                        // TIOCSCTTY: ioctl request type varies by arch (c_int on aarch64, c_ulong on x86_64/macOS)
-                        if nix::libc::ioctl(
-                            slave_raw_fd,
-                            nix::libc::TIOCSCTTY as _,
-                            0 as nix::libc::c_int,
-                        ) < 0
-                        {
-                            return Err(std::io::Error::last_os_error());
-                        }
-                        // Dup slave to stdin/stdout/stderr
-                        nix::libc::dup2(slave_raw_fd, 0);
-                        nix::libc::dup2(slave_raw_fd, 1);
-                        nix::libc::dup2(slave_raw_fd, 2);
-                        if slave_raw_fd > 2 {
-                            nix::libc::close(slave_raw_fd);
-                        }
+fn rlimit_resource(kind: &str) -> Option<nix::sys::resource::Resource> {
+    use nix::sys::resource::Resource;
+    Some(match kind {
+        "RLIMIT_AS" => Resource::RLIMIT_AS,
+        "RLIMIT_CORE" => Resource::RLIMIT_CORE,
+        "RLIMIT_CPU" => Resource::RLIMIT_CPU,
+        "RLIMIT_DATA" => Resource::RLIMIT_DATA,
+        "RLIMIT_FSIZE" => Resource::RLIMIT_FSIZE,
+        "RLIMIT_NOFILE" => Resource::RLIMIT_NOFILE,
+        "RLIMIT_STACK" => Resource::RLIMIT_STACK,
+        #[cfg(target_os = "linux")]
+        "RLIMIT_LOCKS" => Resource::RLIMIT_LOCKS,
+        #[cfg(target_os = "linux")]
+        "RLIMIT_MEMLOCK" => Resource::RLIMIT_MEMLOCK,
+        #[cfg(target_os = "linux")]
+        "RLIMIT_MSGQUEUE" => Resource::RLIMIT_MSGQUEUE,
+        #[cfg(target_os = "linux")]
+        "RLIMIT_NICE" => Resource::RLIMIT_NICE,
+        #[cfg(target_os = "linux")]
+        "RLIMIT_NPROC" => Resource::RLIMIT_NPROC,
+        #[cfg(target_os = "linux")]
+        "RLIMIT_RSS" => Resource::RLIMIT_RSS,
+        #[cfg(target_os = "linux")]
+        "RLIMIT_RTPRIO" => Resource::RLIMIT_RTPRIO,
+        #[cfg(target_os = "linux")]
+        "RLIMIT_RTTIME" => Resource::RLIMIT_RTTIME,
+        #[cfg(target_os = "linux")]
+        "RLIMIT_SIGPENDING" => Resource::RLIMIT_SIGPENDING,
+        _ => return None,
+    })
+}
 
-                        // Apply user/group configuration if present
-                        if let Some(ref user) = user_cfg_for_exec {
-                            // Always clear/set supplementary groups (must be done while privileged).
-                            // When empty, this clears inherited root supplementary groups.
-                            safe_setgroups(&user.additional_gids)?;
+/// Rlimits to set on a workload or exec: `REAPER_DEFAULT_RLIMIT_*` as a
+/// baseline under `process.rlimits`. Unknown types are logged and skipped
+/// rather than failing the container.
+fn workload_rlimits(config: &[OciRlimit]) -> Vec<(nix::sys::resource::Resource, u64, u64)> {
+    merge_rlimits(default_rlimits(), config)
+        .into_iter()
+        .filter_map(|r| match rlimit_resource(&r.kind) {
+            Some(resource) => Some((resource, r.soft, r.hard)),
+            None => {
+                tracing::warn!("skipping unsupported rlimit type {:?}", r.kind);
+                None
+            }
+        })
+        .collect()
+}
 
-                            // Set GID before UID (privilege dropping order matters)
-                            if nix::libc::setgid(user.gid) != 0 {
-                                return Err(std::io::Error::last_os_error());
-                            }
+/// Set `PR_SET_NO_NEW_PRIVS` on the calling process. Inherited by
+/// everything it execs and irreversible; only a syscall, so safe in
+/// `pre_exec`.
+#[cfg(target_os = "linux")]
+fn set_no_new_privs() -> std::io::Result<()> {
+    // SAFETY: prctl with integer arguments only
+    let ret = unsafe {
+        nix::libc::prctl(
+            nix::libc::PR_SET_NO_NEW_PRIVS,
+            1 as nix::libc::c_ulong,
+            0 as nix::libc::c_ulong,
+            0 as nix::libc::c_ulong,
+            0 as nix::libc::c_ulong,
+        )
+    };
+    if ret != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
 
-                            // Set UID (this must be last - irreversible privilege drop)
-                            if nix::libc::setuid(user.uid) != 0 {
-                                return Err(std::io::Error::last_os_error());
-                            }
+/// Whether the workload gets a PTY: as requested at create (`--terminal`),
+/// unless config.json has `process.terminal: false`, which wins.
+fn use_terminal(created: bool, config: Option<bool>) -> bool {
+    created && config != Some(false)
+}
 
-                            // Apply umask if specified
-                            if let Some(mask) = user.umask {
-                                nix::libc::umask(mask as nix::libc::mode_t);
-                            }
-                        }
+/// Default bound on how long `do_start` waits for an `after` dependency.
+const DEFAULT_START_AFTER_TIMEOUT_SECS: u64 = 60;
 
-                        Ok(())
-                    });
-                }
+fn start_after_timeout() -> std::time::Duration {
+    let secs = std::env::var("REAPER_START_AFTER_TIMEOUT_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_START_AFTER_TIMEOUT_SECS);
+    std::time::Duration::from_secs(secs)
+}
 
-                match cmd.spawn() {
-                    Ok(mut child) => {
-                        let workload_pid = child.id() as i32;
+/// Whether `candidate` is the container `reference` names for `waiter`: the
+/// same container ID, or the same Kubernetes container name in the same pod.
+/// Only containers in the waiter's namespace count.
+fn is_start_dependency(
+    candidate: &ContainerState,
+    reference: &str,
+    waiter: &ContainerState,
+) -> bool {
+    if candidate.id == waiter.id || candidate.namespace != waiter.namespace {
+        return false;
+    }
+    candidate.id == reference
+        || (candidate.container_name.as_deref() == Some(reference)
+            && candidate.sandbox_id == waiter.sandbox_id)
+}
 
-                        if let Ok(mut state) = load_state(&container_id) {
-                            state.status = "running".into();
-                            state.pid = Some(workload_pid);
-                            let _ = save_state(&state);
-                            let _ = save_pid(&container_id, workload_pid);
-                        }
+/// Whether a dependency in `status` satisfies a wait for `wanted`. A
+/// container that already stopped has been running.
+fn start_dependency_reached(status: &str, wanted: &str) -> bool {
+    status == STATUS_STOPPED || (wanted == STATUS_RUNNING && status == STATUS_RUNNING)
+}
 
-                        // Close slave in parent - child has it via dup2
-                        drop(pty.slave);
+/// Block until the `after` dependency of `waiter` reaches its status,
+/// polling the state dir every `interval`. Fails after `timeout`.
+fn wait_for_start_dependency(
+    waiter: &ContainerState,
+    after: &annotations::StartAfter,
+    timeout: std::time::Duration,
+    interval: std::time::Duration,
+) -> Result<()> {
+    info!(
+        "do_start() - waiting up to {:?} for {} to be {}",
+        timeout, after.container, after.status
+    );
+    let deadline = std::time::Instant::now() + timeout;
+    loop {
+        let current = state::list_states()
+            .states
+            .into_iter()
+            .find(|s| is_start_dependency(s, &after.container, waiter))
+            .map(|s| s.status);
+        if current
+            .as_deref()
+            .is_some_and(|status| start_dependency_reached(status, &after.status))
+        {
+            info!("do_start() - dependency {} is ready", after.container);
+            return Ok(());
+        }
+        if std::time::Instant::now() >= deadline {
+            bail!(
+                "timed out after {:?} waiting for dependency {} to be {} (status: {})",
+                timeout,
+                after.container,
+                after.status,
+                current.as_deref().unwrap_or("not found")
+            );
+        }
+        std::thread::sleep(interval);
+    }
+}
 
-                        // Capture master raw fd for resize ioctl before converting to File
-                        #[cfg(target_os = "linux")]
-                        let master_raw_fd = {
-                            use std::os::unix::io::AsRawFd;
-                            pty.master.as_raw_fd()
-                        };
+const SETTLE_DELAY: std::time::Duration = std::time::Duration::from_millis(500);
 
-                        // Convert PTY master OwnedFd to File for I/O
-                        let master_file: std::fs::File = pty.master.into();
-                        let master_clone = master_file.try_clone().unwrap_or_else(|e| {
-                            tracing::error!("failed to clone master fd: {}", e);
-                            std::process::exit(1);
-                        });
+/// How long the daemon lets containerd observe "running" before reaping the
+/// workload. The `fast-exit` annotation drops it for non-terminal containers;
+/// their exit is still reported through state and the TaskExit event.
+fn settle_delay(terminal: bool, fast_exit: bool) -> std::time::Duration {
+    if fast_exit && !terminal {
+        std::time::Duration::ZERO
+    } else {
+        SETTLE_DELAY
+    }
+}
 
-                        // Start PTY resize watcher thread
-                        #[cfg(target_os = "linux")]
-                        let resize_stop =
-                            std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
-                        #[cfg(target_os = "linux")]
-                        spawn_resize_watcher(
-                            master_raw_fd,
-                            resize_path(&container_id),
-                            resize_stop.clone(),
-                        );
+/// Whether the OOM killer ended the workload: its cgroup's `oom_kill` count
+/// rose while it ran. Without both counts (no cgroup v2 memory controller)
+/// fall back to a heuristic: a memory-limited workload that died of SIGKILL.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn oom_killed(
+    kills_before: Option<u64>,
+    kills_after: Option<u64>,
+    memory_limited: bool,
+    exit_signal: Option<i32>,
+) -> bool {
+    match (kills_before, kills_after) {
+        (Some(before), Some(after)) => after > before,
+        _ => memory_limited && exit_signal == Some(nix::libc::SIGKILL),
+    }
+}
+
+/// [`oom_killed`] for the workload that just exited from `cgroup`.
+#[cfg(target_os = "linux")]
+fn detect_oom(
+    cgroup: Option<&cgroup::WorkloadCgroup>,
+    kills_before: Option<u64>,
+    memory_limited: bool,
+    exit_signal: Option<i32>,
+) -> bool {
+    let kills_after = cgroup.and_then(|c| c.oom_kills());
+    oom_killed(kills_before, kills_after, memory_limited, exit_signal)
+}
+
+/// Remove the workload's cgroup after it has exited. Processes it left
+/// behind keep the cgroup busy; `delete` tries again.
+#[cfg(target_os = "linux")]
+fn remove_workload_cgroup(cgroup: Option<&cgroup::WorkloadCgroup>) {
+    if let Some(cgroup) = cgroup {
+        if let Err(e) = cgroup.remove() {
+            info!("do_start() - cgroup left for delete: {:#}", e);
+        }
+    }
+}
+
+/// The cgroup path to record in state (so the shim can watch it) once the
+/// workload has moved itself in, or None if it didn't make it.
+#[cfg(target_os = "linux")]
+fn joined_workload_cgroup(cgroup: Option<&cgroup::WorkloadCgroup>, pid: i32) -> Option<PathBuf> {
+    let cgroup = cgroup?;
+    if cgroup.holds(pid) {
+        info!(
+            "do_start() - workload joined cgroup {}",
+            cgroup.path.display()
+        );
+        Some(cgroup.path.clone())
+    } else {
+        tracing::warn!(
+            "do_start() - workload did not join cgroup {}",
+            cgroup.path.display()
+        );
+        None
+    }
+}
+
+/// A workload that didn't join the cgroup its limits were applied to would
+/// run without them: kill it, mark the start failed and exit the daemon.
+#[cfg(target_os = "linux")]
+fn refuse_unlimited_workload(container_id: &str, mut child: std::process::Child) -> ! {
+    tracing::error!("do_start() - workload outside its cgroup, refusing to run it without limits");
+    let _ = child.kill();
+    let _ = child.wait();
+    mark_runtime_failure(container_id);
+    std::process::exit(1);
+}
+
+/// Write `pid` to `path` (runc's `--pid-file`), creating parent directories.
+/// Written to a temporary file and renamed into place, so readers never see
+/// a partial PID.
+fn write_pid_file(path: &Path, pid: i32) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("creating pid file directory {}", parent.display()))?;
+    }
+    let name = path
+        .file_name()
+        .with_context(|| format!("pid file path {} has no file name", path.display()))?;
+    let tmp = path.with_file_name(format!(
+        ".{}.{}.tmp",
+        name.to_string_lossy(),
+        std::process::id()
+    ));
+    let result = fs::write(&tmp, pid.to_string()).and_then(|()| fs::rename(&tmp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result.with_context(|| format!("writing pid file {}", path.display()))
+}
+
+fn do_start(id: &str, bundle: &Path, pid_file: Option<&Path>) -> Result<()> {
+    info!("do_start() called - id={}, bundle={}", id, bundle.display());
+    let mut timer = state::PhaseTimer::new();
+
+    // Load state to get the original bundle path (in case bundle arg is just ".")
+    let mut state = load_state(id)?;
+    if state.sandbox {
+        bail!("container {} is a sandbox; it has no workload to start", id);
+    }
+    mark_starting(&mut state)?;
+    // From here until the daemon is forked, a failure must not leave the
+    // container "starting"
+    let failed = |e: anyhow::Error| {
+        mark_start_failure(id, &e);
+        e
+    };
+    let bundle = &state.bundle;
+    info!("do_start() - using bundle from state: {}", bundle.display());
+
+    let cfg = read_oci_config(bundle).map_err(failed)?;
+    if let Some(ref created) = state.created_config {
+        for change in reconcile_config(created, &config_snapshot(&cfg)) {
+            tracing::warn!(
+                "do_start() - config.json {} changed since create ({}); using the {}-time value",
+                change.field,
+                change.detail,
+                change.source.as_str()
+            );
+        }
+    }
+    let (program_path, argv) = parse_program_and_args(&cfg).map_err(failed)?;
+    let program = program_path.to_string_lossy().to_string();
+    let proc = cfg
+        .process
+        .as_ref()
+        .context("config.json missing 'process' section")
+        .map_err(failed)?;
+
+    info!(
+        "do_start() - program={}, resolved_path={}, args={:?}, cwd={:?}",
+        program,
+        program_path.display(),
+        argv,
+        proc.cwd
+    );
+    let config_terminal = proc.terminal;
+    if state.terminal && !use_terminal(state.terminal, config_terminal) {
+        info!("do_start() - process.terminal is false in config.json, running without a PTY despite --terminal");
+    }
+
+    // Handle user/group ID switching before exec
+    let user_config = with_default_gids(proc.user.clone(), &default_additional_gids());
+    if let Some(ref user) = user_config {
+        info!(
+            "do_start() - user config: uid={}, gid={}, additional_gids={:?}, umask={:?}",
+            user.uid, user.gid, user.additional_gids, user.umask
+        );
+    } else {
+        info!("do_start() - no user config, will run as current user");
+    }
+
+    // Fail now, naming what's missing, rather than with EPERM mid-setup
+    #[cfg(target_os = "linux")]
+    {
+        let required = privileges::requirements(&privileges::StartPlan {
+            rootless: state::rootless_enabled(),
+            user: user_config.as_ref(),
+            strict_setgroups: setgroups_policy() == SetgroupsPolicy::Strict,
+            runtime_ids: (
+                nix::unistd::geteuid().as_raw(),
+                nix::unistd::getegid().as_raw(),
+            ),
+        });
+        let held = caps::current_effective().unwrap_or(u64::MAX);
+        privileges::check(&required, held).map_err(failed)?;
+    }
+    check_rootless_setgroups(
+        state::rootless_enabled(),
+        user_config.as_ref(),
+        setgroups_policy(),
+    )
+    .map_err(failed)?;
+
+    // Parse Reaper annotations from state for per-pod config overrides.
+    // Annotations are stored with the prefix already stripped, so use
+    // parse_stripped_annotations() to avoid a wasteful re-prefix round-trip.
+    let parsed_annotations = state
+        .annotations
+        .as_ref()
+        .and_then(annotations::parse_stripped_annotations);
+    if let Some(ref annots) = parsed_annotations {
+        info!("do_start() - parsed annotations: {:?}", annots);
+    }
+
+    let fast_exit = parsed_annotations
+        .as_ref()
+        .and_then(|a| a.fast_exit)
+        .unwrap_or(false);
+    let argv0 = parsed_annotations.as_ref().and_then(|a| a.argv0.clone());
+    let env_file = parsed_annotations
+        .as_ref()
+        .and_then(|a| a.env_from_mount.clone());
+    let env_file_roots = env_file_roots(&cfg.mounts);
+
+    timer.mark("prepare");
+    if let Some(after) = parsed_annotations.as_ref().and_then(|a| a.after.as_ref()) {
+        wait_for_start_dependency(
+            &state,
+            after,
+            start_after_timeout(),
+            std::time::Duration::from_millis(200),
+        )
+        .map_err(failed)?;
+        timer.mark("start_after");
+    }
+
+    let rlimits = workload_rlimits(&proc.rlimits);
+    if !rlimits.is_empty() {
+        info!("do_start() - rlimits: {:?}", rlimits);
+    }
+    #[cfg(target_os = "linux")]
+    let enable_fuse = parsed_annotations
+        .as_ref()
+        .and_then(|a| a.enable_fuse)
+        .unwrap_or(false);
+    #[cfg(target_os = "linux")]
+    let mut capabilities = proc.capabilities.clone();
+    #[cfg(target_os = "linux")]
+    if enable_fuse {
+        match capabilities.as_mut() {
+            // Mounting a FUSE filesystem without fusermount needs CAP_SYS_ADMIN
+            Some(spec) if caps::fuse_sys_admin_allowed() => {
+                caps::grant(spec, "CAP_SYS_ADMIN");
+                info!("do_start() - enable-fuse: granting CAP_SYS_ADMIN");
+            }
+            Some(spec) if !spec.effective.iter().any(|c| c == "CAP_SYS_ADMIN") => {
+                tracing::warn!(
+                    "do_start() - enable-fuse: workload lacks CAP_SYS_ADMIN; FUSE mounts \
+                     need a setuid fusermount unless REAPER_FUSE_CAP_SYS_ADMIN=1"
+                );
+            }
+            _ => {}
+        }
+    }
+    #[cfg(target_os = "linux")]
+    let cap_plan = capabilities.as_ref().map(caps::CapabilityPlan::from_spec);
+    #[cfg(target_os = "linux")]
+    if let Some(ref plan) = cap_plan {
+        info!("do_start() - capabilities: {:?}", plan);
+    }
+    let no_new_privs = proc.no_new_privileges;
+    if no_new_privs {
+        info!("do_start() - noNewPrivileges set");
+    }
+    #[cfg(target_os = "linux")]
+    let protect_proc = !is_privileged(&cfg);
+    #[cfg(target_os = "linux")]
+    info!("do_start() - read-only /proc/sys: {}", protect_proc);
+
+    // Clone data needed for the forked child
+    let container_id = id.to_string();
+    let container_namespace = state.namespace.clone();
+    #[cfg(target_os = "linux")]
+    let image_rootfs = state.image_rootfs.clone();
+    let cwd = Some(effective_cwd(proc.cwd.as_deref()));
+    let env_vars = proc.env.clone();
+    #[cfg(target_os = "linux")]
+    let oci_mounts = cfg.mounts.clone();
+    #[cfg(target_os = "linux")]
+    let dns_mode_override = parsed_annotations.as_ref().and_then(|a| a.dns_mode.clone());
+    #[cfg(target_os = "linux")]
+    let hostname = workload_hostname(&cfg);
+    #[cfg(target_os = "linux")]
+    let host_network = is_host_network(
+        &cfg,
+        parsed_annotations.as_ref().and_then(|a| a.host_network),
+    );
+    #[cfg(target_os = "linux")]
+    let overlay_name_override = parsed_annotations
+        .as_ref()
+        .and_then(|a| a.overlay_name.clone());
+    #[cfg(target_os = "linux")]
+    let isolation_override = parsed_annotations
+        .as_ref()
+        .and_then(|a| a.overlay_isolation.clone());
+    #[cfg(target_os = "linux")]
+    let shared_overlay = parsed_annotations
+        .as_ref()
+        .and_then(|a| a.shared_overlay.clone());
+    #[cfg(target_os = "linux")]
+    let cgroup_limits = match cfg.linux.as_ref().and_then(|l| l.resources.as_ref()) {
+        Some(resources) => cgroup::CgroupLimits::from_resources(resources).unwrap_or_else(|e| {
+            tracing::warn!("do_start() - not enforcing linux.resources: {:#}", e);
+            cgroup::CgroupLimits::default()
+        }),
+        None => cgroup::CgroupLimits::default(),
+    };
+    #[cfg(target_os = "linux")]
+    let cgroups_path = cfg
+        .linux
+        .as_ref()
+        .and_then(|l| l.cgroups_path.clone())
+        .or_else(|| (!cgroup_limits.is_empty()).then(|| cgroup::default_cgroups_path(id)));
+    #[cfg(target_os = "linux")]
+    let volume_policy = overlay::read_volume_policy_with_override(
+        parsed_annotations
+            .as_ref()
+            .and_then(|a| a.missing_volume_policy.as_ref()),
+    );
+
+    let mut audit_log = audit::open_log();
+
+    use nix::unistd::{fork, ForkResult};
+
+    // CRITICAL: Fork FIRST, then spawn the workload in the forked child.
+    // This ensures the monitoring daemon is the actual parent of the workload,
+    // allowing it to properly call wait() and reap the child.
+    //
+    // Previous bug: We were spawning the workload first, then forking.
+    // After fork(), the std::process::Child handle is invalid in the forked child
+    // because it was created by the parent process.
+
+    match unsafe { fork() } {
+        Ok(ForkResult::Parent { child: daemon_pid }) => {
+            // Parent process (original reaper-runtime start command)
+            // We don't know the workload PID yet - the daemon will report it
+            // For now, just report the daemon PID and let the daemon update state
+            info!(
+                "do_start() - forked monitoring daemon (pid={}), parent returning",
+                daemon_pid
+            );
+
+            // Wait for daemon to spawn workload and update state
+            // The daemon needs time to: setsid(), [enter overlay namespace on Linux], spawn workload, write state to disk
+            // On Linux, overlay namespace setup can take significant time (creating namespace, mounting, pivot_root)
+            // Poll the state file until we see the workload PID or the container exits
+            let mut workload_pid = None;
+            let max_attempts = if cfg!(target_os = "linux") { 100 } else { 20 };
+            let poll_interval_ms = 100;
+
+            for attempt in 0..max_attempts {
+                if let Ok(state) = load_state(&container_id) {
+                    if let Some(pid) = state.pid {
+                        workload_pid = Some(pid);
+                        break;
+                    }
+                    // If container is already stopped, daemon failed to start workload
+                    if state.status == STATUS_STOPPED {
+                        info!(
+                            "do_start() - container stopped before PID was recorded (daemon likely failed), exit_code={:?}",
+                            state.exit_code
+                        );
+                        break;
+                    }
+                }
+                std::thread::sleep(std::time::Duration::from_millis(poll_interval_ms));
+
+                // Log progress every second for debugging
+                if attempt > 0 && attempt % 10 == 0 {
+                    info!(
+                        "do_start() - still waiting for workload PID (attempt {}/{})",
+                        attempt, max_attempts
+                    );
+                }
+            }
+
+            // Report the workload PID if we got it, otherwise fall back to daemon PID
+            let started_pid = match workload_pid {
+                Some(pid) => pid,
+                None => {
+                    info!(
+                        "do_start() - timeout waiting for workload PID after {}ms, reporting daemon PID instead",
+                        max_attempts * poll_interval_ms
+                    );
+                    daemon_pid.as_raw()
+                }
+            };
+            if let Some(path) = pid_file {
+                write_pid_file(path, started_pid)?;
+                info!(
+                    "do_start() - wrote pid {} to {}",
+                    started_pid,
+                    path.display()
+                );
+            }
+            println!("started pid={}", started_pid);
+
+            // Attempt to reap daemon if it has already exited (non-blocking).
+            // WNOHANG means don't block if still running. This prevents zombie processes.
+            use nix::sys::wait::{waitpid, WaitPidFlag};
+            use nix::unistd::Pid;
+            let daemon_pid_raw = Pid::from_raw(daemon_pid.as_raw());
+            let _ = waitpid(daemon_pid_raw, Some(WaitPidFlag::WNOHANG));
+
+            Ok(())
+        }
+        Ok(ForkResult::Child) => {
+            // Child process (monitoring daemon)
+            // This process will spawn and monitor the workload
+
+            // CRITICAL: Close inherited stdout/stderr immediately after fork.
+            // The shim calls reaper-runtime via cmd.output() which creates pipes.
+            // If we keep those pipe fds open, cmd.output() blocks until WE exit,
+            // which prevents the shim's start() from returning and leaves the pod
+            // stuck in ContainerCreating. Redirecting to /dev/null lets the parent's
+            // pipes close when the parent exits.
+            {
+                use std::os::unix::io::AsRawFd;
+                if let Ok(devnull) = std::fs::File::open("/dev/null") {
+                    let fd = devnull.as_raw_fd();
+                    unsafe {
+                        nix::libc::dup2(fd, 1); // stdout
+                        nix::libc::dup2(fd, 2); // stderr
+                    }
+                }
+            }
+
+            // Detach from parent session to become a proper daemon
+            if let Err(e) = nix::unistd::setsid() {
+                // Can't use eprintln! here since stderr is now /dev/null
+                let _ = e;
+            }
+
+            // Create the workload's cgroup while /sys/fs/cgroup is still the
+            // host's; the workload moves itself in before exec. Best effort.
+            #[cfg(target_os = "linux")]
+            let workload_cgroup = cgroups_path
+                .as_deref()
+                .filter(|_| !state::rootless_enabled())
+                .and_then(|p| {
+                    match cgroup::WorkloadCgroup::prepare(Path::new(cgroup::CGROUP_ROOT), p) {
+                        Ok(c) => Some(c),
+                        Err(e) => {
+                            tracing::warn!("do_start() - not joining cgroupsPath {}: {:#}", p, e);
+                            None
+                        }
+                    }
+                });
+            #[cfg(target_os = "linux")]
+            let limits_applied = match workload_cgroup
+                .as_ref()
+                .filter(|_| !cgroup_limits.is_empty())
+            {
+                Some(cgroup) => {
+                    match cgroup.apply_limits(Path::new(cgroup::CGROUP_ROOT), &cgroup_limits) {
+                        Ok(()) => {
+                            info!(
+                                "do_start() - cgroup {} limits: {:?}",
+                                cgroup.path.display(),
+                                cgroup_limits
+                            );
+                            true
+                        }
+                        Err(e) => {
+                            tracing::warn!("do_start() - resource limits not enforced: {:#}", e);
+                            false
+                        }
+                    }
+                }
+                None => false,
+            };
+            #[cfg(target_os = "linux")]
+            if cgroups_path.is_some() {
+                timer.mark("cgroup");
+            }
+            // OOM kills the cgroup saw before the workload, if it was reused
+            #[cfg(target_os = "linux")]
+            let oom_kills_before = workload_cgroup.as_ref().and_then(|c| c.oom_kills());
+            #[cfg(target_os = "linux")]
+            let cgroup_procs = workload_cgroup.as_ref().map(|c| c.procs_fd());
+            #[cfg(target_os = "linux")]
+            let memory_limited = cgroup_limits
+                .memory_max
+                .as_deref()
+                .is_some_and(|m| m != "max");
+
+            // Join shared overlay namespace (Linux only).
+            // Overlay is mandatory in production — workloads must not run on the host filesystem.
+            #[cfg(target_os = "linux")]
+            let isolated = !overlay_disabled();
+            #[cfg(target_os = "linux")]
+            let effective = {
+                if !isolated {
+                    info!("do_start() - overlay disabled via REAPER_NO_OVERLAY");
+                    state::EffectiveSettings::default()
+                } else {
+                    let overlay_config = match container_overlay_config(
+                        &container_id,
+                        container_namespace.as_deref(),
+                        overlay_name_override.as_deref(),
+                        isolation_override.as_deref(),
+                        shared_overlay.as_deref(),
+                        image_rootfs.as_deref(),
+                    ) {
+                        Ok(c) => c,
+                        Err(e) => {
+                            tracing::error!(
+                                "do_start() - overlay config failed: {:#}, refusing to run",
+                                e
+                            );
+                            mark_runtime_failure(&container_id);
+                            std::process::exit(1);
+                        }
+                    };
+                    let entered = traced(
+                        tracing::info_span!(
+                            "overlay_setup",
+                            container.id = %container_id,
+                            k8s.namespace = container_namespace.as_deref(),
+                            outcome = tracing::field::Empty
+                        ),
+                        || overlay::enter_overlay(&overlay_config, &oci_mounts),
+                    );
+                    if let Err(e) = entered {
+                        tracing::error!(
+                            "do_start() - overlay setup failed: {:#}, refusing to run without isolation",
+                            e
+                        );
+                        if let Some(failure) = e.downcast_ref::<overlay::FilterFailure>() {
+                            mark_filter_failure(&container_id, failure);
+                            std::process::exit(overlay::FILTER_FAILURE_EXIT_CODE);
+                        }
+                        mark_runtime_failure(&container_id);
+                        std::process::exit(1);
+                    }
+                    info!("do_start() - joined shared overlay namespace");
+                    timer.mark("overlay");
+
+                    // Apply volume mounts from OCI config (FATAL on failure)
+                    let mut volume_mounts = Vec::new();
+                    if !oci_mounts.is_empty() {
+                        let mounted = traced(
+                            tracing::info_span!(
+                                "volume_mount",
+                                container.id = %container_id,
+                                mounts = oci_mounts.len(),
+                                outcome = tracing::field::Empty
+                            ),
+                            || overlay::apply_volume_mounts(&oci_mounts, &volume_policy),
+                        );
+                        match mounted {
+                            Ok(applied) => volume_mounts = applied,
+                            Err(e) => {
+                                tracing::error!(
+                                    "do_start() - volume mount failed: {:#}, refusing to start workload",
+                                    e
+                                );
+                                mark_runtime_failure(&container_id);
+                                std::process::exit(1);
+                            }
+                        }
+                        info!("do_start() - volume mounts applied");
+                        timer.mark("volume_mounts");
+                    }
+
+                    // Apply Kubernetes DNS if configured (FATAL on failure)
+                    // Per-pod annotation override takes precedence over node-level config;
+                    // host-network pods keep the host resolver unless overridden
+                    let dns_config =
+                        overlay::read_pod_dns_config(dns_mode_override.as_deref(), host_network);
+                    if dns_config.mode == overlay::DnsMode::Kubernetes {
+                        if let Err(e) = overlay::apply_kubernetes_dns(&oci_mounts) {
+                            tracing::error!(
+                                "do_start() - kubernetes DNS setup failed: {:#}, refusing to start workload",
+                                e
+                            );
+                            mark_runtime_failure(&container_id);
+                            std::process::exit(1);
+                        }
+                        info!("do_start() - kubernetes DNS configured");
+                        timer.mark("dns");
+                    }
+
+                    state::EffectiveSettings {
+                        overlay_isolation: match shared_overlay {
+                            Some(_) => Some("shared".to_string()),
+                            None => overlay::read_isolation_mode_with_override(
+                                isolation_override.as_deref(),
+                            )
+                            .map(|mode| mode.as_str().to_string())
+                            .ok(),
+                        },
+                        filter_enabled: overlay::read_filter_config().enabled,
+                        dns_mode: dns_config.mode.as_str().to_string(),
+                        pid_namespace: false,
+                        volume_mounts,
+                        overlay_upper: Some(overlay_config.base_dir.join("upper")),
+                        overlay_ns: Some(overlay_config.ns_path.clone()),
+                    }
+                }
+            };
+
+            // After joining the overlay: a rootless daemon only has the
+            // capability for it inside the overlay's user namespace
+            #[cfg(target_os = "linux")]
+            if let Some(name) = hostname.as_deref() {
+                if let Err(e) = enter_uts_namespace(name) {
+                    tracing::error!(
+                        "do_start() - hostname {:?} not set: {:#}, refusing to start workload",
+                        name,
+                        e
+                    );
+                    mark_runtime_failure(&container_id);
+                    std::process::exit(1);
+                }
+                // Only in the overlay: without it /etc is the host's
+                if isolated {
+                    if let Err(e) =
+                        overlay::write_etc_hostname(Path::new("/etc"), name, &oci_mounts)
+                    {
+                        tracing::warn!("do_start() - /etc/hostname not updated: {:#}", e);
+                    }
+                }
+                info!("do_start() - hostname set to {:?}", name);
+            }
+
+            // In the container's mount namespace, where the workload looks for it
+            #[cfg(target_os = "linux")]
+            if enable_fuse {
+                if let Err(e) = overlay::ensure_fuse_device() {
+                    tracing::error!(
+                        "do_start() - enable-fuse: /dev/fuse unavailable: {:#}, refusing to start workload",
+                        e
+                    );
+                    mark_runtime_failure(&container_id);
+                    std::process::exit(1);
+                }
+                info!("do_start() - /dev/fuse available");
+            }
+
+            // After volume mounts, so the file can come from one of the pod's volumes
+            let env_vars = match env_file.as_deref() {
+                Some(path) => env_from_file(
+                    env_vars,
+                    Path::new(path),
+                    &env_file_roots,
+                    user_config.as_ref(),
+                ),
+                None => env_vars,
+            };
+            let env_vars = derive_user_env(passthrough_env(env_vars), user_config.as_ref());
+
+            let args_size =
+                exec_args_size(&program_path, &argv, &workload_env(env_vars.as_deref()));
+            let args_limit = arg_max();
+            if let Err(message) = check_exec_args_size(args_size, args_limit) {
+                mark_spawn_failure(&container_id, message);
+                std::process::exit(1);
+            }
+            let describe_spawn_failure = |e: &std::io::Error| {
+                if e.raw_os_error() == Some(nix::libc::E2BIG) {
+                    args_too_long_message(args_size, args_limit)
+                } else {
+                    spawn_failure_message(&program_path, cwd.as_deref(), e)
+                }
+            };
+
+            if let Some(log) = audit_log.as_mut() {
+                let full_argv = std::iter::once(argv0.as_deref().unwrap_or(&program))
+                    .chain(argv.iter().map(String::as_str))
+                    .collect();
+                let record = audit_record(
+                    &container_id,
+                    None,
+                    container_namespace.as_deref(),
+                    &program,
+                    full_argv,
+                    user_config.as_ref(),
+                    cwd.as_deref(),
+                );
+                audit::write_record(log, &record);
+            }
+
+            // Now spawn the workload - we are its parent!
+            // Reload state to get I/O paths and terminal flag from create
+            let io_state = load_state(&container_id).ok();
+            let use_terminal = use_terminal(
+                io_state.as_ref().is_some_and(|s| s.terminal),
+                config_terminal,
+            );
+            let log_socket =
+                log_socket_path(&container_id).and_then(|path| LogSocket::connect(&path));
+            let cri_log = crilog::enabled();
+
+            // Clone user config for use in pre_exec closures (both PTY and non-PTY modes)
+            let user_cfg_for_exec = user_config.clone();
+            let setgroups_policy = setgroups_policy_for(user_config.as_ref());
+
+            if use_terminal {
+                // Terminal mode: allocate a PTY so the shell sees isatty()=true.
+                // Relay between containerd FIFOs and the PTY master.
+                use nix::pty::openpty;
+                use std::io::{Read, Write};
+                use std::os::unix::io::AsRawFd;
+
+                let pty = match openpty(None, None) {
+                    Ok(pty) => pty,
+                    Err(e) => {
+                        tracing::error!("do_start() - openpty failed: {}", e);
+                        mark_runtime_failure(&container_id);
+                        std::process::exit(1);
+                    }
+                };
+
+                let slave_raw_fd = pty.slave.as_raw_fd();
+
+                let mut cmd = Command::new(&program_path);
+                cmd.args(&argv);
+                if let Some(ref argv0) = argv0 {
+                    cmd.arg0(argv0);
+                }
+                if let Some(cwd) = cwd.as_deref() {
+                    cmd.current_dir(cwd);
+                }
+                if let Some(envs) = env_vars.as_ref() {
+                    for kv in envs {
+                        if let Some((k, v)) = kv.split_once('=') {
+                            cmd.env(k, v);
+                        }
+                    }
+                }
+
+                // PTY slave becomes stdin/stdout/stderr via pre_exec
+                cmd.stdin(Stdio::null());
+                cmd.stdout(Stdio::null());
+                cmd.stderr(Stdio::null());
+
+                let pty_rlimits = rlimits.clone();
+                unsafe {
+                    cmd.pre_exec(move || {
+                        #[cfg(target_os = "linux")]
+                        if let Some(fd) = cgroup_procs {
+                            // First, so nothing the workload forks starts
+                            // outside. Checked after spawn; never run
+                            // without the limits applied to the cgroup.
+                            if cgroup::join_self(fd).is_err() && limits_applied {
+                                nix::libc::_exit(1);
+                            }
+                        }
+                        // New session so we can set controlling terminal
+                        if nix::libc::setsid() < 0 {
+                            return Err(std::io::Error::last_os_error());
+                        }
+                        // Before dropping privileges, which may be needed to raise hard limits
+                        for &(resource, soft, hard) in &pty_rlimits {
+                            nix::sys::resource::setrlimit(resource, soft, hard)?;
+                        }
+                        #[cfg(target_os = "linux")]
+                        if protect_proc {
+                            overlay::protect_proc_sys()?;
+                        }
+                        // Set controlling terminal
+                        // TIOCSCTTY: ioctl request type varies by arch (c_int on aarch64, c_ulong on x86_64/macOS)
+                        if nix::libc::ioctl(
+                            slave_raw_fd,
+                            nix::libc::TIOCSCTTY as _,
+                            0 as nix::libc::c_int,
+                        ) < 0
+                        {
+                            return Err(std::io::Error::last_os_error());
+                        }
+                        // Dup slave to stdin/stdout/stderr
+                        nix::libc::dup2(slave_raw_fd, 0);
+                        nix::libc::dup2(slave_raw_fd, 1);
+                        nix::libc::dup2(slave_raw_fd, 2);
+                        if slave_raw_fd > 2 {
+                            nix::libc::close(slave_raw_fd);
+                        }
+
+                        #[cfg(target_os = "linux")]
+                        if let Some(ref plan) = cap_plan {
+                            plan.narrow_bounding()?;
+                        }
+                        #[cfg(target_os = "linux")]
+                        if no_new_privs {
+                            set_no_new_privs()?;
+                        }
+
+                        // Apply user/group configuration if present
+                        if let Some(ref user) = user_cfg_for_exec {
+                            // Always clear/set supplementary groups (must be done while privileged).
+                            // When empty, this clears inherited root supplementary groups.
+                            apply_setgroups(&user.additional_gids, setgroups_policy)?;
+
+                            // Set GID before UID (privilege dropping order matters)
+                            if nix::libc::setgid(user.gid) != 0 {
+                                return Err(std::io::Error::last_os_error());
+                            }
+
+                            // Set UID (this must be last - irreversible privilege drop)
+                            if nix::libc::setuid(user.uid) != 0 {
+                                return Err(std::io::Error::last_os_error());
+                            }
+
+                            // Apply umask if specified
+                            if let Some(mask) = user.umask {
+                                nix::libc::umask(mask as nix::libc::mode_t);
+                            }
+                        }
+
+                        #[cfg(target_os = "linux")]
+                        if let Some(ref plan) = cap_plan {
+                            plan.apply()?;
+                        }
+
+                        Ok(())
+                    });
+                }
+
+                match cmd.spawn() {
+                    Ok(child) => {
+                        let workload_pid = child.id() as i32;
+                        #[cfg(target_os = "linux")]
+                        let cgroup_path =
+                            joined_workload_cgroup(workload_cgroup.as_ref(), workload_pid);
+                        #[cfg(target_os = "linux")]
+                        if limits_applied && cgroup_path.is_none() {
+                            refuse_unlimited_workload(&container_id, child);
+                        }
+
+                        timer.mark("spawn");
+                        info!("do_start() - timings: {}", format_timings(timer.phases()));
+                        if let Ok(mut state) = load_state(&container_id) {
+                            state.status = STATUS_RUNNING.into();
+                            state.pid = Some(workload_pid);
+                            state.started_at = Some(state::unix_now());
+                            state.timings.extend_from_slice(timer.phases());
+                            #[cfg(target_os = "linux")]
+                            {
+                                state.cgroup_path = cgroup_path;
+                                state.effective = Some(effective.clone());
+                                state.isolated = isolated;
+                            }
+                            let _ = save_state(&state);
+                            let _ = save_pid(&container_id, workload_pid);
+                        }
+
+                        // Close slave in parent - child has it via dup2
+                        drop(pty.slave);
+
+                        // Capture master raw fd for resize ioctl before converting to File
+                        #[cfg(target_os = "linux")]
+                        let master_raw_fd = {
+                            use std::os::unix::io::AsRawFd;
+                            pty.master.as_raw_fd()
+                        };
+
+                        // Convert PTY master OwnedFd to File for I/O
+                        let master_file: std::fs::File = pty.master.into();
+                        let master_clone = master_file.try_clone().unwrap_or_else(|e| {
+                            tracing::error!("failed to clone master fd: {}", e);
+                            std::process::exit(1);
+                        });
+
+                        // Start PTY resize watcher thread
+                        #[cfg(target_os = "linux")]
+                        let resize_stop =
+                            std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+                        #[cfg(target_os = "linux")]
+                        spawn_resize_watcher(
+                            master_raw_fd,
+                            resize_path(&container_id),
+                            resize_stop.clone(),
+                        );
 
                         // Relay: stdin FIFO → PTY master (user input to process)
                         if let Some(ref state) = io_state {
@@ -764,1327 +3136,3819 @@ fn do_start(id: &str, bundle: &Path) -> Result<()> {
                             }
                         }
 
-                        // Relay: PTY master → stdout FIFO (process output to user)
-                        if let Some(ref state) = io_state {
-                            if let Some(ref stdout_path) = state.stdout {
-                                if !stdout_path.is_empty() {
-                                    let stdout_path = stdout_path.clone();
-                                    let mut master_r = master_file;
-                                    std::thread::spawn(move || {
-                                        if let Ok(mut stdout_file) = std::fs::OpenOptions::new()
-                                            .write(true)
-                                            .open(&stdout_path)
-                                        {
-                                            let mut buf = [0u8; 4096];
-                                            loop {
-                                                match master_r.read(&mut buf) {
-                                                    Ok(0) => break,
-                                                    Ok(n) => {
-                                                        if stdout_file.write_all(&buf[..n]).is_err()
-                                                        {
-                                                            break;
-                                                        }
-                                                    }
-                                                    Err(_) => break, // EIO when slave closes
-                                                }
-                                            }
-                                        }
-                                    });
-                                }
-                            }
+                        // Relay: PTY master → stdout FIFO (process output to user)
+                        if let Some(ref state) = io_state {
+                            if let Some(ref stdout_path) = state.stdout {
+                                if !stdout_path.is_empty() {
+                                    let stdout_path = stdout_path.clone();
+                                    let master_r = master_file;
+                                    let log_socket = log_socket.clone();
+                                    std::thread::spawn(move || {
+                                        if let Ok(stdout_file) = std::fs::OpenOptions::new()
+                                            .write(true)
+                                            .open(&stdout_path)
+                                        {
+                                            // A PTY merges stderr into stdout
+                                            if cri_log
+                                                && stdout_file.metadata().is_ok_and(|m| m.is_file())
+                                            {
+                                                relay_output(
+                                                    master_r,
+                                                    crilog::CriLogWriter::new(
+                                                        stdout_file,
+                                                        "stdout",
+                                                    ),
+                                                    log_socket.as_ref(),
+                                                );
+                                            } else {
+                                                relay_output(
+                                                    master_r,
+                                                    stdout_file,
+                                                    log_socket.as_ref(),
+                                                );
+                                            }
+                                        }
+                                    });
+                                }
+                            }
+                        }
+
+                        std::thread::sleep(settle_delay(true, fast_exit));
+
+                        // Hold the stdout FIFO write end open so containerd doesn't
+                        // see EOF when the relay thread exits. This prevents a race
+                        // where containerd tears down streams via stdout-EOF while
+                        // also receiving the TaskExit event. O_NONBLOCK because by
+                        // now containerd should have the read end open (via attach).
+                        let _stdout_holder = {
+                            use std::os::unix::fs::OpenOptionsExt;
+                            if let Some(ref state) = io_state {
+                                state.stdout.as_ref().and_then(|p| {
+                                    std::fs::OpenOptions::new()
+                                        .write(true)
+                                        .custom_flags(nix::libc::O_NONBLOCK)
+                                        .open(p)
+                                        .ok()
+                                })
+                            } else {
+                                None
+                            }
+                        };
+
+                        match wait_with_usage(child) {
+                            Ok((exit_status, usage)) => {
+                                #[cfg(target_os = "linux")]
+                                resize_stop.store(true, std::sync::atomic::Ordering::Relaxed);
+                                let exit_code = exit_code_from_status(exit_status, true);
+                                #[cfg(target_os = "linux")]
+                                let oom = detect_oom(
+                                    workload_cgroup.as_ref(),
+                                    oom_kills_before,
+                                    memory_limited,
+                                    exit_status.signal(),
+                                );
+                                #[cfg(not(target_os = "linux"))]
+                                let oom = false;
+                                mark_workload_exit(
+                                    &container_id,
+                                    exit_code,
+                                    exit_status.signal(),
+                                    Some(usage),
+                                    oom,
+                                );
+                            }
+                            Err(_e) => {
+                                #[cfg(target_os = "linux")]
+                                resize_stop.store(true, std::sync::atomic::Ordering::Relaxed);
+                                mark_runtime_failure(&container_id);
+                            }
+                        }
+                        #[cfg(target_os = "linux")]
+                        remove_workload_cgroup(workload_cgroup.as_ref());
+
+                        // Keep the daemon alive briefly so the shim can detect the
+                        // stopped state and publish the TaskExit event before we
+                        // drop _stdout_holder. This ensures containerd tears down
+                        // streams via the orderly TaskExit path, not a racy
+                        // stdout-EOF path.
+                        std::thread::sleep(std::time::Duration::from_secs(2));
+                    }
+                    Err(e) => {
+                        mark_spawn_failure(&container_id, describe_spawn_failure(&e));
+                    }
+                }
+            } else {
+                // Non-terminal mode: connect FIFOs directly to the process
+                // (through a relay only when teeing to a log socket)
+                let mut socket_tee = LogSocketTee::default();
+                let mut cmd = Command::new(&program_path);
+                cmd.args(&argv);
+                if let Some(ref argv0) = argv0 {
+                    cmd.arg0(argv0);
+                }
+                if let Some(cwd) = cwd.as_deref() {
+                    cmd.current_dir(cwd);
+                }
+                if let Some(envs) = env_vars.as_ref() {
+                    for kv in envs {
+                        if let Some((k, v)) = kv.split_once('=') {
+                            cmd.env(k, v);
+                        }
+                    }
+                }
+
+                // Configure stdin: use FIFO if available, otherwise null.
+                // Open with O_RDWR so the FIFO always has a writer — prevents EOF
+                // when the real writer (containerd/kubectl attach) hasn't connected yet.
+                // Without O_RDWR, the shell reads EOF immediately and exits.
+                if let Some(ref state) = io_state {
+                    if let Some(ref stdin_path) = state.stdin {
+                        if !stdin_path.is_empty() {
+                            use std::fs::OpenOptions;
+                            use std::os::unix::fs::OpenOptionsExt;
+                            match OpenOptions::new()
+                                .read(true)
+                                .write(true)
+                                .custom_flags(nix::libc::O_NONBLOCK)
+                                .open(stdin_path)
+                            {
+                                Ok(file) => {
+                                    // Clear O_NONBLOCK after open so reads block normally
+                                    use std::os::unix::io::AsRawFd;
+                                    unsafe {
+                                        let flags =
+                                            nix::libc::fcntl(file.as_raw_fd(), nix::libc::F_GETFL);
+                                        nix::libc::fcntl(
+                                            file.as_raw_fd(),
+                                            nix::libc::F_SETFL,
+                                            flags & !nix::libc::O_NONBLOCK,
+                                        );
+                                    }
+                                    cmd.stdin(Stdio::from(file));
+                                    info!("do_start() - connected stdin from FIFO: {}", stdin_path);
+                                }
+                                Err(e) => {
+                                    info!(
+                                    "do_start() - failed to open stdin FIFO ({}), using null: {}",
+                                    stdin_path, e
+                                );
+                                    cmd.stdin(Stdio::null());
+                                }
+                            }
+                        } else {
+                            cmd.stdin(Stdio::null());
+                        }
+                    } else {
+                        cmd.stdin(Stdio::null());
+                    }
+                } else {
+                    cmd.stdin(Stdio::null());
+                }
+
+                // Configure stdout: use FIFO if available, otherwise inherit
+                if let Some(ref state) = io_state {
+                    if let Some(ref stdout_path) = state.stdout {
+                        if !stdout_path.is_empty() {
+                            match open_log_file(stdout_path) {
+                                Ok(file) => {
+                                    cmd.stdout(socket_tee.stdio(
+                                        file,
+                                        log_socket.as_ref(),
+                                        cri_log.then_some("stdout"),
+                                    ));
+                                    info!(
+                                        "do_start() - redirected stdout to FIFO: {}",
+                                        stdout_path
+                                    );
+                                }
+                                Err(e) => {
+                                    tracing::warn!("do_start() - failed to open stdout FIFO ({}), falling back to inherit: {:#}", stdout_path, e);
+                                    cmd.stdout(Stdio::inherit());
+                                }
+                            }
+                        } else {
+                            cmd.stdout(Stdio::inherit());
+                        }
+                    } else {
+                        cmd.stdout(Stdio::inherit());
+                    }
+                } else {
+                    cmd.stdout(Stdio::inherit());
+                }
+
+                // Configure stderr: use FIFO if available, otherwise inherit
+                if let Some(ref state) = io_state {
+                    if let Some(ref stderr_path) = state.stderr {
+                        if !stderr_path.is_empty() {
+                            match open_log_file(stderr_path) {
+                                Ok(file) => {
+                                    cmd.stderr(socket_tee.stdio(
+                                        file,
+                                        log_socket.as_ref(),
+                                        cri_log.then_some("stderr"),
+                                    ));
+                                    info!(
+                                        "do_start() - redirected stderr to FIFO: {}",
+                                        stderr_path
+                                    );
+                                }
+                                Err(e) => {
+                                    tracing::warn!("do_start() - failed to open stderr FIFO ({}), falling back to inherit: {:#}", stderr_path, e);
+                                    cmd.stderr(Stdio::inherit());
+                                }
+                            }
+                        } else {
+                            cmd.stderr(Stdio::inherit());
+                        }
+                    } else {
+                        cmd.stderr(Stdio::inherit());
+                    }
+                } else {
+                    cmd.stderr(Stdio::inherit());
+                }
+
+                // Always call setsid() so the workload gets its own process group
+                // (PGID == workload PID). This is critical for do_kill() which sends
+                // signals to -pid (the process group). Without setsid(), the workload
+                // inherits the daemon's PGID, so kill(-workload_pid) targets a
+                // non-existent process group and the signal never reaches the process.
+                {
+                    let user_cfg_clone = user_config.clone();
+                    let rlimits = rlimits.clone();
+                    unsafe {
+                        cmd.pre_exec(move || {
+                            #[cfg(target_os = "linux")]
+                            if let Some(fd) = cgroup_procs {
+                                // First, so nothing the workload forks starts
+                                // outside. Checked after spawn; never run
+                                // without the limits applied to the cgroup.
+                                if cgroup::join_self(fd).is_err() && limits_applied {
+                                    nix::libc::_exit(1);
+                                }
+                            }
+                            if nix::libc::setsid() < 0 {
+                                return Err(std::io::Error::last_os_error());
+                            }
+                            for &(resource, soft, hard) in &rlimits {
+                                nix::sys::resource::setrlimit(resource, soft, hard)?;
+                            }
+                            #[cfg(target_os = "linux")]
+                            if protect_proc {
+                                overlay::protect_proc_sys()?;
+                            }
+                            #[cfg(target_os = "linux")]
+                            if let Some(ref plan) = cap_plan {
+                                plan.narrow_bounding()?;
+                            }
+                            #[cfg(target_os = "linux")]
+                            if no_new_privs {
+                                set_no_new_privs()?;
+                            }
+
+                            // Apply user/group configuration if present
+                            if let Some(ref user) = user_cfg_clone {
+                                // Set supplementary groups first (must be done while privileged)
+                                if !user.additional_gids.is_empty() {
+                                    apply_setgroups(&user.additional_gids, setgroups_policy)?;
+                                }
+
+                                // Set GID before UID (privilege dropping order matters)
+                                if nix::libc::setgid(user.gid) != 0 {
+                                    return Err(std::io::Error::last_os_error());
+                                }
+
+                                // Set UID (this must be last - irreversible privilege drop)
+                                if nix::libc::setuid(user.uid) != 0 {
+                                    return Err(std::io::Error::last_os_error());
+                                }
+
+                                // Apply umask if specified
+                                if let Some(mask) = user.umask {
+                                    nix::libc::umask(mask as nix::libc::mode_t);
+                                }
+                            }
+
+                            #[cfg(target_os = "linux")]
+                            if let Some(ref plan) = cap_plan {
+                                plan.apply()?;
+                            }
+
+                            Ok(())
+                        });
+                    }
+                }
+
+                match cmd.spawn() {
+                    Ok(child) => {
+                        if socket_tee.is_active() {
+                            // Close our copies of the pipe write ends so the
+                            // relays see EOF when the workload exits
+                            drop(cmd);
+                        }
+                        let workload_pid = child.id() as i32;
+                        #[cfg(target_os = "linux")]
+                        let cgroup_path =
+                            joined_workload_cgroup(workload_cgroup.as_ref(), workload_pid);
+                        #[cfg(target_os = "linux")]
+                        if limits_applied && cgroup_path.is_none() {
+                            refuse_unlimited_workload(&container_id, child);
+                        }
+
+                        // Update state to running with the actual workload PID
+                        timer.mark("spawn");
+                        info!("do_start() - timings: {}", format_timings(timer.phases()));
+                        if let Ok(mut state) = load_state(&container_id) {
+                            state.status = STATUS_RUNNING.into();
+                            state.pid = Some(workload_pid);
+                            state.started_at = Some(state::unix_now());
+                            state.timings.extend_from_slice(timer.phases());
+                            #[cfg(target_os = "linux")]
+                            {
+                                state.cgroup_path = cgroup_path;
+                                state.effective = Some(effective.clone());
+                                state.isolated = isolated;
+                            }
+                            let _ = save_state(&state);
+                            let _ = save_pid(&container_id, workload_pid);
+                        }
+
+                        // IMPORTANT: Give containerd/kubelet time to observe the "running"
+                        // state before the container potentially exits.
+                        std::thread::sleep(settle_delay(false, fast_exit));
+
+                        // Wait for the workload process to exit
+                        // We are the parent, so this will work correctly!
+                        match wait_with_usage(child) {
+                            Ok((exit_status, usage)) => {
+                                let exit_code = exit_code_from_status(exit_status, false);
+                                socket_tee.finish(std::time::Duration::from_secs(2));
+                                if let Some(state) =
+                                    io_state.as_ref().filter(|_| log_eof_marker_enabled())
+                                {
+                                    append_log_eof_markers(
+                                        &[state.stdout.as_deref(), state.stderr.as_deref()],
+                                        exit_status,
+                                    );
+                                }
+                                #[cfg(target_os = "linux")]
+                                let oom = detect_oom(
+                                    workload_cgroup.as_ref(),
+                                    oom_kills_before,
+                                    memory_limited,
+                                    exit_status.signal(),
+                                );
+                                #[cfg(not(target_os = "linux"))]
+                                let oom = false;
+                                mark_workload_exit(
+                                    &container_id,
+                                    exit_code,
+                                    exit_status.signal(),
+                                    Some(usage),
+                                    oom,
+                                );
+                            }
+                            Err(_e) => {
+                                mark_runtime_failure(&container_id);
+                            }
+                        }
+                        #[cfg(target_os = "linux")]
+                        remove_workload_cgroup(workload_cgroup.as_ref());
+                    }
+                    Err(e) => {
+                        mark_spawn_failure(&container_id, describe_spawn_failure(&e));
+                    }
+                }
+            }
+
+            // Daemon exits after workload completes
+            std::process::exit(0);
+        }
+        Err(e) => {
+            bail!("Failed to fork monitoring daemon: {}", e);
+        }
+    }
+}
+
+fn do_state(id: &str) -> Result<()> {
+    info!("do_state() called - id={}", id);
+    let state = load_state(id)?;
+    info!(
+        "do_state() succeeded - id={}, status={}, pid={:?}",
+        id, state.status, state.pid
+    );
+    println!("{}", serde_json::to_string_pretty(&state)?);
+    Ok(())
+}
+
+/// States of all containers under the state root, sorted by ID, with one
+/// warning for each corrupt or unreadable entry skipped.
+fn list_containers() -> Vec<ContainerState> {
+    let listing = state::list_states();
+    for (id, err) in &listing.skipped {
+        eprintln!("reaper: list: skipping {:?}: {:#}", id, err);
+    }
+    listing.states
+}
+
+/// `ID PID STATUS BUNDLE` table, one container per line.
+fn render_container_table(states: &[ContainerState]) -> String {
+    let width = states.iter().map(|s| s.id.len()).max().unwrap_or(0).max(2);
+    let mut out = format!("{:<width$}  {:>8}  {:<8}  BUNDLE\n", "ID", "PID", "STATUS");
+    for s in states {
+        let pid = s.pid.map(|p| p.to_string()).unwrap_or_else(|| "-".into());
+        out.push_str(&format!(
+            "{:<width$}  {:>8}  {:<8}  {}\n",
+            s.id,
+            pid,
+            s.status,
+            s.bundle.display()
+        ));
+    }
+    out
+}
+
+fn do_list(format: &str) -> Result<()> {
+    let states = list_containers();
+    info!("do_list() - {} container(s)", states.len());
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&states)?);
+    } else {
+        print!("{}", render_container_table(&states));
+    }
+    Ok(())
+}
+
+/// PIDs from the `NSpid:` line of /proc/<pid>/status, outermost namespace first.
+#[cfg(target_os = "linux")]
+fn parse_nspid(status: &str) -> Vec<i32> {
+    status
+        .lines()
+        .find_map(|l| l.strip_prefix("NSpid:"))
+        .map(|ids| {
+            ids.split_whitespace()
+                .filter_map(|p| p.parse().ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(target_os = "linux")]
+fn parse_ppid(status: &str) -> Option<i32> {
+    status
+        .lines()
+        .find_map(|l| l.strip_prefix("PPid:"))
+        .and_then(|p| p.trim().parse().ok())
+}
+
+/// Whether the process is PID 1 of a PID namespace nested below ours.
+#[cfg(target_os = "linux")]
+fn is_nested_pid_namespace_init(status: &str) -> bool {
+    let ids = parse_nspid(status);
+    ids.len() > 1 && ids.last() == Some(&1)
+}
+
+/// Host PID of the init of the workload's PID namespace: the workload itself,
+/// or a direct child when the workload forked into a new PID namespace.
+/// `None` when no PID namespace is in use.
+#[cfg(target_os = "linux")]
+fn pid_namespace_init(pid: i32) -> Option<i32> {
+    let status = fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    if is_nested_pid_namespace_init(&status) {
+        return Some(pid);
+    }
+    fs::read_dir("/proc")
+        .ok()?
+        .flatten()
+        .filter_map(|e| e.file_name().to_str()?.parse::<i32>().ok())
+        .find(|&child| {
+            fs::read_to_string(format!("/proc/{}/status", child)).is_ok_and(|status| {
+                parse_ppid(&status) == Some(pid) && is_nested_pid_namespace_init(&status)
+            })
+        })
+}
+
+fn do_kill(id: &str, signal: Option<i32>, timeout: Option<u64>) -> Result<()> {
+    let signal = signal.unwrap_or(15); // Default to SIGTERM
+    info!("do_kill() called - id={}, signal={}", id, signal);
+    let pid = load_pid(id)?;
+    if pid <= 1 {
+        bail!("refusing to send signal to PID {} (must be > 1)", pid);
+    }
+    let sig = nix::sys::signal::Signal::try_from(signal).context("invalid signal")?;
+    signal_workload(pid, sig)?;
+    info!(
+        "do_kill() succeeded - id={}, signal={}, pid={}",
+        id, signal, pid
+    );
+
+    // A configured grace period applies to SIGTERM; an explicit --timeout to
+    // any signal short of SIGKILL
+    let escalate = match timeout {
+        Some(_) => sig != nix::sys::signal::Signal::SIGKILL,
+        None => sig == nix::sys::signal::Signal::SIGTERM,
+    };
+    if escalate {
+        let annotation = load_state(id)
+            .ok()
+            .and_then(|state| state.annotations)
+            .as_ref()
+            .and_then(annotations::parse_stripped_annotations)
+            .and_then(|parsed| parsed.termination_grace);
+        if let Some(grace) = kill_grace(timeout, annotation) {
+            info!(
+                "do_kill() - escalating to SIGKILL in {}s unless {} exits",
+                grace.as_secs(),
+                id
+            );
+            spawn_kill_escalation(id, pid, grace);
+        }
+    }
+    Ok(())
+}
+
+/// Send `sig` to the workload's process group. A process that has already
+/// exited is not an error.
+fn signal_workload(pid: i32, sig: nix::sys::signal::Signal) -> Result<()> {
+    info!(
+        "do_kill() - sending signal {} to process group (pgid={})",
+        sig, pid
+    );
+    // Kill the entire process group (-pid) so children of the workload (e.g. backgrounded
+    // processes) are also signalled. The workload calls setsid() in pre_exec, so its PGID
+    // equals its PID.
+    // A PID namespace init started by the workload (e.g. `unshare --pid --fork
+    // setsid ...`) may sit outside its process group; signal it first, while
+    // it is still the workload's child and can be found.
+    #[cfg(target_os = "linux")]
+    if let Some(init) = pid_namespace_init(pid).filter(|&init| init != pid) {
+        info!(
+            "do_kill() - sending signal {} to PID namespace init (pid={})",
+            sig, init
+        );
+        if let Err(e) = nix::sys::signal::kill(nix::unistd::Pid::from_raw(init), sig) {
+            tracing::warn!(
+                "do_kill() - signalling namespace init {} failed: {}",
+                init,
+                e
+            );
+        }
+    }
+    match nix::sys::signal::kill(nix::unistd::Pid::from_raw(-pid), sig) {
+        Ok(()) => Ok(()),
+        Err(nix::errno::Errno::ESRCH) => {
+            // Process doesn't exist - this is expected if the container already exited
+            // Return success since the goal (container not running) is achieved
+            info!(
+                "do_kill() - process {} already exited (ESRCH), treating as success",
+                pid
+            );
+            Ok(())
+        }
+        Err(e) => bail!("failed to send signal: {}", e),
+    }
+}
+
+/// Time between the signal and SIGKILL: `kill --timeout` when given, else
+/// `REAPER_KILL_GRACE_SECS` when set, else the container's
+/// `termination-grace` annotation. `None` (or `0`) disables escalation.
+fn kill_grace(timeout: Option<u64>, annotation: Option<u64>) -> Option<std::time::Duration> {
+    timeout
+        .or_else(|| {
+            std::env::var("REAPER_KILL_GRACE_SECS")
+                .ok()
+                .and_then(|v| v.trim().parse::<u64>().ok())
+        })
+        .or(annotation)
+        .filter(|&secs| secs > 0)
+        .map(std::time::Duration::from_secs)
+}
+
+/// Whether the workload `pid` that got SIGTERM is still running and needs
+/// SIGKILL. A recorded PID other than `pid` means the container was
+/// restarted under the same ID.
+fn needs_kill_escalation(state: Option<&ContainerState>, pid: i32) -> bool {
+    state.is_some_and(|s| s.status != STATUS_STOPPED && s.pid == Some(pid))
+}
+
+/// Run `task` in a detached (double-forked) process: its own session, with
+/// stdout and stderr on /dev/null. Returns once the intermediate child has
+/// been reaped, without waiting for `task`.
+#[cfg(not(tarpaulin_include))]
+fn spawn_detached(task: impl FnOnce()) -> nix::Result<()> {
+    use nix::unistd::{fork, ForkResult};
+    match unsafe { fork() }? {
+        ForkResult::Parent { child } => {
+            let _ = nix::sys::wait::waitpid(child, None);
+            Ok(())
+        }
+        ForkResult::Child => {
+            if let Ok(ForkResult::Child) = unsafe { fork() } {
+                // Release the shim's output pipes (see do_start)
+                {
+                    use std::os::unix::io::AsRawFd;
+                    if let Ok(devnull) = std::fs::File::open("/dev/null") {
+                        let fd = devnull.as_raw_fd();
+                        unsafe {
+                            nix::libc::dup2(fd, 1);
+                            nix::libc::dup2(fd, 2);
+                        }
+                    }
+                }
+                let _ = nix::unistd::setsid();
+                task();
+            }
+            unsafe { nix::libc::_exit(0) };
+        }
+    }
+}
+
+/// SIGKILL the workload after `grace` unless it exited. Runs detached so
+/// `kill` returns right away.
+#[cfg(not(tarpaulin_include))]
+fn spawn_kill_escalation(id: &str, pid: i32, grace: std::time::Duration) {
+    let spawned = spawn_detached(|| {
+        std::thread::sleep(grace);
+        if needs_kill_escalation(load_state(id).ok().as_ref(), pid) {
+            info!(
+                "do_kill() - {} still running after {}s grace, sending SIGKILL",
+                id,
+                grace.as_secs()
+            );
+            if let Err(e) = signal_workload(pid, nix::sys::signal::Signal::SIGKILL) {
+                tracing::warn!("do_kill() - SIGKILL escalation failed: {:#}", e);
+            }
+        }
+    });
+    if let Err(e) = spawned {
+        tracing::warn!("do_kill() - cannot fork SIGKILL escalation: {}", e);
+    }
+}
+
+fn do_delete(id: &str) -> Result<()> {
+    info!("do_delete() called - id={}", id);
+
+    // A namespace overlay to tear down if this was its last container
+    #[cfg(target_os = "linux")]
+    let mut idle_overlay = None;
+
+    // A private image-rootfs overlay lives only as long as its container
+    #[cfg(target_os = "linux")]
+    if let Ok(state) = load_state(id) {
+        if let Some(ref rootfs) = state.image_rootfs {
+            let parsed = state
+                .annotations
+                .as_ref()
+                .and_then(annotations::parse_stripped_annotations)
+                .unwrap_or_default();
+            match container_overlay_config(
+                id,
+                state.namespace.as_deref(),
+                parsed.overlay_name.as_deref(),
+                parsed.overlay_isolation.as_deref(),
+                parsed.shared_overlay.as_deref(),
+                Some(rootfs),
+            ) {
+                Ok(config) => overlay::remove_private_overlay(&config),
+                Err(e) => tracing::warn!("do_delete() - cannot resolve private overlay: {:#}", e),
+            }
+        } else if overlay::auto_teardown_enabled() {
+            let effective = state.effective.as_ref();
+            let ns = effective.and_then(|e| e.overlay_ns.clone());
+            let isolation = effective.and_then(|e| e.overlay_isolation.as_deref());
+            if let (Some(ns), Some("namespace")) = (ns, isolation) {
+                let parsed = state
+                    .annotations
+                    .as_ref()
+                    .and_then(annotations::parse_stripped_annotations)
+                    .unwrap_or_default();
+                match container_overlay_config(
+                    id,
+                    state.namespace.as_deref(),
+                    parsed.overlay_name.as_deref(),
+                    parsed.overlay_isolation.as_deref(),
+                    None,
+                    None,
+                ) {
+                    Ok(config) if config.ns_path == ns => idle_overlay = Some(config),
+                    Ok(_) => tracing::warn!(
+                        "do_delete() - overlay config changed since start, not tearing down {}",
+                        ns.display()
+                    ),
+                    Err(e) => tracing::warn!("do_delete() - cannot resolve overlay: {:#}", e),
+                }
+            }
+        }
+        // The workload's cgroup, unless the daemon already removed it;
+        // only removable once empty
+        if let Some(ref cgroup) = state.cgroup_path {
+            if let Err(e) = fs::remove_dir(cgroup).or_else(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => Ok(()),
+                _ => Err(e),
+            }) {
+                tracing::warn!(
+                    "do_delete() - cannot remove cgroup {}: {}",
+                    cgroup.display(),
+                    e
+                );
+            }
+        }
+    }
+
+    delete_state(id)?;
+
+    #[cfg(target_os = "linux")]
+    if let Some(config) = idle_overlay {
+        if state::overlay_users(&config.ns_path) == 0 {
+            spawn_overlay_teardown(config, overlay::teardown_grace());
+        }
+    }
+    info!("do_delete() succeeded - id={}", id);
+    println!("deleted {}", id);
+    Ok(())
+}
+
+/// Tear down the namespace overlay of `config` if it stays unused for
+/// `grace`. Runs detached so `delete` returns right away.
+#[cfg(all(target_os = "linux", not(tarpaulin_include)))]
+fn spawn_overlay_teardown(config: overlay::OverlayConfig, grace: std::time::Duration) {
+    let spawned = spawn_detached(|| {
+        info!(
+            "do_delete() - last container of {} gone, tearing down in {}s unless reused",
+            config.ns_path.display(),
+            grace.as_secs()
+        );
+        if let Err(e) = overlay::teardown_idle_namespace(&config, grace, || {
+            state::overlay_users(&config.ns_path)
+        }) {
+            tracing::warn!("do_delete() - overlay teardown failed: {:#}", e);
+        }
+    });
+    if let Err(e) = spawned {
+        tracing::warn!("do_delete() - cannot fork overlay teardown: {}", e);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn exec_with_pty(
+    program: &str,
+    argv: &[String],
+    cwd: Option<String>,
+    env_vars: Option<Vec<String>>,
+    stdin_path: Option<String>,
+    stdout_path: Option<String>,
+    user_config: Option<OciUser>,
+    rlimits: Vec<(nix::sys::resource::Resource, u64, u64)>,
+    capabilities: Option<state::OciCapabilities>,
+    no_new_privs: bool,
+    container_id: &str,
+    exec_id: &str,
+) -> i32 {
+    use nix::pty::openpty;
+    use std::io::{Read, Write};
+    use std::os::unix::io::AsRawFd;
+
+    #[cfg(target_os = "linux")]
+    let cap_plan = capabilities.as_ref().map(caps::CapabilityPlan::from_spec);
+    #[cfg(not(target_os = "linux"))]
+    let _ = capabilities;
+
+    // Create PTY
+    let pty = match openpty(None, None) {
+        Ok(pty) => pty,
+        Err(e) => {
+            tracing::error!("openpty failed: {}", e);
+            return 1;
+        }
+    };
+
+    let slave_raw_fd = pty.slave.as_raw_fd();
+    let setgroups_policy = setgroups_policy_for(user_config.as_ref());
+
+    let mut cmd = Command::new(program);
+    cmd.args(argv);
+    if let Some(ref cwd) = cwd {
+        cmd.current_dir(cwd);
+    }
+    if let Some(ref envs) = env_vars {
+        for kv in envs {
+            if let Some((k, v)) = kv.split_once('=') {
+                cmd.env(k, v);
+            }
+        }
+    }
+
+    // PTY slave becomes stdin/stdout/stderr via pre_exec
+    cmd.stdin(Stdio::null());
+    cmd.stdout(Stdio::null());
+    cmd.stderr(Stdio::null());
+
+    unsafe {
+        cmd.pre_exec(move || {
+            // New session so we can set controlling terminal
+            if nix::libc::setsid() < 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            // Before dropping privileges, which may be needed to raise hard limits
+            for &(resource, soft, hard) in &rlimits {
+                nix::sys::resource::setrlimit(resource, soft, hard)?;
+            }
+            // Set controlling terminal
+            // TIOCSCTTY: ioctl request type varies by arch (c_int on aarch64, c_ulong on x86_64/macOS)
+            if nix::libc::ioctl(
+                slave_raw_fd,
+                nix::libc::TIOCSCTTY as _,
+                0 as nix::libc::c_int,
+            ) < 0
+            {
+                return Err(std::io::Error::last_os_error());
+            }
+            // Dup slave to stdin/stdout/stderr
+            nix::libc::dup2(slave_raw_fd, 0);
+            nix::libc::dup2(slave_raw_fd, 1);
+            nix::libc::dup2(slave_raw_fd, 2);
+            if slave_raw_fd > 2 {
+                nix::libc::close(slave_raw_fd);
+            }
+            #[cfg(target_os = "linux")]
+            if let Some(ref plan) = cap_plan {
+                plan.narrow_bounding()?;
+            }
+            #[cfg(target_os = "linux")]
+            if no_new_privs {
+                set_no_new_privs()?;
+            }
+
+            // Apply user/group configuration if present
+            if let Some(ref user) = user_config {
+                // Set supplementary groups first (must be done while privileged)
+                if !user.additional_gids.is_empty() {
+                    apply_setgroups(&user.additional_gids, setgroups_policy)?;
+                }
+
+                // Set GID before UID (privilege dropping order matters)
+                if nix::libc::setgid(user.gid) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+
+                // Set UID (this must be last - irreversible privilege drop)
+                if nix::libc::setuid(user.uid) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+
+                // Apply umask if specified
+                if let Some(mask) = user.umask {
+                    nix::libc::umask(mask as nix::libc::mode_t);
+                }
+            }
+
+            #[cfg(target_os = "linux")]
+            if let Some(ref plan) = cap_plan {
+                plan.apply()?;
+            }
+
+            Ok(())
+        });
+    }
+
+    let mut child = match cmd.spawn() {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(
+                "exec spawn failed: {}",
+                spawn_failure_message(Path::new(program), cwd.as_deref(), &e)
+            );
+            return 1;
+        }
+    };
+
+    let exec_pid = child.id() as i32;
+
+    // Update exec state with PID
+    if let Ok(mut state) = load_exec_state(container_id, exec_id) {
+        state.status = STATUS_RUNNING.into();
+        state.pid = Some(exec_pid);
+        let _ = save_exec_state(&state);
+    }
+
+    // Close slave in parent - child has it via dup2
+    drop(pty.slave);
+
+    // Capture master raw fd for resize ioctl before converting to File
+    #[cfg(target_os = "linux")]
+    let master_raw_fd = {
+        use std::os::unix::io::AsRawFd;
+        pty.master.as_raw_fd()
+    };
+
+    // Convert PTY master OwnedFd to File for I/O
+    let master_file: std::fs::File = pty.master.into();
+    let master_clone = master_file.try_clone().unwrap_or_else(|e| {
+        tracing::error!("failed to clone master fd: {}", e);
+        std::process::exit(1);
+    });
+
+    // Start PTY resize watcher thread
+    #[cfg(target_os = "linux")]
+    let resize_stop = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+    #[cfg(target_os = "linux")]
+    spawn_resize_watcher(
+        master_raw_fd,
+        exec_resize_path(container_id, exec_id),
+        resize_stop.clone(),
+    );
+
+    // Start relay threads
+    // stdin FIFO → PTY master (user input to process)
+    if let Some(ref stdin_p) = stdin_path {
+        if !stdin_p.is_empty() {
+            let stdin_path = stdin_p.clone();
+            let mut master_w = master_clone; // master_clone for writing
+            std::thread::spawn(move || {
+                if let Ok(mut stdin_file) = std::fs::File::open(&stdin_path) {
+                    let mut buf = [0u8; 4096];
+                    loop {
+                        match stdin_file.read(&mut buf) {
+                            Ok(0) => break,
+                            Ok(n) => {
+                                if master_w.write_all(&buf[..n]).is_err() {
+                                    break;
+                                }
+                            }
+                            Err(_) => break,
+                        }
+                    }
+                }
+            });
+        }
+    }
+
+    // PTY master → stdout FIFO (process output to user)
+    let accounting = exec_output_accounting();
+    let mut relays = ExecOutputRelays::default();
+    if let Some(ref stdout_p) = stdout_path {
+        if !stdout_p.is_empty() {
+            let stdout_path = stdout_p.clone();
+            let master_r = master_file; // master_file for reading
+            let cap = accounting.and_then(|a| a.cap);
+            let counter = std::sync::Arc::new(OutputCounter::default());
+            let relay_counter = counter.clone();
+            relays.threads.push(std::thread::spawn(move || {
+                if let Ok(stdout_file) = std::fs::OpenOptions::new().write(true).open(&stdout_path)
+                {
+                    relay_counted(master_r, stdout_file, cap, &relay_counter);
+                }
+            }));
+            relays.stdout = Some(counter);
+        }
+    }
+
+    // Wait for child
+    let exit = match child.wait() {
+        Ok(status) => exit_code_from_status(status, true),
+        Err(_) => 1,
+    };
+    #[cfg(target_os = "linux")]
+    resize_stop.store(true, std::sync::atomic::Ordering::Relaxed);
+    if accounting.is_some() {
+        record_exec_output(container_id, exec_id, relays.finish(EXEC_OUTPUT_DRAIN));
+    }
+    exit
+}
+
+#[allow(clippy::too_many_arguments)]
+fn exec_without_pty(
+    program: &str,
+    argv: &[String],
+    cwd: Option<String>,
+    env_vars: Option<Vec<String>>,
+    stdin_path: Option<String>,
+    stdout_path: Option<String>,
+    stderr_path: Option<String>,
+    user_config: Option<OciUser>,
+    rlimits: Vec<(nix::sys::resource::Resource, u64, u64)>,
+    capabilities: Option<state::OciCapabilities>,
+    no_new_privs: bool,
+    container_id: &str,
+    exec_id: &str,
+) -> i32 {
+    #[cfg(target_os = "linux")]
+    let cap_plan = capabilities.as_ref().map(caps::CapabilityPlan::from_spec);
+    #[cfg(not(target_os = "linux"))]
+    let _ = capabilities;
+
+    let mut cmd = Command::new(program);
+    cmd.args(argv);
+    if let Some(ref cwd) = cwd {
+        cmd.current_dir(cwd);
+    }
+    if let Some(ref envs) = env_vars {
+        for kv in envs {
+            if let Some((k, v)) = kv.split_once('=') {
+                cmd.env(k, v);
+            }
+        }
+    }
+
+    // Connect FIFOs directly
+    if let Some(ref p) = stdin_path {
+        if !p.is_empty() {
+            if let Ok(f) = std::fs::File::open(p) {
+                cmd.stdin(Stdio::from(f));
+            } else {
+                cmd.stdin(Stdio::null());
+            }
+        } else {
+            cmd.stdin(Stdio::null());
+        }
+    } else {
+        cmd.stdin(Stdio::null());
+    }
+
+    // With output accounting, the FIFOs are fed through counting relays
+    let accounting = exec_output_accounting();
+    let mut relays = ExecOutputRelays::default();
+
+    if let Some(ref p) = stdout_path {
+        if !p.is_empty() {
+            match open_log_file(p) {
+                Ok(f) => cmd.stdout(relays.stdout(f, accounting)),
+                Err(e) => {
+                    tracing::warn!(
+                        "do_exec() - failed to open stdout FIFO ({}), falling back to inherit: {:#}",
+                        p,
+                        e
+                    );
+                    cmd.stdout(Stdio::inherit())
+                }
+            };
+        } else {
+            cmd.stdout(Stdio::inherit());
+        }
+    } else {
+        cmd.stdout(Stdio::inherit());
+    }
+
+    if let Some(ref p) = stderr_path {
+        if !p.is_empty() {
+            match open_log_file(p) {
+                Ok(f) => cmd.stderr(relays.stderr(f, accounting)),
+                Err(e) => {
+                    tracing::warn!(
+                        "do_exec() - failed to open stderr FIFO ({}), falling back to inherit: {:#}",
+                        p,
+                        e
+                    );
+                    cmd.stderr(Stdio::inherit())
+                }
+            };
+        } else {
+            cmd.stderr(Stdio::inherit());
+        }
+    } else {
+        cmd.stderr(Stdio::inherit());
+    }
+
+    // Always call setsid() so the exec process gets its own process group
+    // (PGID == PID), matching what the shim's kill() expects when sending
+    // signals to -pid.
+    {
+        let setgroups_policy = setgroups_policy_for(user_config.as_ref());
+        let user_cfg_clone = user_config;
+        unsafe {
+            cmd.pre_exec(move || {
+                if nix::libc::setsid() < 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                // Before dropping privileges, which may be needed to raise hard limits
+                for &(resource, soft, hard) in &rlimits {
+                    nix::sys::resource::setrlimit(resource, soft, hard)?;
+                }
+                #[cfg(target_os = "linux")]
+                if let Some(ref plan) = cap_plan {
+                    plan.narrow_bounding()?;
+                }
+                #[cfg(target_os = "linux")]
+                if no_new_privs {
+                    set_no_new_privs()?;
+                }
+
+                // Apply user/group configuration if present
+                if let Some(ref user) = user_cfg_clone {
+                    // Set supplementary groups first (must be done while privileged)
+                    if !user.additional_gids.is_empty() {
+                        apply_setgroups(&user.additional_gids, setgroups_policy)?;
+                    }
+
+                    // Set GID before UID (privilege dropping order matters)
+                    if nix::libc::setgid(user.gid) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+
+                    // Set UID (this must be last - irreversible privilege drop)
+                    if nix::libc::setuid(user.uid) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+
+                    // Apply umask if specified
+                    if let Some(mask) = user.umask {
+                        nix::libc::umask(mask as nix::libc::mode_t);
+                    }
+                }
+
+                #[cfg(target_os = "linux")]
+                if let Some(ref plan) = cap_plan {
+                    plan.apply()?;
+                }
+
+                Ok(())
+            });
+        }
+    }
+
+    let mut child = match cmd.spawn() {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!(
+                "exec spawn failed: {}",
+                spawn_failure_message(Path::new(program), cwd.as_deref(), &e)
+            );
+            return 1;
+        }
+    };
+
+    let exec_pid = child.id() as i32;
+
+    // Update exec state
+    if let Ok(mut state) = load_exec_state(container_id, exec_id) {
+        state.status = STATUS_RUNNING.into();
+        state.pid = Some(exec_pid);
+        let _ = save_exec_state(&state);
+    }
+    // Close our copies of the pipe write ends so the relays see EOF
+    drop(cmd);
+
+    let exit = match child.wait() {
+        Ok(status) => exit_code_from_status(status, false),
+        Err(_) => 1,
+    };
+    if accounting.is_some() {
+        record_exec_output(container_id, exec_id, relays.finish(EXEC_OUTPUT_DRAIN));
+    }
+    exit
+}
+
+fn do_exec(container_id: &str, exec_id: &str) -> Result<()> {
+    info!(
+        "do_exec() called - container_id={}, exec_id={}",
+        container_id, exec_id
+    );
+
+    let exec_state = load_exec_state(container_id, exec_id)?;
+
+    // Load container state to get namespace and annotations for overlay isolation.
+    // We need annotations here to extract overlay-name and overlay-isolation, which
+    // determine which overlay namespace to join. Other annotation-driven overrides (like dns-mode)
+    // modify the overlay filesystem during do_start(), and exec'd processes inherit
+    // those changes automatically by joining the same overlay namespace.
+    let container_state = load_state(container_id)?;
+    let container_namespace = container_state.namespace.clone();
+    #[cfg(target_os = "linux")]
+    let image_rootfs = container_state.image_rootfs.clone();
+    #[cfg(target_os = "linux")]
+    let exec_annotations = container_state
+        .annotations
+        .as_ref()
+        .and_then(annotations::parse_stripped_annotations)
+        .unwrap_or_default();
+    #[cfg(target_os = "linux")]
+    let overlay_name_override = exec_annotations.overlay_name;
+    #[cfg(target_os = "linux")]
+    let isolation_override = exec_annotations.overlay_isolation;
+    #[cfg(target_os = "linux")]
+    let shared_overlay = exec_annotations.shared_overlay;
+
+    let args = exec_state.args.clone();
+    if args.is_empty() {
+        bail!("exec process args must not be empty");
+    }
+
+    let program = args[0].clone();
+    let argv: Vec<String> = args[1..].to_vec();
+    let cwd = Some(effective_cwd(exec_state.cwd.as_deref()));
+    let env_vars = exec_state.env.clone();
+    let terminal = exec_state.terminal;
+    let stdin_path = exec_state.stdin.clone();
+    let stdout_path = exec_state.stdout.clone();
+    let stderr_path = exec_state.stderr.clone();
+    let user_cfg = with_default_gids(exec_state.user.clone(), &default_additional_gids());
+    let rlimits = workload_rlimits(&exec_state.rlimits);
+    let capabilities = exec_state.capabilities.clone();
+    let no_new_privs = exec_state.no_new_privileges;
+
+    let container_id = container_id.to_string();
+    let exec_id = exec_id.to_string();
+    let mut audit_log = audit::open_log();
+
+    use nix::unistd::{fork, ForkResult};
+
+    match unsafe { fork() } {
+        Ok(ForkResult::Parent { .. }) => {
+            // Poll exec state for PID (same pattern as do_start)
+            let mut exec_pid = None;
+            for _ in 0..20 {
+                if let Ok(state) = load_exec_state(&container_id, &exec_id) {
+                    if state.pid.is_some() {
+                        exec_pid = state.pid;
+                        break;
+                    }
+                }
+                std::thread::sleep(std::time::Duration::from_millis(100));
+            }
+            if let Some(pid) = exec_pid {
+                println!("exec started pid={}", pid);
+            } else {
+                println!("exec started pid=0");
+            }
+            Ok(())
+        }
+        Ok(ForkResult::Child) => {
+            // Close inherited stdout/stderr to unblock the shim's cmd.output()
+            // (same reason as in do_start - see comment there)
+            {
+                use std::os::unix::io::AsRawFd;
+                if let Ok(devnull) = std::fs::File::open("/dev/null") {
+                    let fd = devnull.as_raw_fd();
+                    unsafe {
+                        nix::libc::dup2(fd, 1);
+                        nix::libc::dup2(fd, 2);
+                    }
+                }
+            }
+
+            if let Err(e) = nix::unistd::setsid() {
+                let _ = e;
+            }
+
+            // Join overlay namespace (Linux only) - same as do_start
+            #[cfg(target_os = "linux")]
+            {
+                let overlay_config = match container_overlay_config(
+                    &container_id,
+                    container_namespace.as_deref(),
+                    overlay_name_override.as_deref(),
+                    isolation_override.as_deref(),
+                    shared_overlay.as_deref(),
+                    image_rootfs.as_deref(),
+                ) {
+                    Ok(c) => c,
+                    Err(e) => {
+                        tracing::error!("do_exec() - overlay config failed: {:#}", e);
+                        if let Ok(mut state) = load_exec_state(&container_id, &exec_id) {
+                            state.status = STATUS_STOPPED.into();
+                            state.exit_code = Some(1);
+                            let _ = save_exec_state(&state);
                         }
+                        std::process::exit(1);
+                    }
+                };
+                if let Err(e) = overlay::enter_overlay(&overlay_config, &[]) {
+                    tracing::error!("do_exec() - overlay failed: {:#}", e);
+                    let code = match e.downcast_ref::<overlay::FilterFailure>() {
+                        Some(_) => overlay::FILTER_FAILURE_EXIT_CODE,
+                        None => 1,
+                    };
+                    if let Ok(mut state) = load_exec_state(&container_id, &exec_id) {
+                        state.status = STATUS_STOPPED.into();
+                        state.exit_code = Some(code);
+                        let _ = save_exec_state(&state);
+                    }
+                    std::process::exit(code);
+                }
+                if let Some(pid) = container_state.pid {
+                    join_uts_namespace(pid);
+                }
+            }
+
+            let env_vars = derive_user_env(passthrough_env(env_vars), user_cfg.as_ref());
+
+            if let Some(log) = audit_log.as_mut() {
+                let record = audit_record(
+                    &container_id,
+                    Some(&exec_id),
+                    container_namespace.as_deref(),
+                    &program,
+                    args.iter().map(String::as_str).collect(),
+                    user_cfg.as_ref(),
+                    cwd.as_deref(),
+                );
+                audit::write_record(log, &record);
+            }
+
+            let exit_code = if terminal {
+                exec_with_pty(
+                    &program,
+                    &argv,
+                    cwd,
+                    env_vars,
+                    stdin_path,
+                    stdout_path,
+                    user_cfg,
+                    rlimits,
+                    capabilities,
+                    no_new_privs,
+                    &container_id,
+                    &exec_id,
+                )
+            } else {
+                exec_without_pty(
+                    &program,
+                    &argv,
+                    cwd,
+                    env_vars,
+                    stdin_path,
+                    stdout_path,
+                    stderr_path,
+                    user_cfg,
+                    rlimits,
+                    capabilities,
+                    no_new_privs,
+                    &container_id,
+                    &exec_id,
+                )
+            };
+
+            // Update exec state to stopped
+            if let Ok(mut state) = load_exec_state(&container_id, &exec_id) {
+                state.status = STATUS_STOPPED.into();
+                state.exit_code = Some(exit_code);
+                let _ = save_exec_state(&state);
+            }
+
+            std::process::exit(0);
+        }
+        Err(e) => bail!("Fork failed: {}", e),
+    }
+}
+
+/// Print a readiness report; fails if any hard requirement is not met.
+#[cfg(target_os = "linux")]
+fn do_preflight(json: bool) -> Result<()> {
+    let results = preflight::run_checks();
+    if json {
+        println!("{}", serde_json::to_string_pretty(&results)?);
+    } else {
+        print!("{}", preflight::render_report(&results));
+    }
+    let failed = preflight::hard_failures(&results);
+    if failed > 0 {
+        bail!("preflight failed: {} hard requirement(s) not met", failed);
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn do_preflight(_json: bool) -> Result<()> {
+    bail!("preflight is only supported on Linux")
+}
+
+/// Print reaper's processes on this node with their memory, flagging the
+/// ones that look leaked.
+#[cfg(target_os = "linux")]
+#[cfg(not(tarpaulin_include))]
+fn do_diag(json: bool) -> Result<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    let mut records = diag::Records::default();
+    for s in state::list_states().states {
+        if let Some(pid) = s.pid {
+            records.workloads.insert(pid, s.id.clone());
+        }
+        records.containers.insert(s.id, s.status == STATUS_STOPPED);
+    }
+    for e in state::list_exec_states() {
+        if let (Some(pid), false) = (e.pid, e.status == STATUS_STOPPED) {
+            records
+                .workloads
+                .insert(pid, format!("{}/{}", e.container_id, e.exec_id));
+        }
+    }
+    for (ns_path, pid, inode) in overlay::recorded_anchors() {
+        records.anchors.insert(pid, (ns_path, inode));
+    }
+
+    let procs = diag::scan_proc(Path::new("/proc"));
+    let host_mnt_ns = std::fs::metadata("/proc/self/ns/mnt").ok().map(|m| m.ino());
+    let found = diag::classify(&procs, &records, host_mnt_ns, std::process::id() as i32);
+    let dead_anchors = diag::dead_anchor_records(&procs, &records);
+    if json {
+        let report = serde_json::json!({
+            "summary": diag::summarize(&found),
+            "processes": found,
+            "dead_anchor_records": dead_anchors,
+        });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print!("{}", diag::render_report(&found, &dead_anchors));
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn do_diag(_json: bool) -> Result<()> {
+    bail!("diag is only supported on Linux")
+}
+
+/// Process as `start` would run it: args, effective cwd, user, and env with
+/// `REAPER_PASSTHROUGH_ENV` applied. HOME/PATH derivation needs the overlay's
+/// /etc/passwd and is not reflected.
+fn inspect_process(cfg: &OciConfig) -> serde_json::Value {
+    let proc = cfg.process.as_ref();
+    serde_json::json!({
+        "args": proc.and_then(|p| p.args.clone()),
+        "cwd": effective_cwd(proc.and_then(|p| p.cwd.as_deref())),
+        "user": proc.and_then(|p| p.user.clone()),
+        "env": passthrough_env(proc.and_then(|p| p.env.clone())),
+    })
+}
+
+/// Volume mounts split into those `start` binds and those it skips.
+#[cfg(target_os = "linux")]
+fn inspect_mounts(cfg: &OciConfig) -> serde_json::Value {
+    let applied = overlay::filter_volume_mounts(&cfg.mounts, overlay::read_etc_hosts_mode());
+    let skipped: Vec<&OciMount> = cfg
+        .mounts
+        .iter()
+        .filter(|m| !applied.iter().any(|a| std::ptr::eq(*a, *m)))
+        .collect();
+    serde_json::json!({ "applied": applied, "skipped": skipped })
+}
+
+/// Overlay config for an existing container, from its recorded namespace,
+/// annotations and image rootfs.
+#[cfg(target_os = "linux")]
+fn state_overlay_config(state: &ContainerState) -> Result<overlay::OverlayConfig> {
+    let annots = state
+        .annotations
+        .as_ref()
+        .and_then(annotations::parse_stripped_annotations);
+    container_overlay_config(
+        &state.id,
+        state.namespace.as_deref(),
+        annots.as_ref().and_then(|a| a.overlay_name.as_deref()),
+        annots.as_ref().and_then(|a| a.overlay_isolation.as_deref()),
+        annots.as_ref().and_then(|a| a.shared_overlay.as_deref()),
+        state.image_rootfs.as_deref(),
+    )
+}
+
+/// Overlay paths for the container's namespace, plus the PID of the helper
+/// daemon holding that namespace, if any.
+#[cfg(target_os = "linux")]
+fn inspect_overlay(state: &ContainerState) -> (serde_json::Value, Option<i32>) {
+    match state_overlay_config(state) {
+        Ok(config) => {
+            let (namespace_live, helper_pid) = overlay::namespace_status(&config);
+            let doc = serde_json::json!({
+                "base_dir": config.base_dir,
+                "ns_path": config.ns_path,
+                "lock_path": config.lock_path,
+                "merged_dir": config.merged_dir,
+                "image_rootfs": config.image_rootfs,
+                "namespace_live": namespace_live,
+            });
+            (doc, helper_pid)
+        }
+        Err(e) => (serde_json::json!({ "error": format!("{:#}", e) }), None),
+    }
+}
+
+/// The isolation settings applied at start as `reaper.runtime/effective.*`
+/// annotations (overlay isolation, filtering, DNS mode, PID namespace), for
+/// tooling that reads annotations. Empty before the workload starts.
+fn effective_annotations(state: &ContainerState) -> std::collections::BTreeMap<String, String> {
+    let Some(effective) = state.effective.as_ref() else {
+        return std::collections::BTreeMap::new();
+    };
+    [
+        (
+            "overlay-isolation",
+            effective
+                .overlay_isolation
+                .clone()
+                .unwrap_or_else(|| "none".into()),
+        ),
+        ("filter-enabled", effective.filter_enabled.to_string()),
+        ("dns-mode", effective.dns_mode.clone()),
+        ("pid-namespace", effective.pid_namespace.to_string()),
+    ]
+    .into_iter()
+    .map(|(key, value)| (format!("reaper.runtime/effective.{}", key), value))
+    .collect()
+}
+
+/// Read-only view of a container for debugging. Sections that can't be
+/// resolved (e.g. a missing bundle) carry an `error` instead of failing.
+fn inspect_document(id: &str) -> Result<serde_json::Value> {
+    let state = load_state(id)?;
+    let (process, mounts) = match read_oci_config(&state.bundle) {
+        Ok(cfg) => {
+            #[cfg(target_os = "linux")]
+            let mounts = inspect_mounts(&cfg);
+            #[cfg(not(target_os = "linux"))]
+            let mounts = serde_json::Value::Null;
+            (inspect_process(&cfg), mounts)
+        }
+        Err(e) => {
+            let err = serde_json::json!({ "error": format!("{:#}", e) });
+            (err.clone(), err)
+        }
+    };
+    #[cfg(target_os = "linux")]
+    let (overlay, daemon_pid) = inspect_overlay(&state);
+    #[cfg(not(target_os = "linux"))]
+    let (overlay, daemon_pid) = (serde_json::Value::Null, None::<i32>);
+    Ok(serde_json::json!({
+        "annotations": effective_annotations(&state),
+        "state": state,
+        "process": process,
+        "mounts": mounts,
+        "overlay": overlay,
+        "liveness": {
+            "pid": state.pid,
+            "pid_alive": state.pid.is_some_and(state::pid_is_alive),
+            "daemon_pid": daemon_pid,
+            "daemon_alive": daemon_pid.is_some_and(state::pid_is_alive),
+        },
+    }))
+}
+
+fn do_inspect(id: &str) -> Result<()> {
+    info!("do_inspect() called - id={}", id);
+    println!("{}", serde_json::to_string_pretty(&inspect_document(id)?)?);
+    Ok(())
+}
+
+/// Volume mounts recorded when the container started, checked against the
+/// mount table of its overlay namespace.
+#[cfg(target_os = "linux")]
+#[cfg(not(tarpaulin_include))]
+fn do_mounts(id: &str) -> Result<()> {
+    info!("do_mounts() called - id={}", id);
+    let state = load_state(id)?;
+    let recorded = state
+        .effective
+        .as_ref()
+        .map(|e| e.volume_mounts.as_slice())
+        .unwrap_or_default();
+    if recorded.is_empty() {
+        println!(
+            "no volume mounts recorded for {} (status {}; mounts are recorded at start)",
+            id, state.status
+        );
+        return Ok(());
+    }
+    let mountinfo = match state_overlay_config(&state)
+        .and_then(|config| overlay::read_namespace_mountinfo(&config))
+    {
+        Ok(mountinfo) => mountinfo,
+        Err(e) => {
+            tracing::warn!("do_mounts() - cannot read overlay mounts: {:#}", e);
+            None
+        }
+    };
+    print!(
+        "{}",
+        overlay::format_volume_mounts(recorded, mountinfo.as_deref())
+    );
+    Ok(())
+}
 
-                        std::thread::sleep(std::time::Duration::from_millis(500));
+#[cfg(not(target_os = "linux"))]
+fn do_mounts(_id: &str) -> Result<()> {
+    bail!("mounts is only supported on Linux")
+}
 
-                        // Hold the stdout FIFO write end open so containerd doesn't
-                        // see EOF when the relay thread exits. This prevents a race
-                        // where containerd tears down streams via stdout-EOF while
-                        // also receiving the TaskExit event. O_NONBLOCK because by
-                        // now containerd should have the read end open (via attach).
-                        let _stdout_holder = {
-                            use std::os::unix::fs::OpenOptionsExt;
-                            if let Some(ref state) = io_state {
-                                state.stdout.as_ref().and_then(|p| {
-                                    std::fs::OpenOptions::new()
-                                        .write(true)
-                                        .custom_flags(nix::libc::O_NONBLOCK)
-                                        .open(p)
-                                        .ok()
-                                })
-                            } else {
-                                None
-                            }
-                        };
+fn do_gc_execs(max_age_secs: u64, dry_run: bool) -> Result<()> {
+    let pruned = state::gc_exec_states(std::time::Duration::from_secs(max_age_secs), dry_run)?;
+    for path in &pruned {
+        if dry_run {
+            println!("would remove {}", path.display());
+        } else {
+            println!("removed {}", path.display());
+        }
+    }
+    info!(
+        "do_gc_execs() - {} orphaned exec state(s){}",
+        pruned.len(),
+        if dry_run {
+            " found (dry run)"
+        } else {
+            " removed"
+        }
+    );
+    Ok(())
+}
 
-                        match child.wait() {
-                            Ok(exit_status) => {
-                                #[cfg(target_os = "linux")]
-                                resize_stop.store(true, std::sync::atomic::Ordering::Relaxed);
-                                let exit_code = exit_code_from_status(exit_status);
-                                if let Ok(mut state) = load_state(&container_id) {
-                                    state.status = "stopped".into();
-                                    state.exit_code = Some(exit_code);
-                                    let _ = save_state(&state);
-                                }
-                            }
-                            Err(_e) => {
-                                #[cfg(target_os = "linux")]
-                                resize_stop.store(true, std::sync::atomic::Ordering::Relaxed);
-                                if let Ok(mut state) = load_state(&container_id) {
-                                    state.status = "stopped".into();
-                                    state.exit_code = Some(1);
-                                    let _ = save_state(&state);
-                                }
-                            }
-                        }
+#[cfg(target_os = "linux")]
+#[cfg(not(tarpaulin_include))]
+fn do_overlay_mounts(namespace: Option<&str>, overlay_name: Option<&str>, all: bool) -> Result<()> {
+    let config = overlay::read_config(namespace, overlay_name, None)?;
+    match overlay::read_namespace_mountinfo(&config)? {
+        Some(mountinfo) => print!("{}", overlay::format_mountinfo(&mountinfo, all)),
+        None => println!(
+            "no overlay namespace at {} (no workload has started there yet)",
+            config.ns_path.display()
+        ),
+    }
+    Ok(())
+}
 
-                        // Keep the daemon alive briefly so the shim can detect the
-                        // stopped state and publish the TaskExit event before we
-                        // drop _stdout_holder. This ensures containerd tears down
-                        // streams via the orderly TaskExit path, not a racy
-                        // stdout-EOF path.
-                        std::thread::sleep(std::time::Duration::from_secs(2));
-                    }
-                    Err(e) => {
-                        tracing::error!(
-                            "do_start() - failed to spawn workload (PTY mode): {:#}",
-                            e
-                        );
-                        if let Ok(mut state) = load_state(&container_id) {
-                            state.status = "stopped".into();
-                            state.exit_code = Some(1);
-                            let _ = save_state(&state);
-                        }
-                    }
-                }
+#[cfg(not(target_os = "linux"))]
+fn do_overlay_mounts(
+    _namespace: Option<&str>,
+    _overlay_name: Option<&str>,
+    _all: bool,
+) -> Result<()> {
+    bail!("overlay-mounts is only supported on Linux")
+}
+
+fn main() -> Result<()> {
+    // Load config file before anything else (env vars override file values)
+    config::load_config();
+
+    let cli = Cli::parse();
+
+    // Setup tracing similar to shim: log to REAPER_RUNTIME_LOG, else --log.
+    // If neither is set, use null writer to prevent stdout pollution
+    let log_file = log_destination(std::env::var("REAPER_RUNTIME_LOG").ok(), cli.log.as_deref())
+        .and_then(|log_path| create_log_file(&log_path).ok().map(|file| (log_path, file)));
+    let fmt_layer = match log_file {
+        // Log to the file, as JSON lines with --log-format json
+        Some((_, ref file)) => {
+            let layer = tracing_subscriber::fmt::layer()
+                .with_ansi(false) // No color codes in log files
+                .with_writer(std::sync::Mutex::new(
+                    file.try_clone().expect("cloning log file handle"),
+                ));
+            let filter =
+                EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+            if cli.log_format.as_deref() == Some("json") {
+                layer.json().with_filter(filter).boxed()
             } else {
-                // Non-terminal mode: connect FIFOs directly to the process
-                let mut cmd = Command::new(&program_path);
-                cmd.args(&argv);
-                if let Some(cwd) = cwd.as_deref() {
-                    cmd.current_dir(cwd);
-                }
-                if let Some(envs) = env_vars.as_ref() {
-                    for kv in envs {
-                        if let Some((k, v)) = kv.split_once('=') {
-                            cmd.env(k, v);
-                        }
-                    }
-                }
+                layer.with_filter(filter).boxed()
+            }
+        }
+        // Not set (or unopenable) - use null writer to discard all logs safely
+        None => tracing_subscriber::fmt::layer()
+            .with_writer(std::sync::Mutex::new(std::io::sink()))
+            .with_ansi(false)
+            .with_filter(LevelFilter::INFO)
+            .boxed(),
+    };
+    let registry = tracing_subscriber::registry().with(fmt_layer);
+    #[cfg(feature = "otel")]
+    let registry = registry.with(telemetry::layer());
+    registry.init();
+
+    if let Some((ref log_path, _)) = log_file {
+        info!("===== Reaper Runtime Starting =====");
+        info!("Log file: {}", log_path.display());
+    }
 
-                // Configure stdin: use FIFO if available, otherwise null.
-                // Open with O_RDWR so the FIFO always has a writer — prevents EOF
-                // when the real writer (containerd/kubectl attach) hasn't connected yet.
-                // Without O_RDWR, the shell reads EOF immediately and exits.
-                if let Some(ref state) = io_state {
-                    if let Some(ref stdin_path) = state.stdin {
-                        if !stdin_path.is_empty() {
-                            use std::fs::OpenOptions;
-                            use std::os::unix::fs::OpenOptionsExt;
-                            match OpenOptions::new()
-                                .read(true)
-                                .write(true)
-                                .custom_flags(nix::libc::O_NONBLOCK)
-                                .open(stdin_path)
-                            {
-                                Ok(file) => {
-                                    // Clear O_NONBLOCK after open so reads block normally
-                                    use std::os::unix::io::AsRawFd;
-                                    unsafe {
-                                        let flags =
-                                            nix::libc::fcntl(file.as_raw_fd(), nix::libc::F_GETFL);
-                                        nix::libc::fcntl(
-                                            file.as_raw_fd(),
-                                            nix::libc::F_SETFL,
-                                            flags & !nix::libc::O_NONBLOCK,
-                                        );
-                                    }
-                                    cmd.stdin(Stdio::from(file));
-                                    info!("do_start() - connected stdin from FIFO: {}", stdin_path);
-                                }
-                                Err(e) => {
-                                    info!(
-                                    "do_start() - failed to open stdin FIFO ({}), using null: {}",
-                                    stdin_path, e
-                                );
-                                    cmd.stdin(Stdio::null());
-                                }
-                            }
-                        } else {
-                            cmd.stdin(Stdio::null());
-                        }
-                    } else {
-                        cmd.stdin(Stdio::null());
-                    }
-                } else {
-                    cmd.stdin(Stdio::null());
-                }
+    info!(
+        "CLI parsed: bundle={:?}, root={:?}, command={:?}",
+        cli.bundle, cli.root, cli.command
+    );
 
-                // Configure stdout: use FIFO if available, otherwise inherit
-                if let Some(ref state) = io_state {
-                    if let Some(ref stdout_path) = state.stdout {
-                        if !stdout_path.is_empty() {
-                            match open_log_file(stdout_path) {
-                                Ok(file) => {
-                                    cmd.stdout(Stdio::from(file));
-                                    info!(
-                                        "do_start() - redirected stdout to FIFO: {}",
-                                        stdout_path
-                                    );
-                                }
-                                Err(e) => {
-                                    info!("do_start() - failed to open stdout FIFO ({}), falling back to inherit: {}", stdout_path, e);
-                                    cmd.stdout(Stdio::inherit());
-                                }
-                            }
-                        } else {
-                            cmd.stdout(Stdio::inherit());
-                        }
-                    } else {
-                        cmd.stdout(Stdio::inherit());
-                    }
-                } else {
-                    cmd.stdout(Stdio::inherit());
-                }
+    // Default bundle to current directory if not specified
+    let bundle = cli.bundle.as_deref().unwrap_or_else(|| Path::new("."));
 
-                // Configure stderr: use FIFO if available, otherwise inherit
-                if let Some(ref state) = io_state {
-                    if let Some(ref stderr_path) = state.stderr {
-                        if !stderr_path.is_empty() {
-                            match open_log_file(stderr_path) {
-                                Ok(file) => {
-                                    cmd.stderr(Stdio::from(file));
-                                    info!(
-                                        "do_start() - redirected stderr to FIFO: {}",
-                                        stderr_path
-                                    );
-                                }
-                                Err(e) => {
-                                    info!("do_start() - failed to open stderr FIFO ({}), falling back to inherit: {}", stderr_path, e);
-                                    cmd.stderr(Stdio::inherit());
-                                }
-                            }
-                        } else {
-                            cmd.stderr(Stdio::inherit());
-                        }
-                    } else {
-                        cmd.stderr(Stdio::inherit());
-                    }
-                } else {
-                    cmd.stderr(Stdio::inherit());
-                }
+    let result = run_command(&cli.command, bundle, cli.pid_file.as_deref());
 
-                // Always call setsid() so the workload gets its own process group
-                // (PGID == workload PID). This is critical for do_kill() which sends
-                // signals to -pid (the process group). Without setsid(), the workload
-                // inherits the daemon's PGID, so kill(-workload_pid) targets a
-                // non-existent process group and the signal never reaches the process.
-                {
-                    let user_cfg_clone = user_config.clone();
-                    unsafe {
-                        cmd.pre_exec(move || {
-                            if nix::libc::setsid() < 0 {
-                                return Err(std::io::Error::last_os_error());
-                            }
+    if let Err(ref e) = result {
+        tracing::error!("Command failed: {:?}", e);
+    } else {
+        info!("Command completed successfully");
+    }
+
+    #[cfg(feature = "otel")]
+    telemetry::shutdown();
+
+    result
+}
+
+/// Log file of this invocation: `REAPER_RUNTIME_LOG` (from the environment
+/// or config file) overrides the `--log` that containerd passes.
+fn log_destination(env_log: Option<String>, flag_log: Option<&Path>) -> Option<PathBuf> {
+    env_log
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| flag_log.map(Path::to_path_buf))
+}
+
+/// Run `f` inside `span` and record whether it succeeded as the span's
+/// `outcome` field (which the span must declare).
+fn traced<T, E>(span: tracing::Span, f: impl FnOnce() -> Result<T, E>) -> Result<T, E> {
+    let result = span.in_scope(f);
+    span.record("outcome", if result.is_ok() { "ok" } else { "error" });
+    result
+}
+
+/// Span for the lifecycle commands; the others run outside any span.
+fn command_span(command: &Commands) -> tracing::Span {
+    use tracing::field::Empty;
+    match command {
+        Commands::Create { id, namespace, .. } => tracing::info_span!(
+            "create",
+            container.id = %id,
+            k8s.namespace = namespace.as_deref(),
+            outcome = Empty
+        ),
+        Commands::Start { id } => tracing::info_span!("start", container.id = %id, outcome = Empty),
+        Commands::Kill { id, signal, .. } => tracing::info_span!(
+            "kill",
+            container.id = %id,
+            signal = signal.unwrap_or(15),
+            outcome = Empty
+        ),
+        Commands::Delete { id, .. } => {
+            tracing::info_span!("delete", container.id = %id, outcome = Empty)
+        }
+        _ => tracing::Span::none(),
+    }
+}
+
+/// Reject unsafe container/exec ids before any subcommand turns them into
+/// paths under the runtime root.
+fn validate_command_ids(command: &Commands) -> Result<()> {
+    match command {
+        Commands::Create { id, .. }
+        | Commands::Start { id }
+        | Commands::State { id }
+        | Commands::Kill { id, .. }
+        | Commands::Delete { id, .. }
+        | Commands::Inspect { id }
+        | Commands::Mounts { id } => {
+            state::validate_id(id).with_context(|| format!("invalid container id {:?}", id))
+        }
+        Commands::Exec { id, exec_id } => {
+            state::validate_id(id).with_context(|| format!("invalid container id {:?}", id))?;
+            state::validate_id(exec_id).with_context(|| format!("invalid exec id {:?}", exec_id))
+        }
+        Commands::OverlayMounts { .. }
+        | Commands::List { .. }
+        | Commands::Preflight { .. }
+        | Commands::Diag { .. }
+        | Commands::GcExecs { .. } => Ok(()),
+    }
+}
+
+fn run_command(command: &Commands, bundle: &Path, pid_file: Option<&Path>) -> Result<()> {
+    traced(command_span(command), || {
+        validate_command_ids(command)?;
+        dispatch_command(command, bundle, pid_file)
+    })
+}
+
+fn dispatch_command(command: &Commands, bundle: &Path, pid_file: Option<&Path>) -> Result<()> {
+    match command {
+        Commands::Create {
+            id,
+            terminal,
+            stdin,
+            stdout,
+            stderr,
+            namespace,
+            annotations,
+            sandbox,
+        } => do_create(
+            id,
+            bundle,
+            *terminal,
+            stdin.clone(),
+            stdout.clone(),
+            stderr.clone(),
+            namespace.clone(),
+            annotations,
+            *sandbox,
+        ),
+        Commands::Start { id } => do_start(id, bundle, pid_file),
+        Commands::State { id } => do_state(id),
+        Commands::List { format } => do_list(format),
+        Commands::Kill {
+            id,
+            signal,
+            timeout,
+        } => do_kill(id, *signal, *timeout),
+        Commands::Delete { id, .. } => do_delete(id),
+        Commands::Exec { id, exec_id } => do_exec(id, exec_id),
+        Commands::OverlayMounts {
+            namespace,
+            overlay_name,
+            all,
+        } => do_overlay_mounts(namespace.as_deref(), overlay_name.as_deref(), *all),
+        Commands::Preflight { json } => do_preflight(*json),
+        Commands::Diag { json } => do_diag(*json),
+        Commands::Inspect { id } => do_inspect(id),
+        Commands::Mounts { id } => do_mounts(id),
+        Commands::GcExecs {
+            max_age_secs,
+            dry_run,
+        } => do_gc_execs(*max_age_secs, *dry_run),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+    use tempfile::TempDir;
 
-                            // Apply user/group configuration if present
-                            if let Some(ref user) = user_cfg_clone {
-                                // Set supplementary groups first (must be done while privileged)
-                                if !user.additional_gids.is_empty() {
-                                    safe_setgroups(&user.additional_gids)?;
-                                }
+    fn setup_test_root() -> TempDir {
+        tempfile::tempdir().expect("Failed to create temp dir")
+    }
 
-                                // Set GID before UID (privilege dropping order matters)
-                                if nix::libc::setgid(user.gid) != 0 {
-                                    return Err(std::io::Error::last_os_error());
-                                }
+    fn with_test_root<F>(f: F)
+    where
+        F: FnOnce(String),
+    {
+        let temp = setup_test_root();
+        let root = temp.path().to_string_lossy().to_string();
+        std::env::set_var("REAPER_RUNTIME_ROOT", &root);
+        f(root);
+        std::env::remove_var("REAPER_RUNTIME_ROOT");
+    }
 
-                                // Set UID (this must be last - irreversible privilege drop)
-                                if nix::libc::setuid(user.uid) != 0 {
-                                    return Err(std::io::Error::last_os_error());
-                                }
+    #[test]
+    fn test_parse_config_without_user() {
+        let bundle_dir = TempDir::new().expect("Failed to create temp dir");
+        let config = serde_json::json!({
+            "process": {
+                "args": ["/bin/echo", "hello"],
+                "cwd": "/tmp"
+            }
+        });
+        fs::write(
+            bundle_dir.path().join("config.json"),
+            serde_json::to_string(&config).unwrap(),
+        )
+        .unwrap();
 
-                                // Apply umask if specified
-                                if let Some(mask) = user.umask {
-                                    nix::libc::umask(mask as nix::libc::mode_t);
-                                }
-                            }
+        let parsed = read_oci_config(bundle_dir.path()).unwrap();
+        assert!(parsed.process.is_some());
+        let process = parsed.process.unwrap();
+        assert!(process.user.is_none());
+        assert_eq!(process.args.unwrap(), vec!["/bin/echo", "hello"]);
+    }
 
-                            Ok(())
-                        });
-                    }
+    #[test]
+    fn test_parse_config_with_basic_user() {
+        let bundle_dir = TempDir::new().expect("Failed to create temp dir");
+        let config = serde_json::json!({
+            "process": {
+                "args": ["/bin/echo", "hello"],
+                "cwd": "/tmp",
+                "user": {
+                    "uid": 1000,
+                    "gid": 1000
                 }
+            }
+        });
+        fs::write(
+            bundle_dir.path().join("config.json"),
+            serde_json::to_string(&config).unwrap(),
+        )
+        .unwrap();
 
-                match cmd.spawn() {
-                    Ok(mut child) => {
-                        let workload_pid = child.id() as i32;
+        let parsed = read_oci_config(bundle_dir.path()).unwrap();
+        let process = parsed.process.unwrap();
+        let user = process.user.unwrap();
+        assert_eq!(user.uid, 1000);
+        assert_eq!(user.gid, 1000);
+        assert_eq!(user.additional_gids, Vec::<u32>::new());
+        assert_eq!(user.umask, None);
+    }
 
-                        // Update state to running with the actual workload PID
-                        if let Ok(mut state) = load_state(&container_id) {
-                            state.status = "running".into();
-                            state.pid = Some(workload_pid);
-                            let _ = save_state(&state);
-                            let _ = save_pid(&container_id, workload_pid);
-                        }
+    #[test]
+    fn test_parse_config_with_full_user() {
+        let bundle_dir = TempDir::new().expect("Failed to create temp dir");
+        let config = serde_json::json!({
+            "process": {
+                "args": ["/bin/sh", "-c", "id"],
+                "cwd": "/tmp",
+                "user": {
+                    "uid": 1001,
+                    "gid": 1001,
+                    "additionalGids": [10, 20, 30],
+                    "umask": 22
+                }
+            }
+        });
+        fs::write(
+            bundle_dir.path().join("config.json"),
+            serde_json::to_string(&config).unwrap(),
+        )
+        .unwrap();
 
-                        // IMPORTANT: Give containerd/kubelet time to observe the "running"
-                        // state before the container potentially exits.
-                        std::thread::sleep(std::time::Duration::from_millis(500));
+        let parsed = read_oci_config(bundle_dir.path()).unwrap();
+        let process = parsed.process.unwrap();
+        let user = process.user.unwrap();
+        assert_eq!(user.uid, 1001);
+        assert_eq!(user.gid, 1001);
+        assert_eq!(user.additional_gids, vec![10, 20, 30]);
+        assert_eq!(user.umask, Some(22));
+    }
 
-                        // Wait for the workload process to exit
-                        // We are the parent, so this will work correctly!
-                        match child.wait() {
-                            Ok(exit_status) => {
-                                let exit_code = exit_code_from_status(exit_status);
-                                if let Ok(mut state) = load_state(&container_id) {
-                                    state.status = "stopped".into();
-                                    state.exit_code = Some(exit_code);
-                                    let _ = save_state(&state);
-                                }
-                            }
-                            Err(_e) => {
-                                if let Ok(mut state) = load_state(&container_id) {
-                                    state.status = "stopped".into();
-                                    state.exit_code = Some(1);
-                                    let _ = save_state(&state);
-                                }
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        tracing::error!("do_start() - failed to spawn workload: {:#}", e);
-                        if let Ok(mut state) = load_state(&container_id) {
-                            state.status = "stopped".into();
-                            state.exit_code = Some(1);
-                            let _ = save_state(&state);
-                        }
-                    }
+    #[test]
+    fn test_parse_config_with_root_user() {
+        let bundle_dir = TempDir::new().expect("Failed to create temp dir");
+        let config = serde_json::json!({
+            "process": {
+                "args": ["/bin/cat", "/etc/shadow"],
+                "cwd": "/",
+                "user": {
+                    "uid": 0,
+                    "gid": 0
                 }
             }
+        });
+        fs::write(
+            bundle_dir.path().join("config.json"),
+            serde_json::to_string(&config).unwrap(),
+        )
+        .unwrap();
 
-            // Daemon exits after workload completes
-            std::process::exit(0);
-        }
-        Err(e) => {
-            bail!("Failed to fork monitoring daemon: {}", e);
-        }
+        let parsed = read_oci_config(bundle_dir.path()).unwrap();
+        let process = parsed.process.unwrap();
+        let user = process.user.unwrap();
+        assert_eq!(user.uid, 0);
+        assert_eq!(user.gid, 0);
     }
-}
 
-fn do_state(id: &str) -> Result<()> {
-    info!("do_state() called - id={}", id);
-    let state = load_state(id)?;
-    info!(
-        "do_state() succeeded - id={}, status={}, pid={:?}",
-        id, state.status, state.pid
-    );
-    println!("{}", serde_json::to_string_pretty(&state)?);
-    Ok(())
-}
+    #[test]
+    fn test_parse_config_missing_user_fields() {
+        // Test that additional_gids defaults to empty vec if not provided
+        let bundle_dir = TempDir::new().expect("Failed to create temp dir");
+        let config = serde_json::json!({
+            "process": {
+                "args": ["/bin/true"],
+                "user": {
+                    "uid": 500,
+                    "gid": 500
+                    // No additionalGids or umask
+                }
+            }
+        });
+        fs::write(
+            bundle_dir.path().join("config.json"),
+            serde_json::to_string(&config).unwrap(),
+        )
+        .unwrap();
 
-fn do_kill(id: &str, signal: Option<i32>) -> Result<()> {
-    let signal = signal.unwrap_or(15); // Default to SIGTERM
-    info!("do_kill() called - id={}, signal={}", id, signal);
-    let pid = load_pid(id)?;
-    if pid <= 1 {
-        bail!("refusing to send signal to PID {} (must be > 1)", pid);
+        let parsed = read_oci_config(bundle_dir.path()).unwrap();
+        let user = parsed.process.unwrap().user.unwrap();
+        assert!(user.additional_gids.is_empty());
+        assert!(user.umask.is_none());
     }
-    info!(
-        "do_kill() - sending signal {} to process group (pgid={})",
-        signal, pid
-    );
-    // Kill the entire process group (-pid) so children of the workload (e.g. backgrounded
-    // processes) are also signalled. The workload calls setsid() in pre_exec, so its PGID
-    // equals its PID.
-    let sig = nix::sys::signal::Signal::try_from(signal).context("invalid signal")?;
-    match nix::sys::signal::kill(nix::unistd::Pid::from_raw(-pid), sig) {
-        Ok(()) => {
-            info!(
-                "do_kill() succeeded - id={}, signal={}, pid={}",
-                id, signal, pid
-            );
-        }
-        Err(nix::errno::Errno::ESRCH) => {
-            // Process doesn't exist - this is expected if the container already exited
-            // Return success since the goal (container not running) is achieved
-            info!(
-                "do_kill() - process {} already exited (ESRCH), treating as success",
-                pid
-            );
-        }
-        Err(e) => {
-            bail!("failed to send signal: {}", e);
-        }
+
+    // --- read_oci_config edge cases ---
+
+    #[test]
+    fn test_read_oci_config_missing_file() {
+        let bundle_dir = TempDir::new().unwrap();
+        // No config.json created
+        let result = read_oci_config(bundle_dir.path());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_read_oci_config_invalid_json() {
+        let bundle_dir = TempDir::new().unwrap();
+        fs::write(bundle_dir.path().join("config.json"), "not valid json").unwrap();
+        let result = read_oci_config(bundle_dir.path());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_read_oci_config_no_process() {
+        let bundle_dir = TempDir::new().unwrap();
+        let config = serde_json::json!({});
+        fs::write(
+            bundle_dir.path().join("config.json"),
+            serde_json::to_string(&config).unwrap(),
+        )
+        .unwrap();
+        let parsed = read_oci_config(bundle_dir.path()).unwrap();
+        assert!(parsed.process.is_none());
+    }
+
+    #[test]
+    fn test_read_oci_config_empty_args() {
+        let bundle_dir = TempDir::new().unwrap();
+        let config = serde_json::json!({
+            "process": {
+                "args": [],
+                "cwd": "/"
+            }
+        });
+        fs::write(
+            bundle_dir.path().join("config.json"),
+            serde_json::to_string(&config).unwrap(),
+        )
+        .unwrap();
+        let parsed = read_oci_config(bundle_dir.path()).unwrap();
+        let process = parsed.process.unwrap();
+        assert!(process.args.unwrap().is_empty());
     }
-    Ok(())
-}
 
-fn do_delete(id: &str) -> Result<()> {
-    info!("do_delete() called - id={}", id);
-    delete_state(id)?;
-    info!("do_delete() succeeded - id={}", id);
-    println!("deleted {}", id);
-    Ok(())
-}
+    #[test]
+    fn test_read_oci_config_with_env() {
+        let bundle_dir = TempDir::new().unwrap();
+        let config = serde_json::json!({
+            "process": {
+                "args": ["/bin/sh"],
+                "env": ["PATH=/usr/bin", "HOME=/root"]
+            }
+        });
+        fs::write(
+            bundle_dir.path().join("config.json"),
+            serde_json::to_string(&config).unwrap(),
+        )
+        .unwrap();
+        let parsed = read_oci_config(bundle_dir.path()).unwrap();
+        let env = parsed.process.unwrap().env.unwrap();
+        assert_eq!(env.len(), 2);
+        assert_eq!(env[0], "PATH=/usr/bin");
+    }
 
-#[allow(clippy::too_many_arguments)]
-fn exec_with_pty(
-    program: &str,
-    argv: &[String],
-    cwd: Option<String>,
-    env_vars: Option<Vec<String>>,
-    stdin_path: Option<String>,
-    stdout_path: Option<String>,
-    user_config: Option<OciUser>,
-    container_id: &str,
-    exec_id: &str,
-) -> i32 {
-    use nix::pty::openpty;
-    use std::io::{Read, Write};
-    use std::os::unix::io::AsRawFd;
+    #[test]
+    fn test_parse_config_with_mounts() {
+        let bundle_dir = TempDir::new().expect("Failed to create temp dir");
+        let config = serde_json::json!({
+            "process": {
+                "args": ["/bin/sh", "-c", "ls /scripts"],
+                "cwd": "/"
+            },
+            "mounts": [
+                {
+                    "destination": "/proc",
+                    "type": "proc",
+                    "source": "proc",
+                    "options": ["nosuid", "noexec", "nodev"]
+                },
+                {
+                    "destination": "/scripts",
+                    "type": "bind",
+                    "source": "/var/lib/kubelet/pods/abc/volumes/kubernetes.io~configmap/scripts",
+                    "options": ["rbind", "ro"]
+                },
+                {
+                    "destination": "/data",
+                    "source": "/host/data",
+                    "options": ["rbind"]
+                }
+            ]
+        });
+        fs::write(
+            bundle_dir.path().join("config.json"),
+            serde_json::to_string(&config).unwrap(),
+        )
+        .unwrap();
 
-    // Create PTY
-    let pty = match openpty(None, None) {
-        Ok(pty) => pty,
-        Err(e) => {
-            tracing::error!("openpty failed: {}", e);
-            return 1;
-        }
-    };
+        let parsed = read_oci_config(bundle_dir.path()).unwrap();
+        assert_eq!(parsed.mounts.len(), 3);
 
-    let slave_raw_fd = pty.slave.as_raw_fd();
+        assert_eq!(parsed.mounts[0].destination, "/proc");
+        assert_eq!(parsed.mounts[0].mount_type, Some("proc".to_string()));
 
-    let mut cmd = Command::new(program);
-    cmd.args(argv);
-    if let Some(ref cwd) = cwd {
-        cmd.current_dir(cwd);
+        assert_eq!(parsed.mounts[1].destination, "/scripts");
+        assert_eq!(parsed.mounts[1].mount_type, Some("bind".to_string()));
+        assert_eq!(
+            parsed.mounts[1].source,
+            Some("/var/lib/kubelet/pods/abc/volumes/kubernetes.io~configmap/scripts".to_string())
+        );
+        assert_eq!(parsed.mounts[1].options, vec!["rbind", "ro"]);
+
+        assert_eq!(parsed.mounts[2].destination, "/data");
+        assert!(parsed.mounts[2].mount_type.is_none());
+        assert_eq!(parsed.mounts[2].options, vec!["rbind"]);
     }
-    if let Some(ref envs) = env_vars {
-        for kv in envs {
-            if let Some((k, v)) = kv.split_once('=') {
-                cmd.env(k, v);
+
+    #[test]
+    fn test_parse_config_without_mounts() {
+        let bundle_dir = TempDir::new().expect("Failed to create temp dir");
+        let config = serde_json::json!({
+            "process": {
+                "args": ["/bin/echo", "hello"]
             }
-        }
+        });
+        fs::write(
+            bundle_dir.path().join("config.json"),
+            serde_json::to_string(&config).unwrap(),
+        )
+        .unwrap();
+
+        let parsed = read_oci_config(bundle_dir.path()).unwrap();
+        assert!(parsed.mounts.is_empty());
     }
 
-    // PTY slave becomes stdin/stdout/stderr via pre_exec
-    cmd.stdin(Stdio::null());
-    cmd.stdout(Stdio::null());
-    cmd.stderr(Stdio::null());
+    // --- parse_program_and_args tests ---
 
-    unsafe {
-        cmd.pre_exec(move || {
-            // New session so we can set controlling terminal
-            if nix::libc::setsid() < 0 {
-                return Err(std::io::Error::last_os_error());
-            }
-            // Set controlling terminal
-            // TIOCSCTTY: ioctl request type varies by arch (c_int on aarch64, c_ulong on x86_64/macOS)
-            if nix::libc::ioctl(
-                slave_raw_fd,
-                nix::libc::TIOCSCTTY as _,
-                0 as nix::libc::c_int,
-            ) < 0
-            {
-                return Err(std::io::Error::last_os_error());
-            }
-            // Dup slave to stdin/stdout/stderr
-            nix::libc::dup2(slave_raw_fd, 0);
-            nix::libc::dup2(slave_raw_fd, 1);
-            nix::libc::dup2(slave_raw_fd, 2);
-            if slave_raw_fd > 2 {
-                nix::libc::close(slave_raw_fd);
-            }
+    #[test]
+    fn test_parse_program_and_args_valid() {
+        let cfg = OciConfig {
+            process: Some(OciProcess {
+                args: Some(vec!["/bin/echo".into(), "hello".into(), "world".into()]),
+                env: None,
+                cwd: None,
+                user: None,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let (program, argv) = parse_program_and_args(&cfg).unwrap();
+        assert_eq!(program, PathBuf::from("/bin/echo"));
+        assert_eq!(argv, vec!["hello", "world"]);
+    }
 
-            // Apply user/group configuration if present
-            if let Some(ref user) = user_config {
-                // Set supplementary groups first (must be done while privileged)
-                if !user.additional_gids.is_empty() {
-                    safe_setgroups(&user.additional_gids)?;
-                }
+    #[test]
+    fn test_parse_program_and_args_single_element() {
+        let cfg = OciConfig {
+            process: Some(OciProcess {
+                args: Some(vec!["/bin/true".into()]),
+                env: None,
+                cwd: None,
+                user: None,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let (program, argv) = parse_program_and_args(&cfg).unwrap();
+        assert_eq!(program, PathBuf::from("/bin/true"));
+        assert!(argv.is_empty());
+    }
 
-                // Set GID before UID (privilege dropping order matters)
-                if nix::libc::setgid(user.gid) != 0 {
-                    return Err(std::io::Error::last_os_error());
-                }
+    #[test]
+    fn test_parse_program_and_args_empty_args() {
+        let cfg = OciConfig {
+            process: Some(OciProcess {
+                args: Some(vec![]),
+                env: None,
+                cwd: None,
+                user: None,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let result = parse_program_and_args(&cfg);
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("at least one element"));
+    }
 
-                // Set UID (this must be last - irreversible privilege drop)
-                if nix::libc::setuid(user.uid) != 0 {
-                    return Err(std::io::Error::last_os_error());
-                }
+    #[test]
+    fn test_parse_program_and_args_none_args() {
+        let cfg = OciConfig {
+            process: Some(OciProcess {
+                args: None,
+                env: None,
+                cwd: None,
+                user: None,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let result = parse_program_and_args(&cfg);
+        assert!(result.is_err());
+    }
 
-                // Apply umask if specified
-                if let Some(mask) = user.umask {
-                    nix::libc::umask(mask as nix::libc::mode_t);
-                }
-            }
+    #[test]
+    fn test_parse_program_and_args_no_process() {
+        let cfg = OciConfig {
+            process: None,
+            ..Default::default()
+        };
+        let result = parse_program_and_args(&cfg);
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("missing 'process'"));
+    }
 
-            Ok(())
-        });
+    #[test]
+    fn test_parse_program_and_args_relative_path() {
+        let cfg = OciConfig {
+            process: Some(OciProcess {
+                args: Some(vec!["my-binary".into(), "--flag".into()]),
+                env: None,
+                cwd: None,
+                user: None,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let (program, argv) = parse_program_and_args(&cfg).unwrap();
+        assert_eq!(program, PathBuf::from("my-binary"));
+        assert_eq!(argv, vec!["--flag"]);
     }
 
-    let mut child = match cmd.spawn() {
-        Ok(c) => c,
-        Err(e) => {
-            tracing::error!("exec spawn failed: {}", e);
-            return 1;
-        }
-    };
+    #[test]
+    fn test_parse_program_and_args_rejects_windows_config() {
+        let bundle_dir = TempDir::new().unwrap();
+        let config = serde_json::json!({
+            "ociVersion": "1.1.0",
+            "process": {
+                "commandLine": "cmd /c echo hello",
+                "cwd": "C:\\"
+            },
+            "windows": {
+                "layerFolders": ["C:\\layers\\base"]
+            }
+        });
+        fs::write(
+            bundle_dir.path().join("config.json"),
+            serde_json::to_string(&config).unwrap(),
+        )
+        .unwrap();
 
-    let exec_pid = child.id() as i32;
+        let cfg = read_oci_config(bundle_dir.path()).unwrap();
+        let err = parse_program_and_args(&cfg).unwrap_err().to_string();
+        assert!(err.contains("non-Linux platform"), "got: {}", err);
+        assert!(err.contains("process.commandLine"), "got: {}", err);
+        assert!(err.contains("windows"), "got: {}", err);
+        assert!(!err.contains("at least one element"), "got: {}", err);
+    }
 
-    // Update exec state with PID
-    if let Ok(mut state) = load_exec_state(container_id, exec_id) {
-        state.status = "running".into();
-        state.pid = Some(exec_pid);
-        let _ = save_exec_state(&state);
+    #[test]
+    fn test_parse_program_and_args_rejects_vm_section() {
+        let cfg = OciConfig {
+            process: Some(OciProcess {
+                args: Some(vec!["/bin/true".into()]),
+                ..Default::default()
+            }),
+            vm: Some(serde_json::json!({"hypervisor": {"path": "/usr/bin/qemu"}})),
+            ..Default::default()
+        };
+        let err = parse_program_and_args(&cfg).unwrap_err().to_string();
+        assert!(err.contains("found vm"), "got: {}", err);
     }
 
-    // Close slave in parent - child has it via dup2
-    drop(pty.slave);
+    // --- REAPER_DERIVE_HOME tests ---
 
-    // Capture master raw fd for resize ioctl before converting to File
-    #[cfg(target_os = "linux")]
-    let master_raw_fd = {
-        use std::os::unix::io::AsRawFd;
-        pty.master.as_raw_fd()
-    };
+    const TEST_PASSWD: &str = "\
+root:x:0:0:root:/root:/bin/bash
+daemon:x:1:1:daemon:/usr/sbin:/usr/sbin/nologin
+# comment line
+broken:x:notanumber:0::/nowhere:/bin/sh
+nohome:x:1001:1001:::/bin/sh
+alice:x:1000:1000:Alice,,,:/home/alice:/bin/zsh
+";
 
-    // Convert PTY master OwnedFd to File for I/O
-    let master_file: std::fs::File = pty.master.into();
-    let master_clone = master_file.try_clone().unwrap_or_else(|e| {
-        tracing::error!("failed to clone master fd: {}", e);
-        std::process::exit(1);
-    });
+    #[test]
+    fn test_passwd_home_for_uid() {
+        assert_eq!(
+            passwd_home_for_uid(TEST_PASSWD, 0).as_deref(),
+            Some("/root")
+        );
+        assert_eq!(
+            passwd_home_for_uid(TEST_PASSWD, 1000).as_deref(),
+            Some("/home/alice")
+        );
+        assert_eq!(passwd_home_for_uid(TEST_PASSWD, 1001), None);
+        assert_eq!(passwd_home_for_uid(TEST_PASSWD, 4242), None);
+        assert_eq!(passwd_home_for_uid("", 0), None);
+    }
 
-    // Start PTY resize watcher thread
-    #[cfg(target_os = "linux")]
-    let resize_stop = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
-    #[cfg(target_os = "linux")]
-    spawn_resize_watcher(
-        master_raw_fd,
-        exec_resize_path(container_id, exec_id),
-        resize_stop.clone(),
-    );
+    #[test]
+    fn test_default_user_env_fills_missing() {
+        let env = default_user_env(Some(vec!["FOO=bar".into()]), Some("/home/alice"));
+        assert_eq!(
+            env,
+            vec![
+                "FOO=bar".to_string(),
+                "HOME=/home/alice".to_string(),
+                format!("PATH={}", DEFAULT_PATH),
+            ]
+        );
 
-    // Start relay threads
-    // stdin FIFO → PTY master (user input to process)
-    if let Some(ref stdin_p) = stdin_path {
-        if !stdin_p.is_empty() {
-            let stdin_path = stdin_p.clone();
-            let mut master_w = master_clone; // master_clone for writing
-            std::thread::spawn(move || {
-                if let Ok(mut stdin_file) = std::fs::File::open(&stdin_path) {
-                    let mut buf = [0u8; 4096];
-                    loop {
-                        match stdin_file.read(&mut buf) {
-                            Ok(0) => break,
-                            Ok(n) => {
-                                if master_w.write_all(&buf[..n]).is_err() {
-                                    break;
-                                }
-                            }
-                            Err(_) => break,
-                        }
-                    }
-                }
-            });
-        }
+        // No env at all, no passwd entry
+        let env = default_user_env(None, None);
+        assert!(env.contains(&"HOME=/".to_string()));
+        assert!(env.contains(&format!("PATH={}", DEFAULT_PATH)));
     }
 
-    // PTY master → stdout FIFO (process output to user)
-    if let Some(ref stdout_p) = stdout_path {
-        if !stdout_p.is_empty() {
-            let stdout_path = stdout_p.clone();
-            let mut master_r = master_file; // master_file for reading
-            std::thread::spawn(move || {
-                if let Ok(mut stdout_file) =
-                    std::fs::OpenOptions::new().write(true).open(&stdout_path)
-                {
-                    let mut buf = [0u8; 4096];
-                    loop {
-                        match master_r.read(&mut buf) {
-                            Ok(0) => break,
-                            Ok(n) => {
-                                if stdout_file.write_all(&buf[..n]).is_err() {
-                                    break;
-                                }
-                            }
-                            Err(_) => break, // EIO when slave closes
-                        }
-                    }
-                }
-            });
-        }
+    #[test]
+    fn test_default_user_env_keeps_explicit_values() {
+        let explicit = vec![
+            "PATH=/opt/bin".to_string(),
+            "HOME=/data".to_string(),
+            "HOMEDIR=/ignored".to_string(),
+        ];
+        let env = default_user_env(Some(explicit.clone()), Some("/home/alice"));
+        assert_eq!(env, explicit);
     }
 
-    // Wait for child
-    let exit = match child.wait() {
-        Ok(status) => exit_code_from_status(status),
-        Err(_) => 1,
-    };
-    #[cfg(target_os = "linux")]
-    resize_stop.store(true, std::sync::atomic::Ordering::Relaxed);
-    exit
-}
+    #[test]
+    #[serial]
+    fn test_derive_user_env_disabled_by_default() {
+        std::env::remove_var("REAPER_DERIVE_HOME");
+        assert_eq!(derive_user_env(None, None), None);
+        let env = Some(vec!["FOO=bar".to_string()]);
+        assert_eq!(derive_user_env(env.clone(), None), env);
+    }
 
-#[allow(clippy::too_many_arguments)]
-fn exec_without_pty(
-    program: &str,
-    argv: &[String],
-    cwd: Option<String>,
-    env_vars: Option<Vec<String>>,
-    stdin_path: Option<String>,
-    stdout_path: Option<String>,
-    stderr_path: Option<String>,
-    user_config: Option<OciUser>,
-    container_id: &str,
-    exec_id: &str,
-) -> i32 {
-    let mut cmd = Command::new(program);
-    cmd.args(argv);
-    if let Some(ref cwd) = cwd {
-        cmd.current_dir(cwd);
+    // --- start dependency tests ---
+
+    fn pod_state(id: &str, name: &str, status: &str) -> ContainerState {
+        let mut state = ContainerState::new(id.into(), PathBuf::from("/bundle"));
+        state.namespace = Some("jobs".into());
+        state.sandbox_id = Some("pod-1".into());
+        state.container_name = Some(name.into());
+        state.status = status.into();
+        state
     }
-    if let Some(ref envs) = env_vars {
-        for kv in envs {
-            if let Some((k, v)) = kv.split_once('=') {
-                cmd.env(k, v);
-            }
+
+    fn after(container: &str, status: &str) -> annotations::StartAfter {
+        annotations::StartAfter {
+            container: container.into(),
+            status: status.into(),
         }
     }
 
-    // Connect FIFOs directly
-    if let Some(ref p) = stdin_path {
-        if !p.is_empty() {
-            if let Ok(f) = std::fs::File::open(p) {
-                cmd.stdin(Stdio::from(f));
-            } else {
-                cmd.stdin(Stdio::null());
-            }
-        } else {
-            cmd.stdin(Stdio::null());
-        }
-    } else {
-        cmd.stdin(Stdio::null());
+    #[test]
+    fn test_is_start_dependency() {
+        let waiter = pod_state("c-app", "app", "created");
+        let dep = pod_state("c-init", "init-fs", "created");
+        assert!(is_start_dependency(&dep, "c-init", &waiter));
+        assert!(is_start_dependency(&dep, "init-fs", &waiter));
+        assert!(!is_start_dependency(&dep, "other", &waiter));
+        // Never itself
+        assert!(!is_start_dependency(&waiter, "app", &waiter));
+
+        // Names only resolve within the same pod; IDs within the namespace
+        let mut other_pod = dep.clone();
+        other_pod.sandbox_id = Some("pod-2".into());
+        assert!(!is_start_dependency(&other_pod, "init-fs", &waiter));
+        assert!(is_start_dependency(&other_pod, "c-init", &waiter));
+        let mut other_ns = dep.clone();
+        other_ns.namespace = Some("web".into());
+        assert!(!is_start_dependency(&other_ns, "c-init", &waiter));
     }
 
-    if let Some(ref p) = stdout_path {
-        if !p.is_empty() {
-            match open_log_file(p) {
-                Ok(f) => cmd.stdout(Stdio::from(f)),
-                Err(_) => cmd.stdout(Stdio::inherit()),
-            };
-        } else {
-            cmd.stdout(Stdio::inherit());
-        }
-    } else {
-        cmd.stdout(Stdio::inherit());
+    #[test]
+    fn test_start_dependency_reached() {
+        assert!(!start_dependency_reached("created", "running"));
+        assert!(start_dependency_reached("running", "running"));
+        assert!(start_dependency_reached("stopped", "running"));
+        assert!(!start_dependency_reached("running", "stopped"));
+        assert!(start_dependency_reached("stopped", "stopped"));
     }
 
-    if let Some(ref p) = stderr_path {
-        if !p.is_empty() {
-            match open_log_file(p) {
-                Ok(f) => cmd.stderr(Stdio::from(f)),
-                Err(_) => cmd.stderr(Stdio::inherit()),
-            };
-        } else {
-            cmd.stderr(Stdio::inherit());
-        }
-    } else {
-        cmd.stderr(Stdio::inherit());
+    #[test]
+    #[serial]
+    fn test_wait_for_start_dependency_blocks_until_running() {
+        with_test_root(|_| {
+            let waiter = pod_state("c-app", "app", "created");
+            save_state(&waiter).unwrap();
+            save_state(&pod_state("c-init", "init-fs", "created")).unwrap();
+
+            let root = std::env::var("REAPER_RUNTIME_ROOT").unwrap();
+            let marker = std::thread::spawn(move || {
+                std::thread::sleep(std::time::Duration::from_millis(300));
+                let mut dep = pod_state("c-init", "init-fs", "running");
+                dep.pid = Some(1);
+                let path = Path::new(&root).join("c-init/state.json");
+                fs::write(path, serde_json::to_vec(&dep).unwrap()).unwrap();
+            });
+
+            let started = std::time::Instant::now();
+            wait_for_start_dependency(
+                &waiter,
+                &after("init-fs", "running"),
+                std::time::Duration::from_secs(5),
+                std::time::Duration::from_millis(20),
+            )
+            .unwrap();
+            assert!(started.elapsed() >= std::time::Duration::from_millis(300));
+            marker.join().unwrap();
+        });
     }
 
-    // Always call setsid() so the exec process gets its own process group
-    // (PGID == PID), matching what the shim's kill() expects when sending
-    // signals to -pid.
-    {
-        let user_cfg_clone = user_config;
-        unsafe {
-            cmd.pre_exec(move || {
-                if nix::libc::setsid() < 0 {
-                    return Err(std::io::Error::last_os_error());
-                }
+    #[test]
+    #[serial]
+    fn test_wait_for_start_dependency_times_out() {
+        with_test_root(|_| {
+            let waiter = pod_state("c-app", "app", "created");
+            save_state(&pod_state("c-init", "init-fs", "running")).unwrap();
+
+            let err = wait_for_start_dependency(
+                &waiter,
+                &after("init-fs", "stopped"),
+                std::time::Duration::from_millis(100),
+                std::time::Duration::from_millis(20),
+            )
+            .unwrap_err();
+            let msg = err.to_string();
+            assert!(msg.contains("init-fs to be stopped"), "{}", msg);
+            assert!(msg.contains("status: running"), "{}", msg);
+
+            let err = wait_for_start_dependency(
+                &waiter,
+                &after("missing", "running"),
+                std::time::Duration::ZERO,
+                std::time::Duration::from_millis(20),
+            )
+            .unwrap_err();
+            assert!(err.to_string().contains("not found"), "{}", err);
+        });
+    }
 
-                // Apply user/group configuration if present
-                if let Some(ref user) = user_cfg_clone {
-                    // Set supplementary groups first (must be done while privileged)
-                    if !user.additional_gids.is_empty() {
-                        safe_setgroups(&user.additional_gids)?;
-                    }
+    #[test]
+    fn test_use_terminal_config_false_wins() {
+        assert!(use_terminal(true, None));
+        assert!(use_terminal(true, Some(true)));
+        assert!(!use_terminal(true, Some(false)));
+        // config.json alone never asks for a PTY
+        assert!(!use_terminal(false, Some(true)));
+        assert!(!use_terminal(false, None));
+    }
 
-                    // Set GID before UID (privilege dropping order matters)
-                    if nix::libc::setgid(user.gid) != 0 {
-                        return Err(std::io::Error::last_os_error());
-                    }
+    #[test]
+    fn test_oci_process_terminal_deserialize() {
+        let proc: OciProcess =
+            serde_json::from_value(serde_json::json!({"args": ["/bin/sh"], "terminal": false}))
+                .unwrap();
+        assert_eq!(proc.terminal, Some(false));
+        let proc: OciProcess =
+            serde_json::from_value(serde_json::json!({"args": ["/bin/sh"]})).unwrap();
+        assert_eq!(proc.terminal, None);
+    }
 
-                    // Set UID (this must be last - irreversible privilege drop)
-                    if nix::libc::setuid(user.uid) != 0 {
-                        return Err(std::io::Error::last_os_error());
-                    }
+    #[test]
+    fn test_settle_delay() {
+        assert_eq!(settle_delay(false, false), SETTLE_DELAY);
+        assert_eq!(settle_delay(false, true), std::time::Duration::ZERO);
+        // Interactive containers keep the delay
+        assert_eq!(settle_delay(true, true), SETTLE_DELAY);
+        assert_eq!(settle_delay(true, false), SETTLE_DELAY);
+    }
 
-                    // Apply umask if specified
-                    if let Some(mask) = user.umask {
-                        nix::libc::umask(mask as nix::libc::mode_t);
-                    }
-                }
+    // --- passthrough env tests ---
 
-                Ok(())
-            });
+    fn proxy_lookup(name: &str) -> Option<String> {
+        match name {
+            "HTTP_PROXY" => Some("http://proxy:3128".into()),
+            "NO_PROXY" => Some("localhost,.svc".into()),
+            "SECRET" => Some("leak".into()),
+            _ => None,
         }
     }
 
-    let mut child = match cmd.spawn() {
-        Ok(c) => c,
-        Err(e) => {
-            tracing::error!("exec spawn failed: {}", e);
-            return 1;
-        }
-    };
+    #[test]
+    fn test_merge_passthrough_env_only_named_vars() {
+        let names = vec!["HTTP_PROXY".to_string(), "NO_PROXY".to_string()];
+        let env = merge_passthrough_env(Some(vec!["FOO=bar".into()]), &names, proxy_lookup);
+        assert_eq!(
+            env,
+            Some(vec![
+                "HTTP_PROXY=http://proxy:3128".to_string(),
+                "NO_PROXY=localhost,.svc".to_string(),
+                "FOO=bar".to_string(),
+            ])
+        );
 
-    let exec_pid = child.id() as i32;
+        // Unset names are skipped; nothing to add leaves the env untouched
+        let names = vec!["UNSET".to_string()];
+        assert_eq!(merge_passthrough_env(None, &names, proxy_lookup), None);
+    }
 
-    // Update exec state
-    if let Ok(mut state) = load_exec_state(container_id, exec_id) {
-        state.status = "running".into();
-        state.pid = Some(exec_pid);
-        let _ = save_exec_state(&state);
+    #[test]
+    fn test_merge_passthrough_env_process_env_wins() {
+        let names = vec!["HTTP_PROXY".to_string(), "NO_PROXY".to_string()];
+        let env = merge_passthrough_env(
+            Some(vec!["HTTP_PROXY=http://pod-proxy:8080".into()]),
+            &names,
+            proxy_lookup,
+        );
+        assert_eq!(
+            env,
+            Some(vec![
+                "NO_PROXY=localhost,.svc".to_string(),
+                "HTTP_PROXY=http://pod-proxy:8080".to_string(),
+            ])
+        );
     }
 
-    match child.wait() {
-        Ok(status) => exit_code_from_status(status),
-        Err(_) => 1,
+    #[test]
+    fn test_parse_env_file() {
+        let content = "\
+# database settings
+DB_HOST=db.internal
+export DB_PORT=5432
+
+GREETING=\"hello world\"
+QUOTED='a=b'
+not a var
+1BAD=x
+DB_HOST=db.other
+EMPTY=
+";
+        assert_eq!(
+            parse_env_file(content),
+            vec![
+                ("DB_HOST".to_string(), "db.other".to_string()),
+                ("DB_PORT".to_string(), "5432".to_string()),
+                ("GREETING".to_string(), "hello world".to_string()),
+                ("QUOTED".to_string(), "a=b".to_string()),
+                ("EMPTY".to_string(), String::new()),
+            ]
+        );
     }
-}
 
-fn do_exec(container_id: &str, exec_id: &str) -> Result<()> {
-    info!(
-        "do_exec() called - container_id={}, exec_id={}",
-        container_id, exec_id
-    );
+    #[test]
+    #[serial]
+    fn test_env_from_file_below_process_env_and_above_passthrough() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = TempDir::new().unwrap();
+        // Read as nobody when the tests run as root
+        fs::set_permissions(dir.path(), fs::Permissions::from_mode(0o755)).unwrap();
+        let roots = [dir.path().to_path_buf()];
+        let path = dir.path().join("env");
+        fs::write(
+            &path,
+            "LOG_LEVEL=debug\nHTTP_PROXY=http://file-proxy:1\nMODE=file\n",
+        )
+        .unwrap();
 
-    let exec_state = load_exec_state(container_id, exec_id)?;
+        // process.env wins over the file
+        let env = env_from_file(Some(vec!["MODE=config".into()]), &path, &roots, None);
+        assert_eq!(
+            env,
+            Some(vec![
+                "LOG_LEVEL=debug".to_string(),
+                "HTTP_PROXY=http://file-proxy:1".to_string(),
+                "MODE=config".to_string(),
+            ])
+        );
 
-    // Load container state to get namespace and annotations for overlay isolation.
-    // We need annotations here to extract overlay-name, which determines which
-    // overlay namespace to join. Other annotation-driven overrides (like dns-mode)
-    // modify the overlay filesystem during do_start(), and exec'd processes inherit
-    // those changes automatically by joining the same overlay namespace.
-    #[cfg(target_os = "linux")]
-    let container_state = load_state(container_id)?;
-    #[cfg(target_os = "linux")]
-    let container_namespace = container_state.namespace.clone();
-    #[cfg(target_os = "linux")]
-    let overlay_name_override = container_state
-        .annotations
-        .as_ref()
-        .and_then(annotations::parse_stripped_annotations)
-        .and_then(|a| a.overlay_name);
+        // The file, applied first, wins over REAPER_PASSTHROUGH_ENV
+        std::env::set_var("REAPER_PASSTHROUGH_ENV", "HTTP_PROXY");
+        std::env::set_var("HTTP_PROXY", "http://node-proxy:2");
+        let env = passthrough_env(env).unwrap();
+        std::env::remove_var("REAPER_PASSTHROUGH_ENV");
+        std::env::remove_var("HTTP_PROXY");
+        assert_eq!(
+            env.iter()
+                .filter(|kv| kv.starts_with("HTTP_PROXY="))
+                .collect::<Vec<_>>(),
+            ["HTTP_PROXY=http://file-proxy:1"]
+        );
 
-    let args = exec_state.args.clone();
-    if args.is_empty() {
-        bail!("exec process args must not be empty");
+        // A missing file leaves the env alone
+        let missing = dir.path().join("missing");
+        let env = env_from_file(Some(vec!["A=1".into()]), &missing, &roots, None);
+        assert_eq!(env, Some(vec!["A=1".to_string()]));
     }
 
-    let program = args[0].clone();
-    let argv: Vec<String> = args[1..].to_vec();
-    let cwd = exec_state.cwd.clone();
-    let env_vars = exec_state.env.clone();
-    let terminal = exec_state.terminal;
-    let stdin_path = exec_state.stdin.clone();
-    let stdout_path = exec_state.stdout.clone();
-    let stderr_path = exec_state.stderr.clone();
-    let user_cfg = exec_state.user.clone();
+    #[test]
+    fn test_env_file_must_be_under_a_mount() {
+        let dir = TempDir::new().unwrap();
+        let volume = dir.path().join("config");
+        fs::create_dir(&volume).unwrap();
+        fs::write(volume.join("env"), "A=1\n").unwrap();
+        fs::write(dir.path().join("shadow"), "root:x\n").unwrap();
+        let roots = [volume.clone()];
+
+        assert!(resolve_env_file(&volume.join("env"), &roots).is_ok());
+        // Outside every mount, directly or through a symlink in the volume
+        let err = resolve_env_file(&dir.path().join("shadow"), &roots).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+        std::os::unix::fs::symlink(dir.path().join("shadow"), volume.join("link")).unwrap();
+        assert!(resolve_env_file(&volume.join("link"), &roots).is_err());
+        assert!(resolve_env_file(&volume.join("../shadow"), &roots).is_err());
+        // The container root never counts as a mount
+        assert!(resolve_env_file(&dir.path().join("shadow"), &[PathBuf::from("/")]).is_err());
+
+        let mounts: Vec<OciMount> = serde_json::from_value(serde_json::json!([
+            {"destination": "/", "type": "bind", "source": "/"},
+            {"destination": "/etc/config", "type": "bind", "source": "/vol"},
+            {"destination": "relative", "type": "bind", "source": "/vol"},
+        ]))
+        .unwrap();
+        assert_eq!(env_file_roots(&mounts), [PathBuf::from("/etc/config")]);
+    }
 
-    let container_id = container_id.to_string();
-    let exec_id = exec_id.to_string();
+    /// As root, the file is read as the workload user (nobody for a root
+    /// workload), never with root's access.
+    #[test]
+    fn test_env_file_read_as_workload_user() {
+        use std::os::unix::fs::PermissionsExt;
+        if !nix::unistd::geteuid().is_root() {
+            eprintln!("skipping: requires root");
+            return;
+        }
+        let dir = TempDir::new().unwrap();
+        fs::set_permissions(dir.path(), fs::Permissions::from_mode(0o755)).unwrap();
+        let roots = [dir.path().to_path_buf()];
+        let public = dir.path().join("public");
+        let secret = dir.path().join("secret");
+        fs::write(&public, "A=1\n").unwrap();
+        fs::write(&secret, "aws_secret_access_key = X\n").unwrap();
+        fs::set_permissions(&public, fs::Permissions::from_mode(0o644)).unwrap();
+        fs::set_permissions(&secret, fs::Permissions::from_mode(0o600)).unwrap();
+
+        assert_eq!(read_env_file(&public, &roots, None).unwrap(), "A=1\n");
+        assert!(read_env_file(&secret, &roots, None).is_err());
+        let root_user: OciUser =
+            serde_json::from_value(serde_json::json!({"uid": 0, "gid": 0})).unwrap();
+        assert!(read_env_file(&secret, &roots, Some(&root_user)).is_err());
+
+        // Readable by its owner
+        nix::unistd::chown(&secret, Some(1000.into()), Some(1000.into())).unwrap();
+        let owner: OciUser =
+            serde_json::from_value(serde_json::json!({"uid": 1000, "gid": 1000})).unwrap();
+        assert!(read_env_file(&secret, &roots, Some(&owner)).is_ok());
+        let env = env_from_file(None, &secret, &roots, None);
+        assert_eq!(env, None);
+    }
 
-    use nix::unistd::{fork, ForkResult};
+    #[test]
+    #[serial]
+    fn test_passthrough_env_names() {
+        std::env::remove_var("REAPER_PASSTHROUGH_ENV");
+        assert!(passthrough_env_names().is_empty());
+        std::env::set_var("REAPER_PASSTHROUGH_ENV", " HTTP_PROXY, ,NO_PROXY ");
+        assert_eq!(passthrough_env_names(), vec!["HTTP_PROXY", "NO_PROXY"]);
+        std::env::remove_var("REAPER_PASSTHROUGH_ENV");
+    }
 
-    match unsafe { fork() } {
-        Ok(ForkResult::Parent { .. }) => {
-            // Poll exec state for PID (same pattern as do_start)
-            let mut exec_pid = None;
-            for _ in 0..20 {
-                if let Ok(state) = load_exec_state(&container_id, &exec_id) {
-                    if state.pid.is_some() {
-                        exec_pid = state.pid;
-                        break;
-                    }
-                }
-                std::thread::sleep(std::time::Duration::from_millis(100));
-            }
-            if let Some(pid) = exec_pid {
-                println!("exec started pid={}", pid);
-            } else {
-                println!("exec started pid=0");
-            }
-            Ok(())
-        }
-        Ok(ForkResult::Child) => {
-            // Close inherited stdout/stderr to unblock the shim's cmd.output()
-            // (same reason as in do_start - see comment there)
-            {
-                use std::os::unix::io::AsRawFd;
-                if let Ok(devnull) = std::fs::File::open("/dev/null") {
-                    let fd = devnull.as_raw_fd();
-                    unsafe {
-                        nix::libc::dup2(fd, 1);
-                        nix::libc::dup2(fd, 2);
+    #[test]
+    fn test_is_privileged() {
+        let parse = |json: &str| -> OciConfig { serde_json::from_str(json).unwrap() };
+        assert!(!is_privileged(&parse(r#"{}"#)));
+        assert!(!is_privileged(&parse(
+            r#"{"linux": {"readonlyPaths": ["/proc/sys"], "maskedPaths": ["/proc/kcore"]}}"#
+        )));
+        assert!(is_privileged(&parse(
+            r#"{"linux": {"cgroupsPath": "/kubepods/x"}}"#
+        )));
+        assert!(is_privileged(&parse(
+            r#"{"linux": {"readonlyPaths": [], "maskedPaths": []}}"#
+        )));
+    }
+
+    #[test]
+    fn test_read_oci_config_resources() {
+        let bundle = TempDir::new().unwrap();
+        fs::write(
+            bundle.path().join("config.json"),
+            serde_json::json!({
+                "process": {"args": ["/bin/true"]},
+                "linux": {
+                    "cgroupsPath": "/kubepods/burstable/pod1/abc",
+                    "resources": {
+                        "memory": {"limit": 268435456, "swap": 268435456, "reservation": 0},
+                        "cpu": {"shares": 256, "quota": 25000, "period": 100000},
+                        "devices": [{"allow": false, "access": "rwm"}]
                     }
                 }
-            }
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let resources = read_oci_config(bundle.path())
+            .unwrap()
+            .linux
+            .unwrap()
+            .resources
+            .unwrap();
+        let memory = resources.memory.unwrap();
+        assert_eq!(
+            (memory.limit, memory.swap),
+            (Some(268435456), Some(268435456))
+        );
+        let cpu = resources.cpu.unwrap();
+        assert_eq!(
+            (cpu.quota, cpu.period, cpu.shares),
+            (Some(25000), Some(100000), Some(256))
+        );
+    }
 
-            if let Err(e) = nix::unistd::setsid() {
-                let _ = e;
-            }
+    #[test]
+    #[serial]
+    fn test_kill_grace_precedence() {
+        use std::time::Duration;
+        std::env::remove_var("REAPER_ANNOTATIONS_ENABLED");
+        std::env::remove_var("REAPER_KILL_GRACE_SECS");
+        assert_eq!(kill_grace(None, None), None);
+
+        // The annotation as do_kill reads it back from the state
+        let stripped =
+            std::collections::HashMap::from([("termination-grace".to_string(), "30".to_string())]);
+        let annotation = annotations::parse_stripped_annotations(&stripped)
+            .and_then(|parsed| parsed.termination_grace);
+        assert_eq!(kill_grace(None, annotation), Some(Duration::from_secs(30)));
+        assert_eq!(kill_grace(None, Some(0)), None);
+
+        // The env var wins, and 0 disables escalation despite the annotation
+        std::env::set_var("REAPER_KILL_GRACE_SECS", "5");
+        assert_eq!(kill_grace(None, Some(30)), Some(Duration::from_secs(5)));
+        std::env::set_var("REAPER_KILL_GRACE_SECS", "0");
+        assert_eq!(kill_grace(None, Some(30)), None);
+        std::env::set_var("REAPER_KILL_GRACE_SECS", "soon");
+        assert_eq!(kill_grace(None, Some(30)), Some(Duration::from_secs(30)));
+
+        // kill --timeout wins over both
+        std::env::set_var("REAPER_KILL_GRACE_SECS", "5");
+        assert_eq!(kill_grace(Some(2), Some(30)), Some(Duration::from_secs(2)));
+        assert_eq!(kill_grace(Some(0), Some(30)), None);
+        std::env::remove_var("REAPER_KILL_GRACE_SECS");
+    }
 
-            // Join overlay namespace (Linux only) - same as do_start
-            #[cfg(target_os = "linux")]
-            {
-                let overlay_config = match overlay::read_config(
-                    container_namespace.as_deref(),
-                    overlay_name_override.as_deref(),
-                ) {
-                    Ok(c) => c,
-                    Err(e) => {
-                        tracing::error!("do_exec() - overlay config failed: {:#}", e);
-                        if let Ok(mut state) = load_exec_state(&container_id, &exec_id) {
-                            state.status = "stopped".into();
-                            state.exit_code = Some(1);
-                            let _ = save_exec_state(&state);
-                        }
-                        std::process::exit(1);
-                    }
-                };
-                if let Err(e) = overlay::enter_overlay(&overlay_config) {
-                    tracing::error!("do_exec() - overlay failed: {:#}", e);
-                    if let Ok(mut state) = load_exec_state(&container_id, &exec_id) {
-                        state.status = "stopped".into();
-                        state.exit_code = Some(1);
-                        let _ = save_exec_state(&state);
-                    }
-                    std::process::exit(1);
-                }
-            }
+    #[test]
+    fn test_write_pid_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a/b/init.pid");
+        write_pid_file(&path, 4242).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "4242");
+        // Replaced, not appended to
+        write_pid_file(&path, 7).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "7");
+
+        // Can't replace a directory; the temporary file is cleaned up
+        let err = write_pid_file(&dir.path().join("a"), 1).unwrap_err();
+        assert!(
+            format!("{:#}", err).contains("writing pid file"),
+            "{:#}",
+            err
+        );
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
 
-            let exit_code = if terminal {
-                exec_with_pty(
-                    &program,
-                    &argv,
-                    cwd,
-                    env_vars,
-                    stdin_path,
-                    stdout_path,
-                    user_cfg,
-                    &container_id,
-                    &exec_id,
-                )
-            } else {
-                exec_without_pty(
-                    &program,
-                    &argv,
-                    cwd,
-                    env_vars,
-                    stdin_path,
-                    stdout_path,
-                    stderr_path,
-                    user_cfg,
-                    &container_id,
-                    &exec_id,
-                )
-            };
+    #[test]
+    fn test_needs_kill_escalation() {
+        let mut state = ContainerState::new("grace".into(), PathBuf::from("/bundle"));
+        state.status = STATUS_RUNNING.into();
+        state.pid = Some(4242);
+        assert!(needs_kill_escalation(Some(&state), 4242));
+        // Restarted under the same ID, or gone
+        assert!(!needs_kill_escalation(Some(&state), 4243));
+        assert!(!needs_kill_escalation(None, 4242));
+        state.mark_stopped(143, FailureOrigin::Workload);
+        assert!(!needs_kill_escalation(Some(&state), 4242));
+    }
 
-            // Update exec state to stopped
-            if let Ok(mut state) = load_exec_state(&container_id, &exec_id) {
-                state.status = "stopped".into();
-                state.exit_code = Some(exit_code);
-                let _ = save_exec_state(&state);
-            }
+    #[test]
+    #[serial]
+    fn test_overlay_disabled() {
+        std::env::remove_var("REAPER_NO_OVERLAY");
+        assert!(!overlay_disabled());
+        std::env::set_var("REAPER_NO_OVERLAY", "true");
+        assert!(overlay_disabled());
+        std::env::set_var("REAPER_NO_OVERLAY", "0");
+        assert!(!overlay_disabled());
+        std::env::remove_var("REAPER_NO_OVERLAY");
+    }
 
-            std::process::exit(0);
-        }
-        Err(e) => bail!("Fork failed: {}", e),
+    #[test]
+    fn test_is_host_network() {
+        let parse = |json: &str| -> OciConfig { serde_json::from_str(json).unwrap() };
+        let pod_ns = r#"{"linux": {"namespaces": [{"type": "pid"}, {"type": "ipc"},
+            {"type": "network", "path": "/var/run/netns/cni-1"}, {"type": "mount"}]}}"#;
+        let host_net_ns = r#"{"linux": {"namespaces": [{"type": "pid"}, {"type": "mount"}]}}"#;
+
+        assert!(!is_host_network(&parse(r#"{}"#), None));
+        assert!(!is_host_network(&parse(r#"{"linux": {}}"#), None));
+        assert!(!is_host_network(&parse(pod_ns), None));
+        assert!(is_host_network(&parse(host_net_ns), None));
+        assert!(is_host_network(
+            &parse(r#"{"annotations": {"io.kubernetes.cri-o.HostNetwork": "true"}}"#),
+            None
+        ));
+        // The annotation overrides detection either way
+        assert!(is_host_network(&parse(pod_ns), Some(true)));
+        assert!(!is_host_network(&parse(host_net_ns), Some(false)));
     }
-}
 
-fn main() -> Result<()> {
-    // Load config file before anything else (env vars override file values)
-    config::load_config();
+    #[test]
+    fn test_workload_hostname() {
+        let parse = |json: &str| -> OciConfig { serde_json::from_str(json).unwrap() };
+        assert_eq!(
+            workload_hostname(&parse(
+                r#"{"hostname": "web-0", "linux": {"namespaces": [{"type": "uts"}]}}"#
+            )),
+            Some("web-0".to_string())
+        );
+        // A path to the sandbox's namespace still gets the workload its own
+        assert_eq!(
+            workload_hostname(&parse(
+                r#"{"hostname": "web-0", "linux": {"namespaces":
+                    [{"type": "uts", "path": "/proc/1/ns/uts"}]}}"#
+            )),
+            Some("web-0".to_string())
+        );
+        // hostNetwork pods: no UTS namespace, the node's hostname
+        assert_eq!(
+            workload_hostname(&parse(
+                r#"{"hostname": "web-0", "linux": {"namespaces": [{"type": "mount"}]}}"#
+            )),
+            None
+        );
+        assert_eq!(workload_hostname(&parse(r#"{"hostname": "web-0"}"#)), None);
+        assert_eq!(
+            workload_hostname(&parse(
+                r#"{"hostname": "", "linux": {"namespaces": [{"type": "uts"}]}}"#
+            )),
+            None
+        );
+    }
 
-    // Setup tracing similar to shim: use REAPER_RUNTIME_LOG env var
-    // If not set, use null writer to prevent stdout pollution
-    if let Ok(log_path) = std::env::var("REAPER_RUNTIME_LOG") {
-        // If REAPER_RUNTIME_LOG is set, log to that file
-        if let Ok(log_file) = std::fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&log_path)
-        {
-            tracing_subscriber::fmt()
-                .with_env_filter(
-                    EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
-                )
-                .with_ansi(false) // No color codes in log files
-                .with_writer(std::sync::Mutex::new(log_file))
-                .init();
+    #[test]
+    #[serial]
+    fn test_default_additional_gids_union() {
+        std::env::set_var("REAPER_DEFAULT_ADDITIONAL_GIDS", "44, 107,,abc,20");
+        let defaults = default_additional_gids();
+        std::env::remove_var("REAPER_DEFAULT_ADDITIONAL_GIDS");
+        assert_eq!(defaults, vec![44, 107, 20]);
+        assert!(default_additional_gids().is_empty());
+
+        let user = OciUser {
+            uid: 1000,
+            gid: 1000,
+            additional_gids: vec![20, 30, 20],
+            umask: None,
+        };
+        let merged = with_default_gids(Some(user), &defaults).unwrap();
+        assert_eq!(merged.additional_gids, vec![20, 30, 44, 107]);
+        assert_eq!((merged.uid, merged.gid), (1000, 1000));
+
+        let no_groups = OciUser {
+            uid: 1000,
+            gid: 1000,
+            additional_gids: Vec::new(),
+            umask: None,
+        };
+        assert_eq!(
+            with_default_gids(Some(no_groups), &[])
+                .unwrap()
+                .additional_gids,
+            Vec::<u32>::new()
+        );
+        assert!(with_default_gids(None, &defaults).is_none());
+    }
 
-            info!("===== Reaper Runtime Starting =====");
-            info!("Log file: {}", log_path);
-        } else {
-            // Failed to open log file - use null writer to discard logs safely
-            let null_writer = std::io::sink();
-            tracing_subscriber::fmt()
-                .with_writer(std::sync::Mutex::new(null_writer))
-                .with_ansi(false)
-                .init();
-        }
-    } else {
-        // REAPER_RUNTIME_LOG not set - use null writer to discard all logs safely
-        let null_writer = std::io::sink();
-        tracing_subscriber::fmt()
-            .with_writer(std::sync::Mutex::new(null_writer))
-            .with_ansi(false)
-            .init();
+    #[test]
+    #[serial]
+    fn test_setgroups_policy() {
+        assert_eq!(
+            SetgroupsPolicy::parse("strict"),
+            Some(SetgroupsPolicy::Strict)
+        );
+        assert_eq!(
+            SetgroupsPolicy::parse("Best-Effort"),
+            Some(SetgroupsPolicy::BestEffort)
+        );
+        assert_eq!(SetgroupsPolicy::parse("lenient"), None);
+
+        std::env::remove_var("REAPER_SETGROUPS_POLICY");
+        assert_eq!(setgroups_policy(), SetgroupsPolicy::Strict);
+        std::env::set_var("REAPER_SETGROUPS_POLICY", "best-effort");
+        assert_eq!(setgroups_policy(), SetgroupsPolicy::BestEffort);
+        std::env::set_var("REAPER_SETGROUPS_POLICY", "bogus");
+        assert_eq!(setgroups_policy(), SetgroupsPolicy::Strict);
+        std::env::remove_var("REAPER_SETGROUPS_POLICY");
     }
 
-    let cli = Cli::parse();
-    info!(
-        "CLI parsed: bundle={:?}, root={:?}, command={:?}",
-        cli.bundle, cli.root, cli.command
-    );
+    #[test]
+    fn test_check_rootless_setgroups() {
+        let with_groups = OciUser {
+            uid: 1000,
+            gid: 1000,
+            additional_gids: vec![44],
+            umask: None,
+        };
+        let no_groups = OciUser {
+            additional_gids: Vec::new(),
+            ..with_groups.clone()
+        };
+        let strict = SetgroupsPolicy::Strict;
 
-    // Default bundle to current directory if not specified
-    let bundle = cli.bundle.as_deref().unwrap_or_else(|| Path::new("."));
+        let err = check_rootless_setgroups(true, Some(&with_groups), strict).unwrap_err();
+        assert!(err.to_string().contains("[44]"), "{err}");
+        // Best effort runs without them; nothing requested or not rootless is fine
+        assert!(
+            check_rootless_setgroups(true, Some(&with_groups), SetgroupsPolicy::BestEffort).is_ok()
+        );
+        assert!(check_rootless_setgroups(true, Some(&no_groups), strict).is_ok());
+        assert!(check_rootless_setgroups(true, None, strict).is_ok());
+        assert!(check_rootless_setgroups(false, Some(&with_groups), strict).is_ok());
+    }
 
-    let result = match cli.command {
-        Commands::Create {
-            ref id,
-            terminal,
-            stdin,
-            stdout,
-            stderr,
-            namespace,
-            ref annotations,
-        } => do_create(
-            id,
-            bundle,
-            terminal,
-            stdin,
-            stdout,
-            stderr,
-            namespace,
-            annotations,
-        ),
-        Commands::Start { ref id } => do_start(id, bundle),
-        Commands::State { ref id } => do_state(id),
-        Commands::Kill { ref id, signal } => do_kill(id, signal),
-        Commands::Delete { ref id, .. } => do_delete(id),
-        Commands::Exec {
-            ref id,
-            ref exec_id,
-        } => do_exec(id, exec_id),
-    };
+    #[test]
+    #[serial]
+    fn test_rootless_strict_setgroups_fails_start() {
+        with_test_root(|_| {
+            let bundle = TempDir::new().unwrap();
+            let config = serde_json::json!({
+                "process": {
+                    "args": ["/bin/true"],
+                    "cwd": "/tmp",
+                    "user": {"uid": 1000, "gid": 1000, "additionalGids": [44]}
+                }
+            });
+            fs::write(
+                bundle.path().join("config.json"),
+                serde_json::to_string(&config).unwrap(),
+            )
+            .unwrap();
+            do_create(
+                "rootless-groups",
+                bundle.path(),
+                false,
+                None,
+                None,
+                None,
+                Some("default".into()),
+                &[],
+                false,
+            )
+            .unwrap();
+
+            std::env::set_var("REAPER_ROOTLESS", "1");
+            std::env::remove_var("REAPER_SETGROUPS_POLICY");
+            let result = do_start("rootless-groups", bundle.path(), None);
+            std::env::remove_var("REAPER_ROOTLESS");
 
-    if let Err(ref e) = result {
-        tracing::error!("Command failed: {:?}", e);
-    } else {
-        info!("Command completed successfully");
+            let err = result.unwrap_err();
+            assert!(err.to_string().contains("rootless"), "{err:#}");
+            let state = load_state("rootless-groups").unwrap();
+            assert_eq!(state.status, STATUS_STOPPED);
+            assert_eq!(state.failure_origin, Some(FailureOrigin::Runtime));
+        });
     }
 
-    result
-}
+    // --- rlimit tests ---
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use serial_test::serial;
-    use tempfile::TempDir;
+    fn rlimit(kind: &str, soft: u64, hard: u64) -> OciRlimit {
+        OciRlimit {
+            kind: kind.to_string(),
+            soft,
+            hard,
+        }
+    }
 
-    fn setup_test_root() -> TempDir {
-        tempfile::tempdir().expect("Failed to create temp dir")
+    #[test]
+    fn test_parse_rlimit_value() {
+        assert_eq!(parse_rlimit_value("65536"), Some((65536, 65536)));
+        assert_eq!(parse_rlimit_value("1024:4096"), Some((1024, 4096)));
+        assert_eq!(parse_rlimit_value("4096:1024"), None);
+        assert_eq!(parse_rlimit_value("lots"), None);
+        assert_eq!(parse_rlimit_value(""), None);
     }
 
-    fn with_test_root<F>(f: F)
-    where
-        F: FnOnce(String),
-    {
-        let temp = setup_test_root();
-        let root = temp.path().to_string_lossy().to_string();
-        std::env::set_var("REAPER_RUNTIME_ROOT", &root);
-        f(root);
-        std::env::remove_var("REAPER_RUNTIME_ROOT");
+    #[test]
+    fn test_merge_rlimits_config_wins() {
+        let defaults = vec![
+            rlimit("RLIMIT_NOFILE", 65536, 65536),
+            rlimit("RLIMIT_NPROC", 4096, 4096),
+        ];
+        let config = vec![
+            rlimit("RLIMIT_NOFILE", 1024, 2048),
+            rlimit("RLIMIT_CORE", 0, 0),
+        ];
+        assert_eq!(
+            merge_rlimits(defaults, &config),
+            vec![
+                rlimit("RLIMIT_NPROC", 4096, 4096),
+                rlimit("RLIMIT_NOFILE", 1024, 2048),
+                rlimit("RLIMIT_CORE", 0, 0),
+            ]
+        );
     }
 
     #[test]
-    fn test_parse_config_without_user() {
-        let bundle_dir = TempDir::new().expect("Failed to create temp dir");
-        let config = serde_json::json!({
-            "process": {
-                "args": ["/bin/echo", "hello"],
-                "cwd": "/tmp"
-            }
-        });
+    #[serial]
+    fn test_default_rlimits_apply_when_config_omits_them() {
+        std::env::set_var("REAPER_DEFAULT_RLIMIT_NOFILE", "1024:65536");
+        std::env::set_var("REAPER_DEFAULT_RLIMIT_NPROC", "bogus");
+        assert_eq!(
+            default_rlimits(),
+            vec![rlimit("RLIMIT_NOFILE", 1024, 65536)]
+        );
+        let limits = workload_rlimits(&[]);
+        assert_eq!(
+            limits,
+            vec![(nix::sys::resource::Resource::RLIMIT_NOFILE, 1024, 65536)]
+        );
+        std::env::remove_var("REAPER_DEFAULT_RLIMIT_NOFILE");
+        std::env::remove_var("REAPER_DEFAULT_RLIMIT_NPROC");
+        assert!(workload_rlimits(&[]).is_empty());
+    }
+
+    #[test]
+    #[serial]
+    fn test_workload_rlimits_skip_unknown_types() {
+        std::env::remove_var("REAPER_DEFAULT_RLIMIT_NOFILE");
+        std::env::remove_var("REAPER_DEFAULT_RLIMIT_NPROC");
+        let limits = workload_rlimits(&[
+            rlimit("RLIMIT_BOGUS", 1, 1),
+            rlimit("RLIMIT_NOFILE", 1024, 4096),
+        ]);
+        assert_eq!(
+            limits,
+            vec![(nix::sys::resource::Resource::RLIMIT_NOFILE, 1024, 4096)]
+        );
+    }
+
+    #[test]
+    fn test_read_oci_config_rlimits() {
+        let bundle = TempDir::new().unwrap();
         fs::write(
-            bundle_dir.path().join("config.json"),
-            serde_json::to_string(&config).unwrap(),
+            bundle.path().join("config.json"),
+            serde_json::json!({
+                "process": {
+                    "args": ["/bin/sh", "-c", "ulimit -n"],
+                    "rlimits": [
+                        {"type": "RLIMIT_NOFILE", "soft": 1024, "hard": 4096},
+                        {"type": "RLIMIT_NPROC", "soft": 512, "hard": 512}
+                    ]
+                }
+            })
+            .to_string(),
         )
         .unwrap();
-
-        let parsed = read_oci_config(bundle_dir.path()).unwrap();
-        assert!(parsed.process.is_some());
-        let process = parsed.process.unwrap();
-        assert!(process.user.is_none());
-        assert_eq!(process.args.unwrap(), vec!["/bin/echo", "hello"]);
+        let cfg = read_oci_config(bundle.path()).unwrap();
+        assert_eq!(
+            cfg.process.unwrap().rlimits,
+            vec![
+                rlimit("RLIMIT_NOFILE", 1024, 4096),
+                rlimit("RLIMIT_NPROC", 512, 512),
+            ]
+        );
     }
 
     #[test]
-    fn test_parse_config_with_basic_user() {
-        let bundle_dir = TempDir::new().expect("Failed to create temp dir");
-        let config = serde_json::json!({
-            "process": {
-                "args": ["/bin/echo", "hello"],
-                "cwd": "/tmp",
-                "user": {
-                    "uid": 1000,
-                    "gid": 1000
-                }
-            }
-        });
+    fn test_read_oci_config_capabilities() {
+        let bundle = TempDir::new().unwrap();
         fs::write(
-            bundle_dir.path().join("config.json"),
-            serde_json::to_string(&config).unwrap(),
+            bundle.path().join("config.json"),
+            serde_json::json!({
+                "process": {
+                    "args": ["/bin/true"],
+                    "capabilities": {
+                        "bounding": ["CAP_NET_ADMIN", "CAP_CHOWN"],
+                        "effective": ["CAP_NET_ADMIN"],
+                        "permitted": ["CAP_NET_ADMIN", "CAP_CHOWN"]
+                    }
+                }
+            })
+            .to_string(),
         )
         .unwrap();
+        let caps = read_oci_config(bundle.path())
+            .unwrap()
+            .process
+            .unwrap()
+            .capabilities
+            .unwrap();
+        assert_eq!(caps.bounding, vec!["CAP_NET_ADMIN", "CAP_CHOWN"]);
+        assert_eq!(caps.effective, vec!["CAP_NET_ADMIN"]);
+        assert_eq!(caps.permitted, vec!["CAP_NET_ADMIN", "CAP_CHOWN"]);
+        // Missing sets are empty
+        assert!(caps.inheritable.is_empty());
+        assert!(caps.ambient.is_empty());
+
+        // No block at all leaves capabilities alone
+        let proc: OciProcess =
+            serde_json::from_value(serde_json::json!({"args": ["/bin/true"]})).unwrap();
+        assert!(proc.capabilities.is_none());
+    }
 
-        let parsed = read_oci_config(bundle_dir.path()).unwrap();
-        let process = parsed.process.unwrap();
-        let user = process.user.unwrap();
-        assert_eq!(user.uid, 1000);
-        assert_eq!(user.gid, 1000);
-        assert_eq!(user.additional_gids, Vec::<u32>::new());
-        assert_eq!(user.umask, None);
+    #[test]
+    fn test_oci_process_rlimits_deserialize() {
+        let proc: OciProcess = serde_json::from_value(serde_json::json!({
+            "args": ["/bin/true"],
+            "rlimits": [{"type": "RLIMIT_NOFILE", "soft": 1024, "hard": 4096}]
+        }))
+        .unwrap();
+        assert_eq!(proc.rlimits, vec![rlimit("RLIMIT_NOFILE", 1024, 4096)]);
     }
 
     #[test]
-    fn test_parse_config_with_full_user() {
-        let bundle_dir = TempDir::new().expect("Failed to create temp dir");
-        let config = serde_json::json!({
-            "process": {
-                "args": ["/bin/sh", "-c", "id"],
-                "cwd": "/tmp",
-                "user": {
-                    "uid": 1001,
-                    "gid": 1001,
-                    "additionalGids": [10, 20, 30],
-                    "umask": 22
-                }
+    fn test_oci_process_no_new_privileges_deserialize() {
+        let proc: OciProcess = serde_json::from_value(serde_json::json!({
+            "args": ["/bin/true"],
+            "noNewPrivileges": true
+        }))
+        .unwrap();
+        assert!(proc.no_new_privileges);
+        let proc: OciProcess =
+            serde_json::from_value(serde_json::json!({"args": ["/bin/true"]})).unwrap();
+        assert!(!proc.no_new_privileges);
+    }
+
+    // --- resource usage tests ---
+
+    #[test]
+    fn test_resource_usage_from_rusage() {
+        let mut ru: nix::libc::rusage = unsafe { std::mem::zeroed() };
+        ru.ru_maxrss = 20480;
+        ru.ru_utime.tv_sec = 2;
+        ru.ru_utime.tv_usec = 500_000;
+        ru.ru_stime.tv_sec = 0;
+        ru.ru_stime.tv_usec = 1_250;
+
+        assert_eq!(
+            resource_usage_from_rusage(&ru),
+            ResourceUsage {
+                max_rss_kib: 20480,
+                user_cpu_us: 2_500_000,
+                system_cpu_us: 1_250,
             }
+        );
+    }
+
+    #[test]
+    fn test_exit_code_from_status_sighup_depends_on_terminal() {
+        use std::os::unix::process::ExitStatusExt;
+        let hup = std::process::ExitStatus::from_raw(nix::libc::SIGHUP);
+        assert_eq!(exit_code_from_status(hup, true), 0);
+        assert_eq!(exit_code_from_status(hup, false), 129);
+
+        let term = std::process::ExitStatus::from_raw(nix::libc::SIGTERM);
+        assert_eq!(exit_code_from_status(term, true), 143);
+        assert_eq!(exit_code_from_status(term, false), 143);
+
+        let exited = std::process::ExitStatus::from_raw(3 << 8);
+        assert_eq!(exit_code_from_status(exited, true), 3);
+    }
+
+    #[test]
+    fn test_wait_with_usage_reports_exit_and_usage() {
+        let child = Command::new("/bin/sh")
+            .args([
+                "-c",
+                "i=0; while [ $i -lt 2000 ]; do i=$((i+1)); done; exit 7",
+            ])
+            .spawn()
+            .unwrap();
+        let (status, usage) = wait_with_usage(child).unwrap();
+        assert_eq!(exit_code_from_status(status, false), 7);
+        assert!(usage.max_rss_kib > 0, "usage: {:?}", usage);
+    }
+
+    #[test]
+    #[serial]
+    fn test_mark_workload_exit_records_usage() {
+        with_test_root(|_| {
+            let bundle = TempDir::new().unwrap();
+            do_create(
+                "usage-test",
+                bundle.path(),
+                false,
+                None,
+                None,
+                None,
+                None,
+                &[],
+                false,
+            )
+            .unwrap();
+            let usage = ResourceUsage {
+                max_rss_kib: 1024,
+                user_cpu_us: 10,
+                system_cpu_us: 20,
+            };
+            mark_workload_exit("usage-test", 0, None, Some(usage), false);
+            let state = load_state("usage-test").unwrap();
+            assert_eq!(state.resource_usage, Some(usage));
+            assert_eq!(state.exit_code, Some(0));
         });
-        fs::write(
-            bundle_dir.path().join("config.json"),
-            serde_json::to_string(&config).unwrap(),
-        )
-        .unwrap();
+    }
 
-        let parsed = read_oci_config(bundle_dir.path()).unwrap();
-        let process = parsed.process.unwrap();
-        let user = process.user.unwrap();
-        assert_eq!(user.uid, 1001);
-        assert_eq!(user.gid, 1001);
-        assert_eq!(user.additional_gids, vec![10, 20, 30]);
-        assert_eq!(user.umask, Some(22));
+    #[test]
+    #[serial]
+    fn test_create_duplicate_idempotent_returns_existing_state() {
+        with_test_root(|_| {
+            let bundle = TempDir::new().unwrap();
+            let mut existing = ContainerState::new("dup".into(), bundle.path().to_path_buf());
+            existing.status = STATUS_RUNNING.into();
+            existing.pid = Some(4242);
+            save_state(&existing).unwrap();
+
+            std::env::remove_var("REAPER_CREATE_IDEMPOTENT");
+            do_create(
+                "dup",
+                bundle.path(),
+                true,
+                None,
+                None,
+                None,
+                None,
+                &[],
+                false,
+            )
+            .unwrap();
+            let state = load_state("dup").unwrap();
+            assert_eq!(state.status, STATUS_RUNNING);
+            assert_eq!(state.pid, Some(4242));
+            assert!(!state.terminal);
+
+            // Same id with another bundle is a different container
+            let other = TempDir::new().unwrap();
+            let err = existing_container("dup", other.path(), true).unwrap_err();
+            assert!(err.to_string().contains("already exists"), "{}", err);
+        });
     }
 
     #[test]
-    fn test_parse_config_with_root_user() {
-        let bundle_dir = TempDir::new().expect("Failed to create temp dir");
-        let config = serde_json::json!({
-            "process": {
-                "args": ["/bin/cat", "/etc/shadow"],
-                "cwd": "/",
-                "user": {
-                    "uid": 0,
-                    "gid": 0
-                }
-            }
+    #[serial]
+    fn test_create_duplicate_rejected_when_not_idempotent() {
+        with_test_root(|_| {
+            let bundle = TempDir::new().unwrap();
+            let mut existing = ContainerState::new("dup".into(), bundle.path().to_path_buf());
+            existing.status = STATUS_RUNNING.into();
+            existing.pid = Some(4242);
+            save_state(&existing).unwrap();
+
+            std::env::set_var("REAPER_CREATE_IDEMPOTENT", "0");
+            let result = do_create(
+                "dup",
+                bundle.path(),
+                false,
+                None,
+                None,
+                None,
+                None,
+                &[],
+                false,
+            );
+            std::env::remove_var("REAPER_CREATE_IDEMPOTENT");
+            let err = result.unwrap_err();
+            assert!(err.to_string().contains("already exists"), "{}", err);
+            assert_eq!(load_state("dup").unwrap().pid, Some(4242));
+
+            assert!(existing_container("fresh", bundle.path(), false)
+                .unwrap()
+                .is_none());
         });
-        fs::write(
-            bundle_dir.path().join("config.json"),
-            serde_json::to_string(&config).unwrap(),
-        )
-        .unwrap();
+    }
 
-        let parsed = read_oci_config(bundle_dir.path()).unwrap();
-        let process = parsed.process.unwrap();
-        let user = process.user.unwrap();
-        assert_eq!(user.uid, 0);
-        assert_eq!(user.gid, 0);
+    fn read_audit_lines(path: &Path) -> Vec<serde_json::Value> {
+        fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect()
     }
 
     #[test]
-    fn test_parse_config_missing_user_fields() {
-        // Test that additional_gids defaults to empty vec if not provided
-        let bundle_dir = TempDir::new().expect("Failed to create temp dir");
-        let config = serde_json::json!({
-            "process": {
-                "args": ["/bin/true"],
-                "user": {
-                    "uid": 500,
-                    "gid": 500
-                    // No additionalGids or umask
-                }
+    #[serial]
+    fn test_audit_record_for_start_and_exec() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("audit.log");
+        std::env::set_var("REAPER_EXEC_AUDIT_LOG", &path);
+        let mut log = audit::open_log().unwrap();
+        std::env::remove_var("REAPER_EXEC_AUDIT_LOG");
+
+        let user = OciUser {
+            uid: 1000,
+            gid: 2000,
+            additional_gids: vec![],
+            umask: None,
+        };
+        let start = audit_record(
+            "web",
+            None,
+            Some("prod"),
+            "/bin/sh",
+            vec!["sh", "-c", "echo $TOKEN"],
+            Some(&user),
+            Some("/srv"),
+        );
+        audit::write_record(&mut log, &start);
+        let exec = audit_record(
+            "web",
+            Some("exec-1"),
+            None,
+            "ls",
+            vec!["ls", "-l"],
+            None,
+            Some("/"),
+        );
+        audit::write_record(&mut log, &exec);
+
+        let lines = read_audit_lines(&path);
+        assert_eq!(lines.len(), 2);
+        let start = &lines[0];
+        assert_eq!(start["event"], "start");
+        assert_eq!(start["container_id"], "web");
+        assert!(start.get("exec_id").is_none());
+        assert_eq!(start["namespace"], "prod");
+        assert_eq!(start["program"], "/bin/sh");
+        assert_eq!(
+            start["argv"],
+            serde_json::json!(["sh", "-c", "echo $TOKEN"])
+        );
+        assert_eq!(start["uid"], 1000);
+        assert_eq!(start["gid"], 2000);
+        assert_eq!(start["cwd"], "/srv");
+        assert!(start["ts"].as_u64().unwrap() > 0);
+
+        // No OCI user: the runtime's own ids are recorded
+        let exec = &lines[1];
+        assert_eq!(exec["event"], "exec");
+        assert_eq!(exec["exec_id"], "exec-1");
+        assert!(exec["namespace"].is_null());
+        assert_eq!(exec["argv"], serde_json::json!(["ls", "-l"]));
+        assert_eq!(exec["uid"], nix::unistd::getuid().as_raw());
+        assert_eq!(exec["gid"], nix::unistd::getgid().as_raw());
+    }
+
+    #[test]
+    #[serial]
+    fn test_audit_log_disabled_when_unset() {
+        std::env::remove_var("REAPER_EXEC_AUDIT_LOG");
+        assert!(audit::open_log().is_none());
+        std::env::set_var("REAPER_EXEC_AUDIT_LOG", "/nonexistent/dir/audit.log");
+        assert!(audit::open_log().is_none());
+        std::env::remove_var("REAPER_EXEC_AUDIT_LOG");
+    }
+
+    fn config_from_json(json: serde_json::Value) -> OciConfig {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_reconcile_config_per_field_source() {
+        let created = config_snapshot(&config_from_json(serde_json::json!({
+            "process": {"args": ["/bin/app", "--old"], "cwd": "/srv"},
+            "root": {"path": "rootfs"},
+            "mounts": [{"destination": "/proc"}, {"destination": "/data"}],
+            "annotations": {
+                "reaper.runtime/dns-mode": "host",
+                "io.kubernetes.cri.container-type": "container",
+                "io.kubernetes.cri.container-name": "app"
             }
+        })));
+        let current = config_snapshot(&config_from_json(serde_json::json!({
+            "process": {"args": ["/bin/app", "--new"], "cwd": "/work"},
+            "root": {"path": "other-rootfs"},
+            "mounts": [{"destination": "/proc"}, {"destination": "/injected"}],
+            "annotations": {
+                "reaper.runtime/dns-mode": "kubernetes",
+                "io.kubernetes.cri.container-type": "container",
+                "io.kubernetes.cri.container-name": "renamed"
+            }
+        })));
+
+        let changes = reconcile_config(&created, &current);
+        let sources: Vec<(&str, ConfigSource)> =
+            changes.iter().map(|c| (c.field, c.source)).collect();
+        assert_eq!(
+            sources,
+            vec![
+                ("args", ConfigSource::Start),
+                ("cwd", ConfigSource::Start),
+                ("mounts", ConfigSource::Start),
+                ("root", ConfigSource::Create),
+                ("annotations", ConfigSource::Create),
+            ]
+        );
+        assert_eq!(
+            changes[2].detail,
+            r#"added ["/injected"], removed ["/data"]"#
+        );
+        // Only Reaper and container-type annotations are tracked
+        assert_eq!(changes[4].detail, r#"changed ["reaper.runtime/dns-mode"]"#);
+    }
+
+    #[test]
+    fn test_reconcile_config_unchanged() {
+        let cfg = config_from_json(serde_json::json!({
+            "process": {"args": ["/bin/app"]},
+            "mounts": [{"destination": "/proc"}],
+            "annotations": {"io.kubernetes.cri.container-name": "app"}
+        }));
+        let created = config_snapshot(&cfg);
+        assert!(reconcile_config(&created, &config_snapshot(&cfg)).is_empty());
+
+        // Untracked annotations don't count as a change
+        let renamed = config_from_json(serde_json::json!({
+            "process": {"args": ["/bin/app"]},
+            "mounts": [{"destination": "/proc"}],
+            "annotations": {"io.kubernetes.cri.container-name": "renamed"}
+        }));
+        assert!(reconcile_config(&created, &config_snapshot(&renamed)).is_empty());
+    }
+
+    #[test]
+    #[serial]
+    fn test_create_records_config_snapshot() {
+        with_test_root(|_| {
+            let bundle = TempDir::new().unwrap();
+            fs::write(
+                bundle.path().join("config.json"),
+                serde_json::json!({
+                    "process": {"args": ["/bin/echo", "hi"], "cwd": "/tmp"},
+                    "mounts": [{"destination": "/proc"}]
+                })
+                .to_string(),
+            )
+            .unwrap();
+            do_create(
+                "snap",
+                bundle.path(),
+                false,
+                None,
+                None,
+                None,
+                None,
+                &[],
+                false,
+            )
+            .unwrap();
+            let snapshot = load_state("snap").unwrap().created_config.unwrap();
+            assert_eq!(snapshot.args, vec!["/bin/echo", "hi"]);
+            assert_eq!(snapshot.cwd.as_deref(), Some("/tmp"));
+            assert_eq!(snapshot.mounts, vec!["/proc"]);
         });
-        fs::write(
-            bundle_dir.path().join("config.json"),
-            serde_json::to_string(&config).unwrap(),
-        )
-        .unwrap();
-
-        let parsed = read_oci_config(bundle_dir.path()).unwrap();
-        let user = parsed.process.unwrap().user.unwrap();
-        assert!(user.additional_gids.is_empty());
-        assert!(user.umask.is_none());
     }
 
-    // --- read_oci_config edge cases ---
+    // --- failure_origin tests ---
 
-    #[test]
-    fn test_read_oci_config_missing_file() {
-        let bundle_dir = TempDir::new().unwrap();
-        // No config.json created
-        let result = read_oci_config(bundle_dir.path());
-        assert!(result.is_err());
+    fn create_for_origin_test(id: &str) -> TempDir {
+        let bundle = TempDir::new().unwrap();
+        do_create(id, bundle.path(), false, None, None, None, None, &[], false).unwrap();
+        assert_eq!(load_state(id).unwrap().failure_origin, None);
+        bundle
     }
 
     #[test]
-    fn test_read_oci_config_invalid_json() {
-        let bundle_dir = TempDir::new().unwrap();
-        fs::write(bundle_dir.path().join("config.json"), "not valid json").unwrap();
-        let result = read_oci_config(bundle_dir.path());
-        assert!(result.is_err());
+    #[serial]
+    fn test_mark_starting_persists_status() {
+        with_test_root(|root| {
+            let _bundle = create_for_origin_test("starting");
+            let mut state = load_state("starting").unwrap();
+            mark_starting(&mut state).unwrap();
+
+            assert_eq!(load_state("starting").unwrap().status, STATUS_STARTING);
+            let raw = fs::read_to_string(Path::new(&root).join("starting/state.json")).unwrap();
+            let json: serde_json::Value = serde_json::from_str(&raw).unwrap();
+            assert_eq!(json["status"], "starting");
+            assert!(json["pid"].is_null());
+        });
     }
 
     #[test]
-    fn test_read_oci_config_no_process() {
-        let bundle_dir = TempDir::new().unwrap();
-        let config = serde_json::json!({});
-        fs::write(
-            bundle_dir.path().join("config.json"),
-            serde_json::to_string(&config).unwrap(),
-        )
-        .unwrap();
-        let parsed = read_oci_config(bundle_dir.path()).unwrap();
-        assert!(parsed.process.is_none());
+    #[serial]
+    fn test_start_dependency_failure_stops_container() {
+        with_test_root(|_| {
+            let bundle = TempDir::new().unwrap();
+            let config = serde_json::json!({
+                "process": {"args": ["/bin/true"], "cwd": "/tmp"}
+            });
+            fs::write(
+                bundle.path().join("config.json"),
+                serde_json::to_string(&config).unwrap(),
+            )
+            .unwrap();
+            do_create(
+                "start-after-fail",
+                bundle.path(),
+                false,
+                None,
+                None,
+                None,
+                Some("default".into()),
+                &["after=missing-dep".to_string()],
+                false,
+            )
+            .unwrap();
+
+            std::env::set_var("REAPER_START_AFTER_TIMEOUT_SECS", "0");
+            let err = do_start("start-after-fail", bundle.path(), None).unwrap_err();
+            std::env::remove_var("REAPER_START_AFTER_TIMEOUT_SECS");
+            assert!(err.to_string().contains("missing-dep"), "{err:#}");
+
+            // Not left "starting": stopped as a runtime failure, with the reason
+            let state = load_state("start-after-fail").unwrap();
+            assert_eq!(state.status, STATUS_STOPPED);
+            assert_eq!(state.exit_code, Some(1));
+            assert_eq!(state.failure_origin, Some(FailureOrigin::Runtime));
+            assert!(state
+                .failure_message
+                .is_some_and(|m| m.contains("missing-dep")));
+        });
     }
 
     #[test]
-    fn test_read_oci_config_empty_args() {
-        let bundle_dir = TempDir::new().unwrap();
-        let config = serde_json::json!({
-            "process": {
-                "args": [],
-                "cwd": "/"
-            }
+    #[serial]
+    fn test_mark_runtime_failure_sets_runtime_origin() {
+        with_test_root(|_| {
+            let _bundle = create_for_origin_test("origin-setup-fail");
+            // Simulates e.g. the overlay/volume-mount fatal branches in do_start
+            mark_runtime_failure("origin-setup-fail");
+
+            let state = load_state("origin-setup-fail").unwrap();
+            assert_eq!(state.status, STATUS_STOPPED);
+            assert_eq!(state.exit_code, Some(1));
+            assert_eq!(state.failure_origin, Some(FailureOrigin::Runtime));
         });
-        fs::write(
-            bundle_dir.path().join("config.json"),
-            serde_json::to_string(&config).unwrap(),
-        )
-        .unwrap();
-        let parsed = read_oci_config(bundle_dir.path()).unwrap();
-        let process = parsed.process.unwrap();
-        assert!(process.args.unwrap().is_empty());
     }
 
     #[test]
-    fn test_read_oci_config_with_env() {
-        let bundle_dir = TempDir::new().unwrap();
-        let config = serde_json::json!({
-            "process": {
-                "args": ["/bin/sh"],
-                "env": ["PATH=/usr/bin", "HOME=/root"]
-            }
+    #[serial]
+    fn test_mark_workload_exit_sets_workload_origin() {
+        with_test_root(|_| {
+            let _bundle = create_for_origin_test("origin-exit-1");
+            // A workload that genuinely exits 1 is indistinguishable by code alone
+            mark_workload_exit("origin-exit-1", 1, None, None, false);
+
+            let state = load_state("origin-exit-1").unwrap();
+            assert_eq!(state.status, STATUS_STOPPED);
+            assert_eq!(state.exit_code, Some(1));
+            assert_eq!(state.failure_origin, Some(FailureOrigin::Workload));
         });
-        fs::write(
-            bundle_dir.path().join("config.json"),
-            serde_json::to_string(&config).unwrap(),
-        )
-        .unwrap();
-        let parsed = read_oci_config(bundle_dir.path()).unwrap();
-        let env = parsed.process.unwrap().env.unwrap();
-        assert_eq!(env.len(), 2);
-        assert_eq!(env[0], "PATH=/usr/bin");
     }
 
     #[test]
-    fn test_parse_config_with_mounts() {
-        let bundle_dir = TempDir::new().expect("Failed to create temp dir");
-        let config = serde_json::json!({
-            "process": {
-                "args": ["/bin/sh", "-c", "ls /scripts"],
-                "cwd": "/"
-            },
-            "mounts": [
-                {
-                    "destination": "/proc",
-                    "type": "proc",
-                    "source": "proc",
-                    "options": ["nosuid", "noexec", "nodev"]
-                },
-                {
-                    "destination": "/scripts",
-                    "type": "bind",
-                    "source": "/var/lib/kubelet/pods/abc/volumes/kubernetes.io~configmap/scripts",
-                    "options": ["rbind", "ro"]
-                },
-                {
-                    "destination": "/data",
-                    "source": "/host/data",
-                    "options": ["rbind"]
-                }
-            ]
-        });
-        fs::write(
-            bundle_dir.path().join("config.json"),
-            serde_json::to_string(&config).unwrap(),
-        )
-        .unwrap();
+    fn test_oom_killed() {
+        let sigkill = Some(nix::libc::SIGKILL);
+        // The cgroup's oom_kill count decides when there is one
+        assert!(oom_killed(Some(0), Some(1), false, None));
+        assert!(oom_killed(Some(2), Some(3), true, sigkill));
+        assert!(!oom_killed(Some(2), Some(2), true, sigkill));
+        // Without it, SIGKILL of a memory-limited workload
+        assert!(oom_killed(None, None, true, sigkill));
+        assert!(oom_killed(Some(0), None, true, sigkill));
+        assert!(!oom_killed(None, None, false, sigkill));
+        assert!(!oom_killed(None, None, true, Some(nix::libc::SIGTERM)));
+        assert!(!oom_killed(None, None, true, None));
+    }
 
-        let parsed = read_oci_config(bundle_dir.path()).unwrap();
-        assert_eq!(parsed.mounts.len(), 3);
+    #[test]
+    #[serial]
+    fn test_mark_workload_exit_records_oom() {
+        with_test_root(|root| {
+            let _bundle = create_for_origin_test("oom-exit");
+            mark_workload_exit("oom-exit", 137, Some(nix::libc::SIGKILL), None, true);
+            assert!(load_state("oom-exit").unwrap().oom);
+            let raw = fs::read_to_string(Path::new(&root).join("oom-exit/state.json")).unwrap();
+            let json: serde_json::Value = serde_json::from_str(&raw).unwrap();
+            assert_eq!(json["oom"], true);
+
+            mark_workload_exit("oom-exit", 0, None, None, false);
+            let raw = fs::read_to_string(Path::new(&root).join("oom-exit/state.json")).unwrap();
+            let json: serde_json::Value = serde_json::from_str(&raw).unwrap();
+            assert!(json.get("oom").is_none());
+        });
+    }
 
-        assert_eq!(parsed.mounts[0].destination, "/proc");
-        assert_eq!(parsed.mounts[0].mount_type, Some("proc".to_string()));
+    #[test]
+    #[serial]
+    fn test_failure_origin_serialized_in_state_json() {
+        with_test_root(|root| {
+            let _bundle = create_for_origin_test("origin-json");
+            let exit_status = Command::new("/bin/sh")
+                .args(["-c", "exit 3"])
+                .status()
+                .unwrap();
+            mark_workload_exit(
+                "origin-json",
+                exit_code_from_status(exit_status, false),
+                exit_status.signal(),
+                None,
+                false,
+            );
 
-        assert_eq!(parsed.mounts[1].destination, "/scripts");
-        assert_eq!(parsed.mounts[1].mount_type, Some("bind".to_string()));
-        assert_eq!(
-            parsed.mounts[1].source,
-            Some("/var/lib/kubelet/pods/abc/volumes/kubernetes.io~configmap/scripts".to_string())
-        );
-        assert_eq!(parsed.mounts[1].options, vec!["rbind", "ro"]);
+            let raw = fs::read_to_string(Path::new(&root).join("origin-json/state.json")).unwrap();
+            let json: serde_json::Value = serde_json::from_str(&raw).unwrap();
+            assert_eq!(json["exit_code"], 3);
+            assert_eq!(json["failure_origin"], "workload");
+        });
+    }
 
-        assert_eq!(parsed.mounts[2].destination, "/data");
-        assert!(parsed.mounts[2].mount_type.is_none());
-        assert_eq!(parsed.mounts[2].options, vec!["rbind"]);
+    fn read_result(root: &str, id: &str) -> serde_json::Value {
+        let raw = fs::read_to_string(Path::new(root).join(id).join("result.json")).unwrap();
+        serde_json::from_str(&raw).unwrap()
     }
 
     #[test]
-    fn test_parse_config_without_mounts() {
-        let bundle_dir = TempDir::new().expect("Failed to create temp dir");
-        let config = serde_json::json!({
-            "process": {
-                "args": ["/bin/echo", "hello"]
-            }
+    #[serial]
+    fn test_result_json_written_on_workload_exit() {
+        with_test_root(|root| {
+            let _bundle = create_for_origin_test("result-exit");
+            assert!(!Path::new(&root).join("result-exit/result.json").exists());
+            let mut state = load_state("result-exit").unwrap();
+            state.status = STATUS_RUNNING.into();
+            state.started_at = Some(state::unix_now());
+            save_state(&state).unwrap();
+
+            let exit_status = Command::new("/bin/sh")
+                .args(["-c", "kill -KILL $$"])
+                .status()
+                .unwrap();
+            mark_workload_exit(
+                "result-exit",
+                exit_code_from_status(exit_status, false),
+                exit_status.signal(),
+                None,
+                false,
+            );
+
+            let result = read_result(&root, "result-exit");
+            assert_eq!(result["exit_code"], 137);
+            assert_eq!(result["exit_signal"], 9);
+            assert_eq!(result["failure_origin"], "workload");
+            assert!(result["message"].is_null());
+            let started = result["started_at"].as_u64().unwrap();
+            assert!(result["finished_at"].as_u64().unwrap() >= started);
         });
-        fs::write(
-            bundle_dir.path().join("config.json"),
-            serde_json::to_string(&config).unwrap(),
-        )
-        .unwrap();
+    }
 
-        let parsed = read_oci_config(bundle_dir.path()).unwrap();
-        assert!(parsed.mounts.is_empty());
+    #[test]
+    #[serial]
+    fn test_result_json_written_on_runtime_failure() {
+        with_test_root(|root| {
+            let _bundle = create_for_origin_test("result-fail");
+            mark_spawn_failure("result-fail", "no such program".into());
+
+            let result = read_result(&root, "result-fail");
+            assert_eq!(result["exit_code"], 1);
+            assert!(result["exit_signal"].is_null());
+            assert!(result["started_at"].is_null());
+            assert!(result["finished_at"].as_u64().is_some());
+            assert_eq!(result["failure_origin"], "runtime");
+            assert_eq!(result["message"], "no such program");
+        });
     }
 
-    // --- parse_program_and_args tests ---
+    // --- spawn failure diagnostics ---
+
+    fn write_script(dir: &Path, name: &str, contents: &str, mode: u32) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+        let path = dir.join(name);
+        fs::write(&path, contents).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+        path
+    }
 
     #[test]
-    fn test_parse_program_and_args_valid() {
-        let cfg = OciConfig {
-            process: Some(OciProcess {
-                args: Some(vec!["/bin/echo".into(), "hello".into(), "world".into()]),
-                env: None,
-                cwd: None,
-                user: None,
-            }),
-            mounts: vec![],
-        };
-        let (program, argv) = parse_program_and_args(&cfg).unwrap();
-        assert_eq!(program, PathBuf::from("/bin/echo"));
-        assert_eq!(argv, vec!["hello", "world"]);
+    fn test_shebang_interpreter() {
+        let dir = TempDir::new().unwrap();
+        let script = write_script(dir.path(), "s", "#! /usr/bin/env python3 -u\nx\n", 0o755);
+        assert_eq!(
+            shebang_interpreter(&script),
+            Some(PathBuf::from("/usr/bin/env"))
+        );
+        let plain = write_script(dir.path(), "p", "echo hi\n", 0o755);
+        assert_eq!(shebang_interpreter(&plain), None);
+        assert_eq!(shebang_interpreter(&dir.path().join("missing")), None);
     }
 
     #[test]
-    fn test_parse_program_and_args_single_element() {
-        let cfg = OciConfig {
-            process: Some(OciProcess {
-                args: Some(vec!["/bin/true".into()]),
-                env: None,
-                cwd: None,
-                user: None,
-            }),
-            mounts: vec![],
-        };
-        let (program, argv) = parse_program_and_args(&cfg).unwrap();
-        assert_eq!(program, PathBuf::from("/bin/true"));
-        assert!(argv.is_empty());
+    fn test_spawn_failure_names_missing_interpreter() {
+        let dir = TempDir::new().unwrap();
+        let script = write_script(
+            dir.path(),
+            "script",
+            "#!/nonexistent/reaper-interp\necho hi\n",
+            0o755,
+        );
+        let err = Command::new(&script).spawn().unwrap_err();
+
+        let msg = spawn_failure_message(&script, None, &err);
+        assert!(
+            msg.contains("shebang interpreter /nonexistent/reaper-interp does not exist"),
+            "{}",
+            msg
+        );
+
+        // Relative paths are resolved against the process cwd
+        let msg = spawn_failure_message(Path::new("./script"), dir.path().to_str(), &err);
+        assert!(msg.contains("/nonexistent/reaper-interp"), "{}", msg);
     }
 
     #[test]
-    fn test_parse_program_and_args_empty_args() {
-        let cfg = OciConfig {
-            process: Some(OciProcess {
-                args: Some(vec![]),
-                env: None,
-                cwd: None,
-                user: None,
-            }),
-            mounts: vec![],
-        };
-        let result = parse_program_and_args(&cfg);
-        assert!(result.is_err());
-        assert!(result
-            .unwrap_err()
-            .to_string()
-            .contains("at least one element"));
+    fn test_spawn_failure_non_executable_script() {
+        let dir = TempDir::new().unwrap();
+        let script = write_script(dir.path(), "script", "#!/bin/sh\necho hi\n", 0o644);
+        let err = std::io::Error::from_raw_os_error(nix::libc::EACCES);
+
+        let msg = spawn_failure_message(&script, None, &err);
+        assert!(msg.contains("not executable (mode 0644)"), "{}", msg);
+        assert!(msg.contains("[\"/bin/sh\","), "{}", msg);
     }
 
     #[test]
-    fn test_parse_program_and_args_none_args() {
-        let cfg = OciConfig {
-            process: Some(OciProcess {
-                args: None,
-                env: None,
-                cwd: None,
-                user: None,
-            }),
-            mounts: vec![],
-        };
-        let result = parse_program_and_args(&cfg);
-        assert!(result.is_err());
+    fn test_spawn_failure_without_diagnosis_keeps_error() {
+        let err = std::io::Error::from_raw_os_error(nix::libc::ENOENT);
+        assert_eq!(
+            spawn_failure_message(Path::new("/nonexistent/bin"), None, &err),
+            format!("failed to exec /nonexistent/bin: {}", err)
+        );
+        // Bare names go through PATH and aren't inspected
+        let msg = spawn_failure_message(Path::new("script"), Some("/"), &err);
+        assert_eq!(msg, format!("failed to exec script: {}", err));
     }
 
     #[test]
-    fn test_parse_program_and_args_no_process() {
-        let cfg = OciConfig {
-            process: None,
-            mounts: vec![],
-        };
-        let result = parse_program_and_args(&cfg);
-        assert!(result.is_err());
-        assert!(result
-            .unwrap_err()
-            .to_string()
-            .contains("missing 'process'"));
+    fn test_exec_args_size_and_check() {
+        let ptr = std::mem::size_of::<usize>();
+        let argv = vec!["-c".to_string(), "echo hi".to_string()];
+        let env = vec!["PATH=/bin".to_string()];
+        // "/bin/sh\0" + "-c\0" + "echo hi\0" + "PATH=/bin\0", 6 pointers
+        assert_eq!(
+            exec_args_size(Path::new("/bin/sh"), &argv, &env),
+            8 + 3 + 8 + 10 + 6 * ptr
+        );
+
+        let huge = vec![format!("BLOB={}", "x".repeat(64 * 1024)); 4];
+        let size = exec_args_size(Path::new("/bin/sh"), &argv, &huge);
+        assert!(size > 4 * 64 * 1024);
+        let msg = check_exec_args_size(size, Some(128 * 1024)).unwrap_err();
+        assert!(msg.contains(&format!("{} bytes", size)), "{}", msg);
+        assert!(msg.contains("131072 bytes (ARG_MAX)"), "{}", msg);
+        assert!(check_exec_args_size(size, Some(size)).is_ok());
+        assert!(check_exec_args_size(size, None).is_ok());
     }
 
     #[test]
-    fn test_parse_program_and_args_relative_path() {
-        let cfg = OciConfig {
-            process: Some(OciProcess {
-                args: Some(vec!["my-binary".into(), "--flag".into()]),
-                env: None,
-                cwd: None,
-                user: None,
-            }),
-            mounts: vec![],
-        };
-        let (program, argv) = parse_program_and_args(&cfg).unwrap();
-        assert_eq!(program, PathBuf::from("my-binary"));
-        assert_eq!(argv, vec!["--flag"]);
+    #[serial]
+    fn test_workload_env_overlays_runtime_env() {
+        std::env::set_var("REAPER_TEST_ENV_OVERLAY", "runtime");
+        let env = workload_env(Some(&["REAPER_TEST_ENV_OVERLAY=workload".to_string()]));
+        let matching: Vec<&String> = env
+            .iter()
+            .filter(|kv| kv.starts_with("REAPER_TEST_ENV_OVERLAY="))
+            .collect();
+        assert_eq!(matching, ["REAPER_TEST_ENV_OVERLAY=workload"]);
+        std::env::remove_var("REAPER_TEST_ENV_OVERLAY");
+    }
+
+    #[test]
+    #[serial]
+    fn test_mark_spawn_failure_records_message() {
+        with_test_root(|_| {
+            let _bundle = create_for_origin_test("spawn-fail");
+            mark_spawn_failure("spawn-fail", "failed to exec ./x: denied".into());
+
+            let state = load_state("spawn-fail").unwrap();
+            assert_eq!(state.exit_code, Some(1));
+            assert_eq!(state.failure_origin, Some(FailureOrigin::Runtime));
+            assert_eq!(
+                state.failure_message.as_deref(),
+                Some("failed to exec ./x: denied")
+            );
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn test_run_command_rejects_unsafe_ids() {
+        with_test_root(|root| {
+            let bundle = TempDir::new().unwrap();
+            for id in ["../escape", "a/b", "..", &"a".repeat(300)] {
+                let create = Commands::Create {
+                    id: id.to_string(),
+                    terminal: false,
+                    stdin: None,
+                    stdout: None,
+                    stderr: None,
+                    namespace: None,
+                    annotations: Vec::new(),
+                    sandbox: true,
+                };
+                let err = run_command(&create, bundle.path(), None).unwrap_err();
+                assert!(
+                    format!("{:#}", err).contains("invalid container id"),
+                    "{:#}",
+                    err
+                );
+                let kill = Commands::Kill {
+                    id: id.to_string(),
+                    signal: None,
+                    timeout: None,
+                };
+                assert!(run_command(&kill, bundle.path(), None).is_err());
+            }
+            let exec = Commands::Exec {
+                id: "ok".into(),
+                exec_id: "../x".into(),
+            };
+            let err = run_command(&exec, bundle.path(), None).unwrap_err();
+            assert!(
+                format!("{:#}", err).contains("invalid exec id"),
+                "{:#}",
+                err
+            );
+            assert_eq!(fs::read_dir(&root).unwrap().count(), 0);
+            assert!(!Path::new(&root).parent().unwrap().join("escape").exists());
+        });
     }
 
     // --- do_create tests ---
@@ -2103,6 +6967,7 @@ mod tests {
                 None,
                 None,
                 &[],
+                false,
             )
             .unwrap();
 
@@ -2116,6 +6981,37 @@ mod tests {
         });
     }
 
+    #[test]
+    #[serial]
+    fn test_list_containers() {
+        with_test_root(|root| {
+            let bundle = TempDir::new().unwrap();
+            for id in ["list-b", "list-a"] {
+                do_create(id, bundle.path(), false, None, None, None, None, &[], false).unwrap();
+            }
+            // Not containers: a directory without state, a lock file, and
+            // a corrupt state file
+            std::fs::create_dir_all(Path::new(&root).join("overlay")).unwrap();
+            std::fs::write(Path::new(&root).join("overlay.lock"), "").unwrap();
+            std::fs::create_dir_all(Path::new(&root).join("broken")).unwrap();
+            std::fs::write(Path::new(&root).join("broken/state.json"), "{not json").unwrap();
+
+            let states = list_containers();
+            let ids: Vec<&str> = states.iter().map(|s| s.id.as_str()).collect();
+            assert_eq!(ids, ["list-a", "list-b"]);
+
+            let table = render_container_table(&states);
+            let lines: Vec<&str> = table.lines().collect();
+            assert_eq!(lines.len(), 3, "{}", table);
+            assert!(lines[0].starts_with("ID") && lines[0].ends_with("BUNDLE"));
+            for (line, id) in lines[1..].iter().zip(["list-a", "list-b"]) {
+                assert!(line.starts_with(id), "{}", table);
+                assert!(line.contains("created"), "{}", table);
+                assert!(line.ends_with(&bundle.path().display().to_string()));
+            }
+        });
+    }
+
     #[test]
     #[serial]
     fn test_do_create_with_terminal() {
@@ -2130,6 +7026,7 @@ mod tests {
                 None,
                 None,
                 &[],
+                false,
             )
             .unwrap();
 
@@ -2152,6 +7049,7 @@ mod tests {
                 Some("/path/stderr".into()),
                 None,
                 &[],
+                false,
             )
             .unwrap();
 
@@ -2162,6 +7060,172 @@ mod tests {
         });
     }
 
+    #[test]
+    #[serial]
+    fn test_effective_cwd_defaults_when_omitted() {
+        std::env::remove_var("REAPER_DEFAULT_CWD");
+        assert_eq!(effective_cwd(None), "/");
+        assert_eq!(effective_cwd(Some("")), "/");
+
+        std::env::set_var("REAPER_DEFAULT_CWD", "/tmp");
+        assert_eq!(effective_cwd(None), "/tmp");
+        std::env::set_var("REAPER_DEFAULT_CWD", "");
+        assert_eq!(effective_cwd(None), "/");
+        std::env::remove_var("REAPER_DEFAULT_CWD");
+    }
+
+    #[test]
+    #[serial]
+    fn test_effective_cwd_explicit_wins() {
+        std::env::set_var("REAPER_DEFAULT_CWD", "/tmp");
+        assert_eq!(effective_cwd(Some("/work")), "/work");
+        std::env::remove_var("REAPER_DEFAULT_CWD");
+        assert_eq!(effective_cwd(Some("/work")), "/work");
+    }
+
+    #[test]
+    fn test_resolve_image_rootfs() {
+        let bundle = TempDir::new().unwrap();
+        fs::create_dir(bundle.path().join("rootfs")).unwrap();
+        let expected = fs::canonicalize(bundle.path().join("rootfs")).unwrap();
+
+        let relative = OciConfig {
+            root: Some(OciRoot {
+                path: "rootfs".into(),
+            }),
+            ..Default::default()
+        };
+        assert_eq!(
+            resolve_image_rootfs(bundle.path(), &relative).unwrap(),
+            Some(expected.clone())
+        );
+
+        let absolute = OciConfig {
+            root: Some(OciRoot {
+                path: expected.to_string_lossy().into_owned(),
+            }),
+            ..Default::default()
+        };
+        assert_eq!(
+            resolve_image_rootfs(bundle.path(), &absolute).unwrap(),
+            Some(expected)
+        );
+
+        let no_root = OciConfig::default();
+        assert_eq!(resolve_image_rootfs(bundle.path(), &no_root).unwrap(), None);
+
+        let missing = OciConfig {
+            root: Some(OciRoot {
+                path: "nope".into(),
+            }),
+            ..Default::default()
+        };
+        assert!(resolve_image_rootfs(bundle.path(), &missing).is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_image_rootfs_setting_precedence() {
+        std::env::remove_var("REAPER_IMAGE_ROOTFS");
+        assert_eq!(image_rootfs_setting(None), None);
+        assert_eq!(image_rootfs_setting(Some(true)), Some(true));
+
+        std::env::set_var("REAPER_IMAGE_ROOTFS", "true");
+        assert_eq!(image_rootfs_setting(None), Some(true));
+        assert_eq!(image_rootfs_setting(Some(false)), Some(false));
+
+        std::env::set_var("REAPER_IMAGE_ROOTFS", "false");
+        assert_eq!(image_rootfs_setting(None), Some(false));
+        std::env::remove_var("REAPER_IMAGE_ROOTFS");
+    }
+
+    #[test]
+    fn test_is_readonly_mount() {
+        let dir = TempDir::new().unwrap();
+        assert!(!is_readonly_mount(dir.path()));
+        assert!(!is_readonly_mount(&dir.path().join("missing")));
+    }
+
+    #[test]
+    #[serial]
+    fn test_do_create_records_image_rootfs_when_requested() {
+        with_test_root(|_| {
+            std::env::remove_var("REAPER_IMAGE_ROOTFS");
+            let bundle = TempDir::new().unwrap();
+            fs::create_dir(bundle.path().join("rootfs")).unwrap();
+            let config = serde_json::json!({
+                "root": { "path": "rootfs" },
+                "process": { "args": ["/bin/true"], "cwd": "/" }
+            });
+            fs::write(
+                bundle.path().join("config.json"),
+                serde_json::to_string(&config).unwrap(),
+            )
+            .unwrap();
+
+            do_create(
+                "test-img",
+                bundle.path(),
+                false,
+                None,
+                None,
+                None,
+                None,
+                &[],
+                false,
+            )
+            .unwrap();
+            assert!(load_state("test-img").unwrap().image_rootfs.is_none());
+
+            do_create(
+                "test-img2",
+                bundle.path(),
+                false,
+                None,
+                None,
+                None,
+                None,
+                &["image-rootfs=true".to_string()],
+                false,
+            )
+            .unwrap();
+            assert_eq!(
+                load_state("test-img2").unwrap().image_rootfs,
+                Some(fs::canonicalize(bundle.path().join("rootfs")).unwrap())
+            );
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn test_do_create_sandbox_persists_flag_without_config() {
+        with_test_root(|_| {
+            // Sandbox bundles are not parsed: no config.json needed
+            let bundle = TempDir::new().unwrap();
+            do_create(
+                "test-sandbox",
+                bundle.path(),
+                false,
+                None,
+                None,
+                None,
+                Some("default".into()),
+                &["image-rootfs=true".to_string()],
+                true,
+            )
+            .unwrap();
+
+            let state = load_state("test-sandbox").unwrap();
+            assert!(state.sandbox);
+            assert_eq!(state.status, "created");
+            assert!(state.annotations.is_none());
+            assert!(state.image_rootfs.is_none());
+
+            let err = do_start("test-sandbox", bundle.path(), None).unwrap_err();
+            assert!(err.to_string().contains("sandbox"));
+        });
+    }
+
     // --- do_state tests ---
 
     #[test]
@@ -2178,6 +7242,7 @@ mod tests {
                 None,
                 None,
                 &[],
+                false,
             )
             .unwrap();
             // do_state prints JSON to stdout — just verify it doesn't error
@@ -2195,6 +7260,162 @@ mod tests {
         });
     }
 
+    // --- inspect tests ---
+
+    #[test]
+    #[serial]
+    fn test_inspect_document_created_container() {
+        with_test_root(|_| {
+            let bundle = TempDir::new().unwrap();
+            let config = serde_json::json!({
+                "process": {
+                    "args": ["/bin/sleep", "60"],
+                    "env": ["FOO=bar"],
+                    "user": { "uid": 1000, "gid": 1000 }
+                },
+                "mounts": [
+                    { "destination": "/proc", "type": "proc", "source": "proc" },
+                    { "destination": "/data", "type": "bind", "source": "/srv/data",
+                      "options": ["rbind", "rw"] }
+                ]
+            });
+            fs::write(
+                bundle.path().join("config.json"),
+                serde_json::to_string(&config).unwrap(),
+            )
+            .unwrap();
+            do_create(
+                "test-inspect",
+                bundle.path(),
+                false,
+                None,
+                None,
+                None,
+                Some("default".into()),
+                &[],
+                false,
+            )
+            .unwrap();
+
+            let doc = inspect_document("test-inspect").unwrap();
+            assert_eq!(doc["annotations"], serde_json::json!({}));
+            assert_eq!(doc["state"]["id"], "test-inspect");
+            assert_eq!(doc["state"]["status"], "created");
+            assert_eq!(doc["process"]["args"][0], "/bin/sleep");
+            assert_eq!(doc["process"]["cwd"], "/");
+            assert_eq!(doc["process"]["user"]["uid"], 1000);
+            assert_eq!(doc["process"]["env"][0], "FOO=bar");
+            assert_eq!(doc["liveness"]["pid_alive"], false);
+            assert_eq!(doc["liveness"]["daemon_alive"], false);
+            #[cfg(target_os = "linux")]
+            {
+                assert_eq!(doc["mounts"]["applied"][0]["destination"], "/data");
+                assert_eq!(doc["mounts"]["skipped"][0]["destination"], "/proc");
+                let ns_path = doc["overlay"]["ns_path"].as_str().unwrap();
+                assert!(ns_path.ends_with("default"), "{}", ns_path);
+                assert!(doc["overlay"]["base_dir"].is_string());
+            }
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn test_inspect_document_effective_annotations() {
+        with_test_root(|_| {
+            let bundle = TempDir::new().unwrap();
+            do_create(
+                "test-inspect-eff",
+                bundle.path(),
+                false,
+                None,
+                None,
+                None,
+                Some("default".into()),
+                &[],
+                false,
+            )
+            .unwrap();
+            let mut state = load_state("test-inspect-eff").unwrap();
+            state.effective = Some(state::EffectiveSettings {
+                overlay_isolation: Some("namespace".into()),
+                filter_enabled: true,
+                dns_mode: "kubernetes".into(),
+                ..Default::default()
+            });
+            save_state(&state).unwrap();
+
+            let doc = inspect_document("test-inspect-eff").unwrap();
+            assert_eq!(
+                doc["annotations"],
+                serde_json::json!({
+                    "reaper.runtime/effective.dns-mode": "kubernetes",
+                    "reaper.runtime/effective.filter-enabled": "true",
+                    "reaper.runtime/effective.overlay-isolation": "namespace",
+                    "reaper.runtime/effective.pid-namespace": "false",
+                })
+            );
+
+            // Started without an overlay
+            state.effective = Some(state::EffectiveSettings::default());
+            save_state(&state).unwrap();
+            let doc = inspect_document("test-inspect-eff").unwrap();
+            assert_eq!(
+                doc["annotations"]["reaper.runtime/effective.overlay-isolation"],
+                "none"
+            );
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn test_inspect_document_missing_bundle() {
+        with_test_root(|_| {
+            let bundle = TempDir::new().unwrap();
+            do_create(
+                "test-inspect-nobundle",
+                bundle.path(),
+                false,
+                None,
+                None,
+                None,
+                Some("default".into()),
+                &[],
+                false,
+            )
+            .unwrap();
+            drop(bundle);
+
+            let doc = inspect_document("test-inspect-nobundle").unwrap();
+            assert_eq!(doc["state"]["id"], "test-inspect-nobundle");
+            assert!(doc["process"]["error"].is_string());
+            assert!(doc["mounts"]["error"].is_string());
+            assert!(inspect_document("nonexistent-container").is_err());
+        });
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_parse_nspid_and_ppid() {
+        let status = "Name:\tsh\nPid:\t4242\nPPid:\t4241\nNSpid:\t4242\t1\n";
+        assert_eq!(parse_nspid(status), vec![4242, 1]);
+        assert_eq!(parse_ppid(status), Some(4241));
+        assert!(is_nested_pid_namespace_init(status));
+
+        // Same namespace as the runtime: a single NSpid entry
+        let host = "Pid:\t4242\nPPid:\t1\nNSpid:\t4242\n";
+        assert!(!is_nested_pid_namespace_init(host));
+        // Nested but not the namespace init
+        let child = "NSpid:\t4243\t2\n";
+        assert!(!is_nested_pid_namespace_init(child));
+        assert!(parse_nspid("Pid:\t1\n").is_empty());
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_pid_namespace_init_none_without_pid_namespace() {
+        assert_eq!(pid_namespace_init(std::process::id() as i32), None);
+    }
+
     // --- do_delete tests ---
 
     #[test]
@@ -2211,6 +7432,7 @@ mod tests {
                 None,
                 None,
                 &[],
+                false,
             )
             .unwrap();
             let result = do_delete("test-del");
@@ -2238,7 +7460,7 @@ mod tests {
     fn test_do_kill_no_pid_file() {
         with_test_root(|_| {
             // No container state / PID file exists
-            let result = do_kill("nonexistent", Some(15));
+            let result = do_kill("nonexistent", Some(15), None);
             assert!(result.is_err());
         });
     }
@@ -2257,6 +7479,7 @@ mod tests {
                 None,
                 None,
                 &[],
+                false,
             )
             .unwrap();
             // Spawn a real short-lived child so we have a valid PID
@@ -2268,7 +7491,7 @@ mod tests {
             save_pid("test-kill", pid).unwrap();
 
             // Kill with default signal (SIGTERM)
-            let result = do_kill("test-kill", None);
+            let result = do_kill("test-kill", None, None);
             assert!(result.is_ok());
 
             // Clean up: wait for child to actually die
@@ -2292,6 +7515,7 @@ mod tests {
                 None,
                 None,
                 &[],
+                false,
             )
             .unwrap();
 
@@ -2304,7 +7528,7 @@ mod tests {
             save_pid("test-esrch", pid).unwrap();
 
             // Kill should succeed (ESRCH is treated as success)
-            let result = do_kill("test-esrch", Some(15));
+            let result = do_kill("test-esrch", Some(15), None);
             assert!(result.is_ok());
         });
     }
@@ -2323,12 +7547,13 @@ mod tests {
                 None,
                 None,
                 &[],
+                false,
             )
             .unwrap();
             save_pid("test-badsig", std::process::id() as i32).unwrap();
 
             // Signal 999 is invalid
-            let result = do_kill("test-badsig", Some(999));
+            let result = do_kill("test-badsig", Some(999), None);
             assert!(result.is_err());
             assert!(result.unwrap_err().to_string().contains("invalid signal"));
         });
@@ -2340,15 +7565,284 @@ mod tests {
     fn test_open_log_file_regular_file() {
         let tmp = TempDir::new().unwrap();
         let path = tmp.path().join("testfile");
-        // Create a regular file first (open_log_file expects it to exist)
+        // An existing regular file is opened as-is
         fs::write(&path, "").unwrap();
         let result = open_log_file(path.to_str().unwrap());
         assert!(result.is_ok());
     }
 
+    #[test]
+    #[serial]
+    fn test_create_log_file_restrictive_mode() {
+        use std::os::unix::fs::PermissionsExt;
+        std::env::remove_var("REAPER_LOG_FILE_MODE");
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("runtime.log");
+        create_log_file(&path).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode, 0o640);
+
+        std::env::set_var("REAPER_LOG_FILE_MODE", "0600");
+        let path = tmp.path().join("private.log");
+        create_log_file(&path).unwrap();
+        std::env::remove_var("REAPER_LOG_FILE_MODE");
+        let mode = fs::metadata(&path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode, 0o600);
+    }
+
+    #[test]
+    fn test_append_log_eof_markers() {
+        use std::os::unix::process::ExitStatusExt;
+        let tmp = TempDir::new().unwrap();
+        let log = tmp.path().join("stdout.log");
+        let log_str = log.to_str().unwrap();
+        fs::write(&log, "hello\n").unwrap();
+
+        // Clean exit (code 3); stdout and stderr share the file: one marker
+        append_log_eof_markers(
+            &[Some(log_str), Some(log_str)],
+            std::process::ExitStatus::from_raw(3 << 8),
+        );
+        assert_eq!(
+            fs::read_to_string(&log).unwrap(),
+            "hello\n{\"reaper\":\"eof\",\"exit_code\":3}\n"
+        );
+
+        // Killed by SIGKILL: no marker
+        fs::write(&log, "hello\n").unwrap();
+        append_log_eof_markers(&[Some(log_str)], std::process::ExitStatus::from_raw(9));
+        assert_eq!(fs::read_to_string(&log).unwrap(), "hello\n");
+
+        // FIFOs are left alone (a write would block without a reader)
+        let fifo = tmp.path().join("fifo");
+        nix::unistd::mkfifo(&fifo, nix::sys::stat::Mode::S_IRWXU).unwrap();
+        append_log_eof_markers(
+            &[fifo.to_str(), None, Some("")],
+            std::process::ExitStatus::from_raw(0),
+        );
+    }
+
     #[test]
     fn test_open_log_file_nonexistent() {
         let result = open_log_file("/nonexistent/path/to/fifo");
         assert!(result.is_err());
+
+        // A missing containerd FIFO is not replaced by a file nobody reads
+        let tmp = TempDir::new().unwrap();
+        let fifo = tmp.path().join("stdout");
+        assert!(open_log_file(fifo.to_str().unwrap()).is_err());
+        assert!(!fifo.exists());
+    }
+
+    #[test]
+    #[serial]
+    fn test_log_socket_receives_relayed_output() {
+        use std::io::Read;
+        let tmp = TempDir::new().unwrap();
+        std::env::set_var("REAPER_LOG_SOCKET_DIR", tmp.path());
+        let path = log_socket_path("ctr-1").unwrap();
+        std::env::remove_var("REAPER_LOG_SOCKET_DIR");
+        assert_eq!(path, tmp.path().join("ctr-1.sock"));
+        assert!(log_socket_path("ctr-1").is_none());
+
+        let listener = std::os::unix::net::UnixListener::bind(&path).unwrap();
+        let socket = LogSocket::connect(&path).unwrap();
+        let (mut conn, _) = listener.accept().unwrap();
+
+        let mut primary = Vec::new();
+        relay_output(&b"line 1\nline 2\n"[..], &mut primary, Some(&socket));
+        assert_eq!(primary, b"line 1\nline 2\n");
+
+        drop(socket);
+        let mut received = String::new();
+        conn.read_to_string(&mut received).unwrap();
+        assert_eq!(received, "line 1\nline 2\n");
+
+        // A listener that goes away never breaks the primary stream
+        let socket = LogSocket::connect(&path).unwrap();
+        drop(listener.accept().unwrap());
+        let data = vec![b'x'; 1 << 20];
+        let mut primary = Vec::new();
+        relay_output(&data[..], &mut primary, Some(&socket));
+        assert_eq!(primary, data);
+
+        drop((conn, listener));
+        assert!(LogSocket::connect(&path).is_none());
+    }
+
+    #[test]
+    fn test_log_socket_tee_relays_pipe_to_fifo_and_socket() {
+        use std::io::Read;
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("ctr.sock");
+        let listener = std::os::unix::net::UnixListener::bind(&path).unwrap();
+        let socket = LogSocket::connect(&path).unwrap();
+        let (mut conn, _) = listener.accept().unwrap();
+
+        let log = tmp.path().join("stdout.log");
+        fs::write(&log, "").unwrap();
+        let file = open_log_file(log.to_str().unwrap()).unwrap();
+        let mut tee = LogSocketTee::default();
+        let stdio = tee.stdio(file, Some(&socket), None);
+        assert!(tee.is_active());
+        drop(socket);
+
+        let mut child = Command::new("/bin/sh")
+            .args(["-c", "echo from workload"])
+            .stdout(stdio)
+            .spawn()
+            .unwrap();
+        child.wait().unwrap();
+        // Joining the relay drops the last socket handle, ending the stream
+        tee.finish(std::time::Duration::from_secs(5));
+        assert_eq!(tee.fifos.len(), 1);
+
+        assert_eq!(fs::read_to_string(&log).unwrap(), "from workload\n");
+        let mut received = String::new();
+        conn.read_to_string(&mut received).unwrap();
+        assert_eq!(received, "from workload\n");
+    }
+
+    #[test]
+    fn test_cri_log_relay_formats_files_only() {
+        let tmp = TempDir::new().unwrap();
+        let log = tmp.path().join("stdout.log");
+        fs::write(&log, "").unwrap();
+        let file = open_log_file(log.to_str().unwrap()).unwrap();
+        let fifo = tmp.path().join("stdout.fifo");
+        nix::unistd::mkfifo(&fifo, nix::sys::stat::Mode::from_bits_truncate(0o600)).unwrap();
+        let fifo_file = open_log_file(fifo.to_str().unwrap()).unwrap();
+
+        let mut tee = LogSocketTee::default();
+        // A FIFO gets the raw stream, without a relay
+        let _ = tee.stdio(fifo_file, None, Some("stdout"));
+        assert!(!tee.is_active());
+        let stdio = tee.stdio(file, None, Some("stdout"));
+        assert!(tee.is_active());
+
+        let mut child = Command::new("/bin/sh")
+            .args(["-c", "echo one; echo two"])
+            .stdout(stdio)
+            .spawn()
+            .unwrap();
+        child.wait().unwrap();
+        tee.finish(std::time::Duration::from_secs(5));
+
+        let content = fs::read_to_string(&log).unwrap();
+        let lines: Vec<&str> = content
+            .lines()
+            .map(|l| l.split_once(' ').unwrap().1)
+            .collect();
+        assert_eq!(lines, ["stdout F one", "stdout F two"]);
+    }
+
+    #[test]
+    fn test_relay_counted_counts_and_caps() {
+        let data: Vec<u8> = (0..10_000u32).map(|i| i as u8).collect();
+
+        let counter = OutputCounter::default();
+        let mut out = Vec::new();
+        relay_counted(&data[..], &mut out, None, &counter);
+        assert_eq!(out, data);
+        assert_eq!((counter.bytes(), counter.truncated()), (10_000, false));
+
+        // A cap equal to the output is not a truncation
+        let counter = OutputCounter::default();
+        let mut out = Vec::new();
+        relay_counted(&data[..], &mut out, Some(10_000), &counter);
+        assert_eq!(out.len(), 10_000);
+        assert!(!counter.truncated());
+
+        // Past the cap, bytes are counted but not forwarded
+        let counter = OutputCounter::default();
+        let mut out = Vec::new();
+        relay_counted(&data[..], &mut out, Some(5_000), &counter);
+        assert_eq!(out, &data[..5_000]);
+        assert_eq!((counter.bytes(), counter.truncated()), (10_000, true));
+    }
+
+    #[test]
+    fn test_exec_output_relays_record_counts() {
+        let tmp = TempDir::new().unwrap();
+        let stdout_log = tmp.path().join("stdout.log");
+        fs::write(&stdout_log, "").unwrap();
+        let stderr_log = tmp.path().join("stderr.log");
+        fs::write(&stderr_log, "").unwrap();
+        let accounting = Some(OutputAccounting { cap: Some(4) });
+
+        let mut relays = ExecOutputRelays::default();
+        let stdout = relays.stdout(
+            open_log_file(stdout_log.to_str().unwrap()).unwrap(),
+            accounting,
+        );
+        let stderr = relays.stderr(
+            open_log_file(stderr_log.to_str().unwrap()).unwrap(),
+            accounting,
+        );
+        let mut child = Command::new("/bin/sh")
+            .args(["-c", "printf 123; printf abcdefgh >&2"])
+            .stdout(stdout)
+            .stderr(stderr)
+            .spawn()
+            .unwrap();
+        child.wait().unwrap();
+
+        let output = relays.finish(std::time::Duration::from_secs(5));
+        assert_eq!(
+            output,
+            ExecOutput {
+                stdout_bytes: Some(3),
+                stderr_bytes: Some(8),
+                truncated: true,
+            }
+        );
+        assert_eq!(fs::read_to_string(&stdout_log).unwrap(), "123");
+        assert_eq!(fs::read_to_string(&stderr_log).unwrap(), "abcd");
+
+        // Without accounting the file is handed to the workload directly
+        let mut relays = ExecOutputRelays::default();
+        relays.stdout(open_log_file(stdout_log.to_str().unwrap()).unwrap(), None);
+        assert!(relays.threads.is_empty() && relays.stdout.is_none());
+    }
+
+    #[test]
+    #[serial]
+    fn test_exec_output_accounting() {
+        std::env::remove_var("REAPER_EXEC_OUTPUT_STATS");
+        std::env::remove_var("REAPER_EXEC_OUTPUT_CAP_BYTES");
+        assert_eq!(exec_output_accounting(), None);
+
+        std::env::set_var("REAPER_EXEC_OUTPUT_STATS", "true");
+        assert_eq!(
+            exec_output_accounting(),
+            Some(OutputAccounting { cap: None })
+        );
+
+        std::env::remove_var("REAPER_EXEC_OUTPUT_STATS");
+        std::env::set_var("REAPER_EXEC_OUTPUT_CAP_BYTES", "1048576");
+        assert_eq!(
+            exec_output_accounting(),
+            Some(OutputAccounting { cap: Some(1048576) })
+        );
+        for off in ["0", "lots"] {
+            std::env::set_var("REAPER_EXEC_OUTPUT_CAP_BYTES", off);
+            assert_eq!(exec_output_accounting(), None);
+        }
+        std::env::remove_var("REAPER_EXEC_OUTPUT_CAP_BYTES");
+    }
+
+    #[test]
+    fn test_log_destination() {
+        let flag = Path::new("/run/containerd/c1/log.json");
+        assert_eq!(log_destination(None, None), None);
+        assert_eq!(log_destination(None, Some(flag)), Some(flag.to_path_buf()));
+        assert_eq!(
+            log_destination(Some("/var/log/reaper-runtime.log".into()), Some(flag)),
+            Some(PathBuf::from("/var/log/reaper-runtime.log"))
+        );
+        assert_eq!(
+            log_destination(Some(String::new()), Some(flag)),
+            Some(flag.to_path_buf())
+        );
     }
 }
@@ -17,11 +17,15 @@
 //! Subsequent workloads simply `setns()` into the existing namespace.
 
 use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
 use std::fs;
 use std::os::unix::io::{AsRawFd, OwnedFd};
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use tracing::info;
 
+use super::state::AppliedMount;
+
 use nix::fcntl::{Flock, FlockArg};
 use nix::libc;
 use nix::mount::{mount, umount2, MntFlags, MsFlags};
@@ -37,6 +41,27 @@ pub enum OverlayIsolation {
     Node,
 }
 
+impl OverlayIsolation {
+    /// Parse "namespace" or "node" (case-insensitive).
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "namespace" => Ok(OverlayIsolation::Namespace),
+            "node" => Ok(OverlayIsolation::Node),
+            _ => bail!(
+                "invalid overlay isolation {:?} (valid: namespace, node)",
+                value
+            ),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OverlayIsolation::Namespace => "namespace",
+            OverlayIsolation::Node => "node",
+        }
+    }
+}
+
 /// Read overlay isolation mode from config.
 ///
 /// - `REAPER_OVERLAY_ISOLATION`: "namespace" (default) or "node"
@@ -49,6 +74,18 @@ pub fn read_isolation_mode() -> OverlayIsolation {
         .unwrap_or(OverlayIsolation::Namespace)
 }
 
+/// Resolve the isolation mode for one container.
+///
+/// Precedence: `overlay-isolation` annotation > `REAPER_OVERLAY_ISOLATION` > namespace.
+pub fn read_isolation_mode_with_override(annotation: Option<&str>) -> Result<OverlayIsolation> {
+    match annotation {
+        Some(value) => {
+            OverlayIsolation::parse(value).context("invalid overlay-isolation annotation")
+        }
+        None => Ok(read_isolation_mode()),
+    }
+}
+
 /// Validate that a Kubernetes namespace name is safe for use as a path component.
 /// K8s namespaces follow DNS label rules: [a-z0-9][a-z0-9-]*[a-z0-9], max 63 chars.
 fn validate_namespace_for_path(ns: &str) -> Result<()> {
@@ -81,6 +118,67 @@ pub struct OverlayConfig {
     pub lock_path: PathBuf,
     /// Directory for pivot_root merged view
     pub merged_dir: PathBuf,
+    /// Container image rootfs stacked above the host root as the topmost
+    /// lower layer. Only set for private per-container overlays
+    /// (see [`OverlayConfig::with_image_rootfs`]).
+    pub image_rootfs: Option<PathBuf>,
+    /// Extra paths that stay writable on a read-only root
+    /// (`REAPER_WRITABLE_PATHS`).
+    pub writable_paths: Vec<PathBuf>,
+    /// Private tmpfs replacing the host's /run (`REAPER_RUN_TMPFS`).
+    pub run_tmpfs: Option<RunTmpfs>,
+}
+
+impl OverlayConfig {
+    /// Turn this into a private overlay for one container whose image rootfs
+    /// is layered over the host root.
+    ///
+    /// Lower layers are fixed when the namespace is created, so an image
+    /// rootfs can't be shared: every path gets a `rootfs-<container_id>`
+    /// suffix and the namespace is torn down by [`remove_private_overlay`]
+    /// when the container is deleted.
+    pub fn with_image_rootfs(self, container_id: &str, rootfs: &Path) -> Result<Self> {
+        super::state::validate_id(container_id)
+            .context("invalid container id for private overlay")?;
+        let suffix = format!("rootfs-{}", container_id);
+        let with_suffix = |p: &Path| {
+            let mut s = p.as_os_str().to_owned();
+            s.push(format!("--{}", suffix));
+            PathBuf::from(s)
+        };
+        Ok(Self {
+            base_dir: self.base_dir.join(&suffix),
+            ns_path: with_suffix(&self.ns_path),
+            lock_path: with_suffix(&self.lock_path),
+            merged_dir: self.merged_dir.join(&suffix),
+            image_rootfs: Some(rootfs.to_path_buf()),
+            writable_paths: self.writable_paths,
+            run_tmpfs: self.run_tmpfs,
+        })
+    }
+}
+
+/// Build the overlayfs `lowerdir=` value.
+///
+/// Overlayfs treats the leftmost entry as the top layer, so the image rootfs
+/// (when present) shadows the host root and host paths show through wherever
+/// the image has nothing. `:` and `\` in paths are escaped; `,` would end the
+/// option and is rejected.
+pub fn compose_lowerdir(image_rootfs: Option<&Path>) -> Result<String> {
+    let Some(rootfs) = image_rootfs else {
+        return Ok("/".to_string());
+    };
+    let path = rootfs
+        .to_str()
+        .with_context(|| format!("image rootfs path is not UTF-8: {}", rootfs.display()))?;
+    if !rootfs.is_absolute() {
+        bail!("image rootfs must be an absolute path: {}", path);
+    }
+    if path.contains(',') {
+        bail!("image rootfs path contains ',': {}", path);
+    }
+    let escaped = path.replace('\\', "\\\\").replace(':', "\\:");
+    Ok(format!("{}:/", escaped.trim_end_matches('/')))
 }
 
 /// Filter configuration for sensitive file filtering.
@@ -93,10 +191,40 @@ pub struct FilterConfig {
     pub custom_paths: Vec<PathBuf>,
     /// Allowlist: paths to exclude from filtering (from REAPER_FILTER_ALLOWLIST)
     pub allowlist: Vec<PathBuf>,
-    /// Directory to store empty placeholder files (default: /run/reaper/overlay-filters)
+    /// Directory to store empty placeholder files (default: /run/reaper/overlay-filters),
+    /// one subdirectory per namespace
     pub filter_dir: PathBuf,
+    /// Abort namespace creation when a path cannot be filtered instead of
+    /// logging and continuing (from REAPER_FILTER_STRICT, default: false)
+    pub strict: bool,
+}
+
+/// Exit code of a container whose start was aborted because a sensitive path
+/// could not be filtered in strict mode (`REAPER_FILTER_STRICT=1`).
+pub const FILTER_FAILURE_EXIT_CODE: i32 = 3;
+
+/// A sensitive path that could not be filtered while `REAPER_FILTER_STRICT`
+/// is on. Returned by [`enter_overlay`] so callers can tell it apart from
+/// other overlay failures.
+#[derive(Debug, PartialEq)]
+pub struct FilterFailure {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+impl std::fmt::Display for FilterFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "strict filtering: failed to filter {}: {}",
+            self.path.display(),
+            self.reason
+        )
+    }
 }
 
+impl std::error::Error for FilterFailure {}
+
 /// Filter mode: append to default filters or replace them entirely.
 #[derive(Debug, PartialEq)]
 pub enum FilterMode {
@@ -115,6 +243,15 @@ pub enum DnsMode {
     Kubernetes,
 }
 
+impl DnsMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DnsMode::Host => "host",
+            DnsMode::Kubernetes => "kubernetes",
+        }
+    }
+}
+
 /// DNS configuration, read from environment variables.
 pub struct DnsConfig {
     /// Which DNS resolver to use
@@ -154,10 +291,91 @@ pub fn read_dns_config_with_override(annotation_override: Option<&str>) -> DnsCo
     DnsConfig { mode }
 }
 
+/// DNS configuration for a pod. Host-network pods resolve like the node, so
+/// they get host mode unless the `dns-mode` annotation asks otherwise (the
+/// `ClusterFirstWithHostNet` case).
+pub fn read_pod_dns_config(annotation_override: Option<&str>, host_network: bool) -> DnsConfig {
+    if host_network && annotation_override.is_none() {
+        return DnsConfig {
+            mode: DnsMode::Host,
+        };
+    }
+    read_dns_config_with_override(annotation_override)
+}
+
+/// PID whose `/proc/<pid>/root` and mount namespace stand for the host's.
+const DEFAULT_HOST_PID: i32 = 1;
+
+/// Host PID from `REAPER_HOST_PID` (default 1), for nodes where PID 1 is not
+/// the host init (e.g. reaper itself running in a container).
+pub fn host_pid() -> i32 {
+    match std::env::var("REAPER_HOST_PID") {
+        Ok(v) => match v.trim().parse::<i32>() {
+            Ok(pid) if pid > 0 => pid,
+            _ => {
+                tracing::warn!(
+                    "invalid REAPER_HOST_PID {:?}, using {}",
+                    v,
+                    DEFAULT_HOST_PID
+                );
+                DEFAULT_HOST_PID
+            }
+        },
+        Err(_) => DEFAULT_HOST_PID,
+    }
+}
+
+/// Mount namespace identifier (e.g. `mnt:[4026531841]`) of `pid`, which may
+/// also be `self`.
+fn mount_ns_id(pid: &str) -> std::io::Result<String> {
+    fs::read_link(format!("/proc/{}/ns/mnt", pid)).map(|p| p.to_string_lossy().into_owned())
+}
+
+/// The host PID must be in a different mount namespace than ours; if it
+/// shares ours, `/proc/<pid>/root` is our own (overlay) root, not the host's.
+fn validate_host_mount_ns(pid: i32, host_ns: &str, own_ns: &str) -> Result<()> {
+    if host_ns == own_ns {
+        bail!(
+            "host pid {} shares this process's mount namespace ({}), so its root is not \
+             the host filesystem; set REAPER_HOST_PID to a process in the host mount namespace",
+            pid,
+            host_ns
+        );
+    }
+    Ok(())
+}
+
+/// The validated host PID, or `None` when its mount namespace can't be read
+/// (e.g. rootless mode), in which case only sources visible in the overlay
+/// can be used.
+fn resolve_host_pid() -> Result<Option<i32>> {
+    let pid = host_pid();
+    let host_ns = match mount_ns_id(&pid.to_string()) {
+        Ok(ns) => ns,
+        Err(e) => {
+            tracing::warn!(
+                "host pid {}: cannot read mount namespace ({}); host-namespace sources unavailable",
+                pid,
+                e
+            );
+            return Ok(None);
+        }
+    };
+    let own_ns = mount_ns_id("self").context("reading own mount namespace")?;
+    validate_host_mount_ns(pid, &host_ns, &own_ns)?;
+    Ok(Some(pid))
+}
+
+/// `path` as seen from the host mount namespace, via `/proc/<pid>/root`.
+fn host_view(pid: i32, path: &str) -> PathBuf {
+    PathBuf::from(format!("/proc/{}/root{}", pid, path))
+}
+
 /// Apply Kubernetes DNS by writing the kubelet-prepared resolv.conf into the overlay.
 ///
 /// Finds the `/etc/resolv.conf` mount in the OCI mounts array, reads its content
-/// from the host namespace via `/proc/1/root/<source>`, and writes it as a regular
+/// from the host namespace via `/proc/<host pid>/root/<source>` (see
+/// `REAPER_HOST_PID`), and writes it as a regular
 /// file into `/etc/resolv.conf` in the overlay. This avoids bind-mount stale mount
 /// issues in the shared namespace.
 ///
@@ -187,19 +405,28 @@ pub fn apply_kubernetes_dns(oci_mounts: &[super::OciMount]) -> Result<()> {
         bail!("dns: /etc/resolv.conf mount has no source path in OCI config");
     }
 
-    // Read content from host namespace via /proc/1/root/<source>
-    let host_path = format!("/proc/1/root{}", source);
+    // Read content from host namespace via /proc/<host pid>/root/<source>
+    let Some(pid) = resolve_host_pid()? else {
+        bail!(
+            "dns: cannot reach the host mount namespace through REAPER_HOST_PID={} \
+             to read {}",
+            host_pid(),
+            source
+        );
+    };
+    let host_path = host_view(pid, source);
     let content = fs::read(&host_path).with_context(|| {
         format!(
             "dns: failed to read kubelet resolv.conf from {} (source: {})",
-            host_path, source
+            host_path.display(),
+            source
         )
     })?;
 
     if content.is_empty() {
         bail!(
             "dns: kubelet resolv.conf at {} is empty; CoreDNS may not be running",
-            host_path
+            host_path.display()
         );
     }
 
@@ -215,6 +442,88 @@ pub fn apply_kubernetes_dns(oci_mounts: &[super::OciMount]) -> Result<()> {
     Ok(())
 }
 
+/// Content for `/etc/hostname`: the kubelet's file when it has one (CRI
+/// mounts it, but it is k8s-internal, so reaper doesn't bind it), otherwise
+/// `hostname` on a line.
+fn hostname_file_content(hostname: &str, kubelet_file: Option<Vec<u8>>) -> Vec<u8> {
+    kubelet_file
+        .filter(|content| !content.iter().all(u8::is_ascii_whitespace))
+        .unwrap_or_else(|| format!("{}\n", hostname).into_bytes())
+}
+
+/// Write the container's hostname to `<etc_dir>/hostname` in the overlay, so
+/// tools reading the file agree with `uname -n`. Like `/etc/resolv.conf`, it
+/// is a regular file in the overlay, shared with the overlay's other
+/// workloads.
+pub fn write_etc_hostname(
+    etc_dir: &Path,
+    hostname: &str,
+    oci_mounts: &[super::OciMount],
+) -> Result<()> {
+    let kubelet_file = oci_mounts
+        .iter()
+        .find(|m| m.destination == "/etc/hostname")
+        .and_then(|m| m.source.as_deref())
+        .filter(|source| !source.is_empty())
+        .and_then(|source| {
+            let pid = resolve_host_pid().ok().flatten()?;
+            let path = host_view(pid, source);
+            match fs::read(&path) {
+                Ok(content) => Some(content),
+                Err(e) => {
+                    tracing::warn!(
+                        "hostname: cannot read kubelet hostname file {}: {}",
+                        path.display(),
+                        e
+                    );
+                    None
+                }
+            }
+        });
+    let path = etc_dir.join("hostname");
+    fs::write(&path, hostname_file_content(hostname, kubelet_file))
+        .with_context(|| format!("writing {}", path.display()))?;
+    Ok(())
+}
+
+/// `/dev/fuse` (char 10:229), as the kernel's FUSE driver registers it.
+pub const FUSE_DEVICE: (&str, u64, u64) = ("/dev/fuse", 10, 229);
+
+/// Make sure `/dev/fuse` exists for a workload annotated `enable-fuse`.
+///
+/// The container's `/dev` is the host's (through the root rbind), so the node
+/// is never created or chmod'ed here: a pod annotation must not change device
+/// nodes on the node. If it is missing the fuse module is not loaded (or the
+/// host `/dev` is stripped) and the start fails with a hint for the admin.
+pub fn ensure_fuse_device() -> Result<()> {
+    let (path, major, minor) = FUSE_DEVICE;
+    check_char_device(Path::new(path), major, minor)
+}
+
+/// Check that `path` is character device `major`:`minor`.
+fn check_char_device(path: &Path, major: u64, minor: u64) -> Result<()> {
+    use nix::sys::stat::makedev;
+    use std::os::unix::fs::{FileTypeExt, MetadataExt};
+
+    match fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_char_device() && meta.rdev() == makedev(major, minor) => {
+            Ok(())
+        }
+        Ok(_) => bail!(
+            "{} exists but is not character device {}:{}",
+            path.display(),
+            major,
+            minor
+        ),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => bail!(
+            "{} does not exist on this node; load the fuse kernel module \
+             (modprobe fuse) before running workloads with enable-fuse",
+            path.display()
+        ),
+        Err(e) => Err(e).with_context(|| format!("checking {}", path.display())),
+    }
+}
+
 /// Read overlay configuration from environment variables.
 ///
 /// In `Namespace` isolation mode (default), paths are scoped per K8s namespace:
@@ -235,20 +544,30 @@ pub fn apply_kubernetes_dns(oci_mounts: &[super::OciMount]) -> Result<()> {
 ///   - lock_path: `/run/reaper/overlay.lock`
 ///   - merged_dir: `/run/reaper/merged`
 ///
+/// `isolation_override` (the `overlay-isolation` annotation) picks the mode
+/// for this container ahead of `REAPER_OVERLAY_ISOLATION`.
+///
 /// Environment overrides (`REAPER_OVERLAY_BASE`, `REAPER_OVERLAY_NS`,
-/// `REAPER_OVERLAY_LOCK`) are respected in both modes as explicit overrides.
+/// `REAPER_OVERLAY_LOCK`, `REAPER_OVERLAY_MERGED_BASE`) are respected in both
+/// modes as explicit overrides.
 pub fn read_config(
     k8s_namespace: Option<&str>,
     overlay_name: Option<&str>,
+    isolation_override: Option<&str>,
 ) -> Result<OverlayConfig> {
-    let isolation = read_isolation_mode();
+    let isolation = read_isolation_mode_with_override(isolation_override)?;
+    let root = super::state::default_root();
+    let merged_base = read_merged_base()?.unwrap_or_else(|| root.join("merged"));
+    let writable_paths = read_writable_paths()?;
+    let run_tmpfs = read_run_tmpfs()?;
 
     match isolation {
         OverlayIsolation::Namespace => {
             let ns = k8s_namespace.ok_or_else(|| {
                 anyhow::anyhow!(
                     "overlay isolation mode is 'namespace' but no --namespace was provided. \
-                     Set REAPER_OVERLAY_ISOLATION=node or pass --namespace to the runtime."
+                     Set REAPER_OVERLAY_ISOLATION=node (or the overlay-isolation annotation) \
+                     or pass --namespace to the runtime."
                 )
             })?;
             validate_namespace_for_path(ns)?;
@@ -267,30 +586,10 @@ pub fn read_config(
                 None => (ns.to_string(), ns.to_string()),
             };
 
-            // Per-namespace paths under the standard /run/reaper/ tree.
-            // Explicit env overrides take precedence.
-            let base_dir = std::env::var("REAPER_OVERLAY_BASE")
-                .map(PathBuf::from)
-                .unwrap_or_else(|_| PathBuf::from(format!("/run/reaper/overlay/{}", overlay_key)));
-
-            let ns_path = std::env::var("REAPER_OVERLAY_NS")
-                .map(PathBuf::from)
-                .unwrap_or_else(|_| PathBuf::from(format!("/run/reaper/ns/{}", flat_key)));
-
-            let lock_path = std::env::var("REAPER_OVERLAY_LOCK")
-                .map(PathBuf::from)
-                .unwrap_or_else(|_| {
-                    PathBuf::from(format!("/run/reaper/overlay-{}.lock", flat_key))
-                });
-
-            let merged_dir = PathBuf::from(format!("/run/reaper/merged/{}", overlay_key));
-
-            Ok(OverlayConfig {
-                base_dir,
-                ns_path,
-                lock_path,
-                merged_dir,
-            })
+            let mut config = keyed_config(&root, &merged_base, &overlay_key, &flat_key);
+            config.writable_paths = writable_paths;
+            config.run_tmpfs = run_tmpfs;
+            Ok(config)
         }
         OverlayIsolation::Node => {
             // Legacy flat layout — ignores namespace and overlay-name arguments.
@@ -303,28 +602,206 @@ pub fn read_config(
 
             let base_dir = std::env::var("REAPER_OVERLAY_BASE")
                 .map(PathBuf::from)
-                .unwrap_or_else(|_| PathBuf::from("/run/reaper/overlay"));
+                .unwrap_or_else(|_| root.join("overlay"));
 
             let ns_path = std::env::var("REAPER_OVERLAY_NS")
                 .map(PathBuf::from)
-                .unwrap_or_else(|_| PathBuf::from("/run/reaper/shared-mnt-ns"));
+                .unwrap_or_else(|_| root.join("shared-mnt-ns"));
 
             let lock_path = std::env::var("REAPER_OVERLAY_LOCK")
                 .map(PathBuf::from)
-                .unwrap_or_else(|_| PathBuf::from("/run/reaper/overlay.lock"));
+                .unwrap_or_else(|_| root.join("overlay.lock"));
 
-            let merged_dir = PathBuf::from("/run/reaper/merged");
+            let merged_dir = merged_base;
 
             Ok(OverlayConfig {
                 base_dir,
                 ns_path,
                 lock_path,
                 merged_dir,
+                image_rootfs: None,
+                writable_paths,
+                run_tmpfs,
             })
         }
     }
 }
 
+/// Paths of a keyed overlay under the standard /run/reaper/ tree (the
+/// per-user root in rootless mode): `overlay_key` names the base and merged
+/// subdirectories, `flat_key` the namespace file and lock. Explicit env
+/// overrides take precedence.
+fn keyed_config(
+    root: &Path,
+    merged_base: &Path,
+    overlay_key: &str,
+    flat_key: &str,
+) -> OverlayConfig {
+    let base_dir = std::env::var("REAPER_OVERLAY_BASE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| root.join(format!("overlay/{}", overlay_key)));
+
+    let ns_path = std::env::var("REAPER_OVERLAY_NS")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| root.join(format!("ns/{}", flat_key)));
+
+    let lock_path = std::env::var("REAPER_OVERLAY_LOCK")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| root.join(format!("overlay-{}.lock", flat_key)));
+
+    OverlayConfig {
+        base_dir,
+        ns_path,
+        lock_path,
+        merged_dir: merged_base.join(overlay_key),
+        image_rootfs: None,
+        writable_paths: Vec::new(),
+        run_tmpfs: None,
+    }
+}
+
+/// Key of node-wide shared overlays in the overlay, merged and ns trees.
+/// The leading `_` can't start a Kubernetes namespace name, so the paths
+/// never collide with a namespace's overlay.
+const SHARED_OVERLAY_KEY: &str = "_shared";
+
+/// Overlay configuration for the node-wide shared overlay `name` (the
+/// `shared-overlay` annotation), independent of the pod's namespace and the
+/// isolation mode. Every pod naming it shares one upper layer, serialized by
+/// a lock scoped to the name:
+///   - base_dir: `/run/reaper/overlay/_shared/<name>/`
+///   - ns_path:  `/run/reaper/ns/_shared--<name>`
+///   - lock_path: `/run/reaper/overlay-_shared--<name>.lock`
+///   - merged_dir: `/run/reaper/merged/_shared/<name>`
+pub fn read_shared_config(name: &str) -> Result<OverlayConfig> {
+    validate_namespace_for_path(name).context("invalid shared-overlay annotation")?;
+    let root = super::state::default_root();
+    let merged_base = read_merged_base()?.unwrap_or_else(|| root.join("merged"));
+    let mut config = keyed_config(
+        &root,
+        &merged_base,
+        &format!("{}/{}", SHARED_OVERLAY_KEY, name),
+        &format!("{}--{}", SHARED_OVERLAY_KEY, name),
+    );
+    config.writable_paths = read_writable_paths()?;
+    config.run_tmpfs = read_run_tmpfs()?;
+    Ok(config)
+}
+
+/// `REAPER_WRITABLE_PATHS`: colon-separated absolute paths that get their
+/// own tmpfs when the root is read-only (bind fallback), so e.g. `/var/run`
+/// or `/var/log` work while the rest stays immutable.
+pub fn read_writable_paths() -> Result<Vec<PathBuf>> {
+    let value = std::env::var("REAPER_WRITABLE_PATHS").unwrap_or_default();
+    value
+        .split(':')
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            let path = Path::new(s);
+            let normal = path
+                .components()
+                .skip(1)
+                .all(|c| matches!(c, std::path::Component::Normal(_)));
+            if !path.is_absolute() || !normal || path.parent().is_none() {
+                bail!(
+                    "REAPER_WRITABLE_PATHS entries must be absolute paths below /, got {:?}",
+                    s
+                );
+            }
+            Ok(path.to_path_buf())
+        })
+        .collect()
+}
+
+/// Options of the private /run tmpfs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunTmpfs {
+    /// `size=` option, e.g. `64m` or `10%` (kernel default when unset)
+    pub size: Option<String>,
+    /// `mode=` option (default `0755`)
+    pub mode: String,
+}
+
+impl RunTmpfs {
+    fn mount_data(&self) -> String {
+        match &self.size {
+            Some(size) => format!("mode={},size={}", self.mode, size),
+            None => format!("mode={}", self.mode),
+        }
+    }
+}
+
+/// `REAPER_RUN_TMPFS`: give workloads a fresh tmpfs at /run instead of the
+/// host's, with only reaper's and containerd's directories bound in.
+/// `REAPER_RUN_TMPFS_SIZE` (`<n>[k|m|g]` or `<n>%`) and `REAPER_RUN_TMPFS_MODE`
+/// (octal, default `0755`) set its options.
+pub fn read_run_tmpfs() -> Result<Option<RunTmpfs>> {
+    let enabled = std::env::var("REAPER_RUN_TMPFS")
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false);
+    if !enabled {
+        return Ok(None);
+    }
+    let size = match std::env::var("REAPER_RUN_TMPFS_SIZE") {
+        Ok(v) if !v.is_empty() => {
+            let digits = v.trim_end_matches(['k', 'm', 'g', 'K', 'M', 'G', '%']);
+            if digits.is_empty()
+                || v.len() - digits.len() > 1
+                || !digits.bytes().all(|b| b.is_ascii_digit())
+            {
+                bail!(
+                    "REAPER_RUN_TMPFS_SIZE must be a size like 64m or 10%, got {:?}",
+                    v
+                );
+            }
+            Some(v)
+        }
+        _ => None,
+    };
+    let mode = std::env::var("REAPER_RUN_TMPFS_MODE").unwrap_or_else(|_| "0755".to_string());
+    if !(3..=4).contains(&mode.len()) || !mode.bytes().all(|b| (b'0'..=b'7').contains(&b)) {
+        bail!(
+            "REAPER_RUN_TMPFS_MODE must be an octal mode, got {:?}",
+            mode
+        );
+    }
+    Ok(Some(RunTmpfs { size, mode }))
+}
+
+/// `REAPER_OVERLAY_MERGED_BASE`: where merged-view mountpoints live instead
+/// of the `merged` dir under the state root (a tmpfs by default). Must be an
+/// absolute path that exists and is writable, or can be created under a
+/// writable ancestor.
+fn read_merged_base() -> Result<Option<PathBuf>> {
+    let Ok(value) = std::env::var("REAPER_OVERLAY_MERGED_BASE") else {
+        return Ok(None);
+    };
+    let base = PathBuf::from(&value);
+    if !base.is_absolute() {
+        bail!(
+            "REAPER_OVERLAY_MERGED_BASE must be an absolute path, got {:?}",
+            value
+        );
+    }
+    let existing = base
+        .ancestors()
+        .find(|p| p.exists())
+        .unwrap_or(Path::new("/"));
+    if !existing.is_dir() {
+        bail!(
+            "REAPER_OVERLAY_MERGED_BASE: {} is not a directory",
+            existing.display()
+        );
+    }
+    nix::unistd::access(existing, nix::unistd::AccessFlags::W_OK).with_context(|| {
+        format!(
+            "REAPER_OVERLAY_MERGED_BASE: {} is not writable",
+            existing.display()
+        )
+    })?;
+    Ok(Some(base))
+}
+
 /// Read filter configuration from environment variables.
 ///
 /// - `REAPER_FILTER_ENABLED`: enable/disable filtering (default: true)
@@ -332,6 +809,7 @@ pub fn read_config(
 /// - `REAPER_FILTER_PATHS`: colon-separated custom paths to filter
 /// - `REAPER_FILTER_ALLOWLIST`: colon-separated paths to exclude from filtering
 /// - `REAPER_FILTER_DIR`: directory for placeholder files (default: /run/reaper/overlay-filters)
+/// - `REAPER_FILTER_STRICT`: fail closed when a path cannot be filtered (default: false)
 pub fn read_filter_config() -> FilterConfig {
     let enabled = std::env::var("REAPER_FILTER_ENABLED")
         .map(|v| v != "false" && v != "0")
@@ -360,7 +838,11 @@ pub fn read_filter_config() -> FilterConfig {
 
     let filter_dir = std::env::var("REAPER_FILTER_DIR")
         .map(PathBuf::from)
-        .unwrap_or_else(|_| PathBuf::from("/run/reaper/overlay-filters"));
+        .unwrap_or_else(|_| super::state::default_root().join("overlay-filters"));
+
+    let strict = std::env::var("REAPER_FILTER_STRICT")
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false);
 
     FilterConfig {
         enabled,
@@ -368,6 +850,7 @@ pub fn read_filter_config() -> FilterConfig {
         custom_paths,
         allowlist,
         filter_dir,
+        strict,
     }
 }
 
@@ -404,40 +887,185 @@ fn get_default_filters() -> Vec<PathBuf> {
 ///
 /// Tested by kind-integration tests (requires root + Linux namespaces).
 #[cfg(not(tarpaulin_include))]
-pub fn enter_overlay(config: &OverlayConfig) -> Result<()> {
+pub fn enter_overlay(config: &OverlayConfig, oci_mounts: &[super::OciMount]) -> Result<()> {
     info!(
         "overlay: enter_overlay started, lock_path={}, ns_path={}",
         config.lock_path.display(),
         config.ns_path.display()
     );
 
-    // Acquire exclusive lock to prevent races during namespace creation
-    info!("overlay: acquiring lock...");
-    let _lock = acquire_lock(&config.lock_path).context("failed to acquire overlay lock")?;
-    info!("overlay: lock acquired");
-
-    // Read host /etc files up front so we can restore them inside an existing namespace
+    // Read host /etc files up front (still in the host namespace) so we can
+    // restore them inside an existing namespace. Done outside the lock.
     let host_etc = read_host_etc_files(Path::new("/etc"));
 
-    if namespace_exists(&config.ns_path) {
-        info!(
-            "overlay: joining existing shared namespace at {}",
-            config.ns_path.display()
-        );
-        join_namespace(&config.ns_path).context("failed to join existing namespace")?;
-    } else {
-        info!("overlay: creating new shared namespace (first workload on this node)");
-        create_namespace(config).context("failed to create shared namespace")?;
-        info!("overlay: shared namespace created successfully");
-    }
+    // The lock only covers the create-or-join decision and the persist step.
+    // The existence check and the setns() happen under the same lock so a
+    // concurrent creator can't race between them (no TOCTOU).
+    with_overlay_lock(&config.lock_path, || {
+        if reanchor_enabled() {
+            ensure_anchor(&config.ns_path);
+        }
+        if namespace_exists(&config.ns_path) {
+            info!(
+                "overlay: joining existing shared namespace at {}",
+                config.ns_path.display()
+            );
+            join_namespace(&config.ns_path).context("failed to join existing namespace")
+        } else {
+            info!("overlay: creating new shared namespace (first workload on this node)");
+            create_namespace(config, oci_mounts).context("failed to create shared namespace")?;
+            info!("overlay: shared namespace created successfully");
+            Ok(())
+        }
+    })?;
 
-    // After joining (or creating) the namespace, ensure resolver files exist/non-empty.
+    // Lock released: resolver file restoration is non-critical and must not
+    // serialize concurrent joins.
     ensure_etc_files_in_namespace(Path::new("/etc"), &host_etc);
 
     info!("overlay: enter_overlay completed successfully");
     Ok(())
 }
 
+/// Tear down a private per-container overlay created for an image rootfs.
+///
+/// Detaches the persisted namespace, stops the namespace anchor helper
+/// recorded next to it (see [`stop_anchor_helper`]), and removes the
+/// overlay's directories and files. Best effort: failures are logged and the
+/// remaining steps still run.
+///
+/// Tested by kind-integration tests (requires root + Linux namespaces).
+#[cfg(not(tarpaulin_include))]
+pub fn remove_private_overlay(config: &OverlayConfig) {
+    if config.image_rootfs.is_none() {
+        tracing::warn!(
+            "overlay: refusing to remove shared overlay {}",
+            config.base_dir.display()
+        );
+        return;
+    }
+
+    release_namespace(&config.ns_path);
+    let _ = fs::remove_file(&config.lock_path);
+    for dir in [&config.base_dir, &config.merged_dir] {
+        if let Err(e) = fs::remove_dir_all(dir) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!("overlay: removing {}: {}", dir.display(), e);
+            }
+        }
+    }
+    info!(
+        "overlay: removed private overlay {}",
+        config.base_dir.display()
+    );
+}
+
+/// Detach the persisted namespace at `ns_path`, stop its anchor helper and
+/// remove the namespace's files. Best effort.
+fn release_namespace(ns_path: &Path) {
+    match umount2(ns_path, MntFlags::MNT_DETACH) {
+        Ok(()) | Err(nix::errno::Errno::EINVAL) | Err(nix::errno::Errno::ENOENT) => {}
+        Err(e) => tracing::warn!("overlay: umount2({}): {}", ns_path.display(), e),
+    }
+    if let Some(pid) = stop_anchor_helper(ns_path, HELPER_EXIT_TIMEOUT) {
+        info!("overlay: stopped anchor helper pid={}", pid);
+    }
+    for file in [ns_path.to_path_buf(), helper_pid_path(ns_path)] {
+        let _ = fs::remove_file(file);
+    }
+    remove_filter_placeholders(&read_filter_config().filter_dir, ns_path);
+}
+
+/// Whether `REAPER_OVERLAY_AUTO_TEARDOWN` asks for namespace overlays to be
+/// torn down once their last container is deleted (default off).
+pub fn auto_teardown_enabled() -> bool {
+    std::env::var("REAPER_OVERLAY_AUTO_TEARDOWN")
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+/// Default for `REAPER_OVERLAY_TEARDOWN_GRACE_SECS`.
+const DEFAULT_TEARDOWN_GRACE_SECS: u64 = 30;
+
+/// How long an idle overlay is kept (`REAPER_OVERLAY_TEARDOWN_GRACE_SECS`),
+/// so pods churning through a namespace don't rebuild it every time.
+pub fn teardown_grace() -> Duration {
+    let secs = std::env::var("REAPER_OVERLAY_TEARDOWN_GRACE_SECS")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(DEFAULT_TEARDOWN_GRACE_SECS);
+    Duration::from_secs(secs)
+}
+
+/// Run `teardown` if `active` reports no containers both now and after
+/// `grace`. Returns whether `teardown` ran and did tear down.
+fn teardown_after_grace(
+    grace: Duration,
+    active: impl Fn() -> usize,
+    teardown: impl FnOnce() -> Result<bool>,
+) -> Result<bool> {
+    if active() > 0 {
+        return Ok(false);
+    }
+    std::thread::sleep(grace);
+    if active() > 0 {
+        return Ok(false);
+    }
+    teardown()
+}
+
+/// Whether a process other than the anchor helper is in the namespace
+/// persisted at `ns_path`, e.g. a container that joined but hasn't
+/// recorded that in its state yet.
+fn namespace_has_users(ns_path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    let helper = read_helper_info(&helper_pid_path(ns_path));
+    let Some(inode) = helper
+        .map(|(_, inode)| inode)
+        .or_else(|| fs::metadata(ns_path).ok().map(|m| m.ino()))
+    else {
+        return false;
+    };
+    namespace_member(inode, helper.map(|(pid, _)| pid)).is_some()
+}
+
+/// Tear down the overlay namespace of `config` once no container uses it
+/// (as counted by `active`) for `grace`: the namespace is detached, its
+/// anchor helper stopped and the merged directory removed. The upper layer
+/// is kept, so the namespace's files survive into its next incarnation.
+///
+/// The final check runs under the overlay lock, so a container starting
+/// meanwhile either keeps the namespace alive or creates a fresh one.
+/// Returns whether the namespace was torn down.
+///
+/// Tested by kind-integration tests (requires root + Linux namespaces).
+#[cfg(not(tarpaulin_include))]
+pub fn teardown_idle_namespace(
+    config: &OverlayConfig,
+    grace: Duration,
+    active: impl Fn() -> usize,
+) -> Result<bool> {
+    teardown_after_grace(grace, &active, || {
+        with_overlay_lock(&config.lock_path, || {
+            if active() > 0 || namespace_has_users(&config.ns_path) {
+                info!(
+                    "overlay: {} in use again, keeping it",
+                    config.ns_path.display()
+                );
+                return Ok(false);
+            }
+            release_namespace(&config.ns_path);
+            if let Err(e) = fs::remove_dir(&config.merged_dir) {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    tracing::warn!("overlay: removing {}: {}", config.merged_dir.display(), e);
+                }
+            }
+            info!("overlay: tore down idle {}", config.ns_path.display());
+            Ok(true)
+        })
+    })
+}
+
 /// Acquire an exclusive file lock. Blocks until the lock is available.
 /// The lock is released when the returned File is dropped.
 fn acquire_lock(lock_path: &Path) -> Result<Flock<fs::File>> {
@@ -459,6 +1087,20 @@ fn acquire_lock(lock_path: &Path) -> Result<Flock<fs::File>> {
     Ok(locked)
 }
 
+/// Run `f` while holding the exclusive overlay lock at `lock_path`.
+///
+/// The lock is released as soon as `f` returns (success or error), so callers
+/// should keep only the namespace create-or-join decision inside `f`.
+fn with_overlay_lock<T>(lock_path: &Path, f: impl FnOnce() -> Result<T>) -> Result<T> {
+    info!("overlay: acquiring lock...");
+    let lock = acquire_lock(lock_path).context("failed to acquire overlay lock")?;
+    info!("overlay: lock acquired");
+    let result = f();
+    drop(lock);
+    info!("overlay: lock released");
+    result
+}
+
 /// Derive the `.pid` file path from the namespace bind-mount path.
 /// E.g., `/run/reaper/ns/default` → `/run/reaper/ns/default.pid`
 fn helper_pid_path(ns_path: &Path) -> PathBuf {
@@ -478,6 +1120,33 @@ fn read_helper_info(pid_path: &Path) -> Option<(i32, u64)> {
     Some((pid, inode))
 }
 
+/// Anchor helpers recorded on this node: namespace path, helper PID and
+/// namespace inode of every `.pid` file under the namespace directory, plus
+/// the node-mode namespace (and a `REAPER_OVERLAY_NS` override).
+pub fn recorded_anchors() -> Vec<(PathBuf, i32, u64)> {
+    let root = super::state::default_root();
+    let mut ns_paths = vec![root.join("shared-mnt-ns")];
+    if let Ok(path) = std::env::var("REAPER_OVERLAY_NS") {
+        ns_paths.push(PathBuf::from(path));
+    }
+    if let Ok(entries) = fs::read_dir(root.join("ns")) {
+        ns_paths.extend(entries.flatten().filter_map(|e| {
+            let path = e.path();
+            let ns = path.to_str()?.strip_suffix(".pid")?;
+            Some(PathBuf::from(ns))
+        }));
+    }
+    ns_paths.sort();
+    ns_paths.dedup();
+    ns_paths
+        .into_iter()
+        .filter_map(|ns| {
+            let (pid, inode) = read_helper_info(&helper_pid_path(&ns))?;
+            Some((ns, pid, inode))
+        })
+        .collect()
+}
+
 /// Write helper PID and namespace inode to a `.pid` file.
 fn write_helper_info(pid_path: &Path, pid: i32, inode: u64) -> Result<()> {
     if let Some(parent) = pid_path.parent() {
@@ -487,6 +1156,51 @@ fn write_helper_info(pid_path: &Path, pid: i32, inode: u64) -> Result<()> {
     Ok(())
 }
 
+/// Record the anchor helper holding the namespace persisted at `ns_path`
+/// (PID and namespace inode) in its sibling `.pid` file, so the namespace can
+/// be joined without the bind-mount and the helper stopped on teardown.
+fn record_anchor_helper(ns_path: &Path, helper_pid: i32) -> Result<u64> {
+    let ns_inode = get_ns_inode(helper_pid).context("getting namespace inode from helper")?;
+    write_helper_info(&helper_pid_path(ns_path), helper_pid, ns_inode)?;
+    Ok(ns_inode)
+}
+
+/// How long teardown waits for a signalled anchor helper to go away.
+const HELPER_EXIT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Stop the anchor helper recorded for the namespace at `ns_path` and wait
+/// (up to `timeout`) until it no longer holds the namespace.
+///
+/// The helper is only signalled if it still holds the recorded namespace
+/// inode, so a recycled PID is never hit. An exited helper loses its
+/// `/proc/<pid>/ns/mnt` link even while it is a zombie awaiting its parent,
+/// so that is what confirms it is gone. Returns the stopped helper's PID.
+fn stop_anchor_helper(ns_path: &Path, timeout: std::time::Duration) -> Option<i32> {
+    let (pid, inode) = read_helper_info(&helper_pid_path(ns_path))?;
+    let holds_ns = || get_ns_inode(pid).ok() == Some(inode);
+    if !holds_ns() {
+        return None;
+    }
+    let _ = nix::sys::signal::kill(
+        nix::unistd::Pid::from_raw(pid),
+        nix::sys::signal::Signal::SIGKILL,
+    );
+
+    let deadline = std::time::Instant::now() + timeout;
+    while holds_ns() {
+        if std::time::Instant::now() >= deadline {
+            tracing::warn!(
+                "overlay: anchor helper pid={} still running {:?} after SIGKILL",
+                pid,
+                timeout
+            );
+            return None;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    Some(pid)
+}
+
 /// Get the inode number of a process's mount namespace.
 fn get_ns_inode(pid: i32) -> Result<u64> {
     use std::os::unix::fs::MetadataExt;
@@ -519,33 +1233,233 @@ fn namespace_exists(ns_path: &Path) -> bool {
     false
 }
 
-/// Open the overlay root directory via /proc/<pid>/root BEFORE setns.
-///
-/// Must be called while still in the host mount namespace where /proc is
-/// accessible. After setns, /proc is on a foreign mount and may not be
-/// traversable.
-fn open_overlay_root_fd(helper_pid: i32) -> Option<fs::File> {
-    let helper_root = format!("/proc/{}/root", helper_pid);
-    match fs::File::open(&helper_root) {
-        Ok(f) => {
-            info!(
-                "overlay: opened helper root fd (pid={}, path={})",
-                helper_pid, helper_root
-            );
-            Some(f)
-        }
-        Err(e) => {
-            tracing::warn!("overlay: failed to open {}: {}", helper_root, e);
-            None
-        }
-    }
+/// Whether the overlay namespace is live, and the PID of the helper process
+/// holding it (from its `.pid` file), for read-only inspection.
+pub fn namespace_status(config: &OverlayConfig) -> (bool, Option<i32>) {
+    let helper = read_helper_info(&helper_pid_path(&config.ns_path)).map(|(pid, _)| pid);
+    (namespace_exists(&config.ns_path), helper)
 }
 
-/// After setns(CLONE_NEWNS), adopt the overlay namespace's root filesystem
-/// using a pre-opened directory fd.
-///
-/// The process root still points to the old (host) namespace's root after setns.
-/// Absolute paths resolve through this stale root, which doesn't see mounts
+/// Whether `REAPER_OVERLAY_REANCHOR` lets a joining container replace a dead
+/// anchor helper (default on).
+pub fn reanchor_enabled() -> bool {
+    std::env::var("REAPER_OVERLAY_REANCHOR")
+        .map(|v| v != "false" && v != "0")
+        .unwrap_or(true)
+}
+
+/// What to do about the anchor helper of a namespace before joining it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnchorAction {
+    /// The anchor is alive, or there is no namespace to anchor
+    Keep,
+    /// The anchor died but process `from` is still in the namespace: start a
+    /// new anchor from it, bind-mounting its namespace again if `rebind`
+    Reanchor { from: i32, rebind: bool },
+    /// The anchor died and no process holds the namespace's root any more:
+    /// release what is left so the namespace is created afresh
+    Recreate,
+}
+
+/// Decide how to keep a namespace joinable. Joiners adopt the overlay root
+/// through the anchor's `/proc/<pid>/root`, so a valid bind mount alone is
+/// not enough: a new anchor needs a live `member` to take the root from.
+/// The mount decides whether that anchor must also be persisted again.
+pub fn anchor_action(
+    recorded: bool,
+    anchor_alive: bool,
+    mount_valid: bool,
+    member: Option<i32>,
+) -> AnchorAction {
+    if anchor_alive || (!recorded && !mount_valid) {
+        return AnchorAction::Keep;
+    }
+    match member {
+        Some(from) => AnchorAction::Reanchor {
+            from,
+            rebind: !mount_valid,
+        },
+        None => AnchorAction::Recreate,
+    }
+}
+
+/// Whether the anchor helper recorded for `ns_path` still holds the
+/// recorded namespace. A dead helper, even a zombie, has no namespace link.
+fn anchor_alive(ns_path: &Path) -> bool {
+    read_helper_info(&helper_pid_path(ns_path))
+        .is_some_and(|(pid, inode)| get_ns_inode(pid).ok() == Some(inode))
+}
+
+/// Whether `ns_path` is a bind-mounted namespace file, rather than the
+/// empty file left behind when the mount is gone.
+fn ns_mount_valid(ns_path: &Path) -> bool {
+    use nix::sys::statfs::{statfs, NSFS_MAGIC};
+    statfs(ns_path).is_ok_and(|fs| fs.filesystem_type() == NSFS_MAGIC)
+}
+
+/// A process other than `except` whose mount namespace has inode `inode`.
+fn namespace_member(inode: u64, except: Option<i32>) -> Option<i32> {
+    fs::read_dir("/proc")
+        .ok()?
+        .flatten()
+        .filter_map(|e| e.file_name().to_str()?.parse::<i32>().ok())
+        .filter(|&pid| Some(pid) != except)
+        .find(|&pid| get_ns_inode(pid).ok() == Some(inode))
+}
+
+/// Replace a dead anchor helper of the namespace at `ns_path` (see
+/// [`anchor_action`]) so that joining it works. Call with the overlay lock
+/// held. Best effort: failures are logged and the join goes ahead.
+///
+/// Tested by kind-integration tests (requires root + Linux namespaces).
+#[cfg(not(tarpaulin_include))]
+fn ensure_anchor(ns_path: &Path) {
+    use std::os::unix::fs::MetadataExt;
+    let helper = read_helper_info(&helper_pid_path(ns_path));
+    let mount_valid = ns_mount_valid(ns_path);
+    let inode = helper.map(|(_, inode)| inode).or_else(|| {
+        mount_valid
+            .then(|| fs::metadata(ns_path).ok().map(|m| m.ino()))
+            .flatten()
+    });
+    let alive = anchor_alive(ns_path);
+    let member = match (alive, inode) {
+        (false, Some(inode)) => namespace_member(inode, helper.map(|(pid, _)| pid)),
+        _ => None,
+    };
+    match anchor_action(helper.is_some(), alive, mount_valid, member) {
+        AnchorAction::Keep => {}
+        AnchorAction::Reanchor { from, rebind } => {
+            tracing::warn!(
+                "overlay: anchor helper of {} died, re-anchoring from pid={}",
+                ns_path.display(),
+                from
+            );
+            if let Err(e) = reanchor_namespace(ns_path, from, rebind) {
+                tracing::warn!(
+                    "overlay: re-anchoring {} failed: {:#}",
+                    ns_path.display(),
+                    e
+                );
+            }
+        }
+        AnchorAction::Recreate => {
+            tracing::warn!(
+                "overlay: anchor helper of {} died and the namespace has no processes left, \
+                 creating it again",
+                ns_path.display()
+            );
+            release_namespace(ns_path);
+        }
+    }
+}
+
+/// Start a new anchor helper in the namespace of `member` (its user
+/// namespace, mount namespace and root), record it, and bind-mount its
+/// namespace at `ns_path` again if `rebind`.
+///
+/// Tested by kind-integration tests (requires root + Linux namespaces).
+#[cfg(not(tarpaulin_include))]
+fn reanchor_namespace(ns_path: &Path, member: i32, rebind: bool) -> Result<()> {
+    let inode = get_ns_inode(member).context("member left the namespace")?;
+    let root = fs::File::open(format!("/proc/{}/root", member))
+        .with_context(|| format!("opening root of pid {}", member))?;
+    let ns = fs::File::open(format!("/proc/{}/ns/mnt", member))
+        .with_context(|| format!("opening namespace of pid {}", member))?;
+
+    let helper_pid = match unsafe { fork() }.context("forking anchor helper")? {
+        ForkResult::Child => {
+            let joined = join_helper_user_namespace(member)
+                .and_then(|()| setns(&ns, CloneFlags::CLONE_NEWNS).map_err(Into::into))
+                .and_then(|()| adopt_overlay_root(Some(&root)));
+            if joined.is_err() {
+                std::process::exit(1);
+            }
+            let _ = nix::unistd::setsid();
+            loop {
+                std::thread::sleep(std::time::Duration::from_secs(3600));
+            }
+        }
+        ForkResult::Parent { child } => child.as_raw(),
+    };
+
+    let deadline = Instant::now() + HELPER_EXIT_TIMEOUT;
+    while get_ns_inode(helper_pid).ok() != Some(inode) {
+        if Instant::now() >= deadline {
+            let _ = nix::sys::signal::kill(
+                nix::unistd::Pid::from_raw(helper_pid),
+                nix::sys::signal::Signal::SIGKILL,
+            );
+            bail!(
+                "new anchor helper pid={} did not join the namespace",
+                helper_pid
+            );
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    record_anchor_helper(ns_path, helper_pid)?;
+    info!(
+        "overlay: pid={} now anchors {}",
+        helper_pid,
+        ns_path.display()
+    );
+
+    if rebind && !super::state::rootless_enabled() {
+        let _ = umount2(ns_path, MntFlags::MNT_DETACH);
+        fs::File::create(ns_path).context("creating ns file")?;
+        let source = format!("/proc/{}/ns/mnt", helper_pid);
+        match mount(
+            Some(source.as_str()),
+            ns_path,
+            None::<&str>,
+            MsFlags::MS_BIND,
+            None::<&str>,
+        ) {
+            Ok(()) => info!(
+                "overlay: namespace persisted again at {}",
+                ns_path.display()
+            ),
+            Err(e) => {
+                // The PID file alone keeps the namespace joinable
+                tracing::warn!(
+                    "overlay: bind-mounting namespace at {} failed: {}, using the PID file",
+                    ns_path.display(),
+                    e
+                );
+                let _ = fs::remove_file(ns_path);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Open the overlay root directory via /proc/<pid>/root BEFORE setns.
+///
+/// Must be called while still in the host mount namespace where /proc is
+/// accessible. After setns, /proc is on a foreign mount and may not be
+/// traversable.
+fn open_overlay_root_fd(helper_pid: i32) -> Option<fs::File> {
+    let helper_root = format!("/proc/{}/root", helper_pid);
+    match fs::File::open(&helper_root) {
+        Ok(f) => {
+            info!(
+                "overlay: opened helper root fd (pid={}, path={})",
+                helper_pid, helper_root
+            );
+            Some(f)
+        }
+        Err(e) => {
+            tracing::warn!("overlay: failed to open {}: {}", helper_root, e);
+            None
+        }
+    }
+}
+
+/// After setns(CLONE_NEWNS), adopt the overlay namespace's root filesystem
+/// using a pre-opened directory fd.
+///
+/// The process root still points to the old (host) namespace's root after setns.
+/// Absolute paths resolve through this stale root, which doesn't see mounts
 /// from the overlay namespace (e.g., /run tmpfs is on a foreign mount). This
 /// causes ENOENT when creating volume mount destinations.
 ///
@@ -562,6 +1476,26 @@ fn adopt_overlay_root(overlay_root_fd: Option<&fs::File>) -> Result<()> {
     Ok(())
 }
 
+/// Join the helper's user namespace if it has its own (rootless mode): a
+/// mount namespace can only be entered from the user namespace that owns it.
+fn join_helper_user_namespace(pid: i32) -> Result<()> {
+    use std::os::unix::fs::MetadataExt;
+    let helper_ns = format!("/proc/{}/ns/user", pid);
+    let theirs = fs::metadata(&helper_ns)
+        .with_context(|| format!("stat {}", helper_ns))?
+        .ino();
+    let ours = fs::metadata("/proc/self/ns/user")
+        .context("stat /proc/self/ns/user")?
+        .ino();
+    if theirs == ours {
+        return Ok(());
+    }
+    let f = fs::File::open(&helper_ns).with_context(|| format!("opening {}", helper_ns))?;
+    setns(&f, CloneFlags::CLONE_NEWUSER).context("setns into helper user namespace")?;
+    info!("overlay: joined helper user namespace (pid={})", pid);
+    Ok(())
+}
+
 /// Join an existing shared mount namespace via setns().
 ///
 /// Tries the bind-mount path first (normal case). If that fails, falls back
@@ -607,6 +1541,7 @@ fn join_namespace(ns_path: &Path) -> Result<()> {
         );
     }
 
+    join_helper_user_namespace(pid)?;
     let ns_proc_path = format!("/proc/{}/ns/mnt", pid);
     let f = fs::File::open(&ns_proc_path)
         .with_context(|| format!("opening helper namespace at {}", ns_proc_path))?;
@@ -620,6 +1555,223 @@ fn join_namespace(ns_path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Read `/proc/self/mountinfo` as seen from inside the overlay namespace.
+///
+/// Returns `None` if the namespace has not been created yet. Joins the
+/// namespace in the calling process, so only use it from a one-shot command.
+///
+/// Tested by kind-integration (requires root + Linux namespaces).
+#[cfg(not(tarpaulin_include))]
+pub fn read_namespace_mountinfo(config: &OverlayConfig) -> Result<Option<String>> {
+    use nix::fcntl::{openat, OFlag};
+    use nix::sys::stat::Mode;
+    use std::io::Read;
+    use std::os::unix::io::FromRawFd;
+
+    if !namespace_exists(&config.ns_path) {
+        return Ok(None);
+    }
+
+    // Open /proc before joining: the overlay root may not have it mounted.
+    let proc_dir = fs::File::open("/proc").context("opening /proc")?;
+    join_namespace(&config.ns_path)?;
+
+    let fd = openat(
+        Some(proc_dir.as_raw_fd()),
+        "thread-self/mountinfo",
+        OFlag::O_RDONLY | OFlag::O_CLOEXEC,
+        Mode::empty(),
+    )
+    .context("opening mountinfo inside overlay namespace")?;
+    // SAFETY: openat returned a fresh fd that nothing else owns
+    let mut file = unsafe { fs::File::from_raw_fd(fd) };
+    let mut content = String::new();
+    file.read_to_string(&mut content)
+        .context("reading mountinfo")?;
+    Ok(Some(content))
+}
+
+/// Kernel pseudo filesystems hidden from `overlay-mounts` unless `--all`.
+const PSEUDO_FSTYPES: &[&str] = &[
+    "autofs",
+    "binfmt_misc",
+    "bpf",
+    "cgroup",
+    "cgroup2",
+    "configfs",
+    "debugfs",
+    "devpts",
+    "fusectl",
+    "hugetlbfs",
+    "mqueue",
+    "nsfs",
+    "proc",
+    "pstore",
+    "securityfs",
+    "sysfs",
+    "tracefs",
+];
+
+/// One parsed line of `/proc/<pid>/mountinfo`.
+#[derive(Debug, PartialEq)]
+pub struct MountInfoEntry {
+    pub mount_point: String,
+    /// Path within the source filesystem; not `/` for bind mounts of subtrees
+    pub root: String,
+    pub fstype: String,
+    pub source: String,
+    pub options: String,
+}
+
+/// Undo the kernel's octal escaping of space, tab, newline and backslash.
+fn unescape_mountinfo(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && i + 3 < bytes.len() {
+            let digits = std::str::from_utf8(&bytes[i + 1..i + 4]).unwrap_or("");
+            if let Ok(b) = u8::from_str_radix(digits, 8) {
+                out.push(b);
+                i += 4;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Parse mountinfo content, skipping malformed lines.
+///
+/// Format: `id parent maj:min root mount-point options [optional...] - fstype source super-options`
+pub fn parse_mountinfo(content: &str) -> Vec<MountInfoEntry> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (pre, post) = line.split_once(" - ")?;
+            let pre: Vec<&str> = pre.split(' ').collect();
+            let post: Vec<&str> = post.split(' ').collect();
+            if pre.len() < 6 || post.len() < 2 {
+                return None;
+            }
+            Some(MountInfoEntry {
+                root: unescape_mountinfo(pre[3]),
+                mount_point: unescape_mountinfo(pre[4]),
+                options: pre[5].to_string(),
+                fstype: post[0].to_string(),
+                source: unescape_mountinfo(post[1]),
+            })
+        })
+        .collect()
+}
+
+/// Render the volume mounts recorded for a container as an aligned table,
+/// marking each as live if `mountinfo` (from the container's overlay
+/// namespace) still has a mount at its destination. `None` means the
+/// namespace couldn't be read, so liveness is unknown.
+pub fn format_volume_mounts(recorded: &[AppliedMount], mountinfo: Option<&str>) -> String {
+    let mount_points: Option<Vec<String>> = mountinfo.map(|content| {
+        parse_mountinfo(content)
+            .into_iter()
+            .map(|e| e.mount_point)
+            .collect()
+    });
+    let rows: Vec<[String; 5]> = recorded
+        .iter()
+        .map(|m| {
+            let live = match &mount_points {
+                Some(points) if points.contains(&m.destination) => "yes",
+                Some(_) => "no",
+                None => "unknown",
+            };
+            let options = if m.options.is_empty() {
+                "-".to_string()
+            } else {
+                m.options.join(",")
+            };
+            [
+                m.destination.clone(),
+                m.source.clone(),
+                if m.cross_namespace { "host-ns" } else { "bind" }.to_string(),
+                options,
+                live.to_string(),
+            ]
+        })
+        .collect();
+
+    let header = ["TARGET", "SOURCE", "VIA", "OPTIONS", "LIVE"].map(String::from);
+    let mut widths = [0usize; 4];
+    for row in std::iter::once(&header).chain(rows.iter()) {
+        for (w, cell) in widths.iter_mut().zip(row.iter()) {
+            *w = (*w).max(cell.len());
+        }
+    }
+
+    let mut out = String::new();
+    for row in std::iter::once(&header).chain(rows.iter()) {
+        out.push_str(&format!(
+            "{:<w0$}  {:<w1$}  {:<w2$}  {:<w3$}  {}\n",
+            row[0],
+            row[1],
+            row[2],
+            row[3],
+            row[4],
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2],
+            w3 = widths[3],
+        ));
+    }
+    out
+}
+
+/// Render mountinfo as an aligned table, findmnt style (`source[root]` for
+/// subtree binds). Pseudo filesystems are dropped unless `all` is set.
+pub fn format_mountinfo(content: &str, all: bool) -> String {
+    let rows: Vec<[String; 4]> = parse_mountinfo(content)
+        .into_iter()
+        .filter(|e| all || !PSEUDO_FSTYPES.contains(&e.fstype.as_str()))
+        .map(|e| {
+            let source = if e.root == "/" {
+                e.source
+            } else {
+                format!("{}[{}]", e.source, e.root)
+            };
+            [e.mount_point, e.fstype, source, e.options]
+        })
+        .collect();
+
+    let header = [
+        "TARGET".to_string(),
+        "FSTYPE".to_string(),
+        "SOURCE".to_string(),
+        "OPTIONS".to_string(),
+    ];
+    let mut widths = [0usize; 3];
+    for row in std::iter::once(&header).chain(rows.iter()) {
+        for (w, cell) in widths.iter_mut().zip(row.iter()) {
+            *w = (*w).max(cell.len());
+        }
+    }
+
+    let mut out = String::new();
+    for row in std::iter::once(&header).chain(rows.iter()) {
+        out.push_str(&format!(
+            "{:<w0$}  {:<w1$}  {:<w2$}  {}\n",
+            row[0],
+            row[1],
+            row[2],
+            row[3],
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2],
+        ));
+    }
+    out
+}
+
 /// Create the shared mount namespace with overlay filesystem.
 ///
 /// Uses an inner fork:
@@ -628,7 +1780,7 @@ fn join_namespace(ns_path: &Path) -> Result<()> {
 ///
 /// Tested by kind-integration (requires root + Linux namespaces).
 #[cfg(not(tarpaulin_include))]
-fn create_namespace(config: &OverlayConfig) -> Result<()> {
+fn create_namespace(config: &OverlayConfig, oci_mounts: &[super::OciMount]) -> Result<()> {
     let upper_dir = config.base_dir.join("upper");
     let work_dir = config.base_dir.join("work");
     let merged_dir = config.merged_dir.clone();
@@ -645,7 +1797,7 @@ fn create_namespace(config: &OverlayConfig) -> Result<()> {
         ForkResult::Child => {
             // Inner child: create the namespace and set up overlay
             drop(read_fd);
-            let _ = inner_child_setup(config, &merged_dir, write_fd);
+            let _ = inner_child_setup(config, &merged_dir, oci_mounts, write_fd);
             // If we get here, something went wrong; exit
             std::process::exit(1);
         }
@@ -655,15 +1807,752 @@ fn create_namespace(config: &OverlayConfig) -> Result<()> {
             inner_parent_persist(config, helper_pid, read_fd)?;
         }
     }
-
+
+    Ok(())
+}
+
+/// What to do when the overlay filesystem cannot be mounted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlayFallback {
+    /// Refuse to run workloads (default; overlay is mandatory)
+    None,
+    /// Read-only bind of the host root with tmpfs over the writable paths
+    Bind,
+}
+
+/// Read the overlay fallback mode from config.
+///
+/// - `REAPER_OVERLAY_FALLBACK`: "none" (default) or "bind"
+pub fn read_overlay_fallback() -> OverlayFallback {
+    std::env::var("REAPER_OVERLAY_FALLBACK")
+        .map(|v| match v.to_ascii_lowercase().as_str() {
+            "bind" => OverlayFallback::Bind,
+            _ => OverlayFallback::None,
+        })
+        .unwrap_or(OverlayFallback::None)
+}
+
+/// What to do after the overlay mount failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlayMountRecovery {
+    /// Clear the work dir's leftovers and mount once more
+    CleanWorkDirAndRetry,
+    /// Not a work dir problem, or the retry already failed
+    GiveUp,
+}
+
+/// Whether an overlay mount error looks like a work dir left dirty by a
+/// crash (EBUSY/ESTALE) and is worth one cleanup-and-retry.
+pub fn overlay_mount_recovery(errno: nix::errno::Errno, retried: bool) -> OverlayMountRecovery {
+    use nix::errno::Errno;
+    match errno {
+        Errno::EBUSY | Errno::ESTALE if !retried => OverlayMountRecovery::CleanWorkDirAndRetry,
+        _ => OverlayMountRecovery::GiveUp,
+    }
+}
+
+/// Remove the kernel-owned leftovers in an overlay work dir (`work/` and
+/// `incompat/`), the documented overlayfs recovery. The kernel recreates
+/// both on mount. Returns the entries removed.
+pub fn clean_overlay_work_dir(work_dir: &Path) -> Vec<PathBuf> {
+    ["work", "incompat"]
+        .iter()
+        .map(|name| work_dir.join(name))
+        .filter(|path| path.exists())
+        .filter(|path| match fs::remove_dir_all(path) {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::warn!("overlay: failed to remove {}: {}", path.display(), e);
+                false
+            }
+        })
+        .collect()
+}
+
+/// Mount the overlay on `merged_dir`, retrying once after cleaning the work
+/// dir when the first attempt fails with EBUSY/ESTALE.
+#[cfg(not(tarpaulin_include))]
+fn mount_overlay_with_recovery(merged_dir: &Path, opts: &str, base_dir: &Path) -> Result<()> {
+    let attempt = || {
+        mount(
+            Some("overlay"),
+            merged_dir,
+            Some("overlay"),
+            MsFlags::empty(),
+            Some(opts),
+        )
+    };
+    let mut result = attempt();
+    if let Err(e) = result {
+        if overlay_mount_recovery(e, false) == OverlayMountRecovery::CleanWorkDirAndRetry {
+            let removed = clean_overlay_work_dir(&base_dir.join("work"));
+            tracing::warn!(
+                "overlay: mount failed with {}, cleared {:?} from the work dir and retrying",
+                e,
+                removed
+            );
+            result = attempt();
+        }
+    }
+    match result {
+        Ok(()) => Ok(()),
+        Err(e) if matches!(e, nix::errno::Errno::EBUSY | nix::errno::Errno::ESTALE) => Err(e)
+            .with_context(|| {
+                format!(
+                    "mounting overlay: the upper/work dirs under {0} look corrupt or are \
+                     still in use after a cleanup retry; stop the workloads using them, then \
+                     remove {0}/upper and {0}/work to reset (this discards the shared \
+                     writable layer)",
+                    base_dir.display()
+                )
+            }),
+        Err(e) => Err(e).context("mounting overlay"),
+    }
+}
+
+/// Paths that get a private tmpfs in bind fallback mode, with tmpfs options.
+///
+/// /run is not listed: it stays bind-mounted from the host, as in overlay
+/// mode, because state files and I/O FIFOs live there.
+const BIND_FALLBACK_WRITABLE: &[(&str, &str)] = &[("tmp", "mode=1777"), ("var", "mode=0755")];
+
+/// One mount(2) call of a mount plan.
+#[derive(Debug, PartialEq)]
+pub struct PlannedMount {
+    pub source: Option<PathBuf>,
+    pub target: PathBuf,
+    pub fstype: Option<&'static str>,
+    pub flags: MsFlags,
+    pub data: Option<String>,
+}
+
+/// Mounts that build the bind fallback root at `merged_dir`.
+///
+/// The host root is bind-mounted recursively (bringing /proc, /sys, /dev and
+/// /run along) and its top-level mount made read-only; separate host
+/// filesystems below it keep their own flags. Writable paths get tmpfs.
+pub fn bind_fallback_plan(merged_dir: &Path) -> Vec<PlannedMount> {
+    let mut plan = vec![
+        PlannedMount {
+            source: Some(PathBuf::from("/")),
+            target: merged_dir.to_path_buf(),
+            fstype: None,
+            flags: MsFlags::MS_BIND | MsFlags::MS_REC,
+            data: None,
+        },
+        PlannedMount {
+            source: None,
+            target: merged_dir.to_path_buf(),
+            fstype: None,
+            flags: MsFlags::MS_BIND | MsFlags::MS_REMOUNT | MsFlags::MS_RDONLY,
+            data: None,
+        },
+    ];
+    plan.extend(
+        BIND_FALLBACK_WRITABLE
+            .iter()
+            .map(|(dir, opts)| PlannedMount {
+                source: Some(PathBuf::from("tmpfs")),
+                target: merged_dir.join(dir),
+                fstype: Some("tmpfs"),
+                flags: MsFlags::MS_NOSUID | MsFlags::MS_NODEV,
+                data: Some(opts.to_string()),
+            }),
+    );
+    plan
+}
+
+/// Tmpfs mounts for `REAPER_WRITABLE_PATHS` under `merged_dir`, applied
+/// after the bind fallback plan so a path below /tmp or /var lands on top.
+pub fn writable_paths_plan(merged_dir: &Path, paths: &[PathBuf]) -> Vec<PlannedMount> {
+    paths
+        .iter()
+        .map(|path| PlannedMount {
+            source: Some(PathBuf::from("tmpfs")),
+            target: merged_dir.join(path.strip_prefix("/").unwrap_or(path)),
+            fstype: Some("tmpfs"),
+            flags: MsFlags::MS_NOSUID | MsFlags::MS_NODEV,
+            data: Some("mode=0755".to_string()),
+        })
+        .collect()
+}
+
+/// Host directory holding containerd's I/O FIFOs, which the runtime opens
+/// from inside the namespace.
+const CONTAINERD_RUN_DIR: &str = "/run/containerd";
+
+/// Mounts that replace /run under `merged_dir` with a private tmpfs: the
+/// tmpfs first, then a recursive bind of each `keep` directory below /run.
+/// Directories outside /run are visible through the root already and are
+/// left out.
+pub fn run_tmpfs_plan(merged_dir: &Path, run: &RunTmpfs, keep: &[PathBuf]) -> Vec<PlannedMount> {
+    let run_dir = merged_dir.join("run");
+    let mut plan = vec![PlannedMount {
+        source: Some(PathBuf::from("tmpfs")),
+        target: run_dir.clone(),
+        fstype: Some("tmpfs"),
+        flags: MsFlags::MS_NOSUID | MsFlags::MS_NODEV,
+        data: Some(run.mount_data()),
+    }];
+    let mut kept: Vec<&Path> = Vec::new();
+    for path in keep {
+        let Ok(rel) = path.strip_prefix("/run") else {
+            continue;
+        };
+        if rel.as_os_str().is_empty() || kept.contains(&rel) {
+            continue;
+        }
+        kept.push(rel);
+        plan.push(PlannedMount {
+            source: Some(path.clone()),
+            target: run_dir.join(rel),
+            fstype: None,
+            flags: MsFlags::MS_BIND | MsFlags::MS_REC,
+            data: None,
+        });
+    }
+    plan
+}
+
+/// Mount the private /run tmpfs under `merged_dir`, keeping the state root,
+/// the filter placeholders and containerd's FIFOs reachable. Kept
+/// directories missing on the host are skipped.
+#[cfg(not(tarpaulin_include))]
+fn mount_run_tmpfs(merged_dir: &Path, run: &RunTmpfs) -> Result<()> {
+    let keep = [
+        super::state::state_dir(),
+        super::state::default_root(),
+        read_filter_config().filter_dir,
+        PathBuf::from(CONTAINERD_RUN_DIR),
+    ];
+    for m in run_tmpfs_plan(merged_dir, run, &keep) {
+        let is_bind = m.fstype.is_none();
+        if is_bind && !m.source.as_deref().is_some_and(Path::is_dir) {
+            continue;
+        }
+        fs::create_dir_all(&m.target).ok();
+        apply_mount_plan(std::slice::from_ref(&m)).context("mounting /run tmpfs")?;
+    }
+    info!("overlay: private /run tmpfs ({}) mounted", run.mount_data());
+    Ok(())
+}
+
+/// Execute a mount plan in order. Targets missing on the host are skipped.
+#[cfg(not(tarpaulin_include))]
+fn apply_mount_plan(plan: &[PlannedMount]) -> Result<()> {
+    for m in plan {
+        if !m.target.exists() {
+            tracing::warn!("overlay: skipping mount on missing {}", m.target.display());
+            continue;
+        }
+        mount(
+            m.source.as_deref(),
+            &m.target,
+            m.fstype,
+            m.flags,
+            m.data.as_deref(),
+        )
+        .with_context(|| format!("mounting {:?} on {}", m.fstype, m.target.display()))?;
+    }
+    Ok(())
+}
+
+/// devpts options when the config has no /dev/pts mount (containerd's defaults).
+const DEVPTS_DEFAULT_OPTIONS: &[&str] = &[
+    "nosuid",
+    "noexec",
+    "newinstance",
+    "ptmxmode=0666",
+    "mode=0620",
+    "gid=5",
+];
+
+/// mqueue options when the config has no /dev/mqueue mount.
+const MQUEUE_DEFAULT_OPTIONS: &[&str] = &["nosuid", "noexec", "nodev"];
+
+/// Split OCI mount options into mount(2) flags and filesystem data options.
+fn split_mount_options<S: AsRef<str>>(options: &[S]) -> (MsFlags, Vec<String>) {
+    let mut flags = MsFlags::empty();
+    let mut data = Vec::new();
+    for opt in options {
+        match opt.as_ref() {
+            "ro" => flags |= MsFlags::MS_RDONLY,
+            "rw" => {}
+            "nosuid" => flags |= MsFlags::MS_NOSUID,
+            "nodev" => flags |= MsFlags::MS_NODEV,
+            "noexec" => flags |= MsFlags::MS_NOEXEC,
+            "noatime" => flags |= MsFlags::MS_NOATIME,
+            "relatime" => flags |= MsFlags::MS_RELATIME,
+            other => data.push(other.to_string()),
+        }
+    }
+    (flags, data)
+}
+
+/// The config's options for a `fstype` mount at `destination`, if it has one.
+fn oci_mount_options<'a>(
+    oci_mounts: &'a [super::OciMount],
+    destination: &str,
+    fstype: &str,
+) -> Option<&'a [String]> {
+    oci_mounts
+        .iter()
+        .find(|m| {
+            normalize_destination(&m.destination) == destination
+                && m.mount_type.as_deref() == Some(fstype)
+        })
+        .map(|m| m.options.as_slice())
+}
+
+/// Flags and data for a fresh devpts. `newinstance` (a private PTY
+/// namespace) and a `ptmxmode` the workload can open are always present.
+pub fn devpts_mount_options(oci_options: Option<&[String]>) -> (MsFlags, String) {
+    let (flags, mut data) = match oci_options {
+        Some(opts) => split_mount_options(opts),
+        None => split_mount_options(DEVPTS_DEFAULT_OPTIONS),
+    };
+    if !data.iter().any(|o| o == "newinstance") {
+        data.push("newinstance".into());
+    }
+    if !data.iter().any(|o| o.starts_with("ptmxmode=")) {
+        data.push("ptmxmode=0666".into());
+    }
+    (flags, data.join(","))
+}
+
+/// Flags and data for a fresh mqueue.
+pub fn mqueue_mount_options(oci_options: Option<&[String]>) -> (MsFlags, String) {
+    let (flags, data) = match oci_options {
+        Some(opts) => split_mount_options(opts),
+        None => split_mount_options(MQUEUE_DEFAULT_OPTIONS),
+    };
+    (flags, data.join(","))
+}
+
+/// Mounts that cover the host's /dev/pts and /dev/mqueue (visible through
+/// the /dev rbind) with fresh instances under `merged_dir`. /dev/ptmx is
+/// bound to the new instance's ptmx so PTYs are allocated from it.
+///
+/// Rootless mode drops devpts `gid=` (unmapped in the user namespace) and
+/// mqueue (mounting it needs an IPC namespace owned by that user namespace).
+pub fn special_fs_plan(
+    merged_dir: &Path,
+    oci_mounts: &[super::OciMount],
+    rootless: bool,
+) -> Vec<PlannedMount> {
+    let (pts_flags, mut pts_data) =
+        devpts_mount_options(oci_mount_options(oci_mounts, "/dev/pts", "devpts"));
+    if rootless {
+        pts_data = pts_data
+            .split(',')
+            .filter(|o| !o.starts_with("gid="))
+            .collect::<Vec<_>>()
+            .join(",");
+    }
+    let (mq_flags, mq_data) =
+        mqueue_mount_options(oci_mount_options(oci_mounts, "/dev/mqueue", "mqueue"));
+    let non_empty = |d: String| (!d.is_empty()).then_some(d);
+    let mut plan = vec![
+        PlannedMount {
+            source: Some(PathBuf::from("devpts")),
+            target: merged_dir.join("dev/pts"),
+            fstype: Some("devpts"),
+            flags: pts_flags,
+            data: non_empty(pts_data),
+        },
+        PlannedMount {
+            source: Some(merged_dir.join("dev/pts/ptmx")),
+            target: merged_dir.join("dev/ptmx"),
+            fstype: None,
+            flags: MsFlags::MS_BIND,
+            data: None,
+        },
+        PlannedMount {
+            source: Some(PathBuf::from("mqueue")),
+            target: merged_dir.join("dev/mqueue"),
+            fstype: Some("mqueue"),
+            flags: mq_flags,
+            data: non_empty(mq_data),
+        },
+    ];
+    if rootless {
+        plan.truncate(2);
+    }
+    plan
+}
+
+/// Mount fresh devpts and mqueue instances. Non-fatal: on failure the
+/// workloads keep the host's instances.
+#[cfg(not(tarpaulin_include))]
+fn mount_special_filesystems(merged_dir: &Path, oci_mounts: &[super::OciMount]) {
+    let plan = special_fs_plan(merged_dir, oci_mounts, super::state::rootless_enabled());
+    // devpts and its ptmx bind go together; mqueue is independent
+    for group in [&plan[..2], &plan[2..]] {
+        if let Err(e) = apply_mount_plan(group) {
+            tracing::warn!("overlay: keeping host instance: {:#}", e);
+        }
+    }
+}
+
+/// Make `/proc/sys` read-only and mask `/proc/sysrq-trigger` for a
+/// non-privileged workload, leaving the rest of `/proc` writable.
+///
+/// Runs in the workload's `pre_exec`: the process moves to its own mount
+/// namespace (a slave copy of the shared overlay namespace) so the change is
+/// per-container and never propagates back to other workloads. A no-op
+/// without root, since `/proc/sys` is then not writable anyway.
+pub fn protect_proc_sys() -> std::io::Result<()> {
+    if !nix::unistd::geteuid().is_root() {
+        return Ok(());
+    }
+    unshare(CloneFlags::CLONE_NEWNS)?;
+    mount(
+        None::<&str>,
+        "/",
+        None::<&str>,
+        MsFlags::MS_REC | MsFlags::MS_SLAVE,
+        None::<&str>,
+    )?;
+
+    // Bind /proc/sys onto itself so the read-only remount covers only it
+    mount(
+        Some("/proc/sys"),
+        "/proc/sys",
+        None::<&str>,
+        MsFlags::MS_BIND | MsFlags::MS_REC,
+        None::<&str>,
+    )?;
+    mount(
+        None::<&str>,
+        "/proc/sys",
+        None::<&str>,
+        MsFlags::MS_REMOUNT
+            | MsFlags::MS_BIND
+            | MsFlags::MS_RDONLY
+            | MsFlags::MS_NOSUID
+            | MsFlags::MS_NODEV
+            | MsFlags::MS_NOEXEC,
+        None::<&str>,
+    )?;
+
+    if Path::new("/proc/sysrq-trigger").exists() {
+        mount(
+            Some("/dev/null"),
+            "/proc/sysrq-trigger",
+            None::<&str>,
+            MsFlags::MS_BIND,
+            None::<&str>,
+        )?;
+    }
+    Ok(())
+}
+
+/// Recreate the top level of a host directory (empty subdirectories with the
+/// same mode, and symlinks such as /var/run -> /run) on a fresh tmpfs.
+fn seed_tmpfs_skeleton(host_dir: &Path, target: &Path) {
+    let Ok(entries) = fs::read_dir(host_dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let Ok(meta) = fs::symlink_metadata(entry.path()) else {
+            continue;
+        };
+        let dst = target.join(entry.file_name());
+        if meta.file_type().is_symlink() {
+            if let Ok(link) = fs::read_link(entry.path()) {
+                let _ = std::os::unix::fs::symlink(link, &dst);
+            }
+        } else if meta.is_dir() && fs::create_dir(&dst).is_ok() {
+            let _ = fs::set_permissions(&dst, meta.permissions());
+        }
+    }
+}
+
+/// Build the bind fallback root at `merged_dir`, then pivot into it.
+///
+/// The read-only root has no room for an `old_root` directory, so this uses
+/// the `pivot_root(".", ".")` idiom and detaches the old root afterwards.
+#[cfg(not(tarpaulin_include))]
+fn setup_bind_fallback_root(
+    merged_dir: &Path,
+    writable_paths: &[PathBuf],
+    run_tmpfs: Option<&RunTmpfs>,
+    oci_mounts: &[super::OciMount],
+) -> Result<()> {
+    apply_mount_plan(&bind_fallback_plan(merged_dir)).context("building bind fallback root")?;
+    if let Some(run) = run_tmpfs {
+        mount_run_tmpfs(merged_dir, run)?;
+    }
+    for (dir, _) in BIND_FALLBACK_WRITABLE {
+        seed_tmpfs_skeleton(&Path::new("/").join(dir), &merged_dir.join(dir));
+    }
+    let plan: Vec<PlannedMount> = writable_paths_plan(merged_dir, writable_paths)
+        .into_iter()
+        .filter(|m| {
+            // A symlink (e.g. /var/run -> /run) would carry the tmpfs out
+            // to wherever it points
+            let is_symlink = fs::symlink_metadata(&m.target)
+                .map(|meta| meta.file_type().is_symlink())
+                .unwrap_or(false);
+            if is_symlink {
+                tracing::warn!(
+                    "overlay: not mounting a writable tmpfs on symlink {}",
+                    m.target.display()
+                );
+            }
+            !is_symlink
+        })
+        .collect();
+    for m in &plan {
+        // Only possible below a tmpfs; elsewhere the host must have the path
+        fs::create_dir_all(&m.target).ok();
+    }
+    apply_mount_plan(&plan).context("mounting REAPER_WRITABLE_PATHS")?;
+    for m in &plan {
+        if let Ok(rel) = m.target.strip_prefix(merged_dir) {
+            seed_tmpfs_skeleton(&Path::new("/").join(rel), &m.target);
+        }
+    }
+    mount_special_filesystems(merged_dir, oci_mounts);
+
+    std::env::set_current_dir(merged_dir).context("chdir to fallback root")?;
+    nix::unistd::pivot_root(".", ".").context("pivot_root to fallback root")?;
+    umount2(".", MntFlags::MNT_DETACH).context("detaching old root")?;
+    std::env::set_current_dir("/").context("chdir to new root")?;
+    Ok(())
+}
+
+/// Single-entry uid/gid map: `outside` becomes 0 inside the user namespace.
+pub fn rootless_id_map(outside: u32) -> String {
+    format!("0 {} 1\n", outside)
+}
+
+/// Unshare a user namespace (mapping the caller to root) and a mount
+/// namespace owned by it. The caller must be single-threaded.
+#[cfg(not(tarpaulin_include))]
+fn enter_rootless_namespaces() -> Result<()> {
+    let uid = nix::unistd::getuid().as_raw();
+    let gid = nix::unistd::getgid().as_raw();
+    unshare(CloneFlags::CLONE_NEWUSER | CloneFlags::CLONE_NEWNS)
+        .context("unshare CLONE_NEWUSER | CLONE_NEWNS (unprivileged user namespaces disabled?)")?;
+    // An unprivileged process may only write gid_map once setgroups is denied
+    fs::write("/proc/self/setgroups", "deny").context("denying setgroups")?;
+    fs::write("/proc/self/uid_map", rootless_id_map(uid)).context("writing uid_map")?;
+    fs::write("/proc/self/gid_map", rootless_id_map(gid)).context("writing gid_map")?;
+    info!("overlay: rootless, uid {} gid {} mapped to root", uid, gid);
+    Ok(())
+}
+
+/// How a top-level host entry is placed in the rootless root.
+#[derive(Debug, PartialEq)]
+pub enum RootlessEntry {
+    /// Bound from the host, as in overlay mode (/proc, /sys, /dev, /run)
+    Bind,
+    /// A directory with its own unprivileged overlay
+    Layered,
+    /// A symlink, recreated with the same target
+    Symlink(PathBuf),
+}
+
+/// Classify a top-level host entry; `None` for anything else (plain files).
+pub fn classify_rootless_entry(path: &Path) -> Option<RootlessEntry> {
+    let name = path.file_name()?.to_str()?;
+    let meta = fs::symlink_metadata(path).ok()?;
+    if meta.file_type().is_symlink() {
+        return fs::read_link(path).ok().map(RootlessEntry::Symlink);
+    }
+    if !meta.is_dir() {
+        return None;
+    }
+    match name {
+        "proc" | "sys" | "dev" | "run" => Some(RootlessEntry::Bind),
+        _ => Some(RootlessEntry::Layered),
+    }
+}
+
+/// Mount options for an unprivileged overlay (Linux 5.11+). `userxattr`
+/// keeps overlay metadata in `user.` xattrs, which an unprivileged mounter
+/// may set.
+pub fn rootless_overlay_options(lower: &Path, upper: &Path, work: &Path) -> String {
+    format!(
+        "lowerdir={},upperdir={},workdir={},userxattr",
+        lower.display(),
+        upper.display(),
+        work.display()
+    )
+}
+
+/// Overlay one top-level host directory at `merged_dir/<name>`.
+#[cfg(not(tarpaulin_include))]
+fn mount_rootless_layer(name: &str, base_dir: &Path, merged_dir: &Path) -> Result<()> {
+    let upper = base_dir.join("upper").join(name);
+    let work = base_dir.join("work").join(name);
+    fs::create_dir_all(&upper).context("creating layer upper dir")?;
+    fs::create_dir_all(&work).context("creating layer work dir")?;
+    let opts = rootless_overlay_options(&Path::new("/").join(name), &upper, &work);
+    mount(
+        Some("overlay"),
+        &merged_dir.join(name),
+        Some("overlay"),
+        MsFlags::empty(),
+        Some(opts.as_str()),
+    )
+    .with_context(|| format!("mounting unprivileged overlay on /{}", name))?;
+    Ok(())
+}
+
+/// Build the rootless root at `merged_dir` and pivot into it.
+///
+/// A single overlay with `lowerdir=/` is refused in a user namespace (the
+/// host root's submounts are locked), so the root is a tmpfs holding one
+/// overlay per top-level directory. A directory whose overlay fails falls
+/// back to a tmpfs (/tmp, /var) or a read-only bind of the host's.
+#[cfg(not(tarpaulin_include))]
+fn setup_rootless_root(
+    config: &OverlayConfig,
+    merged_dir: &Path,
+    oci_mounts: &[super::OciMount],
+) -> Result<()> {
+    mount(
+        Some("tmpfs"),
+        merged_dir,
+        Some("tmpfs"),
+        MsFlags::MS_NOSUID | MsFlags::MS_NODEV,
+        Some("mode=0755"),
+    )
+    .context("mounting rootless root tmpfs")?;
+
+    let mut entries: Vec<PathBuf> = fs::read_dir("/")
+        .context("listing host root")?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .collect();
+    entries.sort();
+
+    for path in entries {
+        let Some(kind) = classify_rootless_entry(&path) else {
+            continue;
+        };
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let target = merged_dir.join(name);
+        match kind {
+            RootlessEntry::Symlink(dest) => {
+                std::os::unix::fs::symlink(&dest, &target)
+                    .with_context(|| format!("recreating symlink /{}", name))?;
+            }
+            RootlessEntry::Bind => {
+                fs::create_dir_all(&target).ok();
+                mount(
+                    Some(&path),
+                    &target,
+                    None::<&str>,
+                    MsFlags::MS_BIND | MsFlags::MS_REC,
+                    None::<&str>,
+                )
+                .with_context(|| format!("bind-mounting /{}", name))?;
+            }
+            RootlessEntry::Layered => {
+                fs::create_dir_all(&target).ok();
+                if let Err(e) = mount_rootless_layer(name, &config.base_dir, merged_dir) {
+                    tracing::warn!("overlay: {:#}, falling back", e);
+                    mount_rootless_fallback(name, &path, &target)?;
+                }
+            }
+        }
+    }
+
+    mount_special_filesystems(merged_dir, oci_mounts);
+    copy_etc_files(Path::new("/etc"), &merged_dir.join("etc"));
+
+    let old_root = merged_dir.join("old_root");
+    fs::create_dir_all(&old_root).context("creating old_root")?;
+    nix::unistd::pivot_root(merged_dir, &old_root).context("pivot_root")?;
+    std::env::set_current_dir("/").context("chdir to new root")?;
+    umount2("/old_root", MntFlags::MNT_DETACH).context("unmounting old root")?;
+    fs::remove_dir("/old_root").ok();
+    Ok(())
+}
+
+/// The mount flags of `statvfs` flags that a user namespace can't clear.
+fn locked_mount_flags(st: nix::sys::statvfs::FsFlags) -> MsFlags {
+    use nix::sys::statvfs::FsFlags;
+    [
+        (FsFlags::ST_NOSUID, MsFlags::MS_NOSUID),
+        (FsFlags::ST_NODEV, MsFlags::MS_NODEV),
+        (FsFlags::ST_NOEXEC, MsFlags::MS_NOEXEC),
+    ]
+    .into_iter()
+    .filter(|(f, _)| st.contains(*f))
+    .fold(MsFlags::empty(), |acc, (_, m)| acc | m)
+}
+
+/// Fallback for a directory that couldn't get its own overlay: a private
+/// tmpfs for the writable paths, otherwise a read-only bind of the host's.
+#[cfg(not(tarpaulin_include))]
+fn mount_rootless_fallback(name: &str, host: &Path, target: &Path) -> Result<()> {
+    if let Some((_, opts)) = BIND_FALLBACK_WRITABLE.iter().find(|(d, _)| *d == name) {
+        mount(
+            Some("tmpfs"),
+            target,
+            Some("tmpfs"),
+            MsFlags::MS_NOSUID | MsFlags::MS_NODEV,
+            Some(*opts),
+        )
+        .with_context(|| format!("mounting tmpfs on /{}", name))?;
+        seed_tmpfs_skeleton(host, target);
+        return Ok(());
+    }
+    mount(
+        Some(host),
+        target,
+        None::<&str>,
+        MsFlags::MS_BIND | MsFlags::MS_REC,
+        None::<&str>,
+    )
+    .with_context(|| format!("bind-mounting /{}", name))?;
+    // Best effort: flags locked by the user namespace must be kept on remount
+    let flags = nix::sys::statvfs::statvfs(host)
+        .map(|st| locked_mount_flags(st.flags()))
+        .unwrap_or(MsFlags::empty());
+    if let Err(e) = mount(
+        None::<&str>,
+        target,
+        None::<&str>,
+        MsFlags::MS_BIND | MsFlags::MS_REMOUNT | MsFlags::MS_RDONLY | flags,
+        None::<&str>,
+    ) {
+        tracing::warn!(
+            "overlay: /{} stays writable (read-only remount: {})",
+            name,
+            e
+        );
+    }
     Ok(())
 }
 
 /// Inner child: creates the mount namespace, mounts overlay, pivots root.
 #[cfg(not(tarpaulin_include))]
-fn inner_child_setup(config: &OverlayConfig, merged_dir: &Path, write_fd: OwnedFd) -> Result<()> {
-    // 1. Create new mount namespace
-    unshare(CloneFlags::CLONE_NEWNS).context("unshare CLONE_NEWNS")?;
+fn inner_child_setup(
+    config: &OverlayConfig,
+    merged_dir: &Path,
+    oci_mounts: &[super::OciMount],
+    write_fd: OwnedFd,
+) -> Result<()> {
+    let rootless = super::state::rootless_enabled();
+    if rootless && config.image_rootfs.is_some() {
+        bail!("image rootfs is not supported in rootless mode");
+    }
+    if rootless && config.run_tmpfs.is_some() {
+        bail!("REAPER_RUN_TMPFS is not supported in rootless mode");
+    }
+
+    // 1. Create new mount namespace (inside a new user namespace when rootless)
+    if rootless {
+        enter_rootless_namespaces()?;
+    } else {
+        unshare(CloneFlags::CLONE_NEWNS).context("unshare CLONE_NEWNS")?;
+    }
 
     // 2. Make all existing mounts private (prevent propagation to host)
     mount(
@@ -675,27 +2564,50 @@ fn inner_child_setup(config: &OverlayConfig, merged_dir: &Path, write_fd: OwnedF
     )
     .context("making mounts private")?;
 
+    if rootless {
+        setup_rootless_root(config, merged_dir, oci_mounts)?;
+        return finish_namespace_setup(config, write_fd);
+    }
+
     // 3. Mount overlay on the merged directory
     let opts = format!(
-        "lowerdir=/,upperdir={},workdir={}",
+        "lowerdir={},upperdir={},workdir={}",
+        compose_lowerdir(config.image_rootfs.as_deref())?,
         config.base_dir.join("upper").display(),
         config.base_dir.join("work").display(),
     );
-    mount(
-        Some("overlay"),
-        merged_dir,
-        Some("overlay"),
-        MsFlags::empty(),
-        Some(opts.as_str()),
-    )
-    .context("mounting overlay")?;
+    if let Err(e) = mount_overlay_with_recovery(merged_dir, &opts, &config.base_dir) {
+        if read_overlay_fallback() != OverlayFallback::Bind {
+            return Err(e);
+        }
+        if config.image_rootfs.is_some() {
+            return Err(e).context("bind fallback cannot layer an image rootfs");
+        }
+        tracing::warn!(
+            "overlay: overlayfs unavailable ({:#}); REAPER_OVERLAY_FALLBACK=bind is active, \
+             running workloads on a read-only bind of the host root with tmpfs /tmp and /var \
+             (weaker isolation, no shared writable layer)",
+            e
+        );
+        setup_bind_fallback_root(
+            merged_dir,
+            &config.writable_paths,
+            config.run_tmpfs.as_ref(),
+            oci_mounts,
+        )?;
+        return finish_namespace_setup(config, write_fd);
+    }
 
     // 4. Bind-mount special filesystems into the merged root.
     // ONLY kernel-backed filesystems (/proc, /sys, /dev) and /run (needed for
     // state file communication between daemon and shim) are bind-mounted.
     // /tmp is NOT bind-mounted — writes to /tmp go through the overlay upper
-    // layer, protecting the host filesystem.
+    // layer, protecting the host filesystem. With REAPER_RUN_TMPFS, /run is a
+    // private tmpfs holding only the directories reaper needs from the host.
     for dir in &["proc", "sys", "dev", "run"] {
+        if *dir == "run" && config.run_tmpfs.is_some() {
+            continue;
+        }
         let src = PathBuf::from("/").join(dir);
         let dst = merged_dir.join(dir);
         if src.exists() && src.is_dir() {
@@ -711,6 +2623,13 @@ fn inner_child_setup(config: &OverlayConfig, merged_dir: &Path, write_fd: OwnedF
         }
     }
 
+    if let Some(ref run) = config.run_tmpfs {
+        mount_run_tmpfs(merged_dir, run)?;
+    }
+
+    // 4.5. Fresh devpts/mqueue instances instead of the host's from the /dev rbind
+    mount_special_filesystems(merged_dir, oci_mounts);
+
     // Copy resolver/hosts config into the namespace so workloads can override them if needed.
     copy_etc_files(Path::new("/etc"), &merged_dir.join("etc"));
 
@@ -727,10 +2646,23 @@ fn inner_child_setup(config: &OverlayConfig, merged_dir: &Path, write_fd: OwnedF
     umount2("/old_root", MntFlags::MNT_DETACH).context("unmounting old root")?;
     fs::remove_dir("/old_root").ok();
 
+    finish_namespace_setup(config, write_fd)
+}
+
+/// Inner child, once its root is in place: filter sensitive paths, tell the
+/// parent the namespace is ready and stay alive as its anchor.
+#[cfg(not(tarpaulin_include))]
+fn finish_namespace_setup(config: &OverlayConfig, write_fd: OwnedFd) -> Result<()> {
     // 7.5. Filter sensitive host paths
     let filter_config = read_filter_config();
-    if let Err(e) = filter_sensitive_paths(&filter_config) {
+    let placeholder_dir = filter_scope_dir(&filter_config.filter_dir, &config.ns_path);
+    if let Err(e) = filter_sensitive_paths(&filter_config, &placeholder_dir) {
         tracing::error!("filter: failed to filter sensitive paths: {:#}", e);
+        if let Some(failure) = e.downcast_ref::<FilterFailure>() {
+            // Strict mode: fail closed and tell the parent which path failed
+            let _ = nix::unistd::write(&write_fd, &encode_filter_failure(failure));
+            return Err(e);
+        }
         // Non-fatal: log error but continue (graceful degradation)
     }
 
@@ -757,54 +2689,64 @@ fn inner_parent_persist(
     read_fd: OwnedFd,
 ) -> Result<()> {
     // 1. Wait for helper to signal namespace is ready
-    let mut buf = [0u8; 1];
+    let mut buf = [0u8; 4096];
     let n = nix::unistd::read(read_fd.as_raw_fd(), &mut buf).context("reading from helper pipe")?;
     drop(read_fd);
 
+    if n > 0 && buf[0] == b'F' {
+        return Err(parse_filter_failure(&buf[1..n]).into());
+    }
     if n == 0 || buf[0] != b'R' {
         bail!("helper child failed to create namespace");
     }
 
-    // 2. Record helper PID and namespace inode (used as fallback if bind-mount fails)
+    // 2. Record helper PID and namespace inode (used as fallback if bind-mount
+    //    fails, and to stop the helper on teardown)
     let ns_source = format!("/proc/{}/ns/mnt", helper_pid);
-    let ns_inode =
-        get_ns_inode(helper_pid.as_raw()).context("getting namespace inode from helper")?;
+    record_anchor_helper(&config.ns_path, helper_pid.as_raw())?;
     let pid_path = helper_pid_path(&config.ns_path);
-    write_helper_info(&pid_path, helper_pid.as_raw(), ns_inode)
-        .context("writing helper PID file")?;
 
-    // 3. Try to persist namespace via bind-mount from HOST namespace
+    // 3. Try to persist namespace via bind-mount from HOST namespace.
+    //    Rootless mode can't bind-mount namespace files; it always uses the PID file.
     if let Some(parent) = config.ns_path.parent() {
         fs::create_dir_all(parent).context("creating ns dir")?;
     }
-    fs::File::create(&config.ns_path).context("creating ns file")?;
 
-    let bind_mount_ok = match mount(
-        Some(ns_source.as_str()),
-        &config.ns_path,
-        None::<&str>,
-        MsFlags::MS_BIND,
-        None::<&str>,
-    ) {
-        Ok(()) => {
-            info!(
-                "overlay: namespace persisted at {}",
-                config.ns_path.display()
-            );
-            true
-        }
-        Err(nix::errno::Errno::EINVAL) => {
-            tracing::warn!(
-                "overlay: bind-mount namespace returned EINVAL (nested container?), \
-                 falling back to PID file at {}",
-                pid_path.display()
-            );
-            // Remove the empty ns file — it's not useful without a bind-mount
-            let _ = fs::remove_file(&config.ns_path);
-            false
-        }
-        Err(e) => {
-            bail!("bind-mounting namespace: {}", e);
+    let bind_mount_ok = if super::state::rootless_enabled() {
+        info!(
+            "overlay: rootless mode, namespace tracked via PID file at {}",
+            pid_path.display()
+        );
+        false
+    } else {
+        fs::File::create(&config.ns_path).context("creating ns file")?;
+        match mount(
+            Some(ns_source.as_str()),
+            &config.ns_path,
+            None::<&str>,
+            MsFlags::MS_BIND,
+            None::<&str>,
+        ) {
+            Ok(()) => {
+                info!(
+                    "overlay: namespace persisted at {}",
+                    config.ns_path.display()
+                );
+                true
+            }
+            Err(nix::errno::Errno::EINVAL) => {
+                tracing::warn!(
+                    "overlay: bind-mount namespace returned EINVAL (nested container?), \
+                     falling back to PID file at {}",
+                    pid_path.display()
+                );
+                // Remove the empty ns file — it's not useful without a bind-mount
+                let _ = fs::remove_file(&config.ns_path);
+                false
+            }
+            Err(e) => {
+                bail!("bind-mounting namespace: {}", e);
+            }
         }
     };
 
@@ -823,6 +2765,7 @@ fn inner_parent_persist(
         // Join directly via /proc/<pid>/ns/mnt (EINVAL fallback)
         // Open overlay root fd BEFORE setns (while /proc is still accessible)
         let overlay_root_fd = open_overlay_root_fd(helper_pid.as_raw());
+        join_helper_user_namespace(helper_pid.as_raw())?;
         let f = fs::File::open(&ns_source)
             .with_context(|| format!("opening namespace at {}", ns_source))?;
         setns(&f, CloneFlags::CLONE_NEWNS).context("setns into shared namespace")?;
@@ -835,11 +2778,12 @@ fn inner_parent_persist(
 }
 
 /// Filter sensitive host paths by bind-mounting empty placeholders over them.
-/// Called AFTER pivot_root, in the new mount namespace.
+/// Called AFTER pivot_root, in the new mount namespace. Placeholders live in
+/// `placeholder_dir` (see [`filter_scope_dir`]).
 ///
 /// Tested by kind-integration tests (requires root + Linux namespaces).
 #[cfg(not(tarpaulin_include))]
-fn filter_sensitive_paths(config: &FilterConfig) -> Result<()> {
+fn filter_sensitive_paths(config: &FilterConfig, placeholder_dir: &Path) -> Result<()> {
     if !config.enabled {
         info!("filter: sensitive file filtering disabled");
         return Ok(());
@@ -864,46 +2808,138 @@ fn filter_sensitive_paths(config: &FilterConfig) -> Result<()> {
     }
 
     // Create filter directory
-    fs::create_dir_all(&config.filter_dir).context("creating filter directory")?;
+    fs::create_dir_all(placeholder_dir).context("creating filter directory")?;
+
+    let filtered_count = apply_filters(&paths, config.strict, |path| {
+        filter_single_path(path, placeholder_dir)
+    })?;
+
+    info!("filter: filtered {} sensitive paths", filtered_count);
+    Ok(())
+}
 
-    // Filter each path
+/// Run `filter_one` over `paths` and return how many were filtered. A failure
+/// is logged and skipped, or, when `strict`, returned as a [`FilterFailure`]
+/// naming the path.
+fn apply_filters(
+    paths: &[PathBuf],
+    strict: bool,
+    mut filter_one: impl FnMut(&Path) -> Result<()>,
+) -> Result<usize> {
     let mut filtered_count = 0;
-    for path in &paths {
-        match filter_single_path(path, &config.filter_dir) {
+    for path in paths {
+        match filter_one(path) {
             Ok(_) => {
                 filtered_count += 1;
                 tracing::debug!("filter: filtered {}", path.display());
             }
+            Err(e) if strict => {
+                return Err(FilterFailure {
+                    path: path.clone(),
+                    reason: format!("{:#}", e),
+                }
+                .into());
+            }
             Err(e) => {
                 tracing::warn!("filter: failed to filter {}: {}", path.display(), e);
             }
         }
     }
+    Ok(filtered_count)
+}
 
-    info!("filter: filtered {} sensitive paths", filtered_count);
-    Ok(())
+/// Message the helper child sends over the coordination pipe instead of `R`
+/// when strict filtering failed: `F<path>\0<reason>`.
+fn encode_filter_failure(failure: &FilterFailure) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+    let mut msg = vec![b'F'];
+    msg.extend_from_slice(failure.path.as_os_str().as_bytes());
+    msg.push(0);
+    msg.extend_from_slice(failure.reason.as_bytes());
+    msg
+}
+
+/// Inverse of [`encode_filter_failure`], given the bytes after the `F`.
+fn parse_filter_failure(msg: &[u8]) -> FilterFailure {
+    use std::os::unix::ffi::OsStrExt;
+    let (path, reason) = match msg.iter().position(|&b| b == 0) {
+        Some(i) => (&msg[..i], &msg[i + 1..]),
+        None => (msg, &b""[..]),
+    };
+    FilterFailure {
+        path: PathBuf::from(std::ffi::OsStr::from_bytes(path)),
+        reason: String::from_utf8_lossy(reason).into_owned(),
+    }
+}
+
+/// Placeholder directory for the overlay whose namespace is persisted at
+/// `ns_path`: `<filter_dir>/<ns file name>` (e.g. `default`, `default--group`,
+/// `shared-mnt-ns`). Scoping placeholders per namespace lets namespace GC
+/// remove them together with the namespace.
+pub fn filter_scope_dir(filter_dir: &Path, ns_path: &Path) -> PathBuf {
+    match ns_path.file_name() {
+        Some(name) => filter_dir.join(name),
+        None => filter_dir.join("shared-mnt-ns"),
+    }
+}
+
+/// Remove the filter placeholders created for the namespace persisted at
+/// `ns_path`. Best effort: a missing directory is not an error.
+pub fn remove_filter_placeholders(filter_dir: &Path, ns_path: &Path) {
+    let dir = filter_scope_dir(filter_dir, ns_path);
+    if let Err(e) = fs::remove_dir_all(&dir) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            tracing::warn!("filter: removing {}: {}", dir.display(), e);
+        }
+    }
 }
 
 /// Filter a single path by bind-mounting a placeholder over it.
 ///
+/// Tested by kind-integration tests (requires root + Linux namespaces).
+#[cfg(not(tarpaulin_include))]
+fn filter_single_path(path: &Path, placeholder_dir: &Path) -> Result<()> {
+    let Some(placeholder) = ensure_placeholder(path, placeholder_dir)? else {
+        tracing::debug!("filter: {} does not exist, skipping", path.display());
+        return Ok(());
+    };
+
+    // Bind-mount placeholder over sensitive path
+    mount(
+        Some(&placeholder),
+        path,
+        None::<&str>,
+        MsFlags::MS_BIND,
+        None::<&str>,
+    )
+    .with_context(|| format!("bind-mounting filter over {}", path.display()))?;
+
+    Ok(())
+}
+
+/// Return the placeholder for `path` in `placeholder_dir`, creating it only if
+/// it does not exist yet. Returns `None` when `path` does not exist on the host.
+///
 /// For shadow-format files (`/etc/shadow`, `/etc/gshadow`), the placeholder is a
 /// sanitized copy with password hashes replaced by `!` so that tools like `useradd`,
 /// `groupadd`, and package post-install scripts can still write to these files.
 /// For all other paths, the placeholder is an empty file or directory.
 ///
-/// Tested by kind-integration tests (requires root + Linux namespaces).
-#[cfg(not(tarpaulin_include))]
-fn filter_single_path(path: &Path, filter_dir: &Path) -> Result<()> {
+/// An existing placeholder is reused as is, so a namespace rebuilt after a
+/// node restart keeps the edits workloads made to its sanitized shadow copy.
+fn ensure_placeholder(path: &Path, placeholder_dir: &Path) -> Result<Option<PathBuf>> {
     // Skip if path doesn't exist on host
     if !path.exists() {
-        tracing::debug!("filter: {} does not exist, skipping", path.display());
-        return Ok(());
+        return Ok(None);
     }
 
-    // Create placeholder (file or directory)
     let sanitized = path.to_string_lossy().replace('/', "_");
-    let placeholder = filter_dir.join(sanitized);
+    let placeholder = placeholder_dir.join(sanitized);
+    if placeholder.exists() {
+        return Ok(Some(placeholder));
+    }
 
+    // Create placeholder (file or directory)
     if path.is_dir() {
         fs::create_dir_all(&placeholder)
             .with_context(|| format!("creating placeholder dir for {}", path.display()))?;
@@ -919,17 +2955,7 @@ fn filter_single_path(path: &Path, filter_dir: &Path) -> Result<()> {
             .with_context(|| format!("creating placeholder file for {}", path.display()))?;
     }
 
-    // Bind-mount placeholder over sensitive path
-    mount(
-        Some(&placeholder),
-        path,
-        None::<&str>,
-        MsFlags::MS_BIND,
-        None::<&str>,
-    )
-    .with_context(|| format!("bind-mounting filter over {}", path.display()))?;
-
-    Ok(())
+    Ok(Some(placeholder))
 }
 
 /// Returns true if the path is a shadow-format file that should be sanitized
@@ -1032,7 +3058,9 @@ fn ensure_etc_files_in_namespace(etc_dir: &Path, host_files: &[(String, Vec<u8>)
 const SYSTEM_MOUNT_PREFIXES: &[&str] = &["/proc", "/sys", "/dev"];
 
 /// Kubernetes-internal mounts that are handled by the kubelet/containerd
-/// and should not be bind-mounted by the runtime.
+/// and should not be bind-mounted by the runtime. /etc/hosts and
+/// /etc/resolv.conf are instead copied into the overlay (see `copy_etc_files`);
+/// `REAPER_ETC_HOSTS=bind` opts /etc/hosts back in.
 const K8S_INTERNAL_MOUNTS: &[&str] = &[
     "/etc/hosts",
     "/etc/hostname",
@@ -1072,9 +3100,49 @@ fn is_read_only(m: &super::OciMount) -> bool {
     m.options.iter().any(|o| o == "ro")
 }
 
+/// Where the container's /etc/hosts comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EtcHostsMode {
+    /// Node's /etc/hosts copied into the overlay at namespace creation;
+    /// the kubelet-provided mount is ignored (default)
+    Copy,
+    /// Kubelet-provided hosts file (OCI mount) bind-mounted over /etc/hosts,
+    /// so updates such as hostAliases are visible. Falls back to the copy
+    /// when the config has no /etc/hosts mount.
+    Bind,
+}
+
+/// Read /etc/hosts handling from config.
+///
+/// - `REAPER_ETC_HOSTS`: "copy" (default) or "bind"
+pub fn read_etc_hosts_mode() -> EtcHostsMode {
+    std::env::var("REAPER_ETC_HOSTS")
+        .map(|v| match v.to_ascii_lowercase().as_str() {
+            "bind" => EtcHostsMode::Bind,
+            _ => EtcHostsMode::Copy,
+        })
+        .unwrap_or(EtcHostsMode::Copy)
+}
+
+/// Host path that will back /etc/hosts for this container: the kubelet mount
+/// source in bind mode (if present), `None` when the overlay's copy is used.
+pub fn etc_hosts_source(mounts: &[super::OciMount], mode: EtcHostsMode) -> Option<&str> {
+    if mode != EtcHostsMode::Bind {
+        return None;
+    }
+    mounts
+        .iter()
+        .find(|m| m.destination == "/etc/hosts" && is_bind_mount(m))
+        .and_then(|m| m.source.as_deref())
+}
+
 /// Filter OCI mounts to only those that should be processed as volume mounts.
-/// Returns bind mounts that are not system or Kubernetes-internal destinations.
-pub fn filter_volume_mounts(mounts: &[super::OciMount]) -> Vec<&super::OciMount> {
+/// Returns bind mounts that are not system or Kubernetes-internal destinations,
+/// except /etc/hosts when `etc_hosts` is [`EtcHostsMode::Bind`].
+pub fn filter_volume_mounts(
+    mounts: &[super::OciMount],
+    etc_hosts: EtcHostsMode,
+) -> Vec<&super::OciMount> {
     mounts
         .iter()
         .filter(|m| {
@@ -1086,6 +3154,10 @@ pub fn filter_volume_mounts(mounts: &[super::OciMount]) -> Vec<&super::OciMount>
                 info!("volume: skipping system destination: {}", m.destination);
                 return false;
             }
+            if m.destination == "/etc/hosts" && etc_hosts == EtcHostsMode::Bind {
+                info!("volume: binding kubelet-provided /etc/hosts (REAPER_ETC_HOSTS=bind)");
+                return true;
+            }
             if is_k8s_internal(&m.destination) {
                 info!("volume: skipping k8s-internal mount: {}", m.destination);
                 return false;
@@ -1095,23 +3167,53 @@ pub fn filter_volume_mounts(mounts: &[super::OciMount]) -> Vec<&super::OciMount>
         .collect()
 }
 
+// Syscall numbers (same on x86_64 and aarch64 for Linux >= 5.2)
+const SYS_OPEN_TREE: libc::c_long = 428;
+const SYS_MOVE_MOUNT: libc::c_long = 429;
+
+/// Check that the kernel implements open_tree() and move_mount().
+///
+/// Both are called with null paths, so a kernel that has them fails with
+/// EFAULT/EBADF/EINVAL without touching any mount. ENOSYS means the kernel
+/// is too old; EPERM usually means a seccomp profile blocks them.
+pub fn probe_mount_api() -> Result<()> {
+    fn check(name: &str, ret: libc::c_long) -> Result<()> {
+        if ret >= 0 {
+            // Cannot happen with a null path, but don't leak the fd if it does
+            unsafe { libc::close(ret as libc::c_int) };
+            return Ok(());
+        }
+        let err = std::io::Error::last_os_error();
+        match err.raw_os_error() {
+            Some(libc::ENOSYS) => bail!("{}() is not implemented by this kernel", name),
+            Some(libc::EPERM) => bail!("{}() is blocked (seccomp?): {}", name, err),
+            _ => Ok(()),
+        }
+    }
+
+    let null = std::ptr::null::<libc::c_char>();
+    check("open_tree", unsafe {
+        libc::syscall(SYS_OPEN_TREE, -1, null, 0)
+    })?;
+    check("move_mount", unsafe {
+        libc::syscall(SYS_MOVE_MOUNT, -1, null, -1, null, 0)
+    })
+}
+
 /// Clone a mount from the host mount namespace into the current (overlay) namespace.
 ///
 /// Uses open_tree(OPEN_TREE_CLONE) + move_mount() (Linux 5.2+) with namespace
-/// switching. Mount-related syscalls cannot follow /proc/1/root/ magic links, so
-/// we must temporarily setns() into the host mount namespace to resolve the path,
-/// clone the mount, switch back to the overlay namespace, then attach it.
+/// switching. Mount-related syscalls cannot follow /proc/<pid>/root/ magic links, so
+/// we must temporarily setns() into the host mount namespace (that of `host_pid`)
+/// to resolve the path, clone the mount, switch back to the overlay namespace,
+/// then attach it.
 ///
 /// The `source` parameter is the path AS SEEN FROM THE HOST namespace (not
-/// /proc/1/root-prefixed).
+/// /proc/<pid>/root-prefixed).
 #[cfg(not(tarpaulin_include))]
-fn cross_namespace_mount(source: &Path, dest: &Path) -> Result<()> {
+fn cross_namespace_mount(host_pid: i32, source: &Path, dest: &Path) -> Result<()> {
     use std::ffi::CString;
 
-    // Syscall numbers (same on x86_64 and aarch64 for Linux >= 5.2)
-    const SYS_OPEN_TREE: libc::c_long = 428;
-    const SYS_MOVE_MOUNT: libc::c_long = 429;
-
     // Flags for open_tree
     const OPEN_TREE_CLONE: libc::c_uint = 1;
     const OPEN_TREE_CLOEXEC: libc::c_uint = libc::O_CLOEXEC as libc::c_uint;
@@ -1128,7 +3230,8 @@ fn cross_namespace_mount(source: &Path, dest: &Path) -> Result<()> {
     // Save the current (overlay) mount namespace so we can return to it
     let overlay_ns =
         fs::File::open("/proc/self/ns/mnt").context("opening overlay mount namespace fd")?;
-    let host_ns = fs::File::open("/proc/1/ns/mnt").context("opening host mount namespace fd")?;
+    let host_ns = fs::File::open(format!("/proc/{}/ns/mnt", host_pid))
+        .context("opening host mount namespace fd")?;
 
     // Step 1: Enter host mount namespace to resolve the source path
     setns(&host_ns, CloneFlags::CLONE_NEWNS).context("setns to host mount namespace")?;
@@ -1233,27 +3336,249 @@ fn unmount_stale_ancestors(dest: &Path) {
     }
 }
 
+/// What to do when a volume mount's source path does not exist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MissingVolumePolicy {
+    /// Log a warning and start without the volume (default)
+    Skip,
+    /// Refuse to start the container
+    Fail,
+}
+
+impl MissingVolumePolicy {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "skip" => Some(Self::Skip),
+            "fail" => Some(Self::Fail),
+            _ => None,
+        }
+    }
+}
+
+/// Missing-source policy for a container's volume mounts.
+#[derive(Debug, PartialEq)]
+pub struct VolumePolicy {
+    /// Node default from `REAPER_MISSING_VOLUME_POLICY`
+    pub default: MissingVolumePolicy,
+    /// Per-destination overrides from the `missing-volume-policy` annotation
+    pub overrides: HashMap<String, MissingVolumePolicy>,
+}
+
+impl VolumePolicy {
+    pub fn for_destination(&self, dest: &str) -> MissingVolumePolicy {
+        self.overrides
+            .get(normalize_destination(dest))
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
+
+/// Strip trailing slashes so "/data/" and "/data" name the same mount.
+fn normalize_destination(dest: &str) -> &str {
+    match dest.trim_end_matches('/') {
+        "" => "/",
+        trimmed => trimmed,
+    }
+}
+
+/// Read the missing-volume policy with optional per-mount annotation overrides.
+///
+/// - `REAPER_MISSING_VOLUME_POLICY`: "skip" (default) or "fail"
+/// - `annotation_overrides`: destination -> policy, already validated by the
+///   annotations module
+pub fn read_volume_policy_with_override(
+    annotation_overrides: Option<&HashMap<String, String>>,
+) -> VolumePolicy {
+    let default = match std::env::var("REAPER_MISSING_VOLUME_POLICY") {
+        Ok(v) => MissingVolumePolicy::parse(&v).unwrap_or_else(|| {
+            tracing::warn!(
+                "volume: invalid REAPER_MISSING_VOLUME_POLICY {:?}, using skip",
+                v
+            );
+            MissingVolumePolicy::Skip
+        }),
+        Err(_) => MissingVolumePolicy::Skip,
+    };
+
+    let overrides = annotation_overrides
+        .into_iter()
+        .flatten()
+        .filter_map(|(dest, policy)| {
+            MissingVolumePolicy::parse(policy).map(|p| (normalize_destination(dest).to_string(), p))
+        })
+        .collect();
+
+    VolumePolicy { default, overrides }
+}
+
+/// Whether `REAPER_VERIFY_MOUNTS` asks for each volume mount to be checked
+/// after it is attached (default off).
+pub fn verify_mounts_enabled() -> bool {
+    std::env::var("REAPER_VERIFY_MOUNTS")
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+/// Default for `REAPER_MOUNT_SOURCE_WAIT_MS`.
+const DEFAULT_MOUNT_SOURCE_WAIT_MS: u64 = 2000;
+
+/// How often a missing volume source is looked for again.
+const MOUNT_SOURCE_POLL: Duration = Duration::from_millis(50);
+
+/// How long `REAPER_MOUNT_SOURCE_WAIT_MS` lets volume setup wait for missing
+/// sources to appear, in total across a container's mounts. Kubelet can
+/// populate a volume slightly after the container is created; `0` disables
+/// the wait.
+pub fn mount_source_wait() -> Duration {
+    let ms = std::env::var("REAPER_MOUNT_SOURCE_WAIT_MS")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(DEFAULT_MOUNT_SOURCE_WAIT_MS);
+    Duration::from_millis(ms)
+}
+
+/// Check `found` every [`MOUNT_SOURCE_POLL`] until it holds or `deadline`
+/// passes. Checks at least once.
+fn wait_until(deadline: Instant, mut found: impl FnMut() -> bool) -> bool {
+    loop {
+        if found() {
+            return true;
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return false;
+        }
+        std::thread::sleep(MOUNT_SOURCE_POLL.min(deadline - now));
+    }
+}
+
+/// Device and inode of `path`, following symlinks.
+fn file_identity(path: &Path) -> Result<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    let meta = fs::metadata(path).with_context(|| format!("stat {}", path.display()))?;
+    Ok((meta.dev(), meta.ino()))
+}
+
+/// Check that `dest` now resolves to the file or directory that was the mount
+/// source (`expected`, taken before mounting), i.e. the mount took effect.
+fn verify_mount_target(expected: (u64, u64), dest: &Path) -> Result<()> {
+    let actual = file_identity(dest)?;
+    if actual != expected {
+        bail!(
+            "volume mount at {} did not take effect: found dev={} ino={}, \
+             expected source dev={} ino={}",
+            dest.display(),
+            actual.0,
+            actual.1,
+            expected.0,
+            expected.1
+        );
+    }
+    Ok(())
+}
+
+/// Lexically normalize an absolute path: drop `.`, empty and trailing
+/// components and resolve `..`, without touching the filesystem.
+fn lexical_path(path: &str) -> PathBuf {
+    use std::path::Component;
+    let mut out = PathBuf::from("/");
+    for component in Path::new(path).components() {
+        match component {
+            Component::Normal(part) => out.push(part),
+            Component::ParentDir => {
+                out.pop();
+            }
+            Component::RootDir | Component::CurDir | Component::Prefix(_) => {}
+        }
+    }
+    out
+}
+
+/// Reject a bind mount whose source and destination overlap. Source and
+/// destination live in the same view of the node's filesystem, so binding a
+/// directory onto itself or one of its descendants (or a descendant over its
+/// ancestor) makes the recursive bind copy the mount into itself.
+/// Paths are compared by whole components: `/data` and `/data2` are disjoint.
+fn check_bind_overlap(source: &str, dest: &str) -> Result<()> {
+    let (src, dst) = (lexical_path(source), lexical_path(dest));
+    let relation = if src == dst {
+        "is the same path as"
+    } else if dst.starts_with(&src) {
+        "contains"
+    } else if src.starts_with(&dst) {
+        "is inside"
+    } else {
+        return Ok(());
+    };
+    bail!(
+        "volume mount source {} {} its destination {}: refusing recursive bind mount",
+        source,
+        relation,
+        dest
+    );
+}
+
+/// Order volume mounts parent-first: by destination depth, shallowest first,
+/// keeping the config order among equal depths. A mount below another's
+/// destination applied before it would be hidden by it.
+pub fn sort_by_destination_depth(mounts: &mut [&super::OciMount]) {
+    mounts.sort_by_key(|m| Path::new(&m.destination).components().count());
+}
+
+/// Whether `dest` already resolves to the file or directory at `source`,
+/// i.e. a bind of it is in effect and mounting again would only stack a
+/// duplicate on top.
+fn is_already_mounted(source: &Path, dest: &Path) -> bool {
+    match (file_identity(source), file_identity(dest)) {
+        (Ok(source), Ok(dest)) => source == dest,
+        _ => false,
+    }
+}
+
 /// Apply volume mounts from OCI config inside the current mount namespace.
 ///
+/// Mounts are applied parent-first (see [`sort_by_destination_depth`]). A
+/// destination already showing its source, e.g. from an earlier container
+/// in the shared namespace, is left as is rather than mounted again.
+///
 /// For each filtered bind mount:
 /// 1. Creates the destination directory (or file) if it doesn't exist
 /// 2. Performs a recursive bind mount from source to destination
 /// 3. If "ro" is in options, remounts read-only
 ///
+/// A missing source is waited for (see [`mount_source_wait`]), then skipped
+/// or fatal depending on `policy`. With
+/// `REAPER_VERIFY_MOUNTS` set, each destination is checked to resolve to
+/// its source afterwards, and a mismatch is fatal.
+///
 /// Must be called AFTER entering the overlay namespace and BEFORE spawning
-/// the workload. Mount failures are fatal.
+/// the workload. Mount failures are fatal. Returns the mounts applied, for
+/// the container's state.
 ///
 /// Tested by kind-integration tests (requires root + Linux namespaces).
 #[cfg(not(tarpaulin_include))]
-pub fn apply_volume_mounts(mounts: &[super::OciMount]) -> Result<()> {
-    let volume_mounts = filter_volume_mounts(mounts);
+pub fn apply_volume_mounts(
+    mounts: &[super::OciMount],
+    policy: &VolumePolicy,
+) -> Result<Vec<AppliedMount>> {
+    let etc_hosts = read_etc_hosts_mode();
+    match etc_hosts_source(mounts, etc_hosts) {
+        Some(source) => info!("volume: /etc/hosts bound from {}", source),
+        None => info!("volume: /etc/hosts is the overlay copy of the node's file"),
+    }
+    let mut volume_mounts = filter_volume_mounts(mounts, etc_hosts);
+    sort_by_destination_depth(&mut volume_mounts);
 
     if volume_mounts.is_empty() {
         info!("volume: no volume mounts to apply");
-        return Ok(());
+        return Ok(Vec::new());
     }
 
     info!("volume: applying {} volume mount(s)", volume_mounts.len());
+    let verify = verify_mounts_enabled();
+    let host_pid = resolve_host_pid()?;
+    let source_deadline = Instant::now() + mount_source_wait();
+    let mut applied = Vec::with_capacity(volume_mounts.len());
 
     for m in &volume_mounts {
         let source = m.source.as_deref().unwrap_or("");
@@ -1262,6 +3587,7 @@ pub fn apply_volume_mounts(mounts: &[super::OciMount]) -> Result<()> {
         if source.is_empty() {
             bail!("volume mount for {} has no source path", dest);
         }
+        check_bind_overlap(source, dest)?;
 
         let dest_path = Path::new(dest);
 
@@ -1272,19 +3598,43 @@ pub fn apply_volume_mounts(mounts: &[super::OciMount]) -> Result<()> {
         // those directories (tmpfs, projected, etc.) are NOT visible because mount
         // propagation is set to MS_PRIVATE.
         //
-        // Strategy: check if the source exists via /proc/1/root/<path> (host ns).
+        // Strategy: check if the source exists via /proc/<host pid>/root/<path> (host ns).
         // If so, use cross_namespace_mount() which setns's to the host ns, clones
         // the mount with open_tree(), returns to overlay ns, and attaches via
         // move_mount(). Falls back to direct bind mount for sources visible in the
         // overlay (e.g., emptyDir is a plain directory, not a mount).
-        let host_path = PathBuf::from(format!("/proc/1/root{}", source));
+        let find_host_source = || {
+            host_pid
+                .map(|pid| (pid, host_view(pid, source)))
+                .filter(|(_, path)| path.exists())
+        };
+        let mut host_source = find_host_source();
         let direct_path = PathBuf::from(source);
 
+        if host_source.is_none() && !direct_path.exists() {
+            info!("volume: source {} not there yet, waiting for it", source);
+            let appeared = wait_until(source_deadline, || {
+                host_source = find_host_source();
+                host_source.is_some() || direct_path.exists()
+            });
+            if appeared {
+                info!("volume: source {} appeared", source);
+            }
+        }
+
         // Determine whether source needs cross-namespace mount
-        let use_host_ns = host_path.exists();
+        let use_host_ns = host_source.is_some();
         let source_path = PathBuf::from(source);
 
         if !use_host_ns && !direct_path.exists() {
+            if policy.for_destination(dest) == MissingVolumePolicy::Fail {
+                bail!(
+                    "volume source {} for {} does not exist (checked host ns and overlay) \
+                     and missing-volume policy is fail",
+                    source,
+                    dest
+                );
+            }
             tracing::warn!(
                 "volume: source {} does not exist (checked host ns and overlay), skipping mount to {}",
                 source,
@@ -1293,6 +3643,22 @@ pub fn apply_volume_mounts(mounts: &[super::OciMount]) -> Result<()> {
             continue;
         }
 
+        // Check via /proc/<host pid>/root if using host ns, otherwise direct.
+        let check_path = match &host_source {
+            Some((_, host_path)) => host_path,
+            None => &direct_path,
+        };
+        if is_already_mounted(check_path, dest_path) {
+            info!("volume: {} already mounted at {}, skipping", source, dest);
+            applied.push(AppliedMount {
+                source: source.to_string(),
+                destination: dest.clone(),
+                options: m.options.clone(),
+                cross_namespace: use_host_ns,
+            });
+            continue;
+        }
+
         if use_host_ns {
             info!(
                 "volume: source {} exists in host namespace, will use cross-ns mount",
@@ -1327,12 +3693,6 @@ pub fn apply_volume_mounts(mounts: &[super::OciMount]) -> Result<()> {
         }
 
         // Create destination: directory if source is a directory, file otherwise.
-        // Check via /proc/1/root if using host ns, otherwise direct.
-        let check_path = if use_host_ns {
-            &host_path
-        } else {
-            &direct_path
-        };
         if check_path.is_dir() {
             fs::create_dir_all(dest_path)
                 .with_context(|| format!("creating mount destination dir {}", dest))?;
@@ -1348,9 +3708,15 @@ pub fn apply_volume_mounts(mounts: &[super::OciMount]) -> Result<()> {
             }
         }
 
-        if use_host_ns {
+        let expected = if verify {
+            Some(file_identity(check_path)?)
+        } else {
+            None
+        };
+
+        if let Some((pid, _)) = host_source {
             // Cross-namespace mount: setns to host, open_tree(CLONE), setns back, move_mount
-            cross_namespace_mount(&source_path, dest_path)
+            cross_namespace_mount(pid, &source_path, dest_path)
                 .with_context(|| format!("cross-ns mounting {} -> {}", source, dest))?;
         } else {
             // Direct bind mount within the overlay namespace
@@ -1365,6 +3731,10 @@ pub fn apply_volume_mounts(mounts: &[super::OciMount]) -> Result<()> {
         }
 
         info!("volume: mounted {} -> {}", source, dest);
+        if let Some(expected) = expected {
+            verify_mount_target(expected, dest_path)?;
+            info!("volume: verified {} is backed by {}", dest, source);
+        }
 
         // Apply read-only remount if requested
         if is_read_only(m) {
@@ -1378,10 +3748,17 @@ pub fn apply_volume_mounts(mounts: &[super::OciMount]) -> Result<()> {
             .with_context(|| format!("remounting {} as read-only", dest))?;
             info!("volume: remounted {} as read-only", dest);
         }
+
+        applied.push(AppliedMount {
+            source: source.to_string(),
+            destination: dest.clone(),
+            options: m.options.clone(),
+            cross_namespace: use_host_ns,
+        });
     }
 
     info!("volume: all volume mounts applied successfully");
-    Ok(())
+    Ok(applied)
 }
 
 #[cfg(test)]
@@ -1400,7 +3777,7 @@ mod tests {
         std::env::remove_var("REAPER_OVERLAY_NS");
         std::env::remove_var("REAPER_OVERLAY_LOCK");
 
-        let config = read_config(None, None).unwrap();
+        let config = read_config(None, None, None).unwrap();
         assert_eq!(config.base_dir, PathBuf::from("/run/reaper/overlay"));
         assert_eq!(config.ns_path, PathBuf::from("/run/reaper/shared-mnt-ns"));
         assert_eq!(config.lock_path, PathBuf::from("/run/reaper/overlay.lock"));
@@ -1410,16 +3787,64 @@ mod tests {
     }
 
     #[test]
-    fn test_read_config_node_mode_custom_base() {
+    fn test_read_config_node_mode_custom_base() {
+        let _guard = ENV_LOCK.lock().unwrap();
+
+        std::env::set_var("REAPER_OVERLAY_ISOLATION", "node");
+        std::env::set_var("REAPER_OVERLAY_BASE", "/custom/overlay");
+        let config = read_config(None, None, None).unwrap();
+        assert_eq!(config.base_dir, PathBuf::from("/custom/overlay"));
+        std::env::remove_var("REAPER_OVERLAY_BASE");
+        std::env::remove_var("REAPER_OVERLAY_NS");
+        std::env::remove_var("REAPER_OVERLAY_LOCK");
+        std::env::remove_var("REAPER_OVERLAY_ISOLATION");
+    }
+
+    #[test]
+    fn test_read_config_merged_base_override() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::remove_var("REAPER_OVERLAY_BASE");
+        std::env::remove_var("REAPER_OVERLAY_NS");
+        std::env::remove_var("REAPER_OVERLAY_LOCK");
+        let tmp = tempfile::tempdir().unwrap();
+        let base = tmp.path().join("merged");
+        std::env::set_var("REAPER_OVERLAY_MERGED_BASE", &base);
+
+        std::env::set_var("REAPER_OVERLAY_ISOLATION", "node");
+        let config = read_config(None, None, None).unwrap();
+        assert_eq!(config.merged_dir, base);
+        assert_eq!(config.base_dir, PathBuf::from("/run/reaper/overlay"));
+
+        std::env::remove_var("REAPER_OVERLAY_ISOLATION");
+        let config = read_config(Some("default"), None, None).unwrap();
+        assert_eq!(config.merged_dir, base.join("default"));
+        let config = read_config(Some("default"), Some("pippo"), None).unwrap();
+        assert_eq!(config.merged_dir, base.join("default/pippo"));
+        assert_eq!(
+            config.ns_path,
+            PathBuf::from("/run/reaper/ns/default--pippo")
+        );
+
+        std::env::remove_var("REAPER_OVERLAY_MERGED_BASE");
+    }
+
+    #[test]
+    fn test_read_config_merged_base_invalid() {
         let _guard = ENV_LOCK.lock().unwrap();
-
         std::env::set_var("REAPER_OVERLAY_ISOLATION", "node");
-        std::env::set_var("REAPER_OVERLAY_BASE", "/custom/overlay");
-        let config = read_config(None, None).unwrap();
-        assert_eq!(config.base_dir, PathBuf::from("/custom/overlay"));
-        std::env::remove_var("REAPER_OVERLAY_BASE");
-        std::env::remove_var("REAPER_OVERLAY_NS");
-        std::env::remove_var("REAPER_OVERLAY_LOCK");
+
+        std::env::set_var("REAPER_OVERLAY_MERGED_BASE", "relative/merged");
+        let err = read_config(None, None, None).unwrap_err();
+        assert!(err.to_string().contains("absolute"), "{}", err);
+
+        let tmp = tempfile::tempdir().unwrap();
+        let file = tmp.path().join("file");
+        fs::write(&file, "").unwrap();
+        std::env::set_var("REAPER_OVERLAY_MERGED_BASE", file.join("merged"));
+        let err = read_config(None, None, None).unwrap_err();
+        assert!(err.to_string().contains("not a directory"), "{}", err);
+
+        std::env::remove_var("REAPER_OVERLAY_MERGED_BASE");
         std::env::remove_var("REAPER_OVERLAY_ISOLATION");
     }
 
@@ -1446,7 +3871,7 @@ mod tests {
         std::env::remove_var("REAPER_OVERLAY_NS");
         std::env::remove_var("REAPER_OVERLAY_LOCK");
 
-        let config = read_config(Some("default"), None).unwrap();
+        let config = read_config(Some("default"), None, None).unwrap();
         assert_eq!(
             config.base_dir,
             PathBuf::from("/run/reaper/overlay/default")
@@ -1462,6 +3887,542 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_compose_lowerdir() {
+        assert_eq!(compose_lowerdir(None).unwrap(), "/");
+        assert_eq!(
+            compose_lowerdir(Some(Path::new("/var/lib/rootfs"))).unwrap(),
+            "/var/lib/rootfs:/"
+        );
+        assert_eq!(
+            compose_lowerdir(Some(Path::new("/snap:1/fs"))).unwrap(),
+            "/snap\\:1/fs:/"
+        );
+        assert!(compose_lowerdir(Some(Path::new("relative/rootfs"))).is_err());
+        assert!(compose_lowerdir(Some(Path::new("/a,upperdir=/x"))).is_err());
+    }
+
+    #[test]
+    fn test_with_image_rootfs_uses_private_paths() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::remove_var("REAPER_OVERLAY_ISOLATION");
+        std::env::remove_var("REAPER_OVERLAY_BASE");
+        std::env::remove_var("REAPER_OVERLAY_NS");
+        std::env::remove_var("REAPER_OVERLAY_LOCK");
+
+        let shared = read_config(Some("default"), None, None).unwrap();
+        assert!(shared.image_rootfs.is_none());
+        let private = shared
+            .with_image_rootfs("abc123", Path::new("/bundles/abc123/rootfs"))
+            .unwrap();
+        assert_eq!(
+            private.base_dir,
+            PathBuf::from("/run/reaper/overlay/default/rootfs-abc123")
+        );
+        assert_eq!(
+            private.ns_path,
+            PathBuf::from("/run/reaper/ns/default--rootfs-abc123")
+        );
+        assert_eq!(
+            private.lock_path,
+            PathBuf::from("/run/reaper/overlay-default.lock--rootfs-abc123")
+        );
+        assert_eq!(
+            private.merged_dir,
+            PathBuf::from("/run/reaper/merged/default/rootfs-abc123")
+        );
+        assert_eq!(
+            private.image_rootfs,
+            Some(PathBuf::from("/bundles/abc123/rootfs"))
+        );
+
+        let shared = read_config(Some("default"), None, None).unwrap();
+        assert!(shared
+            .with_image_rootfs("../escape", Path::new("/rootfs"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_read_config_isolation_annotation_overrides_env() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::remove_var("REAPER_OVERLAY_BASE");
+        std::env::remove_var("REAPER_OVERLAY_NS");
+        std::env::remove_var("REAPER_OVERLAY_LOCK");
+
+        std::env::set_var("REAPER_OVERLAY_ISOLATION", "node");
+        let config = read_config(Some("default"), None, Some("namespace")).unwrap();
+        assert_eq!(config.ns_path, PathBuf::from("/run/reaper/ns/default"));
+
+        std::env::set_var("REAPER_OVERLAY_ISOLATION", "namespace");
+        let config = read_config(Some("default"), None, Some("Node")).unwrap();
+        assert_eq!(config.ns_path, PathBuf::from("/run/reaper/shared-mnt-ns"));
+
+        // No annotation: env decides
+        let config = read_config(Some("default"), None, None).unwrap();
+        assert_eq!(config.ns_path, PathBuf::from("/run/reaper/ns/default"));
+        std::env::remove_var("REAPER_OVERLAY_ISOLATION");
+    }
+
+    #[test]
+    fn test_read_config_invalid_isolation_annotation_errors() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::remove_var("REAPER_OVERLAY_ISOLATION");
+
+        let err = read_config(Some("default"), None, Some("cluster")).unwrap_err();
+        let msg = format!("{:#}", err);
+        assert!(msg.contains("overlay-isolation"), "{}", msg);
+        assert!(msg.contains("valid: namespace, node"), "{}", msg);
+    }
+
+    const SAMPLE_MOUNTINFO: &str = "\
+22 1 0:21 / / rw,relatime shared:1 - overlay overlay rw,lowerdir=/,upperdir=/run/reaper/overlay/default/upper
+23 22 0:5 / /proc rw,nosuid,nodev,noexec - proc proc rw
+24 22 0:22 / /sys/fs/cgroup rw,nosuid shared:9 - cgroup2 cgroup2 rw
+25 22 8:1 /var/lib/kubelet/pods/abc/volumes/cfg /etc/app\\040config ro,relatime - ext4 /dev/sda1 rw
+26 22 0:30 / /tmp rw - tmpfs tmpfs rw,size=65536k
+garbage line without separator
+";
+
+    #[test]
+    fn test_parse_mountinfo() {
+        let entries = parse_mountinfo(SAMPLE_MOUNTINFO);
+        assert_eq!(entries.len(), 5);
+        assert_eq!(
+            entries[0],
+            MountInfoEntry {
+                mount_point: "/".into(),
+                root: "/".into(),
+                fstype: "overlay".into(),
+                source: "overlay".into(),
+                options: "rw,relatime".into(),
+            }
+        );
+        assert_eq!(entries[3].mount_point, "/etc/app config");
+        assert_eq!(entries[3].root, "/var/lib/kubelet/pods/abc/volumes/cfg");
+        assert_eq!(entries[3].options, "ro,relatime");
+    }
+
+    #[test]
+    fn test_unescape_mountinfo() {
+        assert_eq!(unescape_mountinfo("/a\\040b"), "/a b");
+        assert_eq!(unescape_mountinfo("/tab\\011x"), "/tab\tx");
+        assert_eq!(unescape_mountinfo("/back\\134slash"), "/back\\slash");
+        assert_eq!(unescape_mountinfo("/plain"), "/plain");
+        assert_eq!(unescape_mountinfo("/trailing\\"), "/trailing\\");
+    }
+
+    #[test]
+    fn test_format_mountinfo_hides_pseudo_filesystems() {
+        let out = format_mountinfo(SAMPLE_MOUNTINFO, false);
+        let lines: Vec<&str> = out.lines().collect();
+        assert!(lines[0].starts_with("TARGET"));
+        assert_eq!(lines.len(), 4, "{}", out);
+        assert!(!out.contains("/proc"));
+        assert!(!out.contains("cgroup2"));
+        assert!(out.contains("/dev/sda1[/var/lib/kubelet/pods/abc/volumes/cfg]"));
+        assert!(out.contains("/etc/app config"));
+
+        // Columns line up
+        let fstype_col = lines[0].find("FSTYPE").unwrap();
+        assert!(lines[1..]
+            .iter()
+            .all(|l| l.as_bytes()[fstype_col - 1] == b' '));
+    }
+
+    #[test]
+    fn test_format_mountinfo_all() {
+        let out = format_mountinfo(SAMPLE_MOUNTINFO, true);
+        assert_eq!(out.lines().count(), 6);
+        assert!(out.contains("/proc"));
+        assert!(out.contains("cgroup2"));
+    }
+
+    #[test]
+    fn test_format_mountinfo_empty() {
+        assert_eq!(format_mountinfo("", false).lines().count(), 1);
+    }
+
+    #[test]
+    fn test_format_volume_mounts_reports_liveness() {
+        let recorded = vec![
+            AppliedMount {
+                source: "/var/lib/kubelet/pods/abc/volumes/cfg".into(),
+                destination: "/etc/app config".into(),
+                options: vec!["rbind".into(), "ro".into()],
+                cross_namespace: true,
+            },
+            AppliedMount {
+                source: "/srv/data".into(),
+                destination: "/mnt/data".into(),
+                options: Vec::new(),
+                cross_namespace: false,
+            },
+        ];
+
+        let out = format_volume_mounts(&recorded, Some(SAMPLE_MOUNTINFO));
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 3, "{}", out);
+        assert!(lines[0].starts_with("TARGET"));
+        assert!(lines[1].starts_with("/etc/app config"));
+        assert!(lines[1].contains("host-ns"));
+        assert!(lines[1].contains("rbind,ro"));
+        assert!(lines[1].ends_with("yes"));
+        assert!(lines[2].contains("/srv/data"));
+        assert!(lines[2].contains("bind"));
+        assert!(lines[2].ends_with("no"));
+        let live_col = lines[0].find("LIVE").unwrap();
+        assert!(lines[1..].iter().all(|l| l.len() > live_col));
+
+        let out = format_volume_mounts(&recorded, None);
+        assert!(out.lines().skip(1).all(|l| l.ends_with("unknown")));
+    }
+
+    #[test]
+    fn test_overlay_mount_recovery() {
+        use nix::errno::Errno;
+        assert_eq!(
+            overlay_mount_recovery(Errno::EBUSY, false),
+            OverlayMountRecovery::CleanWorkDirAndRetry
+        );
+        assert_eq!(
+            overlay_mount_recovery(Errno::ESTALE, false),
+            OverlayMountRecovery::CleanWorkDirAndRetry
+        );
+        // Only one retry
+        assert_eq!(
+            overlay_mount_recovery(Errno::EBUSY, true),
+            OverlayMountRecovery::GiveUp
+        );
+        // Not a work dir problem
+        for errno in [Errno::ENODEV, Errno::EINVAL, Errno::EPERM] {
+            assert_eq!(
+                overlay_mount_recovery(errno, false),
+                OverlayMountRecovery::GiveUp
+            );
+        }
+    }
+
+    #[test]
+    fn test_clean_overlay_work_dir() {
+        let work = tempfile::tempdir().unwrap();
+        fs::create_dir_all(work.path().join("work/#1f")).unwrap();
+        fs::create_dir_all(work.path().join("incompat/volatile")).unwrap();
+        fs::create_dir_all(work.path().join("index")).unwrap();
+
+        let removed = clean_overlay_work_dir(work.path());
+        assert_eq!(
+            removed,
+            vec![work.path().join("work"), work.path().join("incompat")]
+        );
+        assert!(!work.path().join("work").exists());
+        assert!(!work.path().join("incompat").exists());
+        assert!(work.path().join("index").exists());
+        assert!(clean_overlay_work_dir(work.path()).is_empty());
+    }
+
+    #[test]
+    fn test_read_overlay_fallback() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::remove_var("REAPER_OVERLAY_FALLBACK");
+        assert_eq!(read_overlay_fallback(), OverlayFallback::None);
+        std::env::set_var("REAPER_OVERLAY_FALLBACK", "BIND");
+        assert_eq!(read_overlay_fallback(), OverlayFallback::Bind);
+        std::env::set_var("REAPER_OVERLAY_FALLBACK", "something-else");
+        assert_eq!(read_overlay_fallback(), OverlayFallback::None);
+        std::env::remove_var("REAPER_OVERLAY_FALLBACK");
+    }
+
+    #[test]
+    fn test_bind_fallback_plan() {
+        let merged = Path::new("/run/reaper/merged/default");
+        let plan = bind_fallback_plan(merged);
+        assert_eq!(plan.len(), 2 + BIND_FALLBACK_WRITABLE.len());
+
+        // Host root first, recursively, then the read-only remount of it
+        assert_eq!(plan[0].source.as_deref(), Some(Path::new("/")));
+        assert_eq!(plan[0].target, merged);
+        assert!(plan[0].flags.contains(MsFlags::MS_BIND | MsFlags::MS_REC));
+        assert_eq!(plan[1].target, merged);
+        assert!(plan[1]
+            .flags
+            .contains(MsFlags::MS_BIND | MsFlags::MS_REMOUNT | MsFlags::MS_RDONLY));
+
+        let tmpfs: Vec<_> = plan[2..].iter().map(|m| m.target.clone()).collect();
+        assert_eq!(tmpfs, vec![merged.join("tmp"), merged.join("var")]);
+        assert!(plan[2..].iter().all(|m| m.fstype == Some("tmpfs")
+            && m.flags.contains(MsFlags::MS_NOSUID | MsFlags::MS_NODEV)));
+        assert_eq!(plan[2].data.as_deref(), Some("mode=1777"));
+
+        // /run must stay the host's: state files and FIFOs live there
+        assert!(!plan.iter().any(|m| m.target == merged.join("run")));
+    }
+
+    #[test]
+    fn test_read_writable_paths() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::remove_var("REAPER_WRITABLE_PATHS");
+        assert!(read_writable_paths().unwrap().is_empty());
+        std::env::set_var("REAPER_WRITABLE_PATHS", "/var/log::/opt/app/cache");
+        assert_eq!(
+            read_writable_paths().unwrap(),
+            vec![PathBuf::from("/var/log"), PathBuf::from("/opt/app/cache")]
+        );
+        for invalid in ["var/log", "/", "/var/../etc", "/var/log:relative"] {
+            std::env::set_var("REAPER_WRITABLE_PATHS", invalid);
+            assert!(read_writable_paths().is_err(), "{:?} accepted", invalid);
+        }
+        std::env::remove_var("REAPER_WRITABLE_PATHS");
+    }
+
+    #[test]
+    fn test_writable_paths_plan() {
+        let merged = Path::new("/run/reaper/merged/default");
+        let paths = [PathBuf::from("/var/log"), PathBuf::from("/opt/app")];
+        let plan = writable_paths_plan(merged, &paths);
+        let targets: Vec<_> = plan.iter().map(|m| m.target.clone()).collect();
+        assert_eq!(
+            targets,
+            vec![merged.join("var/log"), merged.join("opt/app")]
+        );
+        assert!(plan.iter().all(|m| m.fstype == Some("tmpfs")
+            && m.source.as_deref() == Some(Path::new("tmpfs"))
+            && m.flags == MsFlags::MS_NOSUID | MsFlags::MS_NODEV
+            && m.data.as_deref() == Some("mode=0755")));
+        assert!(writable_paths_plan(merged, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_read_run_tmpfs() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::remove_var("REAPER_RUN_TMPFS_SIZE");
+        std::env::remove_var("REAPER_RUN_TMPFS_MODE");
+        std::env::remove_var("REAPER_RUN_TMPFS");
+        assert_eq!(read_run_tmpfs().unwrap(), None);
+
+        std::env::set_var("REAPER_RUN_TMPFS", "true");
+        assert_eq!(
+            read_run_tmpfs().unwrap(),
+            Some(RunTmpfs {
+                size: None,
+                mode: "0755".to_string()
+            })
+        );
+        std::env::set_var("REAPER_RUN_TMPFS_SIZE", "64m");
+        std::env::set_var("REAPER_RUN_TMPFS_MODE", "0711");
+        let run = read_run_tmpfs().unwrap().unwrap();
+        assert_eq!(run.mount_data(), "mode=0711,size=64m");
+
+        for size in ["m", "64mb", "-1", "6 4m", "10%%"] {
+            std::env::set_var("REAPER_RUN_TMPFS_SIZE", size);
+            assert!(read_run_tmpfs().is_err(), "size {:?} accepted", size);
+        }
+        std::env::set_var("REAPER_RUN_TMPFS_SIZE", "10%");
+        for mode in ["755x", "0800", "7", "01755"] {
+            std::env::set_var("REAPER_RUN_TMPFS_MODE", mode);
+            assert!(read_run_tmpfs().is_err(), "mode {:?} accepted", mode);
+        }
+        std::env::remove_var("REAPER_RUN_TMPFS_SIZE");
+        std::env::remove_var("REAPER_RUN_TMPFS_MODE");
+        std::env::remove_var("REAPER_RUN_TMPFS");
+    }
+
+    #[test]
+    fn test_run_tmpfs_plan() {
+        let merged = Path::new("/run/reaper/merged/default");
+        let run = RunTmpfs {
+            size: Some("32m".to_string()),
+            mode: "0755".to_string(),
+        };
+        let keep = [
+            PathBuf::from("/run/reaper"),
+            PathBuf::from("/run/reaper"),
+            PathBuf::from("/var/lib/reaper"),
+            PathBuf::from("/run"),
+            PathBuf::from("/run/containerd"),
+        ];
+        let plan = run_tmpfs_plan(merged, &run, &keep);
+
+        // The tmpfs comes first so the binds land on it
+        assert_eq!(plan[0].target, merged.join("run"));
+        assert_eq!(plan[0].fstype, Some("tmpfs"));
+        assert_eq!(plan[0].flags, MsFlags::MS_NOSUID | MsFlags::MS_NODEV);
+        assert_eq!(plan[0].data.as_deref(), Some("mode=0755,size=32m"));
+
+        // Each kept /run directory once; /run itself and paths outside it
+        // are not bound
+        let binds: Vec<_> = plan[1..]
+            .iter()
+            .map(|m| (m.source.clone().unwrap(), m.target.clone()))
+            .collect();
+        assert_eq!(
+            binds,
+            vec![
+                (PathBuf::from("/run/reaper"), merged.join("run/reaper")),
+                (
+                    PathBuf::from("/run/containerd"),
+                    merged.join("run/containerd")
+                ),
+            ]
+        );
+        assert!(plan[1..]
+            .iter()
+            .all(|m| m.fstype.is_none() && m.flags == MsFlags::MS_BIND | MsFlags::MS_REC));
+
+        assert_eq!(run_tmpfs_plan(merged, &run, &[]).len(), 1);
+    }
+
+    #[test]
+    fn test_devpts_mount_options() {
+        let (flags, data) = devpts_mount_options(None);
+        assert_eq!(flags, MsFlags::MS_NOSUID | MsFlags::MS_NOEXEC);
+        assert_eq!(data, "newinstance,ptmxmode=0666,mode=0620,gid=5");
+
+        // Config options are used, but newinstance and ptmxmode are enforced
+        let opts: Vec<String> = ["rw", "nosuid", "mode=0600"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let (flags, data) = devpts_mount_options(Some(&opts));
+        assert_eq!(flags, MsFlags::MS_NOSUID);
+        assert_eq!(data, "mode=0600,newinstance,ptmxmode=0666");
+
+        let opts = vec!["ptmxmode=0600".to_string(), "newinstance".to_string()];
+        let (_, data) = devpts_mount_options(Some(&opts));
+        assert_eq!(data, "ptmxmode=0600,newinstance");
+    }
+
+    #[test]
+    fn test_mqueue_mount_options() {
+        let (flags, data) = mqueue_mount_options(None);
+        assert_eq!(
+            flags,
+            MsFlags::MS_NOSUID | MsFlags::MS_NOEXEC | MsFlags::MS_NODEV
+        );
+        assert!(data.is_empty());
+
+        let opts = vec!["ro".to_string(), "relatime".to_string()];
+        let (flags, _) = mqueue_mount_options(Some(&opts));
+        assert_eq!(flags, MsFlags::MS_RDONLY | MsFlags::MS_RELATIME);
+    }
+
+    #[test]
+    fn test_special_fs_plan() {
+        let merged = Path::new("/merged");
+        let plan = special_fs_plan(merged, &[], false);
+        assert_eq!(plan.len(), 3);
+        assert_eq!(plan[0].target, merged.join("dev/pts"));
+        assert_eq!(plan[0].fstype, Some("devpts"));
+        assert!(plan[0].data.as_deref().unwrap().contains("newinstance"));
+        assert_eq!(
+            plan[1].source.as_deref(),
+            Some(merged.join("dev/pts/ptmx").as_path())
+        );
+        assert_eq!(plan[1].target, merged.join("dev/ptmx"));
+        assert_eq!(plan[1].flags, MsFlags::MS_BIND);
+        assert_eq!(plan[2].target, merged.join("dev/mqueue"));
+        assert_eq!(plan[2].fstype, Some("mqueue"));
+        assert_eq!(plan[2].data, None);
+
+        // Options come from the matching config mount only
+        let mounts = vec![
+            crate::OciMount {
+                destination: "/dev/pts/".into(),
+                source: Some("devpts".into()),
+                mount_type: Some("devpts".into()),
+                options: vec!["mode=0600".into()],
+            },
+            crate::OciMount {
+                destination: "/dev/mqueue".into(),
+                source: Some("/host/mqueue".into()),
+                mount_type: Some("bind".into()),
+                options: vec!["ro".into()],
+            },
+        ];
+        let plan = special_fs_plan(merged, &mounts, false);
+        assert_eq!(
+            plan[0].data.as_deref(),
+            Some("mode=0600,newinstance,ptmxmode=0666")
+        );
+        assert!(!plan[2].flags.contains(MsFlags::MS_RDONLY));
+
+        let plan = special_fs_plan(merged, &[], true);
+        assert_eq!(plan.len(), 2);
+        assert_eq!(
+            plan[0].data.as_deref(),
+            Some("newinstance,ptmxmode=0666,mode=0620")
+        );
+    }
+
+    #[test]
+    fn test_rootless_id_map() {
+        assert_eq!(rootless_id_map(1000), "0 1000 1\n");
+    }
+
+    #[test]
+    fn test_classify_rootless_entry() {
+        let root = tempfile::tempdir().unwrap();
+        for dir in ["proc", "sys", "dev", "run", "usr", "tmp"] {
+            fs::create_dir(root.path().join(dir)).unwrap();
+        }
+        fs::write(root.path().join("swapfile"), b"").unwrap();
+        std::os::unix::fs::symlink("usr/bin", root.path().join("bin")).unwrap();
+
+        let classify = |name: &str| classify_rootless_entry(&root.path().join(name));
+        assert_eq!(classify("proc"), Some(RootlessEntry::Bind));
+        assert_eq!(classify("run"), Some(RootlessEntry::Bind));
+        assert_eq!(classify("usr"), Some(RootlessEntry::Layered));
+        assert_eq!(classify("tmp"), Some(RootlessEntry::Layered));
+        assert_eq!(
+            classify("bin"),
+            Some(RootlessEntry::Symlink(PathBuf::from("usr/bin")))
+        );
+        assert_eq!(classify("swapfile"), None);
+        assert_eq!(classify("missing"), None);
+    }
+
+    #[test]
+    fn test_rootless_overlay_options() {
+        let opts = rootless_overlay_options(
+            Path::new("/usr"),
+            Path::new("/b/upper/usr"),
+            Path::new("/b/work/usr"),
+        );
+        assert_eq!(
+            opts,
+            "lowerdir=/usr,upperdir=/b/upper/usr,workdir=/b/work/usr,userxattr"
+        );
+    }
+
+    #[test]
+    fn test_locked_mount_flags() {
+        use nix::sys::statvfs::FsFlags;
+        assert_eq!(locked_mount_flags(FsFlags::empty()), MsFlags::empty());
+        assert_eq!(
+            locked_mount_flags(FsFlags::ST_NOSUID | FsFlags::ST_NODEV | FsFlags::ST_RDONLY),
+            MsFlags::MS_NOSUID | MsFlags::MS_NODEV
+        );
+    }
+
+    #[test]
+    fn test_seed_tmpfs_skeleton() {
+        let host = tempfile::tempdir().unwrap();
+        let target = tempfile::tempdir().unwrap();
+        fs::create_dir(host.path().join("log")).unwrap();
+        fs::create_dir(host.path().join("log").join("nested")).unwrap();
+        fs::write(host.path().join("file"), b"data").unwrap();
+        std::os::unix::fs::symlink("../run", host.path().join("run")).unwrap();
+
+        seed_tmpfs_skeleton(host.path(), target.path());
+
+        assert!(target.path().join("log").is_dir());
+        assert!(!target.path().join("log").join("nested").exists());
+        assert!(!target.path().join("file").exists());
+        assert_eq!(
+            fs::read_link(target.path().join("run")).unwrap(),
+            PathBuf::from("../run")
+        );
+    }
+
     #[test]
     fn test_read_config_namespace_mode_no_ns_fails() {
         let _guard = ENV_LOCK.lock().unwrap();
@@ -1470,7 +4431,7 @@ mod tests {
         std::env::remove_var("REAPER_OVERLAY_NS");
         std::env::remove_var("REAPER_OVERLAY_LOCK");
 
-        let result = read_config(None, None);
+        let result = read_config(None, None, None);
         assert!(result.is_err());
         let err_msg = result.unwrap_err().to_string();
         assert!(err_msg.contains("namespace"));
@@ -1485,7 +4446,7 @@ mod tests {
         std::env::remove_var("REAPER_OVERLAY_LOCK");
 
         // Node mode returns flat paths regardless of namespace arg
-        let config = read_config(Some("production"), None).unwrap();
+        let config = read_config(Some("production"), None, None).unwrap();
         assert_eq!(config.base_dir, PathBuf::from("/run/reaper/overlay"));
         assert_eq!(config.ns_path, PathBuf::from("/run/reaper/shared-mnt-ns"));
 
@@ -1500,7 +4461,7 @@ mod tests {
         std::env::remove_var("REAPER_OVERLAY_NS");
         std::env::remove_var("REAPER_OVERLAY_LOCK");
 
-        let config = read_config(Some("production"), Some("pippo")).unwrap();
+        let config = read_config(Some("production"), Some("pippo"), None).unwrap();
         assert_eq!(
             config.base_dir,
             PathBuf::from("/run/reaper/overlay/production/pippo")
@@ -1519,6 +4480,69 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_read_shared_config_paths() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::remove_var("REAPER_OVERLAY_BASE");
+        std::env::remove_var("REAPER_OVERLAY_NS");
+        std::env::remove_var("REAPER_OVERLAY_LOCK");
+
+        let config = read_shared_config("ci-cache").unwrap();
+        assert_eq!(
+            config.base_dir,
+            PathBuf::from("/run/reaper/overlay/_shared/ci-cache")
+        );
+        assert_eq!(
+            config.ns_path,
+            PathBuf::from("/run/reaper/ns/_shared--ci-cache")
+        );
+        assert_eq!(
+            config.lock_path,
+            PathBuf::from("/run/reaper/overlay-_shared--ci-cache.lock")
+        );
+        assert_eq!(
+            config.merged_dir,
+            PathBuf::from("/run/reaper/merged/_shared/ci-cache")
+        );
+
+        // The lock is scoped to the name: other shared overlays and a
+        // namespace called like the overlay each get their own
+        let other = read_shared_config("gpu-models").unwrap();
+        assert_ne!(other.lock_path, config.lock_path);
+        assert_ne!(other.ns_path, config.ns_path);
+        let namespace = read_config(Some("ci-cache"), None, None).unwrap();
+        assert_ne!(namespace.lock_path, config.lock_path);
+        assert_ne!(namespace.base_dir, config.base_dir);
+
+        for invalid in ["", "../evil", "Bad-Name", "a/b"] {
+            assert!(read_shared_config(invalid).is_err(), "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_read_shared_config_ignores_isolation_mode() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::remove_var("REAPER_OVERLAY_BASE");
+        std::env::remove_var("REAPER_OVERLAY_NS");
+        std::env::remove_var("REAPER_OVERLAY_LOCK");
+
+        // Pods from different namespaces, under either isolation mode, land
+        // on the same shared overlay
+        std::env::set_var("REAPER_OVERLAY_ISOLATION", "node");
+        let node = read_shared_config("ci-cache").unwrap();
+        assert_eq!(
+            read_config(Some("ci"), None, None).unwrap().base_dir,
+            PathBuf::from("/run/reaper/overlay")
+        );
+        std::env::set_var("REAPER_OVERLAY_ISOLATION", "namespace");
+        let namespace = read_shared_config("ci-cache").unwrap();
+        std::env::remove_var("REAPER_OVERLAY_ISOLATION");
+
+        assert_eq!(node.base_dir, namespace.base_dir);
+        assert_eq!(node.ns_path, namespace.ns_path);
+        assert_eq!(node.lock_path, namespace.lock_path);
+    }
+
     #[test]
     fn test_read_config_namespace_mode_without_overlay_name_unchanged() {
         let _guard = ENV_LOCK.lock().unwrap();
@@ -1528,7 +4552,7 @@ mod tests {
         std::env::remove_var("REAPER_OVERLAY_LOCK");
 
         // Without overlay-name, paths should be same as before (backward compatible)
-        let config = read_config(Some("production"), None).unwrap();
+        let config = read_config(Some("production"), None, None).unwrap();
         assert_eq!(
             config.base_dir,
             PathBuf::from("/run/reaper/overlay/production")
@@ -1553,7 +4577,7 @@ mod tests {
         std::env::remove_var("REAPER_OVERLAY_LOCK");
 
         // Node mode returns flat paths regardless of overlay-name
-        let config = read_config(None, Some("pippo")).unwrap();
+        let config = read_config(None, Some("pippo"), None).unwrap();
         assert_eq!(config.base_dir, PathBuf::from("/run/reaper/overlay"));
         assert_eq!(config.ns_path, PathBuf::from("/run/reaper/shared-mnt-ns"));
         assert_eq!(config.lock_path, PathBuf::from("/run/reaper/overlay.lock"));
@@ -1571,16 +4595,16 @@ mod tests {
         std::env::remove_var("REAPER_OVERLAY_LOCK");
 
         // Uppercase
-        let result = read_config(Some("default"), Some("Bad-Name"));
+        let result = read_config(Some("default"), Some("Bad-Name"), None);
         assert!(result.is_err());
 
         // Path traversal
-        let result = read_config(Some("default"), Some("../evil"));
+        let result = read_config(Some("default"), Some("../evil"), None);
         assert!(result.is_err());
 
         // Too long
         let long = "a".repeat(64);
-        let result = read_config(Some("default"), Some(&long));
+        let result = read_config(Some("default"), Some(&long), None);
         assert!(result.is_err());
     }
 
@@ -1729,6 +4753,40 @@ mod tests {
         drop(lock);
     }
 
+    /// Try to take the lock without blocking; true if it was free.
+    fn lock_is_free(lock_path: &Path) -> bool {
+        let file = fs::OpenOptions::new().write(true).open(lock_path).unwrap();
+        Flock::lock(file, FlockArg::LockExclusiveNonblock).is_ok()
+    }
+
+    #[test]
+    fn test_with_overlay_lock_held_only_during_closure() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let lock_path = dir.path().join("scope.lock");
+
+        let value = super::with_overlay_lock(&lock_path, || {
+            // A second open file description must not get the lock while held
+            assert!(!lock_is_free(&lock_path));
+            Ok(42)
+        })
+        .unwrap();
+        assert_eq!(value, 42);
+
+        // Released once the closure returns
+        assert!(lock_is_free(&lock_path));
+    }
+
+    #[test]
+    fn test_with_overlay_lock_released_on_error() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let lock_path = dir.path().join("scope-err.lock");
+
+        let result: Result<()> =
+            super::with_overlay_lock(&lock_path, || anyhow::bail!("create failed"));
+        assert!(result.is_err());
+        assert!(lock_is_free(&lock_path));
+    }
+
     #[test]
     fn test_ensure_etc_files_restores_empty() {
         let dir = tempfile::tempdir().expect("tempdir");
@@ -1827,12 +4885,69 @@ mod tests {
 
         std::env::set_var("REAPER_FILTER_ALLOWLIST", "/etc/shadow:/root/.ssh");
 
-        let config = super::read_filter_config();
-        assert_eq!(config.allowlist.len(), 2);
-        assert_eq!(config.allowlist[0], PathBuf::from("/etc/shadow"));
-        assert_eq!(config.allowlist[1], PathBuf::from("/root/.ssh"));
+        let config = super::read_filter_config();
+        assert_eq!(config.allowlist.len(), 2);
+        assert_eq!(config.allowlist[0], PathBuf::from("/etc/shadow"));
+        assert_eq!(config.allowlist[1], PathBuf::from("/root/.ssh"));
+
+        std::env::remove_var("REAPER_FILTER_ALLOWLIST");
+    }
+
+    #[test]
+    fn test_read_filter_config_strict() {
+        let _guard = ENV_LOCK.lock().unwrap();
+
+        std::env::remove_var("REAPER_FILTER_STRICT");
+        assert!(!super::read_filter_config().strict);
+
+        std::env::set_var("REAPER_FILTER_STRICT", "1");
+        assert!(super::read_filter_config().strict);
+
+        std::env::set_var("REAPER_FILTER_STRICT", "0");
+        assert!(!super::read_filter_config().strict);
+
+        std::env::remove_var("REAPER_FILTER_STRICT");
+    }
+
+    #[test]
+    fn test_apply_filters_strict_aborts_default_continues() {
+        let paths = vec![
+            PathBuf::from("/etc/shadow"),
+            PathBuf::from("/root/.ssh"),
+            PathBuf::from("/etc/ssh/ssh_host_rsa_key"),
+        ];
+        let attempted = std::cell::Cell::new(0);
+        let fail_ssh = |path: &Path| {
+            attempted.set(attempted.get() + 1);
+            if path == Path::new("/root/.ssh") {
+                bail!("bind-mounting filter over /root/.ssh: EPERM");
+            }
+            Ok(())
+        };
+
+        // Default: the failure is skipped and the remaining paths are filtered
+        assert_eq!(super::apply_filters(&paths, false, fail_ssh).unwrap(), 2);
+        assert_eq!(attempted.get(), 3);
+
+        // Strict: the first failure aborts and names the path
+        attempted.set(0);
+        let err = super::apply_filters(&paths, true, fail_ssh).unwrap_err();
+        let failure = err.downcast_ref::<super::FilterFailure>().unwrap();
+        assert_eq!(failure.path, PathBuf::from("/root/.ssh"));
+        assert!(failure.reason.contains("EPERM"));
+        assert!(err.to_string().contains("/root/.ssh"));
+        assert_eq!(attempted.get(), 2);
+    }
 
-        std::env::remove_var("REAPER_FILTER_ALLOWLIST");
+    #[test]
+    fn test_filter_failure_pipe_roundtrip() {
+        let failure = super::FilterFailure {
+            path: PathBuf::from("/etc/shadow"),
+            reason: "bind-mounting filter over /etc/shadow: EPERM".into(),
+        };
+        let msg = super::encode_filter_failure(&failure);
+        assert_eq!(msg[0], b'F');
+        assert_eq!(super::parse_filter_failure(&msg[1..]), failure);
     }
 
     #[test]
@@ -1855,6 +4970,61 @@ mod tests {
         assert!(!super::is_shadow_format_file(Path::new("/root/.ssh")));
     }
 
+    #[test]
+    fn test_ensure_placeholder_reuses_existing() {
+        let tmp = tempfile::tempdir().unwrap();
+        let host_file = tmp.path().join("secret");
+        fs::write(&host_file, "token").unwrap();
+        let scope = super::filter_scope_dir(
+            &tmp.path().join("filters"),
+            Path::new("/run/reaper/ns/default"),
+        );
+        assert_eq!(scope, tmp.path().join("filters/default"));
+        fs::create_dir_all(&scope).unwrap();
+
+        let first = super::ensure_placeholder(&host_file, &scope)
+            .unwrap()
+            .unwrap();
+        assert_eq!(fs::read(&first).unwrap(), b"");
+        fs::write(&first, "edited").unwrap();
+
+        let second = super::ensure_placeholder(&host_file, &scope)
+            .unwrap()
+            .unwrap();
+        assert_eq!(first, second);
+        assert_eq!(fs::read_to_string(&second).unwrap(), "edited");
+        assert_eq!(fs::read_dir(&scope).unwrap().count(), 1);
+
+        assert!(
+            super::ensure_placeholder(&tmp.path().join("missing"), &scope)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_remove_filter_placeholders_scoped_to_namespace() {
+        let tmp = tempfile::tempdir().unwrap();
+        let filter_dir = tmp.path().join("filters");
+        let host_dir = tmp.path().join("keys");
+        fs::create_dir_all(&host_dir).unwrap();
+
+        for ns in ["default", "default--group"] {
+            let scope = super::filter_scope_dir(&filter_dir, &Path::new("/run/reaper/ns").join(ns));
+            fs::create_dir_all(&scope).unwrap();
+            let placeholder = super::ensure_placeholder(&host_dir, &scope)
+                .unwrap()
+                .unwrap();
+            assert!(placeholder.is_dir());
+        }
+
+        super::remove_filter_placeholders(&filter_dir, Path::new("/run/reaper/ns/default"));
+        assert!(!filter_dir.join("default").exists());
+        assert!(filter_dir.join("default--group").exists());
+        // Removing an already-removed namespace is a no-op.
+        super::remove_filter_placeholders(&filter_dir, Path::new("/run/reaper/ns/default"));
+    }
+
     #[test]
     fn test_sanitize_shadow_content_basic() {
         let input = "root:$6$xyz$longhash:19000:0:99999:7:::\n\
@@ -1998,7 +5168,7 @@ mod tests {
             ),
         ];
 
-        let filtered = super::filter_volume_mounts(&mounts);
+        let filtered = super::filter_volume_mounts(&mounts, super::EtcHostsMode::Copy);
         assert_eq!(filtered.len(), 2);
         assert_eq!(filtered[0].destination, "/scripts");
         assert_eq!(filtered[1].destination, "/data");
@@ -2023,7 +5193,7 @@ mod tests {
             ),
         ];
 
-        let filtered = super::filter_volume_mounts(&mounts);
+        let filtered = super::filter_volume_mounts(&mounts, super::EtcHostsMode::Copy);
         assert_eq!(filtered.len(), 1);
         assert_eq!(filtered[0].destination, "/app/config");
     }
@@ -2063,14 +5233,152 @@ mod tests {
             ),
         ];
 
-        let filtered = super::filter_volume_mounts(&mounts);
+        let filtered = super::filter_volume_mounts(&mounts, super::EtcHostsMode::Copy);
         assert_eq!(filtered.len(), 1);
         assert_eq!(filtered[0].destination, "/scripts");
     }
 
+    fn etc_hosts_mounts() -> Vec<super::super::OciMount> {
+        vec![
+            make_mount(
+                "/etc/hosts",
+                Some("/var/lib/kubelet/pods/abc/etc-hosts"),
+                Some("bind"),
+                &["rbind", "rw"],
+            ),
+            make_mount(
+                "/etc/hostname",
+                Some("/var/lib/containerd/hostname"),
+                Some("bind"),
+                &["rbind", "ro"],
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_etc_hosts_copy_mode_uses_overlay_copy() {
+        let mounts = etc_hosts_mounts();
+        assert_eq!(
+            super::etc_hosts_source(&mounts, super::EtcHostsMode::Copy),
+            None
+        );
+        let filtered = super::filter_volume_mounts(&mounts, super::EtcHostsMode::Copy);
+        assert!(filtered.is_empty());
+    }
+
+    #[test]
+    fn test_etc_hosts_bind_mode_uses_kubelet_mount() {
+        let mounts = etc_hosts_mounts();
+        assert_eq!(
+            super::etc_hosts_source(&mounts, super::EtcHostsMode::Bind),
+            Some("/var/lib/kubelet/pods/abc/etc-hosts")
+        );
+        let filtered = super::filter_volume_mounts(&mounts, super::EtcHostsMode::Bind);
+        // Only /etc/hosts is let through; /etc/hostname stays k8s-internal
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].destination, "/etc/hosts");
+    }
+
+    #[test]
+    fn test_etc_hosts_bind_mode_without_mount_falls_back_to_copy() {
+        let mounts = vec![make_mount(
+            "/data",
+            Some("/var/lib/kubelet/pods/abc/data"),
+            Some("bind"),
+            &["rbind"],
+        )];
+        assert_eq!(
+            super::etc_hosts_source(&mounts, super::EtcHostsMode::Bind),
+            None
+        );
+    }
+
+    #[test]
+    fn test_read_etc_hosts_mode() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::remove_var("REAPER_ETC_HOSTS");
+        assert_eq!(super::read_etc_hosts_mode(), super::EtcHostsMode::Copy);
+        std::env::set_var("REAPER_ETC_HOSTS", "BIND");
+        assert_eq!(super::read_etc_hosts_mode(), super::EtcHostsMode::Bind);
+        std::env::set_var("REAPER_ETC_HOSTS", "copy");
+        assert_eq!(super::read_etc_hosts_mode(), super::EtcHostsMode::Copy);
+        std::env::set_var("REAPER_ETC_HOSTS", "nonsense");
+        assert_eq!(super::read_etc_hosts_mode(), super::EtcHostsMode::Copy);
+        std::env::remove_var("REAPER_ETC_HOSTS");
+    }
+
+    #[test]
+    fn test_check_bind_overlap_rejects_recursive() {
+        for (source, dest) in [
+            ("/data", "/data"),
+            ("/data/", "/data"),
+            ("/data", "/data/sub"),
+            ("/data/sub", "/data"),
+            ("/", "/mnt/host"),
+            ("/var/lib/x/../x", "/var/lib/x/y"),
+            ("/data/./a", "/data/a/b/"),
+        ] {
+            let err = check_bind_overlap(source, dest).unwrap_err().to_string();
+            assert!(
+                err.contains("recursive bind mount"),
+                "{source} -> {dest}: {err}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_check_bind_overlap_allows_disjoint() {
+        for (source, dest) in [
+            ("/data", "/data2"),
+            ("/data2", "/data"),
+            ("/var/lib/kubelet/pods/uid/volumes/cm", "/etc/config"),
+            (
+                "/var/lib/kubelet/pods/uid/volume-subpaths/cm/app/0",
+                "/etc/app/settings.yaml",
+            ),
+            ("/srv/a/b", "/srv/a/c"),
+        ] {
+            assert!(
+                check_bind_overlap(source, dest).is_ok(),
+                "{source} -> {dest}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_sort_by_destination_depth() {
+        let mounts: Vec<_> = ["/data/a/b", "/data", "/other", "/data/a", "/cache"]
+            .iter()
+            .map(|dest| make_mount(dest, Some("/host/src"), Some("bind"), &["rbind"]))
+            .collect();
+        let mut refs: Vec<_> = mounts.iter().collect();
+        sort_by_destination_depth(&mut refs);
+        let order: Vec<&str> = refs.iter().map(|m| m.destination.as_str()).collect();
+        // Parents before children; config order kept within a depth
+        assert_eq!(
+            order,
+            vec!["/data", "/other", "/cache", "/data/a", "/data/a/b"]
+        );
+    }
+
+    #[test]
+    fn test_is_already_mounted() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let source = tmp.path().join("source");
+        let dest = tmp.path().join("dest");
+        fs::create_dir(&source).unwrap();
+        assert!(!is_already_mounted(&source, &dest));
+        fs::create_dir(&dest).unwrap();
+        assert!(!is_already_mounted(&source, &dest));
+        // A destination resolving to the source itself is what a bind in
+        // effect looks like
+        std::os::unix::fs::symlink(&source, tmp.path().join("link")).unwrap();
+        assert!(is_already_mounted(&source, &tmp.path().join("link")));
+    }
+
     #[test]
     fn test_filter_volume_mounts_empty_input() {
-        let filtered = super::filter_volume_mounts(&[]);
+        let filtered = super::filter_volume_mounts(&[], super::EtcHostsMode::Copy);
         assert!(filtered.is_empty());
     }
 
@@ -2162,7 +5470,7 @@ mod tests {
             ),
         ];
 
-        let filtered = super::filter_volume_mounts(&mounts);
+        let filtered = super::filter_volume_mounts(&mounts, super::EtcHostsMode::Copy);
         assert_eq!(filtered.len(), 3);
         assert_eq!(filtered[0].destination, "/scripts");
         assert_eq!(filtered[1].destination, "/data");
@@ -2235,6 +5543,68 @@ mod tests {
         std::env::remove_var("REAPER_DNS_MODE");
     }
 
+    #[test]
+    fn test_dns_mode_annotation_overrides_env() {
+        let _guard = ENV_LOCK.lock().unwrap();
+
+        std::env::set_var("REAPER_DNS_MODE", "kubernetes");
+        let config = super::read_dns_config_with_override(Some("host"));
+        assert_eq!(config.mode, super::DnsMode::Host);
+
+        std::env::set_var("REAPER_DNS_MODE", "host");
+        for mode in ["kubernetes", "K8S"] {
+            let config = super::read_dns_config_with_override(Some(mode));
+            assert_eq!(config.mode, super::DnsMode::Kubernetes);
+        }
+
+        // No annotation: the node setting applies
+        let config = super::read_dns_config_with_override(None);
+        assert_eq!(config.mode, super::DnsMode::Host);
+
+        std::env::remove_var("REAPER_DNS_MODE");
+    }
+
+    #[test]
+    fn test_read_pod_dns_config_host_network() {
+        let _guard = ENV_LOCK.lock().unwrap();
+
+        std::env::set_var("REAPER_DNS_MODE", "kubernetes");
+        let config = super::read_pod_dns_config(None, true);
+        assert_eq!(config.mode, super::DnsMode::Host);
+        let config = super::read_pod_dns_config(None, false);
+        assert_eq!(config.mode, super::DnsMode::Kubernetes);
+        // An explicit annotation still wins for host-network pods
+        let config = super::read_pod_dns_config(Some("kubernetes"), true);
+        assert_eq!(config.mode, super::DnsMode::Kubernetes);
+
+        std::env::remove_var("REAPER_DNS_MODE");
+    }
+
+    #[test]
+    fn test_hostname_file_content() {
+        assert_eq!(super::hostname_file_content("web-0", None), b"web-0\n");
+        // The kubelet's file wins, unless it is empty
+        assert_eq!(
+            super::hostname_file_content("web-0", Some(b"pod-hostname\n".to_vec())),
+            b"pod-hostname\n"
+        );
+        assert_eq!(
+            super::hostname_file_content("web-0", Some(b"\n".to_vec())),
+            b"web-0\n"
+        );
+    }
+
+    #[test]
+    fn test_write_etc_hostname() {
+        let etc = tempfile::tempdir().unwrap();
+        std::fs::write(etc.path().join("hostname"), "node-1\n").unwrap();
+        super::write_etc_hostname(etc.path(), "web-0", &[]).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(etc.path().join("hostname")).unwrap(),
+            "web-0\n"
+        );
+    }
+
     // --- PID file fallback tests ---
 
     #[test]
@@ -2353,4 +5723,465 @@ mod tests {
         // On macOS, get_ns_inode fails → also false
         assert!(!super::namespace_exists(&ns_path));
     }
+
+    #[test]
+    fn test_record_and_stop_anchor_helper() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let ns_path = dir.path().join("test-ns");
+        let mut helper = std::process::Command::new("sleep")
+            .arg("60")
+            .spawn()
+            .unwrap();
+        let pid = helper.id() as i32;
+
+        let inode = super::record_anchor_helper(&ns_path, pid).unwrap();
+        assert_eq!(
+            super::read_helper_info(&super::helper_pid_path(&ns_path)),
+            Some((pid, inode))
+        );
+
+        let stopped = super::stop_anchor_helper(&ns_path, std::time::Duration::from_secs(5));
+        assert_eq!(stopped, Some(pid));
+        let status = helper.wait().unwrap();
+        use std::os::unix::process::ExitStatusExt;
+        assert_eq!(
+            status.signal(),
+            Some(nix::sys::signal::Signal::SIGKILL as i32)
+        );
+    }
+
+    #[test]
+    fn test_stop_anchor_helper_skips_mismatched_inode() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let ns_path = dir.path().join("test-ns");
+        let mut helper = std::process::Command::new("sleep")
+            .arg("60")
+            .spawn()
+            .unwrap();
+        let pid = helper.id() as i32;
+
+        // Recorded inode doesn't match: the PID may have been recycled.
+        super::write_helper_info(&super::helper_pid_path(&ns_path), pid, 0).unwrap();
+        assert_eq!(
+            super::stop_anchor_helper(&ns_path, std::time::Duration::from_millis(50)),
+            None
+        );
+        assert!(helper.try_wait().unwrap().is_none());
+        helper.kill().unwrap();
+        helper.wait().unwrap();
+
+        // No record at all.
+        let other = dir.path().join("other-ns");
+        assert_eq!(
+            super::stop_anchor_helper(&other, std::time::Duration::from_millis(50)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_anchor_alive() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let ns_path = dir.path().join("test-ns");
+        assert!(!super::anchor_alive(&ns_path));
+
+        let mut helper = std::process::Command::new("sleep")
+            .arg("60")
+            .spawn()
+            .unwrap();
+        let pid = helper.id() as i32;
+        super::record_anchor_helper(&ns_path, pid).unwrap();
+        assert!(super::anchor_alive(&ns_path));
+
+        // Exited (and reaped): no namespace link any more
+        helper.kill().unwrap();
+        helper.wait().unwrap();
+        assert!(!super::anchor_alive(&ns_path));
+
+        // A live PID holding another namespace is a recycled PID
+        let my_pid = std::process::id() as i32;
+        super::write_helper_info(&super::helper_pid_path(&ns_path), my_pid, 0).unwrap();
+        assert!(!super::anchor_alive(&ns_path));
+    }
+
+    #[test]
+    fn test_anchor_action() {
+        use super::{anchor_action, AnchorAction};
+        // Live anchor, or nothing recorded and nothing mounted
+        assert_eq!(anchor_action(true, true, true, None), AnchorAction::Keep);
+        assert_eq!(anchor_action(true, true, false, None), AnchorAction::Keep);
+        assert_eq!(anchor_action(false, false, false, None), AnchorAction::Keep);
+        // Dead anchor, mount intact: new anchor, no need to bind again
+        assert_eq!(
+            anchor_action(true, false, true, Some(42)),
+            AnchorAction::Reanchor {
+                from: 42,
+                rebind: false
+            }
+        );
+        // Dead anchor, mount gone (or PID-file mode): bind the new one
+        assert_eq!(
+            anchor_action(true, false, false, Some(42)),
+            AnchorAction::Reanchor {
+                from: 42,
+                rebind: true
+            }
+        );
+        // Mounted but never recorded
+        assert_eq!(
+            anchor_action(false, false, true, Some(42)),
+            AnchorAction::Reanchor {
+                from: 42,
+                rebind: false
+            }
+        );
+        // Nobody left to take the overlay root from
+        assert_eq!(
+            anchor_action(true, false, true, None),
+            AnchorAction::Recreate
+        );
+        assert_eq!(
+            anchor_action(true, false, false, None),
+            AnchorAction::Recreate
+        );
+    }
+
+    #[test]
+    fn test_ns_mount_valid() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let ns_path = dir.path().join("test-ns");
+        assert!(!super::ns_mount_valid(&ns_path));
+        // The empty file left behind by a lost mount
+        fs::write(&ns_path, "").unwrap();
+        assert!(!super::ns_mount_valid(&ns_path));
+
+        if !nix::unistd::getuid().is_root() {
+            return;
+        }
+        // Any namespace file will do; the kernel refuses our own mount
+        // namespace here (it would pin itself)
+        mount(
+            Some("/proc/self/ns/uts"),
+            &ns_path,
+            None::<&str>,
+            MsFlags::MS_BIND,
+            None::<&str>,
+        )
+        .unwrap();
+        let valid = super::ns_mount_valid(&ns_path);
+        umount2(&ns_path, MntFlags::MNT_DETACH).unwrap();
+        assert!(valid);
+    }
+
+    #[test]
+    fn test_namespace_member() {
+        let my_pid = std::process::id() as i32;
+        let inode = super::get_ns_inode(my_pid).unwrap();
+        let member = super::namespace_member(inode, None).unwrap();
+        assert_eq!(super::get_ns_inode(member).unwrap(), inode);
+        assert_ne!(super::namespace_member(inode, Some(member)), Some(member));
+        assert_eq!(super::namespace_member(0, None), None);
+    }
+
+    #[test]
+    fn test_reanchor_enabled() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::remove_var("REAPER_OVERLAY_REANCHOR");
+        assert!(super::reanchor_enabled());
+        std::env::set_var("REAPER_OVERLAY_REANCHOR", "0");
+        assert!(!super::reanchor_enabled());
+        std::env::set_var("REAPER_OVERLAY_REANCHOR", "false");
+        assert!(!super::reanchor_enabled());
+        std::env::remove_var("REAPER_OVERLAY_REANCHOR");
+    }
+
+    // --- missing-volume policy tests ---
+
+    #[test]
+    fn test_verify_mount_target_compares_identity() {
+        let tmp = tempfile::tempdir().unwrap();
+        let source = tmp.path().join("source");
+        let dest = tmp.path().join("dest");
+        fs::create_dir(&source).unwrap();
+        fs::create_dir(&dest).unwrap();
+
+        let expected = file_identity(&source).unwrap();
+        assert!(verify_mount_target(expected, &source).is_ok());
+        let err = verify_mount_target(expected, &dest).unwrap_err();
+        assert!(err.to_string().contains("did not take effect"), "{}", err);
+        assert!(verify_mount_target(expected, &tmp.path().join("missing")).is_err());
+
+        // With root, a real bind mount makes the destination match
+        if !nix::unistd::getuid().is_root() {
+            return;
+        }
+        mount(
+            Some(&source),
+            &dest,
+            None::<&str>,
+            MsFlags::MS_BIND,
+            None::<&str>,
+        )
+        .unwrap();
+        let result = verify_mount_target(expected, &dest);
+        umount2(&dest, MntFlags::MNT_DETACH).unwrap();
+        assert!(result.is_ok(), "{:?}", result);
+    }
+
+    #[test]
+    fn test_verify_mounts_enabled() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::remove_var("REAPER_VERIFY_MOUNTS");
+        assert!(!verify_mounts_enabled());
+        std::env::set_var("REAPER_VERIFY_MOUNTS", "1");
+        assert!(verify_mounts_enabled());
+        std::env::set_var("REAPER_VERIFY_MOUNTS", "no");
+        assert!(!verify_mounts_enabled());
+        std::env::remove_var("REAPER_VERIFY_MOUNTS");
+    }
+
+    #[test]
+    fn test_mount_source_wait() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::remove_var("REAPER_MOUNT_SOURCE_WAIT_MS");
+        assert_eq!(mount_source_wait(), Duration::from_millis(2000));
+        std::env::set_var("REAPER_MOUNT_SOURCE_WAIT_MS", "0");
+        assert_eq!(mount_source_wait(), Duration::ZERO);
+        std::env::set_var("REAPER_MOUNT_SOURCE_WAIT_MS", "soon");
+        assert_eq!(mount_source_wait(), Duration::from_millis(2000));
+        std::env::remove_var("REAPER_MOUNT_SOURCE_WAIT_MS");
+    }
+
+    #[test]
+    fn test_wait_until_source_appears_after_delay() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("configmap");
+        let creator = {
+            let source = source.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(150));
+                fs::create_dir(&source).unwrap();
+            })
+        };
+        let start = Instant::now();
+        assert!(wait_until(start + Duration::from_secs(5), || source.exists()));
+        assert!(start.elapsed() >= Duration::from_millis(150));
+        creator.join().unwrap();
+
+        // A source that never shows up is given up on at the deadline
+        let missing = dir.path().join("secret");
+        let start = Instant::now();
+        assert!(!wait_until(start + Duration::from_millis(120), || missing.exists()));
+        assert!(start.elapsed() >= Duration::from_millis(120));
+        // An exhausted deadline still checks once
+        assert!(wait_until(start, || source.exists()));
+    }
+
+    #[test]
+    fn test_auto_teardown_settings() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::remove_var("REAPER_OVERLAY_AUTO_TEARDOWN");
+        std::env::remove_var("REAPER_OVERLAY_TEARDOWN_GRACE_SECS");
+        assert!(!auto_teardown_enabled());
+        assert_eq!(teardown_grace(), Duration::from_secs(30));
+        std::env::set_var("REAPER_OVERLAY_AUTO_TEARDOWN", "1");
+        std::env::set_var("REAPER_OVERLAY_TEARDOWN_GRACE_SECS", "5");
+        assert!(auto_teardown_enabled());
+        assert_eq!(teardown_grace(), Duration::from_secs(5));
+        std::env::remove_var("REAPER_OVERLAY_AUTO_TEARDOWN");
+        std::env::remove_var("REAPER_OVERLAY_TEARDOWN_GRACE_SECS");
+    }
+
+    #[test]
+    fn test_teardown_only_at_zero_after_grace() {
+        use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+        use std::sync::Arc;
+        let grace = Duration::from_millis(100);
+
+        // Containers still running: no teardown, no wait
+        let torn_down = AtomicBool::new(false);
+        let start = Instant::now();
+        let result = teardown_after_grace(
+            grace,
+            || 2,
+            || {
+                torn_down.store(true, Ordering::SeqCst);
+                Ok(true)
+            },
+        );
+        assert!(!result.unwrap());
+        assert!(!torn_down.load(Ordering::SeqCst));
+        assert!(start.elapsed() < grace);
+
+        // Idle throughout: torn down once the grace period is over
+        let start = Instant::now();
+        let result = teardown_after_grace(
+            grace,
+            || 0,
+            || {
+                torn_down.store(true, Ordering::SeqCst);
+                Ok(true)
+            },
+        );
+        assert!(result.unwrap());
+        assert!(torn_down.load(Ordering::SeqCst));
+        assert!(start.elapsed() >= grace);
+
+        // A container arriving during the grace period keeps it
+        let active = Arc::new(AtomicUsize::new(0));
+        let arrival = {
+            let active = Arc::clone(&active);
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(30));
+                active.store(1, Ordering::SeqCst);
+            })
+        };
+        torn_down.store(false, Ordering::SeqCst);
+        let result = teardown_after_grace(
+            grace,
+            || active.load(Ordering::SeqCst),
+            || {
+                torn_down.store(true, Ordering::SeqCst);
+                Ok(true)
+            },
+        );
+        arrival.join().unwrap();
+        assert!(!result.unwrap());
+        assert!(!torn_down.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_host_pid() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::remove_var("REAPER_HOST_PID");
+        assert_eq!(host_pid(), 1);
+        std::env::set_var("REAPER_HOST_PID", " 42 ");
+        assert_eq!(host_pid(), 42);
+        for bad in ["0", "-3", "init"] {
+            std::env::set_var("REAPER_HOST_PID", bad);
+            assert_eq!(host_pid(), 1, "{:?} should fall back to 1", bad);
+        }
+        std::env::remove_var("REAPER_HOST_PID");
+        assert_eq!(
+            host_view(42, "/var/lib").to_str(),
+            Some("/proc/42/root/var/lib")
+        );
+    }
+
+    #[test]
+    fn test_validate_host_mount_ns() {
+        assert!(validate_host_mount_ns(1, "mnt:[4026531841]", "mnt:[4026532200]").is_ok());
+        let err = validate_host_mount_ns(1, "mnt:[4026532200]", "mnt:[4026532200]")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("REAPER_HOST_PID"), "{}", err);
+    }
+
+    #[test]
+    fn test_resolve_host_pid_unreadable_or_own_ns() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        // PID that can't exist (above pid_max): no host namespace, not an error.
+        std::env::set_var("REAPER_HOST_PID", "2147483647");
+        assert_eq!(resolve_host_pid().unwrap(), None);
+        // Our own PID shares our mount namespace.
+        std::env::set_var("REAPER_HOST_PID", std::process::id().to_string());
+        assert!(resolve_host_pid().is_err());
+        std::env::remove_var("REAPER_HOST_PID");
+    }
+
+    #[test]
+    fn test_missing_volume_policy_parse() {
+        assert_eq!(
+            MissingVolumePolicy::parse("skip"),
+            Some(MissingVolumePolicy::Skip)
+        );
+        assert_eq!(
+            MissingVolumePolicy::parse("FAIL"),
+            Some(MissingVolumePolicy::Fail)
+        );
+        assert_eq!(MissingVolumePolicy::parse("ignore"), None);
+        assert_eq!(MissingVolumePolicy::parse(""), None);
+    }
+
+    #[test]
+    fn test_read_volume_policy_default_skip() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::remove_var("REAPER_MISSING_VOLUME_POLICY");
+        let policy = read_volume_policy_with_override(None);
+        assert_eq!(policy.default, MissingVolumePolicy::Skip);
+        assert!(policy.overrides.is_empty());
+    }
+
+    #[test]
+    fn test_read_volume_policy_env() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::set_var("REAPER_MISSING_VOLUME_POLICY", "fail");
+        assert_eq!(
+            read_volume_policy_with_override(None).default,
+            MissingVolumePolicy::Fail
+        );
+        std::env::set_var("REAPER_MISSING_VOLUME_POLICY", "bogus");
+        assert_eq!(
+            read_volume_policy_with_override(None).default,
+            MissingVolumePolicy::Skip
+        );
+        std::env::remove_var("REAPER_MISSING_VOLUME_POLICY");
+    }
+
+    #[test]
+    fn test_volume_policy_per_mount_override() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::set_var("REAPER_MISSING_VOLUME_POLICY", "skip");
+        let overrides = HashMap::from([
+            ("/etc/creds/".to_string(), "fail".to_string()),
+            ("/cache".to_string(), "skip".to_string()),
+        ]);
+        let policy = read_volume_policy_with_override(Some(&overrides));
+        std::env::remove_var("REAPER_MISSING_VOLUME_POLICY");
+
+        assert_eq!(
+            policy.for_destination("/etc/creds"),
+            MissingVolumePolicy::Fail
+        );
+        assert_eq!(
+            policy.for_destination("/etc/creds/"),
+            MissingVolumePolicy::Fail
+        );
+        assert_eq!(policy.for_destination("/cache"), MissingVolumePolicy::Skip);
+        // Unlisted destinations use the node default
+        assert_eq!(policy.for_destination("/data"), MissingVolumePolicy::Skip);
+    }
+
+    #[test]
+    fn test_volume_policy_override_can_relax_node_fail() {
+        let policy = VolumePolicy {
+            default: MissingVolumePolicy::Fail,
+            overrides: HashMap::from([("/optional".to_string(), MissingVolumePolicy::Skip)]),
+        };
+        assert_eq!(
+            policy.for_destination("/optional"),
+            MissingVolumePolicy::Skip
+        );
+        assert_eq!(policy.for_destination("/other"), MissingVolumePolicy::Fail);
+    }
+
+    #[test]
+    fn test_check_char_device() {
+        let dir = tempfile::TempDir::new().unwrap();
+
+        // Missing: refused, never created
+        let missing = dir.path().join("fuse");
+        let err = check_char_device(&missing, 10, 229).unwrap_err();
+        assert!(err.to_string().contains("modprobe fuse"), "{err}");
+        assert!(!missing.exists());
+
+        // A regular file is refused
+        fs::write(dir.path().join("file"), "").unwrap();
+        assert!(check_char_device(&dir.path().join("file"), 10, 229).is_err());
+
+        // /dev/null is 1:3 on Linux: accepted as such, refused as fuse
+        if Path::new("/dev/null").exists() {
+            check_char_device(Path::new("/dev/null"), 1, 3).unwrap();
+            assert!(check_char_device(Path::new("/dev/null"), 10, 229).is_err());
+        }
+    }
 }
@@ -0,0 +1,301 @@
+//! Node readiness checks for `reaper-runtime preflight`.
+//!
+//! Each check returns a [`CheckResult`]; hard requirements report `Fail`,
+//! optional features report `Warn`. The probes take their inputs (kernel
+//! release, /proc contents, paths) as arguments so they can be tested
+//! without root.
+
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+use crate::overlay;
+use crate::state;
+
+/// Minimum kernel for open_tree()/move_mount(), used for volume mounts.
+const MIN_KERNEL: (u32, u32) = (5, 2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CheckResult {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<&'static str>,
+}
+
+impl CheckResult {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Pass,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, hint: &'static str) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Fail,
+            detail: detail.into(),
+            hint: Some(hint),
+        }
+    }
+
+    fn warn(name: &'static str, detail: impl Into<String>, hint: &'static str) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Warn,
+            detail: detail.into(),
+            hint: Some(hint),
+        }
+    }
+}
+
+/// Parse "major.minor" from a kernel release string like "6.8.0-45-generic".
+pub fn parse_kernel_version(release: &str) -> Option<(u32, u32)> {
+    let mut parts = release.trim().split(['.', '-', '+']);
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+pub fn check_kernel_version(release: &str) -> CheckResult {
+    const NAME: &str = "kernel-version";
+    const HINT: &str = "upgrade the node kernel to 5.2 or newer";
+    match parse_kernel_version(release) {
+        Some(v) if v >= MIN_KERNEL => CheckResult::pass(NAME, release.trim()),
+        Some(_) => CheckResult::fail(
+            NAME,
+            format!(
+                "{} is older than {}.{}",
+                release.trim(),
+                MIN_KERNEL.0,
+                MIN_KERNEL.1
+            ),
+            HINT,
+        ),
+        None => CheckResult::fail(
+            NAME,
+            format!("cannot parse kernel release {:?}", release.trim()),
+            HINT,
+        ),
+    }
+}
+
+/// `proc_filesystems` is the content of /proc/filesystems.
+pub fn check_overlayfs(proc_filesystems: &str) -> CheckResult {
+    const NAME: &str = "overlayfs";
+    let found = proc_filesystems
+        .lines()
+        .any(|line| line.split_whitespace().last() == Some("overlay"));
+    if found {
+        CheckResult::pass(NAME, "overlay listed in /proc/filesystems")
+    } else {
+        CheckResult::fail(
+            NAME,
+            "overlay not listed in /proc/filesystems",
+            "load the module with `modprobe overlay` and add it to /etc/modules-load.d",
+        )
+    }
+}
+
+/// `proc_mounts` is the content of /proc/mounts (or /proc/self/mounts).
+pub fn check_cgroup_v2(proc_mounts: &str) -> CheckResult {
+    const NAME: &str = "cgroup-v2";
+    let mountpoint = proc_mounts.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        let (_, target, fstype) = (fields.next()?, fields.next()?, fields.next()?);
+        (fstype == "cgroup2").then(|| target.to_string())
+    });
+    match mountpoint {
+        Some(target) => CheckResult::pass(NAME, format!("cgroup2 mounted at {}", target)),
+        None => CheckResult::warn(
+            NAME,
+            "no cgroup2 mount found",
+            "boot with systemd.unified_cgroup_hierarchy=1 to enable the unified hierarchy",
+        ),
+    }
+}
+
+/// Verify `dir` exists (creating it if needed) and accepts new files.
+pub fn check_state_dir_writable(dir: &Path) -> CheckResult {
+    const NAME: &str = "state-dir";
+    const HINT: &str = "run as root, or point REAPER_RUNTIME_ROOT at a writable directory";
+    if let Err(e) = fs::create_dir_all(dir) {
+        return CheckResult::fail(
+            NAME,
+            format!("cannot create {}: {}", dir.display(), e),
+            HINT,
+        );
+    }
+    let probe = dir.join(format!(".preflight-{}", std::process::id()));
+    match fs::write(&probe, b"") {
+        Ok(()) => {
+            let _ = fs::remove_file(&probe);
+            CheckResult::pass(NAME, format!("{} is writable", dir.display()))
+        }
+        Err(e) => CheckResult::fail(
+            NAME,
+            format!("cannot write to {}: {}", dir.display(), e),
+            HINT,
+        ),
+    }
+}
+
+pub fn check_mount_api() -> CheckResult {
+    const NAME: &str = "mount-api";
+    match overlay::probe_mount_api() {
+        Ok(()) => CheckResult::pass(NAME, "open_tree() and move_mount() available"),
+        Err(e) => CheckResult::fail(
+            NAME,
+            format!("{:#}", e),
+            "volume mounts need Linux 5.2+ and a seccomp profile that allows open_tree/move_mount",
+        ),
+    }
+}
+
+/// Run every check against the live node.
+#[cfg(not(tarpaulin_include))]
+pub fn run_checks() -> Vec<CheckResult> {
+    let read = |path: &str| fs::read_to_string(path).unwrap_or_default();
+    vec![
+        check_kernel_version(&read("/proc/sys/kernel/osrelease")),
+        check_overlayfs(&read("/proc/filesystems")),
+        check_mount_api(),
+        check_cgroup_v2(&read("/proc/self/mounts")),
+        check_state_dir_writable(&state::state_dir()),
+    ]
+}
+
+/// Human-readable report, one line per check plus an indented hint on failure.
+pub fn render_report(results: &[CheckResult]) -> String {
+    let mut out = String::new();
+    for r in results {
+        let tag = match r.status {
+            CheckStatus::Pass => "PASS",
+            CheckStatus::Warn => "WARN",
+            CheckStatus::Fail => "FAIL",
+        };
+        out.push_str(&format!("[{}] {:<16} {}\n", tag, r.name, r.detail));
+        if let Some(hint) = r.hint {
+            out.push_str(&format!("       hint: {}\n", hint));
+        }
+    }
+    out
+}
+
+pub fn hard_failures(results: &[CheckResult]) -> usize {
+    results
+        .iter()
+        .filter(|r| r.status == CheckStatus::Fail)
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_kernel_version() {
+        assert_eq!(parse_kernel_version("6.8.0-45-generic"), Some((6, 8)));
+        assert_eq!(parse_kernel_version("5.2.0\n"), Some((5, 2)));
+        assert_eq!(
+            parse_kernel_version("5.15.153.1-microsoft-standard-WSL2"),
+            Some((5, 15))
+        );
+        assert_eq!(parse_kernel_version("4.19-rc1"), Some((4, 19)));
+        assert_eq!(parse_kernel_version("6"), None);
+        assert_eq!(parse_kernel_version(""), None);
+        assert_eq!(parse_kernel_version("linux"), None);
+    }
+
+    #[test]
+    fn test_check_kernel_version() {
+        assert_eq!(check_kernel_version("5.2.0").status, CheckStatus::Pass);
+        assert_eq!(
+            check_kernel_version("6.1.0-18-amd64").status,
+            CheckStatus::Pass
+        );
+        assert_eq!(check_kernel_version("5.1.21").status, CheckStatus::Fail);
+        assert_eq!(check_kernel_version("4.19.0").status, CheckStatus::Fail);
+        let garbage = check_kernel_version("garbage");
+        assert_eq!(garbage.status, CheckStatus::Fail);
+        assert!(garbage.hint.is_some());
+    }
+
+    #[test]
+    fn test_check_overlayfs() {
+        let with = "nodev\tsysfs\nnodev\ttmpfs\n\text4\nnodev\toverlay\n";
+        assert_eq!(check_overlayfs(with).status, CheckStatus::Pass);
+        let without = "nodev\tsysfs\n\text4\nnodev\toverlayfoo\n";
+        let r = check_overlayfs(without);
+        assert_eq!(r.status, CheckStatus::Fail);
+        assert!(r.hint.unwrap().contains("modprobe overlay"));
+    }
+
+    #[test]
+    fn test_check_cgroup_v2() {
+        let v2 = "sysfs /sys sysfs rw 0 0\ncgroup2 /sys/fs/cgroup cgroup2 rw,nosuid 0 0\n";
+        let r = check_cgroup_v2(v2);
+        assert_eq!(r.status, CheckStatus::Pass);
+        assert!(r.detail.contains("/sys/fs/cgroup"));
+
+        let v1 = "tmpfs /sys/fs/cgroup tmpfs ro 0 0\ncgroup /sys/fs/cgroup/memory cgroup rw,memory 0 0\n";
+        assert_eq!(check_cgroup_v2(v1).status, CheckStatus::Warn);
+    }
+
+    #[test]
+    fn test_check_state_dir_writable() {
+        let dir = TempDir::new().unwrap();
+        let nested = dir.path().join("run").join("reaper");
+        let r = check_state_dir_writable(&nested);
+        assert_eq!(r.status, CheckStatus::Pass);
+        assert!(nested.is_dir());
+        // Probe file is cleaned up
+        assert_eq!(fs::read_dir(&nested).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_check_state_dir_not_a_directory() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("file");
+        fs::write(&file, b"").unwrap();
+        let r = check_state_dir_writable(&file.join("sub"));
+        assert_eq!(r.status, CheckStatus::Fail);
+        assert!(r.hint.is_some());
+    }
+
+    #[test]
+    fn test_render_report_and_hard_failures() {
+        let results = vec![
+            CheckResult::pass("kernel-version", "6.8.0"),
+            CheckResult::warn("cgroup-v2", "no cgroup2 mount found", "enable it"),
+            CheckResult::fail("overlayfs", "missing", "modprobe overlay"),
+        ];
+        let report = render_report(&results);
+        assert!(report.contains("[PASS] kernel-version"));
+        assert!(report.contains("[WARN] cgroup-v2"));
+        assert!(report.contains("[FAIL] overlayfs"));
+        assert!(report.contains("hint: modprobe overlay"));
+        assert_eq!(hard_failures(&results), 1);
+        assert_eq!(hard_failures(&results[..2]), 0);
+    }
+
+    #[test]
+    fn test_check_result_json_shape() {
+        let json = serde_json::to_value(CheckResult::pass("mount-api", "ok")).unwrap();
+        assert_eq!(json["status"], "pass");
+        assert!(json.get("hint").is_none());
+    }
+}
@@ -0,0 +1,204 @@
+//! Upfront check that the runtime holds the capabilities a container start
+//! needs.
+//!
+//! Without it, a runtime lacking e.g. CAP_SYS_ADMIN (not root and not in
+//! rootless mode, or a restricted runtime pod) gets as far as the overlay
+//! mount and fails there with a bare EPERM. [`check`] fails before anything
+//! is set up, naming each missing capability and what needs it.
+//!
+//! Mounting (the overlay root, `pivot_root`, volumes, `/proc`) needs
+//! CAP_SYS_ADMIN; switching to `process.user` needs CAP_SETGID for the
+//! groups and CAP_SETUID for a different uid. In rootless mode all of this
+//! happens inside the runtime's own user namespace, where it holds every
+//! capability, so nothing is required up front. reaper creates no device
+//! nodes and sets no sysctls (workloads see the host's `/dev` and kernel
+//! settings), so `linux.devices` and `linux.sysctl` add no requirement.
+
+use crate::caps::capability_number;
+use crate::state::OciUser;
+use anyhow::{bail, Result};
+
+/// A capability the runtime needs, and why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Requirement {
+    /// As in the OCI spec, e.g. `CAP_SYS_ADMIN`
+    pub capability: &'static str,
+    pub operation: String,
+}
+
+/// The privileged steps of a container start.
+#[derive(Debug, Clone, Copy)]
+pub struct StartPlan<'a> {
+    pub rootless: bool,
+    /// `process.user`, if the workload switches user
+    pub user: Option<&'a OciUser>,
+    /// Whether a refused `setgroups` fails the start (`REAPER_SETGROUPS_POLICY`)
+    pub strict_setgroups: bool,
+    /// uid and gid the runtime runs as
+    pub runtime_ids: (u32, u32),
+}
+
+/// Capabilities `plan` needs.
+pub fn requirements(plan: &StartPlan) -> Vec<Requirement> {
+    if plan.rootless {
+        return Vec::new();
+    }
+    let mut required = vec![Requirement {
+        capability: "CAP_SYS_ADMIN",
+        operation: "mounting the overlay root filesystem and volumes".to_string(),
+    }];
+    let Some(user) = plan.user else {
+        return required;
+    };
+    let (uid, gid) = plan.runtime_ids;
+    if plan.strict_setgroups {
+        required.push(Requirement {
+            capability: "CAP_SETGID",
+            operation: if user.additional_gids.is_empty() {
+                "clearing supplementary groups".to_string()
+            } else {
+                format!("setting supplementary groups {:?}", user.additional_gids)
+            },
+        });
+    }
+    if user.gid != gid {
+        required.push(Requirement {
+            capability: "CAP_SETGID",
+            operation: format!("switching to gid {}", user.gid),
+        });
+    }
+    if user.uid != uid {
+        required.push(Requirement {
+            capability: "CAP_SETUID",
+            operation: format!("switching to uid {}", user.uid),
+        });
+    }
+    required
+}
+
+/// Fail unless every capability in `required` is in `held` (a bitmask, as
+/// from [`crate::caps::current_effective`]). The error lists each missing
+/// capability with the operations that need it.
+pub fn check(required: &[Requirement], held: u64) -> Result<()> {
+    let mut missing: Vec<(&str, Vec<&str>)> = Vec::new();
+    for req in required {
+        let has = capability_number(req.capability).is_some_and(|cap| held & 1 << cap != 0);
+        if has {
+            continue;
+        }
+        match missing.iter_mut().find(|(cap, _)| *cap == req.capability) {
+            Some((_, ops)) => ops.push(&req.operation),
+            None => missing.push((req.capability, vec![&req.operation])),
+        }
+    }
+    if missing.is_empty() {
+        return Ok(());
+    }
+    let details: Vec<String> = missing
+        .iter()
+        .map(|(cap, ops)| format!("{} (needed for {})", cap, ops.join(", ")))
+        .collect();
+    bail!(
+        "runtime lacks {}; run reaper as root or with these capabilities, \
+         or set REAPER_ROOTLESS=1",
+        details.join("; ")
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(uid: u32, gid: u32, additional_gids: Vec<u32>) -> OciUser {
+        OciUser {
+            uid,
+            gid,
+            additional_gids,
+            umask: None,
+        }
+    }
+
+    fn caps_of(required: &[Requirement]) -> Vec<&str> {
+        required.iter().map(|r| r.capability).collect()
+    }
+
+    fn mask(names: &[&str]) -> u64 {
+        names
+            .iter()
+            .map(|n| 1 << capability_number(n).unwrap())
+            .fold(0, |mask, bit| mask | bit)
+    }
+
+    #[test]
+    fn test_requirements_from_plan() {
+        let plan = StartPlan {
+            rootless: false,
+            user: None,
+            strict_setgroups: true,
+            runtime_ids: (0, 0),
+        };
+        assert_eq!(caps_of(&requirements(&plan)), ["CAP_SYS_ADMIN"]);
+
+        let workload = user(1000, 1000, vec![44]);
+        let plan = StartPlan {
+            user: Some(&workload),
+            ..plan
+        };
+        let required = requirements(&plan);
+        assert_eq!(
+            caps_of(&required),
+            ["CAP_SYS_ADMIN", "CAP_SETGID", "CAP_SETGID", "CAP_SETUID"]
+        );
+        assert_eq!(required[3].operation, "switching to uid 1000");
+
+        // Same ids as the runtime, groups best effort: only the mounts
+        let same = user(1000, 1000, vec![]);
+        let plan = StartPlan {
+            user: Some(&same),
+            strict_setgroups: false,
+            runtime_ids: (1000, 1000),
+            ..plan
+        };
+        assert_eq!(caps_of(&requirements(&plan)), ["CAP_SYS_ADMIN"]);
+
+        // Rootless: everything happens in the runtime's user namespace
+        let plan = StartPlan {
+            rootless: true,
+            user: Some(&workload),
+            ..plan
+        };
+        assert!(requirements(&plan).is_empty());
+    }
+
+    #[test]
+    fn test_check_against_capability_set() {
+        let workload = user(1000, 1000, vec![]);
+        let plan = StartPlan {
+            rootless: false,
+            user: Some(&workload),
+            strict_setgroups: true,
+            runtime_ids: (0, 0),
+        };
+        let required = requirements(&plan);
+
+        let all = mask(&["CAP_SYS_ADMIN", "CAP_SETUID", "CAP_SETGID"]);
+        assert!(check(&required, all).is_ok());
+        assert!(check(&required, u64::MAX).is_ok());
+        assert!(check(&[], 0).is_ok());
+
+        let err = check(&required, mask(&["CAP_SYS_ADMIN", "CAP_SETUID"]))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains(
+            "CAP_SETGID (needed for clearing supplementary groups, switching to gid 1000)"
+        ));
+        assert!(!err.contains("CAP_SYS_ADMIN"));
+        assert!(!err.contains("CAP_SETUID"));
+
+        let err = check(&required, 0).unwrap_err().to_string();
+        assert!(err.contains(
+            "CAP_SYS_ADMIN (needed for mounting the overlay root filesystem and volumes)"
+        ));
+        assert!(err.contains("CAP_SETUID (needed for switching to uid 1000)"));
+    }
+}
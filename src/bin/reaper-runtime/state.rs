@@ -1,24 +1,36 @@
-use anyhow::bail;
+use anyhow::{bail, Context};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::io::Write;
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Default cap on ID length; `REAPER_MAX_ID_LENGTH` overrides it.
+const DEFAULT_MAX_ID_LENGTH: usize = 256;
+
+/// Longest accepted ID, from `REAPER_MAX_ID_LENGTH` (a positive integer).
+pub fn max_id_length() -> usize {
+    std::env::var("REAPER_MAX_ID_LENGTH")
+        .ok()
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(DEFAULT_MAX_ID_LENGTH)
+}
 
 /// Validate that an ID is safe for use in filesystem paths.
-/// Rejects empty strings, path traversal (`..`), and characters outside `[a-zA-Z0-9._-]`.
-/// IDs longer than 256 characters are also rejected.
+/// Rejects empty strings, anything containing `..`, and characters outside
+/// `[a-zA-Z0-9._-]`. IDs longer than [`max_id_length`] are also rejected.
 pub fn validate_id(id: &str) -> anyhow::Result<()> {
     if id.is_empty() {
         bail!("ID must not be empty");
     }
-    if id.len() > 256 {
-        bail!("ID must not exceed 256 characters");
+    let max = max_id_length();
+    if id.len() > max {
+        bail!("ID must not exceed {} characters", max);
     }
-    if id == "." || id == ".." {
-        bail!("ID must not be '.' or '..'");
+    if id == "." || id.contains("..") {
+        bail!("ID must not be '.' or contain '..'");
     }
     if !id
         .chars()
@@ -29,6 +41,21 @@ pub fn validate_id(id: &str) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Status strings written to `state.json` and exec state files.
+///
+/// The shim maps these onto containerd's task `Status` enum in
+/// `parse_container_status()`; keep the two in sync.
+pub const STATUS_CREATED: &str = "created";
+/// `start` is setting the workload up (overlay, mounts); no PID yet.
+pub const STATUS_STARTING: &str = "starting";
+pub const STATUS_RUNNING: &str = "running";
+pub const STATUS_STOPPED: &str = "stopped";
+/// Reserved for pause support; the shim already understands them.
+#[allow(dead_code)]
+pub const STATUS_PAUSING: &str = "pausing";
+#[allow(dead_code)]
+pub const STATUS_PAUSED: &str = "paused";
+
 /// OCI User specification for UID/GID switching
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct OciUser {
@@ -39,11 +66,113 @@ pub struct OciUser {
     pub umask: Option<u32>,
 }
 
+/// OCI `process.rlimits` entry, e.g. `{"type": "RLIMIT_NOFILE", "soft": 1024, "hard": 4096}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OciRlimit {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub soft: u64,
+    pub hard: u64,
+}
+
+/// OCI `process.capabilities`, capability names per set. A missing set is
+/// empty, as in runc.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OciCapabilities {
+    #[serde(default)]
+    pub bounding: Vec<String>,
+    #[serde(default)]
+    pub effective: Vec<String>,
+    #[serde(default)]
+    pub permitted: Vec<String>,
+    #[serde(default)]
+    pub inheritable: Vec<String>,
+    #[serde(default)]
+    pub ambient: Vec<String>,
+}
+
+/// Why a stopped container has its exit code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FailureOrigin {
+    /// The workload ran and exited (or was killed); exit_code is its status.
+    Workload,
+    /// Reaper failed to set the container up (overlay, mounts, spawn, ...)
+    /// and the workload never ran to completion.
+    Runtime,
+}
+
+/// Resource usage of the workload process tree, recorded when it stops.
+///
+/// Taken from the workload's rusage (wait4), so it covers the workload and
+/// any descendants it waited for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceUsage {
+    /// Peak resident set size in KiB
+    pub max_rss_kib: u64,
+    /// User CPU time in microseconds
+    pub user_cpu_us: u64,
+    /// System CPU time in microseconds
+    pub system_cpu_us: u64,
+}
+
+/// Isolation settings reaper actually applied when starting the workload,
+/// so tooling can confirm them without reading logs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EffectiveSettings {
+    /// "namespace", "node", or "shared" for a `shared-overlay`; None when
+    /// the overlay was disabled.
+    pub overlay_isolation: Option<String>,
+    /// Sensitive-file filtering in the overlay.
+    pub filter_enabled: bool,
+    /// "host" or "kubernetes".
+    pub dns_mode: String,
+    /// Whether reaper put the workload in its own PID namespace (it
+    /// currently never does; signals go to the process group).
+    pub pid_namespace: bool,
+    /// Volume mounts reaper applied in the overlay namespace, in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub volume_mounts: Vec<AppliedMount>,
+    /// Upper layer of the overlay the workload writes to (shared by every
+    /// container in the same overlay); None when the overlay was disabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overlay_upper: Option<PathBuf>,
+    /// Persisted mount namespace of that overlay, which identifies it when
+    /// counting the containers still using it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overlay_ns: Option<PathBuf>,
+}
+
+/// A volume mount reaper applied for the workload (see `reaper-runtime mounts`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppliedMount {
+    pub source: String,
+    pub destination: String,
+    pub options: Vec<String>,
+    /// Cloned from the host mount namespace rather than bound within the overlay.
+    pub cross_namespace: bool,
+}
+
+impl Default for EffectiveSettings {
+    /// No overlay: nothing filtered, the host's DNS.
+    fn default() -> Self {
+        Self {
+            overlay_isolation: None,
+            filter_enabled: false,
+            dns_mode: "host".into(),
+            pid_namespace: false,
+            volume_mounts: Vec::new(),
+            overlay_upper: None,
+            overlay_ns: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContainerState {
     pub id: String,
     pub bundle: PathBuf,
-    pub status: String, // created | running | stopped
+    pub status: String, // created | starting | running | stopped (see STATUS_* constants)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pid: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -64,6 +193,160 @@ pub struct ContainerState {
     /// None when no annotations are provided (backward compatible).
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub annotations: Option<HashMap<String, String>>,
+    /// Set together with exit_code when the container stops.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub failure_origin: Option<FailureOrigin>,
+    /// Why the runtime failed, when it can say more than the exit code
+    /// (e.g. a script whose shebang interpreter is missing).
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub failure_message: Option<String>,
+    /// Workload resource usage, set when the workload exits.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub resource_usage: Option<ResourceUsage>,
+    /// Image rootfs layered over the host root in a private overlay
+    /// (`REAPER_IMAGE_ROOTFS` / `image-rootfs` annotation, or a read-only
+    /// rootfs mount). None when the container shares the host-root overlay.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub image_rootfs: Option<PathBuf>,
+    /// Sandbox (pause) container, recorded at create so later operations
+    /// don't need the bundle's config.json.
+    #[serde(default)]
+    pub sandbox: bool,
+    /// Cgroup v2 directory holding only this container's processes. When
+    /// set, the shim's wait() blocks on its `cgroup.events` instead of
+    /// polling state.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub cgroup_path: Option<PathBuf>,
+    /// Kubernetes container name and pod sandbox ID from the CRI
+    /// annotations, so `after` dependencies can name containers.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub container_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub sandbox_id: Option<String>,
+    /// Settings applied at start; None until the workload is spawned.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub effective: Option<EffectiveSettings>,
+    /// Whether the workload was started inside the overlay namespace. False
+    /// until then, and for workloads that ran on the host filesystem
+    /// (`REAPER_NO_OVERLAY`).
+    #[serde(default)]
+    pub isolated: bool,
+    /// Time spent in each phase of `create` and `start`, in order, up to
+    /// the workload being spawned.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub timings: Vec<PhaseTiming>,
+    /// The parts of config.json seen at create, compared at start to catch
+    /// a bundle rewritten in between. None for sandboxes and legacy state.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub created_config: Option<ConfigSnapshot>,
+    /// Unix time (seconds) the workload was spawned and stopped.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub started_at: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub finished_at: Option<u64>,
+    /// Signal that killed the workload, if it didn't exit on its own.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub exit_signal: Option<i32>,
+    /// The OOM killer ended the workload; the shim reports it to containerd
+    /// (Kubernetes' `OOMKilled`).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub oom: bool,
+}
+
+/// The fields of a bundle's config.json that differ in meaning between
+/// create and start (see `reconcile_config` in main.rs).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigSnapshot {
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub cwd: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub root: Option<String>,
+    /// Mount destinations, in config order
+    #[serde(default)]
+    pub mounts: Vec<String>,
+    /// `reaper.runtime/*` and CRI container-type annotations
+    #[serde(default)]
+    pub annotations: std::collections::BTreeMap<String, String>,
+}
+
+/// How long one phase of bringing a container up took.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhaseTiming {
+    pub phase: String,
+    pub ms: u64,
+}
+
+/// Splits elapsed time into consecutive phases: each `mark` closes the
+/// phase running since the previous mark (or since the timer was created).
+#[derive(Debug, Clone)]
+pub struct PhaseTimer {
+    last: std::time::Instant,
+    phases: Vec<PhaseTiming>,
+}
+
+impl PhaseTimer {
+    pub fn new() -> Self {
+        Self {
+            last: std::time::Instant::now(),
+            phases: Vec::new(),
+        }
+    }
+
+    /// Record the time since the previous mark as `phase`.
+    pub fn mark(&mut self, phase: &str) {
+        let now = std::time::Instant::now();
+        self.phases.push(PhaseTiming {
+            phase: phase.to_string(),
+            ms: now.duration_since(self.last).as_millis() as u64,
+        });
+        self.last = now;
+    }
+
+    pub fn phases(&self) -> &[PhaseTiming] {
+        &self.phases
+    }
+}
+
+impl Default for PhaseTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Compact exit summary written to `result.json` when a container stops, so
+/// job controllers can read the outcome without parsing the full state. Kept
+/// until the container is deleted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExitResult {
+    pub exit_code: Option<i32>,
+    pub exit_signal: Option<i32>,
+    pub started_at: Option<u64>,
+    pub finished_at: Option<u64>,
+    pub failure_origin: Option<FailureOrigin>,
+    pub message: Option<String>,
+}
+
+impl ExitResult {
+    pub fn from_state(state: &ContainerState) -> Self {
+        Self {
+            exit_code: state.exit_code,
+            exit_signal: state.exit_signal,
+            started_at: state.started_at,
+            finished_at: state.finished_at,
+            failure_origin: state.failure_origin,
+            message: state.failure_message.clone(),
+        }
+    }
+}
+
+/// Seconds since the Unix epoch.
+pub fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
 }
 
 impl ContainerState {
@@ -71,7 +354,7 @@ impl ContainerState {
         Self {
             id,
             bundle,
-            status: "created".into(),
+            status: STATUS_CREATED.into(),
             pid: None,
             exit_code: None,
             terminal: false,
@@ -80,14 +363,64 @@ impl ContainerState {
             stderr: None,
             namespace: None,
             annotations: None,
+            failure_origin: None,
+            failure_message: None,
+            resource_usage: None,
+            image_rootfs: None,
+            sandbox: false,
+            cgroup_path: None,
+            container_name: None,
+            sandbox_id: None,
+            effective: None,
+            isolated: false,
+            timings: Vec::new(),
+            created_config: None,
+            started_at: None,
+            finished_at: None,
+            exit_signal: None,
+            oom: false,
         }
     }
+
+    /// Mark the container stopped with `exit_code`, recording who produced it.
+    pub fn mark_stopped(&mut self, exit_code: i32, origin: FailureOrigin) {
+        self.status = STATUS_STOPPED.into();
+        self.exit_code = Some(exit_code);
+        self.failure_origin = Some(origin);
+        self.finished_at = Some(unix_now());
+    }
+}
+
+/// `REAPER_ROOTLESS=1`: run without root inside a user namespace.
+pub fn rootless_enabled() -> bool {
+    std::env::var("REAPER_ROOTLESS")
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+/// Default base for state and overlay paths: `/run/reaper`, or in rootless
+/// mode `$XDG_RUNTIME_DIR/reaper` (`/run/user/<uid>/reaper` if unset).
+pub fn default_root() -> PathBuf {
+    if !rootless_enabled() {
+        return PathBuf::from("/run/reaper");
+    }
+    rootless_root(
+        std::env::var("XDG_RUNTIME_DIR").ok(),
+        nix::unistd::getuid().as_raw(),
+    )
+}
+
+fn rootless_root(xdg_runtime_dir: Option<String>, uid: u32) -> PathBuf {
+    xdg_runtime_dir
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(format!("/run/user/{}", uid)))
+        .join("reaper")
 }
 
 pub fn state_dir() -> PathBuf {
     std::env::var("REAPER_RUNTIME_ROOT")
         .map(PathBuf::from)
-        .unwrap_or_else(|_| PathBuf::from("/run/reaper"))
+        .unwrap_or_else(|_| default_root())
 }
 
 pub fn container_dir(id: &str) -> PathBuf {
@@ -102,6 +435,10 @@ pub fn pid_path(id: &str) -> PathBuf {
     container_dir(id).join("pid")
 }
 
+pub fn result_path(id: &str) -> PathBuf {
+    container_dir(id).join("result.json")
+}
+
 /// Path for PTY resize signaling (shim writes width/height, runtime reads)
 #[cfg(target_os = "linux")]
 pub fn resize_path(id: &str) -> PathBuf {
@@ -114,17 +451,85 @@ pub fn exec_resize_path(container_id: &str, exec_id: &str) -> PathBuf {
     container_dir(container_id).join(format!("exec-{}-resize", exec_id))
 }
 
-pub fn save_state(state: &ContainerState) -> anyhow::Result<()> {
-    validate_id(&state.id)?;
-    let dir = container_dir(&state.id);
+const DEFAULT_STATE_DIR_MODE: u32 = 0o700;
+const DEFAULT_STATE_FILE_MODE: u32 = 0o600;
+
+fn parse_mode(value: &str) -> anyhow::Result<u32> {
+    match super::config::parse_octal_mode(value) {
+        Some(mode) => Ok(mode),
+        None => bail!("invalid octal mode {:?}", value),
+    }
+}
+
+/// Mode from `var`, or `None` when unset.
+fn mode_from_env(var: &str) -> anyhow::Result<Option<u32>> {
+    match std::env::var(var) {
+        Ok(v) => parse_mode(&v)
+            .map(Some)
+            .map_err(|e| anyhow::anyhow!("{}: {}", var, e)),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Create the container's state directory with `REAPER_STATE_DIR_MODE`
+/// (default 0700). A state root created here gets the same mode when the
+/// variable is set; otherwise it keeps the umask default.
+fn create_container_dir(id: &str) -> anyhow::Result<PathBuf> {
+    let dir_mode = mode_from_env("REAPER_STATE_DIR_MODE")?;
+    let root = state_dir();
+    let new_root = !root.exists();
+    let dir = container_dir(id);
     fs::create_dir_all(&dir)?;
     #[cfg(unix)]
-    fs::set_permissions(&dir, fs::Permissions::from_mode(0o700))?;
-    let path = state_path(&state.id);
+    {
+        if let (true, Some(mode)) = (new_root, dir_mode) {
+            fs::set_permissions(&root, fs::Permissions::from_mode(mode))?;
+        }
+        let mode = dir_mode.unwrap_or(DEFAULT_STATE_DIR_MODE);
+        fs::set_permissions(&dir, fs::Permissions::from_mode(mode))?;
+    }
+    Ok(dir)
+}
+
+/// Write a state file and apply `REAPER_STATE_FILE_MODE` (default 0600).
+///
+/// Written to a temporary file and renamed into place, so a concurrent
+/// reader (e.g. the shim's `state`/`connect` right after start) never sees
+/// a truncated file.
+fn write_state_file(path: &Path, contents: &[u8]) -> anyhow::Result<()> {
+    let file_mode = mode_from_env("REAPER_STATE_FILE_MODE")?;
+    let name = path
+        .file_name()
+        .with_context(|| format!("state file path {} has no file name", path.display()))?;
+    let tmp = path.with_file_name(format!(
+        ".{}.{}.tmp",
+        name.to_string_lossy(),
+        std::process::id()
+    ));
+    let result = (|| {
+        fs::write(&tmp, contents)?;
+        #[cfg(unix)]
+        fs::set_permissions(
+            &tmp,
+            fs::Permissions::from_mode(file_mode.unwrap_or(DEFAULT_STATE_FILE_MODE)),
+        )?;
+        fs::rename(&tmp, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    Ok(result?)
+}
+
+pub fn save_state(state: &ContainerState) -> anyhow::Result<()> {
+    validate_id(&state.id)?;
+    create_container_dir(&state.id)?;
     let json = serde_json::to_vec_pretty(&state)?;
-    fs::write(&path, json)?;
-    #[cfg(unix)]
-    fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+    write_state_file(&state_path(&state.id), &json)?;
+    if state.status == STATUS_STOPPED && !state.sandbox {
+        let result = serde_json::to_vec_pretty(&ExitResult::from_state(state))?;
+        write_state_file(&result_path(&state.id), &result)?;
+    }
     Ok(())
 }
 
@@ -135,18 +540,63 @@ pub fn load_state(id: &str) -> anyhow::Result<ContainerState> {
     Ok(state)
 }
 
+/// Container states under the state root, sorted by ID, plus the entries
+/// that have a state file which can't be read or parsed.
+#[derive(Debug, Default)]
+pub struct StateListing {
+    pub states: Vec<ContainerState>,
+    /// Entry name and why it was skipped
+    pub skipped: Vec<(String, anyhow::Error)>,
+}
+
+/// Every container state under the state root. Entries without a state
+/// file (e.g. the overlay and namespace directories sharing /run/reaper)
+/// are not containers and are left out silently; unreadable or corrupt
+/// ones are reported in `skipped`.
+pub fn list_states() -> StateListing {
+    let mut listing = StateListing::default();
+    let Ok(entries) = fs::read_dir(state_dir()) else {
+        return listing;
+    };
+    for entry in entries.flatten() {
+        if !entry.path().join("state.json").is_file() {
+            continue;
+        }
+        let id = entry.file_name().to_string_lossy().into_owned();
+        match load_state(&id) {
+            Ok(state) => listing.states.push(state),
+            Err(e) => listing.skipped.push((id, e)),
+        }
+    }
+    listing.states.sort_by(|a, b| a.id.cmp(&b.id));
+    listing.skipped.sort_by(|a, b| a.0.cmp(&b.0));
+    listing
+}
+
+/// Containers that are, or may be, using the overlay namespace at
+/// `ns_path`: running ones that recorded it, plus any still starting
+/// (they record their overlay only once running).
+pub fn overlay_users(ns_path: &Path) -> usize {
+    count_overlay_users(&list_states().states, ns_path)
+}
+
+fn count_overlay_users(states: &[ContainerState], ns_path: &Path) -> usize {
+    states
+        .iter()
+        .filter(|s| match s.status.as_str() {
+            STATUS_STARTING => true,
+            STATUS_RUNNING => {
+                s.effective.as_ref().and_then(|e| e.overlay_ns.as_deref()) == Some(ns_path)
+            }
+            _ => false,
+        })
+        .count()
+}
+
 pub fn save_pid(id: &str, pid: i32) -> anyhow::Result<()> {
     validate_id(id)?;
-    let dir = container_dir(id);
-    fs::create_dir_all(&dir)?;
-    #[cfg(unix)]
-    fs::set_permissions(&dir, fs::Permissions::from_mode(0o700))?;
-    let path = pid_path(id);
-    let mut f = fs::File::create(&path)?;
-    writeln!(f, "{}", pid)?;
-    #[cfg(unix)]
-    fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
-    Ok(())
+    create_container_dir(id)?;
+    write_state_file(&pid_path(id), format!("{}\n", pid).as_bytes())
 }
 
 pub fn load_pid(id: &str) -> anyhow::Result<i32> {
@@ -165,12 +615,27 @@ pub fn delete(id: &str) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Output an exec process produced, counted by the runtime's relay
+/// (`REAPER_EXEC_OUTPUT_STATS`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecOutput {
+    /// Bytes written to stdout (to the terminal for a TTY exec)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stdout_bytes: Option<u64>,
+    /// Bytes written to stderr; absent for a TTY exec, which has none
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stderr_bytes: Option<u64>,
+    /// A stream exceeded `REAPER_EXEC_OUTPUT_CAP_BYTES` and the rest of it
+    /// was dropped
+    pub truncated: bool,
+}
+
 /// State for an exec process within a container
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecState {
     pub container_id: String,
     pub exec_id: String,
-    pub status: String, // created | running | stopped
+    pub status: String, // created | running | stopped (see STATUS_* constants)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pid: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -189,6 +654,17 @@ pub struct ExecState {
     pub stderr: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user: Option<OciUser>,
+    /// `rlimits` of the exec's process spec
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rlimits: Vec<OciRlimit>,
+    /// `capabilities` of the exec's process spec; None leaves them as is
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capabilities: Option<OciCapabilities>,
+    /// `noNewPrivileges` of the exec's process spec
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub no_new_privileges: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<ExecOutput>,
 }
 
 pub fn exec_state_path(container_id: &str, exec_id: &str) -> PathBuf {
@@ -198,16 +674,9 @@ pub fn exec_state_path(container_id: &str, exec_id: &str) -> PathBuf {
 pub fn save_exec_state(state: &ExecState) -> anyhow::Result<()> {
     validate_id(&state.container_id)?;
     validate_id(&state.exec_id)?;
-    let dir = container_dir(&state.container_id);
-    fs::create_dir_all(&dir)?;
-    #[cfg(unix)]
-    fs::set_permissions(&dir, fs::Permissions::from_mode(0o700))?;
-    let path = exec_state_path(&state.container_id, &state.exec_id);
+    create_container_dir(&state.container_id)?;
     let json = serde_json::to_vec_pretty(&state)?;
-    fs::write(&path, json)?;
-    #[cfg(unix)]
-    fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
-    Ok(())
+    write_state_file(&exec_state_path(&state.container_id, &state.exec_id), &json)
 }
 
 pub fn load_exec_state(container_id: &str, exec_id: &str) -> anyhow::Result<ExecState> {
@@ -219,6 +688,97 @@ pub fn load_exec_state(container_id: &str, exec_id: &str) -> anyhow::Result<Exec
     Ok(state)
 }
 
+/// Every readable exec state under the state root.
+pub fn list_exec_states() -> Vec<ExecState> {
+    let Ok(containers) = fs::read_dir(state_dir()) else {
+        return Vec::new();
+    };
+    containers
+        .flatten()
+        .filter_map(|c| fs::read_dir(c.path()).ok())
+        .flat_map(|files| files.flatten())
+        .filter(|f| {
+            let name = f.file_name();
+            let name = name.to_string_lossy();
+            name.starts_with("exec-") && name.ends_with(".json")
+        })
+        .filter_map(|f| fs::read(f.path()).ok())
+        .filter_map(|data| serde_json::from_slice(&data).ok())
+        .collect()
+}
+
+/// Whether an exec state is left over from an exec that will never report
+/// again: it stopped, its process is gone, or it was never started.
+fn exec_state_is_orphaned(state: &ExecState, is_alive: impl Fn(i32) -> bool) -> bool {
+    if state.status == STATUS_STOPPED {
+        return true;
+    }
+    match state.pid {
+        Some(pid) => !is_alive(pid),
+        None => true,
+    }
+}
+
+pub fn pid_is_alive(pid: i32) -> bool {
+    // EPERM means the process exists but belongs to someone else
+    !matches!(
+        nix::sys::signal::kill(nix::unistd::Pid::from_raw(pid), None),
+        Err(nix::errno::Errno::ESRCH)
+    )
+}
+
+/// Find (and unless `dry_run`, remove) orphaned exec state files under the
+/// state root, e.g. left behind when the shim crashed before deleting them.
+///
+/// Only files untouched for at least `max_age` are considered, so execs that
+/// are being set up right now are never pruned. Unreadable files that old are
+/// pruned too. Returns the affected paths.
+pub fn gc_exec_states(max_age: std::time::Duration, dry_run: bool) -> anyhow::Result<Vec<PathBuf>> {
+    let root = state_dir();
+    let mut pruned = Vec::new();
+    let Ok(containers) = fs::read_dir(&root) else {
+        return Ok(pruned);
+    };
+    for container in containers.flatten() {
+        let Ok(entries) = fs::read_dir(container.path()) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if !(name.starts_with("exec-") && name.ends_with(".json")) {
+                continue;
+            }
+            let age = entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.elapsed().ok())
+                .unwrap_or_default();
+            if age < max_age {
+                continue;
+            }
+            let orphaned = fs::read(&path)
+                .ok()
+                .and_then(|data| serde_json::from_slice::<ExecState>(&data).ok())
+                .is_none_or(|state| exec_state_is_orphaned(&state, pid_is_alive));
+            if !orphaned {
+                continue;
+            }
+            if !dry_run {
+                fs::remove_file(&path)?;
+                let resize =
+                    path.with_file_name(format!("{}-resize", name.trim_end_matches(".json")));
+                let _ = fs::remove_file(resize);
+            }
+            pruned.push(path);
+        }
+    }
+    pruned.sort();
+    Ok(pruned)
+}
+
 // pub fn delete_exec_state(container_id: &str, exec_id: &str) -> anyhow::Result<()> {
 //     let path = exec_state_path(container_id, exec_id);
 //     if path.exists() {
@@ -265,6 +825,18 @@ mod tests {
         assert_eq!(dir, PathBuf::from("/run/reaper"));
     }
 
+    #[test]
+    fn test_rootless_root() {
+        assert_eq!(
+            rootless_root(Some("/run/user/1000".into()), 1000),
+            PathBuf::from("/run/user/1000/reaper")
+        );
+        assert_eq!(
+            rootless_root(None, 1234),
+            PathBuf::from("/run/user/1234/reaper")
+        );
+    }
+
     #[test]
     #[serial]
     fn test_container_dir() {
@@ -294,6 +866,31 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_phase_timer_records_phases_in_order() {
+        // A simulated start: overlay setup is the slow phase
+        let mut timer = PhaseTimer::new();
+        timer.mark("prepare");
+        std::thread::sleep(std::time::Duration::from_millis(30));
+        timer.mark("overlay");
+        timer.mark("spawn");
+
+        let phases: Vec<&str> = timer.phases().iter().map(|t| t.phase.as_str()).collect();
+        assert_eq!(phases, ["prepare", "overlay", "spawn"]);
+        assert!(timer.phases()[1].ms >= 30);
+        assert!(timer.phases()[2].ms < 30);
+
+        // Serialized in order, and omitted from state while empty
+        let mut state = ContainerState::new("timed".into(), PathBuf::from("/b"));
+        assert!(serde_json::to_value(&state)
+            .unwrap()
+            .get("timings")
+            .is_none());
+        state.timings = timer.phases().to_vec();
+        let json = serde_json::to_value(&state).unwrap();
+        assert_eq!(json["timings"][1]["phase"], "overlay");
+    }
+
     #[test]
     #[serial]
     fn test_save_and_load_state() {
@@ -310,6 +907,22 @@ mod tests {
                 stderr: None,
                 namespace: None,
                 annotations: None,
+                failure_origin: None,
+                failure_message: None,
+                resource_usage: None,
+                image_rootfs: None,
+                sandbox: false,
+                cgroup_path: None,
+                container_name: None,
+                sandbox_id: None,
+                effective: None,
+                isolated: true,
+                timings: Vec::new(),
+                created_config: None,
+                started_at: None,
+                finished_at: None,
+                exit_signal: None,
+                oom: false,
             };
 
             // Save state
@@ -321,6 +934,115 @@ mod tests {
             assert_eq!(loaded.bundle, state.bundle);
             assert_eq!(loaded.status, state.status);
             assert_eq!(loaded.pid, state.pid);
+            assert!(loaded.isolated);
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn test_effective_settings_round_trip() {
+        with_test_root(|_| {
+            let mut state = ContainerState::new("eff".into(), PathBuf::from("/b"));
+            save_state(&state).unwrap();
+            let raw = fs::read_to_string(state_path("eff")).unwrap();
+            assert!(!raw.contains("effective"), "unset settings are omitted");
+
+            let effective = EffectiveSettings {
+                overlay_isolation: Some("namespace".into()),
+                filter_enabled: true,
+                dns_mode: "kubernetes".into(),
+                pid_namespace: false,
+                volume_mounts: Vec::new(),
+                overlay_upper: Some(PathBuf::from("/run/reaper/overlay/default/upper")),
+                overlay_ns: Some(PathBuf::from("/run/reaper/ns/default")),
+            };
+            state.effective = Some(effective.clone());
+            save_state(&state).unwrap();
+            let raw = fs::read_to_string(state_path("eff")).unwrap();
+            assert!(!raw.contains("volume_mounts"), "no mounts are omitted");
+            assert_eq!(load_state("eff").unwrap().effective, Some(effective));
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn test_list_states_reports_corrupt_entries() {
+        with_test_root(|root| {
+            for id in ["list-b", "list-a"] {
+                save_state(&ContainerState::new(id.into(), PathBuf::from("/b"))).unwrap();
+            }
+            // Not a container: no state file
+            fs::create_dir_all(Path::new(&root).join("overlay")).unwrap();
+            fs::create_dir_all(Path::new(&root).join("broken")).unwrap();
+            fs::write(Path::new(&root).join("broken/state.json"), "{not json").unwrap();
+
+            let listing = list_states();
+            let ids: Vec<&str> = listing.states.iter().map(|s| s.id.as_str()).collect();
+            assert_eq!(ids, ["list-a", "list-b"]);
+            let skipped: Vec<&str> = listing.skipped.iter().map(|(id, _)| id.as_str()).collect();
+            assert_eq!(skipped, ["broken"]);
+        });
+    }
+
+    #[test]
+    fn test_count_overlay_users_per_namespace() {
+        let container = |id: &str, status: &str, ns: Option<&str>| {
+            let mut state = ContainerState::new(id.into(), PathBuf::from("/b"));
+            state.status = status.into();
+            state.effective = ns.map(|ns| EffectiveSettings {
+                overlay_ns: Some(PathBuf::from(ns)),
+                ..Default::default()
+            });
+            state
+        };
+        let default_ns = Path::new("/run/reaper/ns/default");
+        let mut states = vec![
+            container("a", STATUS_RUNNING, Some("/run/reaper/ns/default")),
+            container("b", STATUS_RUNNING, Some("/run/reaper/ns/other")),
+            container("c", STATUS_STOPPED, Some("/run/reaper/ns/default")),
+            container("d", STATUS_CREATED, None),
+        ];
+        assert_eq!(count_overlay_users(&states, default_ns), 1);
+        assert_eq!(
+            count_overlay_users(&states, Path::new("/run/reaper/ns/other")),
+            1
+        );
+
+        // The last running container exits
+        states[0].status = STATUS_STOPPED.into();
+        assert_eq!(count_overlay_users(&states, default_ns), 0);
+
+        // One starting may be about to join any overlay
+        states[3].status = STATUS_STARTING.into();
+        assert_eq!(count_overlay_users(&states, default_ns), 1);
+    }
+
+    #[test]
+    #[serial]
+    fn test_effective_settings_record_volume_mounts() {
+        with_test_root(|_| {
+            let mut state = ContainerState::new("vols".into(), PathBuf::from("/b"));
+            let mounts = vec![
+                AppliedMount {
+                    source: "/var/lib/kubelet/pods/p/volumes/cfg".into(),
+                    destination: "/etc/app".into(),
+                    options: vec!["rbind".into(), "ro".into()],
+                    cross_namespace: true,
+                },
+                AppliedMount {
+                    source: "/data".into(),
+                    destination: "/mnt/data".into(),
+                    options: Vec::new(),
+                    cross_namespace: false,
+                },
+            ];
+            state.effective = Some(EffectiveSettings {
+                volume_mounts: mounts.clone(),
+                ..EffectiveSettings::default()
+            });
+            save_state(&state).unwrap();
+            let loaded = load_state("vols").unwrap().effective.unwrap();
+            assert_eq!(loaded.volume_mounts, mounts);
         });
     }
 
@@ -409,6 +1131,29 @@ mod tests {
         });
     }
 
+    #[test]
+    #[serial]
+    fn test_sandbox_field_round_trip() {
+        with_test_root(|_| {
+            let mut state = ContainerState::new("sb-test".to_string(), PathBuf::from("/bundle"));
+            state.sandbox = true;
+            save_state(&state).expect("Failed to save state");
+
+            let raw: serde_json::Value =
+                serde_json::from_slice(&fs::read(state_path("sb-test")).unwrap()).unwrap();
+            assert_eq!(raw["sandbox"], true);
+            assert!(load_state("sb-test").unwrap().sandbox);
+
+            // Legacy state files without the field are workloads
+            fs::write(
+                state_path("sb-test"),
+                r#"{"id": "sb-test", "bundle": "/bundle", "status": "created"}"#,
+            )
+            .unwrap();
+            assert!(!load_state("sb-test").unwrap().sandbox);
+        });
+    }
+
     #[test]
     #[serial]
     fn test_annotations_field_round_trip() {
@@ -486,6 +1231,18 @@ mod tests {
                 stdout: Some("/path/to/stdout".to_string()),
                 stderr: Some("/path/to/stderr".to_string()),
                 user: None,
+                rlimits: vec![OciRlimit {
+                    kind: "RLIMIT_NOFILE".to_string(),
+                    soft: 1024,
+                    hard: 4096,
+                }],
+                capabilities: None,
+                no_new_privileges: true,
+                output: Some(ExecOutput {
+                    stdout_bytes: Some(42),
+                    stderr_bytes: None,
+                    truncated: false,
+                }),
             };
 
             // Save exec state
@@ -500,6 +1257,109 @@ mod tests {
             assert_eq!(loaded.pid, exec_state.pid);
             assert_eq!(loaded.args, exec_state.args);
             assert_eq!(loaded.terminal, exec_state.terminal);
+            assert_eq!(loaded.rlimits, exec_state.rlimits);
+            assert_eq!(loaded.output, exec_state.output);
+        });
+    }
+
+    fn exec_state(exec_id: &str, status: &str, pid: Option<i32>) -> ExecState {
+        ExecState {
+            container_id: "gc-container".to_string(),
+            exec_id: exec_id.to_string(),
+            status: status.to_string(),
+            pid,
+            exit_code: None,
+            args: vec!["/bin/sh".to_string()],
+            env: None,
+            cwd: None,
+            terminal: false,
+            stdin: None,
+            stdout: None,
+            stderr: None,
+            user: None,
+            rlimits: Vec::new(),
+            capabilities: None,
+            no_new_privileges: false,
+            output: None,
+        }
+    }
+
+    fn dead_pid() -> i32 {
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let pid = child.id() as i32;
+        child.wait().unwrap();
+        pid
+    }
+
+    #[test]
+    fn test_exec_state_is_orphaned() {
+        let alive = |_| true;
+        let dead = |_| false;
+        assert!(!exec_state_is_orphaned(
+            &exec_state("e", "running", Some(1)),
+            alive
+        ));
+        assert!(exec_state_is_orphaned(
+            &exec_state("e", "running", Some(1)),
+            dead
+        ));
+        assert!(exec_state_is_orphaned(
+            &exec_state("e", "stopped", Some(1)),
+            alive
+        ));
+        assert!(exec_state_is_orphaned(
+            &exec_state("e", "created", None),
+            alive
+        ));
+    }
+
+    #[test]
+    #[serial]
+    fn test_gc_exec_states_prunes_dead_and_stopped() {
+        with_test_root(|root| {
+            let own_pid = std::process::id() as i32;
+            save_exec_state(&exec_state("live", "running", Some(own_pid))).unwrap();
+            save_exec_state(&exec_state("dead", "running", Some(dead_pid()))).unwrap();
+            save_exec_state(&exec_state("done", "stopped", Some(own_pid))).unwrap();
+            let dir = container_dir("gc-container");
+            fs::write(dir.join("exec-dead-resize"), b"").unwrap();
+            fs::write(dir.join("exec-garbage.json"), b"not json").unwrap();
+            save_state(&ContainerState::new(
+                "gc-container".into(),
+                PathBuf::from("/bundle"),
+            ))
+            .unwrap();
+
+            // Dry run lists without removing
+            let listed = gc_exec_states(std::time::Duration::ZERO, true).unwrap();
+            assert_eq!(listed.len(), 3);
+            assert!(exec_state_path("gc-container", "dead").exists());
+
+            let pruned = gc_exec_states(std::time::Duration::ZERO, false).unwrap();
+            assert_eq!(pruned, listed);
+            let root = PathBuf::from(root).join("gc-container");
+            assert_eq!(
+                pruned,
+                vec![
+                    root.join("exec-dead.json"),
+                    root.join("exec-done.json"),
+                    root.join("exec-garbage.json"),
+                ]
+            );
+            assert!(exec_state_path("gc-container", "live").exists());
+            assert!(!dir.join("exec-dead-resize").exists());
+            assert!(state_path("gc-container").exists());
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn test_gc_exec_states_skips_recent() {
+        with_test_root(|_| {
+            save_exec_state(&exec_state("fresh", "stopped", None)).unwrap();
+            let pruned = gc_exec_states(std::time::Duration::from_secs(3600), false).unwrap();
+            assert!(pruned.is_empty());
+            assert!(exec_state_path("gc-container", "fresh").exists());
         });
     }
 
@@ -552,6 +1412,9 @@ mod tests {
         assert!(validate_id("abc123").is_ok());
         assert!(validate_id("a.b_c-d").is_ok());
         assert!(validate_id("A").is_ok());
+        // containerd task ids are 64 hex chars; exec ids are often UUIDs
+        assert!(validate_id(&"0123456789abcdef".repeat(4)).is_ok());
+        assert!(validate_id("3f2b8c1e-9d4a-4b6f-8e2a-1c5d7f9b0a3e").is_ok());
     }
 
     #[test]
@@ -564,6 +1427,7 @@ mod tests {
         assert!(validate_id("..").is_err());
         assert!(validate_id("../etc/passwd").is_err());
         assert!(validate_id("foo/../bar").is_err());
+        assert!(validate_id("foo..bar").is_err());
     }
 
     #[test]
@@ -578,17 +1442,96 @@ mod tests {
     }
 
     #[test]
+    #[serial]
     fn test_validate_id_rejects_long() {
+        std::env::remove_var("REAPER_MAX_ID_LENGTH");
         let long_id = "a".repeat(257);
         assert!(validate_id(&long_id).is_err());
         let ok_id = "a".repeat(256);
         assert!(validate_id(&ok_id).is_ok());
     }
 
+    #[test]
+    #[serial]
+    fn test_validate_id_configurable_length() {
+        std::env::set_var("REAPER_MAX_ID_LENGTH", "8");
+        assert!(validate_id("abcdefgh").is_ok());
+        assert!(validate_id("abcdefghi").is_err());
+        std::env::set_var("REAPER_MAX_ID_LENGTH", "0");
+        assert_eq!(max_id_length(), 256);
+        std::env::set_var("REAPER_MAX_ID_LENGTH", "many");
+        assert_eq!(max_id_length(), 256);
+        std::env::remove_var("REAPER_MAX_ID_LENGTH");
+    }
+
     #[test]
     fn test_validate_id_rejects_special_chars() {
         assert!(validate_id("foo bar").is_err());
         assert!(validate_id("foo\nbar").is_err());
         assert!(validate_id("foo\0bar").is_err());
     }
+
+    #[test]
+    fn test_parse_mode() {
+        assert_eq!(parse_mode("0750").unwrap(), 0o750);
+        assert!(parse_mode("0999").is_err());
+    }
+
+    #[cfg(unix)]
+    fn mode_of(path: &Path) -> u32 {
+        fs::metadata(path).unwrap().permissions().mode() & 0o7777
+    }
+
+    #[test]
+    #[serial]
+    #[cfg(unix)]
+    fn test_state_modes_default() {
+        with_test_root(|_| {
+            save_state(&ContainerState::new("c1".into(), PathBuf::from("/b"))).unwrap();
+            save_pid("c1", 42).unwrap();
+            assert_eq!(mode_of(&container_dir("c1")), 0o700);
+            assert_eq!(mode_of(&state_path("c1")), 0o600);
+            assert_eq!(mode_of(&pid_path("c1")), 0o600);
+        });
+    }
+
+    #[test]
+    #[serial]
+    #[cfg(unix)]
+    fn test_state_modes_configured() {
+        with_test_root(|root| {
+            let state_root = Path::new(&root).join("state");
+            std::env::set_var("REAPER_RUNTIME_ROOT", &state_root);
+            std::env::set_var("REAPER_STATE_DIR_MODE", "0750");
+            std::env::set_var("REAPER_STATE_FILE_MODE", "640");
+
+            save_state(&ContainerState::new("c1".into(), PathBuf::from("/b"))).unwrap();
+            save_pid("c1", 42).unwrap();
+            save_exec_state(&exec_state("e1", "created", None)).unwrap();
+
+            std::env::remove_var("REAPER_STATE_DIR_MODE");
+            std::env::remove_var("REAPER_STATE_FILE_MODE");
+            assert_eq!(mode_of(&state_root), 0o750);
+            assert_eq!(mode_of(&container_dir("c1")), 0o750);
+            assert_eq!(mode_of(&state_path("c1")), 0o640);
+            assert_eq!(mode_of(&pid_path("c1")), 0o640);
+            assert_eq!(mode_of(&container_dir("gc-container")), 0o750);
+            assert_eq!(mode_of(&exec_state_path("gc-container", "e1")), 0o640);
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn test_state_mode_invalid_rejected() {
+        with_test_root(|_| {
+            std::env::set_var("REAPER_STATE_FILE_MODE", "0999");
+            let err = save_pid("c1", 42).unwrap_err();
+            std::env::remove_var("REAPER_STATE_FILE_MODE");
+            assert!(
+                err.to_string().contains("REAPER_STATE_FILE_MODE"),
+                "{}",
+                err
+            );
+        });
+    }
 }
@@ -0,0 +1,223 @@
+//! Optional OTLP span export (`otel` feature).
+//!
+//! With `REAPER_OTLP_ENDPOINT` set to a collector's base URL (e.g.
+//! `http://otel-collector:4318`), the runtime's operation spans (create,
+//! start, overlay setup, volume mounts, kill, delete) are exported over
+//! OTLP/HTTP to `<endpoint>/v1/traces`. Unset, no exporter is built.
+//!
+//! Spans are exported synchronously as they close. There are no background
+//! threads, so the forked monitoring daemon exports its own spans, and the
+//! exporter never writes to stdout/stderr (which would corrupt the shim's
+//! view of the runtime's output).
+
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::{WithExportConfig, WithHttpConfig};
+use opentelemetry_sdk::export::trace::SpanExporter;
+use opentelemetry_sdk::trace::{Tracer, TracerProvider};
+use opentelemetry_sdk::Resource;
+use std::io::Read;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing_opentelemetry::OpenTelemetryLayer;
+
+/// Upper bound on a single export, so an unreachable collector can only
+/// delay an operation briefly.
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(2);
+
+static PROVIDER: OnceLock<TracerProvider> = OnceLock::new();
+
+/// `<endpoint>/v1/traces` from `REAPER_OTLP_ENDPOINT`, or `None` when unset.
+fn traces_endpoint() -> Option<String> {
+    let endpoint = std::env::var("REAPER_OTLP_ENDPOINT").ok()?;
+    let endpoint = endpoint.trim().trim_end_matches('/');
+    if endpoint.is_empty() {
+        return None;
+    }
+    Some(format!("{}/v1/traces", endpoint))
+}
+
+/// Blocking HTTP client for the OTLP exporter. ureq needs no runtime or
+/// helper threads, so it keeps working in the forked daemon.
+#[derive(Debug)]
+struct UreqClient(ureq::Agent);
+
+#[async_trait::async_trait]
+impl opentelemetry_http::HttpClient for UreqClient {
+    async fn send(
+        &self,
+        request: http::Request<Vec<u8>>,
+    ) -> Result<http::Response<bytes::Bytes>, opentelemetry_http::HttpError> {
+        let (parts, body) = request.into_parts();
+        let mut req = self
+            .0
+            .request(parts.method.as_str(), &parts.uri.to_string());
+        for (name, value) in &parts.headers {
+            if let Ok(value) = value.to_str() {
+                req = req.set(name.as_str(), value);
+            }
+        }
+        let resp = match req.send_bytes(&body) {
+            Ok(resp) | Err(ureq::Error::Status(_, resp)) => resp,
+            Err(e) => return Err(Box::new(e)),
+        };
+        let status = resp.status();
+        let mut buf = Vec::new();
+        resp.into_reader().take(1 << 20).read_to_end(&mut buf)?;
+        Ok(http::Response::builder()
+            .status(status)
+            .body(bytes::Bytes::from(buf))?)
+    }
+}
+
+fn provider_with_exporter(exporter: impl SpanExporter + 'static) -> TracerProvider {
+    TracerProvider::builder()
+        .with_simple_exporter(exporter)
+        .with_resource(Resource::new([KeyValue::new(
+            "service.name",
+            "reaper-runtime",
+        )]))
+        .build()
+}
+
+fn layer_for_provider<S>(provider: &TracerProvider) -> OpenTelemetryLayer<S, Tracer>
+where
+    S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+{
+    tracing_opentelemetry::layer().with_tracer(provider.tracer("reaper-runtime"))
+}
+
+/// Tracing layer exporting spans to `REAPER_OTLP_ENDPOINT`, or `None` when
+/// it is unset or the exporter can't be built.
+pub fn layer<S>() -> Option<OpenTelemetryLayer<S, Tracer>>
+where
+    S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+{
+    let endpoint = traces_endpoint()?;
+    let agent = ureq::AgentBuilder::new().timeout(EXPORT_TIMEOUT).build();
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_http_client(UreqClient(agent))
+        .with_endpoint(endpoint)
+        .with_timeout(EXPORT_TIMEOUT)
+        .build()
+        .ok()?;
+    let provider = PROVIDER.get_or_init(|| provider_with_exporter(exporter));
+    Some(layer_for_provider(provider))
+}
+
+/// Flush and shut down the exporter, if one was built.
+pub fn shutdown() {
+    if let Some(provider) = PROVIDER.get() {
+        let _ = provider.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::trace::Status;
+    use opentelemetry_sdk::export::trace::{ExportResult, SpanData};
+    use std::sync::{Arc, Mutex};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[derive(Debug, Clone, Default)]
+    struct TestExporter(Arc<Mutex<Vec<SpanData>>>);
+
+    impl SpanExporter for TestExporter {
+        fn export(
+            &mut self,
+            batch: Vec<SpanData>,
+        ) -> std::pin::Pin<Box<dyn std::future::Future<Output = ExportResult> + Send + 'static>>
+        {
+            self.0.lock().unwrap().extend(batch);
+            Box::pin(std::future::ready(Ok(())))
+        }
+    }
+
+    fn attribute(span: &SpanData, key: &str) -> Option<String> {
+        span.attributes
+            .iter()
+            .find(|kv| kv.key.as_str() == key)
+            .map(|kv| kv.value.to_string())
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_traces_endpoint() {
+        std::env::remove_var("REAPER_OTLP_ENDPOINT");
+        assert_eq!(traces_endpoint(), None);
+        std::env::set_var("REAPER_OTLP_ENDPOINT", "http://collector:4318/");
+        assert_eq!(
+            traces_endpoint().as_deref(),
+            Some("http://collector:4318/v1/traces")
+        );
+        std::env::set_var("REAPER_OTLP_ENDPOINT", " ");
+        assert_eq!(traces_endpoint(), None);
+        std::env::remove_var("REAPER_OTLP_ENDPOINT");
+        assert!(layer::<tracing_subscriber::Registry>().is_none());
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_lifecycle_spans_exported() {
+        let exporter = TestExporter::default();
+        let provider = provider_with_exporter(exporter.clone());
+        let subscriber = tracing_subscriber::registry().with(layer_for_provider(&provider));
+
+        let root = tempfile::tempdir().unwrap();
+        let bundle = tempfile::tempdir().unwrap();
+        std::fs::write(
+            bundle.path().join("config.json"),
+            r#"{"process": {"args": ["/bin/true"]}}"#,
+        )
+        .unwrap();
+        std::env::set_var("REAPER_RUNTIME_ROOT", root.path());
+        tracing::subscriber::with_default(subscriber, || {
+            let create = crate::Commands::Create {
+                id: "otel-test".into(),
+                terminal: false,
+                stdin: None,
+                stdout: None,
+                stderr: None,
+                namespace: Some("team-a".into()),
+                annotations: Vec::new(),
+                sandbox: false,
+            };
+            let delete = crate::Commands::Delete {
+                id: "otel-test".into(),
+                force: false,
+            };
+            let kill = crate::Commands::Kill {
+                id: "otel-test".into(),
+                signal: None,
+                timeout: None,
+            };
+            crate::run_command(&create, bundle.path(), None).unwrap();
+            crate::run_command(&delete, bundle.path(), None).unwrap();
+            assert!(crate::run_command(&kill, bundle.path(), None).is_err());
+        });
+        std::env::remove_var("REAPER_RUNTIME_ROOT");
+
+        let spans = exporter.0.lock().unwrap().clone();
+        let names: Vec<&str> = spans.iter().map(|s| s.name.as_ref()).collect();
+        assert_eq!(names, ["create", "delete", "kill"]);
+
+        let create = &spans[0];
+        assert_eq!(
+            attribute(create, "container.id").as_deref(),
+            Some("otel-test")
+        );
+        assert_eq!(
+            attribute(create, "k8s.namespace").as_deref(),
+            Some("team-a")
+        );
+        assert_eq!(attribute(create, "outcome").as_deref(), Some("ok"));
+        assert!(attribute(create, "busy_ns").is_some());
+        assert!(create.end_time >= create.start_time);
+
+        let kill = &spans[2];
+        assert_eq!(attribute(kill, "outcome").as_deref(), Some("error"));
+        assert!(matches!(kill.status, Status::Unset | Status::Error { .. }));
+    }
+}
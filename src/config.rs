@@ -1,32 +1,74 @@
 //! Shared configuration file loader for Reaper binaries.
 //!
-//! Reads `/etc/reaper/reaper.conf` (or path from `REAPER_CONFIG` env var)
-//! and sets environment variables for any keys not already present.
+//! Reads `/etc/reaper/reaper.conf` (or path from `REAPER_CONFIG` env var),
+//! then drop-in files from `/etc/reaper/conf.d/*.conf` (or the directory in
+//! `REAPER_CONFIG_DIR`) in lexical order, and sets environment variables for
+//! any keys not already present.
 //!
 //! File format: simple `KEY=VALUE` lines. Comments (`#`) and blank lines
-//! are ignored. Environment variables always take precedence over file values.
+//! are ignored. Later files override earlier ones; environment variables
+//! always take precedence over file values.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 /// Default config file path, works on any Linux distribution.
 const DEFAULT_CONFIG_PATH: &str = "/etc/reaper/reaper.conf";
 
-/// Load configuration from the Reaper config file.
+/// Default drop-in directory, layered over the main config file.
+const DEFAULT_CONFIG_DIR: &str = "/etc/reaper/conf.d";
+
+/// Load configuration from the Reaper config file and drop-ins.
 ///
 /// Search order:
 /// 1. `REAPER_CONFIG` env var (explicit path override)
 /// 2. `/etc/reaper/reaper.conf`
 ///
-/// For each `KEY=VALUE` line, sets the environment variable only if it
+/// Then every `*.conf` file in `REAPER_CONFIG_DIR` (default
+/// `/etc/reaper/conf.d`), sorted by file name. A key set in several files
+/// takes the value from the last one.
+///
+/// For each merged `KEY=VALUE`, sets the environment variable only if it
 /// is not already set. This ensures env vars always win over file values.
 ///
-/// Silently returns if the config file doesn't exist (not an error).
+/// Silently skips files and directories that don't exist (not an error).
 pub fn load_config() {
     let path = std::env::var("REAPER_CONFIG").unwrap_or_else(|_| DEFAULT_CONFIG_PATH.to_string());
+    let dir = std::env::var("REAPER_CONFIG_DIR").unwrap_or_else(|_| DEFAULT_CONFIG_DIR.to_string());
 
-    let contents = match std::fs::read_to_string(&path) {
-        Ok(c) => c,
-        Err(_) => return, // File doesn't exist or unreadable — silently continue
+    let mut merged = HashMap::new();
+    for file in std::iter::once(PathBuf::from(path)).chain(config_dropins(Path::new(&dir))) {
+        // Missing or unreadable files are skipped
+        if let Ok(contents) = std::fs::read_to_string(&file) {
+            merged.extend(parse_config(&contents, &file));
+        }
+    }
+
+    for (key, value) in merged {
+        // Only set if not already present in environment (env wins)
+        if std::env::var(&key).is_err() {
+            std::env::set_var(key, value);
+        }
+    }
+}
+
+/// `*.conf` files in `dir`, in lexical order of their names.
+fn config_dropins(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
     };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "conf") && p.is_file())
+        .collect();
+    files.sort();
+    files
+}
 
+/// `KEY=VALUE` pairs from one config file, in file order.
+fn parse_config(contents: &str, path: &Path) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
     for line in contents.lines() {
         let trimmed = line.trim();
 
@@ -49,17 +91,37 @@ pub fn load_config() {
             if !key.starts_with("REAPER_") {
                 eprintln!(
                     "reaper: config: ignoring non-REAPER_ key {:?} in {}",
-                    key, path
+                    key,
+                    path.display()
                 );
                 continue;
             }
 
-            // Only set if not already present in environment (env wins)
-            if std::env::var(key).is_err() {
-                std::env::set_var(key, value);
-            }
+            pairs.push((key.to_string(), value.to_string()));
         }
     }
+    pairs
+}
+
+/// Parse an octal permission string such as `750` or `0o640`.
+pub fn parse_octal_mode(value: &str) -> Option<u32> {
+    let v = value.trim();
+    let digits = v.strip_prefix("0o").unwrap_or(v);
+    match u32::from_str_radix(digits, 8) {
+        Ok(mode) if !digits.is_empty() && mode <= 0o7777 => Some(mode),
+        _ => None,
+    }
+}
+
+const DEFAULT_LOG_FILE_MODE: u32 = 0o640;
+
+/// Mode for log files Reaper creates itself (`REAPER_LOG_FILE_MODE`, octal).
+/// Unset or invalid values keep the restrictive 0640 default.
+pub fn log_file_mode() -> u32 {
+    std::env::var("REAPER_LOG_FILE_MODE")
+        .ok()
+        .and_then(|v| parse_octal_mode(&v))
+        .unwrap_or(DEFAULT_LOG_FILE_MODE)
 }
 
 #[cfg(test)]
@@ -179,4 +241,91 @@ mod tests {
         std::env::remove_var("REAPER_CONFIG");
         std::env::remove_var("REAPER_TEST_VALID");
     }
+
+    #[test]
+    fn test_parse_octal_mode() {
+        assert_eq!(parse_octal_mode("750"), Some(0o750));
+        assert_eq!(parse_octal_mode("0640"), Some(0o640));
+        assert_eq!(parse_octal_mode("0o700"), Some(0o700));
+        assert_eq!(parse_octal_mode(""), None);
+        assert_eq!(parse_octal_mode("0o"), None);
+        assert_eq!(parse_octal_mode("789"), None);
+        assert_eq!(parse_octal_mode("rwx"), None);
+        assert_eq!(parse_octal_mode("17777"), None);
+    }
+
+    #[test]
+    #[serial]
+    fn test_log_file_mode() {
+        std::env::remove_var("REAPER_LOG_FILE_MODE");
+        assert_eq!(log_file_mode(), 0o640);
+        std::env::set_var("REAPER_LOG_FILE_MODE", "600");
+        assert_eq!(log_file_mode(), 0o600);
+        std::env::set_var("REAPER_LOG_FILE_MODE", "world");
+        assert_eq!(log_file_mode(), 0o640);
+        std::env::remove_var("REAPER_LOG_FILE_MODE");
+    }
+
+    #[test]
+    #[serial]
+    fn test_load_config_dropins_merge_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let conf = dir.path().join("reaper.conf");
+        std::fs::write(
+            &conf,
+            "REAPER_TEST_DROPIN_A=base\nREAPER_TEST_DROPIN_B=base\nREAPER_TEST_DROPIN_C=base\n",
+        )
+        .unwrap();
+        let conf_d = dir.path().join("conf.d");
+        std::fs::create_dir(&conf_d).unwrap();
+        // Written out of order: lexical file name order decides
+        std::fs::write(
+            conf_d.join("20-late.conf"),
+            "REAPER_TEST_DROPIN_B=late\nREAPER_TEST_DROPIN_C=late\n",
+        )
+        .unwrap();
+        std::fs::write(conf_d.join("10-early.conf"), "REAPER_TEST_DROPIN_B=early\n").unwrap();
+        std::fs::write(conf_d.join("99-ignored.bak"), "REAPER_TEST_DROPIN_A=bak\n").unwrap();
+
+        for key in ["A", "B", "C"] {
+            std::env::remove_var(format!("REAPER_TEST_DROPIN_{}", key));
+        }
+        std::env::set_var("REAPER_TEST_DROPIN_C", "from_env");
+        std::env::set_var("REAPER_CONFIG", &conf);
+        std::env::set_var("REAPER_CONFIG_DIR", &conf_d);
+
+        load_config();
+
+        assert_eq!(std::env::var("REAPER_TEST_DROPIN_A").unwrap(), "base");
+        assert_eq!(std::env::var("REAPER_TEST_DROPIN_B").unwrap(), "late");
+        assert_eq!(std::env::var("REAPER_TEST_DROPIN_C").unwrap(), "from_env");
+
+        std::env::remove_var("REAPER_CONFIG");
+        std::env::remove_var("REAPER_CONFIG_DIR");
+        for key in ["A", "B", "C"] {
+            std::env::remove_var(format!("REAPER_TEST_DROPIN_{}", key));
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_load_config_dropins_without_base_file() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("50-only.conf"),
+            "REAPER_TEST_DROPIN_ONLY=yes\n",
+        )
+        .unwrap();
+
+        std::env::remove_var("REAPER_TEST_DROPIN_ONLY");
+        std::env::set_var("REAPER_CONFIG", "/nonexistent/path/reaper.conf");
+        std::env::set_var("REAPER_CONFIG_DIR", dir.path());
+
+        load_config();
+        assert_eq!(std::env::var("REAPER_TEST_DROPIN_ONLY").unwrap(), "yes");
+
+        std::env::remove_var("REAPER_CONFIG");
+        std::env::remove_var("REAPER_CONFIG_DIR");
+        std::env::remove_var("REAPER_TEST_DROPIN_ONLY");
+    }
 }
@@ -361,3 +361,160 @@ fn test_annotations_stored_even_when_disabled() {
         .arg("test-ann-disabled")
         .output();
 }
+
+/// The `argv0` annotation sets `argv[0]` independently of the program path,
+/// so a shell invoked as `-sh` runs as a login shell.
+#[test]
+fn test_argv0_annotation_runs_login_shell() {
+    let bundle_dir = TempDir::new().expect("Failed to create temp bundle dir");
+    let result = bundle_dir.path().join("argv0");
+    let config = serde_json::json!({
+        "process": {
+            "args": ["/bin/sh", "-c", format!("echo \"$0\" > {}", result.display())],
+            "cwd": "/tmp",
+            "env": ["PATH=/usr/bin:/bin"]
+        }
+    });
+    fs::write(
+        bundle_dir.path().join("config.json"),
+        serde_json::to_string_pretty(&config).unwrap(),
+    )
+    .expect("Failed to write config.json");
+
+    let state_dir = TempDir::new().expect("Failed to create state dir");
+    let state_root = state_dir.path().to_string_lossy().to_string();
+    let reaper_bin = env!("CARGO_BIN_EXE_reaper-runtime");
+    let runtime = |args: &[&str]| {
+        Command::new(reaper_bin)
+            .env("REAPER_RUNTIME_ROOT", &state_root)
+            .env("REAPER_NO_OVERLAY", "1")
+            .args(args)
+            .output()
+            .expect("Failed to run reaper-runtime")
+    };
+
+    let bundle = bundle_dir.path().to_str().unwrap();
+    let id = "test-ann-argv0";
+    let create = runtime(&[
+        "create",
+        id,
+        "--bundle",
+        bundle,
+        "--annotation",
+        "argv0=-sh",
+    ]);
+    assert!(
+        create.status.success(),
+        "create failed: {}",
+        String::from_utf8_lossy(&create.stderr)
+    );
+    let start = runtime(&["start", id, "--bundle", bundle]);
+    assert!(
+        start.status.success(),
+        "start failed: {}",
+        String::from_utf8_lossy(&start.stderr)
+    );
+
+    let started = std::time::Instant::now();
+    let argv0 = loop {
+        if let Ok(content) = fs::read_to_string(&result) {
+            if content.ends_with('\n') {
+                break content;
+            }
+        }
+        assert!(
+            started.elapsed() < std::time::Duration::from_secs(10),
+            "workload did not report its argv[0]"
+        );
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    };
+    runtime(&["delete", id, "--force"]);
+
+    // A leading '-' in argv[0] is what makes a shell a login shell
+    assert_eq!(argv0.trim(), "-sh");
+}
+
+/// With `enable-fuse`, the workload finds /dev/fuse and can mount a FUSE
+/// filesystem on it. Needs root and a host with the fuse module loaded.
+#[test]
+fn test_enable_fuse_annotation_allows_fuse_mount() {
+    if !nix::unistd::getuid().is_root() || !std::path::Path::new("/dev/fuse").exists() {
+        eprintln!("skipping: needs root and /dev/fuse");
+        return;
+    }
+    let bundle_dir = TempDir::new().expect("Failed to create temp bundle dir");
+    let mountpoint = bundle_dir.path().join("mnt");
+    let result = bundle_dir.path().join("fuse");
+    fs::create_dir(&mountpoint).unwrap();
+    // Mounting is all that's checked, so no FUSE daemon is needed to serve
+    // requests: the mount is listed, then lazily unmounted
+    let script = format!(
+        "exec 3<>/dev/fuse && \
+         mount -t fuse -o fd=3,rootmode=40000,user_id=0,group_id=0 reaperfuse {mnt} && \
+         grep -c reaperfuse /proc/self/mounts > {out}.tmp; \
+         umount -l {mnt}; mv {out}.tmp {out}",
+        mnt = mountpoint.display(),
+        out = result.display()
+    );
+    let config = serde_json::json!({
+        "process": {
+            "args": ["/bin/sh", "-c", script],
+            "cwd": "/tmp",
+            "env": ["PATH=/usr/bin:/bin:/usr/sbin:/sbin"]
+        }
+    });
+    fs::write(
+        bundle_dir.path().join("config.json"),
+        serde_json::to_string_pretty(&config).unwrap(),
+    )
+    .expect("Failed to write config.json");
+
+    let state_dir = TempDir::new().expect("Failed to create state dir");
+    let state_root = state_dir.path().to_string_lossy().to_string();
+    let reaper_bin = env!("CARGO_BIN_EXE_reaper-runtime");
+    let runtime = |args: &[&str]| {
+        Command::new(reaper_bin)
+            .env("REAPER_RUNTIME_ROOT", &state_root)
+            .env("REAPER_NO_OVERLAY", "1")
+            .args(args)
+            .output()
+            .expect("Failed to run reaper-runtime")
+    };
+
+    let bundle = bundle_dir.path().to_str().unwrap();
+    let id = "test-ann-fuse";
+    let create = runtime(&[
+        "create",
+        id,
+        "--bundle",
+        bundle,
+        "--annotation",
+        "enable-fuse=true",
+    ]);
+    assert!(
+        create.status.success(),
+        "create failed: {}",
+        String::from_utf8_lossy(&create.stderr)
+    );
+    let start = runtime(&["start", id, "--bundle", bundle]);
+    assert!(
+        start.status.success(),
+        "start failed: {}",
+        String::from_utf8_lossy(&start.stderr)
+    );
+
+    let started = std::time::Instant::now();
+    let mounts = loop {
+        if let Ok(content) = fs::read_to_string(&result) {
+            break content;
+        }
+        assert!(
+            started.elapsed() < std::time::Duration::from_secs(10),
+            "workload did not report its fuse mount"
+        );
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    };
+    runtime(&["delete", id, "--force"]);
+
+    assert_eq!(mounts.trim(), "1", "fuse mount not listed in the container");
+}
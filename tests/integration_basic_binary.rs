@@ -1,8 +1,38 @@
 use std::fs;
-use std::path::PathBuf;
-use std::process::Command;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
 use tempfile::TempDir;
 
+/// A bundle directory, which also holds the state root, and a runner for
+/// `reaper-runtime` against that root with overlays off and `env` set.
+fn runtime_fixture(env: &[(&str, &str)]) -> (TempDir, impl Fn(&[&str]) -> Output) {
+    let bundle_dir = TempDir::new().expect("Failed to create temp bundle dir");
+    let state_root = bundle_dir.path().join("state");
+    let env: Vec<(String, String)> = env
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    let runtime = move |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_reaper-runtime"))
+            .env("REAPER_RUNTIME_ROOT", &state_root)
+            .env("REAPER_NO_OVERLAY", "1")
+            .envs(env.iter().cloned())
+            .args(args)
+            .output()
+            .expect("Failed to run reaper-runtime")
+    };
+    (bundle_dir, runtime)
+}
+
+/// Write `config` as the bundle's config.json.
+fn write_config(bundle: &Path, config: &serde_json::Value) {
+    fs::write(
+        bundle.join("config.json"),
+        serde_json::to_string_pretty(config).unwrap(),
+    )
+    .expect("Failed to write config.json");
+}
+
 /// Test basic binary execution: run `echo "hello world"` through reaper-runtime
 #[test]
 fn test_run_echo_hello_world() {
@@ -122,6 +152,17 @@ fn test_run_echo_hello_world() {
     );
 
     assert_eq!(state["pid"], pid, "PID in state should match start output");
+    assert_eq!(
+        state["isolated"], false,
+        "REAPER_NO_OVERLAY workloads run on the host filesystem"
+    );
+    let phases: Vec<&str> = state["timings"]
+        .as_array()
+        .expect("timings recorded once spawned")
+        .iter()
+        .map(|t| t["phase"].as_str().unwrap())
+        .collect();
+    assert_eq!(phases, ["create", "prepare", "spawn"]);
 
     // If stopped, verify exit code is 0
     if status == "stopped" {
@@ -246,3 +287,568 @@ fn test_invalid_bundle() {
         "start should fail for missing config.json"
     );
 }
+
+/// `--log` writes the runtime's logs to that file, and `--log-format json`
+/// makes them JSON lines.
+#[test]
+fn test_log_flags_write_log_file() {
+    let state_dir = TempDir::new().expect("Failed to create state dir");
+    let reaper_bin = env!("CARGO_BIN_EXE_reaper-runtime");
+
+    for (format, id) in [("text", "log-text"), ("json", "log-json")] {
+        let log_path = state_dir.path().join(format!("{}.log", format));
+        let output = Command::new(reaper_bin)
+            .env("REAPER_RUNTIME_ROOT", state_dir.path())
+            .env("REAPER_NO_OVERLAY", "1")
+            .env_remove("REAPER_RUNTIME_LOG")
+            .env_remove("RUST_LOG")
+            .arg("--log")
+            .arg(&log_path)
+            .arg("--log-format")
+            .arg(format)
+            .arg("create")
+            .arg(id)
+            .arg("--bundle")
+            .arg("/tmp/nonexistent-bundle-12345")
+            .output()
+            .expect("Failed to run create command");
+        assert!(
+            output.status.success(),
+            "create failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let log = fs::read_to_string(&log_path).expect("log file should be written");
+        assert!(
+            log.contains("Reaper Runtime Starting") && log.contains(id),
+            "{} log: {}",
+            format,
+            log
+        );
+        if format == "json" {
+            for line in log.lines() {
+                let entry: serde_json::Value = serde_json::from_str(line)
+                    .unwrap_or_else(|e| panic!("not JSON ({}): {}", e, line));
+                assert!(entry["level"].is_string() && entry["fields"].is_object());
+            }
+        } else {
+            assert!(
+                serde_json::from_str::<serde_json::Value>(log.lines().next().unwrap()).is_err()
+            );
+        }
+    }
+}
+
+/// `kill` delivers the stop signal to the init of a PID namespace the
+/// workload created, even when that init left the workload's process group.
+#[cfg(target_os = "linux")]
+#[test]
+fn test_kill_reaches_pid_namespace_init() {
+    let can_unshare_pid = Command::new("unshare")
+        .args(["--pid", "--fork", "true"])
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false);
+    if !can_unshare_pid {
+        eprintln!("Skipping test_kill_reaches_pid_namespace_init: requires PID namespace support");
+        return;
+    }
+
+    let (bundle_dir, runtime) = runtime_fixture(&[]);
+    let bundle_path = bundle_dir.path();
+    let marker = bundle_path.join("got-term");
+    let ready = bundle_path.join("ready");
+
+    // unshare blocks SIGTERM while waiting, and setsid moves the namespace
+    // init (sh, PID 1) out of the workload's process group, so only a
+    // direct signal to the init reaches the trap.
+    let script = format!(
+        "trap 'echo $$ > {}; exit 0' TERM; touch {}; while :; do sleep 0.1; done",
+        marker.display(),
+        ready.display()
+    );
+    let config = serde_json::json!({
+        "process": {
+            "args": ["unshare", "--pid", "--fork", "setsid", "/bin/sh", "-c", script],
+            "cwd": "/tmp",
+            "env": ["PATH=/usr/bin:/bin:/usr/sbin:/sbin"]
+        }
+    });
+    write_config(bundle_path, &config);
+
+    let bundle = bundle_path.to_str().unwrap();
+    assert!(runtime(&["create", "test-pidns-kill", "--bundle", bundle])
+        .status
+        .success());
+    let start = runtime(&["start", "test-pidns-kill", "--bundle", bundle]);
+    assert!(
+        start.status.success(),
+        "start failed: {}",
+        String::from_utf8_lossy(&start.stderr)
+    );
+
+    let wait_for = |path: &std::path::Path| {
+        for _ in 0..100 {
+            if path.exists() {
+                return true;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+        false
+    };
+    assert!(wait_for(&ready), "workload never became ready");
+
+    let kill = runtime(&["kill", "test-pidns-kill", "15"]);
+    assert!(
+        kill.status.success(),
+        "kill failed: {}",
+        String::from_utf8_lossy(&kill.stderr)
+    );
+    let received = wait_for(&marker);
+
+    runtime(&["kill", "test-pidns-kill", "9"]);
+    runtime(&["delete", "test-pidns-kill"]);
+
+    assert!(received, "namespace init did not receive SIGTERM");
+    assert_eq!(fs::read_to_string(&marker).unwrap().trim(), "1");
+}
+
+/// Run a workload that ignores SIGTERM, `kill` it with `kill_args` and
+/// assert that it is SIGKILLed after a 1s grace period, without `kill`
+/// itself blocking.
+fn assert_kill_escalates(
+    id: &str,
+    create_args: &[&str],
+    kill_args: &[&str],
+    grace_env: Option<&str>,
+) {
+    // An empty REAPER_KILL_GRACE_SECS counts as unset
+    let (bundle_dir, runtime) =
+        runtime_fixture(&[("REAPER_KILL_GRACE_SECS", grace_env.unwrap_or(""))]);
+    let bundle_path = bundle_dir.path();
+    let ready = bundle_path.join("ready");
+
+    // SIG_IGN is inherited across exec, so `sleep` ignores SIGTERM too
+    let script = format!("trap '' TERM; touch {}; sleep 30", ready.display());
+    let config = serde_json::json!({
+        "process": {
+            "args": ["/bin/sh", "-c", script],
+            "cwd": "/tmp",
+            "env": ["PATH=/usr/bin:/bin"]
+        }
+    });
+    write_config(bundle_path, &config);
+
+    let bundle_arg = bundle_path.to_str().unwrap();
+
+    let mut create = vec!["create", id, "--bundle", bundle_arg];
+    create.extend_from_slice(create_args);
+    let create = runtime(&create);
+    assert!(create.status.success(), "create failed: {:?}", create);
+    assert!(runtime(&["start", id, "--bundle", bundle_arg])
+        .status
+        .success());
+    for _ in 0..100 {
+        if ready.exists() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+    assert!(ready.exists(), "workload never became ready");
+
+    let killed_at = std::time::Instant::now();
+    let mut kill = vec!["kill", id];
+    kill.extend_from_slice(kill_args);
+    let kill = runtime(&kill);
+    assert!(kill.status.success(), "kill failed: {:?}", kill);
+    assert!(
+        killed_at.elapsed() < std::time::Duration::from_secs(1),
+        "kill must not wait for the grace period"
+    );
+
+    let state = loop {
+        let state: serde_json::Value =
+            serde_json::from_slice(&runtime(&["state", id]).stdout).unwrap_or_default();
+        if state["status"] == "stopped" || killed_at.elapsed().as_secs() >= 10 {
+            break state;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    };
+    runtime(&["kill", id, "9"]);
+    runtime(&["delete", id]);
+
+    assert_eq!(state["status"], "stopped", "not escalated: {}", state);
+    assert_eq!(state["exit_signal"], 9, "not escalated: {}", state);
+    assert!(killed_at.elapsed() >= std::time::Duration::from_secs(1));
+}
+
+/// SIGTERM escalates to SIGKILL after the `termination-grace` annotation's
+/// delay when the workload ignores it.
+#[test]
+fn test_kill_escalates_after_termination_grace() {
+    // The shim passes the pod's reaper.runtime/* annotations stripped
+    assert_kill_escalates(
+        "test-grace",
+        &["--annotation", "termination-grace=1"],
+        &["15"],
+        None,
+    );
+}
+
+/// `kill --timeout` escalates a `sleep` ignoring SIGTERM, even with
+/// escalation disabled in the node config.
+#[test]
+fn test_kill_timeout_escalates_ignored_sigterm() {
+    assert_kill_escalates(
+        "test-kill-timeout",
+        &[],
+        &["15", "--timeout", "1"],
+        Some("0"),
+    );
+}
+
+/// After `start` returns for a PTY container, `state` (what the shim's
+/// `connect()` reads) reports it running with the workload's PID, and never
+/// a half-written state.
+#[test]
+fn test_pty_container_pid_readable_after_start() {
+    let (bundle_dir, runtime) = runtime_fixture(&[]);
+    let bundle_path = bundle_dir.path();
+    let config = serde_json::json!({
+        "process": {
+            "args": ["/bin/sleep", "5"],
+            "cwd": "/tmp",
+            "env": ["PATH=/usr/bin:/bin"]
+        }
+    });
+    write_config(bundle_path, &config);
+
+    let bundle = bundle_path.to_str().unwrap();
+    let create = runtime(&["create", "test-pty-pid", "--bundle", bundle, "--terminal"]);
+    assert!(
+        create.status.success(),
+        "create failed: {}",
+        String::from_utf8_lossy(&create.stderr)
+    );
+    let start = runtime(&["start", "test-pty-pid", "--bundle", bundle]);
+    assert!(
+        start.status.success(),
+        "start failed: {}",
+        String::from_utf8_lossy(&start.stderr)
+    );
+
+    let started = std::time::Instant::now();
+    let mut pids = Vec::new();
+    while started.elapsed() < std::time::Duration::from_millis(300) {
+        let output = runtime(&["state", "test-pty-pid"]);
+        let state: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap_or_else(|e| {
+            panic!(
+                "unparseable state {:?}: {}",
+                String::from_utf8_lossy(&output.stdout),
+                e
+            )
+        });
+        assert_eq!(state["status"], "running", "state: {}", state);
+        pids.push(state["pid"].as_u64().unwrap_or(0));
+    }
+
+    runtime(&["kill", "test-pty-pid", "9"]);
+    runtime(&["delete", "test-pty-pid"]);
+
+    assert!(!pids.is_empty());
+    assert!(pids.iter().all(|&pid| pid > 0), "pids: {:?}", pids);
+}
+
+/// `start --pid-file` writes the workload PID, the one `start` reports and
+/// records in state, creating the file's directory.
+#[test]
+fn test_start_writes_pid_file() {
+    let (bundle_dir, runtime) = runtime_fixture(&[]);
+    let bundle_path = bundle_dir.path();
+    let config = serde_json::json!({
+        "process": {
+            "args": ["/bin/sleep", "5"],
+            "cwd": "/tmp",
+            "env": ["PATH=/usr/bin:/bin"]
+        }
+    });
+    write_config(bundle_path, &config);
+
+    let bundle = bundle_path.to_str().unwrap();
+    let pid_file = bundle_path.join("run/container.pid");
+    let create = runtime(&["create", "test-pid-file", "--bundle", bundle]);
+    assert!(
+        create.status.success(),
+        "create failed: {}",
+        String::from_utf8_lossy(&create.stderr)
+    );
+    let start = runtime(&[
+        "start",
+        "test-pid-file",
+        "--bundle",
+        bundle,
+        "--pid-file",
+        pid_file.to_str().unwrap(),
+    ]);
+    assert!(
+        start.status.success(),
+        "start failed: {}",
+        String::from_utf8_lossy(&start.stderr)
+    );
+
+    let written = fs::read_to_string(&pid_file).unwrap_or_default();
+    let state: serde_json::Value =
+        serde_json::from_slice(&runtime(&["state", "test-pid-file"]).stdout).unwrap_or_default();
+    runtime(&["kill", "test-pid-file", "9"]);
+    runtime(&["delete", "test-pid-file"]);
+
+    let pid: u64 = written.parse().expect("pid file holds a PID");
+    assert_eq!(Some(pid), state["pid"].as_u64(), "state: {}", state);
+    assert_eq!(
+        String::from_utf8_lossy(&start.stdout).trim(),
+        format!("started pid={}", pid)
+    );
+    // Only the pid file, no temporary left next to it
+    assert_eq!(fs::read_dir(pid_file.parent().unwrap()).unwrap().count(), 1);
+}
+
+/// `process.terminal: false` in config.json wins over `--terminal` from
+/// create: the workload runs without a PTY.
+#[test]
+fn test_config_terminal_false_overrides_create_terminal() {
+    let (bundle_dir, runtime) = runtime_fixture(&[]);
+    let bundle_path = bundle_dir.path();
+    let result = bundle_path.join("result");
+    let script = format!(
+        "if [ -t 1 ]; then echo tty > {0}; else echo notty > {0}; fi",
+        result.display()
+    );
+    let config = serde_json::json!({
+        "process": {
+            "args": ["/bin/sh", "-c", script],
+            "cwd": "/tmp",
+            "env": ["PATH=/usr/bin:/bin"],
+            "terminal": false
+        }
+    });
+    write_config(bundle_path, &config);
+
+    let bundle = bundle_path.to_str().unwrap();
+    let create = runtime(&["create", "test-no-pty", "--bundle", bundle, "--terminal"]);
+    assert!(
+        create.status.success(),
+        "create failed: {}",
+        String::from_utf8_lossy(&create.stderr)
+    );
+    let start = runtime(&["start", "test-no-pty", "--bundle", bundle]);
+    assert!(
+        start.status.success(),
+        "start failed: {}",
+        String::from_utf8_lossy(&start.stderr)
+    );
+
+    let started = std::time::Instant::now();
+    let content = loop {
+        match fs::read_to_string(&result) {
+            Ok(content) if !content.is_empty() => break content,
+            _ if started.elapsed() > std::time::Duration::from_secs(5) => {
+                panic!("workload did not write {}", result.display())
+            }
+            _ => std::thread::sleep(std::time::Duration::from_millis(50)),
+        }
+    };
+    runtime(&["delete", "test-no-pty"]);
+
+    assert_eq!(content.trim(), "notty");
+}
+
+/// Run a workload that tries to write `/proc/sys/kernel/hostname` (writing
+/// back its current value, so a successful write changes nothing) and report
+/// whether the write succeeded.
+#[cfg(target_os = "linux")]
+fn proc_sys_write_succeeds(id: &str, linux: serde_json::Value) -> bool {
+    let (bundle_dir, runtime) = runtime_fixture(&[]);
+    let bundle_path = bundle_dir.path();
+    let result = bundle_path.join("result");
+    let script = format!(
+        "if cat /proc/sys/kernel/hostname > /proc/sys/kernel/hostname; \
+         then echo ok > {0}; else echo denied > {0}; fi",
+        result.display()
+    );
+    let config = serde_json::json!({
+        "process": {
+            "args": ["/bin/sh", "-c", script],
+            "cwd": "/tmp",
+            "env": ["PATH=/usr/bin:/bin"]
+        },
+        "linux": linux
+    });
+    write_config(bundle_path, &config);
+
+    let bundle = bundle_path.to_str().unwrap();
+    assert!(runtime(&["create", id, "--bundle", bundle])
+        .status
+        .success());
+    let start = runtime(&["start", id, "--bundle", bundle]);
+    assert!(
+        start.status.success(),
+        "start failed: {}",
+        String::from_utf8_lossy(&start.stderr)
+    );
+
+    let started = std::time::Instant::now();
+    let outcome = loop {
+        if let Ok(content) = fs::read_to_string(&result) {
+            if content.ends_with('\n') {
+                break content;
+            }
+        }
+        assert!(
+            started.elapsed() < std::time::Duration::from_secs(10),
+            "workload did not report a result"
+        );
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    };
+    runtime(&["delete", id, "--force"]);
+    outcome.trim() == "ok"
+}
+
+/// A workload with a UTS namespace and `hostname` sees that hostname, the
+/// node keeps its own.
+#[cfg(target_os = "linux")]
+#[test]
+fn test_workload_hostname_in_uts_namespace() {
+    if !nix::unistd::geteuid().is_root() {
+        eprintln!("skipping: a UTS namespace needs root");
+        return;
+    }
+    let node_hostname = fs::read_to_string("/proc/sys/kernel/hostname").unwrap();
+    let (bundle_dir, runtime) = runtime_fixture(&[]);
+    let result = bundle_dir.path().join("result");
+    let config = serde_json::json!({
+        "process": {
+            "args": ["/bin/sh", "-c", format!("uname -n > {}", result.display())],
+            "cwd": "/tmp",
+            "env": ["PATH=/usr/bin:/bin"]
+        },
+        "hostname": "reaper-uts-test",
+        "linux": {"namespaces": [{"type": "uts"}, {"type": "mount"}]}
+    });
+    write_config(bundle_dir.path(), &config);
+
+    let bundle = bundle_dir.path().to_str().unwrap();
+    assert!(runtime(&["create", "uts-test", "--bundle", bundle])
+        .status
+        .success());
+    let start = runtime(&["start", "uts-test", "--bundle", bundle]);
+    assert!(
+        start.status.success(),
+        "start failed: {}",
+        String::from_utf8_lossy(&start.stderr)
+    );
+
+    let started = std::time::Instant::now();
+    let hostname = loop {
+        if let Ok(content) = fs::read_to_string(&result) {
+            if content.ends_with('\n') {
+                break content;
+            }
+        }
+        assert!(
+            started.elapsed() < std::time::Duration::from_secs(10),
+            "workload did not report its hostname"
+        );
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    };
+    runtime(&["delete", "uts-test", "--force"]);
+    assert_eq!(hostname.trim(), "reaper-uts-test");
+    assert_eq!(
+        fs::read_to_string("/proc/sys/kernel/hostname").unwrap(),
+        node_hostname
+    );
+}
+
+/// `/proc/sys` is read-only for normal containers (CRI sets readonlyPaths)
+/// and stays writable for privileged ones (no readonly or masked paths).
+#[cfg(target_os = "linux")]
+#[test]
+fn test_proc_sys_read_only_unless_privileged() {
+    if !nix::unistd::geteuid().is_root() {
+        eprintln!("skipping: writing /proc/sys needs root");
+        return;
+    }
+
+    let normal = serde_json::json!({
+        "readonlyPaths": ["/proc/sys", "/proc/bus", "/proc/fs", "/proc/irq"],
+        "maskedPaths": ["/proc/sysrq-trigger", "/proc/kcore"]
+    });
+    assert!(!proc_sys_write_succeeds("test-proc-sys-normal", normal));
+
+    let privileged = serde_json::json!({});
+    assert!(proc_sys_write_succeeds(
+        "test-proc-sys-privileged",
+        privileged
+    ));
+}
+
+/// Start a workload that asks for a supplementary group as the runtime's own
+/// (non-root) user under the given `REAPER_SETGROUPS_POLICY`, and report
+/// whether it ran.
+#[cfg(target_os = "linux")]
+fn workload_runs_with_setgroups_policy(id: &str, policy: &str) -> bool {
+    let (bundle_dir, runtime) = runtime_fixture(&[("REAPER_SETGROUPS_POLICY", policy)]);
+    let bundle_path = bundle_dir.path();
+    let marker = bundle_path.join("ran");
+    let uid = nix::unistd::getuid().as_raw();
+    let gid = nix::unistd::getgid().as_raw();
+    let config = serde_json::json!({
+        "process": {
+            "args": ["/bin/sh", "-c", format!("touch {}", marker.display())],
+            "cwd": "/tmp",
+            "env": ["PATH=/usr/bin:/bin"],
+            "user": {"uid": uid, "gid": gid, "additionalGids": [gid]}
+        }
+    });
+    write_config(bundle_path, &config);
+
+    let bundle = bundle_path.to_str().unwrap();
+    assert!(runtime(&["create", id, "--bundle", bundle])
+        .status
+        .success());
+    runtime(&["start", id, "--bundle", bundle]);
+
+    let started = std::time::Instant::now();
+    loop {
+        let state = runtime(&["state", id]);
+        if String::from_utf8_lossy(&state.stdout).contains("\"stopped\"") {
+            break;
+        }
+        assert!(
+            started.elapsed() < std::time::Duration::from_secs(10),
+            "workload did not stop"
+        );
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+    runtime(&["delete", id, "--force"]);
+    marker.exists()
+}
+
+/// Without CAP_SETGID, `setgroups` is refused: strict (the default) fails the
+/// start, best-effort runs the workload without supplementary groups.
+#[cfg(target_os = "linux")]
+#[test]
+fn test_setgroups_policy_non_root() {
+    if nix::unistd::geteuid().is_root() {
+        eprintln!("skipping: needs a non-root runtime");
+        return;
+    }
+
+    assert!(!workload_runs_with_setgroups_policy(
+        "test-setgroups-strict",
+        "strict"
+    ));
+    assert!(workload_runs_with_setgroups_policy(
+        "test-setgroups-best-effort",
+        "best-effort"
+    ));
+}
@@ -262,6 +262,182 @@ fn test_multiline_output_to_fifo() {
     assert!(content.contains("line 3"));
 }
 
+/// HIGH: Test process.rlimits are applied to the workload (unknown types skipped)
+#[test]
+fn test_rlimit_nofile_applied_via_fifo() {
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    let bundle_dir = TempDir::new().expect("Failed to create temp bundle dir");
+    let bundle_path = bundle_dir.path();
+
+    let config = serde_json::json!({
+        "process": {
+            "args": ["/bin/sh", "-c", "ulimit -n"],
+            "cwd": "/tmp",
+            "rlimits": [
+                {"type": "RLIMIT_NOFILE", "soft": 512, "hard": 512},
+                {"type": "RLIMIT_BOGUS", "soft": 1, "hard": 1}
+            ]
+        }
+    });
+
+    let config_path = bundle_path.join("config.json");
+    fs::write(&config_path, serde_json::to_string_pretty(&config).unwrap())
+        .expect("Failed to write config.json");
+
+    let state_dir = TempDir::new().expect("Failed to create state dir");
+    let state_root = state_dir.path().to_string_lossy().to_string();
+
+    let io_dir = TempDir::new().expect("Failed to create I/O dir");
+    let stdout_fifo_path = io_dir.path().join("stdout").to_string_lossy().to_string();
+
+    create_fifo(&stdout_fifo_path).expect("Failed to create FIFO");
+
+    let reaper_bin = env!("CARGO_BIN_EXE_reaper-runtime");
+
+    Command::new(reaper_bin)
+        .env("REAPER_RUNTIME_ROOT", &state_root)
+        .env("REAPER_NO_OVERLAY", "1")
+        .arg("create")
+        .arg("test-rlimit-nofile")
+        .arg("--bundle")
+        .arg(bundle_path)
+        .arg("--stdout")
+        .arg(&stdout_fifo_path)
+        .output()
+        .expect("Failed to create");
+
+    let fifo_path_for_reader = stdout_fifo_path.clone();
+    let fifo_content = Arc::new(Mutex::new(String::new()));
+    let fifo_content_clone = Arc::clone(&fifo_content);
+
+    let reader_thread = thread::spawn(move || {
+        if let Ok(mut file) = std::fs::File::open(&fifo_path_for_reader) {
+            let mut content = String::new();
+            let _ = file.read_to_string(&mut content);
+            *fifo_content_clone.lock().unwrap() = content;
+        }
+    });
+
+    std::thread::sleep(std::time::Duration::from_millis(100));
+
+    let start_output = Command::new(reaper_bin)
+        .env("REAPER_RUNTIME_ROOT", &state_root)
+        .env("REAPER_NO_OVERLAY", "1")
+        .arg("start")
+        .arg("test-rlimit-nofile")
+        .arg("--bundle")
+        .arg(bundle_path)
+        .output()
+        .expect("Failed to start");
+    assert!(
+        start_output.status.success(),
+        "start failed: {}",
+        String::from_utf8_lossy(&start_output.stderr)
+    );
+
+    let _ = reader_thread.join();
+    let content = fifo_content.lock().unwrap();
+    assert_eq!(content.trim(), "512", "ulimit -n output: {:?}", content);
+}
+
+/// HIGH: Test process.capabilities narrow the workload's capability sets
+#[test]
+fn test_capabilities_applied_via_fifo() {
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    // Narrowing needs CAP_SETPCAP, and capsh to print the result
+    if !nix::unistd::getuid().is_root() || !std::path::Path::new("/usr/sbin/capsh").exists() {
+        return;
+    }
+
+    let bundle_dir = TempDir::new().expect("Failed to create temp bundle dir");
+    let bundle_path = bundle_dir.path();
+
+    let config = serde_json::json!({
+        "process": {
+            "args": ["/usr/sbin/capsh", "--print"],
+            "cwd": "/tmp",
+            "capabilities": {
+                "bounding": ["CAP_NET_ADMIN"],
+                "effective": ["CAP_NET_ADMIN"],
+                "permitted": ["CAP_NET_ADMIN"],
+                "ambient": ["CAP_BOGUS"]
+            }
+        }
+    });
+
+    let config_path = bundle_path.join("config.json");
+    fs::write(&config_path, serde_json::to_string_pretty(&config).unwrap())
+        .expect("Failed to write config.json");
+
+    let state_dir = TempDir::new().expect("Failed to create state dir");
+    let state_root = state_dir.path().to_string_lossy().to_string();
+
+    let io_dir = TempDir::new().expect("Failed to create I/O dir");
+    let stdout_fifo_path = io_dir.path().join("stdout").to_string_lossy().to_string();
+
+    create_fifo(&stdout_fifo_path).expect("Failed to create FIFO");
+
+    let reaper_bin = env!("CARGO_BIN_EXE_reaper-runtime");
+
+    Command::new(reaper_bin)
+        .env("REAPER_RUNTIME_ROOT", &state_root)
+        .env("REAPER_NO_OVERLAY", "1")
+        .arg("create")
+        .arg("test-capabilities")
+        .arg("--bundle")
+        .arg(bundle_path)
+        .arg("--stdout")
+        .arg(&stdout_fifo_path)
+        .output()
+        .expect("Failed to create");
+
+    let fifo_path_for_reader = stdout_fifo_path.clone();
+    let fifo_content = Arc::new(Mutex::new(String::new()));
+    let fifo_content_clone = Arc::clone(&fifo_content);
+
+    let reader_thread = thread::spawn(move || {
+        if let Ok(mut file) = std::fs::File::open(&fifo_path_for_reader) {
+            let mut content = String::new();
+            let _ = file.read_to_string(&mut content);
+            *fifo_content_clone.lock().unwrap() = content;
+        }
+    });
+
+    std::thread::sleep(std::time::Duration::from_millis(100));
+
+    let start_output = Command::new(reaper_bin)
+        .env("REAPER_RUNTIME_ROOT", &state_root)
+        .env("REAPER_NO_OVERLAY", "1")
+        .arg("start")
+        .arg("test-capabilities")
+        .arg("--bundle")
+        .arg(bundle_path)
+        .output()
+        .expect("Failed to start");
+    assert!(
+        start_output.status.success(),
+        "start failed: {}",
+        String::from_utf8_lossy(&start_output.stderr)
+    );
+
+    let _ = reader_thread.join();
+    let content = fifo_content.lock().unwrap();
+    assert!(
+        content.contains("Current: cap_net_admin=ep"),
+        "capsh output: {:?}",
+        content
+    );
+    assert!(
+        content.contains("Bounding set =cap_net_admin\n"),
+        "capsh output: {:?}",
+        content
+    );
+}
+
 /// HIGH: Test fallback to inherited stdio when FIFO doesn't exist
 #[test]
 fn test_fifo_nonexistent_fallback_to_inherit() {
@@ -41,23 +41,26 @@ mod overlay_tests {
     /// Helper: run a workload through reaper-runtime create/start/delete lifecycle.
     /// Returns the state JSON captured from the workload.
     fn run_workload(container_id: &str, command: &[&str]) -> String {
+        let config = serde_json::json!({
+            "process": {
+                "args": command,
+                "cwd": "/tmp",
+                "env": ["PATH=/usr/bin:/bin:/usr/local/bin"]
+            }
+        });
+        run_workload_with_config(container_id, &config, &[])
+    }
+
+    /// Like `run_workload`, but with a full config.json and extra runtime env vars.
+    fn run_workload_with_config(
+        container_id: &str,
+        config: &serde_json::Value,
+        extra_env: &[(&str, &str)],
+    ) -> String {
         let tmp = tempfile::tempdir().unwrap();
         let bundle = tmp.path().join("bundle");
         fs::create_dir_all(&bundle).unwrap();
-
-        // Create config.json
-        let args_json: Vec<String> = command.iter().map(|s| format!("\"{}\"", s)).collect();
-        let config = format!(
-            r#"{{
-                "process": {{
-                    "args": [{}],
-                    "cwd": "/tmp",
-                    "env": ["PATH=/usr/bin:/bin:/usr/local/bin"]
-                }}
-            }}"#,
-            args_json.join(", ")
-        );
-        fs::write(bundle.join("config.json"), &config).unwrap();
+        fs::write(bundle.join("config.json"), config.to_string()).unwrap();
 
         let runtime = reaper_runtime_binary();
         let state_dir = tmp.path().join("state");
@@ -70,6 +73,7 @@ mod overlay_tests {
             .arg(&bundle)
             .env("REAPER_RUNTIME_ROOT", &state_dir)
             .env("REAPER_OVERLAY_ISOLATION", "node")
+            .envs(extra_env.iter().copied())
             .output()
             .unwrap();
         assert!(
@@ -86,6 +90,7 @@ mod overlay_tests {
             .arg(&bundle)
             .env("REAPER_RUNTIME_ROOT", &state_dir)
             .env("REAPER_OVERLAY_ISOLATION", "node")
+            .envs(extra_env.iter().copied())
             .output()
             .unwrap();
         assert!(
@@ -209,6 +214,30 @@ mod overlay_tests {
         );
     }
 
+    #[test]
+    #[serial]
+    fn test_fresh_devpts_instance() {
+        if !can_use_overlay() {
+            eprintln!(
+                "Skipping test_fresh_devpts_instance: requires root + mount namespace support"
+            );
+            return;
+        }
+
+        use std::os::unix::fs::MetadataExt;
+        let host_dev = fs::metadata("/dev/pts").map(|m| m.dev()).unwrap_or(0);
+        let script = format!(
+            "test \"$(stat -c %d /dev/pts)\" != {} && exec 3<>/dev/ptmx && test -e /dev/pts/0",
+            host_dev
+        );
+        let state = run_workload("overlay-devpts-test", &["/bin/sh", "-c", &script]);
+        assert!(
+            state.contains("\"exit_code\": 0") || state.contains("\"exit_code\":0"),
+            "/dev/pts should be a fresh devpts instance with a usable ptmx, got: {}",
+            state
+        );
+    }
+
     #[test]
     #[serial]
     fn test_etc_shadow_filtered() {
@@ -255,6 +284,666 @@ mod overlay_tests {
             state
         );
     }
+
+    /// Config with a bind mount whose source does not exist on the host.
+    fn missing_volume_config(command: &[&str]) -> serde_json::Value {
+        serde_json::json!({
+            "process": {
+                "args": command,
+                "cwd": "/tmp",
+                "env": ["PATH=/usr/bin:/bin:/usr/local/bin"]
+            },
+            "mounts": [{
+                "destination": "/mnt/reaper-required-volume",
+                "type": "bind",
+                "source": "/nonexistent/reaper-missing-volume-source",
+                "options": ["rbind", "ro"]
+            }]
+        })
+    }
+
+    #[test]
+    #[serial]
+    fn test_missing_volume_policy_fail_aborts_start() {
+        if !can_use_overlay() {
+            eprintln!("Skipping test_missing_volume_policy_fail_aborts_start: requires root + mount namespace support");
+            return;
+        }
+
+        // The workload would exit 0 if it ran; the policy must stop it first
+        let state = run_workload_with_config(
+            "missing-volume-fail",
+            &missing_volume_config(&["/bin/true"]),
+            &[("REAPER_MISSING_VOLUME_POLICY", "fail")],
+        );
+        let state: serde_json::Value = serde_json::from_str(&state).unwrap();
+        assert_eq!(state["status"], "stopped", "state: {}", state);
+        assert_eq!(state["exit_code"], 1, "state: {}", state);
+        assert!(state["pid"].is_null(), "workload must not spawn: {}", state);
+    }
+
+    #[test]
+    #[serial]
+    fn test_missing_volume_policy_skip_proceeds() {
+        if !can_use_overlay() {
+            eprintln!("Skipping test_missing_volume_policy_skip_proceeds: requires root + mount namespace support");
+            return;
+        }
+
+        let state = run_workload_with_config(
+            "missing-volume-skip",
+            &missing_volume_config(&["/bin/true"]),
+            &[("REAPER_MISSING_VOLUME_POLICY", "skip")],
+        );
+        let state: serde_json::Value = serde_json::from_str(&state).unwrap();
+        assert_eq!(state["status"], "stopped", "state: {}", state);
+        assert_eq!(state["exit_code"], 0, "state: {}", state);
+    }
+
+    /// Unprivileged uid the rootless tests run as when the suite runs as root.
+    const NOBODY: u32 = 65534;
+
+    /// Unprivileged overlayfs needs Linux 5.11+.
+    fn kernel_supports_unprivileged_overlay() -> bool {
+        let release = fs::read_to_string("/proc/sys/kernel/osrelease").unwrap_or_default();
+        let mut parts = release
+            .split(|c: char| !c.is_ascii_digit())
+            .filter_map(|p| p.parse::<u32>().ok());
+        matches!((parts.next(), parts.next()), (Some(major), Some(minor)) if (major, minor) >= (5, 11))
+    }
+
+    /// Run `cmd` as an unprivileged user: as-is when we aren't root,
+    /// otherwise as `nobody`.
+    fn unprivileged(mut cmd: Command) -> Command {
+        use std::os::unix::process::CommandExt;
+        if is_root() {
+            cmd.uid(NOBODY).gid(NOBODY);
+        }
+        cmd
+    }
+
+    /// Check that an unprivileged user can create a user + mount namespace.
+    fn can_use_rootless() -> bool {
+        if !kernel_supports_unprivileged_overlay() {
+            return false;
+        }
+        let mut cmd = Command::new("unshare");
+        cmd.args(["--user", "--map-root-user", "--mount", "true"]);
+        unprivileged(cmd)
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+    }
+
+    /// Scratch dirs an unprivileged user can use: one with a copy of the
+    /// runtime binary (the build dir may not be reachable by that user), and
+    /// an `XDG_RUNTIME_DIR` under /run, which is bound into the namespace.
+    struct RootlessSandbox {
+        bin: tempfile::TempDir,
+        xdg: tempfile::TempDir,
+    }
+
+    impl RootlessSandbox {
+        fn new() -> Self {
+            use std::os::unix::fs::PermissionsExt;
+            let bin = tempfile::tempdir().unwrap();
+            fs::set_permissions(bin.path(), fs::Permissions::from_mode(0o777)).unwrap();
+            let runtime = bin.path().join("reaper-runtime");
+            fs::copy(reaper_runtime_binary(), &runtime).unwrap();
+            fs::set_permissions(&runtime, fs::Permissions::from_mode(0o755)).unwrap();
+
+            let run_dir = if is_root() {
+                PathBuf::from("/run")
+            } else {
+                std::env::var("XDG_RUNTIME_DIR")
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|_| {
+                        PathBuf::from(format!("/run/user/{}", nix::unistd::getuid()))
+                    })
+            };
+            let xdg = tempfile::tempdir_in(run_dir).unwrap();
+            fs::set_permissions(xdg.path(), fs::Permissions::from_mode(0o777)).unwrap();
+            Self { bin, xdg }
+        }
+
+        fn runtime(&self) -> PathBuf {
+            self.bin.path().join("reaper-runtime")
+        }
+    }
+
+    /// Run a workload through create/start/delete with `REAPER_ROOTLESS=1`
+    /// as an unprivileged user. Workloads sharing a sandbox share the overlay.
+    fn run_rootless_workload(
+        sandbox: &RootlessSandbox,
+        container_id: &str,
+        command: &[&str],
+    ) -> String {
+        let bundle = sandbox.bin.path().join(format!("bundle-{}", container_id));
+        fs::create_dir_all(&bundle).unwrap();
+        let config = serde_json::json!({
+            "process": {
+                "args": command,
+                "cwd": "/tmp",
+                "env": ["PATH=/usr/bin:/bin:/usr/local/bin"]
+            }
+        });
+        fs::write(bundle.join("config.json"), config.to_string()).unwrap();
+
+        let run = |args: &[&str]| {
+            let mut cmd = Command::new(sandbox.runtime());
+            cmd.args(args)
+                .env("REAPER_ROOTLESS", "1")
+                .env("XDG_RUNTIME_DIR", sandbox.xdg.path())
+                .env("REAPER_OVERLAY_ISOLATION", "node")
+                .env_remove("REAPER_RUNTIME_ROOT");
+            unprivileged(cmd).output().unwrap()
+        };
+        let bundle_arg = bundle.to_str().unwrap();
+
+        let output = run(&["create", container_id, "--bundle", bundle_arg]);
+        assert!(
+            output.status.success(),
+            "rootless create failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let output = run(&["start", container_id, "--bundle", bundle_arg]);
+        assert!(
+            output.status.success(),
+            "rootless start failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        std::thread::sleep(std::time::Duration::from_secs(3));
+
+        let state_file = sandbox
+            .xdg
+            .path()
+            .join("reaper")
+            .join(container_id)
+            .join("state.json");
+        let state_data = fs::read_to_string(&state_file).unwrap_or_default();
+        let _ = run(&["delete", container_id]);
+        state_data
+    }
+
+    #[test]
+    #[serial]
+    fn test_rootless_overlay_isolation() {
+        if !can_use_rootless() {
+            eprintln!("Skipping test_rootless_overlay_isolation: requires Linux 5.11+ and unprivileged user namespaces");
+            return;
+        }
+
+        let sandbox = RootlessSandbox::new();
+        let marker = "/tmp/reaper-rootless-overlay-test";
+        let _ = fs::remove_file(marker);
+
+        // The workload is root inside its user namespace and writes to the overlay
+        let state = run_rootless_workload(
+            &sandbox,
+            "rootless-writer",
+            &[
+                "/bin/sh",
+                "-c",
+                &format!("test \"$(id -u)\" = 0 && echo rootless > {}", marker),
+            ],
+        );
+        let state: serde_json::Value = serde_json::from_str(&state).unwrap();
+        assert_eq!(state["exit_code"], 0, "state: {}", state);
+        assert!(
+            !std::path::Path::new(marker).exists(),
+            "file written inside rootless overlay leaked to host filesystem"
+        );
+
+        // A second workload joins the same overlay and sees the write
+        let state = run_rootless_workload(
+            &sandbox,
+            "rootless-reader",
+            &["/bin/sh", "-c", &format!("grep -q rootless {}", marker)],
+        );
+        let state: serde_json::Value = serde_json::from_str(&state).unwrap();
+        assert_eq!(state["exit_code"], 0, "state: {}", state);
+    }
+
+    /// `mount -t tmpfs` at `target`.
+    fn mount_tmpfs(target: &std::path::Path) {
+        let status = Command::new("mount")
+            .args(["-t", "tmpfs", "tmpfs"])
+            .arg(target)
+            .status()
+            .unwrap();
+        assert!(status.success(), "mounting tmpfs on {}", target.display());
+    }
+
+    /// With a read-only root (bind fallback), only `REAPER_WRITABLE_PATHS`
+    /// (and the built-in /tmp and /var) accept writes.
+    #[test]
+    #[serial]
+    fn test_writable_paths_in_bind_fallback() {
+        if !can_use_overlay() {
+            eprintln!("Skipping test_writable_paths_in_bind_fallback: requires root + mount namespace support");
+            return;
+        }
+
+        // Under /run so the paths stay visible from the fallback root
+        // (which gets a private /tmp)
+        let root = PathBuf::from(format!("/run/reaper-writable-paths-{}", std::process::id()));
+        let base = root.join("overlay");
+        let state_dir = root.join("state");
+        for dir in [base.join("upper"), base.join("work"), state_dir.clone()] {
+            fs::create_dir_all(&dir).unwrap();
+        }
+        // A work dir on another filesystem than the upper dir makes the
+        // overlay mount fail, forcing the bind fallback. The state root gets
+        // its own mount so it stays writable on the read-only root.
+        mount_tmpfs(&base.join("work"));
+        mount_tmpfs(&state_dir);
+
+        let bundle = root.join("bundle");
+        fs::create_dir_all(&bundle).unwrap();
+        let config = serde_json::json!({
+            "process": {
+                "args": ["/bin/sh", "-c",
+                    "touch /mnt/reaper-writable && ! touch /etc/reaper-writable 2>/dev/null \
+                     && ! touch /usr/reaper-writable 2>/dev/null"],
+                "cwd": "/",
+                "env": ["PATH=/usr/bin:/bin"]
+            }
+        });
+        fs::write(bundle.join("config.json"), config.to_string()).unwrap();
+
+        let ns_path = root.join("ns");
+        let runtime = |args: &[&str]| {
+            Command::new(reaper_runtime_binary())
+                .args(args)
+                .env("REAPER_RUNTIME_ROOT", &state_dir)
+                .env("REAPER_OVERLAY_ISOLATION", "node")
+                .env("REAPER_OVERLAY_BASE", &base)
+                .env("REAPER_OVERLAY_NS", &ns_path)
+                .env("REAPER_OVERLAY_LOCK", root.join("overlay.lock"))
+                .env("REAPER_OVERLAY_MERGED_BASE", root.join("merged"))
+                .env("REAPER_OVERLAY_FALLBACK", "bind")
+                .env("REAPER_WRITABLE_PATHS", "/mnt")
+                .output()
+                .unwrap()
+        };
+        let bundle_arg = bundle.to_str().unwrap();
+        assert!(
+            runtime(&["create", "writable-paths", "--bundle", bundle_arg])
+                .status
+                .success()
+        );
+        assert!(
+            runtime(&["start", "writable-paths", "--bundle", bundle_arg])
+                .status
+                .success()
+        );
+
+        let started = std::time::Instant::now();
+        let state = loop {
+            let raw =
+                fs::read_to_string(state_dir.join("writable-paths/state.json")).unwrap_or_default();
+            let state: serde_json::Value = serde_json::from_str(&raw).unwrap_or_default();
+            if state["status"] == "stopped" || started.elapsed().as_secs() >= 10 {
+                break state;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        };
+        runtime(&["delete", "writable-paths"]);
+
+        // Tear down the namespace anchor and our mounts
+        if let Ok(info) = fs::read_to_string(root.join("ns.pid")) {
+            if let Some(pid) = info.split_whitespace().next().and_then(|p| p.parse().ok()) {
+                let _ = nix::sys::signal::kill(
+                    nix::unistd::Pid::from_raw(pid),
+                    nix::sys::signal::Signal::SIGKILL,
+                );
+            }
+        }
+        for target in [ns_path, base.join("work"), state_dir] {
+            let _ = nix::mount::umount2(&target, nix::mount::MntFlags::MNT_DETACH);
+        }
+        let _ = fs::remove_dir_all(&root);
+
+        assert_eq!(state["status"], "stopped", "state: {}", state);
+        assert_eq!(state["exit_code"], 0, "state: {}", state);
+        assert!(
+            !std::path::Path::new("/mnt/reaper-writable").exists(),
+            "write to a writable path leaked to the host"
+        );
+    }
+
+    /// With `REAPER_RUN_TMPFS`, workloads get a private /run: host entries
+    /// are hidden and writes stay in the tmpfs, while the state root is
+    /// still shared with the host.
+    #[test]
+    #[serial]
+    fn test_run_tmpfs_hides_host_run() {
+        if !can_use_overlay() {
+            eprintln!(
+                "Skipping test_run_tmpfs_hides_host_run: requires root + mount namespace support"
+            );
+            return;
+        }
+
+        let pid = std::process::id();
+        let root = PathBuf::from(format!("/run/reaper-run-tmpfs-{}", pid));
+        let base = root.join("overlay");
+        let state_dir = root.join("state");
+        for dir in [base.join("upper"), base.join("work"), state_dir.clone()] {
+            fs::create_dir_all(&dir).unwrap();
+        }
+        // Force the bind fallback (see test_writable_paths_in_bind_fallback)
+        mount_tmpfs(&base.join("work"));
+        mount_tmpfs(&state_dir);
+        let host_marker = PathBuf::from(format!("/run/reaper-run-tmpfs-marker-{}", pid));
+        fs::write(&host_marker, "host\n").unwrap();
+        let scratch = format!("/run/reaper-run-tmpfs-scratch-{}", pid);
+
+        let bundle = root.join("bundle");
+        fs::create_dir_all(&bundle).unwrap();
+        let script = format!(
+            "test -d {} && ! test -e {} && touch {}",
+            state_dir.display(),
+            host_marker.display(),
+            scratch
+        );
+        let config = serde_json::json!({
+            "process": {
+                "args": ["/bin/sh", "-c", script],
+                "cwd": "/",
+                "env": ["PATH=/usr/bin:/bin"]
+            }
+        });
+        fs::write(bundle.join("config.json"), config.to_string()).unwrap();
+
+        let ns_path = root.join("ns");
+        let runtime = |args: &[&str]| {
+            Command::new(reaper_runtime_binary())
+                .args(args)
+                .env("REAPER_RUNTIME_ROOT", &state_dir)
+                .env("REAPER_OVERLAY_ISOLATION", "node")
+                .env("REAPER_OVERLAY_BASE", &base)
+                .env("REAPER_OVERLAY_NS", &ns_path)
+                .env("REAPER_OVERLAY_LOCK", root.join("overlay.lock"))
+                .env("REAPER_OVERLAY_MERGED_BASE", root.join("merged"))
+                .env("REAPER_OVERLAY_FALLBACK", "bind")
+                .env("REAPER_RUN_TMPFS", "1")
+                .env("REAPER_RUN_TMPFS_SIZE", "16m")
+                .output()
+                .unwrap()
+        };
+        let bundle_arg = bundle.to_str().unwrap();
+        assert!(runtime(&["create", "run-tmpfs", "--bundle", bundle_arg])
+            .status
+            .success());
+        assert!(runtime(&["start", "run-tmpfs", "--bundle", bundle_arg])
+            .status
+            .success());
+
+        let started = std::time::Instant::now();
+        let state = loop {
+            let raw =
+                fs::read_to_string(state_dir.join("run-tmpfs/state.json")).unwrap_or_default();
+            let state: serde_json::Value = serde_json::from_str(&raw).unwrap_or_default();
+            if state["status"] == "stopped" || started.elapsed().as_secs() >= 10 {
+                break state;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        };
+        runtime(&["delete", "run-tmpfs"]);
+
+        if let Ok(info) = fs::read_to_string(root.join("ns.pid")) {
+            if let Some(pid) = info.split_whitespace().next().and_then(|p| p.parse().ok()) {
+                let _ = nix::sys::signal::kill(
+                    nix::unistd::Pid::from_raw(pid),
+                    nix::sys::signal::Signal::SIGKILL,
+                );
+            }
+        }
+        for target in [ns_path, base.join("work"), state_dir] {
+            let _ = nix::mount::umount2(&target, nix::mount::MntFlags::MNT_DETACH);
+        }
+        let _ = fs::remove_dir_all(&root);
+        let _ = fs::remove_file(&host_marker);
+        let leaked = std::path::Path::new(&scratch).exists();
+        let _ = fs::remove_file(&scratch);
+
+        assert_eq!(state["status"], "stopped", "state: {}", state);
+        assert_eq!(state["exit_code"], 0, "state: {}", state);
+        assert!(!leaked, "write to the private /run leaked to the host");
+    }
+
+    /// Nested volume mounts apply parent-first whatever their config order,
+    /// and a second container with the same mounts in the shared namespace
+    /// doesn't stack duplicates on them.
+    #[test]
+    #[serial]
+    fn test_volume_mounts_nested_order_and_reapply() {
+        if !can_use_overlay() {
+            eprintln!("Skipping test_volume_mounts_nested_order_and_reapply: requires root + mount namespace support");
+            return;
+        }
+
+        let root = PathBuf::from(format!("/run/reaper-volume-order-{}", std::process::id()));
+        let base = root.join("overlay");
+        let state_dir = root.join("state");
+        let (parent_src, child_src) = (root.join("src-parent"), root.join("src-child"));
+        for dir in [
+            base.join("upper"),
+            base.join("work"),
+            state_dir.clone(),
+            parent_src.clone(),
+            child_src.clone(),
+        ] {
+            fs::create_dir_all(&dir).unwrap();
+        }
+        // Force the bind fallback (see test_writable_paths_in_bind_fallback)
+        mount_tmpfs(&base.join("work"));
+        mount_tmpfs(&state_dir);
+        // The fallback root is read-only, so the nested mountpoint must exist
+        fs::create_dir(parent_src.join("sub")).unwrap();
+        fs::write(parent_src.join("parent-marker"), "").unwrap();
+        fs::write(child_src.join("child-marker"), "").unwrap();
+
+        let ns_path = root.join("ns");
+        let runtime = |args: &[&str]| {
+            Command::new(reaper_runtime_binary())
+                .args(args)
+                .env("REAPER_RUNTIME_ROOT", &state_dir)
+                .env("REAPER_OVERLAY_ISOLATION", "node")
+                .env("REAPER_OVERLAY_BASE", &base)
+                .env("REAPER_OVERLAY_NS", &ns_path)
+                .env("REAPER_OVERLAY_LOCK", root.join("overlay.lock"))
+                .env("REAPER_OVERLAY_MERGED_BASE", root.join("merged"))
+                .env("REAPER_OVERLAY_FALLBACK", "bind")
+                .output()
+                .unwrap()
+        };
+        let run = |id: &str, script: &str| {
+            let bundle = root.join(format!("bundle-{}", id));
+            fs::create_dir_all(&bundle).unwrap();
+            // The nested mount comes first in the config
+            let config = serde_json::json!({
+                "process": {
+                    "args": ["/bin/sh", "-c", script],
+                    "cwd": "/",
+                    "env": ["PATH=/usr/bin:/bin"]
+                },
+                "mounts": [
+                    {
+                        "destination": "/tmp/vol/sub",
+                        "type": "bind",
+                        "source": child_src,
+                        "options": ["rbind", "rw"]
+                    },
+                    {
+                        "destination": "/tmp/vol",
+                        "type": "bind",
+                        "source": parent_src,
+                        "options": ["rbind", "rw"]
+                    }
+                ]
+            });
+            fs::write(bundle.join("config.json"), config.to_string()).unwrap();
+            let bundle_arg = bundle.to_str().unwrap();
+            assert!(runtime(&["create", id, "--bundle", bundle_arg])
+                .status
+                .success());
+            assert!(runtime(&["start", id, "--bundle", bundle_arg])
+                .status
+                .success());
+            let started = std::time::Instant::now();
+            let state = loop {
+                let raw =
+                    fs::read_to_string(state_dir.join(id).join("state.json")).unwrap_or_default();
+                let state: serde_json::Value = serde_json::from_str(&raw).unwrap_or_default();
+                if state["status"] == "stopped" || started.elapsed().as_secs() >= 10 {
+                    break state;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(100));
+            };
+            runtime(&["delete", id]);
+            state
+        };
+
+        let first = run(
+            "volume-order-1",
+            "test -f /tmp/vol/parent-marker && test -f /tmp/vol/sub/child-marker",
+        );
+        let second = run(
+            "volume-order-2",
+            "test -f /tmp/vol/sub/child-marker \
+             && test $(grep -c ' /tmp/vol ' /proc/self/mountinfo) -eq 1 \
+             && test $(grep -c ' /tmp/vol/sub ' /proc/self/mountinfo) -eq 1",
+        );
+
+        if let Ok(info) = fs::read_to_string(root.join("ns.pid")) {
+            if let Some(pid) = info.split_whitespace().next().and_then(|p| p.parse().ok()) {
+                let _ = nix::sys::signal::kill(
+                    nix::unistd::Pid::from_raw(pid),
+                    nix::sys::signal::Signal::SIGKILL,
+                );
+            }
+        }
+        for target in [ns_path, base.join("work"), state_dir] {
+            let _ = nix::mount::umount2(&target, nix::mount::MntFlags::MNT_DETACH);
+        }
+        let _ = fs::remove_dir_all(&root);
+
+        assert_eq!(first["exit_code"], 0, "nested mount order: {}", first);
+        assert_eq!(second["exit_code"], 0, "re-applied mounts: {}", second);
+        assert_eq!(first["isolated"], true, "ran in the overlay: {}", first);
+        let phases: Vec<&str> = first["timings"]
+            .as_array()
+            .map(|t| t.iter().filter_map(|t| t["phase"].as_str()).collect())
+            .unwrap_or_default();
+        assert_eq!(
+            phases,
+            ["create", "prepare", "overlay", "volume_mounts", "spawn"]
+        );
+    }
+
+    /// A read-only image rootfs is layered as the top lower layer with a
+    /// private writable upper: image files show through, writes land in the
+    /// upper and never in the rootfs.
+    #[test]
+    #[serial]
+    fn test_readonly_image_rootfs_writes_land_in_upper() {
+        if !can_use_overlay() {
+            eprintln!("Skipping test_readonly_image_rootfs_writes_land_in_upper: requires root + mount namespace support");
+            return;
+        }
+
+        let root = PathBuf::from(format!("/run/reaper-ro-rootfs-{}", std::process::id()));
+        let base = root.join("overlay");
+        let state_dir = root.join("state");
+        let bundle = root.join("bundle");
+        let rootfs = bundle.join("rootfs");
+        for dir in [&base, &state_dir, &rootfs] {
+            fs::create_dir_all(dir).unwrap();
+        }
+
+        // containerd-style read-only rootfs mount. Its own filesystem, like a
+        // snapshot mount: overlayfs refuses a lower layer nested in another.
+        mount_tmpfs(&rootfs);
+        fs::write(rootfs.join("image-marker"), "from-image\n").unwrap();
+        let status = Command::new("mount")
+            .args(["-o", "remount,ro"])
+            .arg(&rootfs)
+            .status()
+            .unwrap();
+        assert!(
+            status.success(),
+            "remounting {} read-only",
+            rootfs.display()
+        );
+
+        let config = serde_json::json!({
+            "root": { "path": "rootfs" },
+            "process": {
+                "args": ["/bin/sh", "-c",
+                    "grep -q from-image /image-marker && echo changed > /image-marker \
+                     && echo new > /new-file"],
+                "cwd": "/",
+                "env": ["PATH=/usr/bin:/bin"]
+            }
+        });
+        fs::write(bundle.join("config.json"), config.to_string()).unwrap();
+
+        let runtime = |args: &[&str]| {
+            Command::new(reaper_runtime_binary())
+                .args(args)
+                .env("REAPER_RUNTIME_ROOT", &state_dir)
+                .env("REAPER_OVERLAY_ISOLATION", "node")
+                .env("REAPER_OVERLAY_BASE", &base)
+                .env("REAPER_OVERLAY_NS", root.join("ns"))
+                .env("REAPER_OVERLAY_LOCK", root.join("overlay.lock"))
+                .env("REAPER_OVERLAY_MERGED_BASE", root.join("merged"))
+                .env_remove("REAPER_IMAGE_ROOTFS")
+                .output()
+                .unwrap()
+        };
+        let bundle_arg = bundle.to_str().unwrap();
+        let create = runtime(&["create", "ro-rootfs", "--bundle", bundle_arg]);
+        assert!(
+            create.status.success(),
+            "create failed: {}",
+            String::from_utf8_lossy(&create.stderr)
+        );
+        assert!(runtime(&["start", "ro-rootfs", "--bundle", bundle_arg])
+            .status
+            .success());
+
+        let started = std::time::Instant::now();
+        let state = loop {
+            let raw =
+                fs::read_to_string(state_dir.join("ro-rootfs/state.json")).unwrap_or_default();
+            let state: serde_json::Value = serde_json::from_str(&raw).unwrap_or_default();
+            if state["status"] == "stopped" || started.elapsed().as_secs() >= 10 {
+                break state;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        };
+        // The private overlay (and its upper) is removed on delete
+        let upper = base.join("rootfs-ro-rootfs/upper");
+        let upper_new = fs::read_to_string(upper.join("new-file")).ok();
+        let upper_marker = fs::read_to_string(upper.join("image-marker")).ok();
+        runtime(&["delete", "ro-rootfs"]);
+
+        let image_marker = fs::read_to_string(rootfs.join("image-marker")).unwrap();
+        let leaked = rootfs.join("new-file").exists();
+        let _ = nix::mount::umount2(&rootfs, nix::mount::MntFlags::MNT_DETACH);
+        let _ = fs::remove_dir_all(&root);
+
+        assert!(state["image_rootfs"].is_string(), "state: {}", state);
+        assert_eq!(state["status"], "stopped", "state: {}", state);
+        assert_eq!(state["exit_code"], 0, "state: {}", state);
+        assert_eq!(upper_new.as_deref(), Some("new\n"));
+        assert_eq!(upper_marker.as_deref(), Some("changed\n"));
+        assert_eq!(image_marker, "from-image\n");
+        assert!(!leaked, "write reached the read-only rootfs");
+    }
 }
 
 // On non-Linux, include a single test that confirms the module compiles
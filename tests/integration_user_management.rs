@@ -858,3 +858,101 @@ fn test_umask_affects_file_permissions() {
         .output()
         .ok();
 }
+
+/// Run `program -u` as uid/gid 65534 and return what it prints, i.e. the
+/// effective uid it ended up with.
+fn effective_uid_of(id: &str, program: &std::path::Path, no_new_privileges: bool) -> String {
+    let bundle_dir = TempDir::new().expect("Failed to create temp bundle dir");
+    let bundle_path = bundle_dir.path();
+    let stdout_fifo = create_fifo(bundle_path, "stdout.fifo");
+
+    let config = serde_json::json!({
+        "process": {
+            "args": [program, "-u"],
+            "cwd": "/",
+            "env": ["PATH=/usr/bin:/bin"],
+            "user": {"uid": 65534, "gid": 65534},
+            "noNewPrivileges": no_new_privileges
+        }
+    });
+    fs::write(
+        bundle_path.join("config.json"),
+        serde_json::to_string_pretty(&config).unwrap(),
+    )
+    .expect("Failed to write config.json");
+
+    let state_dir = TempDir::new().expect("Failed to create state dir");
+    let state_root = state_dir.path().to_string_lossy().to_string();
+    let reaper_bin = env!("CARGO_BIN_EXE_reaper-runtime");
+
+    let create_output = Command::new(reaper_bin)
+        .env("REAPER_RUNTIME_ROOT", &state_root)
+        .env("REAPER_NO_OVERLAY", "1")
+        .args(["create", id, "--bundle"])
+        .arg(bundle_path)
+        .arg("--stdout")
+        .arg(&stdout_fifo)
+        .output()
+        .expect("Failed to run create command");
+    assert!(
+        create_output.status.success(),
+        "create failed: {}",
+        String::from_utf8_lossy(&create_output.stderr)
+    );
+
+    let reaper_bin_clone = reaper_bin.to_string();
+    let state_root_clone = state_root.clone();
+    let bundle_path_clone = bundle_path.to_path_buf();
+    let id_clone = id.to_string();
+    std::thread::spawn(move || {
+        Command::new(&reaper_bin_clone)
+            .env("REAPER_RUNTIME_ROOT", &state_root_clone)
+            .env("REAPER_NO_OVERLAY", "1")
+            .args(["start", &id_clone, "--bundle"])
+            .arg(&bundle_path_clone)
+            .output()
+            .expect("Failed to run start command");
+    });
+
+    let output = read_fifo_with_timeout(&stdout_fifo, Duration::from_secs(2));
+
+    std::thread::sleep(Duration::from_millis(200));
+    Command::new(reaper_bin)
+        .env("REAPER_RUNTIME_ROOT", &state_root)
+        .env("REAPER_NO_OVERLAY", "1")
+        .args(["delete", id])
+        .output()
+        .ok();
+
+    output.trim().to_string()
+}
+
+/// Test that process.noNewPrivileges keeps a setuid-root binary from raising
+/// the workload's effective uid
+#[test]
+fn test_no_new_privileges_blocks_setuid() {
+    use std::os::unix::fs::PermissionsExt;
+
+    if unsafe { nix::libc::getuid() } != 0 {
+        eprintln!("Skipping test_no_new_privileges_blocks_setuid: not running as root");
+        return;
+    }
+
+    // A setuid-root copy of id(1), reachable by uid 65534
+    let helper_dir = TempDir::new().expect("Failed to create helper dir");
+    fs::set_permissions(helper_dir.path(), fs::Permissions::from_mode(0o755)).unwrap();
+    let helper = helper_dir.path().join("suid-id");
+    fs::copy("/usr/bin/id", &helper).expect("Failed to copy id");
+    fs::set_permissions(&helper, fs::Permissions::from_mode(0o4755)).unwrap();
+
+    let escalated = effective_uid_of("test-nnp-off", &helper, false);
+    if escalated != "0" {
+        eprintln!(
+            "Skipping test_no_new_privileges_blocks_setuid: setuid not honored here (got {:?})",
+            escalated
+        );
+        return;
+    }
+
+    assert_eq!(effective_uid_of("test-nnp-on", &helper, true), "65534");
+}